@@ -2,11 +2,14 @@ use std::path::PathBuf;
 
 use anyhow::{Context as AnyhowContext, Result};
 
-pub fn init(directory: Option<PathBuf>) -> Result<gix::discover::repository::Path> {
+pub fn init(directory: Option<PathBuf>, object_hash: gix::hash::Kind) -> Result<gix::discover::repository::Path> {
     gix::create::into(
         directory.unwrap_or_default(),
         gix::create::Kind::WithWorktree,
-        gix::create::Options::default(),
+        gix::create::Options {
+            object_hash,
+            ..Default::default()
+        },
     )
     .with_context(|| "Repository initialization failed")
 }
@@ -50,10 +50,12 @@ impl Pattern for Ignore {
     }
 }
 
-/// A value of an attribute pattern, which is either a macro definition or
+/// A value of an attribute pattern, which is either a macro definition or a set of attribute assignments.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 pub enum Value {
+    /// The attributes a macro expands to, with the macro name being the pattern.
     MacroAttributes(Vec<Assignment>),
+    /// The attributes assigned to paths matching the pattern.
     Assignments(Vec<Assignment>),
 }
 
@@ -129,6 +131,28 @@ where
             .rev()
             .find_map(|pl| pl.pattern_matching_relative_path(relative_path, basename_pos, is_dir, case))
     }
+
+    /// Add the given file at `source` if it exists, otherwise do nothing. If a `root` is provided, it's not considered a global file anymore.
+    /// Returns true if the file was added, or false if it didn't exist.
+    pub fn add_patterns_file(
+        &mut self,
+        source: impl Into<PathBuf>,
+        follow_symlinks: bool,
+        root: Option<&Path>,
+        buf: &mut Vec<u8>,
+    ) -> std::io::Result<bool> {
+        let previous_len = self.patterns.len();
+        self.patterns
+            .extend(PatternList::<T>::from_file(source.into(), root, follow_symlinks, buf)?);
+        Ok(self.patterns.len() != previous_len)
+    }
+
+    /// Add patterns as parsed from `bytes`, providing their `source` path and possibly their `root` path, the path they
+    /// are relative to. This also means that `source` is contained within `root` if `root` is provided.
+    pub fn add_patterns_buffer(&mut self, bytes: &[u8], source: impl Into<PathBuf>, root: Option<&Path>) {
+        self.patterns
+            .push(PatternList::<T>::from_bytes(bytes, source.into(), root));
+    }
 }
 
 impl MatchGroup<Ignore> {
@@ -165,32 +189,6 @@ impl MatchGroup<Ignore> {
             patterns: vec![PatternList::<Ignore>::from_overrides(patterns)],
         }
     }
-
-    /// Add the given file at `source` if it exists, otherwise do nothing. If a `root` is provided, it's not considered a global file anymore.
-    /// Returns true if the file was added, or false if it didn't exist.
-    pub fn add_patterns_file(
-        &mut self,
-        source: impl Into<PathBuf>,
-        follow_symlinks: bool,
-        root: Option<&Path>,
-        buf: &mut Vec<u8>,
-    ) -> std::io::Result<bool> {
-        let previous_len = self.patterns.len();
-        self.patterns.extend(PatternList::<Ignore>::from_file(
-            source.into(),
-            root,
-            follow_symlinks,
-            buf,
-        )?);
-        Ok(self.patterns.len() != previous_len)
-    }
-
-    /// Add patterns as parsed from `bytes`, providing their `source` path and possibly their `root` path, the path they
-    /// are relative to. This also means that `source` is contained within `root` if `root` is provided.
-    pub fn add_patterns_buffer(&mut self, bytes: &[u8], source: impl Into<PathBuf>, root: Option<&Path>) {
-        self.patterns
-            .push(PatternList::<Ignore>::from_bytes(bytes, source.into(), root));
-    }
 }
 
 fn read_in_full_ignore_missing(path: &Path, follow_symlinks: bool, buf: &mut Vec<u8>) -> std::io::Result<bool> {
@@ -288,6 +286,35 @@ where
             )
     }
 
+    /// Like [`pattern_matching_relative_path()`][Self::pattern_matching_relative_path()], but returns all matching patterns,
+    /// from the last to the first as later patterns take precedence over earlier ones.
+    pub fn patterns_matching_relative_path<'a>(
+        &'a self,
+        relative_path: &'a BStr,
+        basename_pos: Option<usize>,
+        is_dir: Option<bool>,
+        case: gix_glob::pattern::Case,
+    ) -> impl Iterator<Item = Match<'a, T::Value>> + 'a {
+        self.strip_base_handle_recompute_basename_pos(relative_path, basename_pos)
+            .into_iter()
+            .flat_map(move |(relative_path, basename_start_pos)| {
+                self.patterns
+                    .iter()
+                    .rev()
+                    .filter(|pm| T::may_use_glob_pattern(&pm.pattern))
+                    .filter(move |pm| {
+                        pm.pattern
+                            .matches_repo_relative_path(relative_path, basename_start_pos, is_dir, case)
+                    })
+                    .map(move |pm| Match {
+                        pattern: &pm.pattern,
+                        value: &pm.value,
+                        source: self.source.as_deref(),
+                        sequence_number: pm.sequence_number,
+                    })
+            })
+    }
+
     /// Like [`pattern_matching_relative_path()`][Self::pattern_matching_relative_path()], but returns an index to the pattern
     /// that matched `relative_path`, instead of the match itself.
     pub fn pattern_idx_matching_relative_path(
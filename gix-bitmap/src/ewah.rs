@@ -47,10 +47,89 @@ pub fn decode(data: &[u8]) -> Result<(Vec, &[u8]), decode::Error> {
     ))
 }
 
+mod init {
+    use std::convert::TryInto;
+
+    use super::{Vec, RLW_LARGEST_LITERAL_COUNT, RLW_LARGEST_RUNNING_COUNT, RLW_RUNNING_BITS};
+
+    impl Vec {
+        /// Create a new instance with the bits at the given `indices` set, which must be sorted in ascending order.
+        ///
+        /// The amount of bits held is one past the highest set bit, similar to what `git` produces when setting
+        /// bits one by one.
+        pub fn from_sorted_indices(indices: impl IntoIterator<Item = usize>) -> Self {
+            let mut words = std::vec::Vec::<u64>::new();
+            let mut num_bits = 0;
+            for index in indices {
+                let word = index / 64;
+                if word >= words.len() {
+                    words.resize(word + 1, 0);
+                }
+                words[word] |= 1 << (index % 64);
+                num_bits = index + 1;
+            }
+
+            let mut bits = std::vec::Vec::with_capacity(words.len() + 1);
+            let mut words = words.as_slice();
+            let rlw = loop {
+                let run_bit = words.first().map_or(false, |w| *w == u64::MAX);
+                let clean_word = if run_bit { u64::MAX } else { 0 };
+                let running_len = words
+                    .iter()
+                    .take(RLW_LARGEST_RUNNING_COUNT as usize)
+                    .take_while(|w| **w == clean_word)
+                    .count();
+                words = &words[running_len..];
+                let literal_words = words
+                    .iter()
+                    .take(RLW_LARGEST_LITERAL_COUNT as usize)
+                    .take_while(|w| **w != 0 && **w != u64::MAX)
+                    .count();
+
+                let rlw = bits.len();
+                bits.push(
+                    u64::from(run_bit) | (running_len as u64) << 1 | (literal_words as u64) << (1 + RLW_RUNNING_BITS),
+                );
+                bits.extend_from_slice(&words[..literal_words]);
+                words = &words[literal_words..];
+                if words.is_empty() {
+                    break rlw;
+                }
+            };
+
+            Vec {
+                num_bits: num_bits.try_into().expect("less than 4 billion bits"),
+                bits,
+                rlw: rlw as u64,
+            }
+        }
+    }
+}
+
+mod write {
+    use std::convert::TryFrom;
+
+    use super::Vec;
+
+    impl Vec {
+        /// Serialize this instance to `out` in the format understood by [`decode()`][super::decode()].
+        pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+            let len = u32::try_from(self.bits.len()).expect("less than 4 billion words");
+            let rlw = u32::try_from(self.rlw).expect("rlw offset is within bounds of words");
+            out.write_all(&self.num_bits.to_be_bytes())?;
+            out.write_all(&len.to_be_bytes())?;
+            for word in &self.bits {
+                out.write_all(&word.to_be_bytes())?;
+            }
+            out.write_all(&rlw.to_be_bytes())
+        }
+    }
+}
+
 mod access {
     use std::convert::{TryFrom, TryInto};
 
-    use super::Vec;
+    use super::{Vec, RLW_LARGEST_RUNNING_COUNT, RLW_RUNNING_BITS};
 
     impl Vec {
         /// Call `f(index)` for each bit that is true, given the index of the bit that identifies it uniquely within the bit array.
@@ -111,11 +190,13 @@ mod access {
     fn rlw_runbit_is_set(w: &u64) -> bool {
         w & 1 == 1
     }
-
-    const RLW_RUNNING_BITS: u64 = 4 * 8;
-    const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
 }
 
+const RLW_RUNNING_BITS: u64 = 4 * 8;
+const RLW_LITERAL_BITS: u64 = 64 - 1 - RLW_RUNNING_BITS;
+const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+const RLW_LARGEST_LITERAL_COUNT: u64 = (1 << RLW_LITERAL_BITS) - 1;
+
 /// A growable collection of u64 that are seen as stream of individual bits.
 #[allow(dead_code)]
 #[derive(Clone)]
@@ -19,6 +19,8 @@ impl File<'static> {
     ///
     /// # Limitations
     ///
+    /// - `hasconfig:remote.*.url` conditions are matched against the remote URLs of this instance including all of its includes,
+    ///   but without the ones from environment variables that may be appended later.
     /// - Note that this method is _not idempotent_ and calling it multiple times will resolve includes multiple
     ///   times. It's recommended use is as part of a multi-step bootstrapping which needs fine-grained control,
     ///   and unless that's given one should prefer one of the other ways of initialization that resolve includes
@@ -38,7 +40,55 @@ impl File<'static> {
 }
 
 pub(crate) fn resolve(config: &mut File<'static>, buf: &mut Vec<u8>, options: init::Options<'_>) -> Result<(), Error> {
-    resolve_includes_recursive(config, 0, buf, options)
+    let has_includes = config.sections_by_name("include").is_some() || config.sections_by_name("includeIf").is_some();
+    let mut remote_urls = if has_includes {
+        RemoteUrls::Unresolved(Box::new(config.clone()))
+    } else {
+        RemoteUrls::Known(Vec::new())
+    };
+    resolve_includes_recursive(config, 0, buf, options, &mut remote_urls)
+}
+
+/// The remote URLs that `hasconfig:remote.*.url` conditions are matched against.
+enum RemoteUrls {
+    /// The configuration before resolving its includes, to obtain the remote URLs from once the first condition needs them.
+    Unresolved(Box<File<'static>>),
+    /// All remote URLs in the configuration, including the ones from included files.
+    Known(Vec<BString>),
+    /// The remote URLs are currently being collected, which is done by following all `hasconfig:remote.*.url` includes.
+    Collecting,
+}
+
+impl RemoteUrls {
+    fn any_matches(&mut self, pattern: &BStr, buf: &mut Vec<u8>, options: init::Options<'_>) -> Result<bool, Error> {
+        if let RemoteUrls::Unresolved(config) = self {
+            let mut config = std::mem::take(config);
+            resolve_includes_recursive(&mut config, 0, buf, options, &mut RemoteUrls::Collecting)?;
+            *self = RemoteUrls::Known(
+                config
+                    .sections_by_name("remote")
+                    .into_iter()
+                    .flatten()
+                    .filter(|section| section.header().subsection_name().is_some())
+                    .flat_map(|section| section.values("url"))
+                    .map(Cow::into_owned)
+                    .collect(),
+            );
+        }
+        Ok(match self {
+            RemoteUrls::Known(urls) => urls
+                .iter()
+                .any(|url| gix_glob::wildmatch(pattern, url.as_bstr(), gix_glob::wildmatch::Mode::empty())),
+            RemoteUrls::Collecting => true,
+            RemoteUrls::Unresolved(_) => unreachable!("resolved above"),
+        })
+    }
+}
+
+fn has_remote_urls(config: &File<'_>) -> bool {
+    config.sections_by_name("remote").map_or(false, |mut sections| {
+        sections.any(|section| section.header().subsection_name().is_some() && section.value("url").is_some())
+    })
 }
 
 fn resolve_includes_recursive(
@@ -46,6 +96,7 @@ fn resolve_includes_recursive(
     depth: u8,
     buf: &mut Vec<u8>,
     options: init::Options<'_>,
+    remote_urls: &mut RemoteUrls,
 ) -> Result<(), Error> {
     if depth == options.includes.max_depth {
         return if options.includes.err_on_max_depth_exceeded {
@@ -66,28 +117,45 @@ fn resolve_includes_recursive(
         let header = &section.header;
         let header_name = header.name.as_ref();
         if header_name == "include" && header.subsection_name.is_none() {
-            detach_include_paths(&mut section_ids_and_include_paths, section, id)
+            detach_include_paths(&mut section_ids_and_include_paths, section, id, false)
         } else if header_name == "includeIf" {
             if let Some(condition) = &header.subsection_name {
                 let target_config_path = section.meta.path.as_deref();
-                if include_condition_match(condition.as_ref(), target_config_path, options.includes)? {
-                    detach_include_paths(&mut section_ids_and_include_paths, section, id)
+                match condition.strip_prefix(b"hasconfig:remote.*.url:") {
+                    Some(pattern) => {
+                        if remote_urls.any_matches(pattern.as_bstr(), buf, options)? {
+                            detach_include_paths(&mut section_ids_and_include_paths, section, id, true)
+                        }
+                    }
+                    None => {
+                        if include_condition_match(condition.as_ref(), target_config_path, options.includes)? {
+                            detach_include_paths(&mut section_ids_and_include_paths, section, id, false)
+                        }
+                    }
                 }
             }
         }
     }
 
-    append_followed_includes_recursively(section_ids_and_include_paths, target_config, depth, options, buf)
+    append_followed_includes_recursively(
+        section_ids_and_include_paths,
+        target_config,
+        depth,
+        options,
+        buf,
+        remote_urls,
+    )
 }
 
 fn append_followed_includes_recursively(
-    section_ids_and_include_paths: Vec<(SectionId, crate::Path<'_>)>,
+    section_ids_and_include_paths: Vec<(SectionId, crate::Path<'_>, bool)>,
     target_config: &mut File<'static>,
     depth: u8,
     options: init::Options<'_>,
     buf: &mut Vec<u8>,
+    remote_urls: &mut RemoteUrls,
 ) -> Result<(), Error> {
-    for (section_id, config_path) in section_ids_and_include_paths {
+    for (section_id, config_path, forbid_remote_urls) in section_ids_and_include_paths {
         let meta = OwnShared::clone(&target_config.sections[&section_id].meta);
         let target_config_path = meta.path.as_deref();
         let config_path = match resolve_path(config_path, target_config_path, options.includes)? {
@@ -101,7 +169,7 @@ fn append_followed_includes_recursively(
         buf.clear();
         std::io::copy(&mut std::fs::File::open(&config_path)?, buf)?;
         let config_meta = Metadata {
-            path: Some(config_path),
+            path: Some(config_path.clone()),
             trust: meta.trust,
             level: meta.level + 1,
             source: meta.source,
@@ -117,7 +185,10 @@ fn append_followed_includes_recursively(
                 init::Error::Interpolate(err) => Error::Interpolate(err),
                 init::Error::Includes(_) => unreachable!("BUG: {:?} not possible due to no-follow options", err),
             })?;
-        resolve_includes_recursive(&mut include_config, depth + 1, buf, options)?;
+        resolve_includes_recursive(&mut include_config, depth + 1, buf, options, remote_urls)?;
+        if forbid_remote_urls && has_remote_urls(&include_config) {
+            return Err(Error::RemoteUrlInHasConfigInclude { path: config_path });
+        }
 
         target_config.append_or_insert(include_config, Some(section_id));
     }
@@ -125,16 +196,17 @@ fn append_followed_includes_recursively(
 }
 
 fn detach_include_paths(
-    include_paths: &mut Vec<(SectionId, crate::Path<'static>)>,
+    include_paths: &mut Vec<(SectionId, crate::Path<'static>, bool)>,
     section: &file::Section<'_>,
     id: SectionId,
+    forbid_remote_urls: bool,
 ) {
     include_paths.extend(
         section
             .body
             .values("path")
             .into_iter()
-            .map(|path| (id, crate::Path::from(Cow::Owned(path.into_owned())))),
+            .map(|path| (id, crate::Path::from(Cow::Owned(path.into_owned())), forbid_remote_urls)),
     )
 }
 
@@ -0,0 +1,158 @@
+use std::fs;
+
+use gix_config::{
+    file::{includes, init, Metadata},
+    File, Source,
+};
+use tempfile::tempdir;
+
+use crate::file::{cow_str, init::from_paths::escape_backslashes};
+
+fn options() -> init::Options<'static> {
+    init::Options {
+        includes: includes::Options::follow(Default::default(), Default::default()),
+        ..Default::default()
+    }
+}
+
+/// Load the global configuration at `global` along with the local one at `local` and resolve includes in both,
+/// like it's done when opening a repository.
+fn global_and_local(global: &std::path::Path, local: &std::path::Path) -> Result<File<'static>, includes::Error> {
+    let mut config = File::from_path_no_includes(global.to_owned(), Source::User).expect("valid");
+    config.append(File::from_path_no_includes(local.to_owned(), Source::Local).expect("valid"));
+    config.resolve_includes(options())?;
+    Ok(config)
+}
+
+#[test]
+fn remote_urls_are_matched_with_glob_semantics() -> crate::Result {
+    let dir = tempdir()?;
+    let org_path = dir.path().join("org");
+    fs::write(&org_path, "[credential]\n\thelper = org\n")?;
+    let other_path = dir.path().join("other");
+    fs::write(&other_path, "[credential]\n\thelper = other\n")?;
+    let global_path = dir.path().join("global");
+    fs::write(
+        &global_path,
+        format!(
+            r#"
+[credential]
+  helper = base
+[includeIf "hasconfig:remote.*.url:https://github.com/org/**"]
+  path = {}
+[includeIf "hasconfig:remote.*.url:https://example.com/*"]
+  path = {}
+[includeIf "hasconfig:remote.*.url:*://unrelated.com/**"]
+  path = {}"#,
+            escape_backslashes(&org_path),
+            escape_backslashes(&other_path),
+            escape_backslashes(&other_path),
+        ),
+    )?;
+
+    let local_path = dir.path().join("local");
+    for (url, expected) in [
+        ("https://github.com/org/repo", &["base", "org"][..]),
+        ("https://github.com/other-org/repo", &["base"][..]),
+        ("https://example.com/nested/repo", &["base", "other"][..]),
+        ("git@github.com:org/repo", &["base"][..]),
+    ] {
+        fs::write(&local_path, format!("[remote \"origin\"]\n\turl = {url}\n"))?;
+        let config = global_and_local(&global_path, &local_path)?;
+        assert_eq!(
+            config.strings("credential", None, "helper"),
+            Some(expected.iter().copied().map(cow_str).collect()),
+            "{url}: URLs configured after the condition count, and `*` also matches slashes"
+        );
+    }
+
+    fs::write(&local_path, "[remote]\n\turl = https://github.com/org/repo\n")?;
+    let config = global_and_local(&global_path, &local_path)?;
+    assert_eq!(
+        config.strings("credential", None, "helper"),
+        Some(vec![cow_str("base")]),
+        "only URLs of named remotes are considered"
+    );
+    Ok(())
+}
+
+#[test]
+fn remote_urls_from_regular_includes_are_considered() -> crate::Result {
+    let dir = tempdir()?;
+    let remotes_path = dir.path().join("remotes");
+    fs::write(
+        &remotes_path,
+        "[remote \"origin\"]\n\turl = https://github.com/org/repo\n",
+    )?;
+    let org_path = dir.path().join("org");
+    fs::write(&org_path, "[credential]\n\thelper = org\n")?;
+    let config_path = dir.path().join("config");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[includeIf "hasconfig:remote.*.url:https://github.com/org/*"]
+  path = {}
+[include]
+  path = {}"#,
+            escape_backslashes(&org_path),
+            escape_backslashes(&remotes_path),
+        ),
+    )?;
+
+    let config = File::from_paths_metadata(Some(Metadata::try_from_path(&config_path, Source::Local)?), options())?
+        .expect("non-empty");
+    assert_eq!(config.string("credential", None, "helper"), Some(cow_str("org")));
+    Ok(())
+}
+
+#[test]
+fn included_files_must_not_define_remote_urls() -> crate::Result {
+    let dir = tempdir()?;
+    let remotes_path = dir.path().join("remotes");
+    fs::write(&remotes_path, "[remote \"other\"]\n\turl = https://example.com/repo\n")?;
+    let indirect_path = dir.path().join("indirect");
+    fs::write(
+        &indirect_path,
+        format!("[include]\n\tpath = {}\n", escape_backslashes(&remotes_path)),
+    )?;
+    let config_path = dir.path().join("config");
+
+    for (pattern, include_path) in [
+        ("https://github.com/org/**", &remotes_path),
+        ("https://github.com/org/**", &indirect_path),
+        ("https://no-match.com/**", &remotes_path),
+    ] {
+        fs::write(
+            &config_path,
+            format!(
+                "[remote \"origin\"]\n\turl = https://github.com/org/repo\n[includeIf \"hasconfig:remote.*.url:{pattern}\"]\n\tpath = {}\n",
+                escape_backslashes(include_path),
+            ),
+        )?;
+        let err = File::from_paths_metadata(Some(Metadata::try_from_path(&config_path, Source::Local)?), options())
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                init::from_paths::Error::Init(init::Error::Includes(
+                    includes::Error::RemoteUrlInHasConfigInclude { .. }
+                ))
+            ),
+            "{pattern} {include_path:?}: like git, it's an error even if the condition doesn't match: {err:?}"
+        );
+    }
+
+    fs::write(
+        &remotes_path,
+        "[remote \"other\"]\n\tpushUrl = https://example.com/repo\n",
+    )?;
+    let config = File::from_paths_metadata(Some(Metadata::try_from_path(&config_path, Source::Local)?), options())?
+        .expect("non-empty");
+    assert_eq!(
+        config.string("remote", Some("other".into()), "pushUrl"),
+        None,
+        "other remote values are fine, and the condition still doesn't match"
+    );
+    Ok(())
+}
@@ -284,7 +284,6 @@ value = branch-override-by-include
                 deref: false,
             }),
             gix::lock::acquire::Fail::Immediately,
-            gix::lock::acquire::Fail::Immediately,
         )?
         .commit(repo.committer().transpose()?)?;
 
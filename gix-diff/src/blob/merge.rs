@@ -0,0 +1,290 @@
+//! Merge the lines of two blobs that were derived from a common ancestor, and mark conflicting changes.
+use std::ops::Range;
+
+use crate::blob::{intern::InternedInput, sources::byte_lines_with_terminator, Algorithm};
+
+/// Options for use in [`merge()`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The algorithm to use for diffing lines.
+    pub algorithm: Algorithm,
+    /// How to merge the blobs, typically as learned from the `merge` attribute.
+    pub driver: Driver,
+    /// How to present conflicts, similar to `merge.conflictStyle`.
+    pub conflict_style: ConflictStyle,
+    /// If set, resolve conflicts automatically in favor of one or both sides instead of writing conflict markers.
+    pub favor: Option<Favor>,
+    /// The width of conflict markers, similar to the `conflict-marker-size` attribute.
+    pub marker_size: usize,
+    /// The label to write after the conflict marker of our side, like `<<<<<<< ours`.
+    pub ours_label: Option<String>,
+    /// The label to write after the conflict marker of the base, like `||||||| base`, if the conflict style shows it.
+    pub base_label: Option<String>,
+    /// The label to write after the conflict marker of their side, like `>>>>>>> theirs`.
+    pub theirs_label: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            algorithm: Algorithm::Myers,
+            driver: Driver::default(),
+            conflict_style: ConflictStyle::default(),
+            favor: None,
+            marker_size: DEFAULT_MARKER_SIZE,
+            ours_label: None,
+            base_label: None,
+            theirs_label: None,
+        }
+    }
+}
+
+/// The built-in ways of merging blobs, as selected by the `merge` attribute.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Driver {
+    /// Merge line by line and mark conflicts, which is the default and used if the `merge` attribute is set.
+    ///
+    /// Note that blobs which look binary are merged with the [`Binary`][Driver::Binary] driver instead.
+    #[default]
+    Text,
+    /// Don't merge at all and keep our side, or the favored side, as result. It's a conflict unless a side is favored.
+    ///
+    /// This is used if the `merge` attribute is unset, for instance by the `binary` macro, or set to `binary`.
+    Binary,
+    /// Merge line by line and resolve conflicts by taking the lines of both sides, used if the `merge` attribute is `union`.
+    Union,
+}
+
+impl Driver {
+    /// Return the built-in driver for a `merge` attribute whose value is `name`, or `None` if `name` refers to a driver
+    /// that has to be configured with `merge.<name>.driver`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "text" => Driver::Text,
+            "binary" => Driver::Binary,
+            "union" => Driver::Union,
+            _ => return None,
+        })
+    }
+}
+
+/// The way conflicts are presented, similar to `merge.conflictStyle`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum ConflictStyle {
+    /// Show our and their side of the conflict, with lines they have in common at the beginning and end moved out of it.
+    #[default]
+    Merge,
+    /// Show our side, the base and their side of the conflict, each in full.
+    Diff3,
+    /// Like [`Diff3`][ConflictStyle::Diff3], but with lines that both sides have in common at the beginning and end moved out
+    /// of the conflict.
+    ZealousDiff3,
+}
+
+/// The side to favor when resolving conflicts, similar to the `--ours`, `--theirs` and `--union` options of `git merge-file`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Favor {
+    /// Take our lines of conflicting regions.
+    Ours,
+    /// Take their lines of conflicting regions.
+    Theirs,
+    /// Take our lines, followed by their lines, of conflicting regions.
+    Union,
+}
+
+/// The result of [`merge()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The merged content, with conflict markers if there are conflicts.
+    pub data: Vec<u8>,
+    /// The amount of conflicting regions, or `1` if binary blobs couldn't be merged.
+    pub conflicts: usize,
+}
+
+/// The default width of conflict markers.
+pub const DEFAULT_MARKER_SIZE: usize = 7;
+
+/// Merge `ours` and `theirs`, which were both derived from `base`, with `options`.
+///
+/// With the [`Text`][Driver::Text] driver, changes to distinct regions of `base` are combined, and changes to the same
+/// or adjacent regions are conflicts unless both sides made the same change.
+/// Conflicts are marked with `<<<<<<<`, `=======` and `>>>>>>>`, and presented according to the
+/// [conflict style][Options::conflict_style], unless a [side is favored][Options::favor].
+/// If any of the blobs looks binary, the [`Binary`][Driver::Binary] driver is used instead, just like `git` does.
+pub fn merge(base: &[u8], ours: &[u8], theirs: &[u8], options: &Options) -> Outcome {
+    let favor = match options.driver {
+        Driver::Union => Some(Favor::Union),
+        Driver::Text | Driver::Binary => options.favor,
+    };
+    let is_binary = [base, ours, theirs]
+        .iter()
+        .any(|data| crate::patch::is_binary_data(data));
+    if options.driver == Driver::Binary || is_binary {
+        return Outcome {
+            data: match favor {
+                Some(Favor::Theirs) => theirs,
+                Some(Favor::Ours | Favor::Union) | None => ours,
+            }
+            .to_vec(),
+            conflicts: usize::from(favor.is_none()),
+        };
+    }
+
+    let base_lines: Vec<&[u8]> = byte_lines_with_terminator(base).collect();
+    let ours_lines: Vec<&[u8]> = byte_lines_with_terminator(ours).collect();
+    let theirs_lines: Vec<&[u8]> = byte_lines_with_terminator(theirs).collect();
+    let ours_changes = changes(base, ours, options.algorithm);
+    let theirs_changes = changes(base, theirs, options.algorithm);
+
+    let mut out = Outcome::default();
+    let (mut base_pos, mut ours_idx, mut theirs_idx) = (0, 0, 0);
+    // The difference between line numbers in `ours` or `theirs` and `base`, before the current position.
+    let (mut ours_offset, mut theirs_offset) = (0isize, 0isize);
+    while ours_idx < ours_changes.len() || theirs_idx < theirs_changes.len() {
+        // Collect all changes of both sides that overlap or touch, starting with the first of either side.
+        let start = match (ours_changes.get(ours_idx), theirs_changes.get(theirs_idx)) {
+            (Some(a), Some(b)) => a.0.start.min(b.0.start),
+            (Some(a), None) => a.0.start,
+            (None, Some(b)) => b.0.start,
+            (None, None) => unreachable!("loop condition"),
+        };
+        let (mut end, mut ours_end, mut theirs_end) = (start, ours_idx, theirs_idx);
+        loop {
+            if let Some(change) = ours_changes.get(ours_end).filter(|c| c.0.start <= end) {
+                end = end.max(change.0.end);
+                ours_end += 1;
+            } else if let Some(change) = theirs_changes.get(theirs_end).filter(|c| c.0.start <= end) {
+                end = end.max(change.0.end);
+                theirs_end += 1;
+            } else {
+                break;
+            }
+        }
+
+        for line in &base_lines[base_pos..start] {
+            out.data.extend_from_slice(line);
+        }
+        let ours_range = side_range(&ours_changes[ours_idx..ours_end], start..end, ours_offset);
+        let theirs_range = side_range(&theirs_changes[theirs_idx..theirs_end], start..end, theirs_offset);
+        ours_offset += (ours_range.len() as isize) - (end - start) as isize;
+        theirs_offset += (theirs_range.len() as isize) - (end - start) as isize;
+        let (ours_chunk, theirs_chunk) = (&ours_lines[ours_range], &theirs_lines[theirs_range]);
+
+        if ours_idx == ours_end {
+            write_lines(&mut out.data, theirs_chunk);
+        } else if theirs_idx == theirs_end || ours_chunk == theirs_chunk {
+            write_lines(&mut out.data, ours_chunk);
+        } else {
+            let conflict = Conflict {
+                ours: ours_chunk,
+                base: &base_lines[start..end],
+                theirs: theirs_chunk,
+            };
+            if conflict.write(&mut out.data, favor, options) {
+                out.conflicts += 1;
+            }
+        }
+
+        base_pos = end;
+        ours_idx = ours_end;
+        theirs_idx = theirs_end;
+    }
+    for line in &base_lines[base_pos..] {
+        out.data.extend_from_slice(line);
+    }
+    out
+}
+
+/// Return the changes needed to turn `base` into `side`, as ranges of lines in `base` and `side` respectively.
+fn changes(base: &[u8], side: &[u8], algorithm: Algorithm) -> Vec<(Range<usize>, Range<usize>)> {
+    let input = InternedInput::new(byte_lines_with_terminator(base), byte_lines_with_terminator(side));
+    let mut changes = Vec::new();
+    crate::blob::diff(algorithm, &input, |before: Range<u32>, after: Range<u32>| {
+        changes.push((
+            before.start as usize..before.end as usize,
+            after.start as usize..after.end as usize,
+        ))
+    });
+    changes
+}
+
+/// Return the lines of a side that correspond to `base` lines, given the `changes` of that side within `base`,
+/// and the `offset` of the side's line numbers before `base`.
+fn side_range(changes: &[(Range<usize>, Range<usize>)], base: Range<usize>, offset: isize) -> Range<usize> {
+    match (changes.first(), changes.last()) {
+        (Some(first), Some(last)) => first.1.start - (first.0.start - base.start)..last.1.end + (base.end - last.0.end),
+        _ => (base.start as isize + offset) as usize..(base.end as isize + offset) as usize,
+    }
+}
+
+struct Conflict<'a> {
+    ours: &'a [&'a [u8]],
+    base: &'a [&'a [u8]],
+    theirs: &'a [&'a [u8]],
+}
+
+impl Conflict<'_> {
+    /// Write this conflict to `out`, or its resolution in case of `favor`, and return `true` if it was written as conflict.
+    fn write(&self, out: &mut Vec<u8>, favor: Option<Favor>, options: &Options) -> bool {
+        let (ours, theirs) = (self.ours, self.theirs);
+        let (common_prefix, common_suffix) = if options.conflict_style == ConflictStyle::Diff3 && favor.is_none() {
+            (0, 0)
+        } else {
+            let common_prefix = ours.iter().zip(theirs.iter()).take_while(|(a, b)| a == b).count();
+            let max_suffix = ours.len().min(theirs.len()) - common_prefix;
+            let common_suffix = ours
+                .iter()
+                .rev()
+                .zip(theirs.iter().rev())
+                .take(max_suffix)
+                .take_while(|(a, b)| a == b)
+                .count();
+            (common_prefix, common_suffix)
+        };
+        let ours_mid = &ours[common_prefix..ours.len() - common_suffix];
+        let theirs_mid = &theirs[common_prefix..theirs.len() - common_suffix];
+
+        write_lines(out, &ours[..common_prefix]);
+        match favor {
+            Some(Favor::Ours) => write_lines(out, ours_mid),
+            Some(Favor::Theirs) => write_lines(out, theirs_mid),
+            Some(Favor::Union) => {
+                write_lines(out, ours_mid);
+                write_lines(out, theirs_mid);
+            }
+            None => {
+                let size = options.marker_size;
+                write_marker(out, b'<', size, options.ours_label.as_deref());
+                write_lines(out, ours_mid);
+                if options.conflict_style != ConflictStyle::Merge {
+                    write_marker(out, b'|', size, options.base_label.as_deref());
+                    write_lines(out, self.base);
+                }
+                write_marker(out, b'=', size, None);
+                write_lines(out, theirs_mid);
+                write_marker(out, b'>', size, options.theirs_label.as_deref());
+            }
+        }
+        write_lines(out, &ours[ours.len() - common_suffix..]);
+        favor.is_none()
+    }
+}
+
+fn write_lines(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    for line in lines {
+        out.extend_from_slice(line);
+    }
+}
+
+fn write_marker(out: &mut Vec<u8>, marker: u8, size: usize, label: Option<&str>) {
+    // Markers always start on a line of their own, even if the last line of a side didn't end in a newline.
+    if !out.is_empty() && out.last() != Some(&b'\n') {
+        out.push(b'\n');
+    }
+    out.extend(std::iter::repeat(marker).take(size));
+    if let Some(label) = label {
+        out.push(b' ');
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(b'\n');
+}
@@ -0,0 +1,187 @@
+//! Render line diffs of two blobs as hunks of a unified diff, just like `git diff` does.
+use std::{io, ops::Range};
+
+use crate::blob::{intern::InternedInput, sources::byte_lines_with_terminator, Algorithm};
+
+/// The maximum amount of bytes of a function name to put into a hunk header, like `git` does.
+const MAX_FUNCTION_NAME_LEN: usize = 80;
+
+/// Options for use in [`write_to()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The amount of unchanged lines to show before and after each change, similar to `git diff -U<n>`.
+    ///
+    /// Changes separated by no more than twice this amount of lines are merged into a single hunk.
+    pub context_lines: u32,
+    /// If `true`, add the closest preceding line which looks like the start of a function to each hunk header,
+    /// using the default heuristic of `git`, i.e. the line starts with a letter, `_` or `$`.
+    pub function_names: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            context_lines: 3,
+            function_names: true,
+        }
+    }
+}
+
+/// Information about the hunks written by [`write_to()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Statistics {
+    /// The amount of hunks that were written.
+    pub hunks: usize,
+    /// The amount of lines that were added.
+    pub insertions: u32,
+    /// The amount of lines that were removed.
+    pub removals: u32,
+    /// The amount of bytes in all removed lines.
+    pub removed_bytes: usize,
+}
+
+/// Diff the lines of `old` and `new` with `algorithm` and write all hunks to `out`, each with its `@@ -a,b +c,d @@`
+/// header, configured by `options`.
+///
+/// Lines lacking a trailing newline are followed by `\ No newline at end of file`. Nothing is written if there is no change.
+/// Note that the file headers, like `--- a/file` aren't written.
+pub fn write_to(
+    old: &[u8],
+    new: &[u8],
+    algorithm: Algorithm,
+    options: Options,
+    mut out: impl io::Write,
+) -> io::Result<Statistics> {
+    let input = InternedInput::new(byte_lines_with_terminator(old), byte_lines_with_terminator(new));
+    let mut changes = Vec::<(Range<usize>, Range<usize>)>::new();
+    crate::blob::diff(algorithm, &input, |before: Range<u32>, after: Range<u32>| {
+        changes.push((
+            before.start as usize..before.end as usize,
+            after.start as usize..after.end as usize,
+        ))
+    });
+    let before = |idx: usize| input.interner[input.before[idx]];
+    let after = |idx: usize| input.interner[input.after[idx]];
+    let (num_before, num_after) = (input.before.len(), input.after.len());
+    let context = options.context_lines as usize;
+
+    let mut stats = Statistics::default();
+    let mut function_name = Vec::new();
+    let mut function_search_limit = None;
+    let mut first = 0;
+    while first < changes.len() {
+        let mut last = first;
+        while last + 1 < changes.len() && changes[last + 1].0.start - changes[last].0.end <= 2 * context {
+            last += 1;
+        }
+        let hunk = &changes[first..=last];
+        let (first_change, last_change) = (&hunk[0], &hunk[hunk.len() - 1]);
+        let start_before = first_change.0.start.saturating_sub(context);
+        let start_after = first_change.1.start.saturating_sub(context);
+        let trailing_context = context
+            .min(num_before - last_change.0.end)
+            .min(num_after - last_change.1.end);
+        let end_before = last_change.0.end + trailing_context;
+        let end_after = last_change.1.end + trailing_context;
+
+        if options.function_names {
+            // Search backwards until the start of the previous hunk, keeping the previous name if nothing is found.
+            let mut line = start_before.checked_sub(1);
+            while let Some(idx) = line.filter(|idx| Some(*idx) != function_search_limit) {
+                if let Some(name) = function_name_of(before(idx)) {
+                    function_name.clear();
+                    function_name.extend_from_slice(name);
+                    break;
+                }
+                line = idx.checked_sub(1);
+            }
+            function_search_limit = start_before.checked_sub(1);
+        }
+
+        write_hunk_header(
+            &mut out,
+            start_before,
+            end_before - start_before,
+            start_after,
+            end_after - start_after,
+            &function_name,
+        )?;
+        let mut cursor = start_after;
+        for (removed, added) in hunk {
+            for idx in cursor..added.start {
+                write_line(&mut out, b' ', after(idx))?;
+            }
+            for idx in removed.clone() {
+                let line = before(idx);
+                write_line(&mut out, b'-', line)?;
+                stats.removed_bytes += line.len();
+            }
+            for idx in added.clone() {
+                write_line(&mut out, b'+', after(idx))?;
+            }
+            stats.removals += removed.len() as u32;
+            stats.insertions += added.len() as u32;
+            cursor = added.end;
+        }
+        for idx in cursor..end_after {
+            write_line(&mut out, b' ', after(idx))?;
+        }
+        stats.hunks += 1;
+        first = last + 1;
+    }
+    Ok(stats)
+}
+
+fn write_hunk_header(
+    out: &mut impl io::Write,
+    start_before: usize,
+    len_before: usize,
+    start_after: usize,
+    len_after: usize,
+    function_name: &[u8],
+) -> io::Result<()> {
+    fn write_range(out: &mut impl io::Write, start: usize, len: usize) -> io::Result<()> {
+        // Empty ranges refer to the line before the insertion point, or to 0 at the beginning of the file.
+        write!(out, "{}", if len == 0 { start } else { start + 1 })?;
+        if len != 1 {
+            write!(out, ",{len}")?;
+        }
+        Ok(())
+    }
+    out.write_all(b"@@ -")?;
+    write_range(out, start_before, len_before)?;
+    out.write_all(b" +")?;
+    write_range(out, start_after, len_after)?;
+    out.write_all(b" @@")?;
+    if !function_name.is_empty() {
+        out.write_all(b" ")?;
+        out.write_all(function_name)?;
+    }
+    out.write_all(b"\n")
+}
+
+fn write_line(out: &mut impl io::Write, prefix: u8, line: &[u8]) -> io::Result<()> {
+    out.write_all(&[prefix])?;
+    out.write_all(line)?;
+    if line.last() != Some(&b'\n') {
+        out.write_all(b"\n\\ No newline at end of file\n")?;
+    }
+    Ok(())
+}
+
+/// Return the function name of `line` according to the default heuristic of `git`, truncated and without trailing whitespace.
+fn function_name_of(line: &[u8]) -> Option<&[u8]> {
+    let first = *line.first()?;
+    if !(first.is_ascii_alphabetic() || first == b'_' || first == b'$') {
+        return None;
+    }
+    let mut name = &line[..line.len().min(MAX_FUNCTION_NAME_LEN)];
+    while let Some((last, rest)) = name.split_last() {
+        // Like `isspace()`, which includes the vertical tab.
+        if !(last.is_ascii_whitespace() || *last == 0x0b) {
+            break;
+        }
+        name = rest;
+    }
+    Some(name)
+}
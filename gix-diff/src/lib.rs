@@ -7,3 +7,6 @@ pub mod tree;
 
 ///
 pub mod blob;
+
+///
+pub mod patch;
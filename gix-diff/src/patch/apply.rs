@@ -0,0 +1,138 @@
+use gix_object::bstr::ByteSlice;
+
+use crate::patch::parse::{Hunk, Line};
+
+/// The error returned by [`apply()`][super::apply()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Hunk {hunk} starting at line {line} does not apply")]
+    HunkMismatch { hunk: usize, line: u32 },
+}
+
+/// Options for use in [`apply()`][super::apply()].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The maximum amount of context lines to ignore at the beginning and at the end of a hunk if it doesn't apply otherwise,
+    /// similar to the fuzz factor of `patch`. With `0`, the default, all context lines have to match.
+    pub fuzz: u32,
+    /// If `true`, differences in whitespace are ignored when matching the lines of a hunk with the lines of the file,
+    /// similar to `git apply --ignore-whitespace`. Context lines keep the whitespace they have in the file.
+    pub ignore_whitespace: bool,
+}
+
+pub(crate) fn hunks(old: &[u8], hunks: &[Hunk], options: Options) -> Result<Vec<u8>, Error> {
+    let lines: Vec<&[u8]> = old.lines_with_terminator().collect();
+    let mut out = Vec::with_capacity(old.len());
+    let mut cursor = 0;
+    let mut offset = 0isize;
+    for (hunk_idx, hunk) in hunks.iter().enumerate() {
+        let (leading, trailing) = hunk.context_len();
+        // Empty ranges refer to the line before the insertion point.
+        let old_start = if hunk.old_len == 0 {
+            hunk.old_start as isize
+        } else {
+            hunk.old_start as isize - 1
+        };
+        let (pos, lead, hunk_lines) = (0..=options.fuzz as usize)
+            .find_map(|fuzz| {
+                let (lead, trail) = (fuzz.min(leading), fuzz.min(trailing));
+                if fuzz > 0 && lead == 0 && trail == 0 {
+                    return None;
+                }
+                // A hunk which starts at the first line has to match there, and one without trailing context has to match at the end,
+                // unless context is reduced.
+                let match_beginning = hunk.old_start <= 1 && lead == 0;
+                let match_end = trailing == 0;
+                let hunk_lines =
+                    &hunk.lines[lead.min(hunk.lines.len())..hunk.lines.len().saturating_sub(trail).max(lead)];
+                let preimage: Vec<&[u8]> = hunk_lines
+                    .iter()
+                    .filter_map(|line| match line {
+                        Line::Context(line) | Line::Removal(line) => Some(line.as_slice()),
+                        Line::Addition(_) => None,
+                    })
+                    .collect();
+                find_preimage(
+                    &lines,
+                    &preimage,
+                    cursor,
+                    old_start + lead as isize + offset,
+                    match_beginning,
+                    match_end,
+                    options.ignore_whitespace,
+                )
+                .map(|pos| (pos, lead, hunk_lines))
+            })
+            .ok_or(Error::HunkMismatch {
+                hunk: hunk_idx + 1,
+                line: hunk.old_start,
+            })?;
+
+        out.extend(lines[cursor..pos].iter().flat_map(|line| line.iter()));
+        let mut file_line = pos;
+        for line in hunk_lines {
+            match line {
+                Line::Context(_) => {
+                    out.extend_from_slice(lines[file_line]);
+                    file_line += 1;
+                }
+                Line::Removal(_) => file_line += 1,
+                Line::Addition(line) => out.extend_from_slice(line),
+            }
+        }
+        offset = pos as isize - (old_start + lead as isize);
+        cursor = file_line;
+    }
+    out.extend(lines[cursor..].iter().flat_map(|line| line.iter()));
+    Ok(out)
+}
+
+/// Find the position of `preimage` in `lines`, starting at `min_pos` and searching close to `expected` first.
+fn find_preimage(
+    lines: &[&[u8]],
+    preimage: &[&[u8]],
+    min_pos: usize,
+    expected: isize,
+    match_beginning: bool,
+    match_end: bool,
+    ignore_whitespace: bool,
+) -> Option<usize> {
+    let max_pos = lines.len().checked_sub(preimage.len())?;
+    if min_pos > max_pos {
+        return None;
+    }
+    let matches_at = |pos: usize| {
+        (!match_beginning || pos == 0)
+            && (!match_end || pos == max_pos)
+            && lines[pos..pos + preimage.len()]
+                .iter()
+                .zip(preimage)
+                .all(|(a, b)| lines_match(a, b, ignore_whitespace))
+    };
+    let expected = (expected.max(0) as usize).clamp(min_pos, max_pos);
+    (0..=max_pos - min_pos)
+        .flat_map(|distance| {
+            let before = expected.checked_sub(distance).filter(|pos| *pos >= min_pos);
+            let after = (distance != 0)
+                .then(|| expected + distance)
+                .filter(|pos| *pos <= max_pos);
+            before.into_iter().chain(after)
+        })
+        .find(|pos| matches_at(*pos))
+}
+
+fn lines_match(a: &[u8], b: &[u8], ignore_whitespace: bool) -> bool {
+    if !ignore_whitespace {
+        return a == b;
+    }
+    let mut a = a.fields();
+    let mut b = b.fields();
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(a), Some(b)) if a == b => {}
+            _ => return false,
+        }
+    }
+}
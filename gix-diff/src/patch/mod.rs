@@ -0,0 +1,282 @@
+//! Render changes to files as patches with `diff --git` headers, like `git diff` does, along with summaries of them.
+use std::io;
+
+use gix_hash::oid;
+use gix_object::{
+    bstr::{BStr, BString},
+    tree::EntryMode,
+};
+
+use crate::blob::{unified_diff, Algorithm};
+
+/// The amount of bytes `git` checks for null bytes to determine if a file is binary.
+const BINARY_CHECK_LEN: usize = 8000;
+
+/// One side of a [`Change`].
+#[derive(Debug, Clone, Copy)]
+pub struct File<'a> {
+    /// The path of the file relative to the repository root.
+    pub location: &'a BStr,
+    /// The id of the blob, or of the commit if this is a submodule.
+    pub id: &'a oid,
+    /// The mode of the file.
+    pub mode: EntryMode,
+    /// The content of the file to diff.
+    ///
+    /// For submodules, this is typically `Subproject commit <hex>\n`.
+    pub data: &'a [u8],
+}
+
+/// A change to a single file, for rendering as patch with [`write_to()`].
+#[derive(Debug, Clone, Copy)]
+pub enum Change<'a> {
+    /// The file was added.
+    Addition {
+        /// The added file.
+        new: File<'a>,
+    },
+    /// The file was deleted.
+    Deletion {
+        /// The deleted file.
+        old: File<'a>,
+    },
+    /// The content or the mode of the file changed.
+    Modification {
+        /// The file before the change.
+        old: File<'a>,
+        /// The file after the change.
+        new: File<'a>,
+    },
+    /// The file was renamed or copied, and possibly changed as well.
+    Rewrite {
+        /// The source of the rename or copy.
+        old: File<'a>,
+        /// The destination of the rename or copy.
+        new: File<'a>,
+        /// If `true`, the file was copied, otherwise it was renamed.
+        copy: bool,
+    },
+}
+
+impl<'a> Change<'a> {
+    /// Return the file before the change, or `None` if it was added.
+    pub fn old_file(&self) -> Option<&File<'a>> {
+        match self {
+            Change::Addition { .. } => None,
+            Change::Deletion { old } | Change::Modification { old, .. } | Change::Rewrite { old, .. } => Some(old),
+        }
+    }
+
+    /// Return the file after the change, or `None` if it was deleted.
+    pub fn new_file(&self) -> Option<&File<'a>> {
+        match self {
+            Change::Deletion { .. } => None,
+            Change::Addition { new } | Change::Modification { new, .. } | Change::Rewrite { new, .. } => Some(new),
+        }
+    }
+}
+
+/// Options for use in [`write_to()`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The algorithm to use for diffing lines.
+    pub algorithm: Algorithm,
+    /// Options for rendering the hunks of changed lines.
+    pub hunks: unified_diff::Options,
+    /// The amount of hexadecimal characters to use for object ids in the `index` line.
+    pub abbrev: usize,
+    /// The prefix for paths of the old version of a file, like `a/`.
+    pub old_prefix: BString,
+    /// The prefix for paths of the new version of a file, like `b/`.
+    pub new_prefix: BString,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            algorithm: Algorithm::Myers,
+            hunks: Default::default(),
+            abbrev: 7,
+            old_prefix: "a/".into(),
+            new_prefix: "b/".into(),
+        }
+    }
+}
+
+/// Write a patch for `change` to `out` using `options`, and return statistics about it for use in summaries.
+///
+/// `is_binary` is `Some(true)` if the `diff` attribute is unset for the file, `Some(false)` if it is set, and `None`
+/// to detect binary files by looking for null bytes, just like `git` does.
+/// Binary files are only mentioned as such without showing their content.
+pub fn write_to(
+    change: &Change<'_>,
+    is_binary: Option<bool>,
+    options: &Options,
+    mut out: impl io::Write,
+) -> io::Result<FileStat> {
+    let (old, new) = (change.old_file(), change.new_file());
+    let old_name = old.or(new).expect("at least one side").location;
+    let new_name = new.or(old).expect("at least one side").location;
+    let old_label = old.map_or_else(
+        || "/dev/null".into(),
+        |_| quote::with_prefix(options.old_prefix.as_ref(), old_name),
+    );
+    let new_label = new.map_or_else(
+        || "/dev/null".into(),
+        |_| quote::with_prefix(options.new_prefix.as_ref(), new_name),
+    );
+
+    writeln!(
+        out,
+        "diff --git {} {}",
+        quote::with_prefix(options.old_prefix.as_ref(), old_name),
+        quote::with_prefix(options.new_prefix.as_ref(), new_name)
+    )?;
+    match (old, new) {
+        (None, Some(new)) => writeln!(out, "new file mode {:06o}", new.mode as u16)?,
+        (Some(old), None) => writeln!(out, "deleted file mode {:06o}", old.mode as u16)?,
+        (Some(old), Some(new)) if old.mode != new.mode => {
+            writeln!(out, "old mode {:06o}", old.mode as u16)?;
+            writeln!(out, "new mode {:06o}", new.mode as u16)?;
+        }
+        _ => {}
+    }
+
+    let is_binary = is_binary.unwrap_or_else(|| old.into_iter().chain(new).any(|file| is_binary_data(file.data)));
+    let mut hunks = Vec::new();
+    let mut stat = FileStat {
+        name: match change {
+            Change::Rewrite { .. } => quote::rename(old_name, new_name),
+            _ => quote::path(new_name).into_owned(),
+        },
+        insertions: 0,
+        removals: 0,
+        binary: None,
+    };
+    let (old_data, new_data) = (old.map_or(&[][..], |f| f.data), new.map_or(&[][..], |f| f.data));
+    if is_binary {
+        stat.binary = Some((old_data.len() as u64, new_data.len() as u64));
+    } else {
+        let hunk_stats = unified_diff::write_to(old_data, new_data, options.algorithm, options.hunks, &mut hunks)?;
+        stat.insertions = hunk_stats.insertions;
+        stat.removals = hunk_stats.removals;
+    }
+    if let Change::Rewrite { copy, old, new } = change {
+        let similarity = if old.id == new.id {
+            100
+        } else {
+            similarity(old.data, new.data, is_binary)
+        };
+        let kind = if *copy { "copy" } else { "rename" };
+        writeln!(out, "similarity index {similarity}%")?;
+        writeln!(out, "{kind} from {}", quote::path(old_name))?;
+        writeln!(out, "{kind} to {}", quote::path(new_name))?;
+    }
+
+    let null = options
+        .abbrev
+        .min(old.or(new).expect("at least one side").id.kind().len_in_hex());
+    let (old_id, new_id) = (old.map(|f| f.id), new.map(|f| f.id));
+    if old_id != new_id {
+        let abbrev = |id: Option<&oid>| match id {
+            Some(id) => id.to_hex_with_len(options.abbrev).to_string(),
+            None => "0".repeat(null),
+        };
+        write!(out, "index {}..{}", abbrev(old_id), abbrev(new_id))?;
+        match (old, new) {
+            (Some(old), Some(new)) if old.mode == new.mode => writeln!(out, " {:06o}", old.mode as u16)?,
+            _ => writeln!(out)?,
+        }
+        if is_binary {
+            writeln!(out, "Binary files {old_label} and {new_label} differ")?;
+        } else if !hunks.is_empty() {
+            let tab_if_space = |label: &BStr| if label.contains(&b' ') { "\t" } else { "" };
+            writeln!(out, "--- {old_label}{}", tab_if_space(old_label.as_ref()))?;
+            writeln!(out, "+++ {new_label}{}", tab_if_space(new_label.as_ref()))?;
+            out.write_all(&hunks)?;
+        }
+    }
+    Ok(stat)
+}
+
+/// Return how similar `new` is to `old` in percent, estimated like `git` does to show the `similarity index` of renames and copies.
+///
+/// Both are split into spans of up to 64 bytes ending with a newline, and the amount of bytes in spans they have in common
+/// is compared to the size of the larger of both. Carriage returns in front of newlines are ignored unless `is_binary` is true.
+pub fn similarity(old: &[u8], new: &[u8], is_binary: bool) -> usize {
+    /// The maximum score, which `git` uses to compute the score with fixed-point arithmetic.
+    const MAX_SCORE: usize = 60000;
+    let max_len = old.len().max(new.len());
+    if max_len == 0 {
+        return 100;
+    }
+    let (old_spans, new_spans) = (span_counts(old, is_binary), span_counts(new, is_binary));
+    let copied: usize = old_spans
+        .iter()
+        .map(|(hash, count)| new_spans.get(hash).map_or(0, |new_count| (*count).min(*new_count)))
+        .sum();
+    let score = (copied as u64 * MAX_SCORE as u64 / max_len as u64) as usize;
+    score * 100 / MAX_SCORE
+}
+
+/// Return the amount of bytes of all spans in `data` by the hash of each span, using the same hash function as `git`.
+fn span_counts(data: &[u8], is_binary: bool) -> std::collections::HashMap<u32, usize> {
+    const HASH_BASE: u32 = 107927;
+    let mut out = std::collections::HashMap::<u32, usize>::new();
+    let (mut accum1, mut accum2, mut len) = (0u32, 0u32, 0);
+    for (idx, &byte) in data.iter().enumerate() {
+        if !is_binary && byte == b'\r' && data.get(idx + 1) == Some(&b'\n') {
+            continue;
+        }
+        let prev_accum1 = accum1;
+        accum1 = (accum1 << 7) ^ (accum2 >> 25);
+        accum2 = (accum2 << 7) ^ (prev_accum1 >> 25);
+        accum1 = accum1.wrapping_add(byte.into());
+        len += 1;
+        if len < 64 && byte != b'\n' {
+            continue;
+        }
+        *out.entry(accum1.wrapping_add(accum2.wrapping_mul(0x61)) % HASH_BASE)
+            .or_default() += len;
+        (accum1, accum2, len) = (0, 0, 0);
+    }
+    if len > 0 {
+        *out.entry(accum1.wrapping_add(accum2.wrapping_mul(0x61)) % HASH_BASE)
+            .or_default() += len;
+    }
+    out
+}
+
+/// Return `true` if `data` is considered binary, which is the case if it contains a null byte within the first 8000 bytes.
+pub fn is_binary_data(data: &[u8]) -> bool {
+    data[..data.len().min(BINARY_CHECK_LEN)].contains(&0)
+}
+
+///
+pub mod stat;
+pub use stat::FileStat;
+
+///
+pub mod quote;
+
+///
+pub mod parse;
+
+/// Parse all patches for single files in `input`, which may be the output of `git diff`, `git format-patch` or `diff -u`,
+/// configured by `options`.
+///
+/// Text which doesn't belong to patches, like commit messages or mail headers, is skipped.
+pub fn parse(input: &[u8], options: parse::Options) -> Result<Vec<parse::FilePatch>, parse::Error> {
+    parse::files(input, options)
+}
+
+///
+pub mod apply;
+
+/// Apply all `hunks` of a patch to the content of the file `old` in order, and return the new content, configured by `options`.
+///
+/// Each hunk is searched close to the line it is supposed to be at, taking into account the offsets of previously applied hunks,
+/// and it is an error if a hunk doesn't apply.
+pub fn apply(old: &[u8], hunks: &[parse::Hunk], options: apply::Options) -> Result<Vec<u8>, apply::Error> {
+    apply::hunks(old, hunks, options)
+}
@@ -0,0 +1,582 @@
+use gix_object::{
+    bstr::{BStr, BString, ByteSlice},
+    tree::EntryMode,
+};
+
+use crate::patch::quote;
+
+/// The error returned by [`parse()`][super::parse()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line}: invalid hunk header {header:?}")]
+    HunkHeader { line: usize, header: BString },
+    #[error("Line {line}: hunk ended unexpectedly, expected {expected} more lines")]
+    TruncatedHunk { line: usize, expected: usize },
+    #[error("Line {line}: invalid file mode {mode:?}")]
+    Mode { line: usize, mode: BString },
+    #[error("Line {line}: could not decode binary patch data")]
+    Binary { line: usize },
+    #[error("Line {line}: could not determine the file name of the patch")]
+    MissingName { line: usize },
+}
+
+/// Options for use in [`parse()`][super::parse()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The amount of leading path components to remove from paths in `diff --git`, `---` and `+++` lines,
+    /// similar to `git apply -p<n>`. Defaults to 1 to remove the `a/` and `b/` prefixes.
+    pub strip: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { strip: 1 }
+    }
+}
+
+/// What happens to the file a [`FilePatch`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// The file is created.
+    Addition,
+    /// The file is deleted.
+    Deletion,
+    /// The content or mode of the file changes.
+    Modification,
+    /// The file is moved to a new location, and possibly changed.
+    Rename,
+    /// The file is copied to a new location, and possibly changed.
+    Copy,
+}
+
+/// A patch for a single file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// What happens to the file.
+    pub operation: Operation,
+    /// The path of the file before the change, or `None` if it is added.
+    pub old_path: Option<BString>,
+    /// The path of the file after the change, or `None` if it is deleted.
+    pub new_path: Option<BString>,
+    /// The mode of the file before the change, if known.
+    pub old_mode: Option<EntryMode>,
+    /// The mode of the file after the change, if known.
+    pub new_mode: Option<EntryMode>,
+    /// The possibly abbreviated hexadecimal id of the blob before the change, as found in the `index` line.
+    pub old_id: Option<BString>,
+    /// The possibly abbreviated hexadecimal id of the blob after the change, as found in the `index` line.
+    pub new_id: Option<BString>,
+    /// The hunks of changed lines.
+    pub hunks: Vec<Hunk>,
+    /// If this is a binary patch, its data.
+    pub binary: Option<Binary>,
+}
+
+/// A hunk of changed lines, with its header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The one-based line number of the first line of the hunk in the old file, or the line before the insertion point
+    /// if `old_len` is 0.
+    pub old_start: u32,
+    /// The amount of context and removed lines of the hunk.
+    pub old_len: u32,
+    /// The one-based line number of the first line of the hunk in the new file.
+    pub new_start: u32,
+    /// The amount of context and added lines of the hunk.
+    pub new_len: u32,
+    /// All lines of the hunk.
+    pub lines: Vec<Line>,
+}
+
+impl Hunk {
+    /// Return an iterator over the lines as they are in the old file, i.e. context and removed lines.
+    pub fn old_lines(&self) -> impl Iterator<Item = &BStr> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Context(line) | Line::Removal(line) => Some(line.as_ref()),
+            Line::Addition(_) => None,
+        })
+    }
+
+    /// Return an iterator over the lines as they are in the new file, i.e. context and added lines.
+    pub fn new_lines(&self) -> impl Iterator<Item = &BStr> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Context(line) | Line::Addition(line) => Some(line.as_ref()),
+            Line::Removal(_) => None,
+        })
+    }
+
+    /// Return the amount of context lines before the first change, and after the last change.
+    pub fn context_len(&self) -> (usize, usize) {
+        let leading = self.lines.iter().take_while(|l| matches!(l, Line::Context(_))).count();
+        let trailing = self
+            .lines
+            .iter()
+            .rev()
+            .take_while(|l| matches!(l, Line::Context(_)))
+            .count();
+        (leading, trailing)
+    }
+}
+
+/// A line in a [`Hunk`], with its line terminator unless it is the last line of a file without trailing newline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    /// A line that is the same in both versions.
+    Context(BString),
+    /// A line that only exists in the old version.
+    Removal(BString),
+    /// A line that only exists in the new version.
+    Addition(BString),
+}
+
+/// The data of a binary patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Binary {
+    /// The patch only states that the binary files differ, without data to apply.
+    Differ,
+    /// The data to create the new version from the old one, and optionally, the data to reverse the change.
+    Data {
+        /// The data to obtain the new version of the file.
+        forward: BinaryHunk,
+        /// The data to obtain the old version of the file from the new one.
+        reverse: Option<BinaryHunk>,
+    },
+}
+
+/// How to interpret the data of a [`BinaryHunk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryKind {
+    /// The data is the entire content of the file.
+    Literal,
+    /// The data is a delta to apply to the other version of the file, in the format used by packs.
+    Delta,
+}
+
+/// The decoded data of a binary patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryHunk {
+    /// How to interpret the data.
+    pub kind: BinaryKind,
+    /// The size of the data once inflated.
+    pub size: u64,
+    /// The zlib-deflated data.
+    pub data: Vec<u8>,
+}
+
+struct Lines<'a> {
+    lines: std::iter::Peekable<gix_object::bstr::LinesWithTerminator<'a>>,
+    line: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let line = self.lines.next()?;
+        self.line += 1;
+        Some(line)
+    }
+
+    fn peek(&mut self) -> Option<&'a [u8]> {
+        self.lines.peek().copied()
+    }
+}
+
+pub(crate) fn files(input: &[u8], options: Options) -> Result<Vec<FilePatch>, Error> {
+    let mut lines = Lines {
+        lines: input.lines_with_terminator().peekable(),
+        line: 0,
+    };
+    let mut files = Vec::new();
+    while let Some(line) = lines.next() {
+        if let Some(names) = line.strip_prefix(b"diff --git ") {
+            files.push(git_patch(trim_newline(names), &mut lines, options)?);
+        } else if line.starts_with(b"--- ") && lines.peek().map_or(false, |l| l.starts_with(b"+++ ")) {
+            let old = label(&line[4..], options.strip);
+            let new_label = lines.next().expect("peeked");
+            let new = label(&new_label[4..], options.strip);
+            if !lines.peek().map_or(false, |l| l.starts_with(b"@@ -")) {
+                continue;
+            }
+            let mut file = FilePatch {
+                operation: Operation::Modification,
+                old_path: old,
+                new_path: new,
+                old_mode: None,
+                new_mode: None,
+                old_id: None,
+                new_id: None,
+                hunks: Vec::new(),
+                binary: None,
+            };
+            file.hunks = hunks(&mut lines)?;
+            set_operation_by_paths(&mut file, lines.line)?;
+            files.push(file);
+        }
+    }
+    Ok(files)
+}
+
+fn git_patch(names: &[u8], lines: &mut Lines<'_>, options: Options) -> Result<FilePatch, Error> {
+    let header_line = lines.line;
+    let mut file = FilePatch {
+        operation: Operation::Modification,
+        old_path: None,
+        new_path: None,
+        old_mode: None,
+        new_mode: None,
+        old_id: None,
+        new_id: None,
+        hunks: Vec::new(),
+        binary: None,
+    };
+    let (mut is_addition, mut is_deletion) = (false, false);
+    let mut labels = None;
+    while let Some(line) = lines.peek() {
+        let content = trim_newline(line);
+        if let Some(mode) = content.strip_prefix(b"old mode ") {
+            file.old_mode = Some(parse_mode(mode, lines.line + 1)?);
+        } else if let Some(mode) = content.strip_prefix(b"new mode ") {
+            file.new_mode = Some(parse_mode(mode, lines.line + 1)?);
+        } else if let Some(mode) = content.strip_prefix(b"deleted file mode ") {
+            file.old_mode = Some(parse_mode(mode, lines.line + 1)?);
+            is_deletion = true;
+        } else if let Some(mode) = content.strip_prefix(b"new file mode ") {
+            file.new_mode = Some(parse_mode(mode, lines.line + 1)?);
+            is_addition = true;
+        } else if let Some(path) = content
+            .strip_prefix(b"rename from ")
+            .or_else(|| content.strip_prefix(b"rename old "))
+        {
+            file.old_path = Some(unquote_or_verbatim(path));
+            file.operation = Operation::Rename;
+        } else if let Some(path) = content
+            .strip_prefix(b"rename to ")
+            .or_else(|| content.strip_prefix(b"rename new "))
+        {
+            file.new_path = Some(unquote_or_verbatim(path));
+            file.operation = Operation::Rename;
+        } else if let Some(path) = content.strip_prefix(b"copy from ") {
+            file.old_path = Some(unquote_or_verbatim(path));
+            file.operation = Operation::Copy;
+        } else if let Some(path) = content.strip_prefix(b"copy to ") {
+            file.new_path = Some(unquote_or_verbatim(path));
+            file.operation = Operation::Copy;
+        } else if content.starts_with(b"similarity index ") || content.starts_with(b"dissimilarity index ") {
+            // Informational only.
+        } else if let Some(index) = content.strip_prefix(b"index ") {
+            let (ids, mode) = match index.find_byte(b' ') {
+                Some(pos) => (&index[..pos], Some(&index[pos + 1..])),
+                None => (index, None),
+            };
+            if let Some(pos) = ids.find(b"..") {
+                file.old_id = Some(ids[..pos].into());
+                file.new_id = Some(ids[pos + 2..].into());
+            }
+            if let Some(mode) = mode {
+                let mode = parse_mode(mode, lines.line + 1)?;
+                file.old_mode = file.old_mode.or(Some(mode));
+                file.new_mode = file.new_mode.or(Some(mode));
+            }
+        } else if line.starts_with(b"--- ") {
+            lines.next();
+            let old = label(&line[4..], options.strip);
+            let new = match lines.peek() {
+                Some(line) if line.starts_with(b"+++ ") => {
+                    lines.next();
+                    label(&line[4..], options.strip)
+                }
+                _ => None,
+            };
+            labels = Some((old, new));
+            continue;
+        } else {
+            break;
+        }
+        lines.next();
+    }
+
+    if is_addition {
+        file.operation = Operation::Addition;
+    } else if is_deletion {
+        file.operation = Operation::Deletion;
+    }
+    if file.old_path.is_none() || file.new_path.is_none() {
+        let (old, new) = match labels {
+            Some((old, new)) => (old, new),
+            None => match git_header_names(names, options.strip) {
+                Some(name) => (Some(name.clone()), Some(name)),
+                None => return Err(Error::MissingName { line: header_line }),
+            },
+        };
+        if !is_addition {
+            file.old_path = file.old_path.take().or(old.clone()).or_else(|| new.clone());
+        }
+        if !is_deletion {
+            file.new_path = file.new_path.take().or(new).or(old);
+        }
+    }
+
+    match lines.peek() {
+        Some(line) if line.starts_with(b"@@ -") => file.hunks = hunks(lines)?,
+        Some(line) if trim_newline(line) == b"GIT binary patch" => {
+            lines.next();
+            let forward = binary_hunk(lines)?.ok_or(Error::Binary { line: lines.line })?;
+            let reverse = binary_hunk(lines)?;
+            file.binary = Some(Binary::Data { forward, reverse });
+        }
+        Some(line) if line.starts_with(b"Binary files ") => {
+            lines.next();
+            file.binary = Some(Binary::Differ);
+        }
+        _ => {}
+    }
+    Ok(file)
+}
+
+fn hunks(lines: &mut Lines<'_>) -> Result<Vec<Hunk>, Error> {
+    let mut hunks = Vec::new();
+    while let Some(header) = lines.peek().filter(|l| l.starts_with(b"@@ -")) {
+        lines.next();
+        let (old_start, old_len, new_start, new_len) = parse_hunk_header(header).ok_or_else(|| Error::HunkHeader {
+            line: lines.line,
+            header: trim_newline(header).into(),
+        })?;
+        let mut hunk = Hunk {
+            old_start,
+            old_len,
+            new_start,
+            new_len,
+            lines: Vec::new(),
+        };
+        let (mut old_remaining, mut new_remaining) = (old_len as usize, new_len as usize);
+        while old_remaining > 0 || new_remaining > 0 {
+            let line = match lines.next() {
+                Some(line) => line,
+                None => {
+                    return Err(Error::TruncatedHunk {
+                        line: lines.line,
+                        expected: old_remaining.max(new_remaining),
+                    })
+                }
+            };
+            let (kind, content) = match line.split_first() {
+                // Some mailers and editors strip the trailing space of empty context lines.
+                Some((b'\n', _)) => (b' ', &b"\n"[..]),
+                Some((b'\r', rest)) if rest == b"\n" => (b' ', line),
+                Some((kind, content)) => (*kind, content),
+                None => (0, line),
+            };
+            match kind {
+                b' ' if old_remaining > 0 && new_remaining > 0 => {
+                    old_remaining -= 1;
+                    new_remaining -= 1;
+                    hunk.lines.push(Line::Context(content.into()));
+                }
+                b'-' if old_remaining > 0 => {
+                    old_remaining -= 1;
+                    hunk.lines.push(Line::Removal(content.into()));
+                }
+                b'+' if new_remaining > 0 => {
+                    new_remaining -= 1;
+                    hunk.lines.push(Line::Addition(content.into()));
+                }
+                b'\\' => strip_newline_of_last_line(&mut hunk),
+                _ => {
+                    return Err(Error::TruncatedHunk {
+                        line: lines.line,
+                        expected: old_remaining.max(new_remaining),
+                    })
+                }
+            }
+        }
+        if lines.peek().map_or(false, |l| l.starts_with(b"\\")) {
+            lines.next();
+            strip_newline_of_last_line(&mut hunk);
+        }
+        hunks.push(hunk);
+    }
+    Ok(hunks)
+}
+
+fn strip_newline_of_last_line(hunk: &mut Hunk) {
+    if let Some(Line::Context(line) | Line::Removal(line) | Line::Addition(line)) = hunk.lines.last_mut() {
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+    }
+}
+
+fn parse_hunk_header(line: &[u8]) -> Option<(u32, u32, u32, u32)> {
+    fn range(input: &[u8]) -> Option<(u32, u32)> {
+        let (start, len) = match input.find_byte(b',') {
+            Some(pos) => (&input[..pos], Some(&input[pos + 1..])),
+            None => (input, None),
+        };
+        let start = start.to_str().ok()?.parse().ok()?;
+        let len = match len {
+            Some(len) => len.to_str().ok()?.parse().ok()?,
+            None => 1,
+        };
+        Some((start, len))
+    }
+    let line = line.strip_prefix(b"@@ -")?;
+    let end = line.find(b" @@")?;
+    let mut ranges = line[..end].split_str(" ");
+    let (old_start, old_len) = range(ranges.next()?)?;
+    let (new_start, new_len) = range(ranges.next()?.strip_prefix(b"+")?)?;
+    Some((old_start, old_len, new_start, new_len))
+}
+
+fn binary_hunk(lines: &mut Lines<'_>) -> Result<Option<BinaryHunk>, Error> {
+    let header = match lines.peek() {
+        Some(line) => trim_newline(line),
+        None => return Ok(None),
+    };
+    let (kind, size) = if let Some(size) = header.strip_prefix(b"literal ") {
+        (BinaryKind::Literal, size)
+    } else if let Some(size) = header.strip_prefix(b"delta ") {
+        (BinaryKind::Delta, size)
+    } else {
+        return Ok(None);
+    };
+    lines.next();
+    let size = size
+        .to_str()
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or(Error::Binary { line: lines.line })?;
+    let mut data = Vec::new();
+    while let Some(line) = lines.next() {
+        let line = trim_newline(line);
+        if line.is_empty() {
+            break;
+        }
+        decode_base85_line(line, &mut data).ok_or(Error::Binary { line: lines.line })?;
+    }
+    Ok(Some(BinaryHunk { kind, size, data }))
+}
+
+/// Decode a line of base85 data as used by `git` in binary patches, with the first character encoding the amount of bytes.
+fn decode_base85_line(line: &[u8], out: &mut Vec<u8>) -> Option<()> {
+    const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+    let (len, data) = line.split_first()?;
+    let len = match len {
+        b'A'..=b'Z' => len - b'A' + 1,
+        b'a'..=b'z' => len - b'a' + 27,
+        _ => return None,
+    } as usize;
+    if data.len() != (len + 3) / 4 * 5 {
+        return None;
+    }
+    let start = out.len();
+    for chunk in data.chunks(5) {
+        let mut value: u32 = 0;
+        for byte in chunk {
+            let digit = ALPHABET.iter().position(|b| b == byte)? as u32;
+            value = value.checked_mul(85)?.checked_add(digit)?;
+        }
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+    out.truncate(start + len);
+    Some(())
+}
+
+fn parse_mode(mode: &[u8], line: usize) -> Result<EntryMode, Error> {
+    let err = || Error::Mode {
+        line,
+        mode: mode.into(),
+    };
+    let value = u32::from_str_radix(mode.trim().to_str().map_err(|_| err())?, 8).map_err(|_| err())?;
+    Ok(match value {
+        0o100644 | 0o100664 | 0o100600 => EntryMode::Blob,
+        0o100755 => EntryMode::BlobExecutable,
+        0o120000 => EntryMode::Link,
+        0o160000 => EntryMode::Commit,
+        0o040000 => EntryMode::Tree,
+        _ => return Err(err()),
+    })
+}
+
+/// Set the operation of a traditional patch which lacks extended headers, based on its paths being `/dev/null`.
+fn set_operation_by_paths(file: &mut FilePatch, line: usize) -> Result<(), Error> {
+    file.operation = match (&file.old_path, &file.new_path) {
+        (None, Some(_)) => Operation::Addition,
+        (Some(_), None) => Operation::Deletion,
+        (Some(_), Some(_)) => Operation::Modification,
+        (None, None) => return Err(Error::MissingName { line }),
+    };
+    Ok(())
+}
+
+/// Parse the path of a `---` or `+++` line, or return `None` if it is `/dev/null`.
+fn label(input: &[u8], strip: usize) -> Option<BString> {
+    let input = trim_newline(input);
+    let path = if input.starts_with(b"\"") {
+        quote::unquote(input).map(|(path, _)| path)?
+    } else {
+        // Anything after a tab is a timestamp, or the tab was added as the name contains spaces.
+        let end = input.find_byte(b'\t').unwrap_or(input.len());
+        input[..end].into()
+    };
+    (path != "/dev/null").then(|| strip_components(path.as_ref(), strip).into())
+}
+
+/// Obtain the name from the header line of a git patch, which is only possible if both names are the same.
+fn git_header_names(names: &[u8], strip: usize) -> Option<BString> {
+    if names.starts_with(b"\"") {
+        let (old, consumed) = quote::unquote(names)?;
+        let rest = names[consumed..].trim_start();
+        let new = if rest.starts_with(b"\"") {
+            quote::unquote(rest)?.0
+        } else {
+            rest.into()
+        };
+        let (old, new) = (
+            strip_components(old.as_ref(), strip),
+            strip_components(new.as_ref(), strip),
+        );
+        return (old == new).then(|| old.into());
+    }
+    names
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b == b' ')
+        .find_map(|(pos, _)| {
+            let old = strip_components(names[..pos].as_bstr(), strip);
+            let new = &names[pos + 1..];
+            let new = if new.starts_with(b"\"") {
+                BString::from(strip_components(quote::unquote(new)?.0.as_ref(), strip))
+            } else {
+                strip_components(new.as_bstr(), strip).into()
+            };
+            (old == new).then(|| old.into())
+        })
+}
+
+fn strip_components(path: &BStr, strip: usize) -> &BStr {
+    let mut path = path;
+    for _ in 0..strip {
+        match path.find_byte(b'/') {
+            Some(pos) => path = path[pos + 1..].as_bstr(),
+            None => break,
+        }
+    }
+    path
+}
+
+fn unquote_or_verbatim(path: &[u8]) -> BString {
+    if path.starts_with(b"\"") {
+        if let Some((path, _)) = quote::unquote(path) {
+            return path;
+        }
+    }
+    path.into()
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
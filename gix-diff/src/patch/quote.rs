@@ -0,0 +1,147 @@
+//! Quoting of paths in patches, which is needed if they contain special characters.
+use std::borrow::Cow;
+
+use gix_object::bstr::{BStr, BString, ByteSlice, ByteVec};
+
+/// Return `path` quoted in C-style if it contains control characters, quotes, backslashes or non-ASCII bytes,
+/// or `path` itself otherwise.
+///
+/// This is the behaviour of `git` with `core.quotePath=true`, its default.
+pub fn path(path: &BStr) -> Cow<'_, BStr> {
+    if !path.iter().any(|b| must_quote(*b)) {
+        return Cow::Borrowed(path);
+    }
+    let mut out = BString::from("\"");
+    append_quoted_unchecked(path, &mut out);
+    out.push_byte(b'"');
+    Cow::Owned(out)
+}
+
+/// Return `prefix` and `path` joined, and quoted as a whole if needed.
+pub fn with_prefix(prefix: &BStr, path: &BStr) -> BString {
+    if !prefix.iter().chain(path.iter()).any(|b| must_quote(*b)) {
+        let mut out = prefix.to_owned();
+        out.push_str(path);
+        return out;
+    }
+    let mut out = BString::from("\"");
+    append_quoted_unchecked(prefix, &mut out);
+    append_quoted_unchecked(path, &mut out);
+    out.push_byte(b'"');
+    out
+}
+
+/// Return the name to show for a file renamed from `old` to `new` in a summary of changes, like `dir/{old => new}/file`.
+pub fn rename(old: &BStr, new: &BStr) -> BString {
+    if old.iter().chain(new.iter()).any(|b| must_quote(*b)) {
+        let mut out = path(old).into_owned();
+        out.push_str(" => ");
+        out.push_str(path(new).as_ref());
+        return out;
+    }
+    let (old, new) = (old.as_bytes(), new.as_bytes());
+    let prefix_len = old
+        .iter()
+        .zip(new)
+        .take_while(|(a, b)| a == b)
+        .enumerate()
+        .filter(|(_, (a, _))| **a == b'/')
+        .last()
+        .map_or(0, |(pos, _)| pos + 1);
+    // The common suffix starts at a slash, and may reuse the slash ending the prefix.
+    let suffix_limit = old.len().min(new.len()) - prefix_len.saturating_sub(1);
+    let suffix_len = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(suffix_limit)
+        .take_while(|(a, b)| a == b)
+        .enumerate()
+        .filter(|(_, (a, _))| **a == b'/')
+        .last()
+        .map_or(0, |(pos, _)| pos + 1);
+    let old_mid = &old[prefix_len..old.len().saturating_sub(suffix_len).max(prefix_len)];
+    let new_mid = &new[prefix_len..new.len().saturating_sub(suffix_len).max(prefix_len)];
+
+    let mut out = BString::from(&old[..prefix_len]);
+    let has_affixes = prefix_len + suffix_len > 0;
+    if has_affixes {
+        out.push_byte(b'{');
+    }
+    out.push_str(old_mid);
+    out.push_str(" => ");
+    out.push_str(new_mid);
+    if has_affixes {
+        out.push_byte(b'}');
+        out.push_str(&old[old.len() - suffix_len..]);
+    }
+    out
+}
+
+/// Unquote the C-style quoted string at the beginning of `input`, returning it along with the amount of consumed bytes
+/// including the quotes, or `None` if `input` doesn't start with a valid quoted string.
+pub fn unquote(input: &[u8]) -> Option<(BString, usize)> {
+    let mut out = BString::default();
+    let mut bytes = input.strip_prefix(b"\"")?.iter().enumerate();
+    while let Some((pos, byte)) = bytes.next() {
+        match byte {
+            b'"' => return Some((out, pos + 2)),
+            b'\\' => {
+                let (_, escaped) = bytes.next()?;
+                out.push(match escaped {
+                    b'a' => 0x07,
+                    b'b' => 0x08,
+                    b't' => b'\t',
+                    b'n' => b'\n',
+                    b'v' => 0x0b,
+                    b'f' => 0x0c,
+                    b'r' => b'\r',
+                    b'0'..=b'7' => {
+                        let mut value = u32::from(escaped - b'0');
+                        for _ in 0..2 {
+                            let (_, digit) = bytes.next()?;
+                            if !(b'0'..=b'7').contains(digit) {
+                                return None;
+                            }
+                            value = value * 8 + u32::from(digit - b'0');
+                        }
+                        u8::try_from(value).ok()?
+                    }
+                    other => *other,
+                });
+            }
+            other => out.push(*other),
+        }
+    }
+    None
+}
+
+fn must_quote(b: u8) -> bool {
+    b < 0x20 || b == b'"' || b == b'\\' || b >= 0x7f
+}
+
+fn append_quoted_unchecked(input: &BStr, out: &mut BString) {
+    for &b in input.iter() {
+        let escaped = match b {
+            0x07 => b'a',
+            0x08 => b'b',
+            b'\t' => b't',
+            b'\n' => b'n',
+            0x0b => b'v',
+            0x0c => b'f',
+            b'\r' => b'r',
+            b'"' => b'"',
+            b'\\' => b'\\',
+            b if must_quote(b) => {
+                out.push_str(format!("\\{b:03o}"));
+                continue;
+            }
+            b => {
+                out.push_byte(b);
+                continue;
+            }
+        };
+        out.push_byte(b'\\');
+        out.push_byte(escaped);
+    }
+}
@@ -0,0 +1,214 @@
+//! Summaries of changes to files, like `git diff --stat`, `--numstat` and `--shortstat` show them.
+use std::io;
+
+use gix_object::bstr::{BStr, BString, ByteSlice};
+
+/// Statistics about the change to a single file, as returned by [`write_to()`][super::write_to()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    /// The name to display for the file, quoted if needed, or like `dir/{old => new}` for renames and copies.
+    pub name: BString,
+    /// The amount of added lines.
+    pub insertions: u32,
+    /// The amount of removed lines.
+    pub removals: u32,
+    /// If the file is binary, the size of its old and its new version in bytes.
+    pub binary: Option<(u64, u64)>,
+}
+
+/// Options for use in [`write_stat()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The total width of each line, similar to `git diff --stat=<width>`.
+    pub width: usize,
+    /// The maximum width of the file name, or `None` to use as much as possible.
+    pub name_width: Option<usize>,
+    /// The maximum width of the graph of `+` and `-`, or `None` to use as much as possible.
+    pub graph_width: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            width: 80,
+            name_width: None,
+            graph_width: None,
+        }
+    }
+}
+
+/// Write one line per file with the amount of added and removed lines, and its name, like `git diff --numstat`.
+///
+/// Binary files show `-` instead of line counts.
+pub fn write_numstat(files: &[FileStat], mut out: impl io::Write) -> io::Result<()> {
+    for file in files {
+        if file.binary.is_some() {
+            out.write_all(b"-\t-\t")?;
+        } else {
+            write!(out, "{}\t{}\t", file.insertions, file.removals)?;
+        }
+        out.write_all(&file.name)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Write the amount of changed files, and the total amount of added and removed lines, like `git diff --shortstat`.
+///
+/// Nothing is written if there are no `files`.
+pub fn write_shortstat(files: &[FileStat], mut out: impl io::Write) -> io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let (insertions, removals) = files.iter().filter(|f| f.binary.is_none()).fold((0, 0), |(i, r), f| {
+        (i + u64::from(f.insertions), r + u64::from(f.removals))
+    });
+    write!(
+        out,
+        " {} file{} changed",
+        files.len(),
+        if files.len() == 1 { "" } else { "s" }
+    )?;
+    if insertions != 0 || removals == 0 {
+        write!(
+            out,
+            ", {insertions} insertion{}(+)",
+            if insertions == 1 { "" } else { "s" }
+        )?;
+    }
+    if removals != 0 || insertions == 0 {
+        write!(out, ", {removals} deletion{}(-)", if removals == 1 { "" } else { "s" })?;
+    }
+    out.write_all(b"\n")
+}
+
+/// Write one line per file with its name, the amount of changed lines and a graph of `+` and `-` scaled to fit into
+/// the width given by `options`, followed by the [short summary][write_shortstat()], like `git diff --stat`.
+///
+/// Nothing is written if there are no `files`.
+pub fn write_stat(files: &[FileStat], options: Options, mut out: impl io::Write) -> io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let mut max_name_len = 0;
+    let mut max_change = 0;
+    let mut number_width = 0;
+    let mut bin_width = 0;
+    for file in files {
+        max_name_len = max_name_len.max(file.name.len());
+        match file.binary {
+            Some((old_size, new_size)) => {
+                // "Bin XXX -> YYY bytes"
+                bin_width = bin_width.max(14 + decimal_width(old_size) + decimal_width(new_size));
+                // Display change counts aligned with "Bin"
+                number_width = 3;
+            }
+            None => max_change = max_change.max(file.insertions as usize + file.removals as usize),
+        }
+    }
+
+    let number_width = number_width.max(decimal_width(max_change as u64));
+    let width = options.width.max(16 + 6 + number_width);
+    let mut graph_width = if max_change + 4 > bin_width {
+        max_change
+    } else {
+        bin_width - 4
+    };
+    if let Some(max) = options.graph_width.filter(|max| *max < graph_width) {
+        graph_width = max;
+    }
+    let mut name_width = options
+        .name_width
+        .filter(|max| *max < max_name_len)
+        .unwrap_or(max_name_len);
+
+    // Adjust widths to not exceed the total width.
+    if name_width + number_width + 6 + graph_width > width {
+        let max_graph_width = (width * 3 / 8).saturating_sub(number_width + 6);
+        if graph_width > max_graph_width {
+            graph_width = max_graph_width.max(6);
+        }
+        if let Some(max) = options.graph_width.filter(|max| graph_width > *max) {
+            graph_width = max;
+        }
+        let available = width.saturating_sub(number_width + 6 + graph_width);
+        if name_width > available {
+            name_width = available;
+        } else {
+            graph_width = width.saturating_sub(number_width + 6 + name_width);
+        }
+    }
+
+    for file in files {
+        let (prefix, name) = scale_name(file.name.as_ref(), name_width);
+        let padding = name_width.saturating_sub(prefix.len() + name.len());
+        write!(out, " {prefix}")?;
+        out.write_all(name)?;
+        write!(out, "{:padding$} | ", "")?;
+        if let Some((old_size, new_size)) = file.binary {
+            write!(out, "{:>number_width$}", "Bin")?;
+            if old_size != 0 || new_size != 0 {
+                write!(out, " {old_size} -> {new_size} bytes")?;
+            }
+            out.write_all(b"\n")?;
+            continue;
+        }
+        let (insertions, removals) = (file.insertions as usize, file.removals as usize);
+        let total = insertions + removals;
+        let (mut add, mut del) = (insertions, removals);
+        if graph_width <= max_change {
+            let mut scaled_total = scale_linear(total, graph_width, max_change);
+            if scaled_total < 2 && add != 0 && del != 0 {
+                scaled_total = 2;
+            }
+            if add < del {
+                add = scale_linear(add, graph_width, max_change);
+                del = scaled_total - add;
+            } else {
+                del = scale_linear(del, graph_width, max_change);
+                add = scaled_total - del;
+            }
+        }
+        write!(
+            out,
+            "{total:>number_width$}{}{}{}",
+            if total != 0 { " " } else { "" },
+            "+".repeat(add),
+            "-".repeat(del)
+        )?;
+        out.write_all(b"\n")?;
+    }
+    write_shortstat(files, out)
+}
+
+/// Shorten `name` to fit into `width` by cutting off its beginning, up to the next slash if possible, and return a
+/// prefix to indicate that.
+fn scale_name(name: &BStr, width: usize) -> (&'static str, &[u8]) {
+    if name.len() <= width {
+        return ("", name.as_bytes());
+    }
+    let len = width.saturating_sub(3);
+    let name = &name[name.len() - len..];
+    let name = match name.find_byte(b'/') {
+        Some(pos) => &name[pos..],
+        None => name,
+    };
+    ("...", name)
+}
+
+/// Scale `value` linearly to `width` with `max` being the largest value, but show at least one character for non-zero values.
+fn scale_linear(value: usize, width: usize, max: usize) -> usize {
+    if value == 0 {
+        return 0;
+    }
+    1 + value * (width.saturating_sub(1)) / max
+}
+
+fn decimal_width(mut value: u64) -> usize {
+    let mut width = 1;
+    while value >= 10 {
+        value /= 10;
+        width += 1;
+    }
+    width
+}
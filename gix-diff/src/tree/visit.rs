@@ -105,8 +105,8 @@ mod tests {
     fn size_of_change() {
         let actual = std::mem::size_of::<Change>();
         assert!(
-            actual <= 46,
-            "{actual} <= 46: this type shouldn't grow without us knowing"
+            actual <= 70,
+            "{actual} <= 70: this type shouldn't grow without us knowing"
         )
     }
 }
@@ -1,2 +1,254 @@
-#[test]
-fn currently_there_is_no_api_surface_to_test_as_it_is_reexporting_imara_diff() {}
+mod unified_diff {
+    use gix_diff::blob::{unified_diff, Algorithm};
+
+    const OLD: &str = "fn main() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n    let d = 4;\n    let e = 5;\n    let f = 6;\n    let g = 7;\n    let h = 8;\n    let i = 9;\n    let j = 10;\n}\n\nfn other() {\n    one();\n    two();\n    three();\n    four();\n}\n";
+
+    fn new() -> String {
+        OLD.replace("let b = 2;", "let b = 20;").replace("three()", "THREE()")
+    }
+
+    fn diff(old: &str, new: &str, options: unified_diff::Options) -> (String, unified_diff::Statistics) {
+        let mut out = Vec::new();
+        let stats = unified_diff::write_to(old.as_bytes(), new.as_bytes(), Algorithm::Myers, options, &mut out)
+            .expect("writing to memory works");
+        (String::from_utf8(out).expect("valid UTF-8"), stats)
+    }
+
+    #[test]
+    fn hunks_have_context_and_function_names() {
+        let (out, stats) = diff(OLD, &new(), Default::default());
+        assert_eq!(
+            out,
+            "@@ -1,6 +1,6 @@\n fn main() {\n     let a = 1;\n-    let b = 2;\n+    let b = 20;\n     let c = 3;\n     let d = 4;\n     let e = 5;\n@@ -14,6 +14,6 @@ fn main() {\n fn other() {\n     one();\n     two();\n-    three();\n+    THREE();\n     four();\n }\n"
+        );
+        assert_eq!(
+            stats,
+            unified_diff::Statistics {
+                hunks: 2,
+                insertions: 2,
+                removals: 2,
+                removed_bytes: 15 + 13
+            }
+        );
+
+        let (out, _) = diff(
+            OLD,
+            &new(),
+            unified_diff::Options {
+                context_lines: 1,
+                function_names: true,
+            },
+        );
+        assert_eq!(
+            out,
+            "@@ -2,3 +2,3 @@ fn main() {\n     let a = 1;\n-    let b = 2;\n+    let b = 20;\n     let c = 3;\n@@ -16,3 +16,3 @@ fn other() {\n     two();\n-    three();\n+    THREE();\n     four();\n"
+        );
+
+        let (out, _) = diff(
+            OLD,
+            &new(),
+            unified_diff::Options {
+                context_lines: 1,
+                function_names: false,
+            },
+        );
+        assert!(out.starts_with("@@ -2,3 +2,3 @@\n"));
+    }
+
+    #[test]
+    fn close_changes_are_merged_into_one_hunk() {
+        let (out, stats) = diff(
+            OLD,
+            &new(),
+            unified_diff::Options {
+                context_lines: 7,
+                function_names: true,
+            },
+        );
+        assert_eq!(stats.hunks, 1);
+        assert!(out.starts_with("@@ -1,19 +1,19 @@\n"), "{out}");
+    }
+
+    #[test]
+    fn missing_newlines_at_end_of_file_are_marked() {
+        let (out, stats) = diff("line\n", "line\nmore", Default::default());
+        assert_eq!(out, "@@ -1 +1,2 @@\n line\n+more\n\\ No newline at end of file\n");
+        assert_eq!(stats.insertions, 1);
+
+        let (out, _) = diff("a", "b", Default::default());
+        assert_eq!(
+            out,
+            "@@ -1 +1 @@\n-a\n\\ No newline at end of file\n+b\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn empty_ranges_refer_to_the_line_before() {
+        let (out, _) = diff("", "a b\n", Default::default());
+        assert_eq!(out, "@@ -0,0 +1 @@\n+a b\n");
+
+        let (out, _) = diff("a\nb\n", "", Default::default());
+        assert_eq!(out, "@@ -1,2 +0,0 @@\n-a\n-b\n");
+    }
+
+    #[test]
+    fn no_change_writes_nothing() {
+        let (out, stats) = diff(OLD, OLD, Default::default());
+        assert!(out.is_empty());
+        assert_eq!(stats, unified_diff::Statistics::default());
+    }
+}
+
+mod merge {
+    use gix_diff::blob::merge;
+
+    fn merged(base: &str, ours: &str, theirs: &str) -> (String, usize) {
+        merged_with(base, ours, theirs, Default::default())
+    }
+
+    fn merged_with(base: &str, ours: &str, theirs: &str, options: merge::Options) -> (String, usize) {
+        let out = merge::merge(
+            base.as_bytes(),
+            ours.as_bytes(),
+            theirs.as_bytes(),
+            &merge::Options {
+                ours_label: Some("ours".into()),
+                base_label: Some("base".into()),
+                theirs_label: Some("theirs".into()),
+                ..options
+            },
+        );
+        (String::from_utf8(out.data).expect("valid UTF-8"), out.conflicts)
+    }
+
+    #[test]
+    fn changes_to_different_regions_are_combined() {
+        assert_eq!(
+            merged("1\n2\n3\n4\n5\n", "one\n2\n3\n4\n5\n", "1\n2\n3\n4\nfive\n"),
+            ("one\n2\n3\n4\nfive\n".into(), 0)
+        );
+        assert_eq!(
+            merged("1\n2\n3\n", "1\n2\n3\n", "1\n3\n"),
+            ("1\n3\n".into(), 0),
+            "changes of one side are taken"
+        );
+        assert_eq!(
+            merged("1\n2\n3\n", "1\ntwo\n3\n", "1\ntwo\n3\n"),
+            ("1\ntwo\n3\n".into(), 0),
+            "identical changes aren't conflicting"
+        );
+    }
+
+    #[test]
+    fn changes_to_the_same_region_conflict() {
+        assert_eq!(
+            merged("1\n2\n3\n", "1\nours\n3\n", "1\ntheirs\n3\n"),
+            ("1\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n3\n".into(), 1)
+        );
+        assert_eq!(
+            merged("1\n2\n3\n", "1\nsame\nours\n3\n", "1\nsame\ntheirs\n3\n"),
+            (
+                "1\nsame\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n3\n".into(),
+                1
+            ),
+            "common lines are moved out of the conflict"
+        );
+        assert_eq!(
+            merged("1\n2", "1\nours", "1\ntheirs"),
+            ("1\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n".into(), 1),
+            "markers are on their own lines"
+        );
+    }
+
+    const BASE: &str = "1\n2\n3\n4\n5\n";
+    const OURS: &str = "1\nX\nsame\n3\n4\n5\n";
+    const THEIRS: &str = "1\nY\nsame\n3\n4\nfive\n";
+
+    #[test]
+    fn conflict_styles() {
+        let style = |conflict_style| merge::Options {
+            conflict_style,
+            ..Default::default()
+        };
+        assert_eq!(
+            merged_with(BASE, OURS, THEIRS, style(merge::ConflictStyle::Diff3)),
+            (
+                "1\n<<<<<<< ours\nX\nsame\n||||||| base\n2\n=======\nY\nsame\n>>>>>>> theirs\n3\n4\nfive\n".into(),
+                1
+            ),
+            "all lines of the conflicting region are shown, along with the base"
+        );
+        assert_eq!(
+            merged_with(BASE, OURS, THEIRS, style(merge::ConflictStyle::ZealousDiff3)),
+            (
+                "1\n<<<<<<< ours\nX\n||||||| base\n2\n=======\nY\n>>>>>>> theirs\nsame\n3\n4\nfive\n".into(),
+                1
+            ),
+            "common lines are moved out of the conflict, but the base is shown in full"
+        );
+        assert_eq!(
+            merged_with(
+                BASE,
+                OURS,
+                THEIRS,
+                merge::Options {
+                    marker_size: 3,
+                    ..Default::default()
+                }
+            ),
+            ("1\n<<< ours\nX\n===\nY\n>>> theirs\nsame\n3\n4\nfive\n".into(), 1)
+        );
+    }
+
+    #[test]
+    fn favored_sides_resolve_conflicts() {
+        for (favor, expected) in [
+            (merge::Favor::Ours, "1\nX\nsame\n3\n4\nfive\n"),
+            (merge::Favor::Theirs, "1\nY\nsame\n3\n4\nfive\n"),
+            (merge::Favor::Union, "1\nX\nY\nsame\n3\n4\nfive\n"),
+        ] {
+            let options = merge::Options {
+                favor: Some(favor),
+                ..Default::default()
+            };
+            assert_eq!(merged_with(BASE, OURS, THEIRS, options), (expected.into(), 0));
+        }
+    }
+
+    #[test]
+    fn drivers() {
+        let driver = |driver| merge::Options {
+            driver,
+            ..Default::default()
+        };
+        assert_eq!(
+            merged_with(BASE, OURS, THEIRS, driver(merge::Driver::Union)),
+            ("1\nX\nY\nsame\n3\n4\nfive\n".into(), 0)
+        );
+        assert_eq!(
+            merged_with(BASE, OURS, THEIRS, driver(merge::Driver::Binary)),
+            (OURS.into(), 1),
+            "binary merges keep our side and are always conflicting"
+        );
+        assert_eq!(
+            merged_with(
+                BASE,
+                OURS,
+                THEIRS,
+                merge::Options {
+                    favor: Some(merge::Favor::Theirs),
+                    ..driver(merge::Driver::Binary)
+                }
+            ),
+            (THEIRS.into(), 0),
+            "unless a side is favored"
+        );
+        assert_eq!(
+            merged("1\n", "1\0\n", "2\n"),
+            ("1\0\n".into(), 1),
+            "blobs that look binary are merged like binaries"
+        );
+        assert_eq!(merge::Driver::from_name("union"), Some(merge::Driver::Union));
+        assert_eq!(merge::Driver::from_name("custom"), None);
+    }
+}
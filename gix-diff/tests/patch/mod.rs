@@ -0,0 +1,487 @@
+use gix_diff::patch::{self, Change, File, FileStat};
+use gix_object::{bstr::ByteSlice, tree::EntryMode};
+
+use crate::hex_to_id;
+
+fn file<'a>(location: &'a str, id: &'a gix_hash::oid, data: &'a [u8]) -> File<'a> {
+    File {
+        location: location.into(),
+        id,
+        mode: EntryMode::Blob,
+        data,
+    }
+}
+
+fn write(change: Change<'_>) -> crate::Result<(String, FileStat)> {
+    let mut out = Vec::new();
+    let stat = patch::write_to(&change, None, &Default::default(), &mut out)?;
+    Ok((out.to_str()?.to_owned(), stat))
+}
+
+#[test]
+fn modification() -> crate::Result {
+    let (old_id, new_id) = (
+        hex_to_id("a999a0c211215fd28e77d6a7c66ade6ec76ccbcb"),
+        hex_to_id("1996e076f18889785558eefbced0739e627af217"),
+    );
+    let (out, stat) = write(Change::Modification {
+        old: file("file", &old_id, b"line\n"),
+        new: file("file", &new_id, b"line\nmore"),
+    })?;
+    assert_eq!(
+        out,
+        "diff --git a/file b/file\nindex a999a0c..1996e07 100644\n--- a/file\n+++ b/file\n@@ -1 +1,2 @@\n line\n+more\n\\ No newline at end of file\n"
+    );
+    assert_eq!(
+        stat,
+        FileStat {
+            name: "file".into(),
+            insertions: 1,
+            removals: 0,
+            binary: None
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn mode_change_without_content_change() -> crate::Result {
+    let id = hex_to_id("a999a0c211215fd28e77d6a7c66ade6ec76ccbcb");
+    let mut new = file("file", &id, b"line\n");
+    new.mode = EntryMode::BlobExecutable;
+    let (out, _) = write(Change::Modification {
+        old: file("file", &id, b"line\n"),
+        new,
+    })?;
+    assert_eq!(out, "diff --git a/file b/file\nold mode 100644\nnew mode 100755\n");
+    Ok(())
+}
+
+#[test]
+fn addition_with_spaces_in_path() -> crate::Result {
+    let id = hex_to_id("b2901ea97cfc0f297529eb23d489eab8cb71f9db");
+    let (out, stat) = write(Change::Addition {
+        new: file("sp ace", &id, b"a b\n"),
+    })?;
+    assert_eq!(
+        out,
+        "diff --git a/sp ace b/sp ace\nnew file mode 100644\nindex 0000000..b2901ea\n--- /dev/null\n+++ b/sp ace\t\n@@ -0,0 +1 @@\n+a b\n"
+    );
+    assert_eq!(stat.insertions, 1);
+    Ok(())
+}
+
+#[test]
+fn deletion_with_quoted_path() -> crate::Result {
+    let id = hex_to_id("587be6b4c3f93f93c489c0111bba5596147a26cb");
+    let (out, stat) = write(Change::Deletion {
+        old: file("tab\there", &id, b"x\n"),
+    })?;
+    assert_eq!(
+        out,
+        "diff --git \"a/tab\\there\" \"b/tab\\there\"\ndeleted file mode 100644\nindex 587be6b..0000000\n--- \"a/tab\\there\"\n+++ /dev/null\n@@ -1 +0,0 @@\n-x\n"
+    );
+    assert_eq!(stat.name, "\"tab\\there\"");
+    Ok(())
+}
+
+#[test]
+fn binary_files_are_not_shown() -> crate::Result {
+    let (old_id, new_id) = (
+        hex_to_id("f77ba9c99d07cbbc00cb659bc47f74692bd81476"),
+        hex_to_id("d3596cfa2c6a0b6d0a15fc962376d52e4abbf01d"),
+    );
+    let (out, stat) = write(Change::Modification {
+        old: file("bin", &old_id, b"b\0in"),
+        new: file("bin", &new_id, b"b\0in2"),
+    })?;
+    assert_eq!(
+        out,
+        "diff --git a/bin b/bin\nindex f77ba9c..d3596cf 100644\nBinary files a/bin and b/bin differ\n"
+    );
+    assert_eq!(stat.binary, Some((4, 5)));
+
+    let mut out = Vec::new();
+    let stat = patch::write_to(
+        &Change::Modification {
+            old: file("text", &old_id, b"a\n"),
+            new: file("text", &new_id, b"b\n"),
+        },
+        Some(true),
+        &Default::default(),
+        &mut out,
+    )?;
+    assert!(
+        out.ends_with(b"Binary files a/text and b/text differ\n"),
+        "attributes win"
+    );
+    assert_eq!(stat.binary, Some((2, 2)));
+    Ok(())
+}
+
+#[test]
+fn rename_with_similarity() -> crate::Result {
+    let (old_id, new_id) = (
+        hex_to_id("a999a0c211215fd28e77d6a7c66ade6ec76ccbcb"),
+        hex_to_id("1996e076f18889785558eefbced0739e627af217"),
+    );
+    let (out, stat) = write(Change::Rewrite {
+        old: file("nonl", &old_id, b"line\n"),
+        new: file("renamed", &new_id, b"line\nmore"),
+        copy: false,
+    })?;
+    assert_eq!(
+        out,
+        "diff --git a/nonl b/renamed\nsimilarity index 55%\nrename from nonl\nrename to renamed\nindex a999a0c..1996e07 100644\n--- a/nonl\n+++ b/renamed\n@@ -1 +1,2 @@\n line\n+more\n\\ No newline at end of file\n"
+    );
+    assert_eq!(stat.name, "nonl => renamed");
+
+    let (out, stat) = write(Change::Rewrite {
+        old: file("dir/a/file", &old_id, b"line\n"),
+        new: file("dir/b/file", &old_id, b"line\n"),
+        copy: true,
+    })?;
+    assert_eq!(
+        out,
+        "diff --git a/dir/a/file b/dir/b/file\nsimilarity index 100%\ncopy from dir/a/file\ncopy to dir/b/file\n"
+    );
+    assert_eq!(stat.name, "dir/{a => b}/file");
+    Ok(())
+}
+
+#[test]
+fn rename_similarity_is_estimated_like_git() -> crate::Result {
+    let (old_id, new_id) = (
+        hex_to_id("f74b347950df6f234c86bd1d8901f84fa0ffaba1"),
+        hex_to_id("01f941b51b7b0d1fa04afa400476a54b252b2549"),
+    );
+    let (out, _stat) = write(Change::Rewrite {
+        old: file("f", &old_id, b"a\nb\nc\nsome longer line here\n"),
+        new: file("g", &new_id, b"c\r\nb\na\nsome longer line here\nd\n"),
+        copy: false,
+    })?;
+    assert!(
+        out.starts_with("diff --git a/f b/g\nsimilarity index 90%\n"),
+        "reordered lines are still similar, and carriage returns are ignored in text files, just like git does"
+    );
+    assert_eq!(
+        gix_diff::patch::similarity(b"a\r\n", b"a\n", true),
+        0,
+        "binary files have no line endings"
+    );
+    Ok(())
+}
+
+mod quote {
+    use gix_diff::patch::quote;
+
+    #[test]
+    fn path() {
+        assert_eq!(quote::path("plain/path".into()).as_ref(), "plain/path");
+        assert_eq!(quote::path("tab\there".into()).as_ref(), "\"tab\\there\"");
+        assert_eq!(quote::path("quo\"te\\".into()).as_ref(), "\"quo\\\"te\\\\\"");
+        assert_eq!(quote::path("ä".into()).as_ref(), "\"\\303\\244\"");
+    }
+
+    #[test]
+    fn with_prefix() {
+        assert_eq!(quote::with_prefix("a/".into(), "file".into()), "a/file");
+        assert_eq!(quote::with_prefix("a/".into(), "\n".into()), "\"a/\\n\"");
+    }
+
+    #[test]
+    fn rename() {
+        assert_eq!(quote::rename("a".into(), "b".into()), "a => b");
+        assert_eq!(quote::rename("dir/a".into(), "dir/b".into()), "dir/{a => b}");
+        assert_eq!(quote::rename("a/file".into(), "b/file".into()), "{a => b}/file");
+        assert_eq!(quote::rename("a/c".into(), "a/b/c".into()), "a/{ => b}/c");
+        assert_eq!(quote::rename("a/b/c".into(), "a/c".into()), "a/{b => }/c");
+        assert_eq!(quote::rename("a\t".into(), "b".into()), "\"a\\t\" => b");
+    }
+}
+
+mod stat {
+    use gix_diff::patch::{stat, FileStat};
+
+    fn files() -> Vec<FileStat> {
+        vec![
+            FileStat {
+                name: "a.rs".into(),
+                insertions: 2,
+                removals: 2,
+                binary: None,
+            },
+            FileStat {
+                name: "bin".into(),
+                insertions: 0,
+                removals: 0,
+                binary: Some((4, 5)),
+            },
+            FileStat {
+                name: "nonl => renamed".into(),
+                insertions: 1,
+                removals: 0,
+                binary: None,
+            },
+        ]
+    }
+
+    fn render(f: impl FnOnce(&mut Vec<u8>) -> std::io::Result<()>) -> String {
+        let mut out = Vec::new();
+        f(&mut out).expect("writing to memory works");
+        String::from_utf8(out).expect("valid UTF-8")
+    }
+
+    #[test]
+    fn numstat() {
+        assert_eq!(
+            render(|out| stat::write_numstat(&files(), out)),
+            "2\t2\ta.rs\n-\t-\tbin\n1\t0\tnonl => renamed\n"
+        );
+    }
+
+    #[test]
+    fn shortstat() {
+        assert_eq!(
+            render(|out| stat::write_shortstat(&files(), out)),
+            " 3 files changed, 3 insertions(+), 2 deletions(-)\n"
+        );
+        assert_eq!(
+            render(|out| stat::write_shortstat(&files()[2..], out)),
+            " 1 file changed, 1 insertion(+)\n"
+        );
+        assert_eq!(
+            render(|out| stat::write_shortstat(&files()[1..2], out)),
+            " 1 file changed, 0 insertions(+), 0 deletions(-)\n",
+            "binary files don't count towards the changed lines"
+        );
+        assert_eq!(render(|out| stat::write_shortstat(&[], out)), "");
+    }
+
+    #[test]
+    fn stat() {
+        assert_eq!(
+            render(|out| stat::write_stat(&files(), Default::default(), out)),
+            " a.rs            |   4 ++--\n bin             | Bin 4 -> 5 bytes\n nonl => renamed |   1 +\n 3 files changed, 3 insertions(+), 2 deletions(-)\n"
+        );
+    }
+
+    #[test]
+    fn stat_scales_graph_and_names_to_fit() {
+        let files = vec![
+            FileStat {
+                name: "very/long/directory/name/that/does/not/fit/into/the/available/width/file.rs".into(),
+                insertions: 1000,
+                removals: 500,
+                binary: None,
+            },
+            FileStat {
+                name: "short".into(),
+                insertions: 0,
+                removals: 1,
+                binary: None,
+            },
+        ];
+        assert_eq!(
+            render(|out| stat::write_stat(&files, Default::default(), out)),
+            " .../does/not/fit/into/the/available/width/file.rs  | 1500 +++++++++++++-------\n short                                              |    1 -\n 2 files changed, 1000 insertions(+), 501 deletions(-)\n"
+        );
+    }
+}
+
+mod parse {
+    use gix_diff::patch::{
+        self,
+        parse::{Binary, BinaryKind, Line, Operation},
+    };
+    use gix_object::tree::EntryMode;
+
+    #[test]
+    fn format_patch_with_all_kinds_of_changes() -> crate::Result {
+        let input = gix_testtools::fixture_bytes_standalone("patches/format-patch.patch");
+        let files = patch::parse(&input, Default::default())?;
+        assert_eq!(
+            files.len(),
+            5,
+            "mail headers, the summary and the signature are skipped"
+        );
+
+        let added = &files[0];
+        assert_eq!(added.operation, Operation::Addition);
+        assert_eq!(added.old_path, None);
+        assert_eq!(added.new_path.as_ref().expect("set"), "added");
+        assert_eq!(added.new_mode, Some(EntryMode::Blob));
+        assert_eq!(added.hunks.len(), 1);
+        assert_eq!(added.hunks[0].lines, vec![Line::Addition("new\n".into())]);
+
+        let binary = &files[1];
+        assert_eq!(binary.operation, Operation::Modification);
+        assert_eq!(
+            binary.old_id.as_ref().expect("set"),
+            "20b5be91886d0b6f26dc98a225c0dac05fe2c86e"
+        );
+        match binary.binary.as_ref().expect("binary") {
+            Binary::Data { forward, reverse } => {
+                assert_eq!(forward.kind, BinaryKind::Literal);
+                assert_eq!(forward.size, 4);
+                assert_eq!(forward.data.len(), 12, "data is still deflated");
+                assert_eq!(reverse.as_ref().expect("present").size, 3);
+            }
+            Binary::Differ => unreachable!("there is data"),
+        }
+
+        let modified = &files[2];
+        assert_eq!(modified.operation, Operation::Modification);
+        assert_eq!(modified.old_path, modified.new_path);
+        assert_eq!(modified.old_mode, Some(EntryMode::Blob), "taken from the index line");
+        let hunk = &modified.hunks[0];
+        assert_eq!(
+            (hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len),
+            (2, 7, 2, 7)
+        );
+        assert_eq!(hunk.context_len(), (3, 3));
+        assert_eq!(
+            hunk.old_lines().collect::<Vec<_>>(),
+            ["2\n", "3\n", "4\n", "5\n", "6\n", "7\n", "8\n"]
+        );
+
+        let renamed = &files[3];
+        assert_eq!(renamed.operation, Operation::Rename);
+        assert_eq!(renamed.old_path.as_ref().expect("set"), "old");
+        assert_eq!(renamed.new_path.as_ref().expect("set"), "new");
+        assert_eq!(renamed.old_id.as_ref().expect("set"), "8a1218a");
+
+        let mode_change = &files[4];
+        assert_eq!(mode_change.new_path.as_ref().expect("set"), "sp ace");
+        assert_eq!(mode_change.old_mode, Some(EntryMode::Blob));
+        assert_eq!(mode_change.new_mode, Some(EntryMode::BlobExecutable));
+        assert!(mode_change.hunks.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn traditional_unified_diff_and_missing_newlines() -> crate::Result {
+        let input = b"Some text before\n--- a/dir/file\t2023-01-01 00:00:00\n+++ b/dir/file\t2023-01-02 00:00:00\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+c\n\\ No newline at end of file\n";
+        let files = patch::parse(input, Default::default())?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].operation, Operation::Modification);
+        assert_eq!(files[0].new_path.as_ref().expect("set"), "dir/file");
+        assert_eq!(
+            files[0].hunks[0].lines,
+            vec![
+                Line::Context("a\n".into()),
+                Line::Removal("b".into()),
+                Line::Addition("c".into())
+            ]
+        );
+
+        let files = patch::parse(input, patch::parse::Options { strip: 0 })?;
+        assert_eq!(files[0].new_path.as_ref().expect("set"), "b/dir/file");
+        Ok(())
+    }
+
+    #[test]
+    fn quoted_paths_and_deletions() -> crate::Result {
+        let input = b"diff --git \"a/tab\\there\" \"b/tab\\there\"\ndeleted file mode 100644\nindex 587be6b..0000000\n--- \"a/tab\\there\"\n+++ /dev/null\n@@ -1 +0,0 @@\n-x\n";
+        let files = patch::parse(input, Default::default())?;
+        assert_eq!(files[0].operation, Operation::Deletion);
+        assert_eq!(files[0].old_path.as_ref().expect("set"), "tab\there");
+        assert_eq!(files[0].new_path, None);
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_hunks_are_an_error() {
+        let input = b"--- a/file\n+++ b/file\n@@ -1,2 +1,2 @@\n a\n";
+        assert!(matches!(
+            patch::parse(input, Default::default()),
+            Err(patch::parse::Error::TruncatedHunk { expected: 1, .. })
+        ));
+    }
+}
+
+mod apply {
+    use gix_diff::patch;
+
+    fn hunks(patch: &str) -> Vec<patch::parse::Hunk> {
+        patch::parse(patch.as_bytes(), Default::default())
+            .expect("valid")
+            .remove(0)
+            .hunks
+    }
+
+    fn numbers(range: std::ops::RangeInclusive<u32>) -> String {
+        range.map(|n| format!("{n}\n")).collect()
+    }
+
+    #[test]
+    fn hunks_apply_at_their_position_or_with_offset() -> crate::Result {
+        let hunks = hunks("--- a/f\n+++ b/f\n@@ -2,3 +2,3 @@\n 2\n-3\n+three\n 4\n@@ -8,3 +8,4 @@\n 8\n 9\n 10\n+11\n");
+        let expected = numbers(1..=10).replace("3\n", "three\n") + "11\n";
+        assert_eq!(
+            patch::apply(numbers(1..=10).as_bytes(), &hunks, Default::default())?,
+            expected.as_bytes()
+        );
+
+        let shifted = format!("0\n{}", numbers(1..=10));
+        assert_eq!(
+            patch::apply(shifted.as_bytes(), &hunks, Default::default())?,
+            format!("0\n{expected}").as_bytes(),
+            "lines may have been added before"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mismatching_context_needs_fuzz() -> crate::Result {
+        let hunks = hunks("--- a/f\n+++ b/f\n@@ -2,3 +2,3 @@\n 2\n-3\n+three\n 4\n");
+        let old = numbers(1..=5).replace("4\n", "four\n");
+        assert!(matches!(
+            patch::apply(old.as_bytes(), &hunks, Default::default()),
+            Err(patch::apply::Error::HunkMismatch { hunk: 1, line: 2 })
+        ));
+        assert_eq!(
+            patch::apply(
+                old.as_bytes(),
+                &hunks,
+                patch::apply::Options {
+                    fuzz: 1,
+                    ..Default::default()
+                }
+            )?,
+            b"1\n2\nthree\nfour\n5\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn whitespace_can_be_ignored() -> crate::Result {
+        let hunks = hunks("--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n a  b\n-c\n+d\n");
+        let old = b"a b \nc\n";
+        assert!(patch::apply(old, &hunks, Default::default()).is_err());
+        assert_eq!(
+            patch::apply(
+                old,
+                &hunks,
+                patch::apply::Options {
+                    ignore_whitespace: true,
+                    ..Default::default()
+                }
+            )?,
+            b"a b \nd\n",
+            "context lines keep their whitespace"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn additions_to_empty_files_and_missing_newlines() -> crate::Result {
+        let hunks = hunks("--- /dev/null\n+++ b/f\n@@ -0,0 +1,2 @@\n+a\n+b\n\\ No newline at end of file\n");
+        assert_eq!(patch::apply(b"", &hunks, Default::default())?, b"a\nb");
+        assert!(
+            patch::apply(b"x\n", &hunks, Default::default()).is_err(),
+            "a hunk without context at the start of the file must match at the start and the end"
+        );
+        Ok(())
+    }
+}
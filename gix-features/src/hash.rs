@@ -3,6 +3,9 @@
 //! With the `fast-sha1` feature, the `Sha1` hash type will use a more elaborate implementation utilizing hardware support
 //! in case it is available. Otherwise the `rustsha1` feature should be set. `fast-sha1` will take precedence.
 //! Otherwise, a minimal yet performant implementation is used instead for a decent trade-off between compile times and run-time performance.
+//!
+//! The `Sha256` hash type is always provided by the `sha2` crate once one of the SHA1 features is enabled, and [`Hasher`]
+//! dispatches to either of them depending on the [kind of hash][gix_hash::Kind] to produce.
 #[cfg(all(feature = "rustsha1", not(feature = "fast-sha1")))]
 mod _impl {
     use super::Sha1Digest;
@@ -52,6 +55,64 @@ mod _impl {
 #[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
 pub use _impl::Sha1;
 
+/// A 32 bytes digest produced by a [`Sha256`] hash implementation.
+#[cfg(any(feature = "fast-sha1", feature = "rustsha1"))]
+pub type Sha256Digest = [u8; 32];
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+mod sha256 {
+    use sha2::Digest;
+
+    use super::Sha256Digest;
+
+    /// A implementation of the Sha256 hash, which can be used once.
+    #[derive(Default, Clone)]
+    pub struct Sha256(sha2::Sha256);
+
+    impl Sha256 {
+        /// Digest the given `bytes`.
+        pub fn update(&mut self, bytes: &[u8]) {
+            self.0.update(bytes)
+        }
+        /// Finalize the hash and produce a digest.
+        pub fn digest(self) -> Sha256Digest {
+            self.0.finalize().into()
+        }
+    }
+}
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+pub use sha256::Sha256;
+
+/// A hash implementation for any of the [kinds of hashes][gix_hash::Kind] we support, which can be used once.
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+#[derive(Clone)]
+pub enum Hasher {
+    /// A Sha1 hash.
+    Sha1(Sha1),
+    /// A Sha256 hash.
+    Sha256(Sha256),
+}
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+impl Hasher {
+    /// Digest the given `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha1(hash) => hash.update(bytes),
+            Hasher::Sha256(hash) => hash.update(bytes),
+        }
+    }
+
+    /// Finalize the hash and produce the id of the hashed bytes.
+    pub fn digest(self) -> gix_hash::ObjectId {
+        match self {
+            Hasher::Sha1(hash) => hash.digest().into(),
+            Hasher::Sha256(hash) => hash.digest().into(),
+        }
+    }
+}
+
 /// Compute a CRC32 hash from the given `bytes`, returning the CRC32 hash.
 ///
 /// When calling this function for the first time, `previous_value` should be `0`. Otherwise it
@@ -76,9 +137,10 @@ pub fn crc32(bytes: &[u8]) -> u32 {
 
 /// Produce a hasher suitable for the given kind of hash.
 #[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
-pub fn hasher(kind: gix_hash::Kind) -> Sha1 {
+pub fn hasher(kind: gix_hash::Kind) -> Hasher {
     match kind {
-        gix_hash::Kind::Sha1 => Sha1::default(),
+        gix_hash::Kind::Sha1 => Hasher::Sha1(Sha1::default()),
+        gix_hash::Kind::Sha256 => Hasher::Sha256(Sha256::default()),
     }
 }
 
@@ -139,19 +201,19 @@ pub fn bytes(
         }
     }
 
-    let id = gix_hash::ObjectId::from(hasher.digest());
+    let id = hasher.digest();
     progress.show_throughput(start);
     Ok(id)
 }
 
 #[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
 mod write {
-    use crate::hash::Sha1;
+    use crate::hash::Hasher;
 
     /// A utility to automatically generate a hash while writing into an inner writer.
     pub struct Write<T> {
         /// The hash implementation.
-        pub hash: Sha1,
+        pub hash: Hasher,
         /// The inner writer.
         pub inner: T,
     }
@@ -177,11 +239,9 @@ mod write {
     {
         /// Create a new hash writer which hashes all bytes written to `inner` with a hash of `kind`.
         pub fn new(inner: T, object_hash: gix_hash::Kind) -> Self {
-            match object_hash {
-                gix_hash::Kind::Sha1 => Write {
-                    inner,
-                    hash: Sha1::default(),
-                },
+            Write {
+                inner,
+                hash: crate::hash::hasher(object_hash),
             }
         }
     }
@@ -11,3 +11,59 @@ fn size_of_sha1() {
 fn size_of_sha1() {
     assert_eq!(std::mem::size_of::<Sha1>(), 104)
 }
+
+mod sha256 {
+    use gix_features::hash::{hasher, Sha256};
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn known_digests() {
+        for (input, expected) in [
+            (
+                &b""[..],
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                b"abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ] {
+            let mut hash = Sha256::default();
+            hash.update(input);
+            assert_eq!(hex(&hash.digest()), expected);
+        }
+    }
+
+    #[test]
+    fn incremental_updates_match_a_single_update() {
+        let input: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let mut expected = Sha256::default();
+        expected.update(&input);
+        let expected = expected.digest();
+        for chunk_size in [1, 7, 55, 56, 63, 64, 65, 128, 999] {
+            let mut hash = Sha256::default();
+            for chunk in input.chunks(chunk_size) {
+                hash.update(chunk);
+            }
+            assert_eq!(hash.digest(), expected, "chunk size {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn hasher_produces_object_ids_of_the_requested_kind() {
+        let mut hash = hasher(gix_hash::Kind::Sha256);
+        hash.update(b"blob 0\0");
+        assert_eq!(hash.digest(), gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha256));
+
+        let mut hash = hasher(gix_hash::Kind::Sha1);
+        hash.update(b"blob 0\0");
+        assert_eq!(hash.digest(), gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha1));
+    }
+}
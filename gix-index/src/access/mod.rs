@@ -20,6 +20,13 @@ impl State {
         self.object_hash
     }
 
+    /// Return the time at which this state was created, which is the modification time of the index file it was read from.
+    ///
+    /// Entries whose files were modified at or after this time may have changed even though their stat information matches.
+    pub fn timestamp(&self) -> filetime::FileTime {
+        self.timestamp
+    }
+
     /// Return our entries
     pub fn entries(&self) -> &[Entry] {
         &self.entries
@@ -40,6 +47,9 @@ impl State {
         })
     }
     /// Return mutable entries along with their path, as obtained from `backing`.
+    ///
+    /// Note that the tree extension isn't invalidated, so this is only suitable for changes that don't affect trees,
+    /// like updating the `stat` information of entries.
     pub fn entries_mut_with_paths_in<'state, 'backing>(
         &'state mut self,
         backing: &'backing PathStorage,
@@ -114,11 +124,17 @@ impl State {
     }
 
     /// Return mutable entries in a slice.
+    ///
+    /// As any entry may change, all trees are [invalidated][State::invalidate_tree()].
     pub fn entries_mut(&mut self) -> &mut [Entry] {
+        self.invalidate_tree();
         &mut self.entries
     }
     /// Return mutable entries along with their paths in an iterator.
+    ///
+    /// As any entry may change, all trees are [invalidated][State::invalidate_tree()].
     pub fn entries_mut_with_paths(&mut self) -> impl Iterator<Item = (&mut Entry, &BStr)> {
+        self.invalidate_tree();
         let paths = &self.path_backing;
         self.entries.iter_mut().map(move |e| {
             let path = paths[e.path.clone()].as_bstr();
@@ -139,9 +155,12 @@ impl State {
 
     /// Like [`entry_index_by_path_and_stage()`][State::entry_index_by_path_and_stage()],
     /// but returns the mutable entry instead of the index.
+    ///
+    /// As the entry is expected to change, all trees containing it are [invalidated][State::invalidate_tree_for_path()].
     pub fn entry_mut_by_path_and_stage(&mut self, path: &BStr, stage: entry::Stage) -> Option<&mut Entry> {
-        self.entry_index_by_path_and_stage(path, stage)
-            .map(|idx| &mut self.entries[idx])
+        let idx = self.entry_index_by_path_and_stage(path, stage)?;
+        self.invalidate_tree_for_path(path);
+        Some(&mut self.entries[idx])
     }
 
     /// Push a new entry containing `stat`, `id`, `flags` and `mode` and `path` to the end of our storage, without performing
@@ -154,6 +173,8 @@ impl State {
     ///
     /// Alternatively, make sure to call [sort_entries()][State::sort_entries()] before entry lookup by path to restore
     /// the invariant.
+    ///
+    /// All trees that would contain the new entry are [invalidated][State::invalidate_tree_for_path()].
     pub fn dangerously_push_entry(
         &mut self,
         stat: entry::Stat,
@@ -162,6 +183,7 @@ impl State {
         mode: entry::Mode,
         path: &BStr,
     ) {
+        self.invalidate_tree_for_path(path);
         let path = {
             let path_start = self.path_backing.len();
             self.path_backing.push_str(path);
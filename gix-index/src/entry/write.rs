@@ -5,6 +5,37 @@ use crate::{entry, Entry, State};
 impl Entry {
     /// Serialize ourselves to `out` with path access via `state`, without padding.
     pub fn write_to(&self, mut out: impl std::io::Write, state: &State) -> std::io::Result<()> {
+        let path = self.path(state);
+        self.write_stat_and_flags(&mut out, path)?;
+        out.write_all(path)?;
+        out.write_all(b"\0")
+    }
+
+    /// Serialize ourselves to `out` with path access via `state` like [`write_to()`][Entry::write_to()], but with the path
+    /// compressed against `previous_path`, the path of the entry written before us, as done by index V4.
+    ///
+    /// The first entry of an index is written with an empty `previous_path`.
+    pub fn write_to_with_prefix_compression(
+        &self,
+        mut out: impl std::io::Write,
+        state: &State,
+        previous_path: &[u8],
+    ) -> std::io::Result<()> {
+        let path = self.path(state);
+        self.write_stat_and_flags(&mut out, path)?;
+
+        let common_prefix_len = previous_path
+            .iter()
+            .zip(path.iter())
+            .take_while(|(previous, current)| previous == current)
+            .count();
+        let mut buf = [0u8; 10];
+        out.write_all(var_int((previous_path.len() - common_prefix_len) as u64, &mut buf))?;
+        out.write_all(&path[common_prefix_len..])?;
+        out.write_all(b"\0")
+    }
+
+    fn write_stat_and_flags(&self, mut out: impl std::io::Write, path: &[u8]) -> std::io::Result<()> {
         let stat = self.stat;
         out.write_all(&stat.ctime.secs.to_be_bytes())?;
         out.write_all(&stat.ctime.nsecs.to_be_bytes())?;
@@ -17,7 +48,6 @@ impl Entry {
         out.write_all(&stat.gid.to_be_bytes())?;
         out.write_all(&stat.size.to_be_bytes())?;
         out.write_all(self.id.as_bytes())?;
-        let path = self.path(state);
         let path_len: u16 = if path.len() >= entry::Flags::PATH_LEN.bits() as usize {
             entry::Flags::PATH_LEN.bits() as u16
         } else {
@@ -33,7 +63,22 @@ impl Entry {
                     .to_be_bytes(),
             )?;
         }
-        out.write_all(path)?;
-        out.write_all(b"\0")
+        Ok(())
+    }
+}
+
+/// Encode `n` as variable-length integer in the way git does it, using `buf` as storage for the returned bytes.
+fn var_int(mut n: u64, buf: &mut [u8; 10]) -> &[u8] {
+    let mut bytes_written = 1;
+    buf[buf.len() - 1] = n as u8 & 0b0111_1111;
+    for out in buf.iter_mut().rev().skip(1) {
+        n >>= 7;
+        if n == 0 {
+            break;
+        }
+        n -= 1;
+        *out = 0b1000_0000 | (n as u8 & 0b0111_1111);
+        bytes_written += 1;
     }
+    &buf[buf.len() - bytes_written..]
 }
@@ -1,3 +1,5 @@
+use std::convert::TryFrom;
+
 use crate::{
     extension::{Link, Signature},
     util::split_at_pos,
@@ -38,6 +40,14 @@ pub mod decode {
     }
 }
 
+/// Return the path to the shared index with `checksum` as referred to by the split index at `split_index_path`.
+pub fn shared_index_path(split_index_path: &std::path::Path, checksum: &gix_hash::oid) -> std::path::PathBuf {
+    split_index_path
+        .parent()
+        .expect("split index file in .git folder")
+        .join(format!("sharedindex.{checksum}"))
+}
+
 pub(crate) fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Result<Link, decode::Error> {
     let (id, data) = split_at_pos(data, object_hash.len_in_bytes())
         .ok_or(decode::Error::Corrupt(
@@ -68,17 +78,33 @@ pub(crate) fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Result<Link, d
 }
 
 impl Link {
+    /// Serialize this instance to `out`.
+    ///
+    /// Note that the bitmaps are only written if present, which is the case if the owning state contains
+    /// the entries of a split index in its on-disk form.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> Result<(), std::io::Error> {
+        let mut data = Vec::with_capacity(self.shared_index_checksum.as_bytes().len());
+        data.extend_from_slice(self.shared_index_checksum.as_bytes());
+        if let Some(bitmaps) = &self.bitmaps {
+            bitmaps.delete.write_to(&mut data)?;
+            bitmaps.replace.write_to(&mut data)?;
+        }
+
+        out.write_all(&SIGNATURE)?;
+        out.write_all(&(u32::try_from(data.len()).expect("less than 4GB link extension")).to_be_bytes())?;
+        out.write_all(&data)?;
+        Ok(())
+    }
+
+    /// Merge the entries of the shared index into the ones of `split_index` and remember the shared index
+    /// for writing the split index again.
     pub(crate) fn dissolve_into(
         self,
         split_index: &mut crate::File,
         object_hash: gix_hash::Kind,
         options: crate::decode::Options,
     ) -> Result<(), crate::file::init::Error> {
-        let shared_index_path = split_index
-            .path
-            .parent()
-            .expect("split index file in .git folder")
-            .join(format!("sharedindex.{}", self.shared_index_checksum));
+        let shared_index_path = shared_index_path(&split_index.path, &self.shared_index_checksum);
         let mut shared_index = crate::File::at(
             &shared_index_path,
             object_hash,
@@ -88,90 +114,106 @@ impl Link {
             },
         )?;
 
-        if let Some(bitmaps) = self.bitmaps {
-            let mut split_entry_index = 0;
-
-            let mut err = None;
-            bitmaps.replace.for_each_set_bit(|replace_index| {
-                let shared_entry = match shared_index.entries.get_mut(replace_index) {
-                    Some(e) => e,
-                    None => {
-                        err = decode::Error::Corrupt("replace bitmap length exceeds shared index length - more entries in bitmap than found in shared index").into();
-                        return None
-                    }
-                };
-
-                if shared_entry.flags.contains(crate::entry::Flags::REMOVE) {
-                    err = decode::Error::Corrupt("entry is marked as both replace and delete").into();
+        let bitmaps = self.bitmaps.unwrap_or_else(|| Bitmaps {
+            delete: gix_bitmap::ewah::Vec::from_sorted_indices(None),
+            replace: gix_bitmap::ewah::Vec::from_sorted_indices(None),
+        });
+        let mut split_entry_index = 0;
+
+        let mut err = None;
+        bitmaps.replace.for_each_set_bit(|replace_index| {
+            let shared_entry = match shared_index.entries.get_mut(replace_index) {
+                Some(e) => e,
+                None => {
+                    err = decode::Error::Corrupt("replace bitmap length exceeds shared index length - more entries in bitmap than found in shared index").into();
                     return None
                 }
+            };
 
-                let split_entry = match split_index.entries.get(split_entry_index) {
-                    Some(e) => e,
-                    None => {
-                        err = decode::Error::Corrupt("replace bitmap length exceeds split index length - more entries in bitmap than found in split index").into();
-                        return None
-                    }
-                };
-                if !split_entry.path.is_empty() {
-                    err = decode::Error::Corrupt("paths in split index entries that are for replacement should be empty").into();
-                    return None
-                }
-                if shared_entry.path.is_empty() {
-                    err = decode::Error::Corrupt("paths in shared index entries that are replaced should not be empty").into();
+            if shared_entry.flags.contains(crate::entry::Flags::REMOVE) {
+                err = decode::Error::Corrupt("entry is marked as both replace and delete").into();
+                return None
+            }
+
+            let split_entry = match split_index.entries.get(split_entry_index) {
+                Some(e) => e,
+                None => {
+                    err = decode::Error::Corrupt("replace bitmap length exceeds split index length - more entries in bitmap than found in split index").into();
                     return None
                 }
-                shared_entry.stat = split_entry.stat;
-                shared_entry.id = split_entry.id;
-                shared_entry.flags = split_entry.flags;
-                shared_entry.mode = split_entry.mode;
-
-                split_entry_index += 1;
-                Some(())
-            });
-            if let Some(err) = err {
-                return Err(err.into());
+            };
+            if !split_entry.path.is_empty() {
+                err = decode::Error::Corrupt("paths in split index entries that are for replacement should be empty").into();
+                return None
             }
+            if shared_entry.path.is_empty() {
+                err = decode::Error::Corrupt("paths in shared index entries that are replaced should not be empty").into();
+                return None
+            }
+            shared_entry.stat = split_entry.stat;
+            shared_entry.id = split_entry.id;
+            shared_entry.flags = split_entry.flags;
+            shared_entry.mode = split_entry.mode;
 
-            let split_index_path_backing = std::mem::take(&mut split_index.path_backing);
-            for mut split_entry in split_index.entries.drain(split_entry_index..) {
-                let start = shared_index.path_backing.len();
-                let split_index_path = split_entry.path.clone();
-
-                split_entry.path = start..start + split_entry.path.len();
-                shared_index.entries.push(split_entry);
+            split_entry_index += 1;
+            Some(())
+        });
+        if let Some(err) = err {
+            return Err(err.into());
+        }
 
-                shared_index
-                    .path_backing
-                    .extend_from_slice(&split_index_path_backing[split_index_path]);
-            }
+        let split_index_path_backing = std::mem::take(&mut split_index.path_backing);
+        for mut split_entry in split_index.entries.drain(split_entry_index..) {
+            let start = shared_index.path_backing.len();
+            let split_index_path = split_entry.path.clone();
 
-            bitmaps.delete.for_each_set_bit(|delete_index| {
-                let shared_entry = match shared_index.entries.get_mut(delete_index) {
-                    Some(e) => e,
-                    None => {
-                        err = decode::Error::Corrupt("delete bitmap length exceeds shared index length - more entries in bitmap than found in shared index").into();
-                        return None
-                    }
-                };
-                shared_entry.flags.insert(crate::entry::Flags::REMOVE);
-                Some(())
-            });
-            if let Some(err) = err {
-                return Err(err.into());
-            }
+            split_entry.path = start..start + split_entry.path.len();
+            shared_index.entries.push(split_entry);
 
             shared_index
-                .entries
-                .retain(|e| !e.flags.contains(crate::entry::Flags::REMOVE));
-
-            let mut shared_entries = std::mem::take(&mut shared_index.entries);
-            shared_entries.sort_by(|a, b| a.cmp(b, &shared_index.state));
+                .path_backing
+                .extend_from_slice(&split_index_path_backing[split_index_path]);
+        }
 
-            split_index.entries = shared_entries;
-            split_index.path_backing = std::mem::take(&mut shared_index.path_backing);
+        bitmaps.delete.for_each_set_bit(|delete_index| {
+            let shared_entry = match shared_index.entries.get_mut(delete_index) {
+                Some(e) => e,
+                None => {
+                    err = decode::Error::Corrupt("delete bitmap length exceeds shared index length - more entries in bitmap than found in shared index").into();
+                    return None
+                }
+            };
+            shared_entry.flags.insert(crate::entry::Flags::REMOVE);
+            Some(())
+        });
+        if let Some(err) = err {
+            return Err(err.into());
         }
 
+        shared_index
+            .entries
+            .retain(|e| !e.flags.contains(crate::entry::Flags::REMOVE));
+
+        let mut shared_entries = std::mem::take(&mut shared_index.entries);
+        // The sort is stable, so added entries of the split index are sorted after shared entries of the same path
+        // and stage, which they replace.
+        shared_entries.sort_by(|a, b| a.cmp(b, &shared_index.state));
+        shared_entries.dedup_by(|later, earlier| {
+            let is_same = later.cmp(earlier, &shared_index.state).is_eq();
+            if is_same {
+                std::mem::swap(later, earlier);
+            }
+            is_same
+        });
+
+        split_index.entries = shared_entries;
+        split_index.path_backing = std::mem::take(&mut shared_index.path_backing);
+        split_index.is_sparse |= shared_index.is_sparse;
+        split_index.link = Some(Link {
+            shared_index_checksum: self.shared_index_checksum,
+            bitmaps: None,
+        });
+
         Ok(())
     }
 }
@@ -38,6 +38,9 @@ pub struct Link {
     /// The checksum of the shared index as last seen.
     pub shared_index_checksum: gix_hash::ObjectId,
     /// Bitmaps to tell us which entries to delete or replace.
+    ///
+    /// They are `None` once the shared index was merged into the entries of the split index, which is when the link
+    /// merely remembers the shared index to use when writing the split index again.
     pub bitmaps: Option<link::Bitmaps>,
 }
 
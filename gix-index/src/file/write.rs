@@ -1,6 +1,6 @@
 use gix_features::hash;
 
-use crate::{write, File, Version};
+use crate::{extension, write, File, State, Version};
 
 /// The error produced by [`File::write()`].
 #[derive(Debug, thiserror::Error)]
@@ -12,6 +12,10 @@ pub enum Error {
     AcquireLock(#[from] gix_lock::acquire::Error),
     #[error("Could not commit lock for index file")]
     CommitLock(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error("Could not read the shared index to write the split index against")]
+    SharedIndex(#[from] crate::file::init::Error),
+    #[error("Could not move the new shared index into place")]
+    PersistSharedIndex(#[from] gix_lock::tempfile::handle::persist::Error<gix_lock::tempfile::handle::Writable>),
 }
 
 impl File {
@@ -19,33 +23,308 @@ impl File {
     /// to retain all information of this index.
     pub fn write_to(
         &self,
-        mut out: impl std::io::Write,
+        out: impl std::io::Write,
         options: write::Options,
     ) -> std::io::Result<(Version, gix_hash::ObjectId)> {
-        let mut hasher = hash::Write::new(&mut out, self.state.object_hash);
-        let version = self.state.write_to(&mut hasher, options)?;
-
-        let hash = hasher.hash.digest();
-        out.write_all(&hash)?;
-        Ok((version, gix_hash::ObjectId::from(hash)))
+        write_hashed(&self.state, out, options)
     }
 
     /// Write ourselves to the path we were read from after acquiring a lock, using `options`.
     ///
+    /// If [`options.split_index`][write::Options::split_index] is set, a split index will be written which refers to
+    /// a shared index that is written next to it if needed.
+    ///
     /// Note that the hash produced will be stored which is why we need to be mutable.
     pub fn write(&mut self, options: write::Options) -> Result<(), Error> {
-        let mut lock = std::io::BufWriter::new(gix_lock::File::acquire_to_update_resource(
-            &self.path,
-            gix_lock::acquire::Fail::Immediately,
-            None,
-        )?);
-        let (version, digest) = self.write_to(&mut lock, options)?;
-        match lock.into_inner() {
-            Ok(lock) => lock.commit()?,
-            Err(err) => return Err(err.into_error().into()),
+        let (version, digest) = match options.split_index {
+            Some(split_index) => self.write_split(options, split_index)?,
+            None => {
+                let (version, digest) = write_locked(&self.state, &self.path, options)?;
+                self.state.link = None;
+                (version, digest)
+            }
         };
         self.state.version = version;
         self.checksum = Some(digest);
         Ok(())
     }
 }
+
+impl File {
+    fn write_split(
+        &mut self,
+        options: write::Options,
+        write::SplitIndex {
+            max_percent_change,
+            shared_index_expire,
+        }: write::SplitIndex,
+    ) -> Result<(Version, gix_hash::ObjectId), Error> {
+        let shared_index = match self.state.link.as_ref() {
+            Some(link) => {
+                let path = extension::link::shared_index_path(&self.path, &link.shared_index_checksum);
+                match File::at(
+                    &path,
+                    self.state.object_hash,
+                    crate::decode::Options {
+                        expected_checksum: Some(link.shared_index_checksum),
+                        ..Default::default()
+                    },
+                ) {
+                    Ok(shared_index) => Some(shared_index),
+                    Err(crate::file::init::Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            None => None,
+        };
+
+        let split = shared_index.as_ref().and_then(|shared_index| {
+            split::entries(
+                &self.state,
+                &shared_index.state,
+                shared_index.checksum.expect("read from disk"),
+                max_percent_change,
+            )
+        });
+        let split_index = match split {
+            Some(split_index) => {
+                // Keep the shared index we are using from expiring.
+                filetime::set_file_mtime(shared_index.expect("present").path, filetime::FileTime::now()).ok();
+                split_index
+            }
+            None => {
+                let shared_index_checksum = self.write_shared_index(shared_index_expire)?;
+                split::without_entries(&self.state, shared_index_checksum)
+            }
+        };
+
+        let (version, digest) = write_locked(&split_index, &self.path, options)?;
+        self.state.link = Some(extension::Link {
+            shared_index_checksum: split_index
+                .link
+                .expect("split index is always linked")
+                .shared_index_checksum,
+            bitmaps: None,
+        });
+        Ok((version, digest))
+    }
+
+    /// Write all of our entries into a new shared index and delete shared indices which expired.
+    fn write_shared_index(
+        &self,
+        shared_index_expire: Option<std::time::Duration>,
+    ) -> Result<gix_hash::ObjectId, Error> {
+        let index_dir = self.path.parent().expect("index file in .git folder");
+        let shared_index = State {
+            tree: None,
+            link: None,
+            resolve_undo: None,
+            untracked: None,
+            fs_monitor: None,
+            ..self.state.clone()
+        };
+        let mut tempfile = std::io::BufWriter::new(gix_lock::tempfile::new(
+            index_dir,
+            gix_lock::tempfile::ContainingDirectory::Exists,
+            gix_lock::tempfile::AutoRemove::Tempfile,
+        )?);
+        let (_version, checksum) = write_hashed(
+            &shared_index,
+            &mut tempfile,
+            write::Options {
+                extensions: write::Extensions::None,
+                version: None,
+                split_index: None,
+            },
+        )?;
+        let shared_index_path = extension::link::shared_index_path(&self.path, &checksum);
+        tempfile
+            .into_inner()
+            .map_err(|err| err.into_error())?
+            .persist(&shared_index_path)?;
+
+        if let Some(expire) = shared_index_expire {
+            delete_expired_shared_indices(index_dir, &shared_index_path, expire)?;
+        }
+        Ok(checksum)
+    }
+}
+
+fn write_hashed(
+    state: &State,
+    mut out: impl std::io::Write,
+    options: write::Options,
+) -> std::io::Result<(Version, gix_hash::ObjectId)> {
+    let mut hasher = hash::Write::new(&mut out, state.object_hash);
+    let version = state.write_to(&mut hasher, options)?;
+
+    let hash = hasher.hash.digest();
+    out.write_all(hash.as_slice())?;
+    Ok((version, hash))
+}
+
+fn write_locked(
+    state: &State,
+    path: &std::path::Path,
+    options: write::Options,
+) -> Result<(Version, gix_hash::ObjectId), Error> {
+    let mut lock = std::io::BufWriter::new(gix_lock::File::acquire_to_update_resource(
+        path,
+        gix_lock::acquire::Fail::Immediately,
+        None,
+    )?);
+    let (version, digest) = write_hashed(state, &mut lock, options)?;
+    match lock.into_inner() {
+        Ok(lock) => lock.commit()?,
+        Err(err) => return Err(err.into_error().into()),
+    };
+    Ok((version, digest))
+}
+
+fn delete_expired_shared_indices(
+    index_dir: &std::path::Path,
+    current_shared_index: &std::path::Path,
+    expire: std::time::Duration,
+) -> std::io::Result<()> {
+    let now = std::time::SystemTime::now();
+    for entry in std::fs::read_dir(index_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path == current_shared_index || !entry.file_name().to_string_lossy().starts_with("sharedindex.") {
+            continue;
+        }
+        let is_expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .map_or(false, |age| age >= expire);
+        if is_expired {
+            std::fs::remove_file(path).ok();
+        }
+    }
+    Ok(())
+}
+
+mod split {
+    use std::cmp::Ordering;
+
+    use crate::{entry, extension, Entry, State};
+
+    /// Return the state to write as split index with all entries of `state` that differ from `shared_index`,
+    /// or `None` if the amount of these would exceed `max_percent_change` and a new shared index should be written instead.
+    pub(super) fn entries(
+        state: &State,
+        shared_index: &State,
+        shared_index_checksum: gix_hash::ObjectId,
+        max_percent_change: u8,
+    ) -> Option<State> {
+        let mut current = state
+            .entries
+            .iter()
+            .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+            .peekable();
+        let mut shared = shared_index.entries.iter().enumerate().peekable();
+
+        let mut replaced = Vec::new();
+        let mut deleted = Vec::new();
+        let mut added = Vec::new();
+        let mut num_entries = 0;
+        loop {
+            let ordering = match (current.peek(), shared.peek()) {
+                (Some(current), Some((_, shared))) => {
+                    Entry::cmp_filepaths(shared.path(shared_index), current.path(state))
+                        .then_with(|| shared.stage().cmp(&current.stage()))
+                }
+                (Some(_), None) => Ordering::Greater,
+                (None, Some(_)) => Ordering::Less,
+                (None, None) => break,
+            };
+            match ordering {
+                Ordering::Less => {
+                    let (shared_idx, _) = shared.next().expect("peeked");
+                    deleted.push(shared_idx);
+                }
+                Ordering::Greater => {
+                    added.push(current.next().expect("peeked"));
+                    num_entries += 1;
+                }
+                Ordering::Equal => {
+                    let (shared_idx, shared_entry) = shared.next().expect("peeked");
+                    let current_entry = current.next().expect("peeked");
+                    if !is_unchanged(shared_entry, current_entry) {
+                        replaced.push((shared_idx, current_entry));
+                    }
+                    num_entries += 1;
+                }
+            }
+        }
+
+        let num_not_shared = replaced.len() + added.len();
+        let needs_new_shared_index = match max_percent_change {
+            0 => true,
+            100.. => false,
+            _ => num_entries * usize::from(max_percent_change) < num_not_shared * 100,
+        };
+        if needs_new_shared_index {
+            return None;
+        }
+
+        let mut split_index = without_entries(state, shared_index_checksum);
+        let link = split_index.link.as_mut().expect("just set");
+        link.bitmaps = Some(extension::link::Bitmaps {
+            delete: gix_bitmap::ewah::Vec::from_sorted_indices(deleted),
+            replace: gix_bitmap::ewah::Vec::from_sorted_indices(replaced.iter().map(|(idx, _)| *idx)),
+        });
+        split_index.entries.reserve(num_not_shared);
+        for (_, entry) in replaced {
+            split_index.entries.push(Entry {
+                path: 0..0,
+                ..entry.clone()
+            });
+        }
+        for entry in added {
+            let path = entry.path(state);
+            let start = split_index.path_backing.len();
+            split_index.path_backing.extend_from_slice(path);
+            split_index.entries.push(Entry {
+                path: start..split_index.path_backing.len(),
+                ..entry.clone()
+            });
+        }
+        Some(split_index)
+    }
+
+    /// Return a state that carries all extensions of `state`, but no entries as all of them are in the shared index
+    /// with `shared_index_checksum`.
+    pub(super) fn without_entries(state: &State, shared_index_checksum: gix_hash::ObjectId) -> State {
+        State {
+            object_hash: state.object_hash,
+            timestamp: state.timestamp,
+            version: state.version,
+            entries: Vec::new(),
+            path_backing: Vec::new(),
+            is_sparse: state.is_sparse,
+            tree: state.tree.clone(),
+            link: Some(extension::Link {
+                shared_index_checksum,
+                bitmaps: Some(extension::link::Bitmaps {
+                    delete: gix_bitmap::ewah::Vec::from_sorted_indices(None),
+                    replace: gix_bitmap::ewah::Vec::from_sorted_indices(None),
+                }),
+            }),
+            resolve_undo: state.resolve_undo.clone(),
+            untracked: state.untracked.clone(),
+            fs_monitor: state.fs_monitor.clone(),
+        }
+    }
+
+    fn is_unchanged(shared: &Entry, current: &Entry) -> bool {
+        shared.stat == current.stat
+            && shared.id == current.id
+            && shared.mode == current.mode
+            && shared.flags.to_storage() == current.flags.to_storage()
+            && entry::at_rest::FlagsExtended::from_flags(shared.flags)
+                == entry::at_rest::FlagsExtended::from_flags(current.flags)
+    }
+}
@@ -0,0 +1,316 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_object::{tree::EntryMode, TreeRefIter};
+
+use crate::{entry, extension, Entry, PathStorage, State};
+
+///
+pub mod expand {
+    use bstr::BString;
+
+    /// The error returned by [State::expand_sparse_directories()][crate::State::expand_sparse_directories()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The tree {oid} of sparse directory '{path}' could not be found")]
+        TreeNotFound { oid: gix_hash::ObjectId, path: BString },
+        #[error("The tree {oid} of sparse directory '{path}' could not be decoded")]
+        TreeDecode {
+            oid: gix_hash::ObjectId,
+            path: BString,
+            source: gix_object::decode::Error,
+        },
+    }
+}
+
+/// Sparse directories
+impl State {
+    /// Replace all sparse directory entries with the entries of the trees they point to, recursively, using `find` to
+    /// obtain trees, turning a sparse index into a full one whose entries outside of the sparse-checkout cone are marked
+    /// with [`SKIP_WORKTREE`][entry::Flags::SKIP_WORKTREE].
+    ///
+    /// The tree extension is updated to contain the expanded trees as well.
+    /// On error, the index remains unchanged.
+    pub fn expand_sparse_directories<Find>(&mut self, mut find: Find) -> Result<(), expand::Error>
+    where
+        Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+    {
+        if !self.entries.iter().any(|e| e.mode.is_sparse()) {
+            self.is_sparse = false;
+            return Ok(());
+        }
+
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut path_backing = Vec::with_capacity(self.path_backing.len());
+        let mut path = BString::default();
+        let mut tree = self.tree.take();
+        for entry in &self.entries {
+            let entry_path = entry.path(self);
+            if !entry.mode.is_sparse() {
+                entries.push(entry_with_path(entry, entry_path, &mut path_backing));
+                continue;
+            }
+
+            path.clear();
+            path.push_str(entry_path);
+            if !path.ends_with(b"/") {
+                path.push(b'/');
+            }
+            let mut expanded = match expand_tree(
+                entry.id,
+                entry.flags,
+                &mut path,
+                &mut find,
+                &mut entries,
+                &mut path_backing,
+            ) {
+                Ok(expanded) => expanded,
+                Err(err) => {
+                    self.tree = tree;
+                    return Err(err);
+                }
+            };
+            if let Some(tree) = tree.as_mut() {
+                let dir = &entry_path[..entry_path.len() - usize::from(entry_path.ends_with(b"/"))];
+                update_subtree(tree, dir, |node| {
+                    expanded.name = std::mem::take(&mut node.name);
+                    *node = expanded;
+                });
+            }
+        }
+        self.tree = tree;
+        self.entries = entries;
+        self.path_backing = path_backing;
+        self.is_sparse = false;
+        Ok(())
+    }
+
+    /// Replace all entries within directories for which `is_excluded(directory)` returns `true` with a single sparse directory entry
+    /// pointing to the directory's tree, making this a sparse index, and return the amount of directories that were collapsed.
+    ///
+    /// `is_excluded` is called with repository-relative directory paths without trailing slash, and is expected to return `true`
+    /// if the directory and everything below it is outside of the sparse-checkout cone. Directories for which it returns `false`
+    /// are searched for excluded directories recursively.
+    ///
+    /// A directory is only collapsed if its tree is known and valid in the tree extension, and if all of its entries are
+    /// marked with [`SKIP_WORKTREE`][entry::Flags::SKIP_WORKTREE] and free of conflicts, hence nothing will be collapsed without
+    /// a tree extension.
+    /// The index is only marked sparse if at least one directory was collapsed.
+    pub fn collapse_sparse_directories(&mut self, mut is_excluded: impl FnMut(&BStr) -> bool) -> usize {
+        let mut tree = match self.tree.take() {
+            Some(tree) => tree,
+            None => return 0,
+        };
+        let mut collapsed = Vec::new();
+        let mut path = BString::default();
+        collapse_children(
+            &mut tree,
+            &mut path,
+            &self.entries,
+            &self.path_backing,
+            &mut is_excluded,
+            &mut collapsed,
+        );
+        self.tree = Some(tree);
+        if collapsed.is_empty() {
+            return 0;
+        }
+
+        collapsed.sort_by_key(|c| c.entries.start);
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut path_backing = Vec::with_capacity(self.path_backing.len());
+        let mut collapsed_dirs = collapsed.iter().peekable();
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            match collapsed_dirs.next_if(|c| c.entries.start == idx) {
+                Some(dir) => {
+                    let start = path_backing.len();
+                    path_backing.extend_from_slice(&dir.path);
+                    entries.push(Entry {
+                        stat: entry::Stat::default(),
+                        id: dir.id,
+                        flags: entry::Flags::EXTENDED | entry::Flags::SKIP_WORKTREE,
+                        mode: entry::Mode::DIR,
+                        path: start..path_backing.len(),
+                    });
+                    idx = dir.entries.end;
+                }
+                None => {
+                    let entry = &self.entries[idx];
+                    entries.push(entry_with_path(entry, entry.path(self), &mut path_backing));
+                    idx += 1;
+                }
+            }
+        }
+        self.entries = entries;
+        self.path_backing = path_backing;
+        self.is_sparse = true;
+        collapsed.len()
+    }
+}
+
+/// A directory to replace with a sparse directory entry.
+struct Collapsed {
+    /// The directory path with trailing slash.
+    path: BString,
+    /// The id of the directory's tree.
+    id: gix_hash::ObjectId,
+    /// The range of entries within the directory.
+    entries: std::ops::Range<usize>,
+}
+
+/// Collapse all excluded directories below `tree` into `out` and return the amount of entries that were removed,
+/// adjusting the amount of entries of `tree` accordingly.
+fn collapse_children(
+    tree: &mut extension::Tree,
+    path: &mut BString,
+    entries: &[Entry],
+    path_backing: &PathStorage,
+    is_excluded: &mut dyn FnMut(&BStr) -> bool,
+    out: &mut Vec<Collapsed>,
+) -> u32 {
+    let mut removed_entries = 0;
+    for child in &mut tree.children {
+        let prev_len = path.len();
+        path.push_str(&child.name);
+        removed_entries += match child.num_entries {
+            Some(num_entries) if is_excluded(path.as_bstr()) => {
+                path.push(b'/');
+                let start = entries.partition_point(|e| e.path_in(path_backing) < path.as_bstr());
+                let end = start
+                    + entries[start..]
+                        .iter()
+                        .take_while(|e| e.path_in(path_backing).starts_with(path))
+                        .count();
+                let dir_entries = &entries[start..end];
+                let can_collapse = dir_entries.len() == num_entries as usize
+                    && dir_entries
+                        .iter()
+                        .all(|e| e.flags.contains(entry::Flags::SKIP_WORKTREE) && e.stage() == 0);
+                if can_collapse {
+                    out.push(Collapsed {
+                        path: path.clone(),
+                        id: child.id,
+                        entries: start..end,
+                    });
+                    child.num_entries = Some(1);
+                    child.children.clear();
+                    num_entries - 1
+                } else {
+                    collapse_children(child, path, entries, path_backing, is_excluded, out)
+                }
+            }
+            _ => {
+                path.push(b'/');
+                collapse_children(child, path, entries, path_backing, is_excluded, out)
+            }
+        };
+        path.truncate(prev_len);
+    }
+    if let Some(num_entries) = tree.num_entries.as_mut() {
+        *num_entries -= removed_entries;
+    }
+    removed_entries
+}
+
+fn expand_tree<Find>(
+    id: gix_hash::ObjectId,
+    flags: entry::Flags,
+    path: &mut BString,
+    find: &mut Find,
+    entries: &mut Vec<Entry>,
+    path_backing: &mut PathStorage,
+) -> Result<extension::Tree, expand::Error>
+where
+    Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+{
+    let mut buf = Vec::new();
+    let tree_entries = find(&id, &mut buf)
+        .ok_or_else(|| expand::Error::TreeNotFound {
+            oid: id,
+            path: path.clone(),
+        })?
+        .map(|e| e.map(|e| (e.mode, e.filename.to_owned(), e.oid.to_owned())))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| expand::Error::TreeDecode {
+            oid: id,
+            path: path.clone(),
+            source: err,
+        })?;
+
+    let mut tree = extension::Tree {
+        name: Default::default(),
+        id,
+        num_entries: None,
+        children: Vec::new(),
+    };
+    let mut num_entries = 0;
+    for (mode, name, oid) in tree_entries {
+        let prev_len = path.len();
+        path.push_str(&name);
+        let mode = match mode {
+            EntryMode::Tree => {
+                path.push(b'/');
+                let mut child = expand_tree(oid, flags, path, find, entries, path_backing)?;
+                child.name = name.as_slice().into();
+                num_entries += child.num_entries.unwrap_or_default();
+                tree.children.push(child);
+                path.truncate(prev_len);
+                continue;
+            }
+            EntryMode::Blob => entry::Mode::FILE,
+            EntryMode::BlobExecutable => entry::Mode::FILE_EXECUTABLE,
+            EntryMode::Link => entry::Mode::SYMLINK,
+            EntryMode::Commit => entry::Mode::COMMIT,
+        };
+        let start = path_backing.len();
+        path_backing.extend_from_slice(path);
+        entries.push(Entry {
+            stat: entry::Stat::default(),
+            id: oid,
+            flags,
+            mode,
+            path: start..path_backing.len(),
+        });
+        num_entries += 1;
+        path.truncate(prev_len);
+    }
+    tree.children.sort_by(|a, b| a.name.cmp(&b.name));
+    tree.num_entries = Some(num_entries);
+    Ok(tree)
+}
+
+/// Find the tree at `dir` below `tree` and call `update` on it, adjusting the amount of entries of all of its parents accordingly.
+/// If there is no such tree, all trees leading up to it are invalidated.
+fn update_subtree(tree: &mut extension::Tree, dir: &[u8], update: impl FnOnce(&mut extension::Tree)) -> Option<i64> {
+    let (name, rest) = match dir.find_byte(b'/') {
+        Some(pos) => (&dir[..pos], Some(&dir[pos + 1..])),
+        None => (dir, None),
+    };
+    let delta = match tree.children.binary_search_by(|c| c.name.as_slice().cmp(name)) {
+        Ok(idx) => {
+            let child = &mut tree.children[idx];
+            match rest {
+                Some(rest) => update_subtree(child, rest, update),
+                None => {
+                    let before = child.num_entries;
+                    update(child);
+                    before
+                        .zip(child.num_entries)
+                        .map(|(before, after)| i64::from(after) - i64::from(before))
+                }
+            }
+        }
+        Err(_) => None,
+    };
+    tree.num_entries = delta.and_then(|delta| tree.num_entries.map(|n| (i64::from(n) + delta) as u32));
+    delta
+}
+
+fn entry_with_path(entry: &Entry, path: &BStr, path_backing: &mut PathStorage) -> Entry {
+    let start = path_backing.len();
+    path_backing.extend_from_slice(path);
+    Entry {
+        path: start..path_backing.len(),
+        ..entry.clone()
+    }
+}
@@ -50,17 +50,63 @@ impl Extensions {
 
 /// The options for use when [writing an index][State::write_to()].
 ///
-/// Note that default options write either index V2 or V3 depending on the content of the entries.
+/// Note that default options write either index V2 or V3 depending on the content of the entries, unless the index
+/// was read as V4 in which case it will remain V4.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Options {
     /// Configures which extensions to write
     pub extensions: Extensions,
+    /// If `Some(version)`, the index will be written in the given version, similar to what's configured with `index.version`.
+    /// Note that `V2` will be upgraded to `V3` if entries with extended flags are present as these can't otherwise be represented.
+    ///
+    /// If `None`, an index read as `V4` will be written as `V4` again, otherwise the smallest possible version is chosen
+    /// to represent all entries.
+    pub version: Option<Version>,
+    /// If `Some(…)`, a split index will be written along with its shared index when [writing to disk][crate::File::write()].
+    /// Otherwise, which is the default, a regular index is written, even if the index was read from a split index.
+    ///
+    /// Callers are expected to set it according to `core.splitIndex`, or if it is unset, to keep writing split indices
+    /// if the index that was read is split already.
+    /// Note that this has no effect when [writing to a stream][State::write_to()].
+    pub split_index: Option<SplitIndex>,
+}
+
+/// Configuration for writing split indices along with their shared index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitIndex {
+    /// The percentage of entries that may be stored in the split index instead of the shared index before a new
+    /// shared index is written, as configured by `splitIndex.maxPercentChange`.
+    ///
+    /// With `0` a new shared index is written every time, with `100` an existing shared index is never replaced.
+    pub max_percent_change: u8,
+    /// Shared index files which aren't referenced by the split index anymore will be deleted once they weren't used for
+    /// the given duration, as configured by `splitIndex.sharedIndexExpire`. If `None`, they will never be deleted.
+    pub shared_index_expire: Option<std::time::Duration>,
+}
+
+impl Default for SplitIndex {
+    fn default() -> Self {
+        SplitIndex {
+            max_percent_change: 20,
+            shared_index_expire: Some(std::time::Duration::from_secs(60 * 60 * 24 * 14)),
+        }
+    }
 }
 
 impl State {
     /// Serialize this instance to `out` with [`options`][Options].
-    pub fn write_to(&self, out: impl std::io::Write, Options { extensions }: Options) -> std::io::Result<Version> {
-        let version = self.detect_required_version();
+    ///
+    /// Note that the `link` extension is only written if it carries bitmaps, which is the case for the split portion of an index.
+    pub fn write_to(
+        &self,
+        out: impl std::io::Write,
+        Options {
+            extensions,
+            version,
+            split_index: _,
+        }: Options,
+    ) -> std::io::Result<Version> {
+        let version = self.detect_required_version(version);
 
         let mut write = CountBytes::new(out);
         let num_entries: u32 = self
@@ -77,7 +123,7 @@ impl State {
             .expect("definitely not too many entries");
 
         let offset_to_entries = header(&mut write, version, num_entries - removed_entries)?;
-        let offset_to_extensions = entries(&mut write, self, offset_to_entries)?;
+        let offset_to_extensions = entries(&mut write, self, version, offset_to_entries)?;
         let (extension_toc, out) = self.write_extensions(write, offset_to_extensions, extensions)?;
 
         if num_entries > 0
@@ -103,6 +149,11 @@ impl State {
     {
         type WriteExtFn<'a> = &'a dyn Fn(&mut dyn std::io::Write) -> Option<std::io::Result<extension::Signature>>;
         let extensions: &[WriteExtFn<'_>] = &[
+            &|write| {
+                self.link()
+                    .filter(|link| link.bitmaps.is_some())
+                    .map(|link| link.write_to(write).map(|_| extension::link::SIGNATURE))
+            },
             &|write| {
                 extensions
                     .should_write(extension::tree::SIGNATURE)
@@ -129,11 +180,16 @@ impl State {
 }
 
 impl State {
-    fn detect_required_version(&self) -> Version {
-        self.entries
-            .iter()
-            .find_map(|e| e.flags.contains(entry::Flags::EXTENDED).then_some(Version::V3))
-            .unwrap_or(Version::V2)
+    fn detect_required_version(&self, desired: Option<Version>) -> Version {
+        match desired.unwrap_or(self.version) {
+            Version::V4 => Version::V4,
+            Version::V3 if desired.is_some() => Version::V3,
+            Version::V2 | Version::V3 => self
+                .entries
+                .iter()
+                .find_map(|e| e.flags.contains(entry::Flags::EXTENDED).then_some(Version::V3))
+                .unwrap_or(Version::V2),
+        }
     }
 }
 
@@ -155,7 +211,24 @@ fn header<T: std::io::Write>(
     Ok(out.count)
 }
 
-fn entries<T: std::io::Write>(out: &mut CountBytes<T>, state: &State, header_size: u32) -> Result<u32, std::io::Error> {
+fn entries<T: std::io::Write>(
+    out: &mut CountBytes<T>,
+    state: &State,
+    version: Version,
+    header_size: u32,
+) -> Result<u32, std::io::Error> {
+    if version == Version::V4 {
+        let mut previous_path: &[u8] = &[];
+        for entry in state.entries() {
+            if entry.flags.contains(entry::Flags::REMOVE) {
+                continue;
+            }
+            entry.write_to_with_prefix_compression(&mut *out, state, previous_path)?;
+            previous_path = entry.path(state);
+        }
+        return Ok(out.count);
+    }
+
     for entry in state.entries() {
         if entry.flags.contains(entry::Flags::REMOVE) {
             continue;
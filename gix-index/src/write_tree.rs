@@ -0,0 +1,206 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::{entry, extension, Entry, State};
+
+/// The error returned by [State::write_tree()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error<E: std::error::Error + Send + Sync + 'static> {
+    #[error("Cannot write a tree while the entry at '{path}' is unmerged")]
+    UnresolvedConflict { path: BString },
+    #[error("Failed to write tree for directory '{path}'")]
+    WriteTree {
+        path: BString,
+        #[source]
+        source: E,
+    },
+}
+
+/// Trees
+impl State {
+    /// Write a tree for each directory of this index and return the id of the root tree, using `write` to store them in an
+    /// object database and obtain their ids.
+    ///
+    /// All unchanged trees in the [tree extension][State::tree()] are reused without calling `write` for them, and the extension
+    /// is updated to reflect the written trees. Entries that are marked as [intent-to-add][entry::Flags::INTENT_TO_ADD] or
+    /// to be [removed][entry::Flags::REMOVE] don't become part of the tree, and the trees containing
+    /// intent-to-add entries remain invalid in the extension as a result, just like `git` does it.
+    ///
+    /// Note that it's an error if there are entries with conflicts, and that the entries need to be sorted.
+    pub fn write_tree<E>(
+        &mut self,
+        mut write: impl FnMut(&gix_object::Tree) -> Result<ObjectId, E>,
+    ) -> Result<ObjectId, Error<E>>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        if let Some(entry) = self.entries.iter().find(|e| e.stage() != 0) {
+            return Err(Error::UnresolvedConflict {
+                path: entry.path(self).to_owned(),
+            });
+        }
+        let cached = self.tree.take();
+        let mut path = BString::default();
+        let tree = write_tree(&self.entries, &self.path_backing, &mut path, cached, &mut write)?;
+        let id = tree.id;
+        self.tree = Some(tree);
+        Ok(id)
+    }
+
+    /// Mark all trees in the tree extension as invalid, so they will be recomputed by [`write_tree()`][State::write_tree()].
+    ///
+    /// This needs to be called whenever entries are changed in ways that aren't tracked by path.
+    pub fn invalidate_tree(&mut self) {
+        fn invalidate(tree: &mut extension::Tree) {
+            tree.num_entries = None;
+            tree.children.iter_mut().for_each(invalidate);
+        }
+        if let Some(tree) = self.tree.as_mut() {
+            invalidate(tree);
+        }
+    }
+
+    /// Mark all trees in the tree extension that contain `path` as invalid, so they will be recomputed
+    /// by [`write_tree()`][State::write_tree()].
+    ///
+    /// This needs to be called whenever an entry at `path` is changed, added or removed.
+    pub fn invalidate_tree_for_path(&mut self, path: &BStr) {
+        let mut tree = match self.tree.as_mut() {
+            Some(tree) => tree,
+            None => return,
+        };
+        let mut components = path.split_str("/").peekable();
+        loop {
+            tree.num_entries = None;
+            let name = match components.next() {
+                Some(name) if components.peek().is_some() => name,
+                _ => break,
+            };
+            match tree.children.binary_search_by(|c| c.name.as_slice().cmp(name)) {
+                Ok(idx) => tree = &mut tree.children[idx],
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+/// Write the tree for the directory at `path`, which is empty or ends with a slash, from `entries`
+/// which all reside in this directory, reusing `cached` if it is valid.
+fn write_tree<E>(
+    entries: &[Entry],
+    path_backing: &crate::PathStorage,
+    path: &mut BString,
+    cached: Option<extension::Tree>,
+    write: &mut dyn FnMut(&gix_object::Tree) -> Result<ObjectId, E>,
+) -> Result<extension::Tree, Error<E>>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let num_entries = entries
+        .iter()
+        .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+        .count() as u32;
+    let mut cached = match cached {
+        Some(cached) if cached.num_entries == Some(num_entries) => return Ok(cached),
+        Some(cached) => cached.children,
+        None => Vec::new(),
+    };
+
+    let mut tree = gix_object::Tree::empty();
+    let mut children = Vec::new();
+    let mut is_valid = true;
+    let mut idx = 0;
+    while idx < entries.len() {
+        let entry = &entries[idx];
+        let rela_path = &entry.path_in(path_backing)[path.len()..];
+        match rela_path.find_byte(b'/') {
+            Some(pos) if !entry.mode.is_sparse() || pos + 1 != rela_path.len() => {
+                let name = &rela_path[..pos];
+                let dir_len = path.len() + pos + 1;
+                let dir_prefix = &entry.path_in(path_backing)[..dir_len];
+                let num_dir_entries = entries[idx..]
+                    .iter()
+                    .take_while(|e| e.path_in(path_backing).starts_with(dir_prefix))
+                    .count();
+                let cached_child = cached
+                    .binary_search_by(|c| c.name.as_slice().cmp(name))
+                    .ok()
+                    .map(|child_idx| cached.remove(child_idx));
+
+                let prev_len = path.len();
+                path.extend_from_slice(&rela_path[..=pos]);
+                let mut child = write_tree(
+                    &entries[idx..][..num_dir_entries],
+                    path_backing,
+                    path,
+                    cached_child,
+                    write,
+                )?;
+                path.truncate(prev_len);
+                idx += num_dir_entries;
+
+                is_valid &= child.num_entries.is_some();
+                child.name = name.as_bytes().into();
+                if child.id != ObjectId::empty_tree(child.id.kind()) {
+                    tree.entries.push(gix_object::tree::Entry {
+                        mode: EntryMode::Tree,
+                        filename: name.into(),
+                        oid: child.id,
+                    });
+                }
+                children.push(child);
+            }
+            sparse_dir_name => {
+                idx += 1;
+                if entry.flags.contains(entry::Flags::REMOVE) {
+                    continue;
+                }
+                if entry.flags.contains(entry::Flags::INTENT_TO_ADD) {
+                    is_valid = false;
+                    continue;
+                }
+                let filename = match sparse_dir_name {
+                    Some(pos) => {
+                        let name = &rela_path[..pos];
+                        children.push(extension::Tree {
+                            name: name.as_bytes().into(),
+                            id: entry.id,
+                            num_entries: Some(1),
+                            children: Vec::new(),
+                        });
+                        name
+                    }
+                    None => rela_path,
+                };
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: entry_mode(entry.mode),
+                    filename: filename.into(),
+                    oid: entry.id,
+                });
+            }
+        }
+    }
+
+    let id = write(&tree).map_err(|err| Error::WriteTree {
+        path: path.clone(),
+        source: err,
+    })?;
+    Ok(extension::Tree {
+        name: Default::default(),
+        id,
+        num_entries: is_valid.then_some(num_entries),
+        children,
+    })
+}
+
+fn entry_mode(mode: entry::Mode) -> EntryMode {
+    match mode {
+        entry::Mode::DIR => EntryMode::Tree,
+        entry::Mode::FILE_EXECUTABLE => EntryMode::BlobExecutable,
+        entry::Mode::SYMLINK => EntryMode::Link,
+        entry::Mode::COMMIT => EntryMode::Commit,
+        _ => EntryMode::Blob,
+    }
+}
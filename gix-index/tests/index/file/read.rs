@@ -310,9 +310,10 @@ fn split_index_and_regular_index_of_same_content_are_indeed_the_same() {
     let split =
         verify(gix_index::File::at(base.join("split/.git/index"), gix_hash::Kind::Sha1, Default::default()).unwrap());
 
+    let link = split.link().expect("the shared index is remembered for writing");
     assert!(
-        split.link().is_none(),
-        "link extension is dissolved, merging the shared index permanently into the split one (for now)"
+        link.bitmaps.is_none(),
+        "link extension is dissolved, merging the shared index permanently into the split one"
     );
 
     let regular = verify(
@@ -92,9 +92,7 @@ fn state_comparisons_with_various_extension_configurations() {
         Generated("v2_more_files"),
         Generated("v2_all_file_kinds"),
         Generated("v2_split_index"),
-        // TODO: this fails because git allows to configure the index version while gitoxide doesn't
-        //       the fixture artificially sets the version to V4 and gitoxide writes it back out as the lowest required version, V2
-        // Generated("v4_more_files_IEOT"),
+        Generated("v4_more_files_IEOT"),
         Generated("v3_skip_worktree"),
         Generated("v3_sparse_index_non_cone"),
         Generated("v3_sparse_index"),
@@ -139,6 +137,47 @@ fn extended_flags_automatically_upgrade_the_version_to_avoid_data_loss() -> crat
     Ok(())
 }
 
+#[test]
+fn v4_is_written_with_prefix_compressed_paths_if_desired() -> crate::Result {
+    let expected = Generated("v2_more_files").open();
+    assert_eq!(expected.version(), Version::V2);
+
+    let mut v2 = Vec::new();
+    expected.write_to(&mut v2, Default::default())?;
+    let mut v4 = Vec::new();
+    let options = Options {
+        version: Some(Version::V4),
+        ..Default::default()
+    };
+    let (actual_version, _digest) = expected.write_to(&mut v4, options)?;
+    assert_eq!(actual_version, Version::V4);
+    assert!(v4.len() < v2.len(), "paths are compressed and entries aren't padded");
+
+    let (actual, _) = State::from_bytes(&v4, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    compare_states(&actual, actual_version, &expected, options, "v2_more_files");
+    Ok(())
+}
+
+#[test]
+fn desired_version_is_upgraded_if_entries_require_it() -> crate::Result {
+    let mut expected = Generated("v4_more_files_IEOT").open();
+    assert_eq!(expected.version(), Version::V4);
+    let mut buf = Vec::new();
+    let (actual_version, _digest) = expected.write_to(&mut buf, Default::default())?;
+    assert_eq!(actual_version, Version::V4, "V4 isn't downgraded by default");
+
+    expected.entries_mut()[0].flags.insert(entry::Flags::EXTENDED);
+    let (actual_version, _digest) = expected.write_to(
+        &mut buf,
+        Options {
+            version: Some(Version::V2),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(actual_version, Version::V3, "extended flags need V3");
+    Ok(())
+}
+
 #[test]
 fn remove_flag_is_respected() -> crate::Result {
     let mut index = Generated("v4_more_files_IEOT").open();
@@ -168,6 +207,153 @@ fn remove_flag_is_respected() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn split_index_reuses_shared_index_if_changes_are_small_enough() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable_standalone("make_index/v2_split_vs_regular_index.sh")?;
+    let (repo, git_dir) = split_repo(tmp.path());
+    let shared_indices = shared_index_paths(&git_dir)?;
+    assert_eq!(shared_indices.len(), 1, "git created a single shared index");
+
+    let mut index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, Default::default())?;
+    let expected = index.entries().len();
+    index.entries_mut()[0].flags.insert(entry::Flags::REMOVE);
+    index.write(split_index_options(100))?;
+    assert_eq!(
+        shared_index_paths(&git_dir)?,
+        shared_indices,
+        "the existing shared index is used"
+    );
+    let link = index.link().expect("still linked");
+    assert!(link.bitmaps.is_none(), "the link only remembers the shared index");
+
+    let (split, _) = State::from_bytes(
+        &std::fs::read(git_dir.join("index"))?,
+        FileTime::now(),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?;
+    let link = split.link().expect("written");
+    assert_eq!(
+        shared_indices[0].file_name().expect("present").to_str(),
+        Some(format!("sharedindex.{}", link.shared_index_checksum).as_str())
+    );
+    let bitmaps = link.bitmaps.as_ref().expect("written as well");
+    let mut deleted = Vec::new();
+    bitmaps.delete.for_each_set_bit(|idx| {
+        deleted.push(idx);
+        Some(())
+    });
+    assert_eq!(
+        deleted,
+        [0, 1, 2, 3],
+        "a, b, c and x of the shared index were deleted, the latter by us"
+    );
+
+    let actual = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, Default::default())?;
+    assert_eq!(actual.entries().len(), expected - 1);
+    actual.verify_entries()?;
+    assert!(
+        gix_testtools::run_git(&repo, &["status", "--porcelain"])?.success(),
+        "git can read our split index"
+    );
+    Ok(())
+}
+
+#[test]
+fn split_index_writes_new_shared_index_if_changes_are_too_big() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable_standalone("make_index/v2_split_vs_regular_index.sh")?;
+    let (repo, git_dir) = split_repo(tmp.path());
+    let previous_shared_indices = shared_index_paths(&git_dir)?;
+
+    let mut index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, Default::default())?;
+    let expected_paths = paths(&index);
+    index.write(split_index_options(0))?;
+
+    let shared_indices = shared_index_paths(&git_dir)?;
+    assert_eq!(
+        shared_indices.len(),
+        2,
+        "a new shared index was written, the old one didn't expire yet"
+    );
+    let link = index.link().expect("linked");
+    assert!(shared_indices
+        .iter()
+        .filter(|p| !previous_shared_indices.contains(p))
+        .all(|p| p.ends_with(format!("sharedindex.{}", link.shared_index_checksum))));
+
+    let (split, _) = State::from_bytes(
+        &std::fs::read(git_dir.join("index"))?,
+        FileTime::now(),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?;
+    assert_eq!(split.entries().len(), 0, "all entries are in the shared index");
+
+    let actual = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, Default::default())?;
+    assert_eq!(paths(&actual), expected_paths);
+    assert!(
+        gix_testtools::run_git(&repo, &["status", "--porcelain"])?.success(),
+        "git can read our split index"
+    );
+    Ok(())
+}
+
+#[test]
+fn split_index_is_removed_if_not_configured() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable_standalone("make_index/v2_split_vs_regular_index.sh")?;
+    let (_repo, git_dir) = split_repo(tmp.path());
+
+    let mut index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, Default::default())?;
+    let expected_paths = paths(&index);
+    index.write(Default::default())?;
+    assert!(index.link().is_none());
+
+    let (actual, _) = State::from_bytes(
+        &std::fs::read(git_dir.join("index"))?,
+        FileTime::now(),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?;
+    assert!(actual.link().is_none(), "a regular index was written");
+    assert_eq!(paths(&actual), expected_paths);
+    Ok(())
+}
+
+fn split_repo(tmp: &std::path::Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let repo = tmp.join("split");
+    let git_dir = repo.join(".git");
+    (repo, git_dir)
+}
+
+fn split_index_options(max_percent_change: u8) -> Options {
+    Options {
+        split_index: Some(write::SplitIndex {
+            max_percent_change,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn shared_index_paths(git_dir: &std::path::Path) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut paths = std::fs::read_dir(git_dir)?
+        .map(|e| e.map(|e| e.path()))
+        .filter(|p| {
+            p.as_ref().map_or(true, |p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .map_or(false, |n| n.starts_with("sharedindex."))
+            })
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+fn paths(state: &State) -> Vec<bstr::BString> {
+    state.entries().iter().map(|e| e.path(state).to_owned()).collect()
+}
+
 fn compare_states_against_baseline(
     actual: &State,
     actual_version: Version,
@@ -207,12 +393,14 @@ fn compare_states(actual: &State, actual_version: Version, expected: &State, opt
 
     // As `write_to` does / should not mutate we can test those properties here.
     // Anything that can be configured has to be tested separately when comparing against baseline
-    assert_eq!(
-        actual.version(),
-        expected.version(),
-        "version mismatch, actual vs expected, in {:?}",
-        fixture
-    );
+    if options.version.is_none() {
+        assert_eq!(
+            actual.version(),
+            expected.version(),
+            "version mismatch, actual vs expected, in {:?}",
+            fixture
+        );
+    }
     assert_eq!(
         actual.is_sparse(),
         expected.is_sparse(),
@@ -264,9 +452,15 @@ fn only_tree_ext() -> Options {
             end_of_index_entry: false,
             tree_cache: true,
         },
+        version: None,
+        split_index: None,
     }
 }
 
 fn options_with(extensions: write::Extensions) -> Options {
-    Options { extensions }
+    Options {
+        extensions,
+        version: None,
+        split_index: None,
+    }
 }
@@ -0,0 +1,90 @@
+use gix::prelude::FindExt;
+use gix_index::{entry, verify::extensions::no_find, File, State};
+use gix_testtools::scripted_fixture_read_only_standalone;
+
+fn open(name: &str) -> crate::Result<(gix::Repository, File)> {
+    let repo = gix::open(scripted_fixture_read_only_standalone(
+        std::path::Path::new("make_index").join(name).with_extension("sh"),
+    )?)?;
+    let index = repo.open_index()?;
+    Ok((repo, index))
+}
+
+#[test]
+fn expand_sparse_directories() -> crate::Result {
+    let (repo, mut sparse) = open("v3_sparse_index")?;
+    let (_, expected) = open("v3_skip_worktree")?;
+    assert!(sparse.is_sparse());
+
+    sparse.expand_sparse_directories(|oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    assert!(!sparse.is_sparse());
+    assert!(sparse.entries().iter().all(|e| !e.mode.is_sparse()));
+    compare_states(&sparse, &expected);
+    Ok(())
+}
+
+#[test]
+fn expand_sparse_directories_without_tree_fails() -> crate::Result {
+    let (_, mut sparse) = open("v3_sparse_index")?;
+    let err = sparse
+        .expand_sparse_directories(|_, _| None)
+        .expect_err("trees are needed");
+    assert!(matches!(
+        err,
+        gix_index::sparse::expand::Error::TreeNotFound { path, .. } if path == "c1/c3/"
+    ));
+    assert!(sparse.is_sparse(), "the index is unchanged on error");
+    assert!(sparse.entries().iter().any(|e| e.mode.is_sparse()));
+    Ok(())
+}
+
+#[test]
+fn collapse_sparse_directories() -> crate::Result {
+    let (_, mut full) = open("v3_skip_worktree")?;
+    let (_, expected) = open("v3_sparse_index")?;
+    assert!(!full.is_sparse());
+
+    let collapsed = full.collapse_sparse_directories(|dir| dir == "c1/c3" || dir == "d");
+    assert_eq!(collapsed, 2);
+    assert!(full.is_sparse());
+    compare_states(&full, &expected);
+    Ok(())
+}
+
+#[test]
+fn collapse_sparse_directories_needs_all_entries_skipped() -> crate::Result {
+    let (_, mut full) = open("v3_skip_worktree")?;
+    let num_entries = full.entries().len();
+
+    let collapsed = full.collapse_sparse_directories(|dir| dir == "c1");
+    assert_eq!(collapsed, 0, "c1 contains entries that are checked out");
+    assert_eq!(full.entries().len(), num_entries);
+    assert!(full.entries().iter().all(|e| e.mode == entry::Mode::FILE));
+    assert!(!full.is_sparse(), "nothing was collapsed");
+    Ok(())
+}
+
+#[test]
+fn collapse_and_expand_roundtrip() -> crate::Result {
+    let (repo, mut state) = open("v3_skip_worktree")?;
+    let (_, expected) = open("v3_skip_worktree")?;
+
+    state.collapse_sparse_directories(|dir| dir.starts_with(b"d") || dir == "c1/c3");
+    state.expand_sparse_directories(|oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    compare_states(&state, &expected);
+    Ok(())
+}
+
+fn compare_states(actual: &State, expected: &State) {
+    actual.verify_entries().expect("valid");
+    actual.verify_extensions(false, no_find).expect("valid");
+
+    assert_eq!(actual.entries().len(), expected.entries().len(), "entry count mismatch");
+    for (a, e) in actual.entries().iter().zip(expected.entries()) {
+        assert_eq!(a.id, e.id, "entry id mismatch");
+        assert_eq!(a.flags, e.flags, "entry flags mismatch");
+        assert_eq!(a.mode, e.mode, "entry mode mismatch");
+        assert_eq!(a.path(actual), e.path(expected), "entry path mismatch");
+    }
+    assert_eq!(actual.tree(), expected.tree(), "the tree extension is kept up to date");
+}
@@ -0,0 +1,157 @@
+use std::cell::Cell;
+
+use gix::prelude::{FindExt, Write};
+use gix_index::{entry, write_tree::Error, File, State};
+use gix_testtools::scripted_fixture_read_only_standalone;
+
+fn open(name: &str) -> crate::Result<(gix::Repository, File)> {
+    let repo = gix::open(scripted_fixture_read_only_standalone(
+        std::path::Path::new("make_index").join(name).with_extension("sh"),
+    )?)?;
+    let index = repo.open_index()?;
+    Ok((repo, index))
+}
+
+fn write_tree(state: &mut State, num_writes: &Cell<usize>) -> Result<gix_hash::ObjectId, Error<std::io::Error>> {
+    let sink = gix::odb::sink(gix_hash::Kind::Sha1);
+    state.write_tree(|tree| {
+        num_writes.set(num_writes.get() + 1);
+        sink.write(tree)
+    })
+}
+
+#[test]
+fn from_scratch_matches_head_tree_and_git_tree_extension() -> crate::Result {
+    for name in ["v2", "v2_more_files", "v4_more_files_IEOT"] {
+        let (repo, index) = open(name)?;
+        let head_tree = repo.head_commit()?.tree_id()?.detach();
+        let mut state = State::from_tree(&head_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+        assert!(state.tree().is_none());
+
+        let num_writes = Cell::new(0);
+        assert_eq!(write_tree(&mut state, &num_writes)?, head_tree, "{name}");
+        assert_eq!(
+            state.tree(),
+            index.tree(),
+            "{name}: the extension is exactly what git writes"
+        );
+
+        num_writes.set(0);
+        assert_eq!(write_tree(&mut state, &num_writes)?, head_tree, "{name}");
+        assert_eq!(num_writes.get(), 0, "{name}: valid trees are reused");
+    }
+    Ok(())
+}
+
+#[test]
+fn sparse_directories_are_written_as_trees() -> crate::Result {
+    let (repo, mut index) = open("v3_sparse_index")?;
+    let head_tree = repo.head_commit()?.tree_id()?.detach();
+    index.invalidate_tree_for_path("".into());
+
+    let num_writes = Cell::new(0);
+    assert_eq!(write_tree(&mut index, &num_writes)?, head_tree);
+    Ok(())
+}
+
+#[test]
+fn changed_entries_invalidate_their_trees() -> crate::Result {
+    let (repo, mut index) = open("v2_more_files")?;
+    let head_tree = repo.head_commit()?.tree_id()?.detach();
+    let expected_tree = index.tree().cloned();
+
+    let entry = index
+        .entry_mut_by_path_and_stage("d/a".into(), 0)
+        .expect("entry present");
+    let previous_id = entry.id;
+    entry.id = gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha1);
+    let num_writes = Cell::new(0);
+    let new_tree = write_tree(&mut index, &num_writes)?;
+    assert_ne!(new_tree, head_tree);
+    assert_eq!(num_writes.get(), 2, "only 'd' and the root are rewritten");
+
+    index
+        .entry_mut_by_path_and_stage("d/a".into(), 0)
+        .expect("entry present")
+        .id = previous_id;
+    assert_eq!(write_tree(&mut index, &num_writes)?, head_tree);
+    assert_eq!(index.tree().cloned(), expected_tree);
+    Ok(())
+}
+
+#[test]
+fn mutable_access_to_all_entries_invalidates_all_trees() -> crate::Result {
+    let (repo, mut index) = open("v2_more_files")?;
+    let head_tree = repo.head_commit()?.tree_id()?.detach();
+    let expected_tree = index.tree().cloned();
+
+    let idx = index
+        .entry_index_by_path_and_stage("d/a".into(), 0)
+        .expect("entry present");
+    let entry = &mut index.entries_mut()[idx];
+    let previous_id = entry.id;
+    entry.id = gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha1);
+    let num_writes = Cell::new(0);
+    assert_ne!(write_tree(&mut index, &num_writes)?, head_tree, "the change is noticed");
+
+    for (entry, path) in index.entries_mut_with_paths() {
+        if path == "d/a" {
+            entry.id = previous_id;
+        }
+    }
+    assert_eq!(write_tree(&mut index, &num_writes)?, head_tree);
+    assert_eq!(index.tree().cloned(), expected_tree);
+    Ok(())
+}
+
+#[test]
+fn intent_to_add_entries_are_skipped_and_keep_trees_invalid() -> crate::Result {
+    let (repo, mut index) = open("v2_more_files")?;
+    let head_tree = repo.head_commit()?.tree_id()?.detach();
+    let head_tree_without_a = {
+        let mut state = index.clone().into_parts().0;
+        state
+            .entry_mut_by_path_and_stage("d/a".into(), 0)
+            .expect("entry present")
+            .flags
+            .insert(entry::Flags::REMOVE);
+        write_tree(&mut state, &Cell::new(0))?
+    };
+
+    index
+        .entry_mut_by_path_and_stage("d/a".into(), 0)
+        .expect("entry present")
+        .flags
+        .insert(entry::Flags::INTENT_TO_ADD | entry::Flags::EXTENDED);
+    let num_writes = Cell::new(0);
+    assert_eq!(write_tree(&mut index, &num_writes)?, head_tree_without_a);
+    assert_ne!(head_tree_without_a, head_tree);
+
+    let tree = index.tree().expect("extension is set");
+    assert_eq!(tree.num_entries, None, "the root remains invalid");
+    let d = tree
+        .children
+        .iter()
+        .find(|c| c.name.as_slice() == b"d")
+        .expect("present");
+    assert_eq!(d.num_entries, None, "the tree containing the entry remains invalid");
+
+    num_writes.set(0);
+    write_tree(&mut index, &num_writes)?;
+    assert_eq!(num_writes.get(), 2, "invalid trees are always rewritten");
+    Ok(())
+}
+
+#[test]
+fn conflicts_are_refused() -> crate::Result {
+    let (_repo, mut index) = open("v2_more_files")?;
+    index
+        .entry_mut_by_path_and_stage("d/b".into(), 0)
+        .expect("entry present")
+        .flags
+        .insert(entry::Flags::from_bits(2 << 12).expect("valid stage"));
+
+    let err = write_tree(&mut index, &Cell::new(0)).expect_err("conflicts can't be written");
+    assert!(matches!(err, Error::UnresolvedConflict { path } if path == "d/b"));
+    Ok(())
+}
@@ -0,0 +1,229 @@
+//! Rewrite the encoded form of objects so that the object ids they refer to use another kind of hash, which is needed to
+//! maintain a [compatibility object format](https://git-scm.com/docs/hash-function-transition) next to the one of the repository.
+//!
+//! Only the object ids are changed while everything else is copied verbatim, just like `git` does it. The exception are the
+//! signatures of tags, which are made for one kind of hash each: the one in the message belongs to the kind of hash of the
+//! object, whereas the one of the other kind is stored in a `gpgsig` or `gpgsig-sha256` header. These are swapped on conversion.
+use bstr::ByteSlice;
+use gix_hash::{oid, ObjectId};
+
+use crate::Kind;
+
+/// The error returned by [`object_ids()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not convert {kind} object as it didn't contain valid object ids")]
+    Malformed { kind: Kind },
+    #[error("There is no object id in the other object format for {id}")]
+    Unmapped { id: ObjectId },
+}
+
+/// Write `data` of an object of `kind` into `out`, with all object ids it refers to converted from `object_hash` into another
+/// kind of hash by `map`, which returns `None` if it doesn't know the converted version of an object id.
+///
+/// Trees have their entries rewritten, commits their `tree` and `parent` headers along with the tags in their `mergetag`
+/// headers, and tags their `object` header and their signatures. Blobs are copied as is. Note that `out` is cleared before writing.
+pub fn object_ids(
+    kind: Kind,
+    data: &[u8],
+    object_hash: gix_hash::Kind,
+    out: &mut Vec<u8>,
+    mut map: impl FnMut(&oid) -> Option<ObjectId>,
+) -> Result<(), Error> {
+    out.clear();
+    match kind {
+        Kind::Blob => out.extend_from_slice(data),
+        Kind::Tree => tree(data, object_hash, out, &mut map)?,
+        Kind::Commit => commit(data, object_hash, out, &mut map)?,
+        Kind::Tag => tag(data, object_hash, out, &mut map)?,
+    }
+    Ok(())
+}
+
+fn tree(
+    mut data: &[u8],
+    object_hash: gix_hash::Kind,
+    out: &mut Vec<u8>,
+    map: &mut dyn FnMut(&oid) -> Option<ObjectId>,
+) -> Result<(), Error> {
+    let hash_len = object_hash.len_in_bytes();
+    while !data.is_empty() {
+        let malformed = || Error::Malformed { kind: Kind::Tree };
+        let id_start = data.find_byte(0).ok_or_else(malformed)? + 1;
+        let id = data.get(id_start..id_start + hash_len).ok_or_else(malformed)?;
+        out.extend_from_slice(&data[..id_start]);
+        out.extend_from_slice(map_id(oid::from_bytes_unchecked(id), map)?.as_slice());
+        data = &data[id_start + hash_len..];
+    }
+    Ok(())
+}
+
+fn commit(
+    data: &[u8],
+    object_hash: gix_hash::Kind,
+    out: &mut Vec<u8>,
+    map: &mut dyn FnMut(&oid) -> Option<ObjectId>,
+) -> Result<(), Error> {
+    let malformed = || Error::Malformed { kind: Kind::Commit };
+    let mut rest = data;
+    while let Some(line_end) = rest.find_byte(b'\n') {
+        let (line, tail) = rest.split_at(line_end + 1);
+        rest = tail;
+        if line == b"\n" {
+            out.extend_from_slice(line);
+            break;
+        }
+        if let Some(field) = [&b"tree "[..], b"parent "].iter().find(|field| line.starts_with(field)) {
+            id_line(field, line, object_hash, out, map).ok_or_else(malformed)??;
+        } else if let Some(first_line) = line.strip_prefix(b"mergetag ") {
+            let mut tag_data = first_line.to_owned();
+            while let Some(continuation) = rest.strip_prefix(b" ") {
+                let line_end = continuation.find_byte(b'\n').ok_or_else(malformed)? + 1;
+                tag_data.extend_from_slice(&continuation[..line_end]);
+                rest = &continuation[line_end..];
+            }
+            let mut converted_tag = Vec::with_capacity(tag_data.len());
+            tag(&tag_data, object_hash, &mut converted_tag, map).map_err(|err| match err {
+                Error::Malformed { .. } => malformed(),
+                err => err,
+            })?;
+            out.extend_from_slice(b"mergetag");
+            for line in converted_tag.lines_with_terminator() {
+                out.push(b' ');
+                out.extend_from_slice(line);
+            }
+        } else {
+            out.extend_from_slice(line);
+        }
+    }
+    out.extend_from_slice(rest);
+    Ok(())
+}
+
+/// Convert the tag in `data`, which like `git` expects `out` to contain nothing else as the signature header is inserted
+/// at the end of the headers within it.
+fn tag(
+    data: &[u8],
+    object_hash: gix_hash::Kind,
+    out: &mut Vec<u8>,
+    map: &mut dyn FnMut(&oid) -> Option<ObjectId>,
+) -> Result<(), Error> {
+    let malformed = || Error::Malformed { kind: Kind::Tag };
+    let first_line_end = data.find_byte(b'\n').ok_or_else(malformed)? + 1;
+    let compat_id = id_line(b"object ", &data[..first_line_end], object_hash, out, map).ok_or_else(malformed)??;
+    let rest = &data[first_line_end..];
+
+    let (payload, signature) = rest.split_at(signature_start(rest).unwrap_or(rest.len()));
+    let compat_signature = without_signature_header(payload, signature_header(compat_id.kind()), out);
+    if !signature.is_empty() {
+        insert_signature_header(out, signature_header(object_hash), signature);
+    }
+    out.extend_from_slice(&compat_signature);
+    Ok(())
+}
+
+/// Write `line` starting with `field` into `out` after converting the object id that follows it, and return the converted id,
+/// or `None` if there is no valid id.
+fn id_line(
+    field: &[u8],
+    line: &[u8],
+    object_hash: gix_hash::Kind,
+    out: &mut Vec<u8>,
+    map: &mut dyn FnMut(&oid) -> Option<ObjectId>,
+) -> Option<Result<ObjectId, Error>> {
+    let hex_end = field.len() + object_hash.len_in_hex();
+    let id = line
+        .strip_prefix(field)
+        .and_then(|_| line.get(field.len()..hex_end))
+        .and_then(|hex| ObjectId::from_hex(hex).ok())?;
+    if line[hex_end..] != b"\n"[..] {
+        return None;
+    }
+    Some(map_id(&id, map).map(|compat_id| {
+        out.extend_from_slice(field);
+        compat_id.write_hex_to(&mut *out).expect("writing to a Vec never fails");
+        out.push(b'\n');
+        compat_id
+    }))
+}
+
+/// The name of the header holding the signature of tags and commits made for objects using `kind` of hash, if it's not the
+/// one of the repository.
+fn signature_header(kind: gix_hash::Kind) -> &'static [u8] {
+    match kind {
+        gix_hash::Kind::Sha1 => b"gpgsig",
+        gix_hash::Kind::Sha256 => b"gpgsig-sha256",
+    }
+}
+
+/// Return the position of the last line in `data` starting a signature, as `parse_signed_buffer()` does in `git`.
+fn signature_start(data: &[u8]) -> Option<usize> {
+    const SIGNATURE_STARTS: &[&[u8]] = &[
+        b"-----BEGIN PGP SIGNATURE-----",
+        b"-----BEGIN PGP MESSAGE-----",
+        b"-----BEGIN SIGNED MESSAGE-----",
+        b"-----BEGIN SSH SIGNATURE-----",
+    ];
+    let mut start = None;
+    let mut pos = 0;
+    for line in data.lines_with_terminator() {
+        if SIGNATURE_STARTS.iter().any(|prefix| line.starts_with(prefix)) {
+            start = Some(pos);
+        }
+        pos += line.len();
+    }
+    start
+}
+
+/// Write `payload` into `out` without the value of the `header` that holds a signature, which is returned instead, and without
+/// all other signature headers, as `parse_buffer_signed_by_header()` does in `git`.
+fn without_signature_header(payload: &[u8], header: &[u8], out: &mut Vec<u8>) -> Vec<u8> {
+    let mut signature = Vec::new();
+    let (mut in_signature, mut in_other_signature) = (false, false);
+    let mut rest = payload;
+    while !rest.is_empty() {
+        let (line, tail) = rest.split_at(rest.find_byte(b'\n').map_or(rest.len(), |pos| pos + 1));
+        rest = tail;
+        let value = if in_signature && line.starts_with(b" ") {
+            Some(&line[1..])
+        } else if line.starts_with(header) && line.get(header.len()) == Some(&b' ') {
+            in_other_signature = false;
+            Some(&line[header.len() + 1..])
+        } else {
+            if line.starts_with(b"gpgsig") {
+                in_other_signature = true;
+            } else if in_other_signature && !line.starts_with(b" ") {
+                in_other_signature = false;
+            }
+            None
+        };
+        in_signature = value.is_some();
+        match value {
+            Some(value) => signature.extend_from_slice(value),
+            None if line.starts_with(b"\n") => {
+                out.extend_from_slice(line);
+                out.extend_from_slice(rest);
+                break;
+            }
+            None if !in_other_signature => out.extend_from_slice(line),
+            None => {}
+        }
+    }
+    signature
+}
+
+/// Insert `signature` as value of the `header` at the end of the headers in `out`, as `add_header_signature()` does in `git`.
+fn insert_signature_header(out: &mut Vec<u8>, header: &[u8], signature: &[u8]) {
+    let insert_at = out.find(b"\n\n").map_or(out.len(), |pos| pos + 1);
+    let mut value = header.to_owned();
+    for line in signature.lines_with_terminator() {
+        value.push(b' ');
+        value.extend_from_slice(line);
+    }
+    out.splice(insert_at..insert_at, value);
+}
+
+fn map_id(id: &oid, map: &mut dyn FnMut(&oid) -> Option<ObjectId>) -> Result<ObjectId, Error> {
+    map(id).ok_or_else(|| Error::Unmapped { id: id.to_owned() })
+}
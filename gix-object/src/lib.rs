@@ -31,6 +31,7 @@ pub mod data;
 mod traits;
 pub use traits::WriteTo;
 
+pub mod convert;
 pub mod encode;
 pub(crate) mod parse;
 
@@ -222,6 +223,8 @@ pub struct TreeRef<'a> {
 pub struct TreeRefIter<'a> {
     /// The directories and files contained in this tree.
     data: &'a [u8],
+    /// The kind of hash used by the object ids of all entries.
+    object_hash: gix_hash::Kind,
 }
 
 /// A mutable Tree, containing other trees, blobs or commits.
@@ -244,6 +247,8 @@ impl Tree {
 pub struct Data<'a> {
     /// kind of object
     pub kind: Kind,
+    /// The kind of hash used by the object database the object was read from, which is needed to decode trees.
+    pub object_hash: gix_hash::Kind,
     /// decoded, decompressed data, owned by a backing store.
     pub data: &'a [u8],
 }
@@ -0,0 +1,178 @@
+use gix_hash::{oid, ObjectId};
+use gix_object::{bstr::ByteSlice, convert, CommitRef, Kind, TagRef, TreeRef};
+
+use crate::{fixture_bytes, hex_to_id};
+
+/// A stand-in for a real object map which derives a Sha256 id from every Sha1 id.
+fn to_sha256(id: &oid) -> Option<ObjectId> {
+    let mut buf = [0; 32];
+    buf[..20].copy_from_slice(id.as_bytes());
+    Some(ObjectId::Sha256(buf))
+}
+
+fn to_sha1(id: &oid) -> Option<ObjectId> {
+    Some(ObjectId::from(&id.as_bytes()[..20]))
+}
+
+/// The signed tag fixture with its signature terminated by a newline, as `git` would write it.
+fn signed_tag() -> Vec<u8> {
+    let mut data = fixture_bytes("tag/signed.txt");
+    data.push(b'\n');
+    data
+}
+
+fn round_trip(kind: Kind, data: &[u8]) -> crate::Result<Vec<u8>> {
+    let mut sha256 = Vec::new();
+    convert::object_ids(kind, data, gix_hash::Kind::Sha1, &mut sha256, to_sha256)?;
+    assert_ne!(sha256, data, "the object ids changed");
+
+    let mut sha1 = Vec::new();
+    convert::object_ids(kind, &sha256, gix_hash::Kind::Sha256, &mut sha1, to_sha1)?;
+    assert_eq!(sha1.as_slice(), data, "everything else is kept as is");
+    Ok(sha256)
+}
+
+#[test]
+fn commit_tree_parents_and_mergetag() -> crate::Result {
+    let data = round_trip(Kind::Commit, &fixture_bytes("commit/mergetag.txt"))?;
+    let commit = CommitRef::from_bytes(&data)?;
+    assert_eq!(
+        commit.tree(),
+        to_sha256(&hex_to_id("1c61918031bf2c7fab9e17dde3c52a6a9884fcb5")).unwrap()
+    );
+    assert_eq!(
+        commit.parents().collect::<Vec<_>>(),
+        [
+            "44ebe016df3aad96e3be8f95ec52397728dd7701",
+            "8d485da0ddee79d0e6713405694253d401e41b93"
+        ]
+        .iter()
+        .map(|hex| to_sha256(&hex_to_id(hex)).unwrap())
+        .collect::<Vec<_>>()
+    );
+    let mergetag = commit.extra_headers().find("mergetag").expect("present");
+    assert!(mergetag.starts_with(
+        format!(
+            "object {}",
+            to_sha256(&hex_to_id("8d485da0ddee79d0e6713405694253d401e41b93")).unwrap()
+        )
+        .as_bytes()
+    ));
+    assert!(
+        mergetag.contains_str("\ngpgsig -----BEGIN PGP SIGNATURE-----\n"),
+        "merged tags are converted like tags, which moves their signature into a header"
+    );
+    Ok(())
+}
+
+#[test]
+fn signed_commit() -> crate::Result {
+    round_trip(Kind::Commit, &fixture_bytes("commit/signed.txt"))?;
+    Ok(())
+}
+
+#[test]
+fn tag_object_and_signature() -> crate::Result {
+    let sha1_data = signed_tag();
+    let data = round_trip(Kind::Tag, &sha1_data)?;
+    let sha1_tag = TagRef::from_bytes(&sha1_data)?;
+    let (headers, message) = data.split_once_str("\n\n").expect("headers and message");
+    assert_eq!(
+        headers.lines().next().expect("object header"),
+        format!(
+            "object {}",
+            to_sha256(&hex_to_id("ffa700b4aca13b80cb6b98a078e7c96804f8e0ec")).unwrap()
+        )
+        .as_bytes()
+    );
+    assert_eq!(
+        message, b"for the signature\n",
+        "the signature in the message belongs to the object using Sha1…"
+    );
+    let signature_header = headers.find("\ngpgsig ").expect("signature header") + 1;
+    assert_eq!(
+        headers[signature_header..].as_bstr(),
+        format!(
+            "gpgsig {}",
+            sha1_tag
+                .pgp_signature
+                .expect("signed")
+                .lines()
+                .map(|line| line.to_str_lossy())
+                .collect::<Vec<_>>()
+                .join("\n ")
+        ),
+        "…which is why it's moved into a header named after it, like `git` does it"
+    );
+    Ok(())
+}
+
+#[test]
+fn tag_signature_of_sha256_objects() -> crate::Result {
+    let sha1_data = signed_tag();
+    let target = to_sha256(&hex_to_id("ffa700b4aca13b80cb6b98a078e7c96804f8e0ec")).unwrap();
+    let mut data = format!("object {target}").into_bytes();
+    data.extend_from_slice(&sha1_data[sha1_data.find_byte(b'\n').expect("object header")..]);
+
+    let mut sha1 = Vec::new();
+    convert::object_ids(Kind::Tag, &data, gix_hash::Kind::Sha256, &mut sha1, to_sha1)?;
+    assert!(
+        sha1.contains_str("\ngpgsig-sha256 -----BEGIN PGP SIGNATURE-----\n Comment: GPGTools"),
+        "the signature in the message of a Sha256 object is moved into the corresponding header"
+    );
+    assert!(sha1.ends_with(b"\n\nfor the signature\n"));
+
+    let mut sha256 = Vec::new();
+    convert::object_ids(Kind::Tag, &sha1, gix_hash::Kind::Sha1, &mut sha256, to_sha256)?;
+    assert_eq!(sha256, data, "and it's moved back when converting the other way");
+    Ok(())
+}
+
+#[test]
+fn tree_entries() -> crate::Result {
+    let data = round_trip(Kind::Tree, &fixture_bytes("tree/everything.tree"))?;
+    let sha256 = TreeRef::from_bytes(&data, gix_hash::Kind::Sha256)?;
+    let sha1_data = fixture_bytes("tree/everything.tree");
+    let sha1 = TreeRef::from_bytes(&sha1_data, gix_hash::Kind::Sha1)?;
+    assert_eq!(sha256.entries.len(), sha1.entries.len());
+    for (sha256, sha1) in sha256.entries.iter().zip(sha1.entries.iter()) {
+        assert_eq!(sha256.filename, sha1.filename);
+        assert_eq!(sha256.mode, sha1.mode);
+        assert_eq!(sha256.oid.to_owned(), to_sha256(sha1.oid).unwrap());
+    }
+    Ok(())
+}
+
+#[test]
+fn blobs_are_copied() -> crate::Result {
+    let mut out = Vec::new();
+    convert::object_ids(Kind::Blob, b"content", gix_hash::Kind::Sha1, &mut out, |_| None)?;
+    assert_eq!(out, b"content");
+    Ok(())
+}
+
+#[test]
+fn unmapped_ids_are_an_error() {
+    let err = convert::object_ids(
+        Kind::Commit,
+        &fixture_bytes("commit/unsigned.txt"),
+        gix_hash::Kind::Sha1,
+        &mut Vec::new(),
+        |_| None,
+    )
+    .unwrap_err();
+    assert!(matches!(err, convert::Error::Unmapped { .. }));
+}
+
+#[test]
+fn object_ids_of_the_wrong_kind_are_malformed() {
+    let err = convert::object_ids(
+        Kind::Tag,
+        &fixture_bytes("tag/signed.txt"),
+        gix_hash::Kind::Sha256,
+        &mut Vec::new(),
+        to_sha1,
+    )
+    .unwrap_err();
+    assert!(matches!(err, convert::Error::Malformed { kind: Kind::Tag }));
+}
@@ -0,0 +1,256 @@
+//! A bidirectional map between the object ids of the repository's object format and the ones of its compatibility object format,
+//! as configured with `extensions.compatObjectFormat`.
+//!
+//! It's stored in `<objects-dir>/loose-object-idx` and looks as follows:
+//!
+//! ```text
+//! # loose-object-idx
+//! <object-id> <compat-object-id>
+//! ```
+//!
+//! Each line maps an object id in the object format of the repository to the id the same object has in the compatibility format.
+//!
+//! Based on the [canonical implementation](https://github.com/git/git/blob/master/loose.c).
+use std::{
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use gix_hash::{oid, ObjectId};
+use gix_hashtable::{HashMap, HashSet};
+use gix_object::bstr::{BString, ByteSlice};
+
+const HEADER: &[u8] = b"# loose-object-idx\n";
+/// How long to wait for other writers to release their lock on the map before giving up.
+const LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// A map between object ids of two different kinds of hashes which is backed by a file in the objects directory.
+#[derive(Debug, Clone)]
+pub struct ObjectMap {
+    path: PathBuf,
+    object_hash: gix_hash::Kind,
+    compat_hash: gix_hash::Kind,
+    to_compat: HashMap<ObjectId, ObjectId>,
+    from_compat: HashMap<ObjectId, ObjectId>,
+    /// The amount of bytes of the backing file that were read, which always ends with a complete line.
+    read_bytes: u64,
+    /// The amount of lines that were read, including the header.
+    read_lines: usize,
+}
+
+///
+pub mod init {
+    use crate::compat::BString;
+
+    /// The error returned by [`ObjectMap::at()`][super::ObjectMap::at()] and [`ObjectMap::refresh()`][super::ObjectMap::refresh()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("The loose object map didn't start with the expected header")]
+        Header,
+        #[error("Line {line_number} of the loose object map could not be parsed: {line:?}")]
+        Line { line_number: usize, line: BString },
+    }
+}
+
+///
+pub mod write {
+    /// The error returned by [`ObjectMap::insert()`][super::ObjectMap::insert()] and [`ObjectMap::extend()`][super::ObjectMap::extend()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Could not lock the loose object map for writing")]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error("Could not read the mappings written by others")]
+        Refresh(#[from] super::init::Error),
+    }
+}
+
+/// Initialization
+impl ObjectMap {
+    /// Load the map from the `objects_directory` of a repository using `object_hash`, mapping its ids to ones using `compat_hash`,
+    /// or start with an empty map if it doesn't exist yet.
+    ///
+    /// The ids of the empty tree and the empty blob are always known.
+    pub fn at(
+        objects_directory: impl AsRef<Path>,
+        object_hash: gix_hash::Kind,
+        compat_hash: gix_hash::Kind,
+    ) -> Result<Self, init::Error> {
+        let mut map = ObjectMap {
+            path: objects_directory.as_ref().join("loose-object-idx"),
+            object_hash,
+            compat_hash,
+            to_compat: Default::default(),
+            from_compat: Default::default(),
+            read_bytes: 0,
+            read_lines: 0,
+        };
+        map.insert_in_memory(ObjectId::empty_tree(object_hash), ObjectId::empty_tree(compat_hash));
+        map.insert_in_memory(ObjectId::empty_blob(object_hash), ObjectId::empty_blob(compat_hash));
+        map.refresh()?;
+        Ok(map)
+    }
+
+    /// Read all mappings that were added to the backing file since it was last read, possibly by other processes.
+    pub fn refresh(&mut self) -> Result<(), init::Error> {
+        match std::fs::File::open(&self.path) {
+            Ok(mut file) => self.read_new_lines(&mut file),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Read all complete lines that follow the ones we read previously from `file`.
+    fn read_new_lines(&mut self, file: &mut std::fs::File) -> Result<(), init::Error> {
+        let mut data = Vec::new();
+        file.seek(SeekFrom::Start(self.read_bytes))?;
+        file.read_to_end(&mut data)?;
+        // Writers may still be busy appending, so only complete lines are consumed.
+        let data = match data.rfind_byte(b'\n') {
+            Some(pos) => &data[..=pos],
+            None => return Ok(()),
+        };
+        let mut lines = data;
+        if self.read_lines == 0 {
+            lines = lines.strip_prefix(HEADER).ok_or(init::Error::Header)?;
+            self.read_lines = 1;
+        }
+        for line in lines.lines() {
+            self.read_lines += 1;
+            let (id, compat_id) = line
+                .split_once_str(b" ")
+                .and_then(|(id, compat_id)| {
+                    let id = ObjectId::from_hex(id).ok().filter(|id| id.kind() == self.object_hash)?;
+                    let compat_id = ObjectId::from_hex(compat_id)
+                        .ok()
+                        .filter(|id| id.kind() == self.compat_hash)?;
+                    Some((id, compat_id))
+                })
+                .ok_or_else(|| init::Error::Line {
+                    line_number: self.read_lines,
+                    line: line.into(),
+                })?;
+            self.insert_in_memory(id, compat_id);
+        }
+        self.read_bytes += data.len() as u64;
+        Ok(())
+    }
+}
+
+/// Access
+impl ObjectMap {
+    /// Return the path to the file backing this map.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the kind of hash used by the repository.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// Return the kind of hash used by the compatibility object format.
+    pub fn compat_hash(&self) -> gix_hash::Kind {
+        self.compat_hash
+    }
+
+    /// Return the id in the compatibility object format of the object with `id`, if known.
+    pub fn to_compat(&self, id: &oid) -> Option<ObjectId> {
+        self.to_compat.get(&id.to_owned()).copied()
+    }
+
+    /// Return the id in the object format of the repository of the object with `compat_id`, if known.
+    pub fn from_compat(&self, compat_id: &oid) -> Option<ObjectId> {
+        self.from_compat.get(&compat_id.to_owned()).copied()
+    }
+}
+
+/// Mutation
+impl ObjectMap {
+    /// Associate `id` with `compat_id` and append the pair to the backing file, creating it if needed.
+    ///
+    /// Return `false` if `id` was already known, in which case nothing is written.
+    ///
+    /// # Panics
+    ///
+    /// If the kinds of `id` or `compat_id` don't match the ones of this map.
+    pub fn insert(&mut self, id: ObjectId, compat_id: ObjectId) -> Result<bool, write::Error> {
+        Ok(self.extend(Some((id, compat_id)))? == 1)
+    }
+
+    /// Associate each id with its compatibility id as provided by `ids` and append all pairs that aren't known yet to the
+    /// backing file at once, while holding a lock on it, and return the amount of written pairs.
+    ///
+    /// Mappings written by others are [read][Self::refresh()] once the lock is held so no pair is written twice.
+    /// The data is synced to disk once, after all pairs were written, and nothing is written if all pairs are known.
+    ///
+    /// # Panics
+    ///
+    /// If the kinds of the ids don't match the ones of this map.
+    pub fn extend(&mut self, ids: impl IntoIterator<Item = (ObjectId, ObjectId)>) -> Result<usize, write::Error> {
+        let mut seen = HashSet::default();
+        let mut new_ids = Vec::new();
+        for (id, compat_id) in ids {
+            assert_eq!(id.kind(), self.object_hash, "BUG: id must be in the object format");
+            assert_eq!(
+                compat_id.kind(),
+                self.compat_hash,
+                "BUG: id must be in the compat object format"
+            );
+            if !self.to_compat.contains_key(&id) && seen.insert(id) {
+                new_ids.push((id, compat_id));
+            }
+        }
+        if new_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let _lock = gix_lock::Marker::acquire_to_hold_resource(
+            &self.path,
+            gix_lock::acquire::Fail::AfterDurationWithBackoff(LOCK_TIMEOUT),
+            None,
+        )?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        self.read_new_lines(&mut file)?;
+        new_ids.retain(|(id, _)| !self.to_compat.contains_key(id));
+        if new_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut lines = Vec::new();
+        let num_header_lines = usize::from(self.read_lines == 0);
+        if num_header_lines != 0 {
+            lines.extend_from_slice(HEADER);
+        }
+        for (id, compat_id) in &new_ids {
+            id.write_hex_to(&mut lines)?;
+            lines.push(b' ');
+            compat_id.write_hex_to(&mut lines)?;
+            lines.push(b'\n');
+        }
+        file.write_all(&lines)?;
+        file.sync_data()?;
+        self.read_bytes += lines.len() as u64;
+        self.read_lines += num_header_lines + new_ids.len();
+
+        let count = new_ids.len();
+        for (id, compat_id) in new_ids {
+            self.insert_in_memory(id, compat_id);
+        }
+        Ok(count)
+    }
+
+    fn insert_in_memory(&mut self, id: ObjectId, compat_id: ObjectId) {
+        self.to_compat.insert(id, compat_id);
+        self.from_compat.insert(compat_id, id);
+    }
+}
@@ -30,6 +30,8 @@ pub use store_impls::{dynamic as store, loose};
 
 pub mod alternate;
 
+pub mod compat;
+
 /// A way to access objects along with pre-configured thread-local caches for packed base objects as well as objects themselves.
 ///
 /// By default, no cache will be used.
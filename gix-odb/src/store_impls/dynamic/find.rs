@@ -157,6 +157,7 @@ where
                             Ok(r) => Ok((
                                 gix_object::Data {
                                     kind: r.kind,
+                                    object_hash: id.kind(),
                                     data: buffer.as_slice(),
                                 },
                                 Some(gix_pack::data::entry::Location {
@@ -256,6 +257,7 @@ where
                                     (
                                         gix_object::Data {
                                             kind: r.kind,
+                                            object_hash: id.kind(),
                                             data: buffer.as_slice(),
                                         },
                                         Some(gix_pack::data::entry::Location {
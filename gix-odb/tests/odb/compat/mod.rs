@@ -0,0 +1,142 @@
+use gix_hash::ObjectId;
+use gix_odb::compat::{init, ObjectMap};
+
+use crate::hex_to_id;
+
+fn sha256_id(hex: &str) -> ObjectId {
+    ObjectId::from_hex(hex.as_bytes()).expect("64 bytes hex")
+}
+
+#[test]
+fn empty_tree_and_blob_are_always_mapped() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let map = ObjectMap::at(dir.path(), gix_hash::Kind::Sha256, gix_hash::Kind::Sha1)?;
+    assert_eq!(
+        map.to_compat(&ObjectId::empty_tree(gix_hash::Kind::Sha256)),
+        Some(ObjectId::empty_tree(gix_hash::Kind::Sha1))
+    );
+    assert_eq!(
+        map.from_compat(&ObjectId::empty_blob(gix_hash::Kind::Sha1)),
+        Some(ObjectId::empty_blob(gix_hash::Kind::Sha256))
+    );
+    assert!(!map.path().exists(), "nothing is written until the first insertion");
+    Ok(())
+}
+
+#[test]
+fn insertions_are_persisted_and_can_be_looked_up_in_both_directions() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let id = sha256_id("e18941661c834f08aa0a19e626484916937df12c0e08d5f015b3b53d0284aa02");
+    let compat_id = hex_to_id("d95f3ad14dee633a758d2e331151e950dd13e4ed");
+
+    let mut map = ObjectMap::at(dir.path(), gix_hash::Kind::Sha256, gix_hash::Kind::Sha1)?;
+    assert!(map.insert(id, compat_id)?);
+    assert!(!map.insert(id, compat_id)?, "known ids aren't written again");
+    assert_eq!(
+        std::fs::read_to_string(map.path())?,
+        format!("# loose-object-idx\n{id} {compat_id}\n")
+    );
+
+    let map = ObjectMap::at(dir.path(), gix_hash::Kind::Sha256, gix_hash::Kind::Sha1)?;
+    assert_eq!(map.to_compat(&id), Some(compat_id));
+    assert_eq!(map.from_compat(&compat_id), Some(id));
+    assert_eq!(
+        map.to_compat(&hex_to_id("d95f3ad14dee633a758d2e331151e950dd13e4ed")),
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn extensions_write_all_unknown_pairs_at_once() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let id = sha256_id("e18941661c834f08aa0a19e626484916937df12c0e08d5f015b3b53d0284aa02");
+    let compat_id = hex_to_id("d95f3ad14dee633a758d2e331151e950dd13e4ed");
+    let empty_tree = ObjectId::empty_tree(gix_hash::Kind::Sha256);
+
+    let mut map = ObjectMap::at(dir.path(), gix_hash::Kind::Sha256, gix_hash::Kind::Sha1)?;
+    assert_eq!(
+        map.extend([(empty_tree, ObjectId::empty_tree(gix_hash::Kind::Sha1))])?,
+        0,
+        "nothing is known, so nothing is written"
+    );
+    assert!(!map.path().exists());
+    assert_eq!(
+        map.extend([
+            (id, compat_id),
+            (empty_tree, ObjectId::empty_tree(gix_hash::Kind::Sha1)),
+            (id, compat_id)
+        ])?,
+        1
+    );
+    assert_eq!(
+        std::fs::read_to_string(map.path())?,
+        format!("# loose-object-idx\n{id} {compat_id}\n")
+    );
+    assert!(
+        !dir.path().join("loose-object-idx.lock").exists(),
+        "the lock is released once the pairs are written"
+    );
+    Ok(())
+}
+
+#[test]
+fn mappings_of_other_writers_are_read_before_writing_and_on_refresh() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let (id, compat_id) = (
+        sha256_id("e18941661c834f08aa0a19e626484916937df12c0e08d5f015b3b53d0284aa02"),
+        hex_to_id("d95f3ad14dee633a758d2e331151e950dd13e4ed"),
+    );
+    let (other_id, other_compat_id) = (
+        sha256_id("8e3f2c1a9b7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e1f"),
+        hex_to_id("3b18e512dba79e4c8300dd08aeb37f8e728b8dad"),
+    );
+
+    let mut ours = ObjectMap::at(dir.path(), gix_hash::Kind::Sha256, gix_hash::Kind::Sha1)?;
+    let mut theirs = ObjectMap::at(dir.path(), gix_hash::Kind::Sha256, gix_hash::Kind::Sha1)?;
+    assert_eq!(theirs.extend([(id, compat_id)])?, 1);
+    assert_eq!(
+        ours.extend([(id, compat_id), (other_id, other_compat_id)])?,
+        1,
+        "pairs written by others aren't written again"
+    );
+    assert_eq!(
+        std::fs::read_to_string(ours.path())?,
+        format!("# loose-object-idx\n{id} {compat_id}\n{other_id} {other_compat_id}\n")
+    );
+    assert_eq!(ours.to_compat(&id), Some(compat_id), "they are known now");
+
+    assert_eq!(theirs.to_compat(&other_id), None);
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(theirs.path())
+        .and_then(|mut file| std::io::Write::write_all(&mut file, b"incomplete"))?;
+    theirs.refresh()?;
+    assert_eq!(theirs.from_compat(&other_compat_id), Some(other_id));
+    Ok(())
+}
+
+#[test]
+fn invalid_files_are_rejected() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("loose-object-idx");
+
+    std::fs::write(&path, "d95f3ad14dee633a758d2e331151e950dd13e4ed\n")?;
+    assert!(matches!(
+        ObjectMap::at(dir.path(), gix_hash::Kind::Sha256, gix_hash::Kind::Sha1),
+        Err(init::Error::Header)
+    ));
+
+    std::fs::write(
+        &path,
+        "# loose-object-idx\nd95f3ad14dee633a758d2e331151e950dd13e4ed d95f3ad14dee633a758d2e331151e950dd13e4ed\n",
+    )?;
+    assert!(
+        matches!(
+            ObjectMap::at(dir.path(), gix_hash::Kind::Sha256, gix_hash::Kind::Sha1),
+            Err(init::Error::Line { line_number: 2, .. })
+        ),
+        "the kind of each id has to match"
+    );
+    Ok(())
+}
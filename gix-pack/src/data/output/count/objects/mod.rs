@@ -229,7 +229,7 @@ mod expand {
                                     push_obj_count_unique(
                                         &mut out, seen_objs, &tree_id, location, progress, stats, true,
                                     );
-                                    gix_object::TreeRefIter::from_bytes(obj.data)
+                                    gix_object::TreeRefIter::from_bytes(obj.data, obj.object_hash)
                                 };
 
                                 let objects = if parent_commit_ids.is_empty() {
@@ -276,7 +276,10 @@ mod expand {
                                                 stats,
                                                 true,
                                             );
-                                            gix_object::TreeRefIter::from_bytes(parent_tree_obj.data)
+                                            gix_object::TreeRefIter::from_bytes(
+                                                parent_tree_obj.data,
+                                                parent_tree_obj.object_hash,
+                                            )
                                         };
 
                                         changes_delegate.clear();
@@ -312,7 +315,7 @@ mod expand {
                             Tree => {
                                 traverse_delegate.clear();
                                 gix_traverse::tree::breadthfirst(
-                                    gix_object::TreeRefIter::from_bytes(obj.0.data),
+                                    gix_object::TreeRefIter::from_bytes(obj.0.data, obj.0.object_hash),
                                     &mut tree_traversal_state,
                                     |oid, buf| {
                                         stats.decoded_objects += 1;
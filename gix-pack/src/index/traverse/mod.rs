@@ -220,7 +220,7 @@ where
         hasher.update(&gix_object::encode::loose_header(object_kind, decompressed.len()));
         hasher.update(decompressed);
 
-        let actual_oid = gix_hash::ObjectId::from(hasher.digest());
+        let actual_oid = hasher.digest();
         if actual_oid != index_entry.oid {
             return Err(Error::PackObjectMismatch {
                 actual: actual_oid,
@@ -113,12 +113,15 @@ mod with_io {
 
         /// Turns on all modern features for V1 and all supported features for V2, returning them as a vector of features.
         /// Note that this is the basis for any fetch operation as these features fulfil basic requirements and reasonably up-to-date servers.
+        ///
+        /// If the server advertises an `object-format` other than `sha1`, it will be requested as well as the server would otherwise
+        /// assume `sha1`.
         pub fn default_features(
             &self,
             version: gix_transport::Protocol,
             server_capabilities: &Capabilities,
         ) -> Vec<Feature> {
-            match self {
+            let mut features = match self {
                 Command::Fetch => match version {
                     gix_transport::Protocol::V1 => {
                         let has_multi_ack_detailed = server_capabilities.contains("multi_ack_detailed");
@@ -155,7 +158,15 @@ mod with_io {
                     }
                 },
                 Command::LsRefs => vec![],
+            };
+            if let Some(object_format) = server_capabilities
+                .capability("object-format")
+                .and_then(|c| c.value().map(|v| v.to_str_lossy().into_owned()))
+                .filter(|format| format != "sha1")
+            {
+                features.push(("object-format", Some(object_format.into())));
             }
+            features
         }
         /// Panics if the given arguments and features don't match what's statically known. It's considered a bug in the delegate.
         pub(crate) fn validate_argument_prefixes_or_panic(
@@ -200,7 +211,7 @@ mod with_io {
                             continue;
                         }
                         match *feature {
-                            "agent" => {}
+                            "agent" | "object-format" => {}
                             _ => panic!("{}: V2 feature/capability {} is not supported", self.as_str(), feature),
                         }
                     }
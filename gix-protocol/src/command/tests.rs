@@ -113,6 +113,21 @@ mod v2 {
                     &[]
                 );
             }
+
+            #[test]
+            fn object_format_is_requested_unless_it_is_sha1() {
+                assert_eq!(
+                    Command::LsRefs
+                        .default_features(gix_transport::Protocol::V2, &capabilities("object-format", "sha256")),
+                    &[("object-format", Some("sha256".into()))]
+                );
+                assert_eq!(
+                    Command::LsRefs
+                        .default_features(gix_transport::Protocol::V2, &capabilities("object-format", "sha1")),
+                    &[],
+                    "sha1 is the default and doesn't have to be requested"
+                );
+            }
         }
 
         mod validate {
@@ -14,6 +14,8 @@
 //!     * one reference maps to a file on disk
 //!   * **packed**
 //!     * references are stored in a single human-readable file, along with their targets if they are symbolic.
+//! * **[reftable][reftable::Store]**
+//!   * references and their logs are stored in a stack of binary tables
 //!
 //! ## Feature Flags
 #![cfg_attr(
@@ -31,7 +33,7 @@ use gix_object::bstr::{BStr, BString};
 
 #[path = "store/mod.rs"]
 mod store_impl;
-pub use store_impl::{file, packed};
+pub use store_impl::{file, packed, reftable};
 
 mod fullname;
 ///
@@ -75,16 +77,15 @@ pub mod store {
 
     /// A thread-local handle for interacting with a [`Store`][crate::Store] to find and iterate references.
     #[derive(Clone)]
-    #[allow(dead_code)]
-    pub(crate) struct Handle {
+    pub struct Handle {
         /// A way to access shared state with the requirement that interior mutability doesn't leak or is incorporated into error types
         /// if it could. The latter can't happen if references to said internal aren't ever returned.
         state: handle::State,
     }
 
-    #[allow(dead_code)]
     pub(crate) enum State {
         Loose { store: file::Store },
+        Reftable { store: reftable::Store },
     }
 
     pub(crate) mod general;
@@ -92,15 +93,16 @@ pub mod store {
     ///
     #[path = "general/handle/mod.rs"]
     mod handle;
-    pub use handle::find;
+    pub use general::init;
+    pub use handle::{find, iter, reflog, transaction};
 
-    use crate::file;
+    use crate::{file, reftable};
 }
 
-/// The git reference store.
-/// TODO: Figure out if handles are needed at all, which depends on the ref-table implementation.
-#[allow(dead_code)]
-pub(crate) struct Store {
+/// The git reference store, which is backed by either loose and packed reference files, or by a stack of reftables.
+///
+/// Use [`Store::to_handle()`] to obtain a handle to find and iterate references, and to change them in transactions.
+pub struct Store {
     inner: store::State,
 }
 
@@ -20,6 +20,8 @@ pub mod to_id {
     pub enum Error {
         #[error("Could not follow a single level of a symbolic reference")]
         Follow(#[from] file::find::existing::Error),
+        #[error("Could not follow a single level of a symbolic reference in a reftable")]
+        FollowReftable(#[from] crate::reftable::find::existing::Error),
         #[error("Aborting due to reference cycle with first seen path being {start_absolute:?}")]
         Cycle { start_absolute: PathBuf },
         #[error("Refusing to follow more than {max_depth} levels of indirection")]
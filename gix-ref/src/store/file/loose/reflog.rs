@@ -75,6 +75,115 @@ impl file::Store {
     }
 }
 
+///
+pub mod retain {
+    use std::{convert::TryInto, io::Write};
+
+    use crate::{
+        store_impl::{file, file::log},
+        FullNameRef,
+    };
+
+    impl file::Store {
+        /// Rewrite the reflog of the reference `name` to only contain the lines for which `keep` returns true, passing them
+        /// from oldest to most recent, and return the amount of removed lines.
+        ///
+        /// The reflog is locked while it is rewritten, using `lock_mode` to determine what to do if the lock is already taken.
+        /// Lines that can't be parsed are kept, and nothing happens if no reflog exists.
+        pub fn reflog_retain<'a, Name, E>(
+            &self,
+            name: Name,
+            lock_mode: gix_lock::acquire::Fail,
+            keep: impl FnMut(&log::LineRef<'_>) -> bool,
+        ) -> Result<usize, Error>
+        where
+            Name: TryInto<&'a FullNameRef, Error = E>,
+            crate::name::Error: From<E>,
+        {
+            let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+            self.retain(name, lock_mode, false, keep)
+        }
+
+        /// Like [`reflog_retain()`][file::Store::reflog_retain()], but also point the reference `name` to the new value of
+        /// the most recent line that is kept, similar to `git reflog delete --updateref`.
+        ///
+        /// The reference is locked along with its reflog so both change together, and it is left untouched if no
+        /// line is kept.
+        pub fn reflog_retain_and_update_ref<'a, Name, E>(
+            &self,
+            name: Name,
+            lock_mode: gix_lock::acquire::Fail,
+            keep: impl FnMut(&log::LineRef<'_>) -> bool,
+        ) -> Result<usize, Error>
+        where
+            Name: TryInto<&'a FullNameRef, Error = E>,
+            crate::name::Error: From<E>,
+        {
+            let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+            self.retain(name, lock_mode, true, keep)
+        }
+
+        fn retain(
+            &self,
+            name: &FullNameRef,
+            lock_mode: gix_lock::acquire::Fail,
+            update_ref: bool,
+            mut keep: impl FnMut(&log::LineRef<'_>) -> bool,
+        ) -> Result<usize, Error> {
+            let path = self.reflog_path(name);
+            if !path.is_file() {
+                return Ok(0);
+            }
+            let ref_lock = update_ref
+                .then(|| {
+                    gix_lock::File::acquire_to_update_resource(
+                        self.reference_path(name),
+                        lock_mode,
+                        Some(self.git_dir.clone()),
+                    )
+                })
+                .transpose()?;
+            let mut lock = gix_lock::File::acquire_to_update_resource(&path, lock_mode, None)?;
+            let content = std::fs::read(&path)?;
+            let mut removed = 0;
+            let mut last_kept = None;
+            for line in content.split_inclusive(|b| *b == b'\n') {
+                let parsed = log::LineRef::from_bytes(line.strip_suffix(b"\n").unwrap_or(line));
+                let is_kept = parsed.as_ref().map_or(true, &mut keep);
+                if is_kept {
+                    if let Ok(line) = parsed {
+                        last_kept = Some(line.new_oid());
+                    }
+                    lock.write_all(line)?;
+                } else {
+                    removed += 1;
+                }
+            }
+            if removed == 0 {
+                return Ok(0);
+            }
+            lock.commit().map_err(|err| err.error)?;
+            if let Some((mut ref_lock, id)) = ref_lock.zip(last_kept) {
+                writeln!(ref_lock, "{id}")?;
+                ref_lock.commit().map_err(|err| err.error)?;
+            }
+            Ok(removed)
+        }
+    }
+
+    /// The error returned by [`file::Store::reflog_retain()`].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reflog name or path is not a valid ref name")]
+        RefnameValidation(#[from] crate::name::Error),
+        #[error("The reflog could not be locked")]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error("The reflog could not be read or written")]
+        Io(#[from] std::io::Error),
+    }
+}
+
 impl file::Store {
     /// Implements the logic required to transform a fully qualified refname into its log name
     pub(crate) fn reflog_path(&self, name: &FullNameRef) -> PathBuf {
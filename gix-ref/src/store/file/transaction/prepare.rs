@@ -391,6 +391,14 @@ impl<'s, 'p> Transaction<'s, 'p> {
         Ok(self)
     }
 
+    /// Return the `RefEdits` of a prepared transaction, or nothing if it wasn't prepared yet.
+    ///
+    /// Note that they have been altered compared to what was initially provided as they have
+    /// been split and know about their current state on disk, just like the edits returned by [`rollback()`][Self::rollback()].
+    pub fn edits(&self) -> impl Iterator<Item = &RefEdit> + '_ {
+        self.updates.iter().flatten().map(|edit| &edit.update)
+    }
+
     /// Rollback all intermediate state and return the `RefEdits` as we know them thus far.
     ///
     /// Note that they have been altered compared to what was initially provided as they have
@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use crate::{file, reftable, store, store::handle, Reference};
+
+/// An intermediate structure to obtain iterators over all references of a [`Handle`][store::Handle], or prefixed ones.
+#[must_use = "Iterators should be obtained from this platform"]
+pub enum Platform<'s> {
+    /// References are stored in loose files and a packed-refs file.
+    Loose(file::iter::Platform<'s>),
+    /// References are stored in a stack of reftables.
+    Reftable(reftable::iter::Platform<'s>),
+}
+
+/// An iterator over references sorted by their name, regardless of the backend used to store them.
+#[allow(clippy::large_enum_variant)]
+pub enum Iter<'p, 's> {
+    /// Iterate over loose and packed references.
+    Loose(file::iter::LooseThenPacked<'p, 's>),
+    /// Iterate over references in a snapshot of a stack of reftables.
+    Reftable(reftable::iter::Iter<'p>),
+}
+
+mod error {
+    /// The error returned by [crate::store::Handle::iter()] and the iterators it produces.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The packed-refs file could not be opened")]
+        PackedOpen(#[from] crate::packed::buffer::open::Error),
+        #[error("The loose references could not be traversed")]
+        Io(#[from] std::io::Error),
+        #[error("An error occurred while iterating loose or packed references")]
+        Loose(#[from] crate::file::iter::loose_then_packed::Error),
+        #[error("An error occurred while iterating references in the reftable")]
+        Reftable(#[from] crate::reftable::find::Error),
+    }
+}
+pub use error::Error;
+
+impl<'s> Platform<'s> {
+    /// Return an iterator over all references, loose or packed, sorted by their name.
+    pub fn all(&self) -> Result<Iter<'_, '_>, Error> {
+        Ok(match self {
+            Platform::Loose(platform) => Iter::Loose(platform.all()?),
+            Platform::Reftable(platform) => Iter::Reftable(platform.all()),
+        })
+    }
+
+    /// As [`all()`][Platform::all()], but filters by `prefix`, i.e. "refs/heads".
+    ///
+    /// Please note that "refs/heads` or "refs\\heads" is equivalent to "refs/heads/"
+    pub fn prefixed(&self, prefix: impl AsRef<Path>) -> Result<Iter<'_, '_>, Error> {
+        Ok(match self {
+            Platform::Loose(platform) => Iter::Loose(platform.prefixed(prefix)?),
+            Platform::Reftable(platform) => Iter::Reftable(platform.prefixed(prefix)),
+        })
+    }
+}
+
+impl<'p, 's> Iterator for Iter<'p, 's> {
+    type Item = Result<Reference, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Loose(iter) => iter.next().map(|res| res.map_err(Into::into)),
+            Iter::Reftable(iter) => iter.next().map(|res| res.map_err(Into::into)),
+        }
+    }
+}
+
+impl store::Handle {
+    /// Return a platform to obtain iterators over all references, or prefixed ones, sorted by their name.
+    pub fn iter(&self) -> Result<Platform<'_>, Error> {
+        Ok(match &self.state {
+            handle::State::Loose { store } => Platform::Loose(store.iter()?),
+            handle::State::Reftable { store } => Platform::Reftable(store.iter()?),
+        })
+    }
+}
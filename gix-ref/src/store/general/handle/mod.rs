@@ -1,9 +1,11 @@
-#![allow(dead_code)]
-use crate::{store, Namespace};
+use std::path::Path;
+
+use crate::{file, reftable, store, store::WriteReflog, Namespace};
 
 #[derive(Clone)]
 pub(crate) enum State {
     Loose { store: crate::file::Store },
+    Reftable { store: crate::reftable::Store },
 }
 
 impl crate::Store {
@@ -18,6 +20,11 @@ impl crate::Store {
         Self::new_handle_inner(&self.inner, namespace)
     }
 
+    /// Return true if references are stored in a stack of reftables.
+    pub fn is_reftable(&self) -> bool {
+        matches!(self.inner, store::State::Reftable { .. })
+    }
+
     fn new_handle_inner(state: &store::State, namespace: Option<Namespace>) -> store::Handle {
         store::Handle {
             state: match state {
@@ -28,16 +35,102 @@ impl crate::Store {
                         store
                     },
                 },
+                store::State::Reftable { store } => store::handle::State::Reftable {
+                    store: {
+                        let mut store = store.clone();
+                        store.namespace = namespace;
+                        store
+                    },
+                },
             },
         }
     }
 }
 
+impl From<file::Store> for store::Handle {
+    fn from(store: file::Store) -> Self {
+        store::Handle {
+            state: State::Loose { store },
+        }
+    }
+}
+
+impl From<reftable::Store> for store::Handle {
+    fn from(store: reftable::Store) -> Self {
+        store::Handle {
+            state: State::Reftable { store },
+        }
+    }
+}
+
+/// Access
+impl store::Handle {
+    /// Return the `.git` directory at which references are loaded, which is private to a work-tree if it is linked.
+    pub fn git_dir(&self) -> &Path {
+        match &self.state {
+            State::Loose { store } => store.git_dir(),
+            State::Reftable { store } => store.git_dir(),
+        }
+    }
+
+    /// Return the namespace all reads and writes are limited to, if set.
+    pub fn namespace(&self) -> Option<&Namespace> {
+        match &self.state {
+            State::Loose { store } => store.namespace.as_ref(),
+            State::Reftable { store } => store.namespace.as_ref(),
+        }
+    }
+
+    /// Limit all reads and writes to `namespace`, or see all references if `None`, and return the previous namespace.
+    pub fn set_namespace(&mut self, namespace: Option<Namespace>) -> Option<Namespace> {
+        match &mut self.state {
+            State::Loose { store } => std::mem::replace(&mut store.namespace, namespace),
+            State::Reftable { store } => std::mem::replace(&mut store.namespace, namespace),
+        }
+    }
+
+    /// Return the way reflogs are written when references are edited.
+    pub fn write_reflog(&self) -> WriteReflog {
+        match &self.state {
+            State::Loose { store } => store.write_reflog,
+            State::Reftable { store } => store.write_reflog,
+        }
+    }
+
+    /// Set the way reflogs are written when references are edited to `mode`.
+    pub fn set_write_reflog(&mut self, mode: WriteReflog) {
+        match &mut self.state {
+            State::Loose { store } => store.write_reflog = mode,
+            State::Reftable { store } => store.write_reflog = mode,
+        }
+    }
+
+    /// Return true if references are stored in a stack of reftables.
+    pub fn is_reftable(&self) -> bool {
+        matches!(self.state, State::Reftable { .. })
+    }
+
+    /// Return the store of loose references and the packed-refs file, or `None` if references are stored in reftables.
+    ///
+    /// Use it only for operations that are specific to this backend.
+    pub fn file_store(&self) -> Option<&file::Store> {
+        match &self.state {
+            State::Loose { store } => Some(store),
+            State::Reftable { .. } => None,
+        }
+    }
+}
+
 ///
 pub mod find;
 
-mod iter {
-    // impl store::Handle {
-    //     pub fn iter<'p, 's>(&'s self, packed: Option<&'p packed::Buffer>) -> std::io::Result<LooseThenPacked<'p, 's>> {
-    // }
-}
+///
+pub mod iter;
+
+mod peel;
+
+///
+pub mod reflog;
+
+///
+pub mod transaction;
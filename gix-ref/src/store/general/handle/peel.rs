@@ -0,0 +1,20 @@
+use gix_hash::ObjectId;
+
+use crate::{file::ReferenceExt, peel, store, store::handle, Reference};
+
+impl store::Handle {
+    /// Follow all symbolic targets of `reference` and peel the object it ultimately points to until a non-tag object
+    /// is found, using `find` to lookup objects, and store the result in `reference`.
+    ///
+    /// See [`ReferenceExt::peel_to_id_in_place()`] for details.
+    pub fn peel_to_id_in_place<E: std::error::Error + Send + Sync + 'static>(
+        &self,
+        reference: &mut Reference,
+        find: impl FnMut(ObjectId, &mut Vec<u8>) -> Result<Option<(gix_object::Kind, &[u8])>, E>,
+    ) -> Result<ObjectId, peel::to_id::Error> {
+        match &self.state {
+            handle::State::Loose { store } => reference.peel_to_id_in_place(store, find),
+            handle::State::Reftable { store } => store.peel_to_id_in_place(reference, find),
+        }
+    }
+}
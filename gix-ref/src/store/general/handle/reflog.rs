@@ -0,0 +1,134 @@
+use crate::{file, log::Line, store, store::handle, FullNameRef};
+
+/// A platform to obtain iterators over the reflog of a reference in a [`Handle`][store::Handle], regardless of the backend
+/// used to store it.
+pub struct Platform<'a, 's> {
+    /// The store containing the reflogs.
+    pub store: &'s store::Handle,
+    /// The full name of the reference whose reflog to retrieve.
+    pub name: &'a FullNameRef,
+    /// A reusable buffer for storing log lines.
+    pub buf: Vec<u8>,
+}
+
+/// An iterator over reflog lines from most recent to oldest, regardless of the backend used to store them.
+pub enum Reverse<'a> {
+    /// Lines read in chunks from the back of a reflog file.
+    Loose(file::log::iter::Reverse<'a, std::fs::File>),
+    /// Lines of the reflog in a stack of reftables.
+    Reftable(std::iter::Rev<std::vec::IntoIter<Line>>),
+}
+
+mod error {
+    use crate::{file, reftable};
+
+    /// The error returned by [`Handle`][crate::store::Handle] methods to read or rewrite reflogs.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reflog file could not be read")]
+        Loose(#[from] file::log::Error),
+        #[error("The reflog file could not be rewritten")]
+        LooseRetain(#[from] file::log::retain::Error),
+        #[error("The reflog could not be read from the reftable")]
+        Reftable(#[from] reftable::find::Error),
+        #[error("The reflog could not be rewritten in the reftable")]
+        ReftableRetain(#[from] reftable::log::retain::Error),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+    }
+}
+pub use error::Error;
+
+impl<'a, 's> Platform<'a, 's> {
+    /// Return a reverse iterator over all log-lines, most recent to oldest, or `None` if there is no reflog.
+    pub fn rev(&mut self) -> Result<Option<Reverse<'_>>, Error> {
+        Ok(match &self.store.state {
+            handle::State::Loose { store } => {
+                self.buf.clear();
+                self.buf.resize(512, 0);
+                store.reflog_iter_rev(self.name, &mut self.buf)?.map(Reverse::Loose)
+            }
+            handle::State::Reftable { store } => store
+                .reflog(self.name)?
+                .map(|lines| Reverse::Reftable(lines.into_iter().rev())),
+        })
+    }
+
+    /// Return a forward iterator over all log-lines, oldest to most recent, or `None` if there is no reflog.
+    pub fn all(&mut self) -> Result<Option<file::log::iter::Forward<'_>>, Error> {
+        self.buf.clear();
+        Ok(match &self.store.state {
+            handle::State::Loose { store } => store.reflog_iter(self.name, &mut self.buf)?,
+            handle::State::Reftable { store } => match store.reflog(self.name)? {
+                Some(lines) => {
+                    for line in lines {
+                        line.write_to(&mut self.buf)?;
+                    }
+                    Some(file::log::iter::forward(&self.buf))
+                }
+                None => None,
+            },
+        })
+    }
+}
+
+impl<'a> Iterator for Reverse<'a> {
+    type Item = Result<Line, file::log::iter::reverse::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Reverse::Loose(iter) => iter.next(),
+            Reverse::Reftable(iter) => iter.next().map(Ok),
+        }
+    }
+}
+
+impl store::Handle {
+    /// Return a platform for obtaining iterators over the reflog of the reference with the full `name`.
+    pub fn log_iter<'a>(&self, name: &'a FullNameRef) -> Platform<'a, '_> {
+        Platform {
+            store: self,
+            name,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Return true if the reference with the full `name` has a reflog.
+    pub fn reflog_exists(&self, name: &FullNameRef) -> Result<bool, Error> {
+        Ok(match &self.state {
+            handle::State::Loose { store } => store.reflog_exists(name).expect("infallible name conversion"),
+            handle::State::Reftable { store } => store.reflog_exists(name)?,
+        })
+    }
+
+    /// Rewrite the reflog of the reference with the full `name` to only contain the lines for which `keep` returns true,
+    /// passing them from oldest to most recent, and return the amount of removed lines.
+    ///
+    /// `lock_mode` determines what to do if the reflog or the stack of reftables is already locked.
+    pub fn reflog_retain(
+        &self,
+        name: &FullNameRef,
+        lock_mode: gix_lock::acquire::Fail,
+        keep: impl FnMut(&file::log::LineRef<'_>) -> bool,
+    ) -> Result<usize, Error> {
+        Ok(match &self.state {
+            handle::State::Loose { store } => store.reflog_retain(name, lock_mode, keep)?,
+            handle::State::Reftable { store } => store.reflog_retain(name, lock_mode, keep)?,
+        })
+    }
+
+    /// Like [`reflog_retain()`][store::Handle::reflog_retain()], but also point the reference to the new value of the most
+    /// recent line that is kept.
+    pub fn reflog_retain_and_update_ref(
+        &self,
+        name: &FullNameRef,
+        lock_mode: gix_lock::acquire::Fail,
+        keep: impl FnMut(&file::log::LineRef<'_>) -> bool,
+    ) -> Result<usize, Error> {
+        Ok(match &self.state {
+            handle::State::Loose { store } => store.reflog_retain_and_update_ref(name, lock_mode, keep)?,
+            handle::State::Reftable { store } => store.reflog_retain_and_update_ref(name, lock_mode, keep)?,
+        })
+    }
+}
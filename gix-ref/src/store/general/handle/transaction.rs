@@ -0,0 +1,85 @@
+use crate::{file, reftable, store, store::handle, transaction::RefEdit};
+
+/// A transaction on a [`Handle`][store::Handle], regardless of the backend used to store references.
+#[derive(Debug)]
+pub enum Transaction<'s> {
+    /// A transaction on loose and packed references.
+    Loose(file::Transaction<'s, 's>),
+    /// A transaction which adds a table to a stack of reftables.
+    Reftable(reftable::Transaction<'s>),
+}
+
+mod error {
+    /// The error returned by [`Transaction::prepare()`][super::Transaction::prepare()] and
+    /// [`Transaction::commit()`][super::Transaction::commit()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        LoosePrepare(#[from] crate::file::transaction::prepare::Error),
+        #[error(transparent)]
+        LooseCommit(#[from] crate::file::transaction::commit::Error),
+        #[error(transparent)]
+        ReftablePrepare(#[from] crate::reftable::transaction::prepare::Error),
+        #[error(transparent)]
+        ReftableCommit(#[from] crate::reftable::transaction::commit::Error),
+    }
+}
+pub use error::Error;
+
+impl<'s> Transaction<'s> {
+    /// Prepare for calling [`commit(…)`][Transaction::commit()] by validating all `edits`, and determine how to fail
+    /// if a lock cannot be obtained with `lock_fail_mode`.
+    pub fn prepare(
+        self,
+        edits: impl IntoIterator<Item = RefEdit>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+    ) -> Result<Self, Error> {
+        Ok(match self {
+            Transaction::Loose(t) => Transaction::Loose(t.prepare(edits, lock_fail_mode, lock_fail_mode)?),
+            Transaction::Reftable(t) => Transaction::Reftable(t.prepare(edits, lock_fail_mode)?),
+        })
+    }
+
+    /// Make all [prepared][Transaction::prepare()] edits permanent and return them, using `committer` for the reflog.
+    pub fn commit<'a>(self, committer: impl Into<Option<gix_actor::SignatureRef<'a>>>) -> Result<Vec<RefEdit>, Error> {
+        Ok(match self {
+            Transaction::Loose(t) => t.commit(committer)?,
+            Transaction::Reftable(t) => t.commit(committer)?,
+        })
+    }
+
+    /// Configure how the packed-refs file is handled if references are stored in loose files, which is ignored for reftables.
+    pub fn packed_refs(self, packed_refs: file::transaction::PackedRefs<'s>) -> Self {
+        match self {
+            Transaction::Loose(t) => Transaction::Loose(t.packed_refs(packed_refs)),
+            Transaction::Reftable(t) => Transaction::Reftable(t),
+        }
+    }
+
+    /// Return the `RefEdits` of a prepared transaction, or nothing if it wasn't prepared yet.
+    pub fn edits(&self) -> Box<dyn Iterator<Item = &RefEdit> + '_> {
+        match self {
+            Transaction::Loose(t) => Box::new(t.edits()),
+            Transaction::Reftable(t) => Box::new(t.edits()),
+        }
+    }
+
+    /// Rollback all intermediate state and return the `RefEdits` as we know them thus far.
+    pub fn rollback(self) -> Vec<RefEdit> {
+        match self {
+            Transaction::Loose(t) => t.rollback(),
+            Transaction::Reftable(t) => t.rollback(),
+        }
+    }
+}
+
+impl store::Handle {
+    /// Open a transaction to apply edits to references, which inherits the namespace of this handle.
+    pub fn transaction(&self) -> Transaction<'_> {
+        match &self.state {
+            handle::State::Loose { store } => Transaction::Loose(store.transaction()),
+            handle::State::Reftable { store } => Transaction::Reftable(store.transaction()),
+        }
+    }
+}
@@ -14,25 +14,31 @@ mod error {
 
 pub use error::Error;
 
-use crate::file;
+use crate::{file, reftable};
 
-#[allow(dead_code)]
 impl crate::Store {
     /// Create a new store at the given location, typically the `.git/` directory.
     ///
+    /// If the directory contains a `reftable` directory, all references are kept in a stack of reftables, otherwise
+    /// they are stored in loose files and a `packed-refs` file.
+    ///
     /// `object_hash` defines the kind of hash to assume when dealing with refs.
     pub fn at(
         git_dir: impl Into<PathBuf>,
         reflog_mode: WriteReflog,
         object_hash: gix_hash::Kind,
     ) -> Result<Self, Error> {
-        // for now, just try to read the directory - later we will do that naturally as we have to figure out if it's a ref-table or not.
         let git_dir = git_dir.into();
         std::fs::read_dir(&git_dir)?;
-        Ok(crate::Store {
-            inner: crate::store::State::Loose {
+        let inner = if git_dir.join("reftable").is_dir() {
+            crate::store::State::Reftable {
+                store: reftable::Store::at(git_dir, reflog_mode, object_hash),
+            }
+        } else {
+            crate::store::State::Loose {
                 store: file::Store::at(git_dir, reflog_mode, object_hash),
-            },
-        })
+            }
+        };
+        Ok(crate::Store { inner })
     }
 }
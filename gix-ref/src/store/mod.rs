@@ -3,3 +3,6 @@ pub mod file;
 
 ///
 pub mod packed;
+
+///
+pub mod reftable;
@@ -0,0 +1,354 @@
+//! Reading of the blocks that make up the sections of a table.
+use std::borrow::Cow;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::BString,
+    store_impl::reftable::{decode::Error, varint, Log, Ref, RefValue, Table},
+};
+
+pub(crate) const REF: u8 = b'r';
+pub(crate) const LOG: u8 = b'g';
+pub(crate) const OBJ: u8 = b'o';
+pub(crate) const INDEX: u8 = b'i';
+
+/// The size of the block header, consisting of its type and 24 bit length.
+pub(crate) const HEADER_LEN: usize = 4;
+
+/// A single block, with the file header removed if it is the first block of its table.
+pub(crate) struct Block<'a> {
+    pub typ: u8,
+    /// All data of the block from its type byte up to the restart table, uncompressed.
+    data: Cow<'a, [u8]>,
+    /// The offset of the first restart point in `data`.
+    restarts_offset: usize,
+    num_restarts: usize,
+    /// The amount of bytes to subtract from restart offsets, as these count from the beginning of the table
+    /// for the first block.
+    header_off: usize,
+    /// The offset of the block following this one in the table.
+    pub next_offset: usize,
+}
+
+impl Table {
+    /// Read the block at `offset`, or return `None` if there is no block as the end of all sections was reached.
+    pub(crate) fn block_at(&self, offset: usize) -> Result<Option<Block<'_>>, Error> {
+        let header_off = if offset == 0 { self.header_len() } else { 0 };
+        let start = offset + header_off;
+        if start >= self.footer_offset {
+            return Ok(None);
+        }
+        let header = self
+            .data
+            .get(start..start + HEADER_LEN)
+            .filter(|_| start + HEADER_LEN <= self.footer_offset)
+            .ok_or(Error::Truncated)?;
+        let typ = header[0];
+        let block_len = be24(&header[1..]);
+        if typ == 0 {
+            // Padding, there are no more blocks in this section.
+            return Ok(None);
+        }
+        if !matches!(typ, REF | LOG | OBJ | INDEX) {
+            return Err(Error::UnknownBlockType { typ, offset });
+        }
+        if block_len < header_off + HEADER_LEN + 2 {
+            return Err(Error::Truncated);
+        }
+
+        let (data, next_offset) = if typ == LOG {
+            let mut data = vec![0; block_len - header_off];
+            data[..HEADER_LEN].copy_from_slice(header);
+            let compressed = &self.data[start + HEADER_LEN..self.footer_offset];
+            let mut inflate = gix_features::zlib::Inflate::default();
+            let (status, consumed_in, consumed_out) = inflate.once(compressed, &mut data[HEADER_LEN..])?;
+            if status != gix_features::zlib::Status::StreamEnd || consumed_out != data.len() - HEADER_LEN {
+                return Err(Error::Truncated);
+            }
+            (Cow::Owned(data), start + HEADER_LEN + consumed_in)
+        } else {
+            let end = offset + block_len;
+            if end > self.footer_offset {
+                return Err(Error::Truncated);
+            }
+            let block_size = self.block_size as usize;
+            // Like git, we detect unaligned blocks by the absence of padding.
+            let next_offset = if block_size == 0 || block_len >= block_size || self.data[end] != 0 {
+                end
+            } else {
+                offset + block_size
+            };
+            (Cow::Borrowed(&self.data[start..end]), next_offset)
+        };
+
+        let data_len = data.len();
+        let num_restarts = be16(&data[data_len - 2..]);
+        let restarts_offset = data_len
+            .checked_sub(2 + num_restarts * 3)
+            .filter(|offset| *offset >= HEADER_LEN)
+            .ok_or(Error::Truncated)?;
+        Ok(Some(Block {
+            typ,
+            data,
+            restarts_offset,
+            num_restarts,
+            header_off,
+            next_offset,
+        }))
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Return the offset of the record at restart point `idx`, relative to our data.
+    fn restart(&self, idx: usize) -> Result<usize, Error> {
+        let pos = self.restarts_offset + idx * 3;
+        be24(&self.data[pos..pos + 3])
+            .checked_sub(self.header_off)
+            .filter(|offset| (HEADER_LEN..self.restarts_offset).contains(offset))
+            .ok_or(Error::Truncated)
+    }
+
+    /// Return an iterator over all records in this block, starting at the first one.
+    pub fn records(&self, table: &'a Table) -> Records<'_> {
+        Records {
+            block: self,
+            table,
+            pos: HEADER_LEN,
+            key: Vec::new(),
+        }
+    }
+
+    /// Return an iterator over all records in this block which starts at the record with the greatest key that is smaller than
+    /// or equal to `key`, using the restart points to find it, or at the first record if there is none.
+    pub fn records_at_or_before(&self, table: &'a Table, key: &[u8]) -> Result<Records<'_>, Error> {
+        let (mut lo, mut hi) = (0, self.num_restarts);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let mut records = self.records(table);
+            records.pos = self.restart(mid)?;
+            records.read_key()?;
+            if records.key.as_slice() <= key {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut records = self.records(table);
+        if lo > 0 {
+            records.pos = self.restart(lo - 1)?;
+        }
+        Ok(records)
+    }
+
+    /// Return the key of the first record in this block.
+    pub fn first_key(&self, table: &'a Table) -> Result<Vec<u8>, Error> {
+        let mut records = self.records(table);
+        records.read_key()?;
+        Ok(records.key)
+    }
+
+    /// Return the offset of the block pointed to by the first index record in this index block whose key is greater than
+    /// or equal to `key`, or `None` if all keys are smaller.
+    pub fn seek_index(&self, table: &'a Table, key: &[u8]) -> Result<Option<usize>, Error> {
+        let mut records = self.records_at_or_before(table, key)?;
+        while let Some(offset) = records.next_index()? {
+            if records.key() >= key {
+                return Ok(Some(offset));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The records of a block, with the key of each record decoded by means of the previous one.
+pub(crate) struct Records<'a> {
+    block: &'a Block<'a>,
+    table: &'a Table,
+    pos: usize,
+    key: Vec<u8>,
+}
+
+impl<'a> Records<'a> {
+    fn data(&self) -> &'a [u8] {
+        &self.block.data[..self.block.restarts_offset]
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let (value, consumed) = varint::decode(&self.data()[self.pos..]).ok_or(Error::Truncated)?;
+        self.pos += consumed;
+        Ok(value)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let data = self.data();
+        let bytes = data.get(self.pos..self.pos + len).ok_or(Error::Truncated)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn id(&mut self) -> Result<ObjectId, Error> {
+        Ok(ObjectId::from(self.bytes(self.table.object_hash.len_in_bytes())?))
+    }
+
+    fn string(&mut self) -> Result<BString, Error> {
+        let len = self.varint()? as usize;
+        Ok(self.bytes(len)?.into())
+    }
+
+    /// Read the key of the next record and return its value type.
+    fn read_key(&mut self) -> Result<u8, Error> {
+        let prefix_len = self.varint()? as usize;
+        let suffix_len_and_type = self.varint()?;
+        if prefix_len > self.key.len() {
+            return Err(Error::Truncated);
+        }
+        self.key.truncate(prefix_len);
+        let suffix = self.bytes((suffix_len_and_type >> 3) as usize)?;
+        self.key.extend_from_slice(suffix);
+        Ok((suffix_len_and_type & 0x7) as u8)
+    }
+
+    fn has_more(&self) -> bool {
+        self.pos < self.block.restarts_offset
+    }
+
+    /// Return the key of the record that was decoded last.
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Decode the next index record and return the offset of the block it points to, whose last key is our [key][Self::key()].
+    pub fn next_index(&mut self) -> Result<Option<usize>, Error> {
+        if !self.has_more() {
+            return Ok(None);
+        }
+        self.read_key()?;
+        Ok(Some(self.varint()? as usize))
+    }
+
+    /// Decode the next object record and return the offsets of all reference blocks which contain references to objects
+    /// whose id starts with our [key][Self::key()].
+    ///
+    /// The offsets may be empty if there were too many to store them, in which case all reference blocks have to be searched.
+    pub fn next_obj(&mut self) -> Result<Option<Vec<usize>>, Error> {
+        if !self.has_more() {
+            return Ok(None);
+        }
+        let count = match self.read_key()? {
+            0 => self.varint()? as usize,
+            count => count as usize,
+        };
+        let mut offsets = Vec::with_capacity(count);
+        let mut last = 0;
+        for idx in 0..count {
+            let value = self.varint()? as usize;
+            last = if idx == 0 {
+                value
+            } else {
+                last.checked_add(value).ok_or(Error::Truncated)?
+            };
+            offsets.push(last);
+        }
+        Ok(Some(offsets))
+    }
+
+    /// Decode the next reference record.
+    pub fn next_ref(&mut self) -> Result<Option<Ref>, Error> {
+        if !self.has_more() {
+            return Ok(None);
+        }
+        let typ = self.read_key()?;
+        let update_index = self.table.min_update_index + self.varint()?;
+        let value = match typ {
+            0 => RefValue::Deletion,
+            1 => RefValue::Object(self.id()?),
+            2 => RefValue::PeeledTag {
+                target: self.id()?,
+                peeled: self.id()?,
+            },
+            3 => RefValue::Symbolic(self.string()?),
+            _ => return Err(Error::UnknownValueType { typ }),
+        };
+        Ok(Some(Ref {
+            name: self.key.as_slice().into(),
+            update_index,
+            value,
+        }))
+    }
+
+    /// Decode the next log record.
+    pub fn next_log(&mut self) -> Result<Option<Log>, Error> {
+        if !self.has_more() {
+            return Ok(None);
+        }
+        let typ = self.read_key()?;
+        let (ref_name, update_index) = split_log_key(&self.key)
+            .map(|(name, update_index)| (BString::from(name), update_index))
+            .ok_or(Error::Truncated)?;
+        let line = match typ {
+            0 => None,
+            1 => {
+                let previous_oid = self.id()?;
+                let new_oid = self.id()?;
+                let name = self.string()?;
+                let email = self.string()?;
+                let seconds = self.varint()?;
+                let offset_in_minutes = i16::from_be_bytes(self.bytes(2)?.try_into().expect("two bytes"));
+                let mut message = self.string()?;
+                if message.last() == Some(&b'\n') {
+                    message.pop();
+                }
+                let offset_in_seconds = i32::from(offset_in_minutes) * 60;
+                Some(crate::log::Line {
+                    previous_oid,
+                    new_oid,
+                    signature: gix_actor::Signature {
+                        name,
+                        email,
+                        time: gix_actor::Time {
+                            seconds_since_unix_epoch: seconds as u32,
+                            offset_in_seconds,
+                            sign: if offset_in_seconds < 0 {
+                                gix_actor::Sign::Minus
+                            } else {
+                                gix_actor::Sign::Plus
+                            },
+                        },
+                    },
+                    message,
+                })
+            }
+            _ => return Err(Error::UnknownValueType { typ }),
+        };
+        Ok(Some(Log {
+            name: ref_name,
+            update_index,
+            line,
+        }))
+    }
+}
+
+/// Log keys are the reference name followed by a null byte and the inverted update index, so that newer entries come first.
+pub(crate) fn log_key(name: &[u8], update_index: u64, out: &mut Vec<u8>) {
+    out.clear();
+    out.extend_from_slice(name);
+    out.push(0);
+    out.extend_from_slice(&(u64::MAX - update_index).to_be_bytes());
+}
+
+fn split_log_key(key: &[u8]) -> Option<(&[u8], u64)> {
+    let pos = key.len().checked_sub(9)?;
+    (key[pos] == 0).then(|| {
+        let inverted = u64::from_be_bytes(key[pos + 1..].try_into().expect("8 bytes"));
+        (&key[..pos], u64::MAX - inverted)
+    })
+}
+
+pub(crate) fn be24(data: &[u8]) -> usize {
+    (usize::from(data[0]) << 16) | (usize::from(data[1]) << 8) | usize::from(data[2])
+}
+
+fn be16(data: &[u8]) -> usize {
+    (usize::from(data[0]) << 8) | usize::from(data[1])
+}
@@ -0,0 +1,117 @@
+use std::ops::Range;
+
+pub use error::Error;
+
+use crate::store_impl::reftable::{stack, write, Stack, Store};
+
+mod error {
+    /// The error returned by [`Store::compact()`][crate::reftable::Store::compact()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The lock for the list of tables could not be obtained")]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error("The stack of tables could not be loaded")]
+        Stack(#[from] crate::reftable::stack::Error),
+        #[error("A table could not be decoded")]
+        Decode(#[from] crate::reftable::decode::Error),
+        #[error("The compacted table could not be encoded")]
+        Encode(#[from] crate::reftable::write::Error),
+        #[error("The compacted table or the list of tables could not be written")]
+        Io(#[from] std::io::Error),
+    }
+}
+
+impl Store {
+    /// Merge all tables of the stack into a single one, dropping all deletions as there is nothing older they could shadow.
+    ///
+    /// Return the amount of tables that were merged, which is 0 if there was nothing to do.
+    pub fn compact(&self, lock_fail_mode: gix_lock::acquire::Fail) -> Result<usize, Error> {
+        self.compact_with(lock_fail_mode, |stack| 0..stack.tables.len())
+    }
+
+    /// Merge the newest tables of the stack so that each table is at least twice as large as all newer tables taken together,
+    /// just like `git` does after each change.
+    ///
+    /// Return the amount of tables that were merged, which is 0 if there was nothing to do.
+    pub fn auto_compact(&self, lock_fail_mode: gix_lock::acquire::Fail) -> Result<usize, Error> {
+        self.compact_with(lock_fail_mode, |stack| {
+            auto_compaction_range(&stack.tables().map(|t| t.size()).collect::<Vec<_>>())
+        })
+    }
+
+    fn compact_with(
+        &self,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        range: impl FnOnce(&Stack) -> Range<usize>,
+    ) -> Result<usize, Error> {
+        let lock = self.lock_tables_list(lock_fail_mode)?;
+        let stack = self.stack()?;
+        let range = range(&stack);
+        if range.len() < 2 {
+            return Ok(0);
+        }
+        let keep_deletions = range.start != 0;
+        let refs: Vec<_> = stack
+            .merged_refs(range.clone(), keep_deletions)?
+            .into_values()
+            .collect();
+        let logs: Vec<_> = stack
+            .merged_logs(range.clone(), keep_deletions)?
+            .into_values()
+            .collect();
+        let min_update_index = stack.tables[range.start].1.min_update_index();
+        let max_update_index = stack.tables[range.end - 1].1.max_update_index();
+        let data = write::table(
+            &refs,
+            &logs,
+            min_update_index,
+            max_update_index,
+            self.object_hash,
+            write::Options::default(),
+        )?;
+        let name = self.write_table(&data, min_update_index, max_update_index)?;
+
+        let names = stack.names().collect::<Vec<_>>();
+        stack::commit_tables_list(
+            lock,
+            names[..range.start]
+                .iter()
+                .copied()
+                .chain(Some(name.as_str()))
+                .chain(names[range.end..].iter().copied()),
+        )?;
+        for obsolete in &names[range.clone()] {
+            // Readers may still have it open, which fails on some platforms, so it's fine to leave them for later.
+            std::fs::remove_file(self.reftable_dir().join(obsolete)).ok();
+        }
+        Ok(range.len())
+    }
+}
+
+/// Return the range of tables, given by their `sizes` from oldest to newest, that need to be merged to restore the
+/// geometric sequence of table sizes.
+fn auto_compaction_range(sizes: &[usize]) -> Range<usize> {
+    let end = sizes.len();
+    let mut start = end.saturating_sub(1);
+    let mut total = sizes.last().copied().unwrap_or(0);
+    while start > 0 && sizes[start - 1] <= 2 * total {
+        start -= 1;
+        total += sizes[start];
+    }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::auto_compaction_range;
+
+    #[test]
+    fn geometric_sequences_are_kept() {
+        assert_eq!(auto_compaction_range(&[]).len(), 0);
+        assert_eq!(auto_compaction_range(&[10]).len(), 1);
+        assert_eq!(auto_compaction_range(&[100, 30, 10]), 2..3, "nothing to do");
+        assert_eq!(auto_compaction_range(&[100, 10, 10]), 1..3);
+        assert_eq!(auto_compaction_range(&[40, 10, 10]), 0..3, "cascades into older tables");
+    }
+}
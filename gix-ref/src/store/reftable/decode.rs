@@ -0,0 +1,327 @@
+use crate::store_impl::{
+    reftable::block::Block,
+    reftable::{block, Log, Ref, RefValue, Table},
+};
+
+/// The error returned when decoding a [`Table`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The table is too short or ends unexpectedly")]
+    Truncated,
+    #[error("The table doesn't start with the 'REFT' signature")]
+    Signature,
+    #[error("Reftable version {version} is not supported")]
+    UnsupportedVersion { version: u8 },
+    #[error("The hash id {hash_id:#x} is unknown")]
+    UnknownHash { hash_id: u32 },
+    #[error("The header and the footer don't match")]
+    HeaderMismatch,
+    #[error("The checksum of the footer didn't match, expected {expected:#x}, got {actual:#x}")]
+    Checksum { expected: u32, actual: u32 },
+    #[error("Encountered unknown block of type {typ:#x} at offset {offset}")]
+    UnknownBlockType { typ: u8, offset: usize },
+    #[error("Encountered unknown record value type {typ}")]
+    UnknownValueType { typ: u8 },
+    #[error("A log block could not be decompressed")]
+    Inflate(#[from] gix_features::zlib::inflate::Error),
+}
+
+pub(crate) const SIGNATURE: &[u8] = b"REFT";
+pub(crate) const HEADER_LEN_V1: usize = 24;
+pub(crate) const HEADER_LEN_V2: usize = 28;
+/// The size of the footer after the copy of the header: five 64 bit offsets and the checksum.
+pub(crate) const FOOTER_LEN_AFTER_HEADER: usize = 5 * 8 + 4;
+pub(crate) const HASH_ID_SHA1: u32 = u32::from_be_bytes(*b"sha1");
+pub(crate) const HASH_ID_SHA256: u32 = u32::from_be_bytes(*b"s256");
+
+/// Initialization
+impl Table {
+    /// Decode the table header and footer in `data`, leaving all blocks to be decoded on access.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, Error> {
+        if data.len() < HEADER_LEN_V1 + HEADER_LEN_V1 + FOOTER_LEN_AFTER_HEADER {
+            return Err(Error::Truncated);
+        }
+        if &data[..4] != SIGNATURE {
+            return Err(Error::Signature);
+        }
+        let version = data[4];
+        let (header_len, object_hash) = match version {
+            1 => (HEADER_LEN_V1, gix_hash::Kind::Sha1),
+            2 => {
+                let hash_id = be32(&data[24..]);
+                match hash_id {
+                    HASH_ID_SHA1 => (HEADER_LEN_V2, gix_hash::Kind::Sha1),
+                    HASH_ID_SHA256 => (HEADER_LEN_V2, gix_hash::Kind::Sha256),
+                    _ => return Err(Error::UnknownHash { hash_id }),
+                }
+            }
+            version => return Err(Error::UnsupportedVersion { version }),
+        };
+        let footer_offset = data
+            .len()
+            .checked_sub(header_len + FOOTER_LEN_AFTER_HEADER)
+            .filter(|offset| *offset >= header_len)
+            .ok_or(Error::Truncated)?;
+        let footer = &data[footer_offset..];
+        if footer[..header_len] != data[..header_len] {
+            return Err(Error::HeaderMismatch);
+        }
+        let (footer, checksum) = footer.split_at(footer.len() - 4);
+        let expected = be32(checksum);
+        let actual = gix_features::hash::crc32(footer);
+        if expected != actual {
+            return Err(Error::Checksum { expected, actual });
+        }
+        let offsets = &footer[header_len..];
+        let offset = |idx: usize| be64(&offsets[idx * 8..]) as usize;
+
+        Ok(Table {
+            version,
+            block_size: block::be24(&data[5..]) as u32,
+            min_update_index: be64(&data[8..]),
+            max_update_index: be64(&data[16..]),
+            object_hash,
+            footer_offset,
+            ref_index_offset: offset(0),
+            obj_offset: offset(1) >> 5,
+            obj_id_len: offset(1) & 0x1f,
+            obj_index_offset: offset(2),
+            log_offset: offset(3),
+            log_index_offset: offset(4),
+            data,
+        })
+    }
+}
+
+/// Decoding
+impl Table {
+    pub(crate) fn header_len(&self) -> usize {
+        if self.version == 1 {
+            HEADER_LEN_V1
+        } else {
+            HEADER_LEN_V2
+        }
+    }
+
+    /// Return the offset at which the reference section ends.
+    fn ref_section_end(&self) -> usize {
+        [
+            self.ref_index_offset,
+            self.obj_offset,
+            self.obj_index_offset,
+            self.log_offset,
+            self.log_index_offset,
+        ]
+        .into_iter()
+        .filter(|offset| *offset != 0)
+        .min()
+        .unwrap_or(self.footer_offset)
+    }
+
+    /// Call `f` with each block of type `typ` starting at `offset` until the section ends at `end`, or until `f` returns false.
+    fn for_each_block(
+        &self,
+        typ: u8,
+        mut offset: usize,
+        end: usize,
+        mut f: impl FnMut(usize, &Block<'_>) -> Result<bool, Error>,
+    ) -> Result<(), Error> {
+        while offset < end {
+            let block = match self.block_at(offset)? {
+                Some(block) if block.typ == typ => block,
+                _ => break,
+            };
+            if !f(offset, &block)? {
+                break;
+            }
+            offset = block.next_offset;
+        }
+        Ok(())
+    }
+
+    /// Return the offset of the block of type `typ` which contains the first record whose key is greater than or equal to `key`
+    /// if there is such a record, or `None` if there is no block of that type.
+    ///
+    /// If `index_offset` isn't 0, the possibly multi-level index starting there is used to find the block, otherwise the
+    /// first keys of all blocks of the section at `section_offset` are compared.
+    fn seek_block(
+        &self,
+        typ: u8,
+        section_offset: usize,
+        index_offset: usize,
+        key: &[u8],
+    ) -> Result<Option<usize>, Error> {
+        let mut next = None;
+        if index_offset == 0 {
+            self.for_each_block(typ, section_offset, self.footer_offset, |offset, block| {
+                if next.is_some() && block.first_key(self)?.as_slice() > key {
+                    return Ok(false);
+                }
+                next = Some(offset);
+                Ok(true)
+            })?;
+            return Ok(next);
+        }
+
+        // Like git, read the top-level of the index linearly as it may consist of multiple blocks.
+        self.for_each_block(block::INDEX, index_offset, self.footer_offset, |_offset, block| {
+            next = block.seek_index(self, key)?;
+            Ok(next.is_none())
+        })?;
+        while let Some(offset) = next {
+            let block = self.block_at(offset)?.ok_or(Error::Truncated)?;
+            if block.typ == typ {
+                return Ok(Some(offset));
+            }
+            if block.typ != block::INDEX {
+                return Err(Error::UnknownBlockType { typ: block.typ, offset });
+            }
+            next = block.seek_index(self, key)?;
+        }
+        Ok(None)
+    }
+
+    /// Return all reference records in this table, sorted by name, including [deletions][super::RefValue::Deletion].
+    pub fn refs(&self) -> Result<Vec<Ref>, Error> {
+        let mut out = Vec::new();
+        self.for_each_block(block::REF, 0, self.ref_section_end(), |_offset, block| {
+            let mut records = block.records(self);
+            while let Some(record) = records.next_ref()? {
+                out.push(record);
+            }
+            Ok(true)
+        })?;
+        Ok(out)
+    }
+
+    /// Find the reference record with the given full `name`, which may be a deletion.
+    ///
+    /// The reference index and the restart points of each block are used to find the record quickly.
+    pub fn find_ref(&self, name: &[u8]) -> Result<Option<Ref>, Error> {
+        let block = match self.seek_block(block::REF, 0, self.ref_index_offset, name)? {
+            Some(offset) => self.block_at(offset)?.ok_or(Error::Truncated)?,
+            None => return Ok(None),
+        };
+        let mut records = block.records_at_or_before(self, name)?;
+        while let Some(record) = records.next_ref()? {
+            match record.name.as_slice().cmp(name) {
+                std::cmp::Ordering::Less => continue,
+                std::cmp::Ordering::Equal => return Ok(Some(record)),
+                std::cmp::Ordering::Greater => break,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return all reference records pointing to `id`, either directly or as peeled object of an annotated tag, sorted by name
+    /// and including records which may be shadowed by newer tables.
+    ///
+    /// The object section is used to only search the blocks which contain such records, if there is one.
+    pub fn refs_pointing_to(&self, id: &gix_hash::oid) -> Result<Vec<Ref>, Error> {
+        if self.obj_offset == 0 {
+            return Ok(self.refs()?.into_iter().filter(|r| r.points_to(id)).collect());
+        }
+        let prefix = &id.as_bytes()[..self.obj_id_len.min(id.as_bytes().len())];
+        let mut block_offsets = None;
+        if let Some(offset) = self.seek_block(block::OBJ, self.obj_offset, self.obj_index_offset, prefix)? {
+            let block = self.block_at(offset)?.ok_or(Error::Truncated)?;
+            let mut records = block.records_at_or_before(self, prefix)?;
+            while let Some(offsets) = records.next_obj()? {
+                match records.key().cmp(prefix) {
+                    std::cmp::Ordering::Less => continue,
+                    std::cmp::Ordering::Equal => block_offsets = Some(offsets),
+                    std::cmp::Ordering::Greater => {}
+                }
+                break;
+            }
+        }
+        match block_offsets {
+            None => Ok(Vec::new()),
+            Some(offsets) if offsets.is_empty() => Ok(self.refs()?.into_iter().filter(|r| r.points_to(id)).collect()),
+            Some(offsets) => {
+                let mut out = Vec::new();
+                for offset in offsets {
+                    let block = self
+                        .block_at(offset)?
+                        .filter(|block| block.typ == block::REF)
+                        .ok_or(Error::Truncated)?;
+                    let mut records = block.records(self);
+                    while let Some(record) = records.next_ref()? {
+                        if record.points_to(id) {
+                            out.push(record);
+                        }
+                    }
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    /// Return all log records of this table, sorted by name and then from newest to oldest, including deletions.
+    pub fn logs(&self) -> Result<Vec<Log>, Error> {
+        let mut out = Vec::new();
+        // Logs may only be at the beginning of the table if there are no references.
+        self.for_each_block(block::LOG, self.log_offset, self.footer_offset, |_offset, block| {
+            let mut records = block.records(self);
+            while let Some(record) = records.next_log()? {
+                out.push(record);
+            }
+            Ok(true)
+        })?;
+        Ok(out)
+    }
+
+    /// Return all log records of the reference with the full `name` from newest to oldest, including deletions.
+    ///
+    /// The log index is used to find them quickly, if there is one.
+    pub fn find_logs(&self, name: &[u8]) -> Result<Vec<Log>, Error> {
+        let mut out = Vec::new();
+        // The newest log entry has the smallest key.
+        let mut key = Vec::new();
+        block::log_key(name, u64::MAX, &mut key);
+        let mut offset = match self.seek_block(block::LOG, self.log_offset, self.log_index_offset, &key)? {
+            Some(offset) => offset,
+            None => return Ok(out),
+        };
+        let mut is_first_block = true;
+        while let Some(block) = self.block_at(offset)?.filter(|block| block.typ == block::LOG) {
+            let mut records = if is_first_block {
+                block.records_at_or_before(self, &key)?
+            } else {
+                block.records(self)
+            };
+            is_first_block = false;
+            while let Some(record) = records.next_log()? {
+                if records.key() < key.as_slice() {
+                    continue;
+                }
+                if record.name != name {
+                    return Ok(out);
+                }
+                out.push(record);
+            }
+            offset = block.next_offset;
+        }
+        Ok(out)
+    }
+}
+
+impl Ref {
+    /// Return true if this record points to `id` directly, or as peeled object of an annotated tag.
+    pub(crate) fn points_to(&self, id: &gix_hash::oid) -> bool {
+        match &self.value {
+            RefValue::Object(oid) => oid.as_ref() == id,
+            RefValue::PeeledTag { target, peeled } => target.as_ref() == id || peeled.as_ref() == id,
+            RefValue::Deletion | RefValue::Symbolic(_) => false,
+        }
+    }
+}
+
+pub(crate) fn be32(data: &[u8]) -> u32 {
+    u32::from_be_bytes(data[..4].try_into().expect("4 bytes"))
+}
+
+fn be64(data: &[u8]) -> u64 {
+    u64::from_be_bytes(data[..8].try_into().expect("8 bytes"))
+}
@@ -0,0 +1,207 @@
+use std::convert::TryInto;
+
+pub use error::Error;
+
+use crate::{
+    bstr::BString,
+    store_impl::{
+        file::loose,
+        reftable::{Ref, RefValue, Stack, Store},
+    },
+    FullName, FullNameRef, PartialNameRef, Reference, Target,
+};
+
+/// References that git keeps as files next to the reftable stack, as they are special in some way.
+const SPECIAL_REFS: &[&str] = &["FETCH_HEAD", "MERGE_HEAD"];
+
+impl Store {
+    /// Find a single reference by the given `path` which is required to be a valid reference name.
+    ///
+    /// Returns `Ok(None)` if no such ref exists.
+    ///
+    /// ### Note
+    ///
+    /// * The lookup algorithm follows the one in [the git documentation][git-lookup-docs].
+    /// * The stack of tables is read each time the method is called. See [`Store::try_find_in()`] for a version
+    ///   with more control.
+    ///
+    /// [git-lookup-docs]: https://github.com/git/git/blob/5d5b1473453400224ebb126bf3947e0a3276bdf5/Documentation/revisions.txt#L34-L46
+    pub fn try_find<'a, Name, E>(&self, partial: Name) -> Result<Option<Reference>, Error>
+    where
+        Name: TryInto<&'a PartialNameRef, Error = E>,
+        Error: From<E>,
+    {
+        let stack = self.stack()?;
+        self.try_find_in(partial, &stack)
+    }
+
+    /// Similar to [`Store::try_find()`], but uses the given snapshot of the `stack` of tables.
+    pub fn try_find_in<'a, Name, E>(&self, partial: Name, stack: &Stack) -> Result<Option<Reference>, Error>
+    where
+        Name: TryInto<&'a PartialNameRef, Error = E>,
+        Error: From<E>,
+    {
+        let partial_name = partial.try_into()?;
+        let mut buf = BString::default();
+        if partial_name.looks_like_full_name() {
+            let full_name = partial_name.construct_full_name_ref(false, "", &mut buf);
+            if let Some(r) = self.find_full_name(full_name, stack)? {
+                return Ok(Some(r));
+            }
+        }
+
+        for inbetween in &["", "tags", "heads", "remotes"] {
+            let full_name = partial_name.construct_full_name_ref(true, inbetween, &mut buf);
+            if let Some(r) = self.find_full_name(full_name, stack)? {
+                return Ok(Some(r));
+            }
+        }
+        let with_head = partial_name.to_owned().join("HEAD").expect("HEAD is valid name");
+        let full_name = with_head.as_ref().construct_full_name_ref(true, "remotes", &mut buf);
+        self.find_full_name(full_name, stack)
+    }
+
+    pub(crate) fn find_full_name(&self, full_name: &FullNameRef, stack: &Stack) -> Result<Option<Reference>, Error> {
+        if SPECIAL_REFS.iter().any(|name| full_name.as_bstr() == *name) {
+            return self.find_special_ref(full_name);
+        }
+        let record = match &self.namespace {
+            Some(namespace) => stack.find_ref(namespace.to_owned().into_namespaced_name(full_name).as_bstr()),
+            None => stack.find_ref(full_name.as_bstr()),
+        }?;
+        record
+            .map(|record| {
+                let mut r = record.try_into_reference()?;
+                if let Some(namespace) = &self.namespace {
+                    r.strip_namespace(namespace);
+                }
+                Ok(r)
+            })
+            .transpose()
+    }
+
+    fn find_special_ref(&self, full_name: &FullNameRef) -> Result<Option<Reference>, Error> {
+        let path = self.git_dir.join(full_name.to_path());
+        match std::fs::read(&path) {
+            Ok(content) => loose::Reference::try_from_path(full_name.to_owned(), &content)
+                .map(|r| Some(r.into()))
+                .map_err(|source| Error::SpecialRef { source, path }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(Error::ReadSpecialRef { source, path }),
+        }
+    }
+}
+
+impl Ref {
+    /// Turn this record into a reference, or fail if it is a deletion or if its symbolic target is invalid.
+    pub(crate) fn try_into_reference(self) -> Result<Reference, Error> {
+        let name = FullName::try_from(self.name)?;
+        Ok(match self.value {
+            RefValue::Deletion => unreachable!("BUG: deletions are never turned into references"),
+            RefValue::Object(id) => Reference {
+                name,
+                target: Target::Peeled(id),
+                peeled: None,
+            },
+            RefValue::PeeledTag { target, peeled } => Reference {
+                name,
+                target: Target::Peeled(target),
+                peeled: Some(peeled),
+            },
+            RefValue::Symbolic(target) => Reference {
+                name,
+                target: Target::Symbolic(FullName::try_from(target)?),
+                peeled: None,
+            },
+        })
+    }
+}
+
+///
+pub mod existing {
+    use std::convert::TryInto;
+
+    pub use error::Error;
+
+    use crate::{
+        store_impl::reftable::{find, Stack, Store},
+        PartialNameRef, Reference,
+    };
+
+    impl Store {
+        /// Similar to [`Store::try_find()`] but a non-existing ref is treated as error.
+        pub fn find<'a, Name, E>(&self, partial: Name) -> Result<Reference, Error>
+        where
+            Name: TryInto<&'a PartialNameRef, Error = E>,
+            crate::name::Error: From<E>,
+        {
+            let stack = self.stack().map_err(find::Error::from)?;
+            self.find_in(partial, &stack)
+        }
+
+        /// Similar to [`Store::find()`], but uses the given snapshot of the `stack` of tables.
+        pub fn find_in<'a, Name, E>(&self, partial: Name, stack: &Stack) -> Result<Reference, Error>
+        where
+            Name: TryInto<&'a PartialNameRef, Error = E>,
+            crate::name::Error: From<E>,
+        {
+            let path = partial
+                .try_into()
+                .map_err(|err| Error::Find(find::Error::RefnameValidation(err.into())))?;
+            match self.try_find_in(path, stack) {
+                Ok(Some(r)) => Ok(r),
+                Ok(None) => Err(Error::NotFound {
+                    name: path.to_partial_path().to_owned(),
+                }),
+                Err(err) => Err(err.into()),
+            }
+        }
+    }
+
+    mod error {
+        use std::path::PathBuf;
+
+        use crate::store_impl::reftable::find;
+
+        /// The error returned by [reftable::Store::find()][crate::reftable::Store::find()].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("An error occurred while trying to find a reference")]
+            Find(#[from] find::Error),
+            #[error("The ref partially named {name:?} could not be found")]
+            NotFound { name: PathBuf },
+        }
+    }
+}
+
+mod error {
+    use std::{convert::Infallible, path::PathBuf};
+
+    use crate::store_impl::{file, reftable};
+
+    /// The error returned by [reftable::Store::try_find()][crate::reftable::Store::try_find()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The ref name or path is not a valid ref name")]
+        RefnameValidation(#[from] crate::name::Error),
+        #[error("The stack of tables could not be loaded")]
+        Stack(#[from] reftable::stack::Error),
+        #[error("A table could not be decoded")]
+        Decode(#[from] reftable::decode::Error),
+        #[error("The reference file {path:?} could not be read")]
+        ReadSpecialRef { source: std::io::Error, path: PathBuf },
+        #[error("The reference file {path:?} could not be decoded")]
+        SpecialRef {
+            source: file::loose::reference::decode::Error,
+            path: PathBuf,
+        },
+    }
+
+    impl From<Infallible> for Error {
+        fn from(_: Infallible) -> Self {
+            unreachable!("this impl is needed to allow passing a known valid partial path as parameter")
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use crate::{
+    bstr::{BString, ByteVec},
+    store_impl::reftable::{find, Ref, Store},
+    Namespace, Reference,
+};
+
+/// An intermediate structure holding a snapshot of all references to iterate over.
+#[must_use = "Iterators should be obtained from this platform"]
+pub struct Platform<'s> {
+    store: &'s Store,
+    refs: Vec<Ref>,
+}
+
+/// An iterator over references in a snapshot of the stack of tables, sorted by their name.
+pub struct Iter<'p> {
+    refs: std::slice::Iter<'p, Ref>,
+    namespace: Option<&'p Namespace>,
+    /// The prefix each reference name must have, including the namespace.
+    prefix: BString,
+}
+
+impl<'s> Platform<'s> {
+    /// Return an iterator over all references, sorted by their name.
+    pub fn all(&self) -> Iter<'_> {
+        self.iter_with_prefix(BString::default())
+    }
+
+    /// As [`all()`][Platform::all()], but filters by `prefix`, i.e. "refs/heads".
+    ///
+    /// Please note that "refs/heads` or "refs\\heads" is equivalent to "refs/heads/"
+    pub fn prefixed(&self, prefix: impl AsRef<Path>) -> Iter<'_> {
+        let mut prefix = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(prefix.as_ref())).into_owned();
+        if !prefix.is_empty() && !prefix.ends_with(b"/") {
+            prefix.push_byte(b'/');
+        }
+        self.iter_with_prefix(prefix)
+    }
+
+    fn iter_with_prefix(&self, mut prefix: BString) -> Iter<'_> {
+        let namespace = self.store.namespace.as_ref();
+        if let Some(namespace) = namespace {
+            prefix.insert_str(0, namespace.as_bstr());
+        }
+        let start = self.refs.partition_point(|r| r.name < prefix);
+        Iter {
+            refs: self.refs[start..].iter(),
+            namespace,
+            prefix,
+        }
+    }
+}
+
+impl<'p> Iterator for Iter<'p> {
+    type Item = Result<Reference, find::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.refs.next()?;
+        if !record.name.starts_with(&self.prefix) {
+            self.refs = [].iter();
+            return None;
+        }
+        Some(record.clone().try_into_reference().map(|mut r| {
+            if let Some(namespace) = self.namespace {
+                r.strip_namespace(namespace);
+            }
+            r
+        }))
+    }
+}
+
+impl Store {
+    /// Return a platform to obtain an iterator over all references, or prefixed ones, sorted by their name.
+    ///
+    /// The iterator sees a snapshot of all references at the time this method is called.
+    pub fn iter(&self) -> Result<Platform<'_>, find::Error> {
+        Ok(Platform {
+            store: self,
+            refs: self.stack()?.refs()?,
+        })
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{
+    bstr::BString,
+    store_impl::reftable::{find, Stack, Store},
+    FullNameRef,
+};
+
+impl Store {
+    /// Return all reflog entries of the reference with `name` from oldest to newest, or `None` if it doesn't have a reflog.
+    pub fn reflog(&self, name: &FullNameRef) -> Result<Option<Vec<crate::log::Line>>, find::Error> {
+        let stack = self.stack()?;
+        self.reflog_in(name, &stack)
+    }
+
+    /// Like [`reflog()`][Store::reflog()], but uses the given snapshot of the `stack` of tables.
+    pub fn reflog_in(&self, name: &FullNameRef, stack: &Stack) -> Result<Option<Vec<crate::log::Line>>, find::Error> {
+        let name = self.namespaced_name(name);
+        let mut lines: Vec<_> = stack.logs_of(&name)?.into_iter().filter_map(|log| log.line).collect();
+        lines.reverse();
+        Ok((!lines.is_empty()).then_some(lines))
+    }
+
+    /// Return true if the reference with `name` has a reflog.
+    pub fn reflog_exists(&self, name: &FullNameRef) -> Result<bool, find::Error> {
+        Ok(self.reflog(name)?.is_some())
+    }
+
+    pub(crate) fn namespaced_name(&self, name: &FullNameRef) -> BString {
+        match &self.namespace {
+            Some(namespace) => namespace.to_owned().into_namespaced_name(name).into_inner(),
+            None => name.as_bstr().to_owned(),
+        }
+    }
+}
+
+///
+pub mod retain {
+    use crate::{
+        store_impl::{
+            file::log,
+            reftable::{decode, stack, write, Log, Ref, RefValue, Store},
+        },
+        FullNameRef,
+    };
+
+    impl Store {
+        /// Rewrite the reflog of the reference `name` to only contain the lines for which `keep` returns true, passing them
+        /// from oldest to most recent, and return the amount of removed lines.
+        ///
+        /// Removed lines are deleted by adding a table to the stack, which is locked using `lock_mode` to determine what to do
+        /// if the lock is already taken. Nothing happens if no reflog exists.
+        pub fn reflog_retain(
+            &self,
+            name: &FullNameRef,
+            lock_mode: gix_lock::acquire::Fail,
+            keep: impl FnMut(&log::LineRef<'_>) -> bool,
+        ) -> Result<usize, Error> {
+            self.retain(name, lock_mode, false, keep)
+        }
+
+        /// Like [`reflog_retain()`][Store::reflog_retain()], but also point the reference `name` to the new value of
+        /// the most recent line that is kept, similar to `git reflog delete --updateref`.
+        ///
+        /// The reference changes in the same table as its reflog, and it is left untouched if no line is kept.
+        pub fn reflog_retain_and_update_ref(
+            &self,
+            name: &FullNameRef,
+            lock_mode: gix_lock::acquire::Fail,
+            keep: impl FnMut(&log::LineRef<'_>) -> bool,
+        ) -> Result<usize, Error> {
+            self.retain(name, lock_mode, true, keep)
+        }
+
+        fn retain(
+            &self,
+            name: &FullNameRef,
+            lock_mode: gix_lock::acquire::Fail,
+            update_ref: bool,
+            mut keep: impl FnMut(&log::LineRef<'_>) -> bool,
+        ) -> Result<usize, Error> {
+            let lock = self.lock_tables_list(lock_mode)?;
+            let stack = self.stack()?;
+            let name = self.namespaced_name(name);
+
+            let mut removed = Vec::new();
+            let mut last_kept = None;
+            let mut buf = Vec::new();
+            for log in stack.logs_of(&name)?.into_iter().rev() {
+                let line = log.line.as_ref().expect("deletions are filtered");
+                buf.clear();
+                line.write_to(&mut buf)?;
+                let is_kept = log::LineRef::from_bytes(&buf[..buf.len() - 1]).map_or(true, |line| keep(&line));
+                if is_kept {
+                    last_kept = Some(line.new_oid);
+                } else {
+                    removed.push(Log {
+                        name: log.name,
+                        update_index: log.update_index,
+                        line: None,
+                    });
+                }
+            }
+            if removed.is_empty() {
+                return Ok(0);
+            }
+
+            let update_index = stack.max_update_index() + 1;
+            let refs: Vec<_> = last_kept
+                .filter(|_| update_ref)
+                .map(|id| Ref {
+                    name,
+                    update_index,
+                    value: RefValue::Object(id),
+                })
+                .into_iter()
+                .collect();
+            // Logs are sorted from newest to oldest.
+            removed.reverse();
+            let data = write::table(
+                &refs,
+                &removed,
+                update_index,
+                update_index,
+                self.object_hash,
+                write::Options::default(),
+            )?;
+            let table_name = self.write_table(&data, update_index, update_index)?;
+            stack::commit_tables_list(lock, stack.names().chain(Some(table_name.as_str())))?;
+            self.auto_compact(gix_lock::acquire::Fail::Immediately).ok();
+            Ok(removed.len())
+        }
+    }
+
+    /// The error returned by [`Store::reflog_retain()`].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The list of tables could not be locked")]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error("The stack of tables could not be loaded")]
+        Stack(#[from] stack::Error),
+        #[error("The existing reflog could not be read")]
+        Decode(#[from] decode::Error),
+        #[error("The table with the removed lines could not be encoded")]
+        Encode(#[from] write::Error),
+        #[error("The table or the list of tables could not be written")]
+        Io(#[from] std::io::Error),
+    }
+}
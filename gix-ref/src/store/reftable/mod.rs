@@ -0,0 +1,197 @@
+//! A reference store using the [reftable format](https://git-scm.com/docs/reftable), as used by repositories with
+//! `extensions.refStorage=reftable`.
+//!
+//! All references and their logs are kept in a stack of immutable _tables_ in the `reftable/` directory, listed from oldest to
+//! newest in its `tables.list` file. Each change adds a new table to the stack, and tables are merged by _compaction_
+//! to keep the stack small.
+use std::path::{Path, PathBuf};
+
+use gix_hash::ObjectId;
+
+use crate::{bstr::BString, store::WriteReflog, Namespace};
+
+/// A store for references which uses a stack of reftables.
+#[derive(Debug, Clone)]
+pub struct Store {
+    /// The `.git` directory which contains the `reftable` directory, or the private directory of a linked work-tree.
+    git_dir: PathBuf,
+    /// The common directory containing the `reftable` directory, only set if this `Store` is for a linked work-tree.
+    common_dir: Option<PathBuf>,
+    /// The kind of hash to use for all object ids in newly written tables.
+    object_hash: gix_hash::Kind,
+    /// The way to handle reflog edits
+    pub write_reflog: WriteReflog,
+    /// The namespace to use for edits and reads
+    pub namespace: Option<Namespace>,
+    /// The stack as it was last loaded, to be able to reuse tables which are still part of the stack.
+    stack_cache: gix_features::threading::OwnShared<gix_features::threading::MutableOnDemand<Stack>>,
+}
+
+/// The value of a [reference record][Ref].
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub enum RefValue {
+    /// The reference was deleted, shadowing all records of the same name in older tables.
+    Deletion,
+    /// The reference points to an object.
+    Object(ObjectId),
+    /// The reference points to an annotated tag `target`, which peels to `peeled`.
+    PeeledTag {
+        /// The object the reference points to.
+        target: ObjectId,
+        /// The object `target` ultimately points to.
+        peeled: ObjectId,
+    },
+    /// The reference points to another reference with the given name.
+    Symbolic(BString),
+}
+
+/// A reference record as stored in a table.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct Ref {
+    /// The full name of the reference.
+    pub name: BString,
+    /// The update index of the change which produced this record.
+    pub update_index: u64,
+    /// The value of the reference.
+    pub value: RefValue,
+}
+
+/// A reflog record as stored in a table.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct Log {
+    /// The full name of the reference this log entry belongs to.
+    pub name: BString,
+    /// The update index of the change which produced this log entry, which orders log entries of a reference.
+    pub update_index: u64,
+    /// The log entry itself, or `None` if the log entry was deleted.
+    pub line: Option<crate::log::Line>,
+}
+
+/// A single decoded table, one of many in a stack of tables.
+#[derive(Debug, Clone)]
+pub struct Table {
+    data: Vec<u8>,
+    version: u8,
+    block_size: u32,
+    min_update_index: u64,
+    max_update_index: u64,
+    object_hash: gix_hash::Kind,
+    /// The offset to the footer, which is where all sections end.
+    footer_offset: usize,
+    /// The offset to the first log block, or 0 if there is none.
+    log_offset: usize,
+    /// The offset to the first block of the object section, or 0 if there is none.
+    obj_offset: usize,
+    /// The length of the abbreviated object ids used as keys in the object section.
+    obj_id_len: usize,
+    /// The offset to the index of the reference section, or 0 if there is none.
+    ref_index_offset: usize,
+    /// The offset to the index of the object section, or 0 if there is none.
+    obj_index_offset: usize,
+    /// The offset to the index of the log section, or 0 if there is none.
+    log_index_offset: usize,
+}
+
+/// Initialization
+impl Store {
+    /// Create a new instance at the given `git_dir`, which contains the `reftable` directory, using `object_hash` for
+    /// the object ids of all written tables.
+    pub fn at(git_dir: impl Into<PathBuf>, write_reflog: WriteReflog, object_hash: gix_hash::Kind) -> Self {
+        Store {
+            git_dir: git_dir.into(),
+            common_dir: None,
+            object_hash,
+            write_reflog,
+            namespace: None,
+            stack_cache: Default::default(),
+        }
+    }
+
+    /// Like [`at()`][Store::at()], but for _linked_ work-trees whose `git_dir` is private to the work-tree, while the stack of
+    /// tables is shared in `common_dir`.
+    pub fn for_linked_worktree(
+        git_dir: impl Into<PathBuf>,
+        common_dir: impl Into<PathBuf>,
+        write_reflog: WriteReflog,
+        object_hash: gix_hash::Kind,
+    ) -> Self {
+        Store {
+            common_dir: Some(common_dir.into()),
+            ..Store::at(git_dir, write_reflog, object_hash)
+        }
+    }
+}
+
+/// Access
+impl Store {
+    /// Return the `.git` directory containing the `reftable` directory.
+    ///
+    /// For linked work-trees, this is their private directory and [`common_dir()`][Store::common_dir()] is set.
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    /// If this is a linked work-tree, there will be `Some(git_dir)` pointing to the parent repository which contains the
+    /// `reftable` directory.
+    pub fn common_dir(&self) -> Option<&Path> {
+        self.common_dir.as_deref()
+    }
+
+    /// Return the directory containing all tables.
+    pub fn reftable_dir(&self) -> PathBuf {
+        self.common_dir.as_deref().unwrap_or(&self.git_dir).join("reftable")
+    }
+
+    /// Return the path to the file listing all tables of the stack.
+    pub fn tables_list_path(&self) -> PathBuf {
+        self.reftable_dir().join("tables.list")
+    }
+}
+
+/// Access
+impl Table {
+    /// The smallest update index of all records in this table.
+    pub fn min_update_index(&self) -> u64 {
+        self.min_update_index
+    }
+
+    /// The largest update index of all records in this table.
+    pub fn max_update_index(&self) -> u64 {
+        self.max_update_index
+    }
+
+    /// The kind of hash used for all object ids in this table.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// The size of the table in bytes.
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+}
+
+mod block;
+mod varint;
+
+///
+pub mod decode;
+///
+pub mod write;
+
+///
+pub mod stack;
+pub use stack::Stack;
+
+///
+pub mod compact;
+///
+pub mod find;
+///
+pub mod iter;
+///
+pub mod log;
+mod peel;
+///
+pub mod transaction;
+pub use transaction::Transaction;
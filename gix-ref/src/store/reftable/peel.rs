@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    peel,
+    store_impl::reftable::{find, Stack, Store},
+    Reference, Target,
+};
+
+impl Store {
+    /// Follow all symbolic targets of `reference` and peel the object it ultimately points to until a non-tag object
+    /// is found, using `find` to lookup objects, and store the result in `reference`.
+    ///
+    /// This is the equivalent of [`ReferenceExt::peel_to_id_in_place()`][crate::file::ReferenceExt::peel_to_id_in_place()]
+    /// for the files backend.
+    pub fn peel_to_id_in_place<E: std::error::Error + Send + Sync + 'static>(
+        &self,
+        reference: &mut Reference,
+        mut find: impl FnMut(ObjectId, &mut Vec<u8>) -> Result<Option<(gix_object::Kind, &[u8])>, E>,
+    ) -> Result<ObjectId, peel::to_id::Error> {
+        if let Some(peeled) = reference.peeled {
+            reference.target = Target::Peeled(peeled);
+            return Ok(peeled);
+        }
+        if reference.target.kind() == crate::Kind::Symbolic {
+            let stack = self.stack().map_err(|err| find::existing::Error::Find(err.into()))?;
+            let mut seen = BTreeSet::new();
+            while let Some(next) = self.follow_in(reference, &stack) {
+                let next = next?;
+                if seen.contains(&next.name) {
+                    return Err(peel::to_id::Error::Cycle {
+                        start_absolute: self.git_dir.join(reference.name.to_path()),
+                    });
+                }
+                *reference = next;
+                seen.insert(reference.name.clone());
+                const MAX_REF_DEPTH: usize = 5;
+                if seen.len() == MAX_REF_DEPTH {
+                    return Err(peel::to_id::Error::DepthLimitExceeded {
+                        max_depth: MAX_REF_DEPTH,
+                    });
+                }
+            }
+        }
+        let mut buf = Vec::new();
+        let mut oid = reference.target.try_id().expect("peeled ref").to_owned();
+        let peeled_id = loop {
+            let (kind, data) = find(oid, &mut buf)
+                .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)?
+                .ok_or_else(|| peel::to_id::Error::NotFound {
+                    oid,
+                    name: reference.name.0.clone(),
+                })?;
+            match kind {
+                gix_object::Kind::Tag => {
+                    oid = gix_object::TagRefIter::from_bytes(data).target_id().map_err(|_err| {
+                        peel::to_id::Error::NotFound {
+                            oid,
+                            name: reference.name.0.clone(),
+                        }
+                    })?;
+                }
+                _ => break oid,
+            };
+        };
+        reference.peeled = Some(peeled_id);
+        reference.target = Target::Peeled(peeled_id);
+        Ok(peeled_id)
+    }
+
+    /// Follow the symbolic `reference` one level and return the ref it refers to, looking it up in `stack`.
+    ///
+    /// Returns `None` if this is not a symbolic reference, hence the leaf of the chain.
+    pub fn follow_in(&self, reference: &Reference, stack: &Stack) -> Option<Result<Reference, find::existing::Error>> {
+        match reference.peeled {
+            Some(peeled) => Some(Ok(Reference {
+                name: reference.name.clone(),
+                target: Target::Peeled(peeled),
+                peeled: None,
+            })),
+            None => match &reference.target {
+                Target::Peeled(_) => None,
+                Target::Symbolic(full_name) => match self.find_full_name(full_name.as_ref(), stack) {
+                    Ok(Some(next)) => Some(Ok(next)),
+                    Ok(None) => Some(Err(find::existing::Error::NotFound {
+                        name: full_name.to_path().to_owned(),
+                    })),
+                    Err(err) => Some(Err(find::existing::Error::Find(err))),
+                },
+            },
+        }
+    }
+}
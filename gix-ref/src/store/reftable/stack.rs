@@ -0,0 +1,254 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use gix_features::threading::{get_mut, OwnShared};
+
+use crate::{
+    bstr::BString,
+    store_impl::reftable::{decode, Log, Ref, RefValue, Store, Table},
+};
+
+/// A snapshot of all tables of a [`Store`], from oldest to newest, which together form the state of all references.
+#[derive(Debug, Clone, Default)]
+pub struct Stack {
+    pub(crate) tables: Vec<(String, OwnShared<Table>)>,
+}
+
+mod error {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Store::stack()`][crate::reftable::Store::stack()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read {path:?}")]
+        Io { source: std::io::Error, path: PathBuf },
+        #[error("Could not decode table at {path:?}")]
+        Decode {
+            source: crate::reftable::decode::Error,
+            path: PathBuf,
+        },
+    }
+}
+pub use error::Error;
+
+/// Stack access
+impl Store {
+    /// Load all tables of the stack as listed in the `tables.list` file, which is an empty stack if it doesn't exist.
+    ///
+    /// As tables are immutable, those which were loaded by previous calls are reused if they are still listed, so that only
+    /// tables which were added in the meantime have to be read.
+    pub fn stack(&self) -> Result<Stack, Error> {
+        let mut attempts = 0;
+        'retry: loop {
+            let list_path = self.tables_list_path();
+            let names = read_tables_list(&list_path).map_err(|source| Error::Io {
+                source,
+                path: list_path.clone(),
+            })?;
+            let mut cache = get_mut(&self.stack_cache);
+            let mut tables = Vec::with_capacity(names.len());
+            for name in names {
+                if let Some((_, table)) = cache.tables.iter().find(|(cached, _)| *cached == name) {
+                    tables.push((name, table.clone()));
+                    continue;
+                }
+                let path = self.reftable_dir().join(&name);
+                let data = match std::fs::read(&path) {
+                    Ok(data) => data,
+                    // The stack was compacted concurrently, and the list changed after we read it.
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound && attempts < 5 => {
+                        attempts += 1;
+                        drop(cache);
+                        continue 'retry;
+                    }
+                    Err(source) => return Err(Error::Io { source, path }),
+                };
+                let table = Table::from_bytes(data).map_err(|source| Error::Decode { source, path })?;
+                tables.push((name, OwnShared::new(table)));
+            }
+            *cache = Stack { tables };
+            return Ok(cache.clone());
+        }
+    }
+
+    /// Write `data` as new table with the given update index range into the reftable directory and return its name.
+    pub(crate) fn write_table(
+        &self,
+        data: &[u8],
+        min_update_index: u64,
+        max_update_index: u64,
+    ) -> std::io::Result<String> {
+        let dir = self.reftable_dir();
+        std::fs::create_dir_all(&dir)?;
+        let name = format!(
+            "{min_update_index:012x}-{max_update_index:012x}-{:08x}.ref",
+            random_suffix()
+        );
+        let mut lock =
+            gix_lock::File::acquire_to_update_resource(dir.join(&name), gix_lock::acquire::Fail::Immediately, None)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::AlreadyExists, err))?;
+        lock.with_mut(|file| file.write_all(data))?;
+        lock.commit().map_err(|err| err.error)?;
+        Ok(name)
+    }
+}
+
+/// Write all `names` into the `lock` of the `tables.list` file and commit it.
+pub(crate) fn commit_tables_list<'a>(
+    mut lock: gix_lock::File,
+    names: impl IntoIterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    lock.with_mut(|file| {
+        for name in names {
+            writeln!(file, "{name}")?;
+        }
+        Ok(())
+    })?;
+    lock.commit().map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// Access
+impl Stack {
+    /// Return all tables from oldest to newest.
+    pub fn tables(&self) -> impl Iterator<Item = &Table> + '_ {
+        self.tables.iter().map(|(_, table)| &**table)
+    }
+
+    /// Return the file names of all tables from oldest to newest.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.tables.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Return the largest update index of all tables, or 0 if there is no table.
+    pub fn max_update_index(&self) -> u64 {
+        self.tables.last().map_or(0, |(_, table)| table.max_update_index())
+    }
+
+    /// Find the current value of the reference with the full `name`, or `None` if it doesn't exist.
+    pub fn find_ref(&self, name: &[u8]) -> Result<Option<Ref>, decode::Error> {
+        for (_, table) in self.tables.iter().rev() {
+            if let Some(r) = table.find_ref(name)? {
+                return Ok((r.value != RefValue::Deletion).then_some(r));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return all existing references pointing to `id`, either directly or as peeled object of an annotated tag, sorted by name.
+    pub fn refs_pointing_to(&self, id: &gix_hash::oid) -> Result<Vec<Ref>, decode::Error> {
+        let mut names = BTreeSet::new();
+        for (_, table) in &self.tables {
+            names.extend(table.refs_pointing_to(id)?.into_iter().map(|r| r.name));
+        }
+        let mut out = Vec::new();
+        for name in names {
+            // The reference may have been changed by a newer table.
+            if let Some(r) = self.find_ref(&name)?.filter(|r| r.points_to(id)) {
+                out.push(r);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return all existing references sorted by name, with the newest record of each reference taking precedence.
+    pub fn refs(&self) -> Result<Vec<Ref>, decode::Error> {
+        Ok(self.merged_refs(0..self.tables.len(), false)?.into_values().collect())
+    }
+
+    /// Return all existing log entries sorted by name, and for each name from newest to oldest.
+    pub fn logs(&self) -> Result<Vec<Log>, decode::Error> {
+        Ok(self.merged_logs(0..self.tables.len(), false)?.into_values().collect())
+    }
+
+    /// Return all existing log entries of the reference with the full `name`, from newest to oldest.
+    pub fn logs_of(&self, name: &[u8]) -> Result<Vec<Log>, decode::Error> {
+        let mut out = BTreeMap::new();
+        for (_, table) in self.tables.iter().rev() {
+            for log in table.find_logs(name)? {
+                out.entry(Reverse(log.update_index)).or_insert(log);
+            }
+        }
+        Ok(out.into_values().filter(|log| log.line.is_some()).collect())
+    }
+
+    /// Merge the references of all tables in `range`, with newer tables taking precedence, and keep deletions if `keep_deletions`
+    /// is true.
+    pub(crate) fn merged_refs(
+        &self,
+        range: std::ops::Range<usize>,
+        keep_deletions: bool,
+    ) -> Result<BTreeMap<BString, Ref>, decode::Error> {
+        let mut out = BTreeMap::new();
+        for (_, table) in self.tables[range].iter().rev() {
+            for r in table.refs()? {
+                out.entry(r.name.clone()).or_insert(r);
+            }
+        }
+        if !keep_deletions {
+            out.retain(|_, r| r.value != RefValue::Deletion);
+        }
+        Ok(out)
+    }
+
+    /// Like [`merged_refs()`][Self::merged_refs()], but for logs.
+    pub(crate) fn merged_logs(
+        &self,
+        range: std::ops::Range<usize>,
+        keep_deletions: bool,
+    ) -> Result<BTreeMap<(BString, Reverse<u64>), Log>, decode::Error> {
+        let mut out = BTreeMap::new();
+        for (_, table) in self.tables[range].iter().rev() {
+            for log in table.logs()? {
+                out.entry((log.name.clone(), Reverse(log.update_index))).or_insert(log);
+            }
+        }
+        if !keep_deletions {
+            out.retain(|_, log| log.line.is_some());
+        }
+        Ok(out)
+    }
+}
+
+fn read_tables_list(path: &Path) -> std::io::Result<Vec<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(list) => Ok(list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+fn random_suffix() -> u32 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos()),
+    );
+    hasher.write_u32(std::process::id());
+    hasher.finish() as u32
+}
+
+impl Store {
+    pub(crate) fn lock_tables_list(
+        &self,
+        fail_mode: gix_lock::acquire::Fail,
+    ) -> Result<gix_lock::File, gix_lock::acquire::Error> {
+        let path: PathBuf = self.tables_list_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).ok();
+        }
+        gix_lock::File::acquire_to_update_resource(path, fail_mode, None)
+    }
+}
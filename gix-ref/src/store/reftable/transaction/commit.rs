@@ -0,0 +1,152 @@
+use std::cmp::Reverse;
+
+use crate::{
+    store::WriteReflog,
+    store_impl::reftable::{stack, write, Log, Ref, RefValue, Transaction},
+    transaction::{Change, PreviousValue, RefEdit, RefLog},
+    FullNameRef, Target,
+};
+
+impl<'s> Transaction<'s> {
+    /// Make all [prepared][Transaction::prepare()] permanent by adding a single table with all changes to the stack, and return
+    /// the performed edits which represent the current state of the affected refs in the ref store in that instant.
+    /// Please note that the obtained edits may have been adjusted to contain more dependent edits or additional information.
+    /// `committer` is used in the reflog and only if the reflog is actually written, which is why it is optional.
+    ///
+    /// As all changes are written into a single table, the transaction is atomic and nothing is changed on error.
+    /// Afterwards, the stack is compacted automatically if needed, ignoring any errors as it is merely an optimization.
+    pub fn commit<'a>(self, committer: impl Into<Option<gix_actor::SignatureRef<'a>>>) -> Result<Vec<RefEdit>, Error> {
+        self.commit_inner(committer.into())
+    }
+
+    fn commit_inner(self, committer: Option<gix_actor::SignatureRef<'_>>) -> Result<Vec<RefEdit>, Error> {
+        let store = self.store;
+        let updates = self.updates.expect("BUG: must call prepare before commit");
+        let lock = self.lock.expect("BUG: a prepared transaction holds the lock");
+        let stack = self.stack.expect("BUG: a prepared transaction has a stack");
+        let update_index = stack.max_update_index() + 1;
+
+        let mut refs = Vec::new();
+        let mut logs = Vec::new();
+        for change in &updates {
+            assert!(!change.update.deref, "Deref mode is turned into splits and turned off");
+            let name = store.namespaced_name(change.update.name.as_ref());
+            match &change.update.change {
+                Change::Update { log, new, expected } => {
+                    if log.mode == RefLog::AndReference {
+                        refs.push(Ref {
+                            name: name.clone(),
+                            update_index,
+                            value: match new {
+                                Target::Peeled(id) => RefValue::Object(*id),
+                                Target::Symbolic(target) => RefValue::Symbolic(target.as_bstr().to_owned()),
+                            },
+                        });
+                    }
+                    let log_update = match new {
+                        // no reflog for symref changes, unless the ref is new and we can obtain a peeled id
+                        // identified by the expectation of what could be there, as is the case when cloning.
+                        Target::Symbolic(_) => match expected {
+                            PreviousValue::ExistingMustMatch(Target::Peeled(oid)) => Some((None, *oid)),
+                            _ => None,
+                        },
+                        Target::Peeled(new_oid) => {
+                            let previous = match expected {
+                                PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
+                                _ => None,
+                            }
+                            .or(change.leaf_referent_previous_oid);
+                            Some((previous, *new_oid))
+                        }
+                    };
+                    let (previous, new_oid) = match log_update {
+                        Some((previous, new_oid)) if previous != Some(new_oid) => (previous, new_oid),
+                        _ => continue,
+                    };
+                    let write_log = match store.write_reflog {
+                        WriteReflog::Disable => false,
+                        WriteReflog::Always => true,
+                        WriteReflog::Normal => {
+                            log.force_create_reflog
+                                || should_autocreate_reflog(change.update.name.as_ref())
+                                || !stack.logs_of(&name)?.is_empty()
+                        }
+                    };
+                    if write_log {
+                        let committer = committer.ok_or(Error::MissingCommitter)?;
+                        logs.push(Log {
+                            name,
+                            update_index,
+                            line: Some(crate::log::Line {
+                                previous_oid: previous.unwrap_or_else(|| new_oid.kind().null()),
+                                new_oid,
+                                signature: committer.to_owned(),
+                                message: log.message.clone(),
+                            }),
+                        });
+                    }
+                }
+                Change::Delete { log: mode, .. } => {
+                    // Like with the files backend, the reflog goes with the reference.
+                    logs.extend(stack.logs_of(&name)?.into_iter().map(|log| Log {
+                        name: log.name,
+                        update_index: log.update_index,
+                        line: None,
+                    }));
+                    if *mode == RefLog::AndReference {
+                        refs.push(Ref {
+                            name,
+                            update_index,
+                            value: RefValue::Deletion,
+                        });
+                    }
+                }
+            }
+        }
+
+        if !refs.is_empty() || !logs.is_empty() {
+            refs.sort_by(|a, b| a.name.cmp(&b.name));
+            logs.sort_by(|a, b| (&a.name, Reverse(a.update_index)).cmp(&(&b.name, Reverse(b.update_index))));
+            let data = write::table(
+                &refs,
+                &logs,
+                update_index,
+                update_index,
+                store.object_hash,
+                write::Options::default(),
+            )?;
+            let table_name = store.write_table(&data, update_index, update_index)?;
+            stack::commit_tables_list(lock, stack.names().chain(Some(table_name.as_str())))?;
+            store.auto_compact(gix_lock::acquire::Fail::Immediately).ok();
+        }
+        Ok(updates.into_iter().map(|edit| edit.update).collect())
+    }
+}
+
+fn should_autocreate_reflog(full_name: &FullNameRef) -> bool {
+    let name = full_name.as_bstr();
+    name.starts_with(b"refs/heads/")
+        || name.starts_with(b"refs/remotes/")
+        || name.starts_with(b"refs/notes/")
+        || name.starts_with(b"refs/worktree/") // NOTE: git does not write reflogs for worktree private refs
+        || name == "HEAD"
+}
+
+mod error {
+    use crate::store_impl::reftable;
+
+    /// The error returned by [`Transaction::commit()`][crate::reftable::Transaction::commit()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The existing reflogs could not be read")]
+        Decode(#[from] reftable::decode::Error),
+        #[error("The table with all changes could not be encoded")]
+        Encode(#[from] reftable::write::Error),
+        #[error("The table or the list of tables could not be written")]
+        Io(#[from] std::io::Error),
+        #[error("reflog messages need a committer which isn't set")]
+        MissingCommitter,
+    }
+}
+pub use error::Error;
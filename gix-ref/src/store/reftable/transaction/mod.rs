@@ -0,0 +1,71 @@
+use std::fmt::Formatter;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    store_impl::reftable::{Stack, Store},
+    transaction::RefEdit,
+};
+
+/// A transaction on a reftable [`Store`], which adds a single table with all edits to the stack once committed.
+///
+/// The list of tables is locked for the lifetime of a prepared transaction, which makes it atomic.
+pub struct Transaction<'s> {
+    store: &'s Store,
+    lock: Option<gix_lock::File>,
+    stack: Option<Stack>,
+    updates: Option<Vec<Edit>>,
+}
+
+#[derive(Debug)]
+struct Edit {
+    update: RefEdit,
+    /// Set if this update is coming from a symbolic reference and used to make it appear like it is the one that is handled,
+    /// instead of the referent reference.
+    parent_index: Option<usize>,
+    /// For symbolic refs, this is the previous OID to put into the reflog instead of our own previous value. It's the
+    /// peeled value of the leaf referent.
+    leaf_referent_previous_oid: Option<ObjectId>,
+}
+
+impl std::borrow::Borrow<RefEdit> for Edit {
+    fn borrow(&self) -> &RefEdit {
+        &self.update
+    }
+}
+
+impl std::borrow::BorrowMut<RefEdit> for Edit {
+    fn borrow_mut(&mut self) -> &mut RefEdit {
+        &mut self.update
+    }
+}
+
+/// Edits
+impl Store {
+    /// Open a transaction to apply edits to references atomically.
+    ///
+    /// The transaction inherits the parent namespace.
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction {
+            store: self,
+            lock: None,
+            stack: None,
+            updates: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for Transaction<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transaction")
+            .field("store", self.store)
+            .field("edits", &self.updates.as_ref().map(|u| u.len()))
+            .finish_non_exhaustive()
+    }
+}
+
+///
+pub mod prepare;
+
+///
+pub mod commit;
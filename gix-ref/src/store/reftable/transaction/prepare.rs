@@ -0,0 +1,209 @@
+use crate::{
+    store_impl::reftable::{
+        transaction::{Edit, Transaction},
+        Stack, Store,
+    },
+    transaction::{Change, PreviousValue, RefEdit, RefEditsExt},
+    Target,
+};
+
+impl<'s> Transaction<'s> {
+    fn apply_change(store: &Store, stack: &Stack, change: &mut Edit) -> Result<(), Error> {
+        let existing_ref = store.find_full_name(change.update.name.as_ref(), stack)?;
+        match &mut change.update.change {
+            Change::Delete { expected, .. } => {
+                match (&expected, &existing_ref) {
+                    (PreviousValue::MustNotExist, _) => {
+                        panic!("BUG: MustNotExist constraint makes no sense if references are to be deleted")
+                    }
+                    (PreviousValue::ExistingMustMatch(_), None)
+                    | (PreviousValue::MustExist, Some(_))
+                    | (PreviousValue::Any, Some(_))
+                    | (PreviousValue::Any, None) => {}
+                    (PreviousValue::MustExist, None) | (PreviousValue::MustExistAndMatch(_), None) => {
+                        return Err(Error::DeleteReferenceMustExist {
+                            full_name: change.update.name.as_bstr().to_owned(),
+                        })
+                    }
+                    (PreviousValue::MustExistAndMatch(previous), Some(existing))
+                    | (PreviousValue::ExistingMustMatch(previous), Some(existing)) => {
+                        if *previous != existing.target {
+                            return Err(Error::ReferenceOutOfDate {
+                                full_name: change.update.name.as_bstr().to_owned(),
+                                expected: previous.clone(),
+                                actual: existing.target.clone(),
+                            });
+                        }
+                    }
+                }
+
+                // Keep the previous value for the caller and ourselves. Maybe they want to keep a log of sorts.
+                if let Some(existing) = existing_ref {
+                    *expected = PreviousValue::MustExistAndMatch(existing.target);
+                }
+            }
+            Change::Update { expected, new, .. } => {
+                match (&expected, &existing_ref) {
+                    (PreviousValue::Any, _)
+                    | (PreviousValue::MustExist, Some(_))
+                    | (PreviousValue::MustNotExist, None)
+                    | (PreviousValue::ExistingMustMatch(_), None) => {}
+                    (PreviousValue::MustExist, None) => {
+                        return Err(Error::MustExist {
+                            full_name: change.update.name.as_bstr().to_owned(),
+                            expected: Target::Peeled(store.object_hash.null()),
+                        });
+                    }
+                    (PreviousValue::MustNotExist, Some(existing)) => {
+                        if existing.target != *new {
+                            return Err(Error::MustNotExist {
+                                full_name: change.update.name.as_bstr().to_owned(),
+                                actual: existing.target.clone(),
+                                new: new.clone(),
+                            });
+                        }
+                    }
+                    (PreviousValue::MustExistAndMatch(previous), Some(existing))
+                    | (PreviousValue::ExistingMustMatch(previous), Some(existing)) => {
+                        if *previous != existing.target {
+                            return Err(Error::ReferenceOutOfDate {
+                                full_name: change.update.name.as_bstr().to_owned(),
+                                expected: previous.clone(),
+                                actual: existing.target.clone(),
+                            });
+                        }
+                    }
+                    (PreviousValue::MustExistAndMatch(previous), None) => {
+                        return Err(Error::MustExist {
+                            full_name: change.update.name.as_bstr().to_owned(),
+                            expected: previous.clone(),
+                        });
+                    }
+                };
+
+                if let Some(existing) = existing_ref {
+                    *expected = PreviousValue::MustExistAndMatch(existing.target);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'s> Transaction<'s> {
+    /// Prepare for calling [`commit(…)`][Transaction::commit()] by locking the list of tables and validating all `edits`
+    /// against the current state of the stack, using `lock_fail_mode` to determine how to wait for the lock.
+    ///
+    /// If the operation succeeds, the transaction can be committed or dropped to cause a rollback automatically.
+    pub fn prepare(
+        mut self,
+        edits: impl IntoIterator<Item = RefEdit>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+    ) -> Result<Self, Error> {
+        assert!(self.updates.is_none(), "BUG: Must not call prepare(…) multiple times");
+        let store = self.store;
+        let lock = store.lock_tables_list(lock_fail_mode)?;
+        let stack = store.stack()?;
+
+        let mut updates: Vec<_> = edits
+            .into_iter()
+            .map(|update| Edit {
+                update,
+                parent_index: None,
+                leaf_referent_previous_oid: None,
+            })
+            .collect();
+        updates
+            .pre_process(
+                |name| store.try_find_in(name, &stack).ok().flatten().map(|r| r.target),
+                |idx, update| Edit {
+                    update,
+                    parent_index: Some(idx),
+                    leaf_referent_previous_oid: None,
+                },
+            )
+            .map_err(Error::PreprocessingFailed)?;
+
+        for cid in 0..updates.len() {
+            let change = &mut updates[cid];
+            Self::apply_change(store, &stack, change)?;
+
+            // traverse parent chain from leaf/peeled ref and set the leaf previous oid accordingly
+            // to help with their reflog entries
+            if let (Some(crate::TargetRef::Peeled(oid)), Some(parent_idx)) =
+                (change.update.change.previous_value(), change.parent_index)
+            {
+                let oid = oid.to_owned();
+                let mut parent_idx_cursor = Some(parent_idx);
+                while let Some(parent) = parent_idx_cursor.take().map(|idx| &mut updates[idx]) {
+                    parent_idx_cursor = parent.parent_index;
+                    parent.leaf_referent_previous_oid = Some(oid);
+                }
+            }
+        }
+        self.lock = Some(lock);
+        self.stack = Some(stack);
+        self.updates = Some(updates);
+        Ok(self)
+    }
+
+    /// Return the `RefEdits` of a prepared transaction, or nothing if it wasn't prepared yet.
+    ///
+    /// Note that they have been altered compared to what was initially provided as they have
+    /// been split and know about their current state in the stack, just like the edits returned by [`rollback()`][Self::rollback()].
+    pub fn edits(&self) -> impl Iterator<Item = &RefEdit> + '_ {
+        self.updates.iter().flatten().map(|edit| &edit.update)
+    }
+
+    /// Rollback all intermediate state and return the `RefEdits` as we know them thus far.
+    ///
+    /// Note that they have been altered compared to what was initially provided as they have
+    /// been split and know about their current state in the stack.
+    ///
+    /// # Note
+    ///
+    /// A rollback happens automatically as this instance is dropped as well.
+    pub fn rollback(self) -> Vec<RefEdit> {
+        self.updates
+            .map(|updates| updates.into_iter().map(|u| u.update).collect())
+            .unwrap_or_default()
+    }
+}
+
+mod error {
+    use gix_object::bstr::BString;
+
+    use crate::{store_impl::reftable, Target};
+
+    /// The error returned by [`Transaction::prepare()`][crate::reftable::Transaction::prepare()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The lock for the list of tables could not be obtained")]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error("The stack of tables could not be loaded")]
+        Stack(#[from] reftable::stack::Error),
+        #[error("A reference could not be read")]
+        Find(#[from] reftable::find::Error),
+        #[error("Edit preprocessing failed with an error")]
+        PreprocessingFailed(#[source] std::io::Error),
+        #[error("The reference {full_name:?} for deletion did not exist or could not be parsed")]
+        DeleteReferenceMustExist { full_name: BString },
+        #[error("Reference {full_name:?} was not supposed to exist when writing it with value {new:?}, but actual content was {actual:?}")]
+        MustNotExist {
+            full_name: BString,
+            actual: Target,
+            new: Target,
+        },
+        #[error("Reference {full_name:?} was supposed to exist with value {expected}, but didn't.")]
+        MustExist { full_name: BString, expected: Target },
+        #[error("The reference {full_name:?} should have content {expected}, actual content was {actual}")]
+        ReferenceOutOfDate {
+            full_name: BString,
+            expected: Target,
+            actual: Target,
+        },
+    }
+}
+
+pub use error::Error;
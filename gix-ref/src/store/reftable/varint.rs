@@ -0,0 +1,55 @@
+//! The variable length integer encoding of reftables, which is the same as the one used for offsets in packs.
+
+/// Decode an integer from the beginning of `data` and return it along with the amount of bytes it occupied,
+/// or `None` if `data` ended prematurely or the value overflows.
+pub(crate) fn decode(data: &[u8]) -> Option<(u64, usize)> {
+    let mut byte = *data.first()?;
+    let mut value = u64::from(byte & 0x7f);
+    let mut consumed = 1;
+    while byte & 0x80 != 0 {
+        byte = *data.get(consumed)?;
+        consumed += 1;
+        if value >= u64::MAX >> 7 {
+            return None;
+        }
+        value = ((value + 1) << 7) | u64::from(byte & 0x7f);
+    }
+    Some((value, consumed))
+}
+
+/// Append `value` to `out`.
+pub(crate) fn encode(mut value: u64, out: &mut Vec<u8>) {
+    let mut buf = [0u8; 10];
+    let mut pos = buf.len() - 1;
+    buf[pos] = (value & 0x7f) as u8;
+    loop {
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+        value -= 1;
+        pos -= 1;
+        buf[pos] = 0x80 | (value & 0x7f) as u8;
+    }
+    out.extend_from_slice(&buf[pos..]);
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn round_trip() {
+        for value in [0, 1, 127, 128, 255, 16511, 16512, u64::from(u32::MAX), u64::MAX] {
+            let mut buf = Vec::new();
+            super::encode(value, &mut buf);
+            assert_eq!(super::decode(&buf), Some((value, buf.len())), "{value}");
+        }
+    }
+
+    #[test]
+    fn known_encodings() {
+        let mut buf = Vec::new();
+        super::encode(128, &mut buf);
+        assert_eq!(buf, [0x80, 0x00], "the offset by one makes encodings unique");
+        assert_eq!(super::decode(&[0x80]), None, "incomplete input");
+    }
+}
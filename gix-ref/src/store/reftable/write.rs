@@ -0,0 +1,452 @@
+use std::{collections::BTreeMap, io::Write};
+
+use crate::store_impl::reftable::{
+    block::{self, log_key},
+    decode::{FOOTER_LEN_AFTER_HEADER, HASH_ID_SHA256, HEADER_LEN_V2, SIGNATURE},
+    varint, Log, Ref, RefValue,
+};
+
+/// The error returned by [`table()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The record for {name:?} doesn't fit into a block of {block_size} bytes")]
+    RecordTooLarge {
+        name: crate::bstr::BString,
+        block_size: u32,
+    },
+    #[error("Records must be sorted and unique, but {name:?} wasn't")]
+    Unsorted { name: crate::bstr::BString },
+    #[error("Update index {update_index} of {name:?} is outside of the table's range")]
+    UpdateIndexOutOfRange {
+        name: crate::bstr::BString,
+        update_index: u64,
+    },
+    #[error("The reflog message of {name:?} must not contain newlines")]
+    MultiLineMessage { name: crate::bstr::BString },
+    #[error("Could not compress a log block")]
+    Deflate(#[from] std::io::Error),
+}
+
+/// Options for writing a table.
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// The size of each block of the reference section, which are padded to this size.
+    ///
+    /// Log blocks are compressed and never padded, but have at most this size before compression.
+    pub block_size: u32,
+    /// After how many records a restart point is placed, which is a record whose key is stored in full.
+    pub restart_interval: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            block_size: 4096,
+            restart_interval: 16,
+        }
+    }
+}
+
+/// Encode `refs` and `logs` into a new table whose records are in the range of `min_update_index` to `max_update_index`,
+/// both inclusive, with all ids being of `object_hash`.
+///
+/// `refs` must be sorted by name, and `logs` by name and then by update index, from newest to oldest.
+/// Each of them must not contain the same name, or the same name and update index, more than once.
+/// The update index of `refs` must be within the table's range, whereas logs may refer to older updates, which is
+/// needed to delete them.
+pub fn table(
+    refs: &[Ref],
+    logs: &[Log],
+    min_update_index: u64,
+    max_update_index: u64,
+    object_hash: gix_hash::Kind,
+    options: Options,
+) -> Result<Vec<u8>, Error> {
+    // Like `git`, only use version 2 if needed to be able to indicate the hash.
+    let version = match object_hash {
+        gix_hash::Kind::Sha1 => 1,
+        gix_hash::Kind::Sha256 => 2,
+    };
+    let mut header = Vec::with_capacity(HEADER_LEN_V2);
+    header.extend_from_slice(SIGNATURE);
+    header.push(version);
+    header.extend_from_slice(&(options.block_size).to_be_bytes()[1..]);
+    header.extend_from_slice(&min_update_index.to_be_bytes());
+    header.extend_from_slice(&max_update_index.to_be_bytes());
+    if version == 2 {
+        header.extend_from_slice(&HASH_ID_SHA256.to_be_bytes());
+    }
+
+    let mut out = header.clone();
+    let mut writer = BlockWriter::new(block::REF, &options, 0, header.len());
+    let mut prev: Option<&Ref> = None;
+    let mut record = Vec::new();
+    let mut ref_blocks_by_id = BTreeMap::<gix_hash::ObjectId, Vec<usize>>::new();
+    for r in refs {
+        if prev.map_or(false, |prev| prev.name >= r.name) {
+            return Err(Error::Unsorted { name: r.name.clone() });
+        }
+        check_update_index(r.name.as_ref(), r.update_index, min_update_index, max_update_index)?;
+        prev = Some(r);
+        let (typ, value) = encode_ref_value(r, min_update_index);
+        if !writer.add(&mut out, &r.name, typ, &value, &mut record)? {
+            return Err(Error::RecordTooLarge {
+                name: r.name.clone(),
+                block_size: options.block_size,
+            });
+        }
+        let ids = match &r.value {
+            RefValue::Object(id) => [Some(id), None],
+            RefValue::PeeledTag { target, peeled } => [Some(target), Some(peeled)],
+            RefValue::Deletion | RefValue::Symbolic(_) => [None, None],
+        };
+        for id in ids.into_iter().flatten() {
+            let offsets = ref_blocks_by_id.entry(*id).or_default();
+            if offsets.last() != Some(&writer.block_start) {
+                offsets.push(writer.block_start);
+            }
+        }
+    }
+    writer.flush(&mut out)?;
+    let ref_index_offset = write_index(
+        &mut out,
+        std::mem::take(&mut writer.index),
+        &options,
+        header.len(),
+        &mut record,
+    )?;
+
+    // Like git, only write the object section if there are enough reference blocks to make it worthwhile.
+    let (obj_offset, obj_id_len, obj_index_offset) = if ref_index_offset != 0 {
+        write_objects(&mut out, &ref_blocks_by_id, &options, header.len(), &mut record)?
+    } else {
+        (0, 0, 0)
+    };
+
+    let mut log_offset = 0;
+    let mut log_index_offset = 0;
+    if !logs.is_empty() {
+        // Without references, the log section starts with the first block, which includes the file header.
+        if out.len() != header.len() {
+            log_offset = out.len();
+        }
+        let mut writer = BlockWriter::new(block::LOG, &options, log_offset, header.len());
+        let mut key = Vec::new();
+        let mut prev_key = Vec::new();
+        for log in logs {
+            log_key(&log.name, log.update_index, &mut key);
+            if !prev_key.is_empty() && prev_key >= key {
+                return Err(Error::Unsorted { name: log.name.clone() });
+            }
+            std::mem::swap(&mut prev_key, &mut key);
+            let (typ, value) = encode_log_value(log)?;
+            if !writer.add(&mut out, &prev_key, typ, &value, &mut record)? {
+                return Err(Error::RecordTooLarge {
+                    name: log.name.clone(),
+                    block_size: options.block_size,
+                });
+            }
+        }
+        writer.flush(&mut out)?;
+        log_index_offset = write_index(
+            &mut out,
+            std::mem::take(&mut writer.index),
+            &options,
+            header.len(),
+            &mut record,
+        )?;
+    }
+
+    let footer_start = out.len();
+    out.extend_from_slice(&header);
+    for offset in [
+        ref_index_offset,
+        (obj_offset << 5) | obj_id_len,
+        obj_index_offset,
+        log_offset,
+        log_index_offset,
+    ] {
+        let offset = offset as u64;
+        out.extend_from_slice(&offset.to_be_bytes());
+    }
+    let checksum = gix_features::hash::crc32(&out[footer_start..]);
+    out.extend_from_slice(&checksum.to_be_bytes());
+    debug_assert_eq!(out.len() - footer_start, header.len() + FOOTER_LEN_AFTER_HEADER);
+    Ok(out)
+}
+
+/// Write the object section for `ref_blocks_by_id`, mapping object ids to the offsets of the reference blocks which contain
+/// references pointing to them, and return its offset, the length of the id prefixes used as keys and the offset of its index.
+fn write_objects(
+    out: &mut Vec<u8>,
+    ref_blocks_by_id: &BTreeMap<gix_hash::ObjectId, Vec<usize>>,
+    options: &Options,
+    file_header_len: usize,
+    record: &mut Vec<u8>,
+) -> Result<(usize, usize, usize), Error> {
+    // Like git, use the shortest prefix which keeps all ids unique, but at least 2 bytes.
+    let obj_id_len = ref_blocks_by_id
+        .keys()
+        .zip(ref_blocks_by_id.keys().skip(1))
+        .map(|(a, b)| {
+            a.as_bytes()
+                .iter()
+                .zip(b.as_bytes())
+                .take_while(|(a, b)| a == b)
+                .count()
+                + 1
+        })
+        .max()
+        .unwrap_or(0)
+        .max(2);
+    let obj_offset = out.len();
+    let mut writer = BlockWriter::new(block::OBJ, options, obj_offset, file_header_len);
+    for (id, offsets) in ref_blocks_by_id {
+        let key = &id.as_bytes()[..obj_id_len];
+        let (typ, value) = encode_obj_value(offsets);
+        if writer.add(out, key, typ, &value, record)? {
+            continue;
+        }
+        // Like git, omit the offsets if there are too many, which makes readers search all reference blocks.
+        let (typ, value) = encode_obj_value(&[]);
+        if !writer.add(out, key, typ, &value, record)? {
+            return Err(Error::RecordTooLarge {
+                name: id.to_string().into(),
+                block_size: options.block_size,
+            });
+        }
+    }
+    writer.flush(out)?;
+    let obj_index_offset = write_index(out, std::mem::take(&mut writer.index), options, file_header_len, record)?;
+    Ok((obj_offset, obj_id_len, obj_index_offset))
+}
+
+/// Write index blocks for `index`, the last key and the offset of each block of a section, and return the offset of the
+/// top-most index level, or 0 if the section has too few blocks to need an index.
+///
+/// Like git, more levels are added until the top-most level has no more than 3 blocks.
+fn write_index(
+    out: &mut Vec<u8>,
+    mut index: Vec<(Vec<u8>, usize)>,
+    options: &Options,
+    file_header_len: usize,
+    record: &mut Vec<u8>,
+) -> Result<usize, Error> {
+    const MAX_BLOCKS_WITHOUT_INDEX: usize = 3;
+    let mut index_offset = 0;
+    let mut value = Vec::new();
+    while index.len() > MAX_BLOCKS_WITHOUT_INDEX {
+        index_offset = out.len();
+        let mut writer = BlockWriter::new(block::INDEX, options, index_offset, file_header_len);
+        for (key, offset) in &index {
+            value.clear();
+            varint::encode(*offset as u64, &mut value);
+            if !writer.add(out, key, 0, &value, record)? {
+                return Err(Error::RecordTooLarge {
+                    name: key.clone().into(),
+                    block_size: options.block_size,
+                });
+            }
+        }
+        writer.flush(out)?;
+        index = std::mem::take(&mut writer.index);
+    }
+    Ok(index_offset)
+}
+
+fn check_update_index(name: &crate::bstr::BStr, update_index: u64, min: u64, max: u64) -> Result<(), Error> {
+    if (min..=max).contains(&update_index) {
+        Ok(())
+    } else {
+        Err(Error::UpdateIndexOutOfRange {
+            name: name.to_owned(),
+            update_index,
+        })
+    }
+}
+
+fn encode_ref_value(r: &Ref, min_update_index: u64) -> (u8, Vec<u8>) {
+    let mut out = Vec::new();
+    varint::encode(r.update_index - min_update_index, &mut out);
+    let typ = match &r.value {
+        RefValue::Deletion => 0,
+        RefValue::Object(id) => {
+            out.extend_from_slice(id.as_bytes());
+            1
+        }
+        RefValue::PeeledTag { target, peeled } => {
+            out.extend_from_slice(target.as_bytes());
+            out.extend_from_slice(peeled.as_bytes());
+            2
+        }
+        RefValue::Symbolic(target) => {
+            varint::encode(target.len() as u64, &mut out);
+            out.extend_from_slice(target);
+            3
+        }
+    };
+    (typ, out)
+}
+
+fn encode_obj_value(offsets: &[usize]) -> (u8, Vec<u8>) {
+    let mut out = Vec::new();
+    let typ = if (1..8).contains(&offsets.len()) {
+        offsets.len() as u8
+    } else {
+        varint::encode(offsets.len() as u64, &mut out);
+        0
+    };
+    let mut prev = 0;
+    for (idx, offset) in offsets.iter().enumerate() {
+        varint::encode((if idx == 0 { *offset } else { offset - prev }) as u64, &mut out);
+        prev = *offset;
+    }
+    (typ, out)
+}
+
+fn encode_log_value(log: &Log) -> Result<(u8, Vec<u8>), Error> {
+    let mut out = Vec::new();
+    let line = match &log.line {
+        Some(line) => line,
+        None => return Ok((0, out)),
+    };
+    if line.message.contains(&b'\n') {
+        return Err(Error::MultiLineMessage { name: log.name.clone() });
+    }
+    let string = |s: &[u8], out: &mut Vec<u8>| {
+        varint::encode(s.len() as u64, out);
+        out.extend_from_slice(s);
+    };
+    out.extend_from_slice(line.previous_oid.as_bytes());
+    out.extend_from_slice(line.new_oid.as_bytes());
+    string(&line.signature.name, &mut out);
+    string(&line.signature.email, &mut out);
+    varint::encode(line.signature.time.seconds_since_unix_epoch.into(), &mut out);
+    out.extend_from_slice(&((line.signature.time.offset_in_seconds / 60) as i16).to_be_bytes());
+    // Like git, we store messages with a trailing newline.
+    let mut message = line.message.clone();
+    if !message.is_empty() {
+        message.push(b'\n');
+    }
+    string(&message, &mut out);
+    Ok((1, out))
+}
+
+/// A utility to fill blocks with records and to write them once they are full.
+struct BlockWriter<'a> {
+    typ: u8,
+    options: &'a Options,
+    /// The offset in the table at which the current block starts.
+    block_start: usize,
+    /// The uncompressed block, starting with its header.
+    buf: Vec<u8>,
+    /// The amount of bytes in front of the block header, the file header, if this is the first block.
+    header_off: usize,
+    /// The length of the file header.
+    file_header_len: usize,
+    restarts: Vec<usize>,
+    num_records: usize,
+    prev_key: Vec<u8>,
+    /// The last key and the offset of each block written so far, to be able to write an index for them.
+    index: Vec<(Vec<u8>, usize)>,
+}
+
+impl<'a> BlockWriter<'a> {
+    fn new(typ: u8, options: &'a Options, block_start: usize, file_header_len: usize) -> Self {
+        let mut writer = BlockWriter {
+            typ,
+            options,
+            block_start,
+            buf: Vec::new(),
+            header_off: 0,
+            file_header_len,
+            restarts: Vec::new(),
+            num_records: 0,
+            prev_key: Vec::new(),
+            index: Vec::new(),
+        };
+        writer.reset(block_start);
+        writer
+    }
+
+    fn reset(&mut self, block_start: usize) {
+        self.block_start = block_start;
+        self.header_off = if block_start == 0 { self.file_header_len } else { 0 };
+        self.buf.clear();
+        self.buf.extend_from_slice(&[self.typ, 0, 0, 0]);
+        self.restarts.clear();
+        self.num_records = 0;
+        self.prev_key.clear();
+    }
+
+    /// Add a record with `key`, `typ` and `value`, returning false if it doesn't fit into a block at all.
+    fn add(
+        &mut self,
+        out: &mut Vec<u8>,
+        key: &[u8],
+        typ: u8,
+        value: &[u8],
+        record: &mut Vec<u8>,
+    ) -> std::io::Result<bool> {
+        for attempt in 0..2 {
+            let is_restart = self.num_records % self.options.restart_interval.max(1) == 0;
+            let prefix_len = if is_restart {
+                0
+            } else {
+                key.iter().zip(self.prev_key.iter()).take_while(|(a, b)| a == b).count()
+            };
+            record.clear();
+            varint::encode(prefix_len as u64, record);
+            varint::encode((((key.len() - prefix_len) as u64) << 3) | u64::from(typ), record);
+            record.extend_from_slice(&key[prefix_len..]);
+            record.extend_from_slice(value);
+
+            let num_restarts = self.restarts.len() + usize::from(is_restart);
+            let size = self.header_off + self.buf.len() + record.len() + num_restarts * 3 + 2;
+            if size <= self.options.block_size as usize {
+                if is_restart {
+                    self.restarts.push(self.header_off + self.buf.len());
+                }
+                self.buf.extend_from_slice(record);
+                self.prev_key.clear();
+                self.prev_key.extend_from_slice(key);
+                self.num_records += 1;
+                return Ok(true);
+            }
+            if attempt == 1 || self.num_records == 0 {
+                break;
+            }
+            self.flush(out)?;
+        }
+        Ok(false)
+    }
+
+    /// Write the current block if it contains records.
+    fn flush(&mut self, out: &mut Vec<u8>) -> std::io::Result<()> {
+        if self.num_records == 0 {
+            return Ok(());
+        }
+        for restart in &self.restarts {
+            self.buf.extend_from_slice(&(*restart as u32).to_be_bytes()[1..]);
+        }
+        self.buf.extend_from_slice(&(self.restarts.len() as u16).to_be_bytes());
+        let block_len = self.header_off + self.buf.len();
+        self.buf[1..4].copy_from_slice(&(block_len as u32).to_be_bytes()[1..]);
+
+        if self.typ == block::LOG {
+            out.extend_from_slice(&self.buf[..4]);
+            let mut deflate = gix_features::zlib::stream::deflate::Write::new(&mut *out);
+            deflate.write_all(&self.buf[4..])?;
+            deflate.flush()?;
+        } else {
+            out.extend_from_slice(&self.buf);
+            // Like git, we pad all reference blocks, which also tells readers that blocks are aligned.
+            out.resize(self.block_start + (self.options.block_size as usize).max(block_len), 0);
+        }
+        self.index.push((std::mem::take(&mut self.prev_key), self.block_start));
+        let next_start = out.len();
+        self.reset(next_start);
+        Ok(())
+    }
+}
@@ -65,3 +65,82 @@ mod iter_rev {
         Ok(())
     }
 }
+
+mod retain {
+    use gix_lock::acquire::Fail;
+
+    #[test]
+    fn removes_lines_and_keeps_the_order_of_the_remaining_ones() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_writable_standalone("make_repo_for_reflog.sh")?;
+        let store = crate::file::Store::at(
+            dir.path().join(".git"),
+            gix_ref::store::WriteReflog::Normal,
+            gix_hash::Kind::Sha1,
+        );
+        let mut buf = Vec::new();
+        let messages = |store: &crate::file::Store, buf: &mut Vec<u8>| -> crate::Result<Vec<String>> {
+            Ok(store
+                .reflog_iter("HEAD", buf)?
+                .expect("exists")
+                .map(|line| line.map(|line| line.message.to_string()))
+                .collect::<Result<_, _>>()?)
+        };
+        let before = messages(&store, &mut buf)?;
+
+        let mut index = 0;
+        let removed = store.reflog_retain("HEAD", Fail::Immediately, |_| {
+            index += 1;
+            index % 2 == 1
+        })?;
+        assert_eq!(removed, 2);
+        assert_eq!(
+            messages(&store, &mut buf)?,
+            before.iter().step_by(2).cloned().collect::<Vec<_>>()
+        );
+
+        assert_eq!(store.reflog_retain("HEAD", Fail::Immediately, |_| true)?, 0);
+        assert_eq!(
+            store.reflog_retain("refs/heads/does-not-exist", Fail::Immediately, |_| false)?,
+            0
+        );
+        Ok(())
+    }
+    #[test]
+    fn removing_the_most_recent_line_can_update_the_reference() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_writable_standalone("make_repo_for_reflog.sh")?;
+        let store = crate::file::Store::at(
+            dir.path().join(".git"),
+            gix_ref::store::WriteReflog::Normal,
+            gix_hash::Kind::Sha1,
+        );
+        let mut buf = vec![0; 1024];
+        let previous = store
+            .reflog_iter_rev("refs/heads/main", &mut buf)?
+            .expect("exists")
+            .nth(1)
+            .expect("more than one line")?
+            .new_oid;
+
+        let mut num_lines = store.reflog_iter("refs/heads/main", &mut buf)?.expect("exists").count();
+        let removed = store.reflog_retain_and_update_ref("refs/heads/main", Fail::Immediately, |_| {
+            num_lines -= 1;
+            num_lines != 0
+        })?;
+        assert_eq!(removed, 1);
+        assert_eq!(
+            store.find_loose("refs/heads/main")?.target.try_id(),
+            Some(previous.as_ref()),
+            "the reference points to the new value of the line that is now the most recent one"
+        );
+        assert_eq!(
+            store
+                .reflog_iter_rev("refs/heads/main", &mut buf)?
+                .expect("exists")
+                .next()
+                .expect("one line")?
+                .new_oid,
+            previous
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --ref-format=reftable
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git tag -a -m "annotated tag" v1
+git commit -q --allow-empty -m c2
+
+# enough references for git to write multiple blocks, along with index and object sections
+for n in $(seq 1000); do
+  echo "create refs/heads/branch-$n HEAD~$((n % 2))"
+done | git update-ref --stdin
+
+git pack-refs --all
+git update-ref -m "new branch" refs/heads/other HEAD~1
+git branch -q -D branch-1
+
+git for-each-ref --format='%(refname) %(objectname)' > for-each-ref.txt
+git for-each-ref --points-at=HEAD~1 --format='%(refname)' > points-at.txt
+git reflog show --format='%H %gs' refs/heads/main > reflog-main.txt
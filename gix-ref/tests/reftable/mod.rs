@@ -0,0 +1,798 @@
+use std::convert::TryInto;
+
+use gix_ref::{
+    bstr::BString,
+    reftable,
+    reftable::{Log, Ref, RefValue},
+    store::WriteReflog,
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+fn signature() -> gix_actor::Signature {
+    gix_actor::Signature {
+        name: "committer".into(),
+        email: "committer@example.com".into(),
+        time: gix_actor::Time {
+            seconds_since_unix_epoch: 1234567890,
+            offset_in_seconds: -7200,
+            sign: gix_actor::Sign::Minus,
+        },
+    }
+}
+
+fn id(n: u8) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from([n; 20])
+}
+
+fn update(name: &str, new: Target, expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: format!("update {name}").into(),
+            },
+            expected,
+            new,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn delete(name: &str, expected: PreviousValue) -> RefEdit {
+    RefEdit {
+        change: Change::Delete {
+            expected,
+            log: RefLog::AndReference,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn empty_store() -> crate::Result<(tempfile::TempDir, reftable::Store)> {
+    let dir = tempfile::tempdir()?;
+    let store = reftable::Store::at(dir.path(), WriteReflog::Normal, gix_hash::Kind::Sha1);
+    Ok((dir, store))
+}
+
+fn commit(store: &reftable::Store, edits: impl IntoIterator<Item = RefEdit>) -> crate::Result<Vec<RefEdit>> {
+    let committer = signature();
+    Ok(store
+        .transaction()
+        .prepare(edits, gix_lock::acquire::Fail::Immediately)?
+        .commit(committer.to_ref())?)
+}
+
+mod table {
+    use super::*;
+
+    fn refs(count: usize) -> Vec<Ref> {
+        let mut refs: Vec<_> = (0..count)
+            .map(|n| Ref {
+                name: format!("refs/heads/branch-{n:04}").into(),
+                update_index: 1 + (n % 3) as u64,
+                value: match n % 4 {
+                    0 => RefValue::Object(id(n as u8)),
+                    1 => RefValue::PeeledTag {
+                        target: id(n as u8),
+                        peeled: id(!(n as u8)),
+                    },
+                    2 => RefValue::Symbolic("refs/heads/main".into()),
+                    _ => RefValue::Deletion,
+                },
+            })
+            .collect();
+        refs.insert(
+            0,
+            Ref {
+                name: "HEAD".into(),
+                update_index: 1,
+                value: RefValue::Symbolic("refs/heads/main".into()),
+            },
+        );
+        refs
+    }
+
+    fn logs(count: usize) -> Vec<Log> {
+        let mut logs = Vec::new();
+        for n in 0..count {
+            for update_index in (1..=3).rev() {
+                logs.push(Log {
+                    name: format!("refs/heads/branch-{n:04}").into(),
+                    update_index,
+                    line: (update_index != 2).then(|| gix_ref::log::Line {
+                        previous_oid: id(update_index as u8),
+                        new_oid: id(update_index as u8 + 1),
+                        signature: signature(),
+                        message: format!("change {update_index} of {n}").into(),
+                    }),
+                });
+            }
+        }
+        logs
+    }
+
+    fn options(block_size: u32) -> reftable::write::Options {
+        reftable::write::Options {
+            block_size,
+            restart_interval: 4,
+        }
+    }
+
+    #[test]
+    fn round_trip_with_many_blocks() -> crate::Result {
+        for block_size in [256, 1024, 4096] {
+            let refs = refs(500);
+            let logs = logs(100);
+            let data = reftable::write::table(&refs, &logs, 1, 3, gix_hash::Kind::Sha1, options(block_size))?;
+            let table = reftable::Table::from_bytes(data)?;
+            assert_eq!(table.min_update_index(), 1);
+            assert_eq!(table.max_update_index(), 3);
+            assert_eq!(table.refs()?, refs, "all refs are read back in order");
+            assert_eq!(
+                table.logs()?,
+                logs,
+                "all logs are read back in order, including deletions"
+            );
+
+            for r in &refs {
+                assert_eq!(
+                    table.find_ref(r.name.as_ref())?.as_ref(),
+                    Some(r),
+                    "every ref can be found by seeking through restart points"
+                );
+            }
+            for missing in ["A", "refs/heads/branch-0000-", "refs/heads/branch-0499x", "zzz"] {
+                assert_eq!(table.find_ref(missing.as_bytes())?, None);
+                assert!(table.find_logs(missing.as_bytes())?.is_empty());
+            }
+            for r in &refs {
+                assert_eq!(
+                    table.find_logs(r.name.as_ref())?,
+                    logs.iter()
+                        .filter(|log| log.name == r.name)
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                    "logs of each ref can be found by seeking"
+                );
+            }
+            for n in [0, 1, 42, 254, 255] {
+                let id = id(n);
+                assert_eq!(
+                    table.refs_pointing_to(&id)?,
+                    refs.iter()
+                        .filter(|r| match &r.value {
+                            RefValue::Object(oid) => *oid == id,
+                            RefValue::PeeledTag { target, peeled } => *target == id || *peeled == id,
+                            _ => false,
+                        })
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                    "refs pointing to an object can be found with and without object section"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the offsets of the reference index, object section, object index, log section and log index from the footer.
+    fn section_offsets(data: &[u8]) -> [u64; 5] {
+        let footer = &data[data.len() - 4 - 5 * 8..data.len() - 4];
+        let mut out = [0; 5];
+        for (offset, bytes) in out.iter_mut().zip(footer.chunks(8)) {
+            *offset = u64::from_be_bytes(bytes.try_into().expect("8 bytes"));
+        }
+        out
+    }
+
+    #[test]
+    fn indices_are_written_only_for_sections_with_many_blocks() -> crate::Result {
+        let data = reftable::write::table(&refs(500), &logs(100), 1, 3, gix_hash::Kind::Sha1, options(256))?;
+        let [ref_index, obj, obj_index, log, log_index] = section_offsets(&data);
+        assert_ne!(ref_index, 0, "many reference blocks have an index");
+        assert_eq!(
+            obj & 0x1f,
+            2,
+            "the shortest unique prefix of all ids is used, but at least 2 bytes"
+        );
+        assert!(obj >> 5 > ref_index, "the object section follows the reference index");
+        assert_ne!(obj_index, 0, "many object blocks have an index");
+        assert!(log > obj_index, "logs come last");
+        assert!(log_index > log, "and have an index as well");
+
+        let data = reftable::write::table(&refs(10), &logs(3), 1, 3, gix_hash::Kind::Sha1, options(4096))?;
+        let [ref_index, obj, obj_index, log, log_index] = section_offsets(&data);
+        assert_eq!(
+            [ref_index, obj, obj_index, log_index],
+            [0; 4],
+            "a few blocks are read without index, and objects are found by scanning all references"
+        );
+        assert_ne!(log, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_with_sha256() -> crate::Result {
+        let sha256 = |n: u8| gix_hash::ObjectId::from([n; 32]);
+        let refs = vec![
+            Ref {
+                name: "refs/heads/main".into(),
+                update_index: 1,
+                value: RefValue::Object(sha256(1)),
+            },
+            Ref {
+                name: "refs/tags/v1".into(),
+                update_index: 1,
+                value: RefValue::PeeledTag {
+                    target: sha256(2),
+                    peeled: sha256(1),
+                },
+            },
+        ];
+        let logs = vec![Log {
+            name: "refs/heads/main".into(),
+            update_index: 1,
+            line: Some(gix_ref::log::Line {
+                previous_oid: gix_hash::Kind::Sha256.null(),
+                new_oid: sha256(1),
+                signature: signature(),
+                message: "created".into(),
+            }),
+        }];
+        let data = reftable::write::table(&refs, &logs, 1, 1, gix_hash::Kind::Sha256, options(256))?;
+        assert_eq!(data[4], 2, "version 2 is needed to indicate the hash");
+        let table = reftable::Table::from_bytes(data)?;
+        assert_eq!(table.refs()?, refs);
+        assert_eq!(table.logs()?, logs);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_table() -> crate::Result {
+        let data = reftable::write::table(&[], &[], 1, 1, gix_hash::Kind::Sha1, Default::default())?;
+        let table = reftable::Table::from_bytes(data)?;
+        assert!(table.refs()?.is_empty());
+        assert!(table.logs()?.is_empty());
+        assert_eq!(table.find_ref(b"HEAD")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn only_logs() -> crate::Result {
+        let logs = logs(3);
+        let data = reftable::write::table(&[], &logs, 1, 3, gix_hash::Kind::Sha1, Default::default())?;
+        let table = reftable::Table::from_bytes(data)?;
+        assert!(table.refs()?.is_empty());
+        assert_eq!(table.logs()?, logs);
+        Ok(())
+    }
+
+    #[test]
+    fn corruption_is_detected() -> crate::Result {
+        let mut data = reftable::write::table(&refs(10), &[], 1, 3, gix_hash::Kind::Sha1, Default::default())?;
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        assert!(matches!(
+            reftable::Table::from_bytes(data.clone()),
+            Err(reftable::decode::Error::Checksum { .. })
+        ));
+        assert!(matches!(
+            reftable::Table::from_bytes(b"REFT".to_vec()),
+            Err(reftable::decode::Error::Truncated)
+        ));
+        data[0] = b'X';
+        assert!(reftable::Table::from_bytes(data).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        let mut refs = refs(2);
+        refs.swap(0, 1);
+        assert!(matches!(
+            reftable::write::table(&refs, &[], 1, 3, gix_hash::Kind::Sha1, Default::default()),
+            Err(reftable::write::Error::Unsorted { .. })
+        ));
+        assert!(matches!(
+            reftable::write::table(
+                &super::table::refs(2),
+                &[],
+                2,
+                3,
+                gix_hash::Kind::Sha1,
+                Default::default()
+            ),
+            Err(reftable::write::Error::UpdateIndexOutOfRange { .. })
+        ));
+    }
+}
+
+mod store {
+    use super::*;
+
+    #[test]
+    fn an_empty_store_has_no_references() -> crate::Result {
+        let (_dir, store) = empty_store()?;
+        assert!(store.stack()?.tables().next().is_none());
+        assert_eq!(store.try_find("HEAD")?, None);
+        assert_eq!(store.iter()?.all().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn transactions_add_tables_which_can_be_read_back() -> crate::Result {
+        let (_dir, store) = empty_store()?;
+        commit(
+            &store,
+            [
+                update(
+                    "HEAD",
+                    Target::Symbolic("refs/heads/main".try_into()?),
+                    PreviousValue::Any,
+                ),
+                update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::MustNotExist),
+                update("refs/tags/v1", Target::Peeled(id(2)), PreviousValue::MustNotExist),
+            ],
+        )?;
+        commit(
+            &store,
+            Some(update(
+                "refs/heads/main",
+                Target::Peeled(id(3)),
+                PreviousValue::MustExistAndMatch(Target::Peeled(id(1))),
+            )),
+        )?;
+
+        let head = store.find("HEAD")?;
+        assert_eq!(
+            head.target.to_ref().try_name().map(|n| n.as_bstr()),
+            Some("refs/heads/main".into())
+        );
+        assert_eq!(
+            store.find("main")?.target.try_id(),
+            Some(id(3).as_ref()),
+            "partial names work"
+        );
+        assert_eq!(store.find("v1")?.name.as_bstr(), "refs/tags/v1");
+
+        let names: Vec<_> = store
+            .iter()?
+            .all()
+            .map(|r| r.map(|r| r.name.as_bstr().to_owned()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(names, vec!["HEAD", "refs/heads/main", "refs/tags/v1"]);
+        let names: Vec<_> = store
+            .iter()?
+            .prefixed("refs/heads")
+            .map(|r| r.map(|r| r.name.as_bstr().to_owned()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(names, vec!["refs/heads/main"]);
+
+        let log = store
+            .reflog("refs/heads/main".try_into()?)?
+            .expect("branches have a log");
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].previous_oid, gix_hash::Kind::Sha1.null());
+        assert_eq!(log[0].new_oid, id(1));
+        assert_eq!(log[1].previous_oid, id(1));
+        assert_eq!(log[1].new_oid, id(3));
+        assert_eq!(log[1].message, "update refs/heads/main");
+        assert_eq!(log[1].signature, signature());
+        assert!(
+            !store.reflog_exists("refs/tags/v1".try_into()?)?,
+            "tags don't get a log by default"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn symbolic_refs_are_dereferenced_and_peeled() -> crate::Result {
+        let (_dir, store) = empty_store()?;
+        commit(
+            &store,
+            [
+                update(
+                    "HEAD",
+                    Target::Symbolic("refs/heads/main".try_into()?),
+                    PreviousValue::Any,
+                ),
+                update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::Any),
+            ],
+        )?;
+        let mut edit = update("HEAD", Target::Peeled(id(2)), PreviousValue::Any);
+        edit.deref = true;
+        let edits = commit(&store, Some(edit))?;
+        assert_eq!(edits.len(), 2, "the edit was split to update the referent as well");
+
+        let mut head = store.find("HEAD")?;
+        assert_eq!(head.target.kind(), gix_ref::Kind::Symbolic, "HEAD stays symbolic");
+        assert_eq!(store.peel_to_id_in_place(&mut head, gix_ref::peel::none)?, id(2));
+        assert_eq!(head.name.as_bstr(), "refs/heads/main", "peeling follows symbolic refs");
+
+        let head_log = store.reflog("HEAD".try_into()?)?.expect("HEAD has a log");
+        assert_eq!(head_log.len(), 1);
+        assert_eq!(
+            head_log[0].previous_oid,
+            id(1),
+            "the previous value of the referent is used"
+        );
+        assert_eq!(head_log[0].new_oid, id(2));
+        Ok(())
+    }
+
+    #[test]
+    fn expectations_are_validated_and_leave_the_store_unchanged() -> crate::Result {
+        let (_dir, store) = empty_store()?;
+        commit(
+            &store,
+            Some(update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::Any)),
+        )?;
+        let tables_before = store.stack()?.names().map(ToOwned::to_owned).collect::<Vec<_>>();
+
+        let err = commit(
+            &store,
+            Some(update(
+                "refs/heads/main",
+                Target::Peeled(id(2)),
+                PreviousValue::MustNotExist,
+            )),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("was not supposed to exist"), "{err}");
+        let err = commit(
+            &store,
+            Some(update(
+                "refs/heads/main",
+                Target::Peeled(id(2)),
+                PreviousValue::MustExistAndMatch(Target::Peeled(id(3))),
+            )),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("should have content"), "{err}");
+        let err = commit(&store, Some(delete("refs/heads/other", PreviousValue::MustExist))).unwrap_err();
+        assert!(err.to_string().contains("for deletion did not exist"), "{err}");
+
+        assert_eq!(
+            store.stack()?.names().map(ToOwned::to_owned).collect::<Vec<_>>(),
+            tables_before
+        );
+        assert_eq!(store.find("main")?.target.try_id(), Some(id(1).as_ref()));
+        Ok(())
+    }
+
+    #[test]
+    fn the_list_of_tables_is_locked_during_transactions() -> crate::Result {
+        let (_dir, store) = empty_store()?;
+        let prepared = store.transaction().prepare(
+            Some(update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::Any)),
+            gix_lock::acquire::Fail::Immediately,
+        )?;
+        assert!(
+            store
+                .transaction()
+                .prepare(
+                    Some(update("refs/heads/other", Target::Peeled(id(1)), PreviousValue::Any)),
+                    gix_lock::acquire::Fail::Immediately,
+                )
+                .is_err(),
+            "only one transaction can be prepared at a time"
+        );
+        assert_eq!(prepared.rollback().len(), 1);
+        commit(
+            &store,
+            Some(update("refs/heads/other", Target::Peeled(id(1)), PreviousValue::Any)),
+        )?;
+        assert_eq!(
+            store.try_find("main")?,
+            None,
+            "the rolled back transaction had no effect"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deletions_shadow_older_tables_and_remove_the_log() -> crate::Result {
+        let (_dir, store) = empty_store()?;
+        commit(
+            &store,
+            [
+                update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::Any),
+                update("refs/heads/other", Target::Peeled(id(1)), PreviousValue::Any),
+            ],
+        )?;
+        let edits = commit(&store, Some(delete("refs/heads/main", PreviousValue::MustExist)))?;
+        assert_eq!(
+            edits[0]
+                .change
+                .previous_value()
+                .and_then(|v| v.try_id().map(ToOwned::to_owned)),
+            Some(id(1)),
+            "the previous value is provided"
+        );
+        assert_eq!(store.try_find("main")?, None);
+        assert!(!store.reflog_exists("refs/heads/main".try_into()?)?);
+        assert_eq!(store.iter()?.all().count(), 1);
+        assert!(store.reflog_exists("refs/heads/other".try_into()?)?);
+        Ok(())
+    }
+
+    #[test]
+    fn the_stack_reflects_tables_added_by_other_instances() -> crate::Result {
+        let (dir, store) = empty_store()?;
+        commit(
+            &store,
+            Some(update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::Any)),
+        )?;
+        let stack = store.stack()?;
+        assert_eq!(stack.tables().count(), 1);
+
+        let other = reftable::Store::at(dir.path(), WriteReflog::Normal, gix_hash::Kind::Sha1);
+        commit(
+            &other,
+            Some(update("refs/heads/main", Target::Peeled(id(2)), PreviousValue::Any)),
+        )?;
+        let stack = store.stack()?;
+        assert_eq!(
+            stack.find_ref(b"refs/heads/main")?.map(|r| r.value),
+            Some(RefValue::Object(id(2))),
+            "the list of tables is always read to pick up new tables"
+        );
+        assert_eq!(stack.logs_of(b"refs/heads/main")?.len(), 2);
+        assert_eq!(
+            stack.refs_pointing_to(&id(1))?,
+            Vec::new(),
+            "older tables are shadowed by newer ones"
+        );
+
+        other.compact(gix_lock::acquire::Fail::Immediately)?;
+        let stack = store.stack()?;
+        assert_eq!(stack.tables().count(), 1, "compacted tables are dropped");
+        assert_eq!(stack.refs_pointing_to(&id(2))?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn compaction_merges_tables_without_changing_the_state() -> crate::Result {
+        let (_dir, store) = empty_store()?;
+        for n in 1..=20u8 {
+            commit(
+                &store,
+                [
+                    update(&format!("refs/heads/b{n}"), Target::Peeled(id(n)), PreviousValue::Any),
+                    update("refs/heads/main", Target::Peeled(id(n)), PreviousValue::Any),
+                ],
+            )?;
+        }
+        commit(&store, Some(delete("refs/heads/b1", PreviousValue::Any)))?;
+        let tables = store.stack()?.tables().count();
+        assert!(tables < 20, "tables are merged automatically, got {tables} tables");
+
+        let refs_before = store.stack()?.refs()?;
+        let log_before = store.reflog("refs/heads/main".try_into()?)?;
+        assert_eq!(refs_before.len(), 20, "19 branches and main");
+
+        let merged = store.compact(gix_lock::acquire::Fail::Immediately)?;
+        assert_eq!(merged, if tables > 1 { tables } else { 0 });
+        let stack = store.stack()?;
+        assert_eq!(stack.tables().count(), 1);
+        assert_eq!(stack.refs()?, refs_before);
+        assert_eq!(store.reflog("refs/heads/main".try_into()?)?, log_before);
+        assert_eq!(log_before.map(|log| log.len()), Some(20));
+        assert_eq!(
+            std::fs::read_dir(store.reftable_dir())?.count(),
+            2,
+            "the obsolete tables were deleted, leaving only the new table and the list"
+        );
+        assert_eq!(
+            store.compact(gix_lock::acquire::Fail::Immediately)?,
+            0,
+            "nothing to do with a single table"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn namespaces_are_applied_to_reads_and_writes() -> crate::Result {
+        let (_dir, mut store) = empty_store()?;
+        store.namespace = Some(gix_ref::namespace::expand("foo")?);
+        commit(
+            &store,
+            Some(update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::Any)),
+        )?;
+        assert_eq!(store.find("main")?.name.as_bstr(), "refs/heads/main");
+        assert_eq!(store.iter()?.prefixed("refs/heads").count(), 1);
+
+        store.namespace = None;
+        assert_eq!(store.try_find("refs/heads/main")?, None);
+        assert!(store.try_find("refs/namespaces/foo/refs/heads/main")?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn special_refs_are_read_from_files() -> crate::Result {
+        let (dir, store) = empty_store()?;
+        std::fs::write(dir.path().join("MERGE_HEAD"), format!("{}\n", id(5)))?;
+        assert_eq!(store.find("MERGE_HEAD")?.target.try_id(), Some(id(5).as_ref()));
+        Ok(())
+    }
+}
+
+mod general_store {
+    use super::*;
+
+    fn names(handle: &gix_ref::store::Handle) -> crate::Result<Vec<BString>> {
+        Ok(handle
+            .iter()?
+            .prefixed("refs")?
+            .map(|r| r.map(|r| r.name.as_bstr().to_owned()))
+            .collect::<Result<_, _>>()?)
+    }
+
+    #[test]
+    fn both_backends_are_supported_through_the_same_api() -> crate::Result {
+        let loose_dir = tempfile::tempdir()?;
+        let reftable_dir = tempfile::tempdir()?;
+        std::fs::create_dir(reftable_dir.path().join("reftable"))?;
+        for (dir, is_reftable) in [(&loose_dir, false), (&reftable_dir, true)] {
+            let store = gix_ref::Store::at(dir.path(), WriteReflog::Normal, gix_hash::Kind::Sha1)?;
+            assert_eq!(store.is_reftable(), is_reftable);
+            let handle = store.to_handle();
+            assert_eq!(handle.try_find("main")?, None);
+            let committer = signature();
+            handle
+                .transaction()
+                .prepare(
+                    [
+                        update(
+                            "HEAD",
+                            Target::Symbolic("refs/heads/main".try_into()?),
+                            PreviousValue::Any,
+                        ),
+                        update("refs/heads/main", Target::Peeled(id(1)), PreviousValue::Any),
+                    ],
+                    gix_lock::acquire::Fail::Immediately,
+                )?
+                .commit(committer.to_ref())?;
+
+            assert_eq!(names(&handle)?, vec!["refs/heads/main"]);
+            let mut head = handle.find("HEAD")?;
+            assert_eq!(handle.peel_to_id_in_place(&mut head, gix_ref::peel::none)?, id(1));
+            assert_eq!(
+                dir.path().join("refs/heads/main").is_file(),
+                !is_reftable,
+                "reftables don't write loose refs"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reflogs_are_read_and_rewritten_through_the_same_api() -> crate::Result {
+        let loose_dir = tempfile::tempdir()?;
+        let reftable_dir = tempfile::tempdir()?;
+        std::fs::create_dir(reftable_dir.path().join("reftable"))?;
+        for dir in [&loose_dir, &reftable_dir] {
+            let handle = gix_ref::Store::at(dir.path(), WriteReflog::Normal, gix_hash::Kind::Sha1)?.to_handle();
+            let committer = signature();
+            for (previous, new) in [(None, 1), (Some(1), 2), (Some(2), 3)] {
+                let expected = previous.map_or(PreviousValue::MustNotExist, |previous| {
+                    PreviousValue::MustExistAndMatch(Target::Peeled(id(previous)))
+                });
+                handle
+                    .transaction()
+                    .prepare(
+                        Some(update("refs/heads/main", Target::Peeled(id(new)), expected)),
+                        gix_lock::acquire::Fail::Immediately,
+                    )?
+                    .commit(committer.to_ref())?;
+            }
+            let name: &gix_ref::FullNameRef = "refs/heads/main".try_into()?;
+            assert!(handle.reflog_exists(name)?);
+            let mut log = handle.log_iter(name);
+            let new_ids = |log: &mut gix_ref::store::reflog::Platform<'_, '_>| -> crate::Result<Vec<_>> {
+                Ok(log
+                    .rev()?
+                    .expect("log present")
+                    .map(|line| line.map(|line| line.new_oid))
+                    .collect::<Result<_, _>>()?)
+            };
+            assert_eq!(new_ids(&mut log)?, [id(3), id(2), id(1)], "most recent first");
+            assert_eq!(
+                log.all()?
+                    .expect("log present")
+                    .map(|line| line.map(|line| line.new_oid()))
+                    .collect::<Result<Vec<_>, _>>()?,
+                [id(1), id(2), id(3)],
+                "oldest first"
+            );
+
+            let lock_mode = gix_lock::acquire::Fail::Immediately;
+            assert_eq!(
+                handle.reflog_retain(name, lock_mode, |line| line.new_oid() != id(2))?,
+                1
+            );
+            assert_eq!(new_ids(&mut log)?, [id(3), id(1)]);
+            assert_eq!(handle.find("main")?.target.try_id(), Some(id(3).as_ref()));
+
+            assert_eq!(
+                handle.reflog_retain_and_update_ref(name, lock_mode, |line| line.new_oid() != id(3))?,
+                1
+            );
+            assert_eq!(new_ids(&mut log)?, [id(1)]);
+            assert_eq!(
+                handle.find("main")?.target.try_id(),
+                Some(id(1).as_ref()),
+                "the reference points to the most recent line that was kept"
+            );
+            assert_eq!(handle.reflog_retain(name, lock_mode, |_| true)?, 0, "nothing to remove");
+        }
+        Ok(())
+    }
+}
+
+mod written_by_git {
+    use gix_ref::bstr::ByteSlice;
+
+    use super::*;
+
+    #[test]
+    fn references_logs_and_object_lookups_match_git() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 45, 0) {
+            return Ok(());
+        }
+        let dir = gix_testtools::scripted_fixture_read_only_standalone("make_reftable_repository.sh")?;
+        let store = reftable::Store::at(dir.join(".git"), WriteReflog::Normal, gix_hash::Kind::Sha1);
+        let stack = store.stack()?;
+        assert!(
+            stack.tables().count() > 1,
+            "git adds a table for each change after packing"
+        );
+
+        let expected = std::fs::read(dir.join("for-each-ref.txt"))?;
+        let mut num_refs = 0;
+        for line in expected.lines() {
+            let (name, id) = line.split_once_str(" ").expect("name and id");
+            let target = match stack.find_ref(name)?.expect("every ref exists").value {
+                RefValue::Object(id) | RefValue::PeeledTag { target: id, .. } => id,
+                other => unreachable!("only objects are referenced, got {other:?}"),
+            };
+            assert_eq!(target.to_string().as_bytes(), id);
+            num_refs += 1;
+        }
+        assert_eq!(stack.refs()?.len(), num_refs, "git deleted one of the branches");
+        assert_eq!(stack.find_ref(b"refs/heads/branch-1")?, None);
+
+        let main = stack.find_ref(b"refs/heads/main")?.expect("present");
+        let parent = match stack.find_ref(b"refs/heads/other")?.expect("present").value {
+            RefValue::Object(id) => id,
+            _ => unreachable!("other is a branch"),
+        };
+        assert_ne!(main.value, RefValue::Object(parent));
+        let names: Vec<_> = stack
+            .refs_pointing_to(&parent)?
+            .into_iter()
+            .map(|r| r.name.to_string())
+            .collect();
+        assert_eq!(
+            names.join("\n"),
+            std::fs::read_to_string(dir.join("points-at.txt"))?.trim_end(),
+            "the object section is used to find refs pointing to an object, including peeled tags"
+        );
+
+        let reflog: Vec<_> = store
+            .reflog("refs/heads/main".try_into()?)?
+            .expect("present")
+            .into_iter()
+            .rev()
+            .map(|line| format!("{} {}", line.new_oid, line.message))
+            .collect();
+        assert_eq!(
+            reflog.join("\n"),
+            std::fs::read_to_string(dir.join("reflog-main.txt"))?.trim_end()
+        );
+        Ok(())
+    }
+}
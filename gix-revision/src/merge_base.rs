@@ -0,0 +1,377 @@
+/// The error returned by [`merge_base()`][function::merge_base()] and the other functions in this module.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error<E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    #[error("Commit {} could not be found during graph traversal", .oid.to_hex())]
+    Find {
+        #[source]
+        err: Option<E>,
+        oid: gix_hash::ObjectId,
+    },
+    #[error("A commit could not be decoded during traversal")]
+    Decode(#[from] gix_object::decode::Error),
+    #[error("The parents of a commit could not be read from the commit-graph")]
+    CommitGraph(#[from] gix_commitgraph::file::commit::Error),
+}
+
+pub use function::{independent, is_ancestor, octopus, symmetric_difference};
+
+pub(crate) mod function {
+    use std::{cmp::Reverse, collections::BinaryHeap};
+
+    use gix_hash::{oid, ObjectId};
+    use gix_hashtable::{hash_map, HashMap};
+    use gix_object::CommitRefIter;
+
+    use super::Error;
+
+    /// Return all best common ancestors of `first` and all of `others`, similar to `git merge-base --all <first> <others>…`.
+    ///
+    /// With more than one commit in `others`, the result are the best common ancestors of `first` and a hypothetical merge
+    /// commit of all `others`. The returned commits are sorted with the most recent one first, and the list is empty if
+    /// there is no common history.
+    ///
+    /// `find` is used to lookup commits by id and to decode them. If `cache` is set, generation numbers, commit times and
+    /// parents are read from the commit-graph instead, which allows to stop traversals early and avoids decoding commits,
+    /// and `find` is only used for commits that are not part of it.
+    pub fn merge_base<Find, E>(
+        first: &oid,
+        others: &[ObjectId],
+        cache: Option<&gix_commitgraph::Graph>,
+        find: Find,
+    ) -> Result<Vec<ObjectId>, Error<E>>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<CommitRefIter<'b>>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Graph::new(cache, find).merge_bases(first, others)
+    }
+
+    /// Return all best common ancestors of all `commits` as if they were merged in an octopus merge, similar to
+    /// `git merge-base --all --octopus <commits>…`.
+    ///
+    /// None of the returned commits can be reached from any of the others.
+    ///
+    /// See [`merge_base()`] for details about `cache` and `find`.
+    pub fn octopus<Find, E>(
+        commits: &[ObjectId],
+        cache: Option<&gix_commitgraph::Graph>,
+        find: Find,
+    ) -> Result<Vec<ObjectId>, Error<E>>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<CommitRefIter<'b>>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut graph = Graph::new(cache, find);
+        let mut bases: Vec<ObjectId> = commits.iter().take(1).cloned().collect();
+        for commit in commits.iter().skip(1) {
+            let mut next_bases = Vec::new();
+            for base in &bases {
+                for id in graph.merge_bases(base, std::slice::from_ref(commit))? {
+                    if !next_bases.contains(&id) {
+                        next_bases.push(id);
+                    }
+                }
+            }
+            bases = next_bases;
+        }
+        graph.remove_redundant(bases)
+    }
+
+    /// Return `true` if `ancestor` is reachable from `descendant`, similar to `git merge-base --is-ancestor`.
+    ///
+    /// Note that each commit is considered its own ancestor.
+    /// See [`merge_base()`] for details about `cache` and `find`.
+    pub fn is_ancestor<Find, E>(
+        ancestor: &oid,
+        descendant: &oid,
+        cache: Option<&gix_commitgraph::Graph>,
+        find: Find,
+    ) -> Result<bool, Error<E>>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<CommitRefIter<'b>>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        if ancestor == descendant {
+            return Ok(true);
+        }
+        let mut graph = Graph::new(cache, find);
+        let min_generation = graph.commit(ancestor)?.generation;
+        graph.paint_down_to_common(ancestor, &[descendant.to_owned()], min_generation)?;
+        Ok(graph.commits[ancestor].flags & PARENT2 != 0)
+    }
+
+    /// Return those of `commits` that can't be reached from any of the other `commits`, similar to
+    /// `git merge-base --independent`.
+    ///
+    /// The order of `commits` is retained, and duplicates are removed.
+    /// See [`merge_base()`] for details about `cache` and `find`.
+    pub fn independent<Find, E>(
+        commits: &[ObjectId],
+        cache: Option<&gix_commitgraph::Graph>,
+        find: Find,
+    ) -> Result<Vec<ObjectId>, Error<E>>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<CommitRefIter<'b>>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut unique = Vec::with_capacity(commits.len());
+        for id in commits {
+            if !unique.contains(id) {
+                unique.push(*id);
+            }
+        }
+        Graph::new(cache, find).remove_redundant(unique)
+    }
+
+    /// Return the commits that can only be reached from `left` and those that can only be reached from `right`, similar to
+    /// `git rev-list --left-right <left>...<right>`, with the most recent commits first.
+    ///
+    /// Both sides are painted in a single walk that stops as soon as all remaining commits can be reached from both
+    /// sides, which is what git does to count how far a branch is ahead and behind its upstream.
+    /// See [`merge_base()`] for details about `cache` and `find`.
+    pub fn symmetric_difference<Find, E>(
+        left: &oid,
+        right: &oid,
+        cache: Option<&gix_commitgraph::Graph>,
+        find: Find,
+    ) -> Result<(Vec<ObjectId>, Vec<ObjectId>), Error<E>>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<CommitRefIter<'b>>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut graph = Graph::new(cache, find);
+        graph.paint_down_to_common(left, &[right.to_owned()], 0)?;
+
+        let mut commits: Vec<_> = graph
+            .commits
+            .iter()
+            .filter(|(_, commit)| {
+                let sides = commit.flags & (PARENT1 | PARENT2);
+                sides == PARENT1 || sides == PARENT2
+            })
+            .map(|(id, commit)| {
+                (
+                    Reverse((commit.generation, commit.time)),
+                    *id,
+                    commit.flags & PARENT1 != 0,
+                )
+            })
+            .collect();
+        commits.sort();
+        let (left, right): (Vec<_>, Vec<_>) = commits.into_iter().partition(|(_, _, is_left)| *is_left);
+        Ok((
+            left.into_iter().map(|(_, id, _)| id).collect(),
+            right.into_iter().map(|(_, id, _)| id).collect(),
+        ))
+    }
+
+    type Flags = u8;
+    /// The commit can be reached from the first commit.
+    const PARENT1: Flags = 1 << 0;
+    /// The commit can be reached from one of the other commits.
+    const PARENT2: Flags = 1 << 1;
+    /// The commit can be reached from a common ancestor, and thus isn't interesting anymore.
+    const STALE: Flags = 1 << 2;
+    /// The commit was already added to the list of common ancestors.
+    const RESULT: Flags = 1 << 3;
+
+    struct Commit {
+        parents: Vec<ObjectId>,
+        generation: u32,
+        time: u64,
+        flags: Flags,
+    }
+
+    /// The order in which commits are processed: the highest generation first, and for equal generations the most
+    /// recent commit, and finally the commit that was queued first.
+    type QueueKey = (u32, u64, Reverse<usize>);
+
+    struct Graph<'cache, Find> {
+        cache: Option<&'cache gix_commitgraph::Graph>,
+        find: Find,
+        buf: Vec<u8>,
+        commits: HashMap<ObjectId, Commit>,
+        queue: BinaryHeap<(QueueKey, ObjectId)>,
+        num_queued: usize,
+    }
+
+    impl<'cache, Find, E> Graph<'cache, Find>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<Option<CommitRefIter<'b>>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        fn new(cache: Option<&'cache gix_commitgraph::Graph>, find: Find) -> Self {
+            Graph {
+                cache,
+                find,
+                buf: Vec::new(),
+                commits: Default::default(),
+                queue: Default::default(),
+                num_queued: 0,
+            }
+        }
+
+        fn merge_bases(&mut self, first: &oid, others: &[ObjectId]) -> Result<Vec<ObjectId>, Error<E>> {
+            if others.iter().any(|other| other.as_ref() == first) {
+                return Ok(vec![first.to_owned()]);
+            }
+            let bases = self.paint_down_to_common(first, others, 0)?;
+            self.clear_flags();
+            if bases.len() < 2 {
+                return Ok(bases);
+            }
+            self.remove_redundant(bases)
+        }
+
+        /// Walk down from `first` and `others` until all commits in the queue are reachable by both sides, and return the
+        /// commits that were found to be reachable from both in the order in which they were found.
+        ///
+        /// Commits with a generation lower than `min_generation` aren't traversed.
+        fn paint_down_to_common(
+            &mut self,
+            first: &oid,
+            others: &[ObjectId],
+            min_generation: u32,
+        ) -> Result<Vec<ObjectId>, Error<E>> {
+            self.queue.clear();
+            self.paint_and_queue(first, PARENT1)?;
+            for other in others {
+                self.paint_and_queue(other, PARENT2)?;
+            }
+
+            let mut bases = Vec::new();
+            while self.has_non_stale_commits() {
+                let ((generation, _time, _order), id) = self.queue.pop().expect("non-empty");
+                if generation < min_generation {
+                    break;
+                }
+                let commit = self.commits.get_mut(&id).expect("queued commits are known");
+                let mut flags = commit.flags & (PARENT1 | PARENT2 | STALE);
+                if flags == (PARENT1 | PARENT2) {
+                    if commit.flags & RESULT == 0 {
+                        commit.flags |= RESULT;
+                        bases.push(id);
+                    }
+                    flags |= STALE;
+                }
+                for parent in commit.parents.clone() {
+                    self.paint_and_queue(&parent, flags)?;
+                }
+            }
+            Ok(bases)
+        }
+
+        /// Return `commits` without those that can be reached from any of the other `commits`, retaining their order.
+        fn remove_redundant(&mut self, commits: Vec<ObjectId>) -> Result<Vec<ObjectId>, Error<E>> {
+            let mut redundant = vec![false; commits.len()];
+            for (idx, commit) in commits.iter().enumerate() {
+                if redundant[idx] {
+                    continue;
+                }
+                let others: Vec<_> = commits
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_idx, _)| *other_idx != idx && !redundant[*other_idx])
+                    .map(|(_, id)| *id)
+                    .collect();
+                let mut min_generation = u32::MAX;
+                for other in &others {
+                    min_generation = min_generation.min(self.commit(other)?.generation);
+                }
+
+                self.paint_down_to_common(commit, &others, min_generation)?;
+                if self.commits[commit].flags & PARENT2 != 0 {
+                    redundant[idx] = true;
+                }
+                for (other_idx, other) in commits.iter().enumerate() {
+                    if other_idx != idx && self.commits.get(other).map_or(false, |c| c.flags & PARENT1 != 0) {
+                        redundant[other_idx] = true;
+                    }
+                }
+                self.clear_flags();
+            }
+            Ok(commits
+                .into_iter()
+                .zip(redundant)
+                .filter_map(|(id, redundant)| (!redundant).then_some(id))
+                .collect())
+        }
+
+        /// Add `flags` to the commit with `id` and queue it, unless it already has all of them.
+        fn paint_and_queue(&mut self, id: &oid, flags: Flags) -> Result<(), Error<E>> {
+            let commit = self.commit(id)?;
+            if commit.flags & flags == flags {
+                return Ok(());
+            }
+            commit.flags |= flags;
+            let key = (commit.generation, commit.time, Reverse(self.num_queued));
+            self.num_queued += 1;
+            self.queue.push((key, id.to_owned()));
+            Ok(())
+        }
+
+        fn has_non_stale_commits(&self) -> bool {
+            self.queue.iter().any(|(_, id)| self.commits[id].flags & STALE == 0)
+        }
+
+        fn clear_flags(&mut self) {
+            for commit in self.commits.values_mut() {
+                commit.flags = 0;
+            }
+        }
+
+        /// Lookup the commit with `id` in the commit-graph if possible, or decode it otherwise.
+        fn commit(&mut self, id: &oid) -> Result<&mut Commit, Error<E>> {
+            let entry = match self.commits.entry(id.to_owned()) {
+                hash_map::Entry::Occupied(entry) => return Ok(entry.into_mut()),
+                hash_map::Entry::Vacant(entry) => entry,
+            };
+            if let Some((cache, commit)) = self.cache.and_then(|cache| cache.commit_by_id(id).map(|c| (cache, c))) {
+                let parents = commit
+                    .iter_parents()
+                    .map(|pos| pos.map(|pos| cache.id_at(pos).to_owned()))
+                    .collect::<Result<_, _>>()?;
+                return Ok(entry.insert(Commit {
+                    parents,
+                    generation: commit.generation(),
+                    time: commit.committer_timestamp(),
+                    flags: 0,
+                }));
+            }
+
+            let iter = (self.find)(id, &mut self.buf)
+                .map_err(|err| Error::Find {
+                    err: Some(err),
+                    oid: id.to_owned(),
+                })?
+                .ok_or_else(|| Error::Find {
+                    err: None,
+                    oid: id.to_owned(),
+                })?;
+            let mut parents = Vec::new();
+            let mut time = 0;
+            for token in iter {
+                match token? {
+                    gix_object::commit::ref_iter::Token::Tree { .. }
+                    | gix_object::commit::ref_iter::Token::Author { .. } => continue,
+                    gix_object::commit::ref_iter::Token::Parent { id } => parents.push(id),
+                    gix_object::commit::ref_iter::Token::Committer { signature } => {
+                        time = signature.time.seconds_since_unix_epoch as u64;
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+            Ok(entry.insert(Commit {
+                parents,
+                generation: gix_commitgraph::GENERATION_NUMBER_INFINITY,
+                time,
+                flags: 0,
+            }))
+        }
+    }
+}
@@ -0,0 +1,74 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config merge.ff false
+
+function commit() {
+  git commit -q --allow-empty -m "$1"
+  git tag "$1"
+}
+
+function merge() {
+  git merge -q --no-edit -m "$1" "$2"
+  git tag "$1"
+}
+
+# A---B---C---------X1--X2
+#  \   \           /
+#   \   E         /
+#    \           /
+#     D---------Y1--Y2
+#
+# with X1 merging D and Y1 merging C, so X2 and Y2 have two best common ancestors.
+git checkout -q -b main
+commit A
+commit B
+commit C
+
+git checkout -q -b e B
+commit E
+
+git checkout -q -b d A
+commit D
+
+git checkout -q -b x C
+merge X1 D
+commit X2
+
+git checkout -q -b y D
+merge Y1 C
+commit Y2
+
+git checkout -q --orphan unrelated
+commit U
+
+# Record what git thinks is correct, one commit per line.
+function baseline() {
+  local name=${1:?}
+  shift
+  git merge-base "$@" > ".git/$name.baseline" || true
+}
+
+baseline two A C
+baseline two-reversed C A
+baseline criss-cross --all X2 Y2
+baseline many --all E X2 D
+baseline octopus --all --octopus C D E
+baseline octopus-criss-cross --all --octopus X2 Y2 E
+baseline independent --independent C B D X1 E
+baseline unrelated --all U C
+
+# Record the commits only reachable from either side, as `<` or `>` followed by the commit.
+function left_right() {
+  local name=${1:?}
+  shift
+  git rev-list --left-right "$@" > ".git/$name.left-right"
+}
+
+left_right criss-cross X2...Y2
+left_right ahead E...X2
+left_right unrelated U...C
+left_right same C...C
+
+git commit-graph write --no-progress --reachable
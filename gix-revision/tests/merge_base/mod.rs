@@ -0,0 +1,175 @@
+use gix::odb::FindExt;
+use gix_hash::ObjectId;
+use gix_revision::merge_base;
+
+struct Fixture {
+    repo: gix::Repository,
+    graph: gix_commitgraph::Graph,
+}
+
+impl Fixture {
+    fn new() -> crate::Result<Self> {
+        let dir = gix_testtools::scripted_fixture_read_only_standalone("make_merge_base_repo.sh")?;
+        let repo = gix::open(dir)?;
+        let graph = gix_commitgraph::Graph::from_info_dir(repo.objects.store_ref().path().join("info"))?;
+        Ok(Fixture { repo, graph })
+    }
+
+    fn ids(&self, tags: &[&str]) -> crate::Result<Vec<ObjectId>> {
+        tags.iter()
+            .map(|tag| {
+                Ok(self
+                    .repo
+                    .rev_parse_single(format!("refs/tags/{tag}").as_str())?
+                    .detach())
+            })
+            .collect()
+    }
+
+    fn baseline(&self, name: &str) -> crate::Result<Vec<ObjectId>> {
+        let ids = std::fs::read_to_string(self.repo.git_dir().join(format!("{name}.baseline")))?;
+        Ok(ids.lines().map(crate::hex_to_id).collect())
+    }
+
+    /// Run `f` without and with a commit-graph.
+    fn with_and_without_graph(
+        &self,
+        mut f: impl FnMut(Option<&gix_commitgraph::Graph>, &Find<'_>) -> crate::Result,
+    ) -> crate::Result {
+        let find: &Find<'_> = &|id, buf| Ok(Some(self.repo.objects.find_commit_iter(id, buf)?));
+        f(None, find)?;
+        f(Some(&self.graph), find)
+    }
+}
+
+type Find<'a> = dyn for<'b> Fn(
+        &gix_hash::oid,
+        &'b mut Vec<u8>,
+    ) -> Result<
+        Option<gix_object::CommitRefIter<'b>>,
+        gix::odb::find::existing_iter::Error<gix::odb::store::find::Error>,
+    > + 'a;
+
+#[test]
+fn merge_base_matches_git() -> crate::Result {
+    let fixture = Fixture::new()?;
+    for (name, first, others) in [
+        ("two", "A", &["C"][..]),
+        ("two-reversed", "C", &["A"]),
+        ("criss-cross", "X2", &["Y2"]),
+        ("many", "E", &["X2", "D"]),
+        ("unrelated", "U", &["C"]),
+    ] {
+        let first = fixture.ids(&[first])?[0];
+        let others = fixture.ids(others)?;
+        let mut expected = fixture.baseline(name)?;
+        expected.sort();
+        fixture.with_and_without_graph(|graph, find| {
+            let mut actual = gix_revision::merge_base(&first, &others, graph, find)?;
+            actual.sort();
+            assert_eq!(actual, expected, "{name}, graph: {}", graph.is_some());
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+#[test]
+fn merge_base_of_commit_with_itself_is_the_commit() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let ids = fixture.ids(&["X2", "X2"])?;
+    fixture.with_and_without_graph(|graph, find| {
+        assert_eq!(merge_base(&ids[0], &ids[1..], graph, find)?, &ids[..1]);
+        Ok(())
+    })
+}
+
+#[test]
+fn octopus_matches_git() -> crate::Result {
+    let fixture = Fixture::new()?;
+    for (name, commits) in [
+        ("octopus", &["C", "D", "E"][..]),
+        ("octopus-criss-cross", &["X2", "Y2", "E"]),
+    ] {
+        let commits = fixture.ids(commits)?;
+        let expected = fixture.baseline(name)?;
+        fixture.with_and_without_graph(|graph, find| {
+            assert_eq!(
+                merge_base::octopus(&commits, graph, find)?,
+                expected,
+                "{name}, graph: {}",
+                graph.is_some()
+            );
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+#[test]
+fn independent_matches_git() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let commits = fixture.ids(&["C", "B", "D", "X1", "E", "E"])?;
+    let expected = fixture.baseline("independent")?;
+    fixture.with_and_without_graph(|graph, find| {
+        assert_eq!(merge_base::independent(&commits, graph, find)?, expected);
+        Ok(())
+    })
+}
+
+#[test]
+fn is_ancestor() -> crate::Result {
+    let fixture = Fixture::new()?;
+    for (ancestor, descendant, expected) in [
+        ("A", "X2", true),
+        ("D", "X2", true),
+        ("X2", "X2", true),
+        ("X2", "A", false),
+        ("E", "X2", false),
+        ("Y1", "X2", false),
+        ("U", "C", false),
+    ] {
+        let ids = fixture.ids(&[ancestor, descendant])?;
+        fixture.with_and_without_graph(|graph, find| {
+            assert_eq!(
+                merge_base::is_ancestor(&ids[0], &ids[1], graph, find)?,
+                expected,
+                "{ancestor} -> {descendant}, graph: {}",
+                graph.is_some()
+            );
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+#[test]
+fn symmetric_difference_matches_git() -> crate::Result {
+    let fixture = Fixture::new()?;
+    for (name, left, right) in [
+        ("criss-cross", "X2", "Y2"),
+        ("ahead", "E", "X2"),
+        ("unrelated", "U", "C"),
+        ("same", "C", "C"),
+    ] {
+        let ids = fixture.ids(&[left, right])?;
+        let baseline = std::fs::read_to_string(fixture.repo.git_dir().join(format!("{name}.left-right")))?;
+        let side = |marker: char| {
+            let mut ids: Vec<_> = baseline
+                .lines()
+                .filter_map(|line| line.strip_prefix(marker).map(crate::hex_to_id))
+                .collect();
+            ids.sort();
+            ids
+        };
+        let expected = (side('<'), side('>'));
+        fixture.with_and_without_graph(|graph, find| {
+            let (mut left, mut right) = merge_base::symmetric_difference(&ids[0], &ids[1], graph, find)?;
+            left.sort();
+            right.sort();
+            assert_eq!((left, right), expected, "{name}, graph: {}", graph.is_some());
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
@@ -18,6 +18,7 @@ pub trait DefaultForLevel {
 }
 
 /// Associate instructions for how to deal with various `Trust` levels as they are encountered in the wild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Mapping<T> {
     /// The value for fully trusted resources.
     pub full: T,
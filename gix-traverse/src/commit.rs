@@ -56,12 +56,13 @@ impl Default for Sorting {
 pub mod ancestors {
     use std::{
         borrow::{Borrow, BorrowMut},
-        collections::VecDeque,
+        cmp::Reverse,
+        collections::{BinaryHeap, VecDeque},
         iter::FromIterator,
     };
 
     use gix_hash::{oid, ObjectId};
-    use gix_hashtable::HashSet;
+    use gix_hashtable::{HashMap, HashSet};
     use gix_object::CommitRefIter;
 
     use crate::commit::{Ancestors, Parents, Sorting};
@@ -145,6 +146,175 @@ pub mod ancestors {
         }
     }
 
+    /// Builder
+    impl<Find, Predicate, StateMut, E> Ancestors<Find, Predicate, StateMut>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+        StateMut: BorrowMut<State>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        /// Hide all commits reachable from `tips`, so that neither they nor any of their ancestors are returned, similar to
+        /// `^tip` in `git rev-list`.
+        ///
+        /// To learn which commits are hidden, all commits reachable from the tips of this instance and from `tips` are
+        /// traversed by commit time right away until the remaining ones are known to be hidden. Note that commits that
+        /// are older than their parents can cause hidden commits to be returned nonetheless.
+        ///
+        /// It's an error if any of these commits can't be found, use
+        /// [`with_hidden_in_shallow_clone()`][Self::with_hidden_in_shallow_clone()] for shallow clones.
+        pub fn with_hidden(self, tips: impl IntoIterator<Item = impl Into<ObjectId>>) -> Result<Self, Error> {
+            self.with_hidden_in_shallow_clone(tips, None::<ObjectId>)
+        }
+
+        /// Like [`with_hidden()`][Self::with_hidden()], but for shallow clones whose history ends at the `shallow` commits,
+        /// so that their parents aren't looked up as they are expected to be missing.
+        pub fn with_hidden_in_shallow_clone(
+            mut self,
+            tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+            shallow: impl IntoIterator<Item = impl Into<ObjectId>>,
+        ) -> Result<Self, Error> {
+            let hidden_tips: Vec<_> = tips.into_iter().map(Into::into).collect();
+            if hidden_tips.is_empty() {
+                return Ok(self);
+            }
+            let shallow: HashSet<_> = shallow.into_iter().map(Into::into).collect();
+            let state = self.state.borrow_mut();
+            let tips: Vec<_> = state.next.iter().map(|(id, _)| *id).collect();
+            let hidden = hidden_commits(&mut self.find, &tips, &hidden_tips, &shallow, &mut state.buf)?;
+            state.next.retain(|(id, _)| !hidden.contains(id));
+            state.seen.extend(hidden);
+            Ok(self)
+        }
+    }
+
+    /// Return all commits reachable from `hidden_tips` that might also be reachable from `tips`.
+    fn hidden_commits<Find, E>(
+        find: &mut Find,
+        tips: &[ObjectId],
+        hidden_tips: &[ObjectId],
+        shallow: &HashSet<ObjectId>,
+        buf: &mut Vec<u8>,
+    ) -> Result<HashSet<ObjectId>, Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        struct Info {
+            hidden: bool,
+            visited: bool,
+            time: TimeInSeconds,
+            parents: Vec<ObjectId>,
+        }
+
+        fn lookup<Find, E>(find: &mut Find, id: &oid, buf: &mut Vec<u8>) -> Result<Info, Error>
+        where
+            Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+            E: std::error::Error + Send + Sync + 'static,
+        {
+            let commit_iter = find(id, buf).map_err(|err| Error::FindExisting {
+                oid: id.to_owned(),
+                source: err.into(),
+            })?;
+            let mut info = Info {
+                hidden: false,
+                visited: false,
+                time: 0,
+                parents: Vec::new(),
+            };
+            for token in commit_iter {
+                match token? {
+                    gix_object::commit::ref_iter::Token::Tree { .. }
+                    | gix_object::commit::ref_iter::Token::Author { .. } => continue,
+                    gix_object::commit::ref_iter::Token::Parent { id } => info.parents.push(id),
+                    gix_object::commit::ref_iter::Token::Committer { signature } => {
+                        info.time = signature.time.seconds_since_unix_epoch;
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+            Ok(info)
+        }
+
+        let mut commits = HashMap::<ObjectId, Info>::default();
+        // Newest commits first, and the ones queued first for equal times.
+        let mut queue = BinaryHeap::<(TimeInSeconds, Reverse<usize>, ObjectId)>::new();
+        let mut num_queued = 0;
+        for (id, hidden) in tips
+            .iter()
+            .map(|id| (id, false))
+            .chain(hidden_tips.iter().map(|id| (id, true)))
+        {
+            if let Some(info) = commits.get_mut(id) {
+                info.hidden |= hidden;
+                continue;
+            }
+            let mut info = lookup(find, id, buf)?;
+            info.hidden = hidden;
+            queue.push((info.time, Reverse(num_queued), *id));
+            num_queued += 1;
+            commits.insert(*id, info);
+        }
+
+        let mut oldest_visible = None;
+        loop {
+            if queue.iter().all(|(_, _, id)| commits[id].hidden) {
+                match (queue.peek(), oldest_visible) {
+                    // The remaining commits are all older than the visible ones, so they can't hide them anymore.
+                    (Some((newest_hidden, _, _)), Some(oldest_visible)) if *newest_hidden < oldest_visible => break,
+                    (Some(_), Some(_)) => {}
+                    (None, _) | (Some(_), None) => break,
+                }
+            }
+            let (_, _, id) = queue.pop().expect("non-empty");
+            let info = commits.get_mut(&id).expect("queued commits are known");
+            info.visited = true;
+            if !info.hidden {
+                oldest_visible = Some(oldest_visible.map_or(info.time, |time: TimeInSeconds| time.min(info.time)));
+            }
+            let hidden = info.hidden;
+            if shallow.contains(&id) {
+                // The history ends here, and the parents are expected to be missing.
+                continue;
+            }
+            for parent_id in info.parents.clone() {
+                match commits.get_mut(&parent_id) {
+                    Some(parent) => {
+                        if hidden && !parent.hidden {
+                            parent.hidden = true;
+                            let mut stack = vec![parent_id];
+                            while let Some(id) = stack.pop() {
+                                let info = &commits[&id];
+                                if !info.visited {
+                                    continue;
+                                }
+                                for parent_id in info.parents.clone() {
+                                    if let Some(parent) = commits.get_mut(&parent_id) {
+                                        if !parent.hidden {
+                                            parent.hidden = true;
+                                            stack.push(parent_id);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    None => {
+                        let mut parent = lookup(find, &parent_id, buf)?;
+                        parent.hidden = hidden;
+                        queue.push((parent.time, Reverse(num_queued), parent_id));
+                        num_queued += 1;
+                        commits.insert(parent_id, parent);
+                    }
+                }
+            }
+        }
+        Ok(commits
+            .into_iter()
+            .filter_map(|(id, info)| info.hidden.then_some(id))
+            .collect())
+    }
+
     /// Initialization
     impl<Find, StateMut, E> Ancestors<Find, fn(&oid) -> bool, StateMut>
     where
@@ -9,6 +9,7 @@ mod ancestor {
         init_script: &'a str,
         tips: &'a [&'a str],
         expected: &'a [&'a str],
+        hidden: &'a [&'a str],
         mode: commit::Parents,
         sorting: commit::Sorting,
     }
@@ -19,6 +20,7 @@ mod ancestor {
                 init_script,
                 tips,
                 expected,
+                hidden: &[],
                 mode: Default::default(),
                 sorting: Default::default(),
             }
@@ -33,6 +35,11 @@ mod ancestor {
             self.sorting = sorting;
             self
         }
+
+        fn with_hidden(&mut self, hidden: &'a [&'a str]) -> &mut Self {
+            self.hidden = hidden;
+            self
+        }
     }
 
     impl TraversalAssertion<'_> {
@@ -43,6 +50,7 @@ mod ancestor {
             let expected: Vec<ObjectId> = tips
                 .clone()
                 .into_iter()
+                .filter(|tip| !self.hidden.contains(&tip.to_hex().to_string().as_str()))
                 .chain(self.expected.iter().map(|hex_id| hex_to_id(hex_id)))
                 .collect();
             Ok((store, tips, expected))
@@ -70,6 +78,7 @@ mod ancestor {
                 commit::Ancestors::new(tips, commit::ancestors::State::default(), move |oid, buf| {
                     store.find_commit_iter(oid, buf).map(|t| t.0)
                 })
+                .with_hidden(self.hidden.iter().copied().map(hex_to_id))?
                 .sorting(self.sorting)?
                 .parents(self.mode)
                 .collect();
@@ -270,4 +279,101 @@ mod ancestor {
         .with_parents(commit::Parents::First)
         .check()
     }
+
+    #[test]
+    fn hidden_tip_hides_its_ancestors() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits.sh",
+            &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"],
+            &[
+                "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+                "9152eeee2328073cf23dcf8e90c949170b711659",
+            ],
+        )
+        .with_hidden(&["9556057aee5abb06912922e9f26c46386a816822"])
+        .check()?;
+
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits.sh",
+            &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"],
+            &["efd9a841189668f1bab5b8ebade9cd0a1b139a37"],
+        )
+        .with_hidden(&["ce2e8ffaa9608a26f7b21afc1db89cadb54fd353"])
+        .with_sorting(commit::Sorting::ByCommitTimeNewestFirst)
+        .check()
+    }
+
+    #[test]
+    fn hidden_tips_hide_tips_and_commits_reachable_from_both_sides() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits.sh",
+            &[
+                "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+            ],
+            &["9152eeee2328073cf23dcf8e90c949170b711659"],
+        )
+        .with_hidden(&[
+            "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+            "9556057aee5abb06912922e9f26c46386a816822",
+        ])
+        .check()
+    }
+
+    #[test]
+    fn hidden_commits_must_exist_unless_the_history_is_shallow() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only_standalone("make_traversal_repo_for_commits.sh")?;
+        let store = gix_odb::at(dir.join(".git").join("objects"))?;
+        let (tip, hidden, missing) = (
+            hex_to_id("01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"),
+            hex_to_id("9556057aee5abb06912922e9f26c46386a816822"),
+            hex_to_id("17d78c64cef6c33a10a604573fd2c429e477fd63"),
+        );
+        let ancestors = || {
+            let store = store.clone();
+            commit::Ancestors::new(Some(tip), commit::ancestors::State::default(), move |oid, buf| {
+                if oid == missing {
+                    return Err(gix_odb::pack::find::existing_iter::Error::NotFound { oid: oid.to_owned() });
+                }
+                store.find_commit_iter(oid, buf).map(|t| t.0)
+            })
+        };
+
+        assert!(
+            matches!(
+                ancestors().with_hidden(Some(hidden)),
+                Err(commit::ancestors::Error::FindExisting { oid, .. }) if oid == missing
+            ),
+            "missing parents of hidden commits would make the walk return commits that should be hidden"
+        );
+
+        let oids = ancestors()
+            .with_hidden_in_shallow_clone(Some(hidden), Some(hidden))?
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            oids,
+            [
+                "01ec18a3ebf2855708ad3c9d244306bc1fae3e9b",
+                "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+                "9152eeee2328073cf23dcf8e90c949170b711659",
+            ]
+            .map(hex_to_id),
+            "the parents of shallow commits aren't looked up"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hidden_tips_with_dates() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits_with_dates.sh",
+            &["288e509293165cb5630d08f4185bdf2445bf6170"],
+            &["bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac"],
+        )
+        .with_hidden(&["9902e3c3e8f0c569b4ab295ddf473e6de763e1e7"])
+        .with_sorting(commit::Sorting::ByCommitTimeNewestFirst)
+        .check()
+    }
 }
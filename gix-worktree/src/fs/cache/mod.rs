@@ -29,6 +29,8 @@ pub enum State {
     },
     /// Used when providing worktree status information.
     IgnoreStack(state::Ignore),
+    /// Used when only attributes are needed, for example to learn how to diff or merge files.
+    AttributesStack(state::Attributes),
 }
 
 #[cfg(debug_assertions)]
@@ -40,6 +40,20 @@ impl<'a> Platform<'a> {
             gix_path::to_unix_separators_on_windows(gix_path::into_bstr(self.parent.stack.current_relative.as_path()));
         ignore.matching_exclude_pattern(relative_path.as_bstr(), self.is_dir, self.parent.case)
     }
+
+    /// Return the state of the attribute `name` for the currently set path, or `None` if no matching pattern mentions it.
+    ///
+    /// See [`Attributes::state()`][fs::cache::state::Attributes::state()] for details.
+    ///
+    /// # Panics
+    ///
+    /// If the cache was configured without attributes.
+    pub fn attribute_state(&self, name: &str) -> Option<gix_attributes::State> {
+        let attributes = self.parent.state.attributes_or_panic();
+        let relative_path =
+            gix_path::to_unix_separators_on_windows(gix_path::into_bstr(self.parent.stack.current_relative.as_path()));
+        attributes.state(relative_path.as_bstr(), name, self.parent.case)
+    }
 }
 
 impl<'a> std::fmt::Debug for Platform<'a> {
@@ -64,10 +78,23 @@ where
     fn push_directory(&mut self, stack: &fs::Stack) -> std::io::Result<()> {
         match &mut self.state {
             State::CreateDirectoryAndAttributesStack { attributes: _, .. } => {
-                // TODO: attributes
+                // TODO: attributes, which are read from the index when checking out
             }
-            State::AttributesAndIgnoreStack { ignore, attributes: _ } => {
-                // TODO: attributes
+            State::AttributesStack(attributes) => attributes.push_directory(
+                &stack.root,
+                &stack.current,
+                self.buf,
+                self.attribute_files_in_index,
+                &mut self.find,
+            )?,
+            State::AttributesAndIgnoreStack { ignore, attributes } => {
+                attributes.push_directory(
+                    &stack.root,
+                    &stack.current,
+                    self.buf,
+                    self.attribute_files_in_index,
+                    &mut self.find,
+                )?;
                 ignore.push_directory(
                     &stack.root,
                     &stack.current,
@@ -110,7 +137,7 @@ where
                     create_leading_directory(is_last_component, stack, self.is_dir, *unlink_on_collision)?
                 }
             }
-            State::AttributesAndIgnoreStack { .. } | State::IgnoreStack(_) => {}
+            State::AttributesAndIgnoreStack { .. } | State::IgnoreStack(_) | State::AttributesStack(_) => {}
         }
         Ok(())
     }
@@ -118,10 +145,13 @@ where
     fn pop_directory(&mut self) {
         match &mut self.state {
             State::CreateDirectoryAndAttributesStack { attributes: _, .. } => {
-                // TODO: attributes
+                // TODO: attributes, which are read from the index when checking out
+            }
+            State::AttributesStack(attributes) => {
+                attributes.pop_directory();
             }
-            State::AttributesAndIgnoreStack { attributes: _, ignore } => {
-                // TODO: attributes
+            State::AttributesAndIgnoreStack { attributes, ignore } => {
+                attributes.pop_directory();
                 ignore.pop_directory();
             }
             State::IgnoreStack(ignore) => {
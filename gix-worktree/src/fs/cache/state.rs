@@ -11,8 +11,9 @@ type IgnoreMatchGroup = gix_attributes::MatchGroup<gix_attributes::Ignore>;
 
 /// State related to attributes associated with files in the repository.
 #[derive(Default, Clone)]
-#[allow(unused)]
 pub struct Attributes {
+    /// Attribute patterns from `$GIT_DIR/info/attributes`, which are consulted first.
+    pub info: AttributeMatchGroup,
     /// Attribute patterns that match the currently set directory (in the stack).
     pub stack: AttributeMatchGroup,
     /// Attribute patterns which aren't tied to the repository root, hence are global. They are consulted last.
@@ -192,9 +193,96 @@ impl Attributes {
     pub fn new(globals: AttributeMatchGroup) -> Self {
         Attributes {
             globals,
+            info: Default::default(),
             stack: Default::default(),
         }
     }
+
+    /// Use the patterns of `info`, typically read from `$GIT_DIR/info/attributes`, which take precedence over all others.
+    pub fn with_info(mut self, info: AttributeMatchGroup) -> Self {
+        self.info = info;
+        self
+    }
+}
+
+impl Attributes {
+    /// Return the state of the attribute `name` for the file at `relative_path`, or `None` if no matching pattern mentions it.
+    ///
+    /// Patterns in [`info`][Self::info] take precedence over those in the `.gitattributes` files of the stack, where files in
+    /// deeper directories take precedence over those of their parents, and [`globals`][Self::globals] are consulted last.
+    /// The built-in `binary` macro is expanded, but macros defined in attribute files are not supported yet.
+    pub fn state(&self, relative_path: &BStr, name: &str, case: Case) -> Option<gix_attributes::State> {
+        let basename_pos = relative_path.rfind(b"/").map(|p| p + 1);
+        [&self.info, &self.stack, &self.globals]
+            .into_iter()
+            .flat_map(|group| group.patterns.iter().rev())
+            .flat_map(|list| list.patterns_matching_relative_path(relative_path, basename_pos, Some(false), case))
+            .find_map(|match_| {
+                let assignments = match match_.value {
+                    gix_attributes::Value::Assignments(assignments) => assignments,
+                    gix_attributes::Value::MacroAttributes(_) => return None,
+                };
+                assignments.iter().rev().find_map(|assignment| {
+                    if assignment.name.as_str() == name {
+                        Some(assignment.state.clone())
+                    } else if assignment.name.as_str() == "binary"
+                        && assignment.state == gix_attributes::State::Set
+                        && matches!(name, "diff" | "merge" | "text")
+                    {
+                        // The built-in `binary` macro expands to `-diff -merge -text`.
+                        Some(gix_attributes::State::Unset)
+                    } else {
+                        None
+                    }
+                })
+            })
+    }
+
+    pub(crate) fn pop_directory(&mut self) {
+        self.stack.patterns.pop().expect("something to pop");
+    }
+
+    pub(crate) fn push_directory<Find, E>(
+        &mut self,
+        root: &Path,
+        dir: &Path,
+        buf: &mut Vec<u8>,
+        attribute_files_in_index: &[PathOidMapping],
+        mut find: Find,
+    ) -> std::io::Result<()>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<gix_object::BlobRef<'b>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let rela_dir = dir.strip_prefix(root).expect("dir in root");
+        let attr_path_relative = rela_dir.join(".gitattributes");
+        let attr_path_relative = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(attr_path_relative));
+        let attr_file_in_index =
+            attribute_files_in_index.binary_search_by(|t| t.0.as_bstr().cmp(attr_path_relative.as_ref()));
+        let follow_symlinks = attr_file_in_index.is_err();
+        if !self
+            .stack
+            .add_patterns_file(dir.join(".gitattributes"), follow_symlinks, Some(root), buf)?
+        {
+            match attr_file_in_index {
+                Ok(idx) => {
+                    let blob = find(&attribute_files_in_index[idx].1, buf)
+                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+                    let attr_path = gix_path::from_bstring(attr_path_relative.into_owned());
+                    self.stack.add_patterns_buffer(blob.data, attr_path, Some(root));
+                }
+                Err(_) => {
+                    // Need one stack level per component so push and pop matches.
+                    self.stack.patterns.push(gix_attributes::PatternList {
+                        patterns: Vec::new(),
+                        source: None,
+                        base: None,
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl From<AttributeMatchGroup> for Attributes {
@@ -223,6 +311,11 @@ impl State {
     pub fn for_status(ignore: Ignore) -> Self {
         State::IgnoreStack(ignore)
     }
+
+    /// Configure a state for looking up attributes only.
+    pub fn for_attributes(attributes: Attributes) -> Self {
+        State::AttributesStack(attributes)
+    }
 }
 
 impl State {
@@ -255,6 +348,10 @@ impl State {
                 a1_backing = [(".gitattributes".into(), true)];
                 a1_backing.as_ref()
             }
+            State::AttributesStack(_) => {
+                a1_backing = [(".gitattributes".into(), false)];
+                a1_backing.as_ref()
+            }
         };
 
         index
@@ -293,9 +390,18 @@ impl State {
         match self {
             State::IgnoreStack(v) => v,
             State::AttributesAndIgnoreStack { ignore, .. } => ignore,
-            State::CreateDirectoryAndAttributesStack { .. } => {
+            State::CreateDirectoryAndAttributesStack { .. } | State::AttributesStack(_) => {
                 unreachable!("BUG: must not try to check excludes without it being setup")
             }
         }
     }
+
+    pub(crate) fn attributes_or_panic(&self) -> &Attributes {
+        match self {
+            State::AttributesStack(attributes) | State::AttributesAndIgnoreStack { attributes, .. } => attributes,
+            State::CreateDirectoryAndAttributesStack { .. } | State::IgnoreStack(_) => {
+                unreachable!("BUG: must not try to check attributes without it being setup")
+            }
+        }
+    }
 }
@@ -185,5 +185,6 @@ where
         .try_into()
         .expect("by 2038 we found a solution for this");
     stat.ctime.nsecs = ctime.subsec_nanos();
+    stat.size = meta.len() as u32;
     Ok(())
 }
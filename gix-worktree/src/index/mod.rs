@@ -8,6 +8,26 @@ use crate::fs;
 pub mod checkout;
 pub(crate) mod entry;
 
+/// Return true if `meta` of a file on disk seems to match the information `entry` has about it, without looking at the
+/// content of the file.
+///
+/// If `check_stat` is false, the sub-second portion of the modification time is ignored.
+/// Note that files that were changed in the same moment the index was written can't be detected this way.
+pub fn is_unchanged(entry: &gix_index::Entry, meta: &std::fs::Metadata, check_stat: bool) -> bool {
+    let mtime = match meta
+        .modified()
+        .ok()
+        .and_then(|mtime| mtime.duration_since(std::time::UNIX_EPOCH).ok())
+    {
+        Some(mtime) => mtime,
+        None => return false,
+    };
+    // Like `git`, only the lower 32 bits of the file size and time are stored.
+    meta.len() as u32 == entry.stat.size
+        && mtime.as_secs() as u32 == entry.stat.mtime.secs
+        && (!check_stat || mtime.subsec_nanos() == entry.stat.mtime.nsecs)
+}
+
 /// Note that interruption still produce an `Ok(…)` value, so the caller should look at `should_interrupt` to communicate the outcome.
 /// `dir` is the directory into which to checkout the `index`.
 /// `git_dir` is the `.git` directory for reading additional per-repository configuration files.
@@ -223,8 +243,8 @@ mod chunk {
         let mut bytes_written = 0;
 
         for (entry, entry_path) in entries_with_paths {
-            // TODO: write test for that
-            if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) {
+            // Sparse directories are always marked to be skipped, but we don't rely on it to not try to check them out.
+            if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) || entry.mode.is_sparse() {
                 files.inc();
                 continue;
             }
@@ -12,4 +12,7 @@ pub mod fs;
 ///
 pub mod index;
 
+///
+pub mod sparse;
+
 pub(crate) mod os;
@@ -0,0 +1,193 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+use crate::sparse::Cone;
+
+/// Initialization
+impl Cone {
+    /// Create a new instance which includes only the files in the root of the repository, along with all of `directories`.
+    pub fn new<'a>(directories: impl IntoIterator<Item = &'a BStr>) -> Self {
+        let mut cone = Cone::default();
+        for dir in directories {
+            cone.add_directory(dir);
+        }
+        cone
+    }
+
+    /// Parse `bytes` as written in _cone mode_, or return `None` if any of the contained patterns can't be expressed in it.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut cone = Cone::default();
+        for line in bytes.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with(b"#") || line == b"/*" || line == b"!/*/" {
+                continue;
+            }
+            if let Some(dir) = line.strip_prefix(b"!/").and_then(|l| l.strip_suffix(b"/*/")) {
+                // A parent directory is first listed like a recursive one, and then has its sub-directories excluded.
+                let dir = unescape(dir)?;
+                if !cone.recursive.remove(&dir) {
+                    return None;
+                }
+                cone.parents.insert(dir);
+            } else if let Some(dir) = line.strip_prefix(b"/").and_then(|l| l.strip_suffix(b"/")) {
+                cone.recursive.insert(unescape(dir)?);
+            } else {
+                return None;
+            }
+        }
+        Some(cone)
+    }
+}
+
+/// Access
+impl Cone {
+    /// Return all directories whose contents are entirely included, in order, and without trailing slash.
+    pub fn directories(&self) -> impl Iterator<Item = &BStr> + '_ {
+        self.recursive.iter().map(AsRef::as_ref)
+    }
+
+    /// Return true if the repository-relative `path` is included in the worktree, where `is_dir` is true if it's a directory.
+    pub fn is_included(&self, path: &BStr, is_dir: bool) -> bool {
+        let dir = if is_dir {
+            path
+        } else {
+            match path.rfind_byte(b'/') {
+                Some(pos) => path[..pos].as_bstr(),
+                None => return true,
+            }
+        };
+        self.parents.contains(dir) || self.is_in_recursive_directory(dir)
+    }
+
+    /// Return true if `dir` and everything below it is not included in the worktree.
+    pub fn is_excluded_directory(&self, dir: &BStr) -> bool {
+        if self.is_included(dir, true) {
+            return false;
+        }
+        let mut prefix = BString::from(dir);
+        prefix.push(b'/');
+        !self
+            .recursive
+            .range(prefix.clone()..)
+            .next()
+            .map_or(false, |candidate| candidate.starts_with(&prefix))
+    }
+
+    fn is_in_recursive_directory(&self, mut dir: &BStr) -> bool {
+        loop {
+            if self.recursive.contains(dir) {
+                return true;
+            }
+            match dir.rfind_byte(b'/') {
+                Some(pos) => dir = dir[..pos].as_bstr(),
+                None => return false,
+            }
+        }
+    }
+}
+
+/// Modification
+impl Cone {
+    /// Include `dir` and everything below it, and return true if it wasn't included already.
+    ///
+    /// Leading and trailing slashes are ignored.
+    pub fn add_directory(&mut self, dir: &BStr) -> bool {
+        let dir = trim_slashes(dir);
+        if dir.is_empty() || self.is_in_recursive_directory(dir) {
+            return false;
+        }
+        self.remove_recursive_below(dir);
+        self.recursive.insert(dir.to_owned());
+        self.recompute_parents();
+        true
+    }
+
+    /// Exclude `dir` and all directories below it that were previously added, and return true if at least one directory
+    /// was removed.
+    ///
+    /// Leading and trailing slashes are ignored. Note that directories that are merely included because one of their
+    /// parent directories is included can't be removed.
+    pub fn remove_directory(&mut self, dir: &BStr) -> bool {
+        let dir = trim_slashes(dir);
+        let removed = self.recursive.remove(dir) | self.remove_recursive_below(dir);
+        if removed {
+            self.recompute_parents();
+        }
+        removed
+    }
+
+    fn remove_recursive_below(&mut self, dir: &BStr) -> bool {
+        let num_dirs = self.recursive.len();
+        self.recursive
+            .retain(|candidate| !(candidate.starts_with(dir) && candidate.get(dir.len()) == Some(&b'/')));
+        num_dirs != self.recursive.len()
+    }
+
+    /// Make sure all leading directories of recursive directories are parents, just like `git` does.
+    fn recompute_parents(&mut self) {
+        self.parents.clear();
+        for dir in &self.recursive {
+            let mut dir = dir.as_bstr();
+            while let Some(pos) = dir.rfind_byte(b'/') {
+                dir = dir[..pos].as_bstr();
+                self.parents.insert(dir.to_owned());
+            }
+        }
+    }
+}
+
+/// Serialization
+impl Cone {
+    /// Write these patterns to `out` in the format of the `info/sparse-checkout` file, just like `git` does.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        out.write_all(b"/*\n!/*/\n")?;
+        let mut buf = BString::default();
+        for parent in &self.parents {
+            escape(parent, &mut buf);
+            out.write_all(b"/")?;
+            out.write_all(&buf)?;
+            out.write_all(b"/\n!/")?;
+            out.write_all(&buf)?;
+            out.write_all(b"/*/\n")?;
+        }
+        for dir in &self.recursive {
+            escape(dir, &mut buf);
+            out.write_all(b"/")?;
+            out.write_all(&buf)?;
+            out.write_all(b"/\n")?;
+        }
+        Ok(())
+    }
+}
+
+fn trim_slashes(dir: &BStr) -> &BStr {
+    dir.trim_start_with(|c| c == '/').trim_end_with(|c| c == '/').as_bstr()
+}
+
+fn is_glob_special(b: u8) -> bool {
+    matches!(b, b'*' | b'?' | b'[' | b'\\')
+}
+
+/// Remove escapes of glob characters, or return `None` if there are unescaped glob characters which makes `dir` a
+/// pattern that isn't supported in cone mode.
+fn unescape(dir: &[u8]) -> Option<BString> {
+    let mut out = BString::default();
+    let mut bytes = dir.iter().copied();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'\\' => out.push(bytes.next()?),
+            b if is_glob_special(b) => return None,
+            b => out.push(b),
+        }
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+fn escape(dir: &[u8], out: &mut BString) {
+    out.clear();
+    for &b in dir.iter() {
+        if is_glob_special(b) {
+            out.push_byte(b'\\');
+        }
+        out.push_byte(b);
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_features::progress::Progress;
+use gix_hash::oid;
+
+use crate::index;
+
+mod cone;
+///
+pub mod reapply;
+
+/// The patterns of a sparse checkout as stored in the `info/sparse-checkout` file, deciding which paths are included
+/// in the worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Patterns {
+    /// Patterns in _cone mode_, which include entire directories along with all files in their parent directories
+    /// and in the root of the repository.
+    Cone(Cone),
+    /// Patterns in the style of `.gitignore` files, with the difference that matching paths are included in the worktree.
+    NonCone(gix_attributes::PatternList<gix_attributes::Ignore>),
+}
+
+/// Sparse-checkout patterns in _cone mode_, as configured with `core.sparseCheckoutCone`.
+///
+/// Besides all files in the root of the repository, all files below [recursive directories][Cone::directories()] are included,
+/// as well as all files directly within their parent directories.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cone {
+    /// Directories whose entire contents are included, without trailing slash.
+    recursive: BTreeSet<BString>,
+    /// Directories whose files are included, but none of their sub-directories unless these are listed themselves,
+    /// without trailing slash.
+    parents: BTreeSet<BString>,
+}
+
+/// Initialization
+impl Patterns {
+    /// The path to the file containing sparse-checkout patterns within `git_dir`.
+    pub fn path(git_dir: &Path) -> PathBuf {
+        git_dir.join("info").join("sparse-checkout")
+    }
+
+    /// Parse `bytes` as sparse-checkout patterns, which were read from `source`.
+    ///
+    /// If `cone` is true, the patterns are interpreted in _cone mode_, but if they are not in the format written in this mode,
+    /// we fall back to interpreting them as non-cone patterns just like `git` does.
+    pub fn from_bytes(bytes: &[u8], cone: bool, source: impl Into<PathBuf>) -> Self {
+        match cone.then(|| Cone::from_bytes(bytes)).flatten() {
+            Some(cone) => Patterns::Cone(cone),
+            None => Patterns::NonCone(gix_attributes::PatternList::from_bytes(bytes, source, None)),
+        }
+    }
+
+    /// Read sparse-checkout patterns from the `info/sparse-checkout` file in `git_dir`, interpreting them in _cone mode_
+    /// if `cone` is true, and using `buf` to hold its contents.
+    ///
+    /// Return `None` if the file doesn't exist.
+    pub fn from_git_dir(git_dir: &Path, cone: bool, buf: &mut Vec<u8>) -> std::io::Result<Option<Self>> {
+        let path = Self::path(git_dir);
+        buf.clear();
+        match std::fs::File::open(&path) {
+            Ok(mut file) => {
+                std::io::Read::read_to_end(&mut file, buf)?;
+                Ok(Some(Self::from_bytes(buf, cone, path)))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Access
+impl Patterns {
+    /// Return true if the repository-relative `path` is included in the worktree, where `is_dir` is true if it's a directory.
+    ///
+    /// `case` is only used when matching non-cone patterns.
+    pub fn is_included(&self, path: &BStr, is_dir: bool, case: gix_glob::pattern::Case) -> bool {
+        match self {
+            Patterns::Cone(cone) => cone.is_included(path, is_dir),
+            Patterns::NonCone(list) => {
+                // Like `git`, let the match of the path itself take precedence, then try its leading directories.
+                let (mut path, mut is_dir) = (path, is_dir);
+                loop {
+                    let basename_pos = path.rfind_byte(b'/').map(|pos| pos + 1);
+                    if let Some(m) = list.pattern_matching_relative_path(path, basename_pos, Some(is_dir), case) {
+                        return !m.pattern.is_negative();
+                    }
+                    match basename_pos {
+                        Some(pos) => {
+                            path = path[..pos - 1].as_bstr();
+                            is_dir = true;
+                        }
+                        None => return false,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return true if `dir` and everything below it is not included in the worktree, which means it can be represented
+    /// by a sparse directory entry in a sparse index.
+    ///
+    /// Note that this is only ever the case for cone patterns.
+    pub fn is_excluded_directory(&self, dir: &BStr) -> bool {
+        match self {
+            Patterns::Cone(cone) => cone.is_excluded_directory(dir),
+            Patterns::NonCone(_) => false,
+        }
+    }
+
+    /// Return the cone patterns, if these are in cone mode.
+    pub fn cone(&self) -> Option<&Cone> {
+        match self {
+            Patterns::Cone(cone) => Some(cone),
+            Patterns::NonCone(_) => None,
+        }
+    }
+
+    /// Return the cone patterns for modification, if these are in cone mode.
+    pub fn cone_mut(&mut self) -> Option<&mut Cone> {
+        match self {
+            Patterns::Cone(cone) => Some(cone),
+            Patterns::NonCone(_) => None,
+        }
+    }
+}
+
+/// Serialization
+impl Patterns {
+    /// Write these patterns to `out` in the format of the `info/sparse-checkout` file.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        match self {
+            Patterns::Cone(cone) => cone.write_to(out),
+            Patterns::NonCone(list) => {
+                for mapping in &list.patterns {
+                    writeln!(out, "{}", mapping.pattern)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Application
+impl Patterns {
+    /// Set the [`SKIP_WORKTREE`][gix_index::entry::Flags::SKIP_WORKTREE] flag on all entries of `index` that are not
+    /// included by these patterns, and remove it from those that are, returning the amount of entries that changed.
+    ///
+    /// Conflicting entries are never skipped, and sparse directory entries remain untouched, so sparse indices should be
+    /// [expanded][gix_index::State::expand_sparse_directories()] beforehand.
+    /// Note that this doesn't change the worktree, use [`reapply()`] for that.
+    pub fn apply_to_index(&self, index: &mut gix_index::State, case: gix_glob::pattern::Case) -> usize {
+        let mut num_changed = 0;
+        for (entry, path) in index.entries_mut_with_paths() {
+            if entry.mode.is_sparse() {
+                continue;
+            }
+            let skip = entry.stage() == 0 && !self.is_included(path, false, case);
+            if skip != entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) {
+                set_skip_worktree(entry, skip);
+                num_changed += 1;
+            }
+        }
+        num_changed
+    }
+}
+
+fn set_skip_worktree(entry: &mut gix_index::Entry, skip: bool) {
+    use gix_index::entry::Flags;
+    if skip {
+        entry.flags.insert(Flags::SKIP_WORKTREE | Flags::EXTENDED);
+    } else {
+        entry.flags.remove(Flags::SKIP_WORKTREE);
+        if !entry.flags.contains(Flags::INTENT_TO_ADD) {
+            entry.flags.remove(Flags::EXTENDED);
+        }
+    }
+}
+
+/// Apply `patterns` to `index` and bring the worktree at `dir` in line with them, removing files of entries that
+/// are not included anymore, and checking out the entries that became included using `find` to obtain blobs.
+///
+/// Files of entries that are to be excluded are only removed if they appear unchanged compared to the information in the index,
+/// otherwise they are kept along with their entries, which won't be marked with
+/// [`SKIP_WORKTREE`][gix_index::entry::Flags::SKIP_WORKTREE].
+/// Directories that are empty after the removal of files are removed as well.
+///
+/// `files`, `bytes`, `should_interrupt` and `options` are used like in [`index::checkout()`].
+#[allow(clippy::too_many_arguments)]
+pub fn reapply<Find, E>(
+    index: &mut gix_index::State,
+    patterns: &Patterns,
+    dir: impl Into<PathBuf>,
+    find: Find,
+    files: &mut impl Progress,
+    bytes: &mut impl Progress,
+    should_interrupt: &AtomicBool,
+    options: index::checkout::Options,
+) -> Result<reapply::Outcome, index::checkout::Error<E>>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E> + Send + Clone,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    use gix_index::entry::Flags;
+
+    let dir = dir.into();
+    let case = if options.fs.ignore_case {
+        gix_glob::pattern::Case::Fold
+    } else {
+        gix_glob::pattern::Case::Sensitive
+    };
+    let mut outcome = reapply::Outcome::default();
+    let mut emptied_dirs = BTreeSet::new();
+    let mut needs_checkout = false;
+    let mut final_flags = Vec::with_capacity(index.entries().len());
+    for (entry, path) in index.entries_mut_with_paths() {
+        let was_skipped = entry.flags.contains(Flags::SKIP_WORKTREE);
+        final_flags.push(entry.flags);
+        if entry.mode.is_sparse() {
+            continue;
+        }
+        let skip = entry.stage() == 0 && !patterns.is_included(path, false, case);
+        match (was_skipped, skip) {
+            (false, true) => {
+                let rela_path = gix_path::try_from_bstr(path)
+                    .map_err(|_| index::checkout::Error::IllformedUtf8 { path: path.to_owned() })?;
+                let file_path = dir.join(&rela_path);
+                match std::fs::symlink_metadata(&file_path) {
+                    Ok(meta) if !meta.is_dir() && index::is_unchanged(entry, &meta, options.check_stat) => {
+                        std::fs::remove_file(&file_path)?;
+                        outcome.files_removed += 1;
+                        emptied_dirs.extend(
+                            rela_path
+                                .parent()
+                                .filter(|p| !p.as_os_str().is_empty())
+                                .map(ToOwned::to_owned),
+                        );
+                        set_skip_worktree(entry, true);
+                    }
+                    Ok(_) => outcome.kept_modified.push(path.to_owned()),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => set_skip_worktree(entry, true),
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            (true, false) => {
+                set_skip_worktree(entry, false);
+                *final_flags.last_mut().expect("just pushed") = entry.flags;
+                needs_checkout = true;
+                continue;
+            }
+            _ => {}
+        }
+        *final_flags.last_mut().expect("just pushed") = entry.flags;
+        // Prevent checkout of everything that doesn't need it.
+        entry.flags.insert(Flags::SKIP_WORKTREE);
+    }
+
+    for emptied_dir in emptied_dirs.iter().rev() {
+        for dir_to_remove in emptied_dir.ancestors().take_while(|p| !p.as_os_str().is_empty()) {
+            if std::fs::remove_dir(dir.join(dir_to_remove)).is_err() {
+                break;
+            }
+        }
+    }
+
+    let res = if needs_checkout {
+        index::checkout(index, &dir, find, files, bytes, should_interrupt, options).map(|checkout| {
+            outcome.checkout = checkout;
+        })
+    } else {
+        Ok(())
+    };
+    for (entry, flags) in index.entries_mut().iter_mut().zip(final_flags) {
+        entry.flags = flags;
+    }
+    res.map(|_| outcome)
+}
@@ -0,0 +1,12 @@
+use bstr::BString;
+
+/// The outcome of [`reapply()`][crate::sparse::reapply()].
+#[derive(Default)]
+pub struct Outcome {
+    /// The amount of files that were removed from the worktree as they are not included anymore.
+    pub files_removed: usize,
+    /// The paths of entries that are not included anymore, but whose files were kept as they appear to be modified.
+    pub kept_modified: Vec<BString>,
+    /// The outcome of checking out the entries that became included.
+    pub checkout: crate::index::checkout::Outcome,
+}
@@ -0,0 +1,25 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  touch a b
+  mkdir c1
+  (cd c1 && touch a b && mkdir c2 && cd c2 && echo -n "a" > a && echo -n "bb" > b)
+  (cd c1 && mkdir c3 && cd c3 && touch a b)
+  mkdir d
+  (cd d && touch a b && mkdir c4 && cd c4 && touch a b c5)
+
+  git add .
+  git commit -q -m "init"
+)
+
+git clone -q base cone
+(cd cone
+  git sparse-checkout set c1/c2
+)
+
+git clone -q base non-cone
+(cd non-cone
+  git sparse-checkout set --no-cone '/*' '!/*/' '/c1/c2/' '!/c1/c2/b'
+)
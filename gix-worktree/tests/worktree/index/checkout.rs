@@ -172,6 +172,50 @@ fn allow_or_disallow_symlinks() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn sparse_directories_are_skipped() -> crate::Result {
+    let opts = opts_from_probe();
+    let (source_tree, destination, index, outcome) = checkout_index_in_tmp_dir(opts.clone(), "make_sparse_index")?;
+    assert!(index.is_sparse());
+
+    assert_eq!(
+        assert_equality(&source_tree, &destination, opts.fs.symlink)?,
+        6,
+        "only files within the sparse-checkout cone are checked out"
+    );
+    assert!(outcome.collisions.is_empty());
+    assert!(outcome.errors.is_empty());
+    Ok(())
+}
+
+#[test]
+fn stat_of_checked_out_files_is_updated() -> crate::Result {
+    let git_dir = fixture_path("make_mixed_without_submodules").join(".git");
+    let mut index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, Default::default())?;
+    for entry in index.entries_mut() {
+        entry.stat = Default::default();
+    }
+    let odb = gix_odb::at(git_dir.join("objects"))?.into_inner().into_arc()?;
+    let destination = tempfile::tempdir_in(std::env::current_dir()?)?;
+    index::checkout(
+        &mut index,
+        destination.path(),
+        move |oid, buf| odb.find_blob(oid, buf),
+        &mut progress::Discard,
+        &mut progress::Discard,
+        &AtomicBool::default(),
+        opts_from_probe(),
+    )?;
+
+    for entry in index.entries() {
+        let path = entry.path(&index);
+        let meta = fs::symlink_metadata(destination.path().join(gix_path::from_bstr(path)))?;
+        assert_eq!(u64::from(entry.stat.size), meta.len(), "{path}: the size is recorded");
+        assert_ne!(entry.stat.mtime.secs, 0, "{path}: the modification time is recorded");
+    }
+    Ok(())
+}
+
 #[test]
 fn keep_going_collects_results() {
     let mut opts = opts_from_probe();
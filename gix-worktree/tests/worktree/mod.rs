@@ -1,5 +1,6 @@
 mod fs;
 mod index;
+mod sparse;
 
 use std::path::{Path, PathBuf};
 
@@ -0,0 +1,216 @@
+use std::{path::Path, sync::atomic::AtomicBool};
+
+use bstr::ByteSlice;
+use gix_features::progress;
+use gix_glob::pattern::Case;
+use gix_index::entry::Flags;
+use gix_odb::FindExt;
+use gix_worktree::sparse::Patterns;
+
+use crate::fixture_path;
+
+fn patterns(repo: &Path, cone: bool) -> crate::Result<Patterns> {
+    Ok(Patterns::from_git_dir(&repo.join(".git"), cone, &mut Vec::new())?.expect("file present"))
+}
+
+fn index(repo: &Path) -> crate::Result<gix_index::File> {
+    Ok(gix_index::File::at(
+        repo.join(".git").join("index"),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?)
+}
+
+fn skipped_paths(index: &gix_index::State) -> Vec<&str> {
+    index
+        .entries()
+        .iter()
+        .filter(|e| e.flags.contains(Flags::SKIP_WORKTREE))
+        .map(|e| e.path(index).to_str().expect("valid UTF-8"))
+        .collect()
+}
+
+mod cone {
+    use bstr::ByteSlice;
+    use gix_glob::pattern::Case;
+    use gix_worktree::sparse::{Cone, Patterns};
+
+    use super::patterns;
+    use crate::fixture_path;
+
+    #[test]
+    fn parse_and_write() -> crate::Result {
+        let repo = fixture_path("make_sparse_checkout").join("cone");
+        let patterns = patterns(&repo, true)?;
+        let cone = patterns.cone().expect("cone mode");
+        assert_eq!(cone.directories().collect::<Vec<_>>(), ["c1/c2"]);
+
+        let mut buf = Vec::new();
+        patterns.write_to(&mut buf)?;
+        assert_eq!(
+            buf.as_bstr(),
+            std::fs::read(Patterns::path(&repo.join(".git")))?.as_bstr(),
+            "we write exactly what git writes"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn non_cone_patterns_fall_back_to_non_cone_mode() -> crate::Result {
+        let repo = fixture_path("make_sparse_checkout").join("non-cone");
+        let patterns = patterns(&repo, true)?;
+        assert!(
+            patterns.cone().is_none(),
+            "negated files can't be expressed in cone mode"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inclusion() {
+        let patterns = Patterns::Cone(Cone::new(Some("c1/c2".into())));
+        for (path, is_dir, expected) in [
+            ("a", false, true),
+            ("c1", true, true),
+            ("c1/a", false, true),
+            ("c1/c2", true, true),
+            ("c1/c2/a", false, true),
+            ("c1/c2/deep/a", false, true),
+            ("c1/c3", true, false),
+            ("c1/c3/a", false, false),
+            ("d", true, false),
+            ("d/a", false, false),
+        ] {
+            assert_eq!(
+                patterns.is_included(path.into(), is_dir, Case::Sensitive),
+                expected,
+                "{path}"
+            );
+        }
+
+        assert!(patterns.is_excluded_directory("c1/c3".into()));
+        assert!(patterns.is_excluded_directory("d".into()));
+        assert!(
+            !patterns.is_excluded_directory("c1".into()),
+            "it contains an included directory"
+        );
+        assert!(!patterns.is_excluded_directory("c1/c2".into()));
+        assert!(!patterns.is_excluded_directory("c1/c2/deep".into()));
+    }
+
+    #[test]
+    fn add_and_remove_directories() -> crate::Result {
+        let mut cone = Cone::new(Some("c1/c2".into()));
+        assert!(!cone.add_directory("/c1/c2/".into()), "already present");
+        assert!(!cone.add_directory("c1/c2/deep".into()), "already included");
+        assert!(cone.add_directory("d/c4".into()));
+        assert!(cone.add_directory("c1".into()));
+        assert_eq!(
+            cone.directories().collect::<Vec<_>>(),
+            ["c1", "d/c4"],
+            "sub-directories of recursive directories are removed"
+        );
+        let mut buf = Vec::new();
+        cone.write_to(&mut buf)?;
+        assert_eq!(buf.as_bstr(), "/*\n!/*/\n/d/\n!/d/*/\n/c1/\n/d/c4/\n");
+
+        assert!(cone.remove_directory("d".into()), "sub-directories are removed as well");
+        assert!(!cone.remove_directory("d".into()));
+        assert!(
+            !cone.remove_directory("c1/c2".into()),
+            "it's just included, but wasn't added"
+        );
+        buf.clear();
+        cone.write_to(&mut buf)?;
+        assert_eq!(buf.as_bstr(), "/*\n!/*/\n/c1/\n");
+        Ok(())
+    }
+
+    #[test]
+    fn glob_characters_are_escaped() -> crate::Result {
+        let cone = Cone::new(Some("a*b/c[d]".into()));
+        let mut buf = Vec::new();
+        cone.write_to(&mut buf)?;
+        assert_eq!(buf.as_bstr(), "/*\n!/*/\n/a\\*b/\n!/a\\*b/*/\n/a\\*b/c\\[d]/\n");
+
+        let patterns = Patterns::from_bytes(&buf, true, "in-memory");
+        assert_eq!(patterns.cone(), Some(&cone), "escapes are removed when parsing");
+        assert!(patterns.is_included("a*b/c[d]/file".into(), false, Case::Sensitive));
+        Ok(())
+    }
+}
+
+#[test]
+fn apply_to_index_matches_git() -> crate::Result {
+    for (name, cone) in [("cone", true), ("non-cone", false)] {
+        let repo = fixture_path("make_sparse_checkout").join(name);
+        let patterns = patterns(&repo, cone)?;
+        let expected = index(&repo)?;
+        let mut actual = index(&repo)?;
+        for entry in actual.entries_mut() {
+            entry.flags.remove(Flags::SKIP_WORKTREE | Flags::EXTENDED);
+        }
+
+        let num_changed = patterns.apply_to_index(&mut actual, Case::Sensitive);
+        assert_eq!(num_changed, skipped_paths(&expected).len(), "{name}");
+        for (actual_entry, expected_entry) in actual.entries().iter().zip(expected.entries()) {
+            assert_eq!(actual_entry.flags, expected_entry.flags, "{name}");
+        }
+        assert_eq!(
+            patterns.apply_to_index(&mut actual, Case::Sensitive),
+            0,
+            "{name}: nothing changes when applying again"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn reapply_checks_out_and_removes_files() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("make_sparse_checkout.sh")?;
+    let repo = tmp.path().join("cone");
+    let odb = gix_odb::at(repo.join(".git").join("objects"))?
+        .into_inner()
+        .into_arc()?;
+    let mut index = index(&repo)?;
+    let opts = gix_worktree::index::checkout::Options::default();
+
+    let mut reapply = |index: &mut gix_index::File, patterns: &Patterns| {
+        let odb = odb.clone();
+        gix_worktree::sparse::reapply(
+            index,
+            patterns,
+            &repo,
+            move |oid, buf| odb.find_blob(oid, buf),
+            &mut progress::Discard,
+            &mut progress::Discard,
+            &AtomicBool::default(),
+            opts.clone(),
+        )
+    };
+
+    let mut patterns = patterns(&repo, true)?;
+    patterns.cone_mut().expect("cone mode").add_directory("d".into());
+    let outcome = reapply(&mut index, &patterns)?;
+    assert_eq!(outcome.files_removed, 0);
+    assert_eq!(outcome.checkout.files_updated, 5);
+    assert!(outcome.kept_modified.is_empty());
+    assert_eq!(skipped_paths(&index), ["c1/c3/a", "c1/c3/b"]);
+    for path in ["d/a", "d/b", "d/c4/a", "d/c4/b", "d/c4/c5"] {
+        assert!(repo.join(path).is_file(), "{path} was checked out");
+    }
+
+    std::fs::write(repo.join("d/a"), "modified")?;
+    patterns.cone_mut().expect("cone mode").remove_directory("d".into());
+    let outcome = reapply(&mut index, &patterns)?;
+    assert_eq!(outcome.files_removed, 4);
+    assert_eq!(outcome.kept_modified, ["d/a"], "modified files are kept");
+    assert_eq!(outcome.checkout.files_updated, 0);
+    assert_eq!(
+        skipped_paths(&index),
+        ["c1/c3/a", "c1/c3/b", "d/b", "d/c4/a", "d/c4/b", "d/c4/c5"]
+    );
+    assert!(!repo.join("d/c4").exists(), "empty directories are removed");
+    assert!(repo.join("d/a").is_file());
+    Ok(())
+}
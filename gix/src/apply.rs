@@ -0,0 +1,349 @@
+//! Apply patches, as parsed by [`gix_diff::patch::parse()`], to trees, indices or the working tree.
+use std::collections::BTreeMap;
+
+use gix_diff::patch::{
+    apply as hunks,
+    parse::{Binary, BinaryKind, FilePatch, Operation},
+};
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    prelude::Find,
+    Repository,
+};
+
+/// The error returned by [`Repository::apply_to_tree()`], [`Repository::apply_to_index()`] and
+/// [`Repository::apply_to_worktree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Patch for '{path}' cannot be applied as the file does not exist")]
+    Missing { path: BString },
+    #[error("Patch for '{path}' cannot be applied as the file already exists")]
+    AlreadyExists { path: BString },
+    #[error("Patch for '{path}' does not apply")]
+    Hunks {
+        path: BString,
+        #[source]
+        source: hunks::Error,
+    },
+    #[error("Deletion of '{path}' does not apply as the file has content the patch doesn't remove")]
+    DeletionMismatch { path: BString },
+    #[error("Binary patch for '{path}' has no data to apply")]
+    BinaryWithoutData { path: BString },
+    #[error("Binary patch for '{path}' has invalid data or does not apply")]
+    BinaryData { path: BString },
+    #[error("The 3-way merge of '{path}' has conflicts, which cannot be stored in a tree")]
+    Conflict { path: BString },
+    #[error("The submodule entry at '{path}' cannot be changed to the patched content")]
+    Submodule { path: BString },
+    #[error(transparent)]
+    FindExisting(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    WriteBlob(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error<crate::object::write::Error>),
+    #[error(transparent)]
+    TraverseTree(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error("A bare repository has no working tree to apply patches to")]
+    MissingWorkTree,
+    #[error("Could not read or write '{path}' in the working tree")]
+    Io {
+        path: BString,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Options for use in [`Repository::apply_to_tree()`], [`Repository::apply_to_index()`] and
+/// [`Repository::apply_to_worktree()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Options to control how hunks are matched against the lines of the files.
+    pub hunks: hunks::Options,
+    /// If `true`, and if hunks don't apply, apply them to the blob the patch was created against as found in the object database
+    /// by the id in its `index` line, and merge the result with the current version of the file, similar to `git apply --3way`.
+    pub three_way: bool,
+}
+
+/// The result of applying patches.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The paths of all files which were merged with conflicts as part of a 3-way merge.
+    ///
+    /// In the index, these are stored as entries of stage 1 to 3, and in the working tree, they contain conflict markers.
+    pub conflicts: Vec<BString>,
+}
+
+/// A file as seen by a patch, with its mode and data.
+#[derive(Debug, Clone)]
+pub(crate) struct File {
+    pub mode: EntryMode,
+    pub data: Vec<u8>,
+}
+
+/// The conflicting versions of a file after a failed 3-way merge.
+#[derive(Debug, Clone)]
+pub(crate) struct Conflict {
+    pub base: ObjectId,
+    pub ours: File,
+    pub theirs: File,
+}
+
+/// The state of all files touched by patches after applying them, with `None` for deleted files.
+#[derive(Default)]
+pub(crate) struct Changes {
+    pub files: BTreeMap<BString, Option<File>>,
+    pub conflicts: BTreeMap<BString, Conflict>,
+}
+
+impl Changes {
+    /// Apply all `patches` on top of the files returned by `read()`, which returns `None` if a file doesn't exist.
+    ///
+    /// Later patches see the changes of earlier ones.
+    pub(crate) fn apply(
+        repo: &Repository,
+        patches: &[FilePatch],
+        options: &Options,
+        mut read: impl FnMut(&BStr) -> Result<Option<File>, Error>,
+    ) -> Result<Self, Error> {
+        let mut changes = Changes::default();
+        for patch in patches {
+            let current = match patch.old_path.as_ref() {
+                Some(path) => match changes.files.get(path) {
+                    Some(file) => file.clone(),
+                    None => read(path.as_ref())?,
+                },
+                None => None,
+            };
+            let path = patch
+                .new_path
+                .as_ref()
+                .or(patch.old_path.as_ref())
+                .expect("parsing assures at least one path");
+            if let Some(new_path) = patch.new_path.as_ref().filter(|_| {
+                matches!(
+                    patch.operation,
+                    Operation::Addition | Operation::Rename | Operation::Copy
+                )
+            }) {
+                let exists = match changes.files.get(new_path) {
+                    Some(file) => file.is_some(),
+                    None => read(new_path.as_ref())?.is_some(),
+                };
+                if exists && patch.old_path.as_ref() != Some(new_path) {
+                    return Err(Error::AlreadyExists { path: new_path.clone() });
+                }
+            }
+
+            let current = match (patch.operation, current) {
+                (Operation::Addition, _) => None,
+                (_, Some(current)) => Some(current),
+                (_, None) => return Err(Error::Missing { path: path.clone() }),
+            };
+            let old_data = current.as_ref().map_or(&[][..], |f| f.data.as_slice());
+            let (data, conflict) = match new_data(repo, patch, path, old_data, options)? {
+                Data::Clean(data) => (data, None),
+                Data::Conflict { merged, base, theirs } => (merged, Some((base, theirs))),
+            };
+
+            if patch.operation == Operation::Deletion {
+                if !data.is_empty() {
+                    return Err(Error::DeletionMismatch { path: path.clone() });
+                }
+                changes.files.insert(path.clone(), None);
+                continue;
+            }
+            let mode = patch
+                .new_mode
+                .or_else(|| current.as_ref().map(|f| f.mode))
+                .unwrap_or(EntryMode::Blob);
+            if patch.operation == Operation::Rename {
+                if let Some(old_path) = patch.old_path.as_ref().filter(|old_path| *old_path != path) {
+                    changes.files.insert(old_path.clone(), None);
+                }
+            }
+            if let Some((base, theirs)) = conflict {
+                changes.conflicts.insert(
+                    path.clone(),
+                    Conflict {
+                        base,
+                        ours: current.clone().expect("conflicts only happen for existing files"),
+                        theirs: File { mode, data: theirs },
+                    },
+                );
+            } else {
+                changes.conflicts.remove(path);
+            }
+            changes.files.insert(path.clone(), Some(File { mode, data }));
+        }
+        Ok(changes)
+    }
+}
+
+enum Data {
+    Clean(Vec<u8>),
+    Conflict {
+        merged: Vec<u8>,
+        base: ObjectId,
+        theirs: Vec<u8>,
+    },
+}
+
+/// Compute the data of the file at `path` after applying `patch` to `old`.
+fn new_data(
+    repo: &Repository,
+    patch: &FilePatch,
+    path: &BString,
+    old: &[u8],
+    options: &Options,
+) -> Result<Data, Error> {
+    match patch.binary.as_ref() {
+        Some(Binary::Data { forward, .. }) => {
+            let err = || Error::BinaryData { path: path.clone() };
+            let data = inflate(&forward.data, forward.size).ok_or_else(err)?;
+            return Ok(Data::Clean(match forward.kind {
+                BinaryKind::Literal => data,
+                BinaryKind::Delta => apply_delta(old, &data).ok_or_else(err)?,
+            }));
+        }
+        Some(Binary::Differ) => {
+            // Without data, the new blob has to be known to us already, which requires its full id.
+            return match patch
+                .new_id
+                .as_ref()
+                .and_then(|id| ObjectId::from_hex(id).ok())
+                .filter(|id| repo.objects.contains(id))
+            {
+                Some(id) => Ok(Data::Clean(repo.find_object(id)?.detach().data)),
+                None => Err(Error::BinaryWithoutData { path: path.clone() }),
+            };
+        }
+        None => {}
+    }
+
+    let err = match gix_diff::patch::apply(old, &patch.hunks, options.hunks) {
+        Ok(data) => return Ok(Data::Clean(data)),
+        Err(err) => err,
+    };
+    let base = options
+        .three_way
+        .then(|| patch.old_id.as_ref().and_then(|id| lookup_prefix(repo, id.as_ref())))
+        .flatten();
+    let base = match base {
+        Some(base) => base,
+        None => {
+            return Err(Error::Hunks {
+                path: path.clone(),
+                source: err,
+            })
+        }
+    };
+    let base_data = repo.find_object(base)?.detach().data;
+    let theirs =
+        gix_diff::patch::apply(&base_data, &patch.hunks, Default::default()).map_err(|source| Error::Hunks {
+            path: path.clone(),
+            source,
+        })?;
+    let merged = gix_diff::blob::merge(
+        &base_data,
+        old,
+        &theirs,
+        &gix_diff::blob::merge::Options {
+            algorithm: repo.config.diff_algorithm().unwrap_or(gix_diff::blob::Algorithm::Myers),
+            ours_label: Some("ours".into()),
+            theirs_label: Some("theirs".into()),
+            ..Default::default()
+        },
+    );
+    Ok(if merged.conflicts == 0 {
+        Data::Clean(merged.data)
+    } else {
+        Data::Conflict {
+            merged: merged.data,
+            base,
+            theirs,
+        }
+    })
+}
+
+/// Return the id of the object with the abbreviated `hex` id, if it is unambiguous.
+fn lookup_prefix(repo: &Repository, hex: &BStr) -> Option<ObjectId> {
+    let prefix = gix_hash::Prefix::from_hex(hex.to_str().ok()?).ok()?;
+    if prefix.hex_len() == prefix.as_oid().kind().len_in_hex() {
+        let id = prefix.as_oid().to_owned();
+        return repo.objects.contains(id).then_some(id);
+    }
+    repo.objects.lookup_prefix(prefix, None).ok().flatten()?.ok()
+}
+
+fn inflate(data: &[u8], size: u64) -> Option<Vec<u8>> {
+    let mut out = vec![0; usize::try_from(size).ok()?];
+    let mut inflate = gix_features::zlib::Inflate::default();
+    let (status, _consumed, produced) = inflate.once(data, &mut out).ok()?;
+    (status == gix_features::zlib::Status::StreamEnd && produced == out.len()).then_some(out)
+}
+
+/// Apply `delta` in the format used in packs to `base`, or return `None` if it is invalid.
+fn apply_delta(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    fn size(delta: &mut &[u8]) -> Option<u64> {
+        let mut size = 0u64;
+        for (idx, byte) in delta.iter().enumerate() {
+            size |= u64::from(byte & 0x7f) << (7 * idx);
+            if byte & 0x80 == 0 {
+                *delta = &delta[idx + 1..];
+                return Some(size);
+            }
+        }
+        None
+    }
+    let mut delta = delta;
+    if size(&mut delta)? != base.len() as u64 {
+        return None;
+    }
+    let result_size = size(&mut delta)?;
+    let mut out = Vec::with_capacity(usize::try_from(result_size).ok()?);
+    while let Some((&cmd, rest)) = delta.split_first() {
+        delta = rest;
+        if cmd & 0x80 != 0 {
+            let mut value = |bits: std::ops::Range<u32>| -> Option<usize> {
+                let mut value = 0usize;
+                for (byte_idx, bit) in bits.enumerate() {
+                    if cmd & (1 << bit) != 0 {
+                        let (&byte, rest) = delta.split_first()?;
+                        delta = rest;
+                        value |= usize::from(byte) << (8 * byte_idx);
+                    }
+                }
+                Some(value)
+            };
+            let offset = value(0..4)?;
+            let size = match value(4..7)? {
+                0 => 0x10000,
+                size => size,
+            };
+            out.extend_from_slice(base.get(offset..offset.checked_add(size)?)?);
+        } else if cmd != 0 {
+            let len = usize::from(cmd);
+            out.extend_from_slice(delta.get(..len)?);
+            delta = &delta[len..];
+        } else {
+            return None;
+        }
+    }
+    (out.len() as u64 == result_size).then_some(out)
+}
+
+/// Return the data to diff for a submodule at `id`, just like `git` does it.
+pub(crate) fn submodule_data(id: &gix_hash::oid) -> Vec<u8> {
+    format!("Subproject commit {id}\n").into_bytes()
+}
+
+/// Parse the data of a submodule entry back into its commit id.
+pub(crate) fn submodule_id(data: &[u8]) -> Option<ObjectId> {
+    let hex = data.strip_prefix(b"Subproject commit ")?;
+    let hex = hex.strip_suffix(b"\n").unwrap_or(hex);
+    ObjectId::from_hex(hex).ok()
+}
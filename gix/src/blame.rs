@@ -0,0 +1,480 @@
+//! Attribute each line of a file to the commit that introduced it, similar to `git blame`.
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    ops::Range,
+    path::PathBuf,
+};
+
+use gix_diff::blob::{intern::InternedInput, sources::byte_lines_with_terminator, Algorithm};
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    object::tree::diff::{change::Event, Rewrites},
+    Repository,
+};
+
+/// The error returned by [`Repository::blame()`] and [`Options::from_repo()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    HeadCommit(#[from] Box<crate::reference::head_commit::Error>),
+    #[error(transparent)]
+    FindExisting(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    NotACommit(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error("The file '{path}' does not exist in commit {commit}")]
+    PathNotFound { path: BString, commit: ObjectId },
+    #[error("The line range {}..{} is out of bounds as the file only has {lines} lines", .range.start, .range.end)]
+    InvalidRange { range: Range<u32>, lines: u32 },
+    #[error(transparent)]
+    RewritesConfig(#[from] Box<crate::object::tree::diff::rewrites::Error>),
+    #[error(transparent)]
+    Diff(#[from] crate::object::tree::diff::for_each::Error),
+    #[error(transparent)]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+    #[error("Could not interpolate the path of the file with revisions to ignore")]
+    IgnoreRevsFilePath(#[from] gix_config::path::interpolate::Error),
+    #[error("Could not read the file with revisions to ignore at '{}'", .path.display())]
+    IgnoreRevsFile { path: PathBuf, source: std::io::Error },
+    #[error("The line {line:?} of the file with revisions to ignore isn't a full object id")]
+    IgnoreRevsFileLine { line: BString },
+}
+
+/// Options for use in [`Repository::blame()`].
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// The commit to start at, or the `HEAD` commit if `None`.
+    pub commit: Option<ObjectId>,
+    /// The 0-based ranges of lines to blame, with exclusive end, or all lines if empty.
+    pub ranges: Vec<Range<u32>>,
+    /// Commits whose changes should be attributed to the commits that changed the respective lines before, similar to
+    /// `git blame --ignore-rev`.
+    pub ignore_revs: Vec<ObjectId>,
+    /// The way renames are detected to follow the file through its history, or `None` to stop at the commit that
+    /// introduced the file under its current name.
+    ///
+    /// Note that copies are never considered.
+    pub rewrites: Option<Rewrites>,
+    /// The algorithm to use to find the lines that changed between commits.
+    pub algorithm: Algorithm,
+    /// If true, the author of each entry is resolved using the mailmap of the repository.
+    pub use_mailmap: bool,
+}
+
+impl Options {
+    /// Create options the way `git blame` would use them, which follows renames, resolves authors using the
+    /// mailmap, uses the configured `diff.algorithm` and ignores the revisions in the file at `blame.ignoreRevsFile`.
+    pub fn from_repo(repo: &Repository) -> Result<Self, Error> {
+        Ok(Options {
+            commit: None,
+            ranges: Vec::new(),
+            ignore_revs: ignore_revs_from_file(repo)?,
+            rewrites: Some(repo.config.diff_renames().map_err(Box::new)?.unwrap_or_default()),
+            algorithm: repo.config.diff_algorithm()?,
+            use_mailmap: true,
+        })
+    }
+}
+
+/// Read the revisions to ignore from the file at `blame.ignoreRevsFile`, which is relative to the work tree if there is one.
+fn ignore_revs_from_file(repo: &Repository) -> Result<Vec<ObjectId>, Error> {
+    let path = match repo.config.apply_leniency(repo.config.blame_ignore_revs_file())? {
+        Some(path) => repo.work_dir().unwrap_or_else(|| repo.git_dir()).join(path),
+        None => return Ok(Vec::new()),
+    };
+    let content = std::fs::read(&path).map_err(|source| Error::IgnoreRevsFile { path, source })?;
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with(b"#"))
+        .map(|line| {
+            ObjectId::from_hex(line).map_err(|_| Error::IgnoreRevsFileLine {
+                line: line.as_bstr().to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// A range of lines in the blamed file along with the commit that introduced them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The 0-based range of lines in the blamed file, with exclusive end.
+    pub range_in_blamed_file: Range<u32>,
+    /// The 0-based range of the same lines in the file as it was added or changed in `commit_id`, with exclusive end.
+    pub range_in_source_file: Range<u32>,
+    /// The commit that introduced the lines.
+    pub commit_id: ObjectId,
+    /// The path of the file in `commit_id`, which differs from the blamed path if the file was renamed since.
+    pub source_path: BString,
+    /// The author of `commit_id`, resolved using the mailmap if [configured][Options::use_mailmap].
+    pub author: gix_actor::Signature,
+}
+
+/// The result of [`Repository::blame()`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// All blamed lines grouped into entries, ordered by their position in the blamed file.
+    pub entries: Vec<Entry>,
+    /// The content of the blamed file.
+    pub blob: Vec<u8>,
+}
+
+impl Outcome {
+    /// Return an iterator over all entries along with the lines of the blamed file they are attributed to.
+    pub fn entries_with_lines(&self) -> impl Iterator<Item = (&Entry, Vec<&BStr>)> {
+        let lines: Vec<&BStr> = byte_lines_with_terminator(&self.blob)
+            .map(|line| line.as_bstr())
+            .collect();
+        self.entries.iter().map(move |entry| {
+            let range = entry.range_in_blamed_file.start as usize..entry.range_in_blamed_file.end as usize;
+            (entry, lines[range].to_vec())
+        })
+    }
+}
+
+/// Lines of the blamed file that have yet to be attributed to a commit, along with their position in the file of the
+/// commit that is currently suspected to have introduced them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UnblamedHunk {
+    start_in_blamed_file: u32,
+    range_in_suspect: Range<u32>,
+}
+
+impl UnblamedHunk {
+    fn range_in_blamed_file(&self) -> Range<u32> {
+        self.start_in_blamed_file..self.start_in_blamed_file + (self.range_in_suspect.end - self.range_in_suspect.start)
+    }
+}
+
+/// A file in a commit that possibly introduced some of the lines of the blamed file.
+struct Suspect {
+    blob: ObjectId,
+    hunks: Vec<UnblamedHunk>,
+}
+
+struct Commit {
+    time: u32,
+    tree: ObjectId,
+    parents: Vec<ObjectId>,
+}
+
+pub(crate) fn blame(repo: &Repository, path: &BStr, options: &Options) -> Result<Outcome, Error> {
+    let start = match options.commit {
+        Some(id) => id,
+        None => repo.head_commit().map_err(Box::new)?.id,
+    };
+    let start_commit = commit(repo, start)?;
+    let start_blob = blob_at(repo, start_commit.tree, path)?.ok_or_else(|| Error::PathNotFound {
+        path: path.to_owned(),
+        commit: start,
+    })?;
+    let blob = repo.find_object(start_blob)?.detach().data;
+    let num_lines = byte_lines_with_terminator(&blob).count() as u32;
+
+    let mut suspects = HashMap::<(ObjectId, BString), Suspect>::new();
+    let mut queue = BinaryHeap::<(u32, Reverse<usize>, ObjectId, BString)>::new();
+    suspects.insert(
+        (start, path.to_owned()),
+        Suspect {
+            blob: start_blob,
+            hunks: line_ranges(&options.ranges, num_lines)?
+                .into_iter()
+                .map(|range| UnblamedHunk {
+                    start_in_blamed_file: range.start,
+                    range_in_suspect: range,
+                })
+                .collect(),
+        },
+    );
+    queue.push((start_commit.time, Reverse(0), start, path.to_owned()));
+    let mut num_queued = 1;
+
+    let mut blamed = Vec::<(UnblamedHunk, ObjectId, BString)>::new();
+    while let Some((_, _, commit_id, path)) = queue.pop() {
+        let Suspect { blob, hunks } = suspects
+            .remove(&(commit_id, path.clone()))
+            .expect("queued suspects are present");
+        let current = commit(repo, commit_id)?;
+
+        let mut parents = Vec::with_capacity(current.parents.len());
+        for parent_id in &current.parents {
+            let parent = commit(repo, *parent_id)?;
+            let file = match blob_at(repo, parent.tree, path.as_ref())? {
+                Some(blob) => Some((path.clone(), blob)),
+                None => match renamed_from(repo, parent.tree, current.tree, path.as_ref(), options.rewrites)? {
+                    Some(source) => blob_at(repo, parent.tree, source.as_ref())?.map(|blob| (source, blob)),
+                    None => None,
+                },
+            };
+            parents.push((*parent_id, parent.time, file));
+        }
+
+        let mut pass_to_parent =
+            |parent_id: ObjectId, time: u32, path: BString, blob: ObjectId, hunks: Vec<UnblamedHunk>| {
+                if hunks.is_empty() {
+                    return;
+                }
+                let suspect = suspects.entry((parent_id, path.clone())).or_insert_with(|| {
+                    queue.push((time, Reverse(num_queued), parent_id, path));
+                    num_queued += 1;
+                    Suspect {
+                        blob,
+                        hunks: Vec::new(),
+                    }
+                });
+                suspect.hunks.extend(hunks);
+            };
+
+        if let Some((parent_id, time, Some((parent_path, parent_blob)))) = parents
+            .iter()
+            .find(|(_, _, file)| file.as_ref().map_or(false, |(_, parent_blob)| *parent_blob == blob))
+        {
+            pass_to_parent(*parent_id, *time, parent_path.clone(), *parent_blob, hunks);
+            continue;
+        }
+
+        let mut remaining = hunks;
+        let mut is_ignored = options.ignore_revs.contains(&commit_id);
+        if !parents.is_empty() && !remaining.is_empty() {
+            let data = repo.find_object(blob)?.detach().data;
+            for (parent_id, time, file) in parents {
+                let (parent_path, parent_blob) = match file {
+                    Some(file) => file,
+                    None => continue,
+                };
+                let parent_data = repo.find_object(parent_blob)?.detach().data;
+                let (passed, kept) = split_hunks(
+                    remaining,
+                    &changes(&parent_data, &data, options.algorithm),
+                    std::mem::take(&mut is_ignored),
+                );
+                pass_to_parent(parent_id, time, parent_path, parent_blob, passed);
+                remaining = kept;
+                if remaining.is_empty() {
+                    break;
+                }
+            }
+        }
+        blamed.extend(remaining.into_iter().map(|hunk| (hunk, commit_id, path.clone())));
+    }
+
+    let mailmap = options.use_mailmap.then(|| repo.open_mailmap());
+    let mut authors = HashMap::<ObjectId, gix_actor::Signature>::new();
+    blamed.sort_by_key(|(hunk, _, _)| hunk.start_in_blamed_file);
+    let mut entries = Vec::<Entry>::with_capacity(blamed.len());
+    for (hunk, commit_id, source_path) in blamed {
+        if let Some(previous) = entries.last_mut() {
+            if previous.commit_id == commit_id
+                && previous.source_path == source_path
+                && previous.range_in_blamed_file.end == hunk.start_in_blamed_file
+                && previous.range_in_source_file.end == hunk.range_in_suspect.start
+            {
+                previous.range_in_blamed_file.end = hunk.range_in_blamed_file().end;
+                previous.range_in_source_file.end = hunk.range_in_suspect.end;
+                continue;
+            }
+        }
+        let author = match authors.get(&commit_id) {
+            Some(author) => author.clone(),
+            None => {
+                let commit = repo.find_object(commit_id)?.try_into_commit()?;
+                let author = commit.author()?;
+                let author = match &mailmap {
+                    Some(mailmap) => mailmap.resolve(author),
+                    None => author.to_owned(),
+                };
+                authors.insert(commit_id, author.clone());
+                author
+            }
+        };
+        entries.push(Entry {
+            range_in_blamed_file: hunk.range_in_blamed_file(),
+            range_in_source_file: hunk.range_in_suspect,
+            commit_id,
+            source_path,
+            author,
+        });
+    }
+    Ok(Outcome { entries, blob })
+}
+
+fn commit(repo: &Repository, id: ObjectId) -> Result<Commit, Error> {
+    let commit = repo.find_object(id)?.try_into_commit()?;
+    let commit = commit.decode()?;
+    Ok(Commit {
+        time: commit.committer.time.seconds_since_unix_epoch,
+        tree: commit.tree(),
+        parents: commit.parents().collect(),
+    })
+}
+
+/// Return the id of the file at `path` in `tree`, or `None` if there is no such file.
+fn blob_at(repo: &Repository, tree: ObjectId, path: &BStr) -> Result<Option<ObjectId>, Error> {
+    let path = match gix_path::try_from_bstr(path) {
+        Ok(path) => path,
+        Err(_) => return Ok(None),
+    };
+    Ok(repo
+        .find_object(tree)?
+        .into_tree()
+        .lookup_entry_by_path(path)?
+        .filter(|entry| entry.mode().is_blob_or_symlink())
+        .map(|entry| entry.object_id()))
+}
+
+/// Return the path of the file in `parent_tree` that was renamed to `path` in `tree`, if there is one.
+fn renamed_from(
+    repo: &Repository,
+    parent_tree: ObjectId,
+    tree: ObjectId,
+    path: &BStr,
+    rewrites: Option<Rewrites>,
+) -> Result<Option<BString>, Error> {
+    let rewrites = match rewrites {
+        Some(rewrites) => rewrites,
+        None => return Ok(None),
+    };
+    let tree = repo.find_object(tree)?.into_tree();
+    let mut source = None;
+    repo.find_object(parent_tree)?
+        .into_tree()
+        .changes()
+        .map_err(Box::new)?
+        .track_path()
+        .track_rewrites(Some(Rewrites {
+            copies: None,
+            ..rewrites
+        }))
+        .for_each_to_obtain_tree(&tree, |change| {
+            if let Event::Rewrite {
+                source_location,
+                copy: false,
+                ..
+            } = change.event
+            {
+                if change.location == path {
+                    source = Some(source_location.to_owned());
+                }
+            }
+            Ok::<_, std::convert::Infallible>(Default::default())
+        })?;
+    Ok(source)
+}
+
+/// Return the given `ranges` sorted and with overlapping ones merged, or a range over all lines if there are none.
+fn line_ranges(ranges: &[Range<u32>], num_lines: u32) -> Result<Vec<Range<u32>>, Error> {
+    if ranges.is_empty() {
+        return Ok((num_lines != 0).then_some(0..num_lines).into_iter().collect());
+    }
+    let mut sorted = Vec::<Range<u32>>::with_capacity(ranges.len());
+    for range in ranges {
+        if range.start >= range.end || range.end > num_lines {
+            return Err(Error::InvalidRange {
+                range: range.clone(),
+                lines: num_lines,
+            });
+        }
+        sorted.push(range.clone());
+    }
+    sorted.sort_by_key(|range| range.start);
+    let mut out = Vec::<Range<u32>>::with_capacity(sorted.len());
+    for range in sorted {
+        match out.last_mut() {
+            Some(previous) if previous.end >= range.start => previous.end = previous.end.max(range.end),
+            _ => out.push(range),
+        }
+    }
+    Ok(out)
+}
+
+/// Return the changes needed to turn `old` into `new`, as ranges of lines in `old` and `new` respectively.
+fn changes(old: &[u8], new: &[u8], algorithm: Algorithm) -> Vec<(Range<u32>, Range<u32>)> {
+    let input = InternedInput::new(byte_lines_with_terminator(old), byte_lines_with_terminator(new));
+    let mut changes = Vec::new();
+    gix_diff::blob::diff(algorithm, &input, |before: Range<u32>, after: Range<u32>| {
+        changes.push((before, after))
+    });
+    changes
+}
+
+/// Split `hunks` of a suspect into those that are unchanged in its parent, translated to the lines of the parent,
+/// and those that were changed by the suspect, given the `changes` that turn the parent into the suspect.
+///
+/// If `is_ignored`, the changed lines are attributed to the lines of the parent that were replaced by them as well,
+/// unless lines were only added.
+fn split_hunks(
+    hunks: Vec<UnblamedHunk>,
+    changes: &[(Range<u32>, Range<u32>)],
+    is_ignored: bool,
+) -> (Vec<UnblamedHunk>, Vec<UnblamedHunk>) {
+    enum Lines {
+        Unchanged { start_in_parent: u32 },
+        Changed { range_in_parent: Range<u32> },
+    }
+    let mut segments = Vec::with_capacity(changes.len() * 2 + 1);
+    let (mut parent_pos, mut pos) = (0, 0);
+    for (before, after) in changes {
+        if after.start > pos {
+            segments.push((
+                pos..after.start,
+                Lines::Unchanged {
+                    start_in_parent: parent_pos,
+                },
+            ));
+        }
+        if !after.is_empty() {
+            segments.push((
+                after.clone(),
+                Lines::Changed {
+                    range_in_parent: before.clone(),
+                },
+            ));
+        }
+        parent_pos = before.end;
+        pos = after.end;
+    }
+    segments.push((
+        pos..u32::MAX,
+        Lines::Unchanged {
+            start_in_parent: parent_pos,
+        },
+    ));
+
+    let (mut passed, mut kept) = (Vec::new(), Vec::new());
+    for hunk in hunks {
+        for (range, lines) in &segments {
+            let start = range.start.max(hunk.range_in_suspect.start);
+            let end = range.end.min(hunk.range_in_suspect.end);
+            if start >= end {
+                continue;
+            }
+            let start_in_blamed_file = hunk.start_in_blamed_file + (start - hunk.range_in_suspect.start);
+            match lines {
+                Lines::Unchanged { start_in_parent } => passed.push(UnblamedHunk {
+                    start_in_blamed_file,
+                    range_in_suspect: start_in_parent + (start - range.start)..start_in_parent + (end - range.start),
+                }),
+                Lines::Changed { range_in_parent } if is_ignored && !range_in_parent.is_empty() => {
+                    for line in start..end {
+                        let line_in_parent = range_in_parent.start
+                            + (line - range.start).min(range_in_parent.end - range_in_parent.start - 1);
+                        passed.push(UnblamedHunk {
+                            start_in_blamed_file: start_in_blamed_file + (line - start),
+                            range_in_suspect: line_in_parent..line_in_parent + 1,
+                        });
+                    }
+                }
+                Lines::Changed { .. } => kept.push(UnblamedHunk {
+                    start_in_blamed_file,
+                    range_in_suspect: start..end,
+                }),
+            }
+        }
+    }
+    (passed, kept)
+}
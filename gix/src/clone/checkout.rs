@@ -14,6 +14,8 @@ pub mod main_worktree {
     pub enum Error {
         #[error("Repository at \"{}\" is a bare repository and cannot have a main worktree checkout", git_dir.display())]
         BareRepository { git_dir: PathBuf },
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
         #[error("The object pointed to by HEAD is not a treeish")]
         NoHeadTree(#[from] crate::object::peel::to_kind::Error),
         #[error("Could not create index from tree at {id}")]
@@ -22,7 +24,7 @@ pub mod main_worktree {
             source: gix_traverse::tree::breadthfirst::Error,
         },
         #[error(transparent)]
-        WriteIndex(#[from] gix_index::file::write::Error),
+        WriteIndex(#[from] crate::worktree::write_index::Error),
         #[error(transparent)]
         CheckoutOptions(#[from] crate::config::checkout_options::Error),
         #[error(transparent)]
@@ -36,6 +38,10 @@ pub mod main_worktree {
         FindHead(#[from] crate::reference::find::existing::Error),
         #[error("The HEAD reference could not be located")]
         PeelHeadToId(#[from] crate::head::peel::Error),
+        #[error(transparent)]
+        SparseCheckoutPatterns(#[from] crate::worktree::sparse_checkout_patterns::Error),
+        #[error(transparent)]
+        Hook(#[from] crate::hook::Error),
     }
 
     /// The progress ids used in [`PrepareCheckout::main_worktree()`].
@@ -67,18 +73,20 @@ pub mod main_worktree {
         /// if the `head()` of the returned repository is not unborn.
         pub fn main_worktree(
             &mut self,
-            mut progress: impl crate::Progress,
+            progress: impl crate::Progress,
             should_interrupt: &AtomicBool,
         ) -> Result<(Repository, gix_worktree::index::checkout::Outcome), Error> {
             let repo = self
                 .repo
                 .as_ref()
                 .expect("still present as we never succeeded the worktree checkout yet");
-            let workdir = repo.work_dir().ok_or_else(|| Error::BareRepository {
-                git_dir: repo.git_dir().to_owned(),
-            })?;
-            let root_tree = match repo.head()?.peel_to_id_in_place().transpose()? {
-                Some(id) => id.object().expect("downloaded from remote").peel_to_tree()?.id,
+            if repo.work_dir().is_none() {
+                return Err(Error::BareRepository {
+                    git_dir: repo.git_dir().to_owned(),
+                });
+            }
+            let head_id = match repo.head()?.peel_to_id_in_place().transpose()? {
+                Some(id) => id,
                 None => {
                     return Ok((
                         self.repo.take().expect("still present"),
@@ -86,42 +94,78 @@ pub mod main_worktree {
                     ))
                 }
             };
-            let index = gix_index::State::from_tree(&root_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
-                .map_err(|err| Error::IndexFromTree {
-                    id: root_tree,
-                    source: err,
-                })?;
-            let mut index = gix_index::File::from_state(index, repo.index_path());
-
-            let mut opts = repo.config.checkout_options(repo.git_dir())?;
-            opts.destination_is_initially_empty = true;
-
-            let mut files = progress.add_child_with_id("checkout", ProgressId::CheckoutFiles.into());
-            let mut bytes = progress.add_child_with_id("writing", ProgressId::BytesWritten.into());
-
-            files.init(Some(index.entries().len()), crate::progress::count("files"));
-            bytes.init(None, crate::progress::bytes());
-
-            let start = std::time::Instant::now();
-            let outcome = gix_worktree::index::checkout(
-                &mut index,
-                workdir,
-                {
-                    let objects = repo.objects.clone().into_arc()?;
-                    move |oid, buf| objects.find_blob(oid, buf)
-                },
-                &mut files,
-                &mut bytes,
-                should_interrupt,
-                opts,
-            )?;
-            files.show_throughput(start);
-            bytes.show_throughput(start);
-
-            index.write(Default::default())?;
+            let outcome = checkout_head(repo, head_id.detach(), progress, should_interrupt, true)?;
             Ok((self.repo.take().expect("still present"), outcome))
         }
     }
+
+    /// Check out the tree of the commit `head_id` into the work tree of `repo` and write the index, before running the
+    /// `post-checkout` hook.
+    ///
+    /// Set `destination_is_initially_empty` if the work tree is known to be empty.
+    pub(crate) fn checkout_head(
+        repo: &Repository,
+        head_id: gix_hash::ObjectId,
+        mut progress: impl crate::Progress,
+        should_interrupt: &AtomicBool,
+        destination_is_initially_empty: bool,
+    ) -> Result<gix_worktree::index::checkout::Outcome, Error> {
+        let workdir = repo.work_dir().ok_or_else(|| Error::BareRepository {
+            git_dir: repo.git_dir().to_owned(),
+        })?;
+        let root_tree = repo.find_object(head_id)?.peel_to_tree()?.id;
+        let index = gix_index::State::from_tree(&root_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
+            .map_err(|err| Error::IndexFromTree {
+                id: root_tree,
+                source: err,
+            })?;
+        let mut index = gix_index::File::from_state(index, repo.index_path());
+
+        let mut opts = repo.config.checkout_options(repo.git_dir())?;
+        opts.destination_is_initially_empty = destination_is_initially_empty;
+
+        if let Some(patterns) = repo
+            .worktree()
+            .expect("non-bare as checked above")
+            .sparse_checkout_patterns()?
+        {
+            let case = if opts.fs.ignore_case {
+                gix_glob::pattern::Case::Fold
+            } else {
+                gix_glob::pattern::Case::Sensitive
+            };
+            patterns.apply_to_index(&mut index, case);
+        }
+
+        let mut files = progress.add_child_with_id("checkout", ProgressId::CheckoutFiles.into());
+        let mut bytes = progress.add_child_with_id("writing", ProgressId::BytesWritten.into());
+
+        files.init(Some(index.entries().len()), crate::progress::count("files"));
+        bytes.init(None, crate::progress::bytes());
+
+        let start = std::time::Instant::now();
+        let outcome = gix_worktree::index::checkout(
+            &mut index,
+            workdir,
+            {
+                let objects = repo.objects.clone().into_arc()?;
+                move |oid, buf| objects.find_blob(oid, buf)
+            },
+            &mut files,
+            &mut bytes,
+            should_interrupt,
+            opts,
+        )?;
+        files.show_throughput(start);
+        bytes.show_throughput(start);
+
+        repo.write_index(&mut index)?;
+        repo.run_hook(
+            "post-checkout",
+            [repo.object_hash().null().to_string(), head_id.to_string(), "1".into()],
+        )?;
+        Ok(outcome)
+    }
 }
 
 /// Access
@@ -23,6 +23,10 @@ pub enum Error {
     SaveConfig(#[from] crate::remote::save::AsError),
     #[error("Failed to write repository configuration to disk")]
     SaveConfigIo(#[from] std::io::Error),
+    #[error("Failed to reopen the repository after configuring the object hash used by the remote")]
+    ReopenRepository(#[from] crate::open::Error),
+    #[error("Failed to apply configuration after reopening the repository")]
+    ApplyConfig(#[from] crate::config::Error),
     #[error("The remote HEAD points to a reference named {head_ref_name:?} which is invalid.")]
     InvalidHeadRef {
         source: gix_validate::refname::Error,
@@ -71,20 +75,8 @@ impl PrepareFetch {
                 .unwrap_or_else(|| "origin".into()),
         };
 
-        let mut remote = repo
-            .remote_at(self.url.clone())?
-            .with_refspecs(
-                Some(format!("+refs/heads/*:refs/remotes/{remote_name}/*").as_str()),
-                remote::Direction::Fetch,
-            )
-            .expect("valid static spec");
-        let mut clone_fetch_tags = None;
-        if let Some(f) = self.configure_remote.as_mut() {
-            remote = f(remote).map_err(|err| Error::RemoteConfiguration(err))?;
-        } else {
-            clone_fetch_tags = remote::fetch::Tags::All.into();
-        }
-
+        let (mut remote, clone_fetch_tags) =
+            util::configured_remote(repo, &self.url, remote_name.as_ref(), self.configure_remote.as_mut())?;
         let config = util::write_remote_to_local_config_file(&mut remote, remote_name.clone())?;
 
         // Now we are free to apply remote configuration we don't want to be written to disk.
@@ -100,16 +92,31 @@ impl PrepareFetch {
         )
         .expect("valid")
         .to_owned();
-        let pending_pack: remote::fetch::Prepare<'_, '_, _, _> =
-            remote.connect(remote::Direction::Fetch, progress)?.prepare_fetch({
-                let mut opts = self.fetch_options.clone();
-                if !opts.extra_refspecs.contains(&head_refspec) {
-                    opts.extra_refspecs.push(head_refspec)
-                }
-                opts
-            })?;
-        if pending_pack.ref_map().object_hash != repo.object_hash() {
-            unimplemented!("configure repository to expect a different object hash as advertised by the server")
+        let fetch_options = {
+            let mut opts = self.fetch_options.clone();
+            if !opts.extra_refspecs.contains(&head_refspec) {
+                opts.extra_refspecs.push(head_refspec)
+            }
+            opts
+        };
+        let mut pending_pack: remote::fetch::Prepare<'_, '_, _, _> = remote
+            .connect(remote::Direction::Fetch, progress)?
+            .prepare_fetch(fetch_options)?;
+        let object_hash = pending_pack.ref_map().object_hash;
+        if object_hash != repo.object_hash() {
+            // The repository is still empty, so like `git` we adopt the object hash of the remote and continue with the
+            // connection and refs of the first handshake, using a remote that is tied to the reopened repository.
+            let (transport, progress, ref_map) = pending_pack.into_transport_and_ref_map();
+            util::configure_object_hash(repo, object_hash)?;
+            let (new_remote, clone_fetch_tags) =
+                util::configured_remote(repo, &self.url, remote_name.as_ref(), self.configure_remote.as_mut())?;
+            remote = match clone_fetch_tags {
+                Some(fetch_tags) => new_remote.with_fetch_tags(fetch_tags),
+                None => new_remote,
+            };
+            pending_pack = remote
+                .to_connection_with_transport(transport, progress)
+                .prepare_fetch_with_ref_map(ref_map);
         }
         let reflog_message = {
             let mut b = self.url.to_bstring();
@@ -17,6 +17,30 @@ enum WriteMode {
     Append,
 }
 
+/// Create the remote to clone from, and return it along with the tags to fetch if these shouldn't be persisted.
+#[allow(clippy::result_large_err)]
+pub fn configured_remote<'repo>(
+    repo: &'repo Repository,
+    url: &gix_url::Url,
+    remote_name: &BStr,
+    configure_remote: Option<&mut crate::clone::ConfigureRemoteFn>,
+) -> Result<(crate::Remote<'repo>, Option<crate::remote::fetch::Tags>), Error> {
+    let mut remote = repo
+        .remote_at(url.clone())?
+        .with_refspecs(
+            Some(format!("+refs/heads/*:refs/remotes/{remote_name}/*").as_str()),
+            crate::remote::Direction::Fetch,
+        )
+        .expect("valid static spec");
+    let mut clone_fetch_tags = None;
+    if let Some(f) = configure_remote {
+        remote = f(remote).map_err(|err| Error::RemoteConfiguration(err))?;
+    } else {
+        clone_fetch_tags = crate::remote::fetch::Tags::All.into();
+    }
+    Ok((remote, clone_fetch_tags))
+}
+
 #[allow(clippy::result_large_err)]
 pub fn write_remote_to_local_config_file(
     remote: &mut crate::Remote<'_>,
@@ -29,6 +53,48 @@ pub fn write_remote_to_local_config_file(
     Ok(config)
 }
 
+/// Configure the freshly initialized `repo` to use `object_hash` as advertised by the remote and persist it in the local configuration,
+/// then reopen it so objects and references are handled accordingly.
+#[allow(clippy::result_large_err)]
+pub fn configure_object_hash(repo: &mut Repository, object_hash: gix_hash::Kind) -> Result<(), Error> {
+    let path = local_config_meta(repo).path.expect("local config with path set");
+    let mut local_config = gix_config::File::from_path_no_includes(path.clone(), gix_config::Source::Local)?;
+    set_object_format(&mut local_config, object_hash);
+    std::fs::write(path, local_config.to_bstring())?;
+
+    let mut config = repo.config_snapshot_mut();
+    set_object_format(&mut config, object_hash);
+    config.commit().expect("configuration we set is valid");
+
+    let mut reopened = crate::ThreadSafeRepository::open_from_paths(
+        repo.git_dir().to_owned(),
+        repo.work_dir().map(ToOwned::to_owned),
+        repo.options.clone(),
+    )?
+    .to_thread_local();
+    reopened.reread_values_and_clear_caches_replacing_config(repo.config.resolved.clone())?;
+    *repo = reopened;
+    Ok(())
+}
+
+fn set_object_format(config: &mut gix_config::File<'static>, object_hash: gix_hash::Kind) {
+    let mut is_local = |meta: &gix_config::file::Metadata| meta.source == gix_config::Source::Local;
+    config
+        .section_mut_or_create_new_filter("core", None, &mut is_local)
+        .expect("valid at compile time")
+        .set(
+            "repositoryformatversion".try_into().expect("valid at compile time"),
+            "1",
+        );
+    config
+        .section_mut_or_create_new_filter("extensions", None, &mut is_local)
+        .expect("valid at compile time")
+        .set(
+            "objectformat".try_into().expect("valid at compile time"),
+            object_hash.to_string().to_ascii_lowercase().as_str(),
+        );
+}
+
 fn local_config_meta(repo: &Repository) -> gix_config::file::Metadata {
     let meta = repo.config.resolved.meta().clone();
     assert_eq!(
@@ -103,50 +169,43 @@ pub fn update_head(
                 head_ref_name: referent.to_owned(),
                 source: err,
             })?;
-            repo.refs
-                .transaction()
-                .packed_refs(gix_ref::file::transaction::PackedRefs::DeletionsAndNonSymbolicUpdates(
+            repo.commit_transaction(
+                repo.ref_transaction(gix_ref::file::transaction::PackedRefs::DeletionsAndNonSymbolicUpdates(
                     Box::new(|oid, buf| {
                         repo.objects
                             .try_find(oid, buf)
                             .map(|obj| obj.map(|obj| obj.kind))
                             .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
                     }),
-                ))
-                .prepare(
-                    {
-                        let mut edits = vec![RefEdit {
+                )),
+                {
+                    let mut edits = vec![RefEdit {
+                        change: gix_ref::transaction::Change::Update {
+                            log: reflog_message(),
+                            expected: PreviousValue::Any,
+                            new: Target::Symbolic(referent.clone()),
+                        },
+                        name: head.clone(),
+                        deref: false,
+                    }];
+                    if let Some(head_peeled_id) = head_peeled_id {
+                        edits.push(RefEdit {
                             change: gix_ref::transaction::Change::Update {
                                 log: reflog_message(),
                                 expected: PreviousValue::Any,
-                                new: Target::Symbolic(referent.clone()),
+                                new: Target::Peeled(head_peeled_id.to_owned()),
                             },
-                            name: head.clone(),
+                            name: referent.clone(),
                             deref: false,
-                        }];
-                        if let Some(head_peeled_id) = head_peeled_id {
-                            edits.push(RefEdit {
-                                change: gix_ref::transaction::Change::Update {
-                                    log: reflog_message(),
-                                    expected: PreviousValue::Any,
-                                    new: Target::Peeled(head_peeled_id.to_owned()),
-                                },
-                                name: referent.clone(),
-                                deref: false,
-                            });
-                        };
-                        edits
-                    },
+                        });
+                    };
+                    edits
+                },
+                (
                     gix_lock::acquire::Fail::Immediately,
                     gix_lock::acquire::Fail::Immediately,
-                )
-                .map_err(crate::reference::edit::Error::from)?
-                .commit(
-                    repo.committer()
-                        .transpose()
-                        .map_err(|err| Error::HeadUpdate(crate::reference::edit::Error::ParseCommitterTime(err)))?,
-                )
-                .map_err(crate::reference::edit::Error::from)?;
+                ),
+            )?;
 
             if let Some(head_peeled_id) = head_peeled_id {
                 let mut log = reflog_message();
@@ -19,6 +19,14 @@ pub enum Error {
     WriteObject(#[from] crate::object::write::Error),
     #[error(transparent)]
     ReferenceEdit(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    Sign(#[from] crate::signing::Error),
+    #[error(transparent)]
+    Hook(#[from] crate::hook::Error),
+    #[error("Could not exchange the commit message with the commit-msg hook")]
+    CommitMessageFile(#[from] std::io::Error),
 }
 
 ///
@@ -0,0 +1,24 @@
+//! Support for the compatibility object format configured with `extensions.compatObjectFormat`, which allows to know the id
+//! each object would have if the repository was using another kind of hash.
+//!
+//! Mappings are only kept in the loose object map, for loose and packed objects alike, as pack indices with mapping sections
+//! can neither be read nor written. Remotes using the compatibility object format can't be fetched from or pushed to either,
+//! as objects aren't translated when talking to them.
+pub use gix_odb::compat::ObjectMap;
+
+/// The error returned by [`Repository::compat_object_id()`][crate::Repository::compat_object_id()] and
+/// [`Repository::object_id_from_compat()`][crate::Repository::object_id_from_compat()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    LoadObjectMap(#[from] gix_odb::compat::init::Error),
+    #[error("Could not record the id of an object in the compatibility object format")]
+    WriteObjectMap(#[from] gix_odb::compat::write::Error),
+    #[error("Could not compute the id of an object in the compatibility object format")]
+    ComputeId(#[from] std::io::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Convert(#[from] gix_object::convert::Error),
+}
@@ -9,11 +9,12 @@ use crate::{
     config::{
         cache::util::{ApplyLeniency, ApplyLeniencyDefault},
         checkout_options,
-        tree::{Checkout, Core, Key},
+        tree::{keys, Blame, Checkout, Core, Gpg, Key, Merge},
         Cache,
     },
     remote,
     repository::identity,
+    signing,
 };
 
 /// Access
@@ -79,6 +80,44 @@ impl Cache {
             .copied()
     }
 
+    /// Returns the rename tracking to use when merging, or `None` if it is disabled by `merge.renames` or `diff.renames`.
+    pub(crate) fn merge_renames(
+        &self,
+    ) -> Result<Option<crate::object::tree::diff::Rewrites>, crate::object::tree::diff::rewrites::Error> {
+        crate::object::tree::diff::Rewrites::try_from_merge_config(&self.resolved, self.lenient_config)
+    }
+
+    /// Returns the style of conflict markers to use when merging, as configured with `merge.conflictStyle`.
+    pub(crate) fn merge_conflict_style(
+        &self,
+    ) -> Result<gix_diff::blob::merge::ConflictStyle, config::key::GenericErrorWithValue> {
+        self.resolved
+            .string("merge", None, Merge::CONFLICT_STYLE.name)
+            .map(|value| Merge::CONFLICT_STYLE.try_into_conflict_style(value))
+            .transpose()
+            .with_leniency(self.lenient_config)
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Returns true if objects are to be signed according to `key`, which is `commit.gpgSign` or `tag.gpgSign`.
+    pub(crate) fn gpg_sign(&self, key: &'static keys::Boolean) -> Result<bool, config::boolean::Error> {
+        self.resolved
+            .boolean(key.section().name(), None, key.name)
+            .map(|value| key.enrich_error(value))
+            .transpose()
+            .with_leniency(self.lenient_config)
+            .map(Option::unwrap_or_default)
+    }
+
+    pub(crate) fn gpg_format(&self) -> Result<signing::Format, config::key::GenericErrorWithValue> {
+        self.resolved
+            .string("gpg", None, Gpg::FORMAT.name)
+            .map(|value| Gpg::FORMAT.try_into_format(value))
+            .transpose()
+            .with_leniency(self.lenient_config)
+            .map(Option::unwrap_or_default)
+    }
+
     /// Returns (file-timeout, pack-refs timeout)
     pub(crate) fn lock_timeout(
         &self,
@@ -106,6 +145,13 @@ impl Cache {
             .into()
     }
 
+    /// The path to the file with revisions to ignore when blaming, as configured in `blame.ignoreRevsFile`.
+    pub(crate) fn blame_ignore_revs_file(&self) -> Option<Result<PathBuf, gix_config::path::interpolate::Error>> {
+        self.trusted_file_path("blame", None, Blame::IGNORE_REVS_FILE.name)?
+            .map(|p| p.into_owned())
+            .into()
+    }
+
     /// A helper to obtain a file from trusted configuration at `section_name`, `subsection_name`, and `key`, which is interpolated
     /// if present.
     pub(crate) fn trusted_file_path(
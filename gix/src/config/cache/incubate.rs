@@ -1,6 +1,9 @@
 #![allow(clippy::result_large_err)]
 use super::{util, Error};
-use crate::config::tree::{Core, Extensions};
+use crate::{
+    bstr::BString,
+    config::tree::{extensions::RefStorageFormat, Core, Extensions},
+};
 
 /// A utility to deal with the cyclic dependency between the ref store and the configuration. The ref-store needs the
 /// object hash kind, and the configuration needs the current branch name to resolve conditional includes with `onbranch`.
@@ -11,6 +14,8 @@ pub(crate) struct StageOne {
     pub is_bare: bool,
     pub lossy: Option<bool>,
     pub object_hash: gix_hash::Kind,
+    pub compat_object_hash: Option<gix_hash::Kind>,
+    pub ref_storage: RefStorageFormat,
     pub reflog: Option<gix_ref::store::WriteReflog>,
 }
 
@@ -40,6 +45,7 @@ impl StageOne {
             .map(|version| Core::REPOSITORY_FORMAT_VERSION.try_into_usize(version))
             .transpose()?
             .unwrap_or_default();
+        verify_repository_format(&config, repo_format_version)?;
         let object_hash = (repo_format_version != 1)
             .then_some(Ok(gix_hash::Kind::Sha1))
             .or_else(|| {
@@ -49,6 +55,12 @@ impl StageOne {
             })
             .transpose()?
             .unwrap_or(gix_hash::Kind::Sha1);
+        let compat_object_hash = (repo_format_version == 1)
+            .then(|| config.string("extensions", None, "compatObjectFormat"))
+            .flatten()
+            .map(|format| Extensions::COMPAT_OBJECT_FORMAT.try_into_object_format(format))
+            .transpose()?
+            .filter(|compat_object_hash| *compat_object_hash != object_hash);
 
         let extension_worktree = util::config_bool(
             &config,
@@ -68,6 +80,25 @@ impl StageOne {
             config.append(worktree_config);
         };
 
+        util::config_bool(
+            &config,
+            &Extensions::PRECIOUS_OBJECTS,
+            "extensions.preciousObjects",
+            false,
+            lenient,
+        )?;
+        let ref_storage = match (repo_format_version == 1)
+            .then(|| config.string("extensions", None, "refStorage"))
+            .flatten()
+        {
+            Some(ref_storage) => Extensions::REF_STORAGE
+                .try_into_ref_storage(ref_storage.clone())
+                .map_err(|_| Error::UnsupportedRefStorage {
+                    name: ref_storage.into_owned(),
+                })?,
+            None => RefStorageFormat::Files,
+        };
+
         let reflog = util::query_refupdates(&config, lenient)?;
         Ok(StageOne {
             git_dir_config: config,
@@ -75,11 +106,46 @@ impl StageOne {
             is_bare,
             lossy,
             object_hash,
+            compat_object_hash,
+            ref_storage,
             reflog,
         })
     }
 }
 
+/// Like `git`, refuse to open repositories with a format `version` we don't know, or with extensions in `config` which we
+/// don't understand but which alter the repository in ways that we would have to be aware of to not corrupt it.
+///
+/// Note that unknown extensions are ignored in repositories of version 0, as they predate their introduction.
+fn verify_repository_format(config: &gix_config::File<'_>, version: usize) -> Result<(), Error> {
+    if version > 1 {
+        return Err(Error::UnsupportedRepositoryFormatVersion { version });
+    }
+    let mut unknown = Vec::new();
+    let mut v1_only = Vec::new();
+    for section in config.sections_by_name("extensions").into_iter().flatten() {
+        for key in section.keys() {
+            let name: BString = match section.header().subsection_name() {
+                Some(subsection) => format!("{subsection}.{key}").into(),
+                None => key.as_ref().into(),
+            };
+            let names = match name.to_ascii_lowercase().as_slice() {
+                b"noop" | b"preciousobjects" | b"partialclone" | b"worktreeconfig" => continue,
+                b"noop-v1" | b"objectformat" | b"compatobjectformat" | b"refstorage" => &mut v1_only,
+                _ => &mut unknown,
+            };
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    match version {
+        0 if !v1_only.is_empty() => Err(Error::ExtensionsRequireVersion1 { names: v1_only }),
+        1 if !unknown.is_empty() => Err(Error::UnknownExtensions { names: unknown }),
+        _ => Ok(()),
+    }
+}
+
 fn load_config(
     config_path: std::path::PathBuf,
     buf: &mut Vec<u8>,
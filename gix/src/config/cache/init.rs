@@ -25,6 +25,8 @@ impl Cache {
             lossy,
             is_bare,
             object_hash,
+            compat_object_hash,
+            ref_storage: _,
             reflog: _,
         }: StageOne,
         git_dir: &std::path::Path,
@@ -167,6 +169,7 @@ impl Cache {
             resolved: config.into(),
             use_multi_pack_index,
             object_hash,
+            compat_object_hash,
             object_kind_hint,
             pack_cache_bytes,
             object_cache_bytes,
@@ -254,7 +257,8 @@ impl crate::Repository {
     }
 
     fn apply_changed_values(&mut self) {
-        self.refs.write_reflog = util::reflog_or_default(self.config.reflog, self.work_dir().is_some());
+        let write_reflog = util::reflog_or_default(self.config.reflog, self.work_dir().is_some());
+        self.refs.set_write_reflog(write_reflog);
     }
 }
 
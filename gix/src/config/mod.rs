@@ -27,11 +27,14 @@ pub struct Snapshot<'repo> {
 /// Note that these values won't update even if the underlying file(s) change.
 ///
 /// Use [`forget()`][Self::forget()] to not apply any of the changes.
-// TODO: make it possible to load snapshots with reloading via .config() and write mutated snapshots back to disk which should be the way
-//       to affect all instances of a repo, probably via `config_mut()` and `config_mut_at()`.
+///
+/// If created with [`Repository::config_snapshot_mut_at()`], it contains only a single configuration file and changes are
+/// applied by writing that file instead, which only happens when [committing][Self::commit()].
 pub struct SnapshotMut<'repo> {
     pub(crate) repo: Option<&'repo mut Repository>,
     pub(crate) config: gix_config::File<'static>,
+    /// The lock of the configuration file to write when committing, if this snapshot contains only that file.
+    pub(crate) lock: Option<gix_lock::File>,
 }
 
 /// A utility structure created by [`SnapshotMut::commit_auto_rollback()`] that restores the previous configuration on drop.
@@ -60,6 +63,14 @@ pub enum Error {
     ConfigTypedString(#[from] key::GenericErrorWithValue),
     #[error("Cannot handle objects formatted as {:?}", .name)]
     UnsupportedObjectFormat { name: BString },
+    #[error("Expected a repository format version of 0 or 1, found {version}")]
+    UnsupportedRepositoryFormatVersion { version: usize },
+    #[error("Unknown repository extensions found: {}", join_names(.names))]
+    UnknownExtensions { names: Vec<BString> },
+    #[error("The repository format version is 0, but extensions requiring version 1 were found: {}", join_names(.names))]
+    ExtensionsRequireVersion1 { names: Vec<BString> },
+    #[error("Cannot handle references stored as {:?}", .name)]
+    UnsupportedRefStorage { name: BString },
     #[error(transparent)]
     CoreAbbrev(#[from] abbrev::Error),
     #[error("Could not read configuration file")]
@@ -72,6 +83,15 @@ pub enum Error {
     FromEnv(#[from] gix_config::file::init::from_env::Error),
     #[error(transparent)]
     PathInterpolation(#[from] gix_config::path::interpolate::Error),
+    #[error("Could not write the configuration file at '{}'", path.display())]
+    WriteFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    CommitFile(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error("Could not reload the configuration after writing it")]
+    ReloadFile(#[source] Box<crate::open::Error>),
     #[error("{source:?} configuration overrides at open or init time could not be applied.")]
     ConfigOverrides {
         #[source]
@@ -80,6 +100,43 @@ pub enum Error {
     },
 }
 
+fn join_names(names: &[BString]) -> String {
+    names.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+///
+pub mod snapshot_mut_at {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Repository::config_snapshot_mut_at()`][crate::Repository::config_snapshot_mut_at()] and
+    /// [`Repository::edit_worktree_config()`][crate::Repository::edit_worktree_config()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(
+            "Configuration of source {config_source:?} isn't stored in a file, or its location could not be determined"
+        )]
+        NoStorageLocation { config_source: gix_config::Source },
+        #[error(
+            "extensions.worktreeConfig must be enabled to configure a single worktree if there are linked worktrees"
+        )]
+        ExtensionDisabled,
+        #[error(transparent)]
+        ConfigBoolean(#[from] super::boolean::Error),
+        #[error("Could not list the linked worktrees")]
+        ListWorktrees(#[source] std::io::Error),
+        #[error(transparent)]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error("Could not read the configuration file at '{}'", path.display())]
+        Read {
+            path: PathBuf,
+            source: gix_config::file::init::from_paths::Error,
+        },
+        #[error(transparent)]
+        Commit(#[from] super::Error),
+    }
+}
+
 ///
 pub mod diff {
     ///
@@ -115,6 +172,21 @@ pub mod checkout_options {
     }
 }
 
+///
+pub mod index_write_options {
+    /// The error produced when obtaining the options for writing the index.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        IndexVersion(#[from] super::key::GenericError),
+        #[error(transparent)]
+        ConfigBoolean(#[from] super::boolean::Error),
+        #[error(transparent)]
+        SharedIndexExpire(#[from] super::key::GenericErrorWithValue),
+    }
+}
+
 ///
 pub mod protocol {
     ///
@@ -415,6 +487,8 @@ pub(crate) struct Cache {
     pub is_bare: bool,
     /// The type of hash to use.
     pub object_hash: gix_hash::Kind,
+    /// The type of hash of the compatibility object format, if one is configured that differs from `object_hash`.
+    pub compat_object_hash: Option<gix_hash::Kind>,
     /// If true, multi-pack indices, whether present or not, may be used by the object database.
     pub use_multi_pack_index: bool,
     /// The representation of `core.logallrefupdates`, or `None` if the variable wasn't set.
@@ -25,6 +25,10 @@ impl Debug for SnapshotMut<'_> {
 
 impl Drop for SnapshotMut<'_> {
     fn drop(&mut self) {
+        // Files are only written if committed explicitly, and dropping the lock leaves them untouched.
+        if self.lock.is_some() {
+            return;
+        }
         if let Some(repo) = self.repo.take() {
             self.commit_inner(repo).ok();
         };
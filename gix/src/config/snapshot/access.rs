@@ -94,6 +94,12 @@ impl<'repo> SnapshotMut<'repo> {
     ///
     /// Note that this would also happen once this instance is dropped, but using this method may be more intuitive and won't squelch errors
     /// in case the new configuration is partially invalid.
+    ///
+    /// If this instance was created with [`Repository::config_snapshot_mut_at()`][crate::Repository::config_snapshot_mut_at()],
+    /// the configuration file is atomically replaced with the changed configuration instead, creating it if needed, before the
+    /// configuration of the repository is reloaded from disk. This also discards all in-memory changes made with
+    /// [`Repository::config_snapshot_mut()`][crate::Repository::config_snapshot_mut()]. Such instances are never committed
+    /// when dropped.
     pub fn commit(mut self) -> Result<&'repo mut crate::Repository, crate::config::Error> {
         let repo = self.repo.take().expect("always present here");
         self.commit_inner(repo)
@@ -103,11 +109,39 @@ impl<'repo> SnapshotMut<'repo> {
         &mut self,
         repo: &'repo mut crate::Repository,
     ) -> Result<&'repo mut crate::Repository, crate::config::Error> {
-        repo.reread_values_and_clear_caches_replacing_config(std::mem::take(&mut self.config).into())?;
+        match self.lock.take() {
+            Some(lock) => {
+                Self::write_file(&self.config, lock)?;
+                let reopened = crate::ThreadSafeRepository::open_from_paths(
+                    repo.git_dir().to_owned(),
+                    repo.work_dir().map(ToOwned::to_owned),
+                    repo.options.clone(),
+                )
+                .map_err(|err| crate::config::Error::ReloadFile(Box::new(err)))?;
+                repo.reread_values_and_clear_caches_replacing_config(reopened.config.resolved)?;
+            }
+            None => repo.reread_values_and_clear_caches_replacing_config(std::mem::take(&mut self.config).into())?,
+        }
         Ok(repo)
     }
 
+    fn write_file(config: &gix_config::File<'static>, mut lock: gix_lock::File) -> Result<(), crate::config::Error> {
+        use std::io::Write;
+
+        config
+            .write_to(&mut lock)
+            .and_then(|_| lock.flush())
+            .map_err(|err| crate::config::Error::WriteFile {
+                path: lock.resource_path(),
+                source: err,
+            })?;
+        lock.commit()?;
+        Ok(())
+    }
+
     /// Create a structure the temporarily commits the changes, but rolls them back when dropped.
+    ///
+    /// Note that only the in-memory configuration is rolled back, so a configuration file written by committing stays as is.
     pub fn commit_auto_rollback(mut self) -> Result<CommitAutoRollback<'repo>, crate::config::Error> {
         let repo = self.repo.take().expect("this only runs once on consumption");
         let prev_config = OwnShared::clone(&repo.config.resolved);
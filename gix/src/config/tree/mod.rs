@@ -18,12 +18,16 @@ pub(crate) mod root {
     impl Tree {
         /// The `author` section.
         pub const AUTHOR: sections::Author = sections::Author;
+        /// The `blame` section.
+        pub const BLAME: sections::Blame = sections::Blame;
         /// The `branch` section.
         pub const BRANCH: sections::Branch = sections::Branch;
         /// The `checkout` section.
         pub const CHECKOUT: sections::Checkout = sections::Checkout;
         /// The `clone` section.
         pub const CLONE: sections::Clone = sections::Clone;
+        /// The `commit` section.
+        pub const COMMIT: sections::Commit = sections::Commit;
         /// The `committer` section.
         pub const COMMITTER: sections::Committer = sections::Committer;
         /// The `core` section.
@@ -34,12 +38,20 @@ pub(crate) mod root {
         pub const DIFF: sections::Diff = sections::Diff;
         /// The `extensions` section.
         pub const EXTENSIONS: sections::Extensions = sections::Extensions;
+        /// The `feature` section.
+        pub const FEATURE: sections::Feature = sections::Feature;
         /// The `gitoxide` section.
         pub const GITOXIDE: sections::Gitoxide = sections::Gitoxide;
+        /// The `gpg` section.
+        pub const GPG: sections::Gpg = sections::Gpg;
         /// The `http` section.
         pub const HTTP: sections::Http = sections::Http;
+        /// The `index` section.
+        pub const INDEX: sections::Index = sections::Index;
         /// The `init` section.
         pub const INIT: sections::Init = sections::Init;
+        /// The `merge` section.
+        pub const MERGE: sections::Merge = sections::Merge;
         /// The `pack` section.
         pub const PACK: sections::Pack = sections::Pack;
         /// The `protocol` section.
@@ -48,8 +60,12 @@ pub(crate) mod root {
         pub const REMOTE: sections::Remote = sections::Remote;
         /// The `safe` section.
         pub const SAFE: sections::Safe = sections::Safe;
+        /// The `splitIndex` section.
+        pub const SPLIT_INDEX: sections::SplitIndex = sections::SplitIndex;
         /// The `ssh` section.
         pub const SSH: sections::Ssh = sections::Ssh;
+        /// The `tag` section.
+        pub const TAG: sections::Tag = sections::Tag;
         /// The `user` section.
         pub const USER: sections::User = sections::User;
         /// The `url` section.
@@ -59,22 +75,30 @@ pub(crate) mod root {
         pub fn sections(&self) -> &[&dyn Section] {
             &[
                 &Self::AUTHOR,
+                &Self::BLAME,
                 &Self::BRANCH,
                 &Self::CHECKOUT,
                 &Self::CLONE,
+                &Self::COMMIT,
                 &Self::COMMITTER,
                 &Self::CORE,
                 &Self::CREDENTIAL,
                 &Self::DIFF,
                 &Self::EXTENSIONS,
+                &Self::FEATURE,
                 &Self::GITOXIDE,
+                &Self::GPG,
                 &Self::HTTP,
+                &Self::INDEX,
                 &Self::INIT,
+                &Self::MERGE,
                 &Self::PACK,
                 &Self::PROTOCOL,
                 &Self::REMOTE,
                 &Self::SAFE,
+                &Self::SPLIT_INDEX,
                 &Self::SSH,
+                &Self::TAG,
                 &Self::USER,
                 &Self::URL,
             ]
@@ -84,9 +108,9 @@ pub(crate) mod root {
 
 mod sections;
 pub use sections::{
-    branch, checkout, core, credential, diff, extensions, gitoxide, http, protocol, remote, ssh, Author, Branch,
-    Checkout, Clone, Committer, Core, Credential, Diff, Extensions, Gitoxide, Http, Init, Pack, Protocol, Remote, Safe,
-    Ssh, Url, User,
+    branch, checkout, core, credential, diff, extensions, gitoxide, gpg, http, index, merge, protocol, remote,
+    split_index, ssh, Author, Blame, Branch, Checkout, Clone, Commit, Committer, Core, Credential, Diff, Extensions,
+    Feature, Gitoxide, Gpg, Http, Index, Init, Merge, Pack, Protocol, Remote, Safe, SplitIndex, Ssh, Tag, Url, User,
 };
 
 /// Generic value implementations for static instantiation.
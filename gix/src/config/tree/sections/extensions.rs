@@ -4,16 +4,34 @@ use crate::{
 };
 
 impl Extensions {
+    /// The `extensions.noop` key, which has no effect and is known to repositories of any format version.
+    pub const NOOP: keys::Any = keys::Any::new("noop", &config::Tree::EXTENSIONS);
+    /// The `extensions.noop-v1` key, which has no effect but is only valid in repositories of format version 1.
+    pub const NOOP_V1: keys::Any = keys::Any::new("noop-v1", &config::Tree::EXTENSIONS);
+    /// The `extensions.preciousObjects` key.
+    pub const PRECIOUS_OBJECTS: keys::Boolean =
+        keys::Boolean::new_boolean("preciousObjects", &config::Tree::EXTENSIONS)
+            .with_note("objects are never deleted by gitoxide, so there is nothing to prevent");
+    /// The `extensions.partialClone` key.
+    pub const PARTIAL_CLONE: keys::RemoteName =
+        keys::RemoteName::new_remote_name("partialClone", &config::Tree::EXTENSIONS)
+            .with_note("missing objects are not fetched from the promisor remote yet");
+    /// The `extensions.refStorage` key.
+    pub const REF_STORAGE: RefStorage =
+        RefStorage::new_with_validate("refStorage", &config::Tree::EXTENSIONS, validate::RefStorage)
+            .with_note("per-worktree references of linked worktrees aren't read from reftables yet");
     /// The `extensions.worktreeConfig` key.
     pub const WORKTREE_CONFIG: keys::Boolean = keys::Boolean::new_boolean("worktreeConfig", &config::Tree::EXTENSIONS);
     /// The `extensions.objectFormat` key.
     pub const OBJECT_FORMAT: ObjectFormat =
-        ObjectFormat::new_with_validate("objectFormat", &config::Tree::EXTENSIONS, validate::ObjectFormat).with_note(
-            "Support for SHA256 is prepared but not fully implemented yet. For now we abort when encountered",
-        );
+        ObjectFormat::new_with_validate("objectFormat", &config::Tree::EXTENSIONS, validate::ObjectFormat);
+    /// The `extensions.compatObjectFormat` key.
+    pub const COMPAT_OBJECT_FORMAT: ObjectFormat =
+        ObjectFormat::new_with_validate("compatObjectFormat", &config::Tree::EXTENSIONS, validate::ObjectFormat)
+            .with_note("fetching from and pushing to remotes using the compatibility object format isn't supported");
 }
 
-/// The `core.checkStat` key.
+/// The `extensions.objectFormat` and `extensions.compatObjectFormat` keys.
 pub type ObjectFormat = keys::Any<validate::ObjectFormat>;
 
 mod object_format {
@@ -28,6 +46,8 @@ mod object_format {
         ) -> Result<gix_hash::Kind, config::key::GenericErrorWithValue> {
             if value.as_ref().eq_ignore_ascii_case(b"sha1") {
                 Ok(gix_hash::Kind::Sha1)
+            } else if value.as_ref().eq_ignore_ascii_case(b"sha256") {
+                Ok(gix_hash::Kind::Sha256)
             } else {
                 Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned()))
             }
@@ -35,13 +55,58 @@ mod object_format {
     }
 }
 
+/// The `extensions.refStorage` key.
+pub type RefStorage = keys::Any<validate::RefStorage>;
+
+/// The backend used to store references, as configured with `extensions.refStorage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefStorageFormat {
+    /// Loose reference files along with a `packed-refs` file, the default.
+    Files,
+    /// A stack of reftables.
+    Reftable,
+}
+
+mod ref_storage {
+    use std::borrow::Cow;
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::sections::extensions::{RefStorage, RefStorageFormat},
+    };
+
+    impl RefStorage {
+        /// Derive the backend used to store references from `value`.
+        pub fn try_into_ref_storage(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<RefStorageFormat, config::key::GenericErrorWithValue> {
+            Ok(match value.as_ref().as_bytes() {
+                b"files" => RefStorageFormat::Files,
+                b"reftable" => RefStorageFormat::Reftable,
+                _ => return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+            })
+        }
+    }
+}
+
 impl Section for Extensions {
     fn name(&self) -> &str {
         "extensions"
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::OBJECT_FORMAT, &Self::WORKTREE_CONFIG]
+        &[
+            &Self::NOOP,
+            &Self::NOOP_V1,
+            &Self::OBJECT_FORMAT,
+            &Self::COMPAT_OBJECT_FORMAT,
+            &Self::PARTIAL_CLONE,
+            &Self::PRECIOUS_OBJECTS,
+            &Self::REF_STORAGE,
+            &Self::WORKTREE_CONFIG,
+        ]
     }
 }
 
@@ -56,4 +121,13 @@ mod validate {
             Ok(())
         }
     }
+
+    pub struct RefStorage;
+
+    impl keys::Validate for RefStorage {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Extensions::REF_STORAGE.try_into_ref_storage(value.into())?;
+            Ok(())
+        }
+    }
 }
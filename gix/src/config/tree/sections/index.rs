@@ -0,0 +1,60 @@
+use crate::{
+    config,
+    config::tree::{keys, Index, Key, Section},
+};
+
+impl Index {
+    /// The `index.version` key.
+    pub const VERSION: IndexVersion =
+        IndexVersion::new_with_validate("version", &config::Tree::INDEX, validate::Version);
+    /// The `index.sparse` key.
+    pub const SPARSE: keys::Boolean = keys::Boolean::new_boolean("sparse", &config::Tree::INDEX);
+}
+
+/// The `index.version` key.
+pub type IndexVersion = keys::Any<validate::Version>;
+
+mod index_version {
+    use crate::{config, config::tree::sections::index::IndexVersion};
+
+    impl IndexVersion {
+        /// Try to interpret an integer value as index version.
+        pub fn try_into_index_version(
+            &'static self,
+            value: Result<i64, gix_config::value::Error>,
+        ) -> Result<gix_index::Version, config::key::GenericError> {
+            let value = value.map_err(|err| config::key::GenericError::from(self).with_source(err))?;
+            Ok(match value {
+                2 => gix_index::Version::V2,
+                3 => gix_index::Version::V3,
+                4 => gix_index::Version::V4,
+                _ => return Err(config::key::GenericError::from(self)),
+            })
+        }
+    }
+}
+
+impl Section for Index {
+    fn name(&self) -> &str {
+        "index"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::VERSION, &Self::SPARSE]
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct Version;
+    impl keys::Validate for Version {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Index::VERSION.try_into_index_version(gix_config::Integer::try_from(value).and_then(|int| {
+                int.to_decimal()
+                    .ok_or_else(|| gix_config::value::Error::new("integer out of range", value))
+            }))?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Merge, Section},
+};
+
+impl Merge {
+    /// The `merge.conflictStyle` key.
+    pub const CONFLICT_STYLE: ConflictStyle =
+        ConflictStyle::new_with_validate("conflictStyle", &config::Tree::MERGE, validate::ConflictStyle);
+    /// The `merge.renameLimit` key.
+    pub const RENAME_LIMIT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("renameLimit", &config::Tree::MERGE)
+            .with_note("Defaults to the value of diff.renameLimit");
+    /// The `merge.renames` key.
+    pub const RENAMES: super::diff::Renames = super::diff::Renames::new_renames("renames", &config::Tree::MERGE)
+        .with_note("Defaults to the value of diff.renames, and copies are never tracked during merges");
+}
+
+impl Section for Merge {
+    fn name(&self) -> &str {
+        "merge"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::CONFLICT_STYLE, &Self::RENAME_LIMIT, &Self::RENAMES]
+    }
+}
+
+/// The `merge.conflictStyle` key.
+pub type ConflictStyle = keys::Any<validate::ConflictStyle>;
+
+mod conflict_style {
+    use std::borrow::Cow;
+
+    use gix_diff::blob::merge;
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::sections::merge::ConflictStyle,
+    };
+
+    impl ConflictStyle {
+        /// Derive the style of conflict markers from `value`.
+        pub fn try_into_conflict_style(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<merge::ConflictStyle, config::key::GenericErrorWithValue> {
+            Ok(match value.as_ref().as_bytes() {
+                b"merge" => merge::ConflictStyle::Merge,
+                b"diff3" => merge::ConflictStyle::Diff3,
+                b"zdiff3" => merge::ConflictStyle::ZealousDiff3,
+                _ => return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+            })
+        }
+    }
+}
+
+mod validate {
+    use crate::{
+        bstr::BStr,
+        config::tree::{keys, Merge},
+    };
+
+    pub struct ConflictStyle;
+    impl keys::Validate for ConflictStyle {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            Merge::CONFLICT_STYLE.try_into_conflict_style(value.into())?;
+            Ok(())
+        }
+    }
+}
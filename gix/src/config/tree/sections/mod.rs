@@ -5,6 +5,11 @@
 pub struct Author;
 mod author;
 
+/// The `blame` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Blame;
+mod blame;
+
 /// The `branch` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Branch;
@@ -20,6 +25,11 @@ pub mod checkout;
 pub struct Clone;
 mod clone;
 
+/// The `commit` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Commit;
+mod commit;
+
 /// The `committer` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Committer;
@@ -45,21 +55,41 @@ pub mod diff;
 pub struct Extensions;
 pub mod extensions;
 
+/// The `feature` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Feature;
+mod feature;
+
 /// The `gitoxide` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Gitoxide;
 pub mod gitoxide;
 
+/// The `gpg` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Gpg;
+pub mod gpg;
+
 /// The `http` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Http;
 pub mod http;
 
+/// The `index` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Index;
+pub mod index;
+
 /// The `init` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Init;
 mod init;
 
+/// The `merge` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Merge;
+pub mod merge;
+
 /// The `pack` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Pack;
@@ -80,11 +110,21 @@ pub mod remote;
 pub struct Safe;
 mod safe;
 
+/// The `splitIndex` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct SplitIndex;
+pub mod split_index;
+
 /// The `ssh` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Ssh;
 pub mod ssh;
 
+/// The `tag` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Tag;
+mod tag;
+
 /// The `user` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct User;
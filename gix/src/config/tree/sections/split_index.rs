@@ -0,0 +1,112 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Section, SplitIndex},
+};
+
+impl SplitIndex {
+    /// The `splitIndex.maxPercentChange` key.
+    pub const MAX_PERCENT_CHANGE: MaxPercentChange = MaxPercentChange::new_with_validate(
+        "maxPercentChange",
+        &config::Tree::SPLIT_INDEX,
+        validate::MaxPercentChange,
+    );
+    /// The `splitIndex.sharedIndexExpire` key.
+    pub const SHARED_INDEX_EXPIRE: SharedIndexExpire = SharedIndexExpire::new_with_validate(
+        "sharedIndexExpire",
+        &config::Tree::SPLIT_INDEX,
+        validate::SharedIndexExpire,
+    );
+}
+
+/// The `splitIndex.maxPercentChange` key.
+pub type MaxPercentChange = keys::Any<validate::MaxPercentChange>;
+
+/// The `splitIndex.sharedIndexExpire` key.
+pub type SharedIndexExpire = keys::Any<validate::SharedIndexExpire>;
+
+mod max_percent_change {
+    use crate::{config, config::tree::sections::split_index::MaxPercentChange};
+
+    impl MaxPercentChange {
+        /// Try to interpret an integer value as percentage between `0` and `100`.
+        pub fn try_into_percentage(
+            &'static self,
+            value: Result<i64, gix_config::value::Error>,
+        ) -> Result<u8, config::key::GenericError> {
+            let value = value.map_err(|err| config::key::GenericError::from(self).with_source(err))?;
+            u8::try_from(value)
+                .ok()
+                .filter(|percent| *percent <= 100)
+                .ok_or_else(|| config::key::GenericError::from(self))
+        }
+    }
+}
+
+mod shared_index_expire {
+    use std::{borrow::Cow, time::Duration};
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::sections::split_index::SharedIndexExpire,
+    };
+
+    impl SharedIndexExpire {
+        /// Interpret `value` as the time after which unused shared indices expire, relative to `now`, or `None` if they
+        /// never expire.
+        ///
+        /// Besides `never` and `now`, dates like `2.weeks.ago` are supported.
+        pub fn try_into_expiry(
+            &'static self,
+            value: Cow<'_, BStr>,
+            now: std::time::SystemTime,
+        ) -> Result<Option<Duration>, config::key::GenericErrorWithValue> {
+            let err = || config::key::GenericErrorWithValue::from_value(self, value.clone().into_owned());
+            let date = value.to_str().map_err(|_| err())?;
+            Ok(match date {
+                "never" => None,
+                "now" | "all" => Some(Duration::default()),
+                date => {
+                    let time = gix_date::parse(&date.replace('.', " "), Some(now)).map_err(|_| err())?;
+                    let time = std::time::UNIX_EPOCH + Duration::from_secs(time.seconds_since_unix_epoch.into());
+                    Some(now.duration_since(time).unwrap_or_default())
+                }
+            })
+        }
+    }
+}
+
+impl Section for SplitIndex {
+    fn name(&self) -> &str {
+        "splitIndex"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::MAX_PERCENT_CHANGE, &Self::SHARED_INDEX_EXPIRE]
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct MaxPercentChange;
+    impl keys::Validate for MaxPercentChange {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::SplitIndex::MAX_PERCENT_CHANGE.try_into_percentage(
+                gix_config::Integer::try_from(value).and_then(|int| {
+                    int.to_decimal()
+                        .ok_or_else(|| gix_config::value::Error::new("integer out of range", value))
+                }),
+            )?;
+            Ok(())
+        }
+    }
+
+    pub struct SharedIndexExpire;
+    impl keys::Validate for SharedIndexExpire {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::SplitIndex::SHARED_INDEX_EXPIRE.try_into_expiry(value.into(), std::time::SystemTime::now())?;
+            Ok(())
+        }
+    }
+}
@@ -118,6 +118,11 @@ pub struct Options {
     /// If set, use these filesystem capabilities to populate the respective gix-config fields.
     /// If `None`, the directory will be probed.
     pub fs_capabilities: Option<gix_worktree::fs::Capabilities>,
+    /// The kind of hash to use for all objects in the repository, similar to `--object-format`.
+    ///
+    /// Anything but `Sha1` requires the repository format version `1` along with the `extensions.objectFormat` key,
+    /// which makes the repository unreadable for older `git` versions.
+    pub object_hash: gix_hash::Kind,
 }
 
 /// Create a new `.git` repository of `kind` within the possibly non-existing `directory`
@@ -131,6 +136,7 @@ pub fn into(
     Options {
         fs_capabilities,
         destination_must_be_empty,
+        object_hash,
     }: Options,
 ) -> Result<gix_discover::repository::Path, Error> {
     let mut dot_git = directory.into();
@@ -211,7 +217,11 @@ pub fn into(
             let caps = fs_capabilities.unwrap_or_else(|| gix_worktree::fs::Capabilities::probe(&dot_git));
             let mut core = config.new_section("core", None).expect("valid section name");
 
-            core.push(key("repositoryformatversion"), Some("0".into()));
+            let version = match object_hash {
+                gix_hash::Kind::Sha1 => "0",
+                _ => "1",
+            };
+            core.push(key("repositoryformatversion"), Some(version.into()));
             core.push(key("filemode"), Some(bool(caps.executable_bit).into()));
             core.push(key("bare"), Some(bool(bare).into()));
             core.push(key("logallrefupdates"), Some(bool(!bare).into()));
@@ -219,6 +229,13 @@ pub fn into(
             core.push(key("ignorecase"), Some(bool(caps.ignore_case).into()));
             core.push(key("precomposeunicode"), Some(bool(caps.precompose_unicode).into()));
         }
+        if object_hash != gix_hash::Kind::Sha1 {
+            let mut extensions = config.new_section("extensions", None).expect("valid section name");
+            extensions.push(
+                key("objectformat"),
+                Some(object_hash.to_string().to_ascii_lowercase().as_str().into()),
+            );
+        }
         let mut cursor = PathCursor(&mut dot_git);
         let config_path = cursor.at("config");
         std::fs::write(config_path, config.to_bstring()).map_err(|err| Error::IoWrite {
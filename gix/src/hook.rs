@@ -0,0 +1,141 @@
+//! Run hooks, the programs in the hooks directory of a repository which `git` runs at certain points of its operation.
+//!
+//! Hooks are looked up in `core.hooksPath`, or in the `hooks` directory of the repository, and run just like `git` runs them.
+//! `gitoxide` runs the `reference-transaction` hook whenever it changes references, the `post-checkout` hook after cloning,
+//! and the `pre-commit`, `commit-msg` and `post-commit` hooks when [creating commits][crate::Repository::commit()].
+//!
+//! Whether hooks run at all is controlled by the [hook permissions][crate::Permissions::hooks], which depend on the trust
+//! in the repository.
+use std::{
+    ffi::OsString,
+    io::Write,
+    path::PathBuf,
+    process::{ExitStatus, Stdio},
+};
+
+use crate::Repository;
+
+/// The error returned when finding or running hooks.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    HooksPath(#[from] gix_config::path::interpolate::Error),
+    #[error("Running the '{name}' hook isn't permitted")]
+    Forbidden {
+        name: String,
+        source: gix_sec::permission::Error<PathBuf>,
+    },
+    #[error("Could not run the '{name}' hook at '{}'", path.display())]
+    Spawn {
+        name: String,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("The '{name}' hook failed with {status}")]
+    Failed { name: String, status: ExitStatus },
+}
+
+/// A hook that is ready to run, obtained by [`Repository::hook()`].
+///
+/// It runs with the work tree as current working directory, or the `.git` directory in bare repositories, and with `GIT_DIR`
+/// set. Its output is written to the standard error of the current process, like `git` does.
+pub struct Prepare<'repo> {
+    /// The name of the hook, like `pre-commit`.
+    pub name: String,
+    /// The path to the executable hook.
+    pub path: PathBuf,
+    args: Vec<OsString>,
+    env: Vec<(OsString, OsString)>,
+    stdin: Option<Vec<u8>>,
+    repo: &'repo Repository,
+}
+
+/// Builder
+impl<'repo> Prepare<'repo> {
+    pub(crate) fn new(name: &str, path: PathBuf, repo: &'repo Repository) -> Self {
+        Prepare {
+            name: name.into(),
+            path,
+            args: Vec::new(),
+            env: Vec::new(),
+            stdin: None,
+            repo,
+        }
+    }
+
+    /// Add `arg` to the arguments of the hook.
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add `args` to the arguments of the hook.
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<OsString>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the environment variable `key` to `value` for the hook.
+    pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Pass `data` to the hook on its standard input, which is empty otherwise.
+    pub fn stdin(mut self, data: impl Into<Vec<u8>>) -> Self {
+        self.stdin = Some(data.into());
+        self
+    }
+}
+
+/// Execution
+impl Prepare<'_> {
+    /// Run the hook and wait for it to finish, failing if it doesn't exit successfully.
+    pub fn run(self) -> Result<(), Error> {
+        let Prepare {
+            name,
+            path,
+            args,
+            env,
+            stdin,
+            repo,
+        } = self;
+        let git_dir = repo.absolute(repo.git_dir());
+        let mut cmd: std::process::Command = gix_command::prepare(path.clone().into_os_string())
+            .args(args)
+            .env("GIT_DIR", git_dir.as_os_str())
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .into();
+        cmd.envs(env).current_dir(repo.hooks_working_dir());
+
+        let spawn_failed = |source| Error::Spawn {
+            name: name.clone(),
+            path: path.clone(),
+            source,
+        };
+        let mut child = cmd.spawn().map_err(spawn_failed)?;
+        let output = match stdin {
+            Some(data) => {
+                let mut input = child.stdin.take().expect("configured");
+                // Hooks are free to ignore their input, and like `git` we don't mind if they exit without reading it.
+                std::thread::scope(|scope| {
+                    scope.spawn(move || input.write_all(&data).ok());
+                    child.wait_with_output()
+                })
+            }
+            None => child.wait_with_output(),
+        }
+        .map_err(spawn_failed)?;
+
+        std::io::stderr().write_all(&output.stdout).ok();
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(Error::Failed {
+                name,
+                status: output.status,
+            })
+        }
+    }
+}
@@ -108,8 +108,8 @@ pub mod prelude {
 ///
 pub mod path;
 
-/// The standard type for a store to handle git references.
-pub type RefStore = gix_ref::file::Store;
+/// The standard type for a store to handle git references, backed by loose files or by a stack of reftables.
+pub type RefStore = gix_ref::store::Handle;
 /// A handle for finding objects in an object database, abstracting away caches for thread-local use.
 pub type OdbHandle = gix_odb::Handle;
 /// A way to access git configuration
@@ -122,11 +122,18 @@ pub use types::{
     Worktree,
 };
 
+///
+pub mod apply;
+///
+pub mod blame;
 ///
 pub mod clone;
 pub mod commit;
+pub mod compat;
 pub mod head;
 pub mod id;
+///
+pub mod merge;
 pub mod object;
 pub mod reference;
 mod repository;
@@ -263,6 +270,9 @@ pub mod open;
 ///
 pub mod config;
 
+///
+pub mod hook;
+
 ///
 pub mod mailmap;
 
@@ -271,6 +281,14 @@ pub mod worktree;
 
 pub mod revision;
 
+///
+pub mod sequencer;
+
+///
+pub mod signing;
+///
+pub mod stash;
+
 ///
 pub mod remote;
 
@@ -0,0 +1,4 @@
+//! Merge trees and the blobs within them.
+
+///
+pub mod tree;
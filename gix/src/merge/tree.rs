@@ -0,0 +1,536 @@
+//! Merge trees with a common ancestor, similar to the `ort` merge strategy of `git`.
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use gix_diff::blob::merge as blob;
+use gix_hash::ObjectId;
+use gix_index::entry;
+use gix_object::tree::EntryMode;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    object::tree::diff::{change::Event, patch::Attributes, Rewrites},
+    prelude::FindExt,
+    Id, Repository,
+};
+
+/// The error returned by [`Repository::merge_trees()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExisting(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    TraverseTree(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    Diff(#[from] crate::object::tree::diff::for_each::Error),
+    #[error(transparent)]
+    RewritesConfig(#[from] Box<crate::object::tree::diff::rewrites::Error>),
+    #[error(transparent)]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+    #[error(transparent)]
+    ConflictStyle(#[from] crate::config::key::GenericErrorWithValue),
+    #[error("Could not read attributes")]
+    Attributes(#[from] crate::object::tree::diff::patch::attributes::Error),
+    #[error(transparent)]
+    WriteBlob(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error<crate::object::write::Error>),
+}
+
+/// Options for use in [`Repository::merge_trees_opts()`].
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// The way renames are detected on each side, or `None` to not detect renames at all.
+    ///
+    /// Note that copies are never considered.
+    pub rewrites: Option<Rewrites>,
+    /// Options to control how the content of files is merged, along with the labels to use in conflict markers.
+    ///
+    /// The labels are also used to move files out of the way of directories, like `path~ours`, and default to `ours` and `theirs`.
+    pub blob: blob::Options,
+    /// The attributes to select the [merge driver][Attributes::merge_driver()] and conflict marker size by path, if set.
+    pub attributes: Option<Attributes>,
+}
+
+/// A file that is part of a [`Conflict`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The path of the file relative to the root of the tree it is contained in.
+    pub location: BString,
+    /// The mode of the file.
+    pub mode: EntryMode,
+    /// The id of the file's object.
+    pub id: ObjectId,
+}
+
+/// The kind of [`Conflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum ConflictKind {
+    /// Both sides changed the same file in conflicting ways.
+    ///
+    /// The merged file contains conflict markers, or our version if it couldn't be merged, for instance as it is binary.
+    Content,
+    /// Both sides added a different file at the same location.
+    ///
+    /// The merged file contains conflict markers as if both sides were derived from an empty file.
+    AddAdd,
+    /// One side deleted a file which was modified or renamed by the other side, whose version is kept.
+    ModifyDelete,
+    /// Both sides renamed a file to different locations, and it is kept at both locations.
+    RenameRename,
+    /// Both sides changed the mode of a file differently, for instance turning it into a symbolic link and making it
+    /// executable respectively. Our version is kept.
+    Mode,
+    /// One side added a file at a location at which the other side added a directory.
+    ///
+    /// The file is moved out of the way to `<location>~<label of its side>`.
+    DirectoryFile,
+}
+
+/// A conflict that was encountered while merging trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The kind of the conflict.
+    pub kind: ConflictKind,
+    /// The location of the conflicting file in the merged tree, or `None` if it isn't contained in it.
+    pub location: Option<BString>,
+    /// The file as it was in the common ancestor, if it existed there.
+    pub base: Option<Entry>,
+    /// Our version of the file, if it exists.
+    pub ours: Option<Entry>,
+    /// Their version of the file, if it exists.
+    pub theirs: Option<Entry>,
+}
+
+impl Conflict {
+    /// Return the entries of this conflict along with their stage for use in an index, with `1` for the base, `2` for ours
+    /// and `3` for theirs, just like `git merge-tree` would list them.
+    ///
+    /// All entries are placed at the [location in the merged tree][Conflict::location] if there is one, or at their own
+    /// location otherwise.
+    pub fn stages(&self) -> impl Iterator<Item = (entry::Stage, Entry)> + '_ {
+        [(1, &self.base), (2, &self.ours), (3, &self.theirs)]
+            .into_iter()
+            .filter_map(move |(stage, entry)| {
+                let mut entry = entry.clone()?;
+                if let Some(location) = &self.location {
+                    entry.location = location.clone();
+                }
+                Some((stage, entry))
+            })
+    }
+}
+
+/// The result of [`Repository::merge_trees()`].
+#[derive(Debug, Clone)]
+pub struct Outcome<'repo> {
+    /// The id of the merged tree, which was written to the object database.
+    ///
+    /// Conflicting files are contained in the tree as described by their [kind][ConflictKind].
+    pub tree: Id<'repo>,
+    /// All conflicts that were encountered, with the merge being clean if there are none.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Outcome<'_> {
+    /// Return `true` if there were no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Record all conflicts in `index` as entries of stage 1 to 3, removing the entries of stage 0 at the same location.
+    ///
+    /// `index` is typically created from the [merged tree][Outcome::tree] and contains no other conflicts, to represent the
+    /// state after a merge like `git merge` would.
+    pub fn write_conflicts_to(&self, index: &mut gix_index::State) {
+        let stages: Vec<_> = self.conflicts.iter().flat_map(Conflict::stages).collect();
+        let locations: HashSet<&BStr> = stages.iter().map(|(_, e)| e.location.as_bstr()).collect();
+        let mut invalidated = Vec::new();
+        for (entry, path) in index.entries_mut_with_paths() {
+            if entry.stage() == 0 && locations.contains(path) {
+                entry.flags.insert(entry::Flags::REMOVE);
+                invalidated.push(path.to_owned());
+            }
+        }
+        for path in invalidated {
+            index.invalidate_tree_for_path(path.as_ref());
+        }
+        for (stage, entry) in stages {
+            index.dangerously_push_entry(
+                Default::default(),
+                entry.id,
+                entry::Flags::from_bits_truncate(stage << 12),
+                index_mode(entry.mode),
+                entry.location.as_ref(),
+            );
+        }
+        index.sort_entries();
+    }
+}
+
+impl Options {
+    /// Create options for `repo` as they would be used by `git merge`, with rename tracking as configured by `merge.renames`
+    /// and `merge.renameLimit`, falling back to their `diff.*` counterparts,
+    /// the diff algorithm and conflict style of `diff.algorithm` and `merge.conflictStyle`, and attributes read from
+    /// `.gitattributes` files in the work tree, if present, and `$GIT_DIR/info/attributes`.
+    pub fn from_repo(repo: &Repository) -> Result<Self, Error> {
+        Ok(Options {
+            rewrites: repo.config.merge_renames().map_err(Box::new)?,
+            blob: blob::Options {
+                algorithm: repo.config.diff_algorithm()?,
+                conflict_style: repo.config.merge_conflict_style()?,
+                ..Default::default()
+            },
+            attributes: Some(Attributes::from_repo(repo)?),
+        })
+    }
+}
+
+type Files = BTreeMap<BString, (EntryMode, ObjectId)>;
+
+pub(crate) fn merge(
+    repo: &Repository,
+    base: ObjectId,
+    ours: ObjectId,
+    theirs: ObjectId,
+    options: &Options,
+) -> Result<(ObjectId, Vec<Conflict>), Error> {
+    let (base_files, ours_files, theirs_files) = (files(repo, base)?, files(repo, ours)?, files(repo, theirs)?);
+    let ours_renames = renames(repo, base, ours, options.rewrites)?;
+    let theirs_renames = renames(repo, base, theirs, options.rewrites)?;
+    let mut state = State {
+        repo,
+        options,
+        result: Files::new(),
+        conflicts: Vec::new(),
+    };
+
+    let (mut ours_seen, mut theirs_seen) = (HashSet::<&BStr>::new(), HashSet::<&BStr>::new());
+    for (path, b) in &base_files {
+        let ours_path = ours_renames.get(path).map_or(path.as_bstr(), |p| p.as_bstr());
+        let theirs_path = theirs_renames.get(path).map_or(path.as_bstr(), |p| p.as_bstr());
+        let o = ours_files.get(ours_path);
+        let t = theirs_files.get(theirs_path);
+        if o.is_some() {
+            ours_seen.insert(ours_path);
+        }
+        if t.is_some() {
+            theirs_seen.insert(theirs_path);
+        }
+        let base_entry = || entry(path.as_bstr(), b);
+        match (o, t) {
+            (None, None) => {}
+            (Some(o), None) | (None, Some(o)) => {
+                let is_ours = t.is_none();
+                let location = if is_ours { ours_path } else { theirs_path };
+                if location == path.as_bstr() && o == b {
+                    // Deleted on one side and unchanged on the other.
+                    continue;
+                }
+                state.insert(location, *o)?;
+                let side = Some(entry(location, o));
+                state.conflicts.push(Conflict {
+                    kind: ConflictKind::ModifyDelete,
+                    location: Some(location.to_owned()),
+                    base: Some(base_entry()),
+                    ours: if is_ours { side.clone() } else { None },
+                    theirs: if is_ours { None } else { side },
+                });
+            }
+            (Some(o), Some(t)) => {
+                let location = if ours_path == theirs_path || theirs_path == path.as_bstr() {
+                    ours_path
+                } else if ours_path == path.as_bstr() {
+                    theirs_path
+                } else {
+                    state.insert(ours_path, *o)?;
+                    state.insert(theirs_path, *t)?;
+                    state.conflicts.push(Conflict {
+                        kind: ConflictKind::RenameRename,
+                        location: None,
+                        base: Some(base_entry()),
+                        ours: Some(entry(ours_path, o)),
+                        theirs: Some(entry(theirs_path, t)),
+                    });
+                    continue;
+                };
+                let (merged, kind) = state.merge_entries(location, Some(*b), *o, *t)?;
+                state.insert(location, merged)?;
+                if let Some(kind) = kind {
+                    state.conflicts.push(Conflict {
+                        kind,
+                        location: Some(location.to_owned()),
+                        base: Some(base_entry()),
+                        ours: Some(entry(ours_path, o)),
+                        theirs: Some(entry(theirs_path, t)),
+                    });
+                }
+            }
+        }
+    }
+
+    for (path, o) in ours_files
+        .iter()
+        .filter(|(path, _)| !ours_seen.contains(path.as_bstr()))
+    {
+        match theirs_files.get(path).filter(|_| !theirs_seen.contains(path.as_bstr())) {
+            Some(t) => {
+                theirs_seen.insert(path.as_bstr());
+                state.insert_added(path.as_bstr(), *o, *t)?;
+            }
+            None => state.insert(path.as_bstr(), *o)?,
+        }
+    }
+    for (path, t) in theirs_files
+        .iter()
+        .filter(|(path, _)| !theirs_seen.contains(path.as_bstr()))
+    {
+        state.insert(path.as_bstr(), *t)?;
+    }
+
+    let State {
+        mut result,
+        mut conflicts,
+        ..
+    } = state;
+    move_files_out_of_the_way_of_directories(&mut result, &mut conflicts, &ours_files, options);
+
+    let mut index = gix_index::State::new(repo.object_hash());
+    for (path, (mode, id)) in &result {
+        index.dangerously_push_entry(
+            Default::default(),
+            *id,
+            entry::Flags::empty(),
+            index_mode(*mode),
+            path.as_ref(),
+        );
+    }
+    index.sort_entries();
+    let tree = index.write_tree(|tree| repo.write_object(tree).map(|id| id.detach()))?;
+    Ok((tree, conflicts))
+}
+
+struct State<'a> {
+    repo: &'a Repository,
+    options: &'a Options,
+    result: Files,
+    conflicts: Vec<Conflict>,
+}
+
+impl State<'_> {
+    /// Place `file` at `path` in the result, and merge it as addition on both sides if there is a different file already.
+    fn insert(&mut self, path: &BStr, file: (EntryMode, ObjectId)) -> Result<(), Error> {
+        match self.result.get(path).copied() {
+            Some(existing) if existing != file => self.insert_added(path, existing, file),
+            _ => {
+                self.result.insert(path.to_owned(), file);
+                Ok(())
+            }
+        }
+    }
+
+    /// Merge two files that were added at `path` on both sides.
+    fn insert_added(
+        &mut self,
+        path: &BStr,
+        ours: (EntryMode, ObjectId),
+        theirs: (EntryMode, ObjectId),
+    ) -> Result<(), Error> {
+        let (merged, kind) = self.merge_entries(path, None, ours, theirs)?;
+        self.result.insert(path.to_owned(), merged);
+        if kind.is_some() {
+            self.conflicts.push(Conflict {
+                kind: ConflictKind::AddAdd,
+                location: Some(path.to_owned()),
+                base: None,
+                ours: Some(entry(path, &ours)),
+                theirs: Some(entry(path, &theirs)),
+            });
+        }
+        Ok(())
+    }
+
+    /// Merge the mode and content of `ours` and `theirs` at `path` given their common `base`, which is an empty file if `None`.
+    fn merge_entries(
+        &mut self,
+        path: &BStr,
+        base: Option<(EntryMode, ObjectId)>,
+        ours: (EntryMode, ObjectId),
+        theirs: (EntryMode, ObjectId),
+    ) -> Result<((EntryMode, ObjectId), Option<ConflictKind>), Error> {
+        if ours == theirs || base == Some(theirs) {
+            return Ok((ours, None));
+        }
+        if base == Some(ours) {
+            return Ok((theirs, None));
+        }
+        let base_mode = base.map(|b| b.0);
+        let mode = if Some(ours.0) == base_mode || ours.0 == theirs.0 {
+            theirs.0
+        } else if Some(theirs.0) == base_mode {
+            ours.0
+        } else {
+            return Ok((ours, Some(ConflictKind::Mode)));
+        };
+        let base_id = base.map(|b| b.1);
+        if Some(ours.1) == base_id || ours.1 == theirs.1 {
+            return Ok(((mode, theirs.1), None));
+        }
+        if Some(theirs.1) == base_id {
+            return Ok(((mode, ours.1), None));
+        }
+        let is_blob = |mode: EntryMode| matches!(mode, EntryMode::Blob | EntryMode::BlobExecutable);
+        if !(is_blob(ours.0) && is_blob(theirs.0) && base_mode.map_or(true, is_blob)) {
+            return Ok((ours, Some(ConflictKind::Content)));
+        }
+
+        let base_data = match base {
+            Some(base) => self.repo.find_object(base.1)?.detach().data,
+            None => Vec::new(),
+        };
+        let ours_data = self.repo.find_object(ours.1)?.detach().data;
+        let theirs_data = self.repo.find_object(theirs.1)?.detach().data;
+        let mut options = blob::Options {
+            ours_label: Some(label(&self.options.blob.ours_label, "ours").into()),
+            theirs_label: Some(label(&self.options.blob.theirs_label, "theirs").into()),
+            ..self.options.blob.clone()
+        };
+        if let Some(attributes) = &self.options.attributes {
+            options.driver = attributes.merge_driver(path);
+            options.marker_size = attributes.conflict_marker_size(path).unwrap_or(options.marker_size);
+        }
+        let merged = blob::merge(&base_data, &ours_data, &theirs_data, &options);
+        let id = if merged.data == ours_data {
+            ours.1
+        } else {
+            self.repo.write_blob(&merged.data)?.detach()
+        };
+        Ok(((mode, id), (merged.conflicts != 0).then_some(ConflictKind::Content)))
+    }
+}
+
+/// Move files that are in the way of directories to `<path>~<label>`, with the label of the side the file is from.
+fn move_files_out_of_the_way_of_directories(
+    result: &mut Files,
+    conflicts: &mut Vec<Conflict>,
+    ours_files: &Files,
+    options: &Options,
+) {
+    let in_the_way: Vec<BString> = result
+        .keys()
+        .filter(|path| {
+            let mut prefix = (*path).clone();
+            prefix.push_byte(b'/');
+            result
+                .range(prefix.clone()..)
+                .next()
+                .map_or(false, |(next, _)| next.starts_with(&prefix))
+        })
+        .cloned()
+        .collect();
+    for path in in_the_way {
+        let file = result.remove(&path).expect("present");
+        let is_ours = ours_files.get(&path) == Some(&file);
+        let side_label = if is_ours {
+            label(&options.blob.ours_label, "ours")
+        } else {
+            label(&options.blob.theirs_label, "theirs")
+        };
+        let mut location = path.clone();
+        location.push_str(format!("~{side_label}"));
+        while result.contains_key(&location) {
+            location.push_byte(b'_');
+        }
+        result.insert(location.clone(), file);
+        let side = Some(entry(path.as_ref(), &file));
+        conflicts.push(Conflict {
+            kind: ConflictKind::DirectoryFile,
+            location: Some(location),
+            base: None,
+            ours: if is_ours { side.clone() } else { None },
+            theirs: if is_ours { None } else { side },
+        });
+    }
+}
+
+fn label<'a>(label: &'a Option<String>, default: &'a str) -> &'a str {
+    label.as_deref().unwrap_or(default)
+}
+
+fn entry(location: &BStr, (mode, id): &(EntryMode, ObjectId)) -> Entry {
+    Entry {
+        location: location.to_owned(),
+        mode: *mode,
+        id: *id,
+    }
+}
+
+/// Return all files in the tree with id `tree`, by path.
+fn files(repo: &Repository, tree: ObjectId) -> Result<Files, Error> {
+    let state = gix_index::State::from_tree(&tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    Ok(state
+        .entries()
+        .iter()
+        .map(|e| (e.path(&state).to_owned(), (entry_mode(e.mode), e.id)))
+        .collect())
+}
+
+/// Return the destinations of all files in `base` that were renamed in `other`, by their path in `base`.
+fn renames(
+    repo: &Repository,
+    base: ObjectId,
+    other: ObjectId,
+    rewrites: Option<Rewrites>,
+) -> Result<HashMap<BString, BString>, Error> {
+    let mut renames = HashMap::new();
+    let rewrites = match rewrites {
+        Some(rewrites) => rewrites,
+        None => return Ok(renames),
+    };
+    let base = repo.find_object(base)?.peel_to_tree()?;
+    let other = repo.find_object(other)?.peel_to_tree()?;
+    base.changes()
+        .map_err(Box::new)?
+        .track_path()
+        .track_rewrites(Some(Rewrites {
+            copies: None,
+            ..rewrites
+        }))
+        .for_each_to_obtain_tree(&other, |change| {
+            if let Event::Rewrite {
+                source_location,
+                copy: false,
+                entry_mode,
+                ..
+            } = change.event
+            {
+                if entry_mode != EntryMode::Tree {
+                    renames.insert(source_location.to_owned(), change.location.to_owned());
+                }
+            }
+            Ok::<_, std::convert::Infallible>(Default::default())
+        })?;
+    Ok(renames)
+}
+
+fn entry_mode(mode: entry::Mode) -> EntryMode {
+    match mode {
+        entry::Mode::DIR => EntryMode::Tree,
+        entry::Mode::FILE_EXECUTABLE => EntryMode::BlobExecutable,
+        entry::Mode::SYMLINK => EntryMode::Link,
+        entry::Mode::COMMIT => EntryMode::Commit,
+        _ => EntryMode::Blob,
+    }
+}
+
+fn index_mode(mode: EntryMode) -> entry::Mode {
+    match mode {
+        EntryMode::Tree => entry::Mode::DIR,
+        EntryMode::BlobExecutable => entry::Mode::FILE_EXECUTABLE,
+        EntryMode::Link => entry::Mode::SYMLINK,
+        EntryMode::Commit => entry::Mode::COMMIT,
+        EntryMode::Blob => entry::Mode::FILE,
+    }
+}
@@ -147,6 +147,21 @@ impl<'repo> Commit<'repo> {
             max_candidates: 10,
         }
     }
+
+    /// Verify the signature of this commit with a [verifier][crate::signing::verify::Verifier::from_repo()] configured like
+    /// `git verify-commit` would be, or return `None` if it isn't signed.
+    ///
+    /// Use [`signing::split_signed_commit()`][crate::signing::split_signed_commit()] with a reused
+    /// [`Verifier`][crate::signing::verify::Verifier] to verify many commits.
+    pub fn verify_signature(&self) -> Result<Option<crate::signing::verify::Outcome>, crate::signing::verify::Error> {
+        let (payload, signature) = match crate::signing::split_signed_commit(&self.data, self.repo.object_hash()) {
+            Some(signed) => signed,
+            None => return Ok(None),
+        };
+        crate::signing::verify::Verifier::from_repo(self.repo)?
+            .verify(&payload, &signature)
+            .map(Some)
+    }
 }
 
 impl<'r> std::fmt::Debug for Commit<'r> {
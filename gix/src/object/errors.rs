@@ -30,5 +30,14 @@ pub mod find {
 ///
 pub mod write {
     /// An error to indicate writing to the loose object store failed.
-    pub type Error = gix_odb::store::write::Error;
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Odb(#[from] gix_odb::store::write::Error),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Could not record the id of the written object in the compatibility object format")]
+        Compat(#[from] crate::compat::Error),
+    }
 }
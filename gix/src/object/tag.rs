@@ -12,4 +12,16 @@ impl<'repo> Tag<'repo> {
     pub fn tagger(&self) -> Result<Option<gix_actor::SignatureRef<'_>>, gix_object::decode::Error> {
         gix_object::TagRefIter::from_bytes(&self.data).tagger()
     }
+
+    /// Verify the signature of this tag with a [verifier][crate::signing::verify::Verifier::from_repo()] configured like
+    /// `git verify-tag` would be, or return `None` if it isn't signed.
+    pub fn verify_signature(&self) -> Result<Option<crate::signing::verify::Outcome>, crate::signing::verify::Error> {
+        let (payload, signature) = match crate::signing::split_signed_tag(&self.data) {
+            Some(signed) => signed,
+            None => return Ok(None),
+        };
+        crate::signing::verify::Verifier::from_repo(self.repo)?
+            .verify(payload, signature)
+            .map(Some)
+    }
 }
@@ -31,6 +31,9 @@ pub struct Change<'a, 'old, 'new> {
 ///
 pub mod change;
 
+///
+pub mod patch;
+
 /// Diffing
 impl<'repo> Tree<'repo> {
     /// Return a platform to see the changes needed to create other trees, for instance.
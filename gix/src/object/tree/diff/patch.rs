@@ -0,0 +1,289 @@
+use std::{cell::RefCell, io};
+
+pub use gix_diff::patch::{stat, FileStat};
+use gix_diff::{blob::unified_diff, patch::File};
+use gix_odb::FindExt;
+
+use crate::{
+    bstr::{BStr, ByteSlice},
+    object::tree::diff::{change::Event, Change},
+    Id, Repository,
+};
+
+/// The error returned by [`Change::write_patch()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not find the previous blob or the new blob to diff against")]
+    FindExisting(#[from] crate::object::find::existing::Error),
+    #[error("Could not obtain diff algorithm from configuration")]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+    #[error("Could not write the patch")]
+    Io(#[from] io::Error),
+}
+
+/// Options for use in [`Change::write_patch()`].
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Options for rendering the hunks of changed lines, like the amount of lines of context.
+    pub hunks: unified_diff::Options,
+    /// The attributes to consult to learn if a file is binary, or `None` to only detect binary files by their content.
+    pub attributes: Option<Attributes>,
+}
+
+/// Attributes from `.gitattributes` files in the worktree, or in the index if they are only present there, and from
+/// `$GIT_DIR/info/attributes`, used to learn how files should be diffed and merged.
+#[derive(Clone, Default)]
+pub struct Attributes {
+    /// The attributes of `$GIT_DIR/info/attributes`, used if there is no worktree.
+    bare: gix_worktree::fs::cache::state::Attributes,
+    /// The attribute stack of the worktree along with the objects to read attribute files from the index, if there is a worktree.
+    worktree: Option<(RefCell<gix_worktree::fs::Cache>, crate::OdbHandle)>,
+}
+
+impl std::fmt::Debug for Attributes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Attributes")
+            .field("has_worktree", &self.worktree.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+///
+pub mod attributes {
+    /// The error returned by [`Attributes::from_repo()`][super::Attributes::from_repo()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not open the index to read attribute files from it")]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        WorktreeAttributes(#[from] crate::worktree::attributes::Error),
+        #[error("Could not read attributes")]
+        Io(#[from] std::io::Error),
+    }
+}
+
+impl Attributes {
+    /// Load the attributes of `repo`, ignoring attribute files that don't exist.
+    ///
+    /// `.gitattributes` files in the worktree are read lazily as paths in their directories are queried, and those in the index
+    /// are used if they aren't present in the worktree. In bare repositories, only `$GIT_DIR/info/attributes` is used.
+    pub fn from_repo(repo: &Repository) -> Result<Self, attributes::Error> {
+        let worktree = match repo.worktree() {
+            Some(worktree) => {
+                let index = match repo.index() {
+                    Ok(index) => Some(index),
+                    Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                        if err.kind() == io::ErrorKind::NotFound =>
+                    {
+                        None
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+                let cache = match &index {
+                    Some(index) => worktree.attributes(index)?,
+                    None => worktree.attributes(&gix_index::State::new(repo.object_hash()))?,
+                };
+                return Ok(Attributes {
+                    bare: Default::default(),
+                    worktree: Some((RefCell::new(cache), repo.objects.clone())),
+                });
+            }
+            None => None,
+        };
+        let mut info = gix_attributes::MatchGroup::default();
+        info.add_patterns_file(
+            repo.common_dir().join("info").join("attributes"),
+            true,
+            None,
+            &mut Vec::new(),
+        )?;
+        Ok(Attributes {
+            bare: gix_worktree::fs::cache::state::Attributes::default().with_info(info),
+            worktree,
+        })
+    }
+
+    /// Return `Some(true)` if the file at `path` is binary as the `diff` attribute is unset, possibly through the `binary` macro,
+    /// `Some(false)` if the `diff` attribute is set, or `None` if it is unspecified.
+    pub fn is_binary(&self, path: &BStr) -> Option<bool> {
+        match self.state(path, "diff")? {
+            gix_attributes::State::Set | gix_attributes::State::Value(_) => Some(false),
+            gix_attributes::State::Unset => Some(true),
+            gix_attributes::State::Unspecified => None,
+        }
+    }
+
+    /// Return the built-in merge driver for the file at `path` as selected by its `merge` attribute, which is unset by the
+    /// `binary` macro.
+    ///
+    /// Note that drivers configured with `merge.<name>.driver` are not supported and fall back to the
+    /// [text driver][gix_diff::blob::merge::Driver::Text], just like they do in `git` if they are not configured.
+    pub fn merge_driver(&self, path: &BStr) -> gix_diff::blob::merge::Driver {
+        use gix_diff::blob::merge::Driver;
+        match self.state(path, "merge") {
+            Some(gix_attributes::State::Unset) => Driver::Binary,
+            Some(gix_attributes::State::Value(name)) => {
+                name.to_str().ok().and_then(Driver::from_name).unwrap_or(Driver::Text)
+            }
+            Some(gix_attributes::State::Set | gix_attributes::State::Unspecified) | None => Driver::Text,
+        }
+    }
+
+    /// Return the size of conflict markers for the file at `path` as set by its `conflict-marker-size` attribute, or `None`
+    /// if it is not set to a valid number.
+    pub fn conflict_marker_size(&self, path: &BStr) -> Option<usize> {
+        match self.state(path, "conflict-marker-size")? {
+            gix_attributes::State::Value(size) => size.to_str().ok()?.parse().ok().filter(|size| *size > 0),
+            _ => None,
+        }
+    }
+
+    /// Return the state of the attribute `name` for `path`, or `None` if it isn't mentioned at all or if attribute files
+    /// couldn't be read.
+    fn state(&self, path: &BStr, name: &str) -> Option<gix_attributes::State> {
+        match &self.worktree {
+            Some((cache, objects)) => {
+                let mut cache = cache.borrow_mut();
+                let platform = cache
+                    .at_entry(path, Some(false), |id, buf| objects.find_blob(id, buf))
+                    .ok()?;
+                platform.attribute_state(name)
+            }
+            None => self.bare.state(path, name, gix_glob::pattern::Case::Sensitive),
+        }
+    }
+}
+
+/// Rendering
+impl<'a, 'old, 'new> Change<'a, 'old, 'new> {
+    /// Write this change as patch to `out`, with `diff --git` header and hunks of changed lines, like `git diff` does, using `options`.
+    ///
+    /// Return statistics about the change for use in summaries like [`stat::write_stat()`], or `None` if this change
+    /// is for a tree which isn't rendered at all.
+    /// Changes of the kind of an entry, like a file turning into a symbolic link, are written as deletion followed by an addition.
+    ///
+    /// Note that the [location][Change::location] should be [tracked as path][super::Platform::track_path()] for the
+    /// patch to be meaningful.
+    pub fn write_patch(&self, options: &Options, mut out: impl io::Write) -> Result<Option<FileStat>, Error> {
+        let (old, new) = match self.event {
+            Event::Addition { entry_mode, id } => (None, Some((self.location, entry_mode, id))),
+            Event::Deletion { entry_mode, id } => (Some((self.location, entry_mode, id)), None),
+            Event::Modification {
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } => (
+                Some((self.location, previous_entry_mode, previous_id)),
+                Some((self.location, entry_mode, id)),
+            ),
+            Event::Rewrite {
+                source_location,
+                source_entry_mode,
+                source_id,
+                entry_mode,
+                id,
+                ..
+            } => (
+                Some((source_location, source_entry_mode, source_id)),
+                Some((self.location, entry_mode, id)),
+            ),
+        };
+        if old.or(new).map_or(true, |(_, mode, _)| mode.is_tree()) {
+            return Ok(None);
+        }
+
+        let repo = old.or(new).expect("at least one side").2.repo;
+        let diff_options = gix_diff::patch::Options {
+            algorithm: repo.config.diff_algorithm()?,
+            hunks: options.hunks,
+            abbrev: old
+                .into_iter()
+                .chain(new)
+                .map(|(_, _, id)| {
+                    id.shorten()
+                        .map_or(repo.config.hex_len.unwrap_or(7), |prefix| prefix.hex_len())
+                })
+                .max()
+                .unwrap_or(7),
+            ..Default::default()
+        };
+        let old_data = old.map(|(_, mode, id)| data(mode, id)).transpose()?;
+        let new_data = new.map(|(_, mode, id)| data(mode, id)).transpose()?;
+        let old_id = old.map(|(_, _, id)| id.detach());
+        let new_id = new.map(|(_, _, id)| id.detach());
+        let old_file = old
+            .zip(old_data.as_deref())
+            .zip(old_id.as_ref())
+            .map(|(((location, mode, _), data), id)| File {
+                location,
+                id,
+                mode,
+                data,
+            });
+        let new_file = new
+            .zip(new_data.as_deref())
+            .zip(new_id.as_ref())
+            .map(|(((location, mode, _), data), id)| File {
+                location,
+                id,
+                mode,
+                data,
+            });
+
+        let is_binary = options
+            .attributes
+            .as_ref()
+            .and_then(|attrs| attrs.is_binary(new_file.or(old_file).expect("at least one side").location));
+        let changes = match (old_file, new_file) {
+            (None, Some(new)) => vec![gix_diff::patch::Change::Addition { new }],
+            (Some(old), None) => vec![gix_diff::patch::Change::Deletion { old }],
+            (Some(old), Some(new)) if kind_of(old.mode) != kind_of(new.mode) => vec![
+                gix_diff::patch::Change::Deletion { old },
+                gix_diff::patch::Change::Addition { new },
+            ],
+            (Some(old), Some(new)) => vec![match self.event {
+                Event::Rewrite { copy, .. } => gix_diff::patch::Change::Rewrite { old, new, copy },
+                _ => gix_diff::patch::Change::Modification { old, new },
+            }],
+            (None, None) => unreachable!("at least one side is set"),
+        };
+
+        let mut stat: Option<FileStat> = None;
+        for change in changes {
+            let change_stat = gix_diff::patch::write_to(&change, is_binary, &diff_options, &mut out)?;
+            stat = Some(match stat {
+                None => change_stat,
+                Some(mut stat) => {
+                    stat.insertions += change_stat.insertions;
+                    stat.removals += change_stat.removals;
+                    stat.binary = stat.binary.or(change_stat.binary);
+                    stat
+                }
+            });
+        }
+        Ok(stat)
+    }
+}
+
+/// The data to diff for the entry with `mode` and `id`, which is the commit itself for submodules, just like `git` does it.
+fn data(mode: gix_object::tree::EntryMode, id: Id<'_>) -> Result<Vec<u8>, crate::object::find::existing::Error> {
+    Ok(if mode == gix_object::tree::EntryMode::Commit {
+        format!("Subproject commit {}\n", id.detach()).into_bytes()
+    } else {
+        id.object()?.detach().data
+    })
+}
+
+/// Entries of a different kind can't be diffed with each other.
+fn kind_of(mode: gix_object::tree::EntryMode) -> u8 {
+    use gix_object::tree::EntryMode::*;
+    match mode {
+        Blob | BlobExecutable => 0,
+        Link => 1,
+        Commit => 2,
+        Tree => 3,
+    }
+}
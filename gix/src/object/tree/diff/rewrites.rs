@@ -1,5 +1,8 @@
 use crate::{
-    config::{cache::util::ApplyLeniency, tree::Diff},
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{Diff, Merge},
+    },
     diff::rename::Tracking,
     object::tree::diff::Rewrites,
 };
@@ -105,4 +108,42 @@ impl Rewrites {
         }
         .into())
     }
+
+    /// Create an instance for use during merges by reading `merge.renames` and `merge.renameLimit` from `config`,
+    /// falling back to `diff.renames` and `diff.renameLimit` respectively, while being `lenient` or not.
+    /// Returns `Ok(None)` if rename tracking is disabled.
+    ///
+    /// Like git, renames are tracked if nothing is configured, copies are never tracked, and the limit defaults to 7000.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn try_from_merge_config(
+        config: &gix_config::File<'static>,
+        lenient: bool,
+    ) -> Result<Option<Self>, Error> {
+        let renames = match config.boolean_by_key("merge.renames") {
+            Some(value) => Some(Merge::RENAMES.try_into_renames(value, || config.string_by_key("merge.renames"))),
+            None => config
+                .boolean_by_key("diff.renames")
+                .map(|value| Diff::RENAMES.try_into_renames(value, || config.string_by_key("diff.renames"))),
+        }
+        .transpose()
+        .with_leniency(lenient)?;
+        if renames == Some(Tracking::Disabled) {
+            return Ok(None);
+        }
+
+        let limit = match config.integer_by_key("merge.renameLimit") {
+            Some(value) => Some(Merge::RENAME_LIMIT.try_into_usize(value)),
+            None => config
+                .integer_by_key("diff.renameLimit")
+                .map(|value| Diff::RENAME_LIMIT.try_into_usize(value)),
+        }
+        .transpose()
+        .with_leniency(lenient)?;
+        Ok(Rewrites {
+            copies: None,
+            limit: limit.unwrap_or(7000),
+            ..Self::default()
+        }
+        .into())
+    }
 }
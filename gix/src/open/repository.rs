@@ -8,7 +8,7 @@ use crate::{
     config,
     config::{
         cache::{interpolate_context, util::ApplyLeniency},
-        tree::{gitoxide, Core, Key, Safe},
+        tree::{extensions::RefStorageFormat, gitoxide, Core, Key, Safe},
     },
     permission, Permissions, ThreadSafeRepository,
 };
@@ -146,7 +146,7 @@ impl ThreadSafeRepository {
             lenient_config,
             bail_if_untrusted,
             open_path_as_is: _,
-            permissions: Permissions { ref env, config },
+            permissions: Permissions { ref env, config, .. },
             ref api_config_overrides,
             ref cli_config_overrides,
             ref current_dir,
@@ -170,13 +170,18 @@ impl ThreadSafeRepository {
             lossy_config,
             lenient_config,
         )?;
-        let mut refs = {
-            let reflog = repo_config.reflog.unwrap_or(gix_ref::store::WriteReflog::Disable);
-            let object_hash = repo_config.object_hash;
-            match &common_dir {
-                Some(common_dir) => crate::RefStore::for_linked_worktree(&git_dir, common_dir, reflog, object_hash),
-                None => crate::RefStore::at(&git_dir, reflog, object_hash),
+        let reflog = repo_config.reflog.unwrap_or(gix_ref::store::WriteReflog::Disable);
+        let object_hash = repo_config.object_hash;
+        let mut refs: crate::RefStore = match (repo_config.ref_storage, &common_dir) {
+            // TODO: support per-worktree references of linked worktrees, which are kept in their own stack of reftables.
+            (RefStorageFormat::Reftable, Some(common_dir)) => {
+                gix_ref::reftable::Store::for_linked_worktree(&git_dir, common_dir, reflog, object_hash).into()
             }
+            (RefStorageFormat::Reftable, None) => gix_ref::reftable::Store::at(&git_dir, reflog, object_hash).into(),
+            (RefStorageFormat::Files, Some(common_dir)) => {
+                gix_ref::file::Store::for_linked_worktree(&git_dir, common_dir, reflog, object_hash).into()
+            }
+            (RefStorageFormat::Files, None) => gix_ref::file::Store::at(&git_dir, reflog, object_hash).into(),
         };
         let head = refs.find("HEAD").ok();
         let git_install_dir = crate::path::install_dir().ok();
@@ -230,20 +235,26 @@ impl ThreadSafeRepository {
             None => {}
         }
 
-        refs.write_reflog = config::cache::util::reflog_or_default(config.reflog, worktree_dir.is_some());
+        refs.set_write_reflog(config::cache::util::reflog_or_default(
+            config.reflog,
+            worktree_dir.is_some(),
+        ));
         let replacements = replacement_objects_refs_prefix(&config.resolved, lenient_config, filter_config_section)?
             .and_then(|prefix| {
-                let platform = refs.iter().ok()?;
-                let iter = platform.prefixed(&prefix).ok()?;
-                let prefix = prefix.to_str()?;
-                let replacements = iter
+                let prefix_str = prefix.to_str()?;
+                let to_replacement = |r: gix_ref::Reference| {
+                    let target = r.target.try_id()?.to_owned();
+                    let source =
+                        gix_hash::ObjectId::from_hex(r.name.as_bstr().strip_prefix(prefix_str.as_bytes())?).ok()?;
+                    Some((source, target))
+                };
+                let replacements = refs
+                    .iter()
+                    .ok()?
+                    .prefixed(&prefix)
+                    .ok()?
                     .filter_map(Result::ok)
-                    .filter_map(|r: gix_ref::Reference| {
-                        let target = r.target.try_id()?.to_owned();
-                        let source =
-                            gix_hash::ObjectId::from_hex(r.name.as_bstr().strip_prefix(prefix.as_bytes())?).ok()?;
-                        Some((source, target))
-                    })
+                    .filter_map(to_replacement)
                     .collect::<Vec<_>>();
                 Some(replacements)
             })
@@ -12,11 +12,17 @@ pub mod edit {
         #[error(transparent)]
         FileTransactionCommit(#[from] gix_ref::file::transaction::commit::Error),
         #[error(transparent)]
+        ReftableTransactionPrepare(#[from] gix_ref::reftable::transaction::prepare::Error),
+        #[error(transparent)]
+        ReftableTransactionCommit(#[from] gix_ref::reftable::transaction::commit::Error),
+        #[error(transparent)]
         NameValidation(#[from] gix_validate::reference::name::Error),
         #[error("Could not interpret core.filesRefLockTimeout or core.packedRefsTimeout, it must be the number in milliseconds to wait for locks or negative to wait forever")]
         LockTimeoutConfiguration(#[from] config::lock_timeout::Error),
         #[error(transparent)]
         ParseCommitterTime(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        Hook(#[from] crate::hook::Error),
     }
 }
 
@@ -84,6 +90,8 @@ pub mod find {
         #[error(transparent)]
         Find(#[from] gix_ref::file::find::Error),
         #[error(transparent)]
+        FindInStore(#[from] gix_ref::store::find::Error),
+        #[error(transparent)]
         PackedRefsOpen(#[from] gix_ref::packed::buffer::open::Error),
     }
 }
@@ -1,30 +1,39 @@
 //!
 use std::path::Path;
 
-use gix_odb::pack::Find;
-use gix_ref::file::ReferenceExt;
+use gix_ref::store::iter;
 
 /// A platform to create iterators over references.
 #[must_use = "Iterators should be obtained from this iterator platform"]
 pub struct Platform<'r> {
-    pub(crate) platform: gix_ref::file::iter::Platform<'r>,
+    pub(crate) platform: iter::Platform<'r>,
     pub(crate) repo: &'r crate::Repository,
 }
 
 /// An iterator over references, with or without filter.
 pub struct Iter<'r> {
-    inner: gix_ref::file::iter::LooseThenPacked<'r, 'r>,
+    inner: iter::Iter<'r, 'r>,
     peel: bool,
     repo: &'r crate::Repository,
 }
 
 impl<'r> Iter<'r> {
-    fn new(repo: &'r crate::Repository, platform: gix_ref::file::iter::LooseThenPacked<'r, 'r>) -> Self {
-        Iter {
-            inner: platform,
+    fn new(
+        repo: &'r crate::Repository,
+        platform: &'r iter::Platform<'r>,
+        prefix: Option<&Path>,
+    ) -> Result<Self, init::Error> {
+        let inner = match (platform, prefix) {
+            (iter::Platform::Loose(platform), None) => iter::Iter::Loose(platform.all()?),
+            (iter::Platform::Loose(platform), Some(prefix)) => iter::Iter::Loose(platform.prefixed(prefix)?),
+            (iter::Platform::Reftable(platform), None) => iter::Iter::Reftable(platform.all()),
+            (iter::Platform::Reftable(platform), Some(prefix)) => iter::Iter::Reftable(platform.prefixed(prefix)),
+        };
+        Ok(Iter {
+            inner,
             peel: false,
             repo,
-        }
+        })
     }
 }
 
@@ -34,7 +43,7 @@ impl<'r> Platform<'r> {
     /// Even broken or otherwise unparsable or inaccessible references are returned and have to be handled by the caller on a
     /// case by case basis.
     pub fn all(&self) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.all()?))
+        Iter::new(self.repo, &self.platform, None)
     }
 
     /// Return an iterator over all references that match the given `prefix`.
@@ -43,7 +52,7 @@ impl<'r> Platform<'r> {
     // TODO: Create a custom `Path` type that enforces the requirements of git naturally, this type is surprising possibly on windows
     //       and when not using a trailing '/' to signal directories.
     pub fn prefixed(&self, prefix: impl AsRef<Path>) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.prefixed(prefix)?))
+        Iter::new(self.repo, &self.platform, Some(prefix.as_ref()))
     }
 
     // TODO: tests
@@ -51,7 +60,7 @@ impl<'r> Platform<'r> {
     ///
     /// They are all prefixed with `refs/tags`.
     pub fn tags(&self) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.prefixed("refs/tags/")?))
+        Iter::new(self.repo, &self.platform, Some(Path::new("refs/tags/")))
     }
 
     // TODO: tests
@@ -59,7 +68,7 @@ impl<'r> Platform<'r> {
     ///
     /// They are all prefixed with `refs/heads`.
     pub fn local_branches(&self) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.prefixed("refs/heads/")?))
+        Iter::new(self.repo, &self.platform, Some(Path::new("refs/heads/")))
     }
 
     // TODO: tests
@@ -67,7 +76,7 @@ impl<'r> Platform<'r> {
     ///
     /// They are all prefixed with `refs/remotes`.
     pub fn remote_branches(&self) -> Result<Iter<'_>, init::Error> {
-        Ok(Iter::new(self.repo, self.platform.prefixed("refs/remotes/")?))
+        Iter::new(self.repo, &self.platform, Some(Path::new("refs/remotes/")))
     }
 }
 
@@ -94,15 +103,10 @@ impl<'r> Iterator for Iter<'r> {
             res.map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
                 .and_then(|mut r| {
                     if self.peel {
-                        let handle = &self.repo;
-                        r.peel_to_id_in_place(&handle.refs, |oid, buf| {
-                            handle
-                                .objects
-                                .try_find(oid, buf)
-                                .map(|po| po.map(|(o, _l)| (o.kind, o.data)))
-                        })
-                        .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
-                        .map(|_| r)
+                        self.repo
+                            .peel_reference_in_place(&mut r)
+                            .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync + 'static>)
+                            .map(|_| r)
                     } else {
                         Ok(r)
                     }
@@ -123,5 +127,15 @@ pub mod init {
     }
 }
 
-/// The error returned by [references()][crate::Repository::references()].
-pub type Error = gix_ref::packed::buffer::open::Error;
+mod error {
+    /// The error returned by [references()][crate::Repository::references()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        PackedRefsOpen(#[from] gix_ref::packed::buffer::open::Error),
+        #[error(transparent)]
+        Store(#[from] gix_ref::store::iter::Error),
+    }
+}
+pub use error::Error;
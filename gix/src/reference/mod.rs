@@ -1,8 +1,5 @@
 //!
 
-use gix_odb::pack::Find;
-use gix_ref::file::ReferenceExt;
-
 use crate::{Id, Reference};
 
 pub mod iter;
@@ -69,11 +66,7 @@ impl<'repo> Reference<'repo> {
     /// This is useful to learn where this reference is ultimately pointing to.
     pub fn peel_to_id_in_place(&mut self) -> Result<Id<'repo>, peel::Error> {
         let repo = &self.repo;
-        let oid = self.inner.peel_to_id_in_place(&repo.refs, |oid, buf| {
-            repo.objects
-                .try_find(oid, buf)
-                .map(|po| po.map(|(o, _l)| (o.kind, o.data)))
-        })?;
+        let oid = repo.peel_reference_in_place(&mut self.inner)?;
         Ok(Id::from_id(oid, repo))
     }
 
@@ -15,6 +15,8 @@ pub enum Error {
         local: gix_hash::Kind,
         remote: gix_hash::Kind,
     },
+    #[error("Cannot fetch from a remote that uses {remote} as objects aren't translated from the compatibility object format of the local repository during fetches")]
+    CompatObjectHash { remote: gix_hash::Kind },
     #[error(transparent)]
     Negotiate(#[from] super::negotiate::Error),
     #[error(transparent)]
@@ -143,13 +143,19 @@ where
             return Err(prepare::Error::MissingRefSpecs);
         }
         let ref_map = self.ref_map_inner(options).await?;
-        Ok(Prepare {
+        Ok(self.prepare_fetch_with_ref_map(ref_map))
+    }
+
+    /// Like [`prepare_fetch()`][Self::prepare_fetch()], but use the `ref_map` of a handshake that was already performed with our
+    /// `transport` instead of performing another one.
+    pub(crate) fn prepare_fetch_with_ref_map(self, ref_map: RefMap) -> Prepare<'remote, 'repo, T, P> {
+        Prepare {
             con: Some(self),
             ref_map,
             dry_run: DryRun::No,
             reflog_message: None,
             write_packed_refs: WritePackedRefs::Never,
-        })
+        }
     }
 }
 
@@ -161,6 +167,18 @@ where
     pub fn ref_map(&self) -> &RefMap {
         &self.ref_map
     }
+
+    /// Detach our connection from its remote and return its transport and progress along with the ref-map of the handshake,
+    /// to continue the interaction with [`prepare_fetch_with_ref_map()`][Connection::prepare_fetch_with_ref_map()] of
+    /// another connection.
+    #[cfg(feature = "blocking-network-client")]
+    pub(crate) fn into_transport_and_ref_map(mut self) -> (T, P, RefMap) {
+        let con = self
+            .con
+            .take()
+            .expect("only taken when receiving a pack, which consumes us");
+        (con.transport, con.progress, std::mem::take(&mut self.ref_map))
+    }
 }
 
 mod config;
@@ -90,6 +90,11 @@ where
         let mut round = 1;
 
         if self.ref_map.object_hash != repo.object_hash() {
+            if repo.compat_object_hash() == Some(self.ref_map.object_hash) {
+                return Err(Error::CompatObjectHash {
+                    remote: self.ref_map.object_hash,
+                });
+            }
             return Err(Error::IncompatibleObjectHash {
                 local: repo.object_hash(),
                 remote: self.ref_map.object_hash,
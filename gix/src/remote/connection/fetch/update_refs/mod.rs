@@ -227,13 +227,12 @@ pub(crate) fn update(
 
     let edits = match dry_run {
         fetch::DryRun::No => {
-            let (file_lock_fail, packed_refs_lock_fail) = repo
+            let lock_fail = repo
                 .config
                 .lock_timeout()
                 .map_err(crate::reference::edit::Error::from)?;
-            repo.refs
-                .transaction()
-                .packed_refs(
+            repo.commit_transaction(
+                repo.ref_transaction(
                     match write_packed_refs {
                         fetch::WritePackedRefs::Only => {
                             gix_ref::file::transaction::PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(Box::new(|oid, buf| {
@@ -244,11 +243,10 @@ pub(crate) fn update(
                             }))},
                         fetch::WritePackedRefs::Never => gix_ref::file::transaction::PackedRefs::DeletionsOnly
                     }
-                )
-                .prepare(edits, file_lock_fail, packed_refs_lock_fail)
-                .map_err(crate::reference::edit::Error::from)?
-                .commit(repo.committer().transpose().map_err(|err| update::Error::EditReferences(crate::reference::edit::Error::ParseCommitterTime(err)))?)
-                .map_err(crate::reference::edit::Error::from)?
+                ),
+                edits,
+                lock_fail,
+            )?
         }
         fetch::DryRun::Yes => edits,
     };
@@ -245,7 +245,7 @@ where
     }
 }
 
-/// Assume sha1 if server says nothing, otherwise configure anything beyond sha1 in the local repo configuration
+/// Assume sha1 if server says nothing, otherwise use the object format it advertises.
 #[allow(clippy::result_large_err)]
 fn extract_object_format(
     _repo: &crate::Repository,
@@ -259,6 +259,7 @@ fn extract_object_format(
             })?;
             match object_format {
                 "sha1" => gix_hash::Kind::Sha1,
+                "sha256" => gix_hash::Kind::Sha256,
                 unknown => return Err(Error::UnknownObjectFormat { format: unknown.into() }),
             }
         } else {
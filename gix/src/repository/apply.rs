@@ -0,0 +1,257 @@
+use gix_diff::patch::parse::FilePatch;
+use gix_index::entry;
+use gix_object::tree::EntryMode;
+
+use crate::{
+    apply::{self, submodule_data, submodule_id, Changes, File},
+    bstr::{BStr, ByteSlice},
+    prelude::{FindExt, ObjectIdExt},
+    Id,
+};
+
+/// Patch application
+impl crate::Repository {
+    /// Apply all `patches` to the tree with id `tree` and write all changed blobs and trees to the object database,
+    /// returning the id of the resulting tree.
+    ///
+    /// Note that changes which result in conflicts with [`three_way`][apply::Options::three_way] merges enabled are an error
+    /// as trees can't represent them.
+    pub fn apply_to_tree(
+        &self,
+        tree: impl Into<gix_hash::ObjectId>,
+        patches: &[FilePatch],
+        options: &apply::Options,
+    ) -> Result<Id<'_>, apply::Error> {
+        let tree = tree.into();
+        let mut index = gix_index::State::from_tree(&tree, |oid, buf| self.objects.find_tree_iter(oid, buf).ok())?;
+        let outcome = self.apply_to_index(&mut index, patches, options)?;
+        if let Some(path) = outcome.conflicts.into_iter().next() {
+            return Err(apply::Error::Conflict { path });
+        }
+        let id = index.write_tree(|tree| self.write_object(tree).map(|id| id.detach()))?;
+        Ok(id.attach(self))
+    }
+
+    /// Apply all `patches` to the entries of `index`, writing new blobs to the object database, similar to `git apply --cached`.
+    ///
+    /// Files with conflicts after a [3-way merge][apply::Options::three_way] are stored as entries of stage 1 (base),
+    /// 2 (ours) and 3 (theirs).
+    /// Note that the working tree isn't touched and that no change is made to `index` if any patch doesn't apply.
+    pub fn apply_to_index(
+        &self,
+        index: &mut gix_index::State,
+        patches: &[FilePatch],
+        options: &apply::Options,
+    ) -> Result<apply::Outcome, apply::Error> {
+        let changes = Changes::apply(self, patches, options, |path| {
+            let entry = match index
+                .entry_by_path_and_stage(path, 0)
+                .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+            {
+                Some(entry) => entry,
+                None => return Ok(None),
+            };
+            let mode = entry_mode(entry.mode);
+            let data = if mode == EntryMode::Commit {
+                submodule_data(&entry.id)
+            } else {
+                self.find_object(entry.id)?.detach().data
+            };
+            Ok(Some(File { mode, data }))
+        })?;
+
+        let mut outcome = apply::Outcome::default();
+        let mut conflicts = Vec::new();
+        for (path, file) in &changes.files {
+            if let Some(conflict) = changes.conflicts.get(path) {
+                conflicts.push((
+                    path,
+                    [
+                        (conflict.base, conflict.ours.mode),
+                        (self.blob_id(path.as_ref(), &conflict.ours)?, conflict.ours.mode),
+                        (self.blob_id(path.as_ref(), &conflict.theirs)?, conflict.theirs.mode),
+                    ],
+                ));
+                outcome.conflicts.push(path.clone());
+                set_entry(index, path.as_ref(), None);
+            } else {
+                let file = match file {
+                    Some(file) => Some((self.blob_id(path.as_ref(), file)?, file.mode)),
+                    None => None,
+                };
+                set_entry(index, path.as_ref(), file);
+            }
+        }
+        for (path, stages) in conflicts {
+            for (stage, (id, mode)) in (1u32..).zip(stages) {
+                let flags = entry::Flags::from_bits_truncate(stage << 12);
+                index.dangerously_push_entry(Default::default(), id, flags, index_mode(mode), path.as_ref());
+            }
+        }
+        index.sort_entries();
+        Ok(outcome)
+    }
+
+    /// Apply all `patches` to the files in the working tree, similar to `git apply`.
+    ///
+    /// Files with conflicts after a [3-way merge][apply::Options::three_way] contain conflict markers.
+    /// Note that neither the index nor submodules are touched, and that no file is changed if any patch doesn't apply.
+    pub fn apply_to_worktree(
+        &self,
+        patches: &[FilePatch],
+        options: &apply::Options,
+    ) -> Result<apply::Outcome, apply::Error> {
+        let root = self.work_dir().ok_or(apply::Error::MissingWorkTree)?;
+        let io_err = |path: &BStr| {
+            let path = path.to_owned();
+            move |source| apply::Error::Io { path, source }
+        };
+        let changes = Changes::apply(self, patches, options, |path| {
+            let fs_path = root.join(gix_path::from_bstr(path));
+            let meta = match std::fs::symlink_metadata(&fs_path) {
+                Ok(meta) => meta,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(io_err(path)(err)),
+            };
+            let file = if meta.file_type().is_symlink() {
+                let target = std::fs::read_link(&fs_path).map_err(io_err(path))?;
+                File {
+                    mode: EntryMode::Link,
+                    data: gix_path::into_bstr(target).into_owned().into(),
+                }
+            } else if meta.is_file() {
+                File {
+                    mode: if is_executable(&meta) {
+                        EntryMode::BlobExecutable
+                    } else {
+                        EntryMode::Blob
+                    },
+                    data: std::fs::read(&fs_path).map_err(io_err(path))?,
+                }
+            } else {
+                return Ok(None);
+            };
+            Ok(Some(file))
+        })?;
+
+        for (path, file) in &changes.files {
+            let fs_path = root.join(gix_path::from_bstr(path.as_bstr()));
+            match std::fs::symlink_metadata(&fs_path) {
+                Ok(meta) if !meta.is_dir() => std::fs::remove_file(&fs_path).map_err(io_err(path.as_ref()))?,
+                _ => {}
+            }
+            let file = match file {
+                Some(file) if file.mode != EntryMode::Commit => file,
+                _ => continue,
+            };
+            if let Some(parent) = fs_path.parent() {
+                std::fs::create_dir_all(parent).map_err(io_err(path.as_ref()))?;
+            }
+            if file.mode == EntryMode::Link {
+                let target = gix_path::from_bstr(file.data.as_bstr());
+                create_symlink(&target, &fs_path).map_err(io_err(path.as_ref()))?;
+            } else {
+                std::fs::write(&fs_path, &file.data).map_err(io_err(path.as_ref()))?;
+                if file.mode == EntryMode::BlobExecutable {
+                    set_executable(&fs_path).map_err(io_err(path.as_ref()))?;
+                }
+            }
+        }
+        for (path, file) in &changes.files {
+            // Directories left empty by deletions and renames are removed, just like `git` does it.
+            if file.is_none() {
+                let mut dir = root.join(gix_path::from_bstr(path.as_bstr()));
+                while dir.pop() && dir != root && std::fs::remove_dir(&dir).is_ok() {}
+            }
+        }
+        Ok(apply::Outcome {
+            conflicts: changes.conflicts.into_keys().collect(),
+        })
+    }
+
+    fn blob_id(&self, path: &BStr, file: &File) -> Result<gix_hash::ObjectId, apply::Error> {
+        if file.mode == EntryMode::Commit {
+            return submodule_id(&file.data).ok_or_else(|| apply::Error::Submodule { path: path.to_owned() });
+        }
+        Ok(self.write_blob(&file.data)?.detach())
+    }
+}
+
+/// Set the entry at `path` to the blob `id` with `mode`, or remove it along with all conflicting stages.
+pub(super) fn set_entry(index: &mut gix_index::State, path: &BStr, file: Option<(gix_hash::ObjectId, EntryMode)>) {
+    index.invalidate_tree_for_path(path);
+    let mut is_set = false;
+    for (entry, entry_path) in index.entries_mut_with_paths() {
+        if entry_path != path {
+            continue;
+        }
+        match file {
+            Some((id, mode)) if entry.stage() == 0 => {
+                entry.id = id;
+                entry.mode = index_mode(mode);
+                entry.stat = Default::default();
+                entry.flags.remove(entry::Flags::REMOVE);
+                is_set = true;
+            }
+            _ => entry.flags.insert(entry::Flags::REMOVE),
+        }
+    }
+    if let Some((id, mode)) = file.filter(|_| !is_set) {
+        index.dangerously_push_entry(Default::default(), id, entry::Flags::empty(), index_mode(mode), path);
+    }
+}
+
+pub(super) fn entry_mode(mode: entry::Mode) -> EntryMode {
+    match mode {
+        entry::Mode::DIR => EntryMode::Tree,
+        entry::Mode::FILE_EXECUTABLE => EntryMode::BlobExecutable,
+        entry::Mode::SYMLINK => EntryMode::Link,
+        entry::Mode::COMMIT => EntryMode::Commit,
+        _ => EntryMode::Blob,
+    }
+}
+
+pub(super) fn index_mode(mode: EntryMode) -> entry::Mode {
+    match mode {
+        EntryMode::Tree => entry::Mode::DIR,
+        EntryMode::BlobExecutable => entry::Mode::FILE_EXECUTABLE,
+        EntryMode::Link => entry::Mode::SYMLINK,
+        EntryMode::Commit => entry::Mode::COMMIT,
+        EntryMode::Blob => entry::Mode::FILE,
+    }
+}
+
+#[cfg(unix)]
+pub(super) fn is_executable(meta: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o100 != 0
+}
+
+#[cfg(not(unix))]
+pub(super) fn is_executable(_meta: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+pub(super) fn set_executable(path: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut perm = std::fs::metadata(path)?.permissions();
+    // Everyone who can read the file can execute it.
+    perm.set_mode(perm.mode() | (perm.mode() & 0o444) >> 2);
+    std::fs::set_permissions(path, perm)
+}
+
+#[cfg(not(unix))]
+pub(super) fn set_executable(_path: &std::path::Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+pub(super) fn create_symlink(original: &std::path::Path, link: &std::path::Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+pub(super) fn create_symlink(original: &std::path::Path, link: &std::path::Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
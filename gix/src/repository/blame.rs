@@ -0,0 +1,16 @@
+use crate::{blame, bstr::BStr};
+
+/// Blaming
+impl crate::Repository {
+    /// Attribute each line of the file at `path` to the commit that introduced it, using `options`, similar to `git blame`.
+    ///
+    /// History is traversed from [`options.commit`][blame::Options::commit] and the file is followed across renames,
+    /// with lines being passed on to the first parent that has them unchanged.
+    /// As only shared references to the repository are needed, many files can be blamed in parallel by using a
+    /// repository per thread, obtained from a [`ThreadSafeRepository`][crate::ThreadSafeRepository].
+    ///
+    /// Use [`blame::Options::from_repo()`] to obtain options that match those of `git blame`.
+    pub fn blame(&self, path: impl AsRef<BStr>, options: &blame::Options) -> Result<blame::Outcome, blame::Error> {
+        blame::blame(self, path.as_ref(), options)
+    }
+}
@@ -0,0 +1,120 @@
+use std::cell::RefMut;
+
+use gix_hash::{oid, ObjectId};
+use gix_hashtable::HashMap;
+use gix_odb::{FindExt, Write};
+
+use crate::compat;
+
+/// Methods related to the compatibility object format.
+impl crate::Repository {
+    /// Return the id the object with `id` has in the [compatibility object format][crate::Repository::compat_object_hash()],
+    /// or `None` if none is configured.
+    ///
+    /// If it isn't known yet, also not by other processes, the object along with all objects it refers to is converted to
+    /// compute it, and the results are recorded in the loose object map at once. Note that this can be costly for commits, as all of their history
+    /// is converted unless this was done before.
+    pub fn compat_object_id(&self, id: impl Into<ObjectId>) -> Result<Option<ObjectId>, compat::Error> {
+        let id = id.into();
+        let mut map = match self.compat_object_map()? {
+            Some(map) => map,
+            None => return Ok(None),
+        };
+        if let Some(compat_id) = map.to_compat(&id) {
+            return Ok(Some(compat_id));
+        }
+        map.refresh()?;
+        if let Some(compat_id) = map.to_compat(&id) {
+            return Ok(Some(compat_id));
+        }
+
+        let object_hash = map.object_hash();
+        let compat_hash = map.compat_hash();
+        let (mut buf, mut out, mut missing) = (Vec::new(), Vec::new(), Vec::new());
+        let mut converted = HashMap::default();
+        let mut ids = vec![id];
+        while let Some(&id) = ids.last() {
+            if map.to_compat(&id).is_some() || converted.contains_key(&id) {
+                ids.pop();
+                continue;
+            }
+            let kind = self.objects.find(id, &mut buf)?.kind;
+            gix_object::convert::object_ids(kind, &buf, object_hash, &mut out, |id| {
+                map.to_compat(id)
+                    .or_else(|| converted.get(&id.to_owned()).copied())
+                    .or_else(|| {
+                        missing.push(id.to_owned());
+                        Some(compat_hash.null())
+                    })
+            })?;
+            if missing.is_empty() {
+                let compat_id = gix_odb::sink(compat_hash).write_buf(kind, &out)?;
+                converted.insert(id, compat_id);
+                ids.pop();
+            } else {
+                ids.append(&mut missing);
+            }
+        }
+        let compat_id = converted.get(&id).copied();
+        map.extend(converted)?;
+        Ok(compat_id)
+    }
+
+    /// Record the id that the newly written object with `id` has in the compatibility object format, if one is configured
+    /// and if all objects it refers to are known in that format already.
+    ///
+    /// Otherwise nothing is recorded, leaving it to [`compat_object_id()`][Self::compat_object_id()] to convert the object
+    /// along with its history only when asked to.
+    pub(crate) fn record_compat_object_id(&self, id: ObjectId) -> Result<(), compat::Error> {
+        let mut map = match self.compat_object_map()? {
+            Some(map) => map,
+            None => return Ok(()),
+        };
+        if map.to_compat(&id).is_some() {
+            return Ok(());
+        }
+        let (mut buf, mut out) = (Vec::new(), Vec::new());
+        let kind = self.objects.find(id, &mut buf)?.kind;
+        match gix_object::convert::object_ids(kind, &buf, map.object_hash(), &mut out, |id| map.to_compat(id)) {
+            Ok(()) => {}
+            Err(gix_object::convert::Error::Unmapped { .. }) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        }
+        let compat_id = gix_odb::sink(map.compat_hash()).write_buf(kind, &out)?;
+        map.insert(id, compat_id)?;
+        Ok(())
+    }
+
+    /// Return the id in the object format of this repository of the object known as `compat_id` in the
+    /// [compatibility object format][crate::Repository::compat_object_hash()].
+    ///
+    /// Return `None` if no compatibility object format is configured, or if the id of the object wasn't recorded yet,
+    /// by us or by other processes.
+    pub fn object_id_from_compat(&self, compat_id: impl AsRef<oid>) -> Result<Option<ObjectId>, compat::Error> {
+        let mut map = match self.compat_object_map()? {
+            Some(map) => map,
+            None => return Ok(None),
+        };
+        if let Some(id) = map.from_compat(compat_id.as_ref()) {
+            return Ok(Some(id));
+        }
+        map.refresh()?;
+        Ok(map.from_compat(compat_id.as_ref()))
+    }
+
+    fn compat_object_map(&self) -> Result<Option<RefMut<'_, compat::ObjectMap>>, compat::Error> {
+        let compat_hash = match self.compat_object_hash() {
+            Some(kind) => kind,
+            None => return Ok(None),
+        };
+        let mut map = self.compat_object_map.borrow_mut();
+        if map.is_none() {
+            *map = Some(compat::ObjectMap::at(
+                self.objects.store_ref().path(),
+                self.object_hash(),
+                compat_hash,
+            )?);
+        }
+        Ok(Some(RefMut::map(map, |map| map.as_mut().expect("set above"))))
+    }
+}
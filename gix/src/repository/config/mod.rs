@@ -19,9 +19,126 @@ impl crate::Repository {
         config::SnapshotMut {
             repo: Some(self),
             config,
+            lock: None,
         }
     }
 
+    /// Return a mutable snapshot of only the configuration file of `source` for changing it with the [`gix_config::File`] API,
+    /// similar to `git config --local`, `--worktree`, `--global` or `--system`.
+    ///
+    /// Unlike with [`config_snapshot_mut()`][Self::config_snapshot_mut()], [committing][config::SnapshotMut::commit()] the
+    /// snapshot atomically replaces the file with the changed configuration while preserving comments and formatting, and
+    /// reloads the configuration of this instance from disk. Dropping or [forgetting][config::SnapshotMut::forget()] it
+    /// discards the changes instead.
+    ///
+    /// The file is locked before it's read, just like `git` does it, and stays locked until the snapshot is committed or
+    /// dropped so concurrent changes can't be lost. It's loaded without resolving includes and may not exist yet, in
+    /// which case it starts out empty.
+    /// The sources map to files as follows:
+    ///
+    /// * [`Local`][gix_config::Source::Local] is the `config` file in the [common directory][Self::common_dir()].
+    /// * [`Worktree`][gix_config::Source::Worktree] is `config.worktree` in the private git directory of the current worktree
+    ///   if `extensions.worktreeConfig` is enabled. Otherwise, just like `git`, it's the repository-local configuration file,
+    ///   which is only possible if there are no linked worktrees as it is shared with all of them.
+    /// * [`User`][gix_config::Source::User], [`Git`][gix_config::Source::Git], [`System`][gix_config::Source::System] and
+    ///   [`GitInstallation`][gix_config::Source::GitInstallation] are located like when reading them, respecting
+    ///   `GIT_CONFIG_GLOBAL`, `GIT_CONFIG_SYSTEM`, `XDG_CONFIG_HOME` and `HOME` as far as the
+    ///   [environment permissions][crate::permissions::Environment] allow.
+    ///
+    /// All other sources aren't backed by a file and can't be opened.
+    #[allow(clippy::result_large_err)]
+    pub fn config_snapshot_mut_at(
+        &mut self,
+        source: gix_config::Source,
+    ) -> Result<config::SnapshotMut<'_>, config::snapshot_mut_at::Error> {
+        use config::snapshot_mut_at::Error;
+        use gix_config::Source;
+
+        let path = match source {
+            Source::Local => self.common_dir().join("config"),
+            Source::Worktree => {
+                if self.worktree_config_enabled()? {
+                    self.git_dir().join("config.worktree")
+                } else if self.worktrees().map_err(Error::ListWorktrees)?.is_empty() {
+                    return self.config_snapshot_mut_at(Source::Local);
+                } else {
+                    return Err(Error::ExtensionDisabled);
+                }
+            }
+            Source::User | Source::Git | Source::System | Source::GitInstallation => {
+                let env = &self.options.permissions.env;
+                source
+                    .storage_location(&mut |name| {
+                        match name {
+                            git_ if git_.starts_with("GIT_") => Some(&env.git_prefix),
+                            "XDG_CONFIG_HOME" => Some(&env.xdg_config_home),
+                            "HOME" => Some(&env.home),
+                            _ => None,
+                        }
+                        .and_then(|perm| perm.check_opt(name).and_then(std::env::var_os))
+                    })
+                    .ok_or(Error::NoStorageLocation { config_source: source })?
+                    .into_owned()
+            }
+            Source::Env | Source::Cli | Source::Api | Source::EnvOverride => {
+                return Err(Error::NoStorageLocation { config_source: source })
+            }
+        };
+        // The directory of the configuration file in `$XDG_CONFIG_HOME/git` may not exist yet.
+        let boundary_directory = (source == Source::Git)
+            .then(|| path.parent().and_then(std::path::Path::parent).map(ToOwned::to_owned))
+            .flatten();
+        let lock = gix_lock::File::acquire_to_update_resource(
+            &path,
+            gix_lock::acquire::Fail::Immediately,
+            boundary_directory,
+        )?;
+        let config = match gix_config::File::from_path_no_includes(path.clone(), source) {
+            Ok(file) => file,
+            Err(gix_config::file::init::from_paths::Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                gix_config::File::new(gix_config::file::Metadata::from(source).at(&path))
+            }
+            Err(err) => return Err(Error::Read { path, source: err }),
+        };
+        Ok(config::SnapshotMut {
+            repo: Some(self),
+            config,
+            lock: Some(lock),
+        })
+    }
+
+    /// Change the configuration of the current worktree with `edit`, similar to `git config --worktree`, and return its result
+    /// after writing the changed configuration file and reloading the configuration of this instance from disk.
+    ///
+    /// This is a shortcut for [committing][config::SnapshotMut::commit()] the changes made to a
+    /// [snapshot][Self::config_snapshot_mut_at()] of [`Worktree`][gix_config::Source::Worktree] configuration.
+    /// Note that in-memory changes made with [`config_snapshot_mut()`][Self::config_snapshot_mut()] are lost when the
+    /// configuration is reloaded.
+    #[allow(clippy::result_large_err)]
+    pub fn edit_worktree_config<T>(
+        &mut self,
+        edit: impl FnOnce(&mut gix_config::File<'static>) -> T,
+    ) -> Result<T, config::snapshot_mut_at::Error> {
+        let mut snapshot = self.config_snapshot_mut_at(gix_config::Source::Worktree)?;
+        let out = edit(&mut snapshot);
+        snapshot.commit()?;
+        Ok(out)
+    }
+
+    /// Return true if `extensions.worktreeConfig` is enabled, which makes `config.worktree` in the private git directory of
+    /// each worktree part of its configuration.
+    pub(crate) fn worktree_config_enabled(&self) -> Result<bool, config::boolean::Error> {
+        use crate::config::{cache::util::ApplyLeniency, tree::Extensions};
+        Ok(self
+            .config
+            .resolved
+            .boolean_by_key("extensions.worktreeConfig")
+            .map(|value| Extensions::WORKTREE_CONFIG.enrich_error(value))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+            .unwrap_or(false))
+    }
+
     /// The options used to open the repository.
     pub fn open_options(&self) -> &crate::open::Options {
         &self.options
@@ -64,10 +181,100 @@ impl crate::Repository {
         Ok(opts)
     }
 
+    /// Obtain options for use when writing the index, with its version configured by `index.version`, or `4` if
+    /// `feature.manyFiles` is enabled. If neither is set, the version the index was read with is retained if possible.
+    ///
+    /// A split index is written if `core.splitIndex` is `true`, configured by `splitIndex.maxPercentChange` and
+    /// `splitIndex.sharedIndexExpire`. Use [`index_write_options_for()`][Self::index_write_options_for()] to also keep
+    /// split indices split if `core.splitIndex` is unset.
+    pub fn index_write_options(&self) -> Result<gix_index::write::Options, config::index_write_options::Error> {
+        self.index_write_options_inner(false)
+    }
+
+    /// Like [`index_write_options()`][Self::index_write_options()], but if `core.splitIndex` is unset, a split index is
+    /// written if `index` was read from a split index, just like `git` does it.
+    pub fn index_write_options_for(
+        &self,
+        index: &gix_index::State,
+    ) -> Result<gix_index::write::Options, config::index_write_options::Error> {
+        self.index_write_options_inner(index.link().is_some())
+    }
+
+    fn index_write_options_inner(
+        &self,
+        is_split: bool,
+    ) -> Result<gix_index::write::Options, config::index_write_options::Error> {
+        use crate::config::{
+            cache::util::ApplyLeniency,
+            tree::{Core, Feature, Index, SplitIndex},
+        };
+
+        let config = &self.config.resolved;
+        let version = match config
+            .integer_by_key("index.version")
+            .map(|version| Index::VERSION.try_into_index_version(version))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+        {
+            Some(version) => Some(version),
+            None => config
+                .boolean_by_key("feature.manyFiles")
+                .map(|many_files| Feature::MANY_FILES.enrich_error(many_files))
+                .transpose()
+                .with_leniency(self.options.lenient_config)?
+                .unwrap_or(false)
+                .then_some(gix_index::Version::V4),
+        };
+        let split_index = match config
+            .boolean_by_key("core.splitIndex")
+            .map(|split_index| Core::SPLIT_INDEX.enrich_error(split_index))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+        {
+            Some(split_index) => split_index,
+            None => is_split,
+        };
+        let split_index = if split_index {
+            let default = gix_index::write::SplitIndex::default();
+            Some(gix_index::write::SplitIndex {
+                max_percent_change: config
+                    .integer_by_key("splitIndex.maxPercentChange")
+                    .map(|percent| SplitIndex::MAX_PERCENT_CHANGE.try_into_percentage(percent))
+                    .transpose()
+                    .with_leniency(self.options.lenient_config)?
+                    .unwrap_or(default.max_percent_change),
+                shared_index_expire: match config
+                    .string_by_key("splitIndex.sharedIndexExpire")
+                    .map(|expire| SplitIndex::SHARED_INDEX_EXPIRE.try_into_expiry(expire, std::time::SystemTime::now()))
+                    .transpose()
+                    .with_leniency(self.options.lenient_config)?
+                {
+                    Some(expire) => expire,
+                    None => default.shared_index_expire,
+                },
+            })
+        } else {
+            None
+        };
+        Ok(gix_index::write::Options {
+            version,
+            split_index,
+            ..Default::default()
+        })
+    }
+
     /// The kind of object hash the repository is configured to use.
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.config.object_hash
     }
+
+    /// The kind of object hash of the compatibility object format configured with `extensions.compatObjectFormat`, if any.
+    ///
+    /// If set, the ids of objects in this format are recorded as objects are written, and
+    /// [looked up][crate::Repository::compat_object_id()] as needed.
+    pub fn compat_object_hash(&self) -> Option<gix_hash::Kind> {
+        self.config.compat_object_hash
+    }
 }
 
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
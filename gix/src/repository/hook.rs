@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use crate::{config::tree::Core, hook};
+
+/// Hooks
+impl crate::Repository {
+    /// Return the directory with the hooks of this repository, which is `core.hooksPath` if set, or the `hooks` directory
+    /// of the repository otherwise.
+    ///
+    /// Note that `core.hooksPath` is only read from trusted configuration files, and relative paths are relative to the
+    /// directory hooks run in, the work tree or the `.git` directory of bare repositories.
+    pub fn hooks_dir(&self) -> Result<PathBuf, hook::Error> {
+        Ok(
+            match self
+                .config
+                .trusted_file_path("core", None, Core::HOOKS_PATH.name)
+                .transpose()?
+            {
+                Some(path) => self.hooks_working_dir().join(path),
+                None => self.absolute(&self.common_dir().join("hooks")),
+            },
+        )
+    }
+
+    /// Return the hook called `name`, like `pre-commit`, ready to run, or `None` if there is no executable hook of that name
+    /// or if hooks are [denied][crate::Permissions::hooks] for this repository.
+    pub fn hook(&self, name: &str) -> Result<Option<hook::Prepare<'_>>, hook::Error> {
+        let path = self.hooks_dir()?.join(name);
+        if !std::fs::metadata(&path).map_or(false, |meta| meta.is_file() && is_executable(&meta)) {
+            return Ok(None);
+        }
+        let path = self
+            .options
+            .permissions
+            .hooks
+            .by_level(self.git_dir_trust())
+            .check(path)
+            .map_err(|source| hook::Error::Forbidden {
+                name: name.into(),
+                source,
+            })?;
+        Ok(path.map(|path| hook::Prepare::new(name, path, self)))
+    }
+
+    /// Run the hook called `name` with `args` if it exists, failing if it doesn't succeed.
+    pub(crate) fn run_hook(
+        &self,
+        name: &str,
+        args: impl IntoIterator<Item = impl Into<std::ffi::OsString>>,
+    ) -> Result<(), hook::Error> {
+        match self.hook(name)? {
+            Some(hook) => hook.args(args).run(),
+            None => Ok(()),
+        }
+    }
+
+    /// The directory hooks run in.
+    pub(crate) fn hooks_working_dir(&self) -> PathBuf {
+        self.absolute(self.work_dir().unwrap_or_else(|| self.git_dir()))
+    }
+
+    /// Return `path` relative to the current working directory at the time the repository was opened as absolute path.
+    pub(crate) fn absolute(&self, path: &Path) -> PathBuf {
+        match &self.options.current_dir {
+            Some(cwd) => cwd.join(path),
+            None => path.to_owned(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_meta: &std::fs::Metadata) -> bool {
+    true
+}
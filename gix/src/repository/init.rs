@@ -1,6 +1,7 @@
 use std::cell::RefCell;
 
 impl crate::Repository {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn from_refs_and_objects(
         refs: crate::RefStore,
         objects: crate::OdbHandle,
@@ -13,6 +14,7 @@ impl crate::Repository {
         let objects = setup_objects(objects, &config);
         crate::Repository {
             bufs: RefCell::new(Vec::with_capacity(4)),
+            compat_object_map: RefCell::new(None),
             work_tree,
             common_dir,
             objects,
@@ -0,0 +1,45 @@
+use crate::{merge::tree, Id};
+
+/// Merging
+impl crate::Repository {
+    /// Merge the trees `ours` and `theirs`, which both derive from the tree `base`, and write the merged tree to the object
+    /// database, using options as configured in this repository.
+    ///
+    /// All arguments may also be commits, which are peeled to their tree.
+    /// Conflicts don't abort the merge, but are recorded in the returned outcome, as the merged tree is always produced.
+    /// See [`merge_trees_opts()`][Self::merge_trees_opts()] for details.
+    pub fn merge_trees(
+        &self,
+        base: impl Into<gix_hash::ObjectId>,
+        ours: impl Into<gix_hash::ObjectId>,
+        theirs: impl Into<gix_hash::ObjectId>,
+    ) -> Result<tree::Outcome<'_>, tree::Error> {
+        self.merge_trees_opts(base, ours, theirs, &tree::Options::from_repo(self)?)
+    }
+
+    /// Merge the trees `ours` and `theirs`, which both derive from the tree `base`, with `options`, and write the merged tree
+    /// to the object database.
+    ///
+    /// Changes of both sides are combined, and files are followed across renames on either side.
+    /// The merged tree contains all conflicting files as described by their [kind][tree::ConflictKind], which allows them to
+    /// be [written to an index][tree::Outcome::write_conflicts_to()] as stage 1 to 3 entries to represent the result of a
+    /// merge like `git merge` would, without needing a work tree.
+    pub fn merge_trees_opts(
+        &self,
+        base: impl Into<gix_hash::ObjectId>,
+        ours: impl Into<gix_hash::ObjectId>,
+        theirs: impl Into<gix_hash::ObjectId>,
+        options: &tree::Options,
+    ) -> Result<tree::Outcome<'_>, tree::Error> {
+        let mut ids = [base.into(), ours.into(), theirs.into()];
+        for id in &mut ids {
+            *id = self.find_object(*id)?.peel_to_tree()?.id;
+        }
+        let [base, ours, theirs] = ids;
+        let (tree, conflicts) = tree::merge(self, base, ours, theirs, options)?;
+        Ok(tree::Outcome {
+            tree: Id::from_id(tree, self),
+            conflicts,
+        })
+    }
+}
@@ -19,18 +19,26 @@ impl crate::Repository {
     }
 }
 
+mod apply;
+mod blame;
 mod cache;
+mod compat;
 mod config;
+mod hook;
 pub(crate) mod identity;
 mod impls;
 mod init;
 mod location;
+mod merge;
 mod object;
 pub(crate) mod permissions;
 mod reference;
 mod remote;
 mod revision;
+mod sequencer;
 mod snapshots;
+mod stash;
 mod state;
+mod status;
 mod thread_safe;
 mod worktree;
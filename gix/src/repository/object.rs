@@ -8,7 +8,12 @@ use gix_ref::{
     FullName,
 };
 
-use crate::{commit, ext::ObjectIdExt, object, tag, Id, Object, Reference, Tree};
+use gix_object::{
+    bstr::{BString, ByteSlice},
+    WriteTo,
+};
+
+use crate::{commit, config, ext::ObjectIdExt, hook, object, signing, tag, Id, Object, Reference, Tree};
 
 /// Methods related to object creation.
 impl crate::Repository {
@@ -59,18 +64,19 @@ impl crate::Repository {
     }
 
     /// Write the given object into the object database and return its object id.
+    ///
+    /// If a [compatibility object format][crate::Repository::compat_object_hash()] is configured, the id of the object in
+    /// that format is recorded as well if the objects it refers to are known in that format, which is also true for all other
+    /// methods writing objects. Otherwise it can be [computed on demand][crate::Repository::compat_object_id()].
     pub fn write_object(&self, object: impl gix_object::WriteTo) -> Result<Id<'_>, object::write::Error> {
-        self.objects
-            .write(object)
-            .map(|oid| oid.attach(self))
-            .map_err(Into::into)
+        let id = self.objects.write(object)?;
+        self.written(id)
     }
 
     /// Write a blob from the given `bytes`.
     pub fn write_blob(&self, bytes: impl AsRef<[u8]>) -> Result<Id<'_>, object::write::Error> {
-        self.objects
-            .write_buf(gix_object::Kind::Blob, bytes.as_ref())
-            .map(|oid| oid.attach(self))
+        let id = self.objects.write_buf(gix_object::Kind::Blob, bytes.as_ref())?;
+        self.written(id)
     }
 
     /// Write a blob from the given `Read` implementation.
@@ -82,9 +88,13 @@ impl crate::Repository {
         let len = bytes.seek(std::io::SeekFrom::End(0))? - current;
         bytes.seek(std::io::SeekFrom::Start(current))?;
 
-        self.objects
-            .write_stream(gix_object::Kind::Blob, len, bytes)
-            .map(|oid| oid.attach(self))
+        let id = self.objects.write_stream(gix_object::Kind::Blob, len, bytes)?;
+        self.written(id)
+    }
+
+    fn written(&self, id: ObjectId) -> Result<Id<'_>, object::write::Error> {
+        self.record_compat_object_id(id)?;
+        Ok(id.attach(self))
     }
 
     /// Create a tag reference named `name` (without `refs/tags/` prefix) pointing to a newly created tag object
@@ -92,6 +102,8 @@ impl crate::Repository {
     ///
     /// It will be created with `constraint` which is most commonly to [only create it][PreviousValue::MustNotExist]
     /// or to [force overwriting a possibly existing tag](PreviousValue::Any).
+    ///
+    /// If `tag.gpgSign` is set, the tag object is [signed][crate::signing] and its message is terminated with a newline if needed.
     pub fn tag(
         &self,
         name: impl AsRef<str>,
@@ -101,7 +113,7 @@ impl crate::Repository {
         message: impl AsRef<str>,
         constraint: PreviousValue,
     ) -> Result<Reference<'_>, tag::Error> {
-        let tag = gix_object::Tag {
+        let mut tag = gix_object::Tag {
             target: target.as_ref().into(),
             target_kind,
             name: name.as_ref().into(),
@@ -109,13 +121,35 @@ impl crate::Repository {
             message: message.as_ref().into(),
             pgp_signature: None,
         };
-        let tag_id = self.write_object(&tag)?;
+        let tag_id = if self.config.gpg_sign(&config::tree::Tag::GPG_SIGN)? {
+            if !tag.message.ends_with(b"\n") {
+                tag.message.push(b'\n');
+            }
+            // The signature follows the message directly, which is what `git` and the decoder expect.
+            let mut data = Vec::new();
+            tag.write_to(&mut data).map_err(object::write::Error::from)?;
+            let signature = signing::Signer::from_repo(self)?.sign(&data)?;
+            data.extend_from_slice(&signature);
+            let id = self
+                .objects
+                .write_buf(gix_object::Kind::Tag, &data)
+                .map_err(object::write::Error::from)?;
+            self.written(id)?
+        } else {
+            self.write_object(&tag)?
+        };
         self.tag_reference(name, tag_id, constraint).map_err(Into::into)
     }
 
     /// Similar to [`commit(…)`][crate::Repository::commit()], but allows to create the commit with `committer` and `author` specified.
     ///
     /// This forces setting the commit time and author time by hand. Note that typically, committer and author are the same.
+    ///
+    /// If `commit.gpgSign` is set, the commit is [signed][crate::signing] as well.
+    ///
+    /// The `pre-commit` and `commit-msg` [hooks][crate::hook] run before the commit is created, and either can prevent it.
+    /// The latter may also change the commit message. The `post-commit` hook runs once the commit was created and `reference`
+    /// was updated, but can't affect the outcome anymore.
     pub fn commit_as<'a, 'c, Name, E>(
         &self,
         committer: impl Into<gix_actor::SignatureRef<'c>>,
@@ -137,8 +171,26 @@ impl crate::Repository {
         // TODO: possibly use CommitRef to save a few allocations (but will have to allocate for object ids anyway.
         //       This can be made vastly more efficient though if we wanted to, so we lie in the API
         let reference = reference.try_into()?;
+        let index_path = self.absolute(&self.index_path());
+        let commit_hook = |name: &str| -> Result<_, hook::Error> {
+            Ok(self.hook(name)?.map(|hook| {
+                hook.env("GIT_INDEX_FILE", index_path.as_os_str())
+                    .env("GIT_EDITOR", ":")
+            }))
+        };
+        if let Some(hook) = commit_hook("pre-commit")? {
+            hook.run()?;
+        }
+        let mut message: BString = message.as_ref().into();
+        if let Some(hook) = commit_hook("commit-msg")? {
+            let path = self.absolute(&self.git_dir().join("COMMIT_EDITMSG"));
+            std::fs::write(&path, &message)?;
+            hook.arg(path.as_os_str()).run()?;
+            message = std::fs::read(&path)?.into();
+        }
+
         let commit = gix_object::Commit {
-            message: message.as_ref().into(),
+            message,
             tree: tree.into(),
             author: author.into().to_owned(),
             committer: committer.into().to_owned(),
@@ -146,8 +198,7 @@ impl crate::Repository {
             parents: parents.into_iter().map(|id| id.into()).collect(),
             extra_headers: Default::default(),
         };
-
-        let commit_id = self.write_object(&commit)?;
+        let (commit_id, commit) = self.write_commit(commit)?;
         self.edit_reference(RefEdit {
             change: Change::Update {
                 log: LogChange {
@@ -170,9 +221,33 @@ impl crate::Repository {
             name: reference,
             deref: true,
         })?;
+        if let Some(hook) = commit_hook("post-commit")? {
+            hook.run().ok();
+        }
         Ok(commit_id)
     }
 
+    /// Write `commit` to the object database, after [signing][crate::signing] it if `commit.gpgSign` is set, and return
+    /// its id along with the commit as written.
+    ///
+    /// All commits created on behalf of the user go through here so none of them misses the signature.
+    pub(crate) fn write_commit(
+        &self,
+        mut commit: gix_object::Commit,
+    ) -> Result<(Id<'_>, gix_object::Commit), commit::Error> {
+        if self.config.gpg_sign(&config::tree::Commit::GPG_SIGN)? {
+            let mut payload = Vec::new();
+            commit.write_to(&mut payload).map_err(object::write::Error::from)?;
+            let signature = signing::Signer::from_repo(self)?.sign(&payload)?;
+            commit.extra_headers.push((
+                signing::commit_header_name(self.object_hash()).into(),
+                signature.trim_end_with(|c| c == '\n').into(),
+            ));
+        }
+        let id = self.write_object(&commit)?;
+        Ok((id, commit))
+    }
+
     /// Create a new commit object with `message` referring to `tree` with `parents`, and point `reference`
     /// to it. The commit is written without message encoding field, which can be assumed to be UTF-8.
     /// `author` and `committer` fields are pre-set from the configuration, which can be altered
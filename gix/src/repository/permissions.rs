@@ -7,6 +7,10 @@ pub struct Permissions {
     pub env: Environment,
     /// Permissions related to the handling of git configuration.
     pub config: Config,
+    /// Control whether [hooks][crate::hook] may run, depending on the trust in the repository they belong to.
+    ///
+    /// Note that [`gix_sec::Permission::Forbid`] will cause operations to fail if they would run an existing hook.
+    pub hooks: gix_sec::trust::Mapping<gix_sec::Permission>,
 }
 
 /// Configure from which sources git configuration may be loaded.
@@ -109,10 +113,16 @@ impl Permissions {
     ///
     /// This allows to read and write repositories even if they aren't owned by the current user, but avoid using
     /// anything else that could cause us to write into unknown locations or use programs beyond our `PATH`.
+    ///
+    /// Hooks only run in repositories that are owned by the current user.
     pub fn secure() -> Self {
         Permissions {
             env: Environment::all(),
             config: Config::all(),
+            hooks: gix_sec::trust::Mapping {
+                full: gix_sec::Permission::Allow,
+                reduced: gix_sec::Permission::Deny,
+            },
         }
     }
 
@@ -122,10 +132,14 @@ impl Permissions {
         Permissions {
             env: Environment::all(),
             config: Config::all(),
+            hooks: gix_sec::trust::Mapping {
+                full: gix_sec::Permission::Allow,
+                reduced: gix_sec::Permission::Allow,
+            },
         }
     }
 
-    /// Don't read any but the local git configuration and deny reading any environment variables.
+    /// Don't read any but the local git configuration, deny reading any environment variables and don't run hooks.
     pub fn isolated() -> Self {
         Permissions {
             config: Config {
@@ -148,6 +162,10 @@ impl Permissions {
                     objects: deny,
                 }
             },
+            hooks: gix_sec::trust::Mapping {
+                full: gix_sec::Permission::Deny,
+                reduced: gix_sec::Permission::Deny,
+            },
         }
     }
 }
@@ -6,6 +6,8 @@ use gix_ref::{
     FullName, PartialNameRef, Target,
 };
 
+use gix_odb::pack::Find;
+
 use crate::{bstr::BString, ext::ReferenceExt, reference, Reference};
 
 /// Obtain and alter references comfortably
@@ -46,12 +48,12 @@ impl crate::Repository {
     ///
     /// Namespaces allow to partition references, and is configured per `Easy`.
     pub fn namespace(&self) -> Option<&gix_ref::Namespace> {
-        self.refs.namespace.as_ref()
+        self.refs.namespace()
     }
 
     /// Remove the currently set reference namespace and return it, affecting only this `Easy`.
     pub fn clear_namespace(&mut self) -> Option<gix_ref::Namespace> {
-        self.refs.namespace.take()
+        self.refs.set_namespace(None)
     }
 
     /// Set the reference namespace to the given value, like `"foo"` or `"foo/bar"`.
@@ -66,7 +68,7 @@ impl crate::Repository {
         gix_validate::refname::Error: From<E>,
     {
         let namespace = gix_ref::namespace::expand(namespace)?;
-        Ok(self.refs.namespace.replace(namespace))
+        Ok(self.refs.set_namespace(Some(namespace)))
     }
 
     // TODO: more tests or usage
@@ -132,12 +134,68 @@ impl crate::Repository {
         &self,
         edits: impl IntoIterator<Item = RefEdit>,
     ) -> Result<Vec<RefEdit>, reference::edit::Error> {
-        let (file_lock_fail, packed_refs_lock_fail) = self.config.lock_timeout()?;
-        self.refs
-            .transaction()
-            .prepare(edits, file_lock_fail, packed_refs_lock_fail)?
-            .commit(self.committer().transpose()?)
-            .map_err(Into::into)
+        self.commit_transaction(
+            self.ref_transaction(gix_ref::file::transaction::PackedRefs::default()),
+            edits,
+            self.config.lock_timeout()?,
+        )
+    }
+
+    /// Return a transaction on the stack of reftables if `extensions.refStorage` is `reftable`, or one on loose references
+    /// which handles the packed-refs file according to `packed_refs` otherwise.
+    pub(crate) fn ref_transaction<'a>(
+        &'a self,
+        packed_refs: gix_ref::file::transaction::PackedRefs<'a>,
+    ) -> gix_ref::store::transaction::Transaction<'a> {
+        self.refs.transaction().packed_refs(packed_refs)
+    }
+
+    /// Prepare `transaction` with `edits` and commit it, while letting the `reference-transaction` hook know about each
+    /// state of the transaction. The hook may reject the transaction once it's prepared.
+    pub(crate) fn commit_transaction(
+        &self,
+        transaction: gix_ref::store::transaction::Transaction<'_>,
+        edits: impl IntoIterator<Item = RefEdit>,
+        (file_lock_fail, packed_refs_lock_fail): (gix_lock::acquire::Fail, gix_lock::acquire::Fail),
+    ) -> Result<Vec<RefEdit>, reference::edit::Error> {
+        use gix_ref::store::transaction::Transaction;
+        const HOOK: &str = "reference-transaction";
+        let committer = self.committer().transpose()?;
+        let transaction = match transaction {
+            Transaction::Loose(t) => Transaction::Loose(t.prepare(edits, file_lock_fail, packed_refs_lock_fail)?),
+            Transaction::Reftable(t) => Transaction::Reftable(t.prepare(edits, file_lock_fail)?),
+        };
+        let commit = |transaction: Transaction<'_>| -> Result<_, reference::edit::Error> {
+            Ok(match transaction {
+                Transaction::Loose(t) => t.commit(committer)?,
+                Transaction::Reftable(t) => t.commit(committer)?,
+            })
+        };
+        let input = reference_transaction_input(&transaction.edits().collect::<Vec<_>>(), self.object_hash());
+        if input.is_empty() || self.hook(HOOK)?.is_none() {
+            return commit(transaction);
+        }
+
+        let run = |state: &str| match self.hook(HOOK)? {
+            Some(hook) => hook.arg(state).stdin(input.clone()).run(),
+            None => Ok(()),
+        };
+        // Like `git`, the outcome of the hook only matters while the transaction can still be aborted.
+        if let Err(err) = run("prepared") {
+            transaction.rollback();
+            run("aborted").ok();
+            return Err(err.into());
+        }
+        match commit(transaction) {
+            Ok(edits) => {
+                run("committed").ok();
+                Ok(edits)
+            }
+            Err(err) => {
+                run("aborted").ok();
+                Err(err)
+            }
+        }
     }
 
     /// Return the repository head, an abstraction to help dealing with the `HEAD` reference.
@@ -231,13 +289,62 @@ impl crate::Repository {
         Name: TryInto<&'a PartialNameRef, Error = E>,
         gix_ref::file::find::Error: From<E>,
     {
-        let state = self;
-        match state.refs.try_find(name) {
-            Ok(r) => match r {
-                Some(r) => Ok(Some(Reference::from_ref(r, self))),
-                None => Ok(None),
-            },
-            Err(err) => Err(err.into()),
-        }
+        let name = name
+            .try_into()
+            .map_err(|err| reference::find::Error::Find(err.into()))?;
+        Ok(self
+            .try_find_reference_inner(name)?
+            .map(|r| Reference::from_ref(r, self)))
+    }
+
+    fn try_find_reference_inner(
+        &self,
+        name: &PartialNameRef,
+    ) -> Result<Option<gix_ref::Reference>, reference::find::Error> {
+        Ok(self.refs.try_find(name)?)
+    }
+
+    /// Follow all symbolic targets of `reference` and peel the object it ultimately points to, using the backend
+    /// configured with `extensions.refStorage`.
+    pub(crate) fn peel_reference_in_place(
+        &self,
+        reference: &mut gix_ref::Reference,
+    ) -> Result<ObjectId, gix_ref::peel::to_id::Error> {
+        self.refs.peel_to_id_in_place(reference, |oid, buf| {
+            self.objects
+                .try_find(oid, buf)
+                .map(|po| po.map(|(o, _l)| (o.kind, o.data)))
+        })
+    }
+}
+
+/// Produce the input of the `reference-transaction` hook for `edits` of a prepared transaction, with one
+/// `<old-value> <new-value> <ref-name>` line per edit that doesn't set a symbolic reference.
+fn reference_transaction_input(edits: &[&RefEdit], object_hash: gix_hash::Kind) -> Vec<u8> {
+    let null = object_hash.null();
+    let previous_id = |edit: &RefEdit| match edit.change.previous_value() {
+        Some(gix_ref::TargetRef::Peeled(id)) => id.to_owned(),
+        Some(gix_ref::TargetRef::Symbolic(_)) | None => null,
+    };
+    let mut out = Vec::new();
+    for edit in edits {
+        let new = match &edit.change {
+            Change::Update {
+                new: Target::Peeled(id),
+                ..
+            } => *id,
+            Change::Update { .. } => continue,
+            Change::Delete { .. } => null,
+        };
+        // Symbolic references that were split report the previous value of their referent.
+        let previous = match edit.change.previous_value() {
+            Some(gix_ref::TargetRef::Symbolic(referent)) => edits
+                .iter()
+                .find(|edit| edit.name.as_ref() == referent)
+                .map_or(null, |edit| previous_id(edit)),
+            _ => previous_id(edit),
+        };
+        out.extend_from_slice(format!("{} {} {}\n", previous, new, edit.name.as_bstr()).as_bytes());
     }
+    out
 }
@@ -1,4 +1,6 @@
-use crate::{bstr::BStr, revision, Id};
+use gix_hash::{oid, ObjectId};
+
+use crate::{bstr::BStr, ext::ObjectIdExt, revision, revision::merge_base, Id};
 
 /// Methods for resolving revisions by spec or working with the commit graph.
 impl crate::Repository {
@@ -39,4 +41,132 @@ impl crate::Repository {
     ) -> revision::walk::Platform<'_> {
         revision::walk::Platform::new(tips, self)
     }
+
+    /// Return an iterator over all commits reachable from either `left` or `right` but not both, similar to
+    /// `git rev-list --left-right <left>...<right>`, along with the side they can be reached from.
+    ///
+    /// Both sides are traversed in a single walk, using generation numbers from the commit-graph if present.
+    pub fn rev_walk_left_right(
+        &self,
+        left: impl Into<ObjectId>,
+        right: impl Into<ObjectId>,
+    ) -> Result<revision::walk::LeftRight<'_>, merge_base::Error> {
+        let cache = self.commit_graph_if_present();
+        let (left, right) =
+            gix_revision::merge_base::symmetric_difference(&left.into(), &right.into(), cache.as_ref(), |id, buf| {
+                self.find_commit_iter(id, buf)
+            })?;
+        Ok(revision::walk::LeftRight {
+            left: left.into_iter(),
+            right: right.into_iter(),
+            repo: self,
+        })
+    }
+
+    /// Return the amount of commits that are only reachable from `ahead` and the amount of commits that are only reachable
+    /// from `behind`, similar to `git rev-list --count --left-right <ahead>...<behind>`.
+    ///
+    /// This is typically used to learn how far a branch is ahead and behind its upstream branch.
+    pub fn ahead_behind(
+        &self,
+        ahead: impl Into<ObjectId>,
+        behind: impl Into<ObjectId>,
+    ) -> Result<(usize, usize), merge_base::Error> {
+        let walk = self.rev_walk_left_right(ahead, behind)?;
+        Ok((walk.left.len(), walk.right.len()))
+    }
+
+    /// Return the best common ancestor of `one` and `two`, similar to `git merge-base <one> <two>`, or fail if there
+    /// is none.
+    ///
+    /// If there are multiple equally good common ancestors, the most recent one is returned.
+    /// Generation numbers from the commit-graph are used to speed up the traversal if present.
+    pub fn merge_base(&self, one: impl Into<ObjectId>, two: impl Into<ObjectId>) -> Result<Id<'_>, merge_base::Error> {
+        let (one, two) = (one.into(), two.into());
+        self.merge_bases(one, Some(two))?
+            .into_iter()
+            .next()
+            .ok_or(merge_base::Error::NotFound {
+                first: one,
+                second: two,
+            })
+    }
+
+    /// Return all best common ancestors of `one` and a hypothetical merge commit of all `others`, similar to
+    /// `git merge-base --all <one> <others>…`, with the most recent one first.
+    ///
+    /// The returned list is empty if there is no common history.
+    pub fn merge_bases(
+        &self,
+        one: impl Into<ObjectId>,
+        others: impl IntoIterator<Item = impl Into<ObjectId>>,
+    ) -> Result<Vec<Id<'_>>, merge_base::Error> {
+        let others: Vec<_> = others.into_iter().map(Into::into).collect();
+        let cache = self.commit_graph_if_present();
+        let bases = gix_revision::merge_base(&one.into(), &others, cache.as_ref(), |id, buf| {
+            self.find_commit_iter(id, buf)
+        })?;
+        Ok(self.attach_all(bases))
+    }
+
+    /// Return all best common ancestors of `commits` as if they were merged in an octopus merge, similar to
+    /// `git merge-base --all --octopus <commits>…`.
+    pub fn merge_bases_octopus(
+        &self,
+        commits: impl IntoIterator<Item = impl Into<ObjectId>>,
+    ) -> Result<Vec<Id<'_>>, merge_base::Error> {
+        let commits: Vec<_> = commits.into_iter().map(Into::into).collect();
+        let cache = self.commit_graph_if_present();
+        let bases =
+            gix_revision::merge_base::octopus(&commits, cache.as_ref(), |id, buf| self.find_commit_iter(id, buf))?;
+        Ok(self.attach_all(bases))
+    }
+
+    /// Return `true` if `ancestor` can be reached from `descendant`, similar to `git merge-base --is-ancestor`.
+    ///
+    /// Each commit is considered its own ancestor.
+    pub fn is_ancestor(
+        &self,
+        ancestor: impl Into<ObjectId>,
+        descendant: impl Into<ObjectId>,
+    ) -> Result<bool, merge_base::Error> {
+        let cache = self.commit_graph_if_present();
+        Ok(gix_revision::merge_base::is_ancestor(
+            &ancestor.into(),
+            &descendant.into(),
+            cache.as_ref(),
+            |id, buf| self.find_commit_iter(id, buf),
+        )?)
+    }
+
+    /// Return those of `commits` that can't be reached from any of the others, similar to `git merge-base --independent`,
+    /// in the order in which they were passed.
+    pub fn independent_commits(
+        &self,
+        commits: impl IntoIterator<Item = impl Into<ObjectId>>,
+    ) -> Result<Vec<Id<'_>>, merge_base::Error> {
+        let commits: Vec<_> = commits.into_iter().map(Into::into).collect();
+        let cache = self.commit_graph_if_present();
+        let commits =
+            gix_revision::merge_base::independent(&commits, cache.as_ref(), |id, buf| self.find_commit_iter(id, buf))?;
+        Ok(self.attach_all(commits))
+    }
+
+    /// Load the commit-graph of this repository, if there is a valid one.
+    pub(crate) fn commit_graph_if_present(&self) -> Option<gix_commitgraph::Graph> {
+        gix_commitgraph::Graph::from_info_dir(self.objects.store_ref().path().join("info")).ok()
+    }
+
+    fn find_commit_iter<'b>(
+        &self,
+        id: &oid,
+        buf: &'b mut Vec<u8>,
+    ) -> Result<Option<gix_object::CommitRefIter<'b>>, gix_odb::store::find::Error> {
+        use gix_odb::Find;
+        Ok(self.objects.try_find(id, buf)?.and_then(|d| d.try_into_commit_iter()))
+    }
+
+    fn attach_all(&self, ids: Vec<ObjectId>) -> Vec<Id<'_>> {
+        ids.into_iter().map(|id| id.attach(self)).collect()
+    }
 }
@@ -0,0 +1,42 @@
+#![allow(clippy::result_large_err)]
+use crate::sequencer;
+
+/// Cherry-picking and reverting
+impl crate::Repository {
+    /// Apply the changes `commit` introduced compared to its parent onto the commit `onto` with `options`, similar to
+    /// `git cherry-pick`.
+    ///
+    /// The new commit keeps the message and author of `commit` unless [configured][sequencer::Options] otherwise, and
+    /// has `onto` as its only parent. No reference is changed, which allows to pick commits onto any branch, even in
+    /// bare repositories. If `commit.gpgSign` is set, the new commit is [signed][crate::signing].
+    /// If the changes conflict, the conflicting merge is returned instead, which can be
+    /// [recorded in the repository][sequencer::Conflicted::write_state()] for the user to resolve.
+    pub fn cherry_pick(
+        &self,
+        commit: impl Into<gix_hash::ObjectId>,
+        onto: impl Into<gix_hash::ObjectId>,
+        options: &sequencer::Options,
+    ) -> Result<sequencer::Outcome<'_>, sequencer::Error> {
+        sequencer::apply(
+            self,
+            sequencer::Operation::CherryPick,
+            commit.into(),
+            onto.into(),
+            options,
+        )
+    }
+
+    /// Undo the changes `commit` introduced compared to its parent in a new commit on top of the commit `onto` with
+    /// `options`, similar to `git revert`.
+    ///
+    /// The new commit has a message like `Revert "<summary of commit>"` and the configured author, and works just like
+    /// [`cherry_pick()`][Self::cherry_pick()] otherwise.
+    pub fn revert(
+        &self,
+        commit: impl Into<gix_hash::ObjectId>,
+        onto: impl Into<gix_hash::ObjectId>,
+        options: &sequencer::Options,
+    ) -> Result<sequencer::Outcome<'_>, sequencer::Error> {
+        sequencer::apply(self, sequencer::Operation::Revert, commit.into(), onto.into(), options)
+    }
+}
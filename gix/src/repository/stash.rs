@@ -0,0 +1,472 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use gix_hash::ObjectId;
+use gix_index::entry;
+use gix_object::tree::EntryMode;
+use gix_odb::Write;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use super::apply::{create_symlink, entry_mode, is_executable, set_entry, set_executable};
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    ext::ObjectIdExt,
+    prelude::{Find, FindExt},
+    stash::{self, Error},
+    Id,
+};
+
+const STASH_REF: &str = "refs/stash";
+
+/// Files by path along with their id and mode.
+type Files = BTreeMap<BString, (ObjectId, EntryMode)>;
+
+/// Stashing
+impl crate::Repository {
+    /// Save all changes in the index and the work tree compared to the `HEAD` commit in a new stash and reset the index and
+    /// the work tree to the `HEAD` commit, similar to `git stash push`.
+    ///
+    /// Return the id of the commit recording the stash, or `None` if there were no local changes to stash.
+    /// Note that the work tree is compared to the index by content, and that files are written and read without applying
+    /// any filters.
+    pub fn stash_push(&self, options: &stash::PushOptions) -> Result<Option<Id<'_>>, Error> {
+        let root = self.work_dir().ok_or(Error::MissingWorkTree)?;
+        let head = self.head_commit().map_err(Box::new)?;
+        let head_tree = head.tree_id()?.detach();
+        let branch = match self.head_name()? {
+            Some(name) => name.shorten().to_owned(),
+            None => "(no branch)".into(),
+        };
+        let description = format!("{branch}: {} {}", head.id().shorten_or_id(), head.message()?.summary());
+
+        let mut index = self.open_index()?;
+        if index.entries().iter().any(|e| e.stage() != 0) {
+            return Err(Error::UnmergedIndex);
+        }
+        let index_tree = self.write_index_tree(&mut index)?;
+
+        let mut worktree = (*index).clone();
+        let mut changed = Vec::new();
+        for (entry, path) in worktree.entries_mut_with_paths() {
+            if entry.mode == entry::Mode::COMMIT || entry.flags.contains(entry::Flags::SKIP_WORKTREE) {
+                continue;
+            }
+            match self.worktree_file(root, path, true)? {
+                Some((id, mode)) if id == entry.id && mode == entry_mode(entry.mode) => continue,
+                Some((id, mode)) => {
+                    entry.id = id;
+                    entry.mode = super::apply::index_mode(mode);
+                }
+                None => entry.flags.insert(entry::Flags::REMOVE),
+            }
+            changed.push(path.to_owned());
+        }
+        for path in changed {
+            worktree.invalidate_tree_for_path(path.as_ref());
+        }
+        let worktree_tree = self.write_index_tree(&mut worktree)?;
+        let untracked = if options.include_untracked {
+            self.untracked_files(root, &index, true)?
+        } else {
+            Files::default()
+        };
+        if index_tree == head_tree && worktree_tree == head_tree && untracked.is_empty() {
+            return Ok(None);
+        }
+
+        let committer = self.committer().ok_or(crate::commit::Error::CommitterMissing)?;
+        let committer = committer.map_err(crate::commit::Error::from)?;
+        let commit = |message: String, tree: ObjectId, parents: Vec<ObjectId>| {
+            self.write_commit(gix_object::Commit {
+                tree,
+                parents: parents.into(),
+                author: committer.to_owned(),
+                committer: committer.to_owned(),
+                encoding: None,
+                message: message.into(),
+                extra_headers: Default::default(),
+            })
+            .map(|(id, _)| id.detach())
+        };
+        let mut parents = vec![
+            head.id,
+            commit(format!("index on {description}\n"), index_tree, vec![head.id])?,
+        ];
+        if !untracked.is_empty() {
+            let mut state = gix_index::State::new(self.object_hash());
+            for (path, (id, mode)) in &untracked {
+                set_entry(&mut state, path.as_ref(), Some((*id, *mode)));
+            }
+            state.sort_entries();
+            let tree = self.write_index_tree(&mut state)?;
+            parents.push(commit(format!("untracked files on {description}\n"), tree, Vec::new())?);
+        }
+        let message = match &options.message {
+            Some(message) => format!("On {branch}: {message}"),
+            None => format!("WIP on {description}"),
+        };
+        let id = commit(message.clone(), worktree_tree, parents)?;
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: true,
+                    message: message.into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(id),
+            },
+            name: STASH_REF.try_into().expect("valid"),
+            deref: false,
+        })?;
+
+        let (target, mut new_index) = if options.keep_index {
+            (index_tree, None)
+        } else {
+            (head_tree, Some(self.index_from_tree(head_tree)?))
+        };
+        let (current, target) = (self.tree_files(worktree_tree)?, self.tree_files(target)?);
+        for path in current.keys().chain(target.keys()).collect::<BTreeSet<_>>() {
+            if current.get(path) != target.get(path) {
+                self.write_worktree_file(root, path.as_ref(), target.get(path))?;
+            }
+        }
+        for path in untracked.keys() {
+            self.write_worktree_file(root, path.as_ref(), None)?;
+        }
+        if let Some(state) = new_index.take() {
+            self.write_index(&mut gix_index::File::from_state(state, self.index_path()))?;
+        }
+        Ok(Some(id.attach(self)))
+    }
+
+    /// List all stashes, most recent first, similar to `git stash list`.
+    pub fn stash_list(&self) -> Result<Vec<stash::Entry>, Error> {
+        let reference = match self.try_find_reference(STASH_REF)? {
+            Some(reference) => reference,
+            None => return Ok(Vec::new()),
+        };
+        let mut log = reference.log_iter();
+        let lines = match log.rev().map_err(Error::OpenReflog)? {
+            Some(lines) => lines,
+            None => return Ok(Vec::new()),
+        };
+        let mut out = Vec::new();
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            out.push(stash::Entry {
+                index,
+                id: line.new_oid,
+                message: line.message,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Return the changes recorded in the stash at `index`, with `0` being the most recent one, compared to the commit that
+    /// was checked out when stashing, similar to `git stash show --name-status`.
+    ///
+    /// Note that stashed untracked files aren't included.
+    pub fn stash_show(&self, index: usize) -> Result<Vec<stash::Change>, Error> {
+        let stash = self.stash_commit(index)?;
+        let (base, stashed) = (self.tree_files(stash.base_tree)?, self.tree_files(stash.tree)?);
+        Ok(base
+            .keys()
+            .chain(stashed.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|path| {
+                let kind = match (base.get(path), stashed.get(path)) {
+                    (None, Some(_)) => stash::ChangeKind::Addition,
+                    (Some(_), None) => stash::ChangeKind::Deletion,
+                    (Some(previous), Some(current)) if previous != current => stash::ChangeKind::Modification,
+                    _ => return None,
+                };
+                Some(stash::Change {
+                    location: path.clone(),
+                    kind,
+                })
+            })
+            .collect())
+    }
+
+    /// Merge the changes recorded in the stash at `index`, with `0` being the most recent one, into the work tree and the
+    /// index with `options`, similar to `git stash apply`.
+    ///
+    /// Only local changes to files that aren't affected by the stash are allowed. Files with conflicts contain conflict
+    /// markers and are recorded as entries of stage 1 to 3 in the index.
+    pub fn stash_apply(&self, index: usize, options: &stash::ApplyOptions) -> Result<stash::Outcome, Error> {
+        let root = self.work_dir().ok_or(Error::MissingWorkTree)?;
+        let stash = self.stash_commit(index)?;
+        let mut index = self.open_index()?;
+        if index.entries().iter().any(|e| e.stage() != 0) {
+            return Err(Error::UnmergedIndex);
+        }
+        let current_tree = self.write_index_tree(&mut index)?;
+
+        let mut merge_options = options.merge.clone();
+        merge_options
+            .blob
+            .ours_label
+            .get_or_insert_with(|| "Updated upstream".into());
+        merge_options
+            .blob
+            .theirs_label
+            .get_or_insert_with(|| "Stashed changes".into());
+        let merge = self.merge_trees_opts(stash.base_tree, current_tree, stash.tree, &merge_options)?;
+        let (current, merged) = (self.tree_files(current_tree)?, self.tree_files(merge.tree.detach())?);
+        let changed: BTreeSet<_> = current
+            .keys()
+            .chain(merged.keys())
+            .filter(|path| current.get(*path) != merged.get(*path))
+            .collect();
+        for path in &changed {
+            if self.worktree_file(root, path.as_ref(), false)? != current.get(*path).map(|(id, mode)| (*id, *mode)) {
+                return Err(Error::WouldOverwrite { path: (*path).clone() });
+            }
+        }
+        let untracked = match stash.untracked_tree {
+            Some(tree) => self.tree_files(tree)?,
+            None => Files::default(),
+        };
+        for path in untracked.keys() {
+            if std::fs::symlink_metadata(root.join(gix_path::from_bstr(path.as_bstr()))).is_ok() {
+                return Err(Error::UntrackedExists { path: path.clone() });
+            }
+        }
+        let restored_index = if options.index && merge.is_clean() && stash.index_tree != stash.base_tree {
+            let outcome = self.merge_trees_opts(stash.base_tree, current_tree, stash.index_tree, &merge_options)?;
+            if !outcome.is_clean() {
+                return Err(Error::IndexConflict);
+            }
+            Some(outcome.tree.detach())
+        } else {
+            None
+        };
+
+        for path in &changed {
+            self.write_worktree_file(root, path.as_ref(), merged.get(*path))?;
+        }
+        for (path, file) in &untracked {
+            self.write_worktree_file(root, path.as_ref(), Some(file))?;
+        }
+        if !merge.is_clean() {
+            let mut state = self.index_from_tree(merge.tree.detach())?;
+            merge.write_conflicts_to(&mut state);
+            self.write_index(&mut gix_index::File::from_state(state, self.index_path()))?;
+        } else if let Some(tree) = restored_index {
+            self.write_index(&mut gix_index::File::from_state(
+                self.index_from_tree(tree)?,
+                self.index_path(),
+            ))?;
+        } else {
+            // Just like `git`, files added by the stash are added to the index so they don't appear untracked.
+            for (path, file) in &merged {
+                if !current.contains_key(path) {
+                    set_entry(&mut index, path.as_ref(), Some(*file));
+                }
+            }
+            index.sort_entries();
+            self.write_index(&mut index)?;
+        }
+        Ok(stash::Outcome {
+            conflicts: merge.conflicts,
+        })
+    }
+
+    /// Apply the stash at `index` like [`stash_apply()`][Self::stash_apply()] and drop it if there were no conflicts,
+    /// similar to `git stash pop`.
+    pub fn stash_pop(&self, index: usize, options: &stash::ApplyOptions) -> Result<stash::Outcome, Error> {
+        let outcome = self.stash_apply(index, options)?;
+        if outcome.is_clean() {
+            self.stash_drop(index)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Remove the stash at `index`, with `0` being the most recent one, and return the id of its commit, similar to
+    /// `git stash drop`.
+    ///
+    /// `refs/stash` is deleted along with its reflog once the last stash is dropped.
+    pub fn stash_drop(&self, index: usize) -> Result<ObjectId, Error> {
+        let stashes = self.stash_list()?;
+        let dropped = stashes.get(index).ok_or(Error::NotFound { index })?.id;
+        let name: gix_ref::FullName = STASH_REF.try_into().expect("valid");
+        if stashes.len() == 1 {
+            self.edit_reference(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::Any,
+                    log: RefLog::AndReference,
+                },
+                name,
+                deref: false,
+            })?;
+            return Ok(dropped);
+        }
+
+        // Lines are passed oldest first. Dropping the most recent stash also points the reference to the one before it.
+        let line_to_remove = stashes.len() - 1 - index;
+        let mut line_index = 0;
+        let keep = |_: &gix_ref::file::log::LineRef<'_>| {
+            let keep = line_index != line_to_remove;
+            line_index += 1;
+            keep
+        };
+        let lock_mode = gix_lock::acquire::Fail::Immediately;
+        if index == 0 {
+            self.refs.reflog_retain_and_update_ref(name.as_ref(), lock_mode, keep)
+        } else {
+            self.refs.reflog_retain(name.as_ref(), lock_mode, keep)
+        }
+        .map_err(Error::RewriteReflog)?;
+        Ok(dropped)
+    }
+
+    fn stash_commit(&self, index: usize) -> Result<StashCommit, Error> {
+        let stash = self
+            .stash_list()?
+            .into_iter()
+            .nth(index)
+            .ok_or(Error::NotFound { index })?;
+        let commit = self.find_object(stash.id)?.try_into_commit()?;
+        let tree_of = |id: ObjectId| -> Result<ObjectId, Error> {
+            Ok(self.find_object(id)?.try_into_commit()?.tree_id()?.detach())
+        };
+        let parents: Vec<_> = commit.parent_ids().map(Id::detach).collect();
+        let base = *parents.first().ok_or(Error::NotFound { index })?;
+        Ok(StashCommit {
+            tree: commit.tree_id()?.detach(),
+            base_tree: tree_of(base)?,
+            index_tree: match parents.get(1) {
+                Some(id) => tree_of(*id)?,
+                None => tree_of(base)?,
+            },
+            untracked_tree: parents.get(2).map(|id| tree_of(*id)).transpose()?,
+        })
+    }
+
+    fn write_index_tree(&self, index: &mut gix_index::State) -> Result<ObjectId, Error> {
+        Ok(index.write_tree(|tree| self.write_object(tree).map(Id::detach))?)
+    }
+
+    fn index_from_tree(&self, tree: ObjectId) -> Result<gix_index::State, Error> {
+        Ok(gix_index::State::from_tree(&tree, |oid, buf| {
+            self.objects.find_tree_iter(oid, buf).ok()
+        })?)
+    }
+
+    fn tree_files(&self, tree: ObjectId) -> Result<Files, Error> {
+        let state = self.index_from_tree(tree)?;
+        Ok(state
+            .entries()
+            .iter()
+            .map(|e| (e.path(&state).to_owned(), (e.id, entry_mode(e.mode))))
+            .collect())
+    }
+
+    /// Return the id and mode of the file at `path` in the work tree at `root`, or `None` if there is no file.
+    /// If `write` is true, the file is written to the object database as well.
+    fn worktree_file(&self, root: &Path, path: &BStr, write: bool) -> Result<Option<(ObjectId, EntryMode)>, Error> {
+        let fs_path = root.join(gix_path::from_bstr(path));
+        let io_err = |source| Error::Io {
+            path: fs_path.clone(),
+            source,
+        };
+        let meta = match std::fs::symlink_metadata(&fs_path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_err(err)),
+        };
+        let (data, mode) = if meta.file_type().is_symlink() {
+            let target = std::fs::read_link(&fs_path).map_err(io_err)?;
+            (gix_path::into_bstr(target).into_owned().into(), EntryMode::Link)
+        } else if meta.is_file() {
+            let mode = if is_executable(&meta) {
+                EntryMode::BlobExecutable
+            } else {
+                EntryMode::Blob
+            };
+            (std::fs::read(&fs_path).map_err(io_err)?, mode)
+        } else {
+            return Ok(None);
+        };
+        let id = gix_odb::sink(self.object_hash())
+            .write_buf(gix_object::Kind::Blob, &data)
+            .map_err(io_err)?;
+        if write && !self.objects.contains(id) {
+            self.write_blob(&data)?;
+        }
+        Ok(Some((id, mode)))
+    }
+
+    /// Write the file `file` to `path` in the work tree at `root`, or delete the file at `path` along with directories it
+    /// leaves empty if it is `None`.
+    fn write_worktree_file(&self, root: &Path, path: &BStr, file: Option<&(ObjectId, EntryMode)>) -> Result<(), Error> {
+        let fs_path = root.join(gix_path::from_bstr(path));
+        let io_err = |source| Error::Io {
+            path: fs_path.clone(),
+            source,
+        };
+        match std::fs::symlink_metadata(&fs_path) {
+            Ok(meta) if !meta.is_dir() => std::fs::remove_file(&fs_path).map_err(io_err)?,
+            _ => {}
+        }
+        let (id, mode) = match file {
+            Some((id, mode)) if *mode != EntryMode::Commit => (*id, *mode),
+            Some(_) => return Ok(()),
+            None => {
+                let mut dir = fs_path.clone();
+                while dir.pop() && dir != root && std::fs::remove_dir(&dir).is_ok() {}
+                return Ok(());
+            }
+        };
+        if let Some(parent) = fs_path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        let data = self.find_object(id)?.detach().data;
+        if mode == EntryMode::Link {
+            create_symlink(&gix_path::from_bstr(data.as_bstr()), &fs_path).map_err(io_err)?;
+        } else {
+            std::fs::write(&fs_path, &data).map_err(io_err)?;
+            if mode == EntryMode::BlobExecutable {
+                set_executable(&fs_path).map_err(io_err)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return all files in the work tree at `root` which aren't tracked in `index` and aren't ignored, after writing them
+    /// to the object database if `write` is true.
+    fn untracked_files(&self, root: &Path, index: &gix_index::State, write: bool) -> Result<Files, Error> {
+        let mut paths = Vec::new();
+        self.visit_untracked_files(root, index, |path| {
+            paths.push(path);
+            true
+        })?;
+        let mut out = Files::default();
+        for path in paths {
+            if let Some(file) = self.worktree_file(root, path.as_ref(), write)? {
+                out.insert(path, file);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// The trees recorded by a stash commit.
+struct StashCommit {
+    /// The tree of the work tree.
+    tree: ObjectId,
+    /// The tree of the commit that was checked out when stashing.
+    base_tree: ObjectId,
+    /// The tree of the index.
+    index_tree: ObjectId,
+    /// The tree with all untracked files, if they were stashed.
+    untracked_tree: Option<ObjectId>,
+}
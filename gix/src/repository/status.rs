@@ -0,0 +1,176 @@
+#![allow(clippy::result_large_err)]
+use std::{collections::BTreeMap, path::Path};
+
+use gix_hash::ObjectId;
+use gix_index::entry;
+use gix_odb::Write;
+
+use super::apply::is_executable;
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    prelude::FindExt,
+    worktree::status::Error,
+};
+
+/// Status
+impl crate::Repository {
+    /// Return true if the index differs from the `HEAD` commit, if the work tree differs from the index, or if there are
+    /// untracked files which aren't ignored, which is when `git status` would show changes.
+    ///
+    /// Like `git`, files are only read if their stat information doesn't match the one in the index, or if they were
+    /// modified in the same moment the index was written.
+    pub(crate) fn has_local_changes(&self) -> Result<bool, Error> {
+        let root = self.work_dir().ok_or(Error::MissingWorkTree)?;
+        let head = match self.head()?.id() {
+            Some(id) => {
+                let tree = id.object()?.try_into_commit()?.tree_id()?;
+                gix_index::State::from_tree(&tree, |oid, buf| self.objects.find_tree_iter(oid, buf).ok())?
+            }
+            None => gix_index::State::new(self.object_hash()),
+        };
+        let index = match self.open_index() {
+            Ok(index) => index.into(),
+            Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                gix_index::State::new(self.object_hash())
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if index.entries().len() != head.entries().len() {
+            return Ok(true);
+        }
+
+        let head: BTreeMap<&BStr, (ObjectId, entry::Mode)> =
+            head.entries().iter().map(|e| (e.path(&head), (e.id, e.mode))).collect();
+        let options = self.config.checkout_options(self.git_dir())?;
+        for entry in index.entries() {
+            let path = entry.path(&index);
+            if entry.stage() != 0 || head.get(path) != Some(&(entry.id, entry.mode)) {
+                return Ok(true);
+            }
+            if entry.mode == entry::Mode::COMMIT || entry.flags.contains(entry::Flags::SKIP_WORKTREE) {
+                continue;
+            }
+            if self.is_modified_in_worktree(root, path, entry, &index, &options)? {
+                return Ok(true);
+            }
+        }
+
+        let mut has_untracked_files = false;
+        self.visit_untracked_files(root, &index, |_| {
+            has_untracked_files = true;
+            false
+        })?;
+        Ok(has_untracked_files)
+    }
+
+    /// Call `visit` with the path of each file in the work tree at `root` which isn't tracked in `index` and isn't ignored,
+    /// until it returns false.
+    pub(crate) fn visit_untracked_files(
+        &self,
+        root: &Path,
+        index: &gix_index::State,
+        mut visit: impl FnMut(BString) -> bool,
+    ) -> Result<(), Error> {
+        let tracked: std::collections::BTreeSet<&BStr> = index.entries().iter().map(|e| e.path(index)).collect();
+        let mut excludes = self.worktree().ok_or(Error::MissingWorkTree)?.excludes(index, None)?;
+        let mut dirs = vec![BString::default()];
+        while let Some(dir) = dirs.pop() {
+            let fs_dir = root.join(gix_path::from_bstr(dir.as_bstr()));
+            let io_err = |source| Error::Io {
+                path: fs_dir.clone(),
+                source,
+            };
+            for entry in std::fs::read_dir(&fs_dir).map_err(io_err)? {
+                let entry = entry.map_err(io_err)?;
+                let name = gix_path::into_bstr(std::path::PathBuf::from(entry.file_name())).into_owned();
+                if name == ".git" {
+                    continue;
+                }
+                let mut path = dir.clone();
+                if !path.is_empty() {
+                    path.push(b'/');
+                }
+                path.push_str(&name);
+                let file_type = entry.file_type().map_err(io_err)?;
+                let is_dir = file_type.is_dir();
+                if is_dir && entry.path().join(".git").exists() {
+                    continue;
+                }
+                if !is_dir && tracked.contains(path.as_bstr()) {
+                    continue;
+                }
+                let is_excluded = excludes
+                    .at_entry(path.as_bstr(), Some(is_dir), |id, buf| self.objects.find_blob(id, buf))
+                    .map_err(io_err)?
+                    .is_excluded();
+                if is_excluded {
+                    continue;
+                }
+                if is_dir {
+                    dirs.push(path);
+                } else if (file_type.is_file() || file_type.is_symlink()) && !visit(path) {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return true if the file of `entry` at `path` in the work tree at `root` differs from what's recorded in `index`,
+    /// whose timestamp is used to detect racily clean entries.
+    fn is_modified_in_worktree(
+        &self,
+        root: &Path,
+        path: &BStr,
+        entry: &gix_index::Entry,
+        index: &gix_index::State,
+        options: &gix_worktree::index::checkout::Options,
+    ) -> Result<bool, Error> {
+        let fs_path = root.join(gix_path::from_bstr(path));
+        let io_err = |source| Error::Io {
+            path: fs_path.clone(),
+            source,
+        };
+        let meta = match std::fs::symlink_metadata(&fs_path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+            Err(err) => return Err(io_err(err)),
+        };
+        let is_symlink = meta.file_type().is_symlink();
+        let mode = if is_symlink || (entry.mode == entry::Mode::SYMLINK && !options.fs.symlink && meta.is_file()) {
+            entry::Mode::SYMLINK
+        } else if !meta.is_file() {
+            return Ok(true);
+        } else if !options.fs.executable_bit && entry.mode != entry::Mode::SYMLINK {
+            entry.mode
+        } else if is_executable(&meta) {
+            entry::Mode::FILE_EXECUTABLE
+        } else {
+            entry::Mode::FILE
+        };
+        if mode != entry.mode {
+            return Ok(true);
+        }
+
+        let timestamp = index.timestamp();
+        let is_racy = timestamp.unix_seconds() < i64::from(entry.stat.mtime.secs)
+            || (timestamp.unix_seconds() == i64::from(entry.stat.mtime.secs)
+                && timestamp.nanoseconds() <= entry.stat.mtime.nsecs);
+        if !is_racy && gix_worktree::index::is_unchanged(entry, &meta, options.check_stat) {
+            return Ok(false);
+        }
+        let data = if is_symlink {
+            gix_path::into_bstr(std::fs::read_link(&fs_path).map_err(io_err)?)
+                .into_owned()
+                .into()
+        } else {
+            std::fs::read(&fs_path).map_err(io_err)?
+        };
+        let id = gix_odb::sink(self.object_hash())
+            .write_buf(gix_object::Kind::Blob, &data)
+            .map_err(io_err)?;
+        Ok(id != entry.id)
+    }
+}
@@ -1,4 +1,21 @@
-use crate::{worktree, Worktree};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    worktree::{
+        self,
+        proxy::{remove_worktrees_dir_if_empty, write_gitdir_file},
+    },
+    Worktree,
+};
 
 /// Worktree iteration
 impl crate::Repository {
@@ -27,6 +44,450 @@ impl crate::Repository {
     }
 }
 
+/// Worktree management
+impl crate::Repository {
+    /// Create a new linked worktree at `path` and check out the commit and branch as configured by `options`, similar to
+    /// `git worktree add`, and return it as repository.
+    ///
+    /// `path` must not exist or be an empty directory. Its private git directory is created in the `worktrees` directory
+    /// of the common git directory, named after the last component of `path`. The checkout is performed just like
+    /// [when cloning][crate::clone::PrepareCheckout::main_worktree()], and the `post-checkout` hook is run afterwards.
+    ///
+    /// If anything fails after the private git directory was created, it is removed along with the work tree if it was created
+    /// by us. Branches that were created remain though, like in `git`.
+    #[allow(clippy::result_large_err)]
+    pub fn add_worktree(
+        &self,
+        path: impl AsRef<Path>,
+        options: &worktree::add::Options,
+        progress: impl crate::Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<crate::Repository, worktree::add::Error> {
+        use worktree::add::Error;
+        let path = path.as_ref();
+        let base_exists = path.exists();
+        if base_exists && std::fs::read_dir(path).map_or(true, |mut entries| entries.next().is_some()) {
+            return Err(Error::DestinationExists { path: path.to_owned() });
+        }
+        // Registered work trees are recorded by their real path, which also normalizes paths that don't exist (anymore).
+        let real_path = gix_path::realpath(path)?;
+        for proxy in self.worktrees().map_err(Error::ListWorktrees)? {
+            if proxy
+                .base()
+                .ok()
+                .and_then(|base| gix_path::realpath(base).ok())
+                .map_or(false, |base| base == real_path)
+            {
+                return Err(Error::AlreadyRegistered { path: path.to_owned() });
+            }
+        }
+        let name: BString = path
+            .file_name()
+            .map(|name| gix_path::into_bstr(PathBuf::from(name)).into_owned())
+            .ok_or_else(|| Error::InvalidName { path: path.to_owned() })?;
+        let id = sanitized_id(name.as_ref());
+        if id.is_empty() {
+            return Err(Error::InvalidName { path: path.to_owned() });
+        }
+
+        let local_branch = |name: &BStr| -> Result<Option<FullName>, Error> {
+            let name: FullName = match format!("refs/heads/{name}").try_into() {
+                Ok(name) => name,
+                Err(_) => return Ok(None),
+            };
+            Ok(self.try_find_reference(&name)?.map(|_| name))
+        };
+        let mut new_branch = options.new_branch.clone();
+        let mut branch = None;
+        if !options.detach && new_branch.is_none() {
+            match &options.commit {
+                Some(spec) => branch = local_branch(spec.as_ref())?,
+                None => match local_branch(name.as_ref())? {
+                    Some(existing) => branch = Some(existing),
+                    None => new_branch = Some(name.clone()),
+                },
+            }
+        }
+        let spec = match &branch {
+            Some(branch) => branch.as_bstr(),
+            None => options.commit.as_ref().map_or("HEAD".into(), AsRef::as_ref),
+        };
+        let commit_id = self
+            .rev_parse_single(spec)?
+            .object()?
+            .peel_to_kind(gix_object::Kind::Commit)?
+            .id;
+
+        if let Some(branch) = branch.as_ref().filter(|_| !options.force) {
+            if let Some(path) = self.worktree_with_branch(branch)? {
+                return Err(Error::BranchCheckedOut {
+                    branch: branch.shorten().to_owned(),
+                    path,
+                });
+            }
+        }
+        if let Some(new_branch) = new_branch {
+            let name: FullName = format!("refs/heads/{new_branch}").try_into()?;
+            self.reference(
+                name.clone(),
+                commit_id,
+                PreviousValue::MustNotExist,
+                format!("branch: Created from {spec}"),
+            )?;
+            branch = Some(name);
+        }
+
+        let worktrees = self.common_dir().join("worktrees");
+        let io_err = |path: &Path| {
+            let path = path.to_owned();
+            move |source| Error::Io { path, source }
+        };
+        std::fs::create_dir_all(&worktrees).map_err(io_err(&worktrees))?;
+        let mut git_dir = worktrees.join(gix_path::from_bstr(id.as_bstr()));
+        let mut counter = 1;
+        loop {
+            match std::fs::create_dir(&git_dir) {
+                Ok(()) => break,
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let mut numbered = id.clone();
+                    numbered.push_str(counter.to_string());
+                    git_dir = worktrees.join(gix_path::from_bstr(numbered.as_bstr()));
+                    counter += 1;
+                }
+                Err(err) => return Err(io_err(&git_dir)(err)),
+            }
+        }
+
+        let res = (|| {
+            let write = |path: PathBuf, content: &[u8]| std::fs::write(&path, content).map_err(io_err(&path));
+            write(git_dir.join("locked"), b"initializing\n")?;
+            std::fs::create_dir_all(path).map_err(io_err(path))?;
+            let base = gix_path::realpath(path)?;
+            write_gitdir_file(&git_dir, &base).map_err(io_err(&git_dir.join("gitdir")))?;
+            let mut dot_git = BString::from("gitdir: ");
+            dot_git.push_str(gix_path::into_bstr(gix_path::realpath(&git_dir)?).as_ref());
+            dot_git.push(b'\n');
+            write(base.join(gix_discover::DOT_GIT_DIR), &dot_git)?;
+            write(git_dir.join("commondir"), b"../..\n")?;
+            write(
+                git_dir.join("HEAD"),
+                format!("{}\n", self.object_hash().null()).as_bytes(),
+            )?;
+            self.copy_worktree_local_files(&git_dir)?;
+
+            let repo: crate::Repository =
+                crate::ThreadSafeRepository::open_from_paths(git_dir.clone(), Some(base), self.options.clone())?.into();
+            repo.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: Default::default(),
+                    },
+                    expected: PreviousValue::Any,
+                    new: match branch {
+                        Some(branch) => Target::Symbolic(branch),
+                        None => Target::Peeled(commit_id),
+                    },
+                },
+                name: "HEAD".try_into().expect("valid"),
+                deref: false,
+            })?;
+            if !options.no_checkout {
+                crate::clone::checkout::main_worktree::checkout_head(
+                    &repo,
+                    commit_id,
+                    progress,
+                    should_interrupt,
+                    true,
+                )?;
+            }
+            match &options.lock_reason {
+                Some(reason) => {
+                    let mut content = reason.clone();
+                    if !content.is_empty() {
+                        content.push(b'\n');
+                    }
+                    write(git_dir.join("locked"), &content)?;
+                }
+                None => std::fs::remove_file(git_dir.join("locked")).map_err(io_err(&git_dir.join("locked")))?,
+            }
+            Ok(repo)
+        })();
+        if res.is_err() {
+            std::fs::remove_dir_all(&git_dir).ok();
+            if base_exists {
+                for entry in std::fs::read_dir(path).into_iter().flatten().flatten() {
+                    let path = entry.path();
+                    std::fs::remove_dir_all(&path)
+                        .or_else(|_| std::fs::remove_file(&path))
+                        .ok();
+                }
+            } else {
+                std::fs::remove_dir_all(path).ok();
+            }
+            remove_worktrees_dir_if_empty(self);
+        }
+        res
+    }
+
+    /// Remove the private git directories of all worktrees that are [prunable][worktree::Proxy::prunable()] as their
+    /// work tree was deleted, similar to `git worktree prune`, and return them in order of their name.
+    ///
+    /// Locked worktrees are never pruned. The `worktrees` directory itself is removed if it's empty afterwards.
+    pub fn prune_worktrees(
+        &self,
+        options: &worktree::prune::Options,
+    ) -> Result<Vec<worktree::prune::Pruned>, worktree::prune::Error> {
+        use worktree::prune::{reason, Error, Pruned};
+        let worktrees = self.common_dir().join("worktrees");
+        let io_err = |path: &Path| {
+            let path = path.to_owned();
+            move |source| Error::Io { path, source }
+        };
+        let mut entries = match std::fs::read_dir(&worktrees) {
+            Ok(entries) => entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(io_err(&worktrees))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(io_err(&worktrees)(err)),
+        };
+        entries.sort();
+
+        let mut out = Vec::new();
+        for git_dir in entries {
+            let reason = match reason(&git_dir, options.expire) {
+                Some(reason) => reason,
+                None => continue,
+            };
+            if !options.dry_run {
+                if git_dir.is_dir() {
+                    std::fs::remove_dir_all(&git_dir)
+                } else {
+                    std::fs::remove_file(&git_dir)
+                }
+                .map_err(io_err(&git_dir))?;
+            }
+            out.push(Pruned {
+                id: gix_path::into_bstr(PathBuf::from(git_dir.file_name().expect("directory entry"))).into_owned(),
+                reason,
+            });
+        }
+        if !options.dry_run {
+            remove_worktrees_dir_if_empty(self);
+        }
+        Ok(out)
+    }
+
+    /// Rewrite the `.git` files in the work trees of all linked worktrees which don't point to their private git directory
+    /// anymore, similar to `git worktree repair` without arguments, and return what was repaired.
+    ///
+    /// This is useful after the repository was moved. Worktrees whose work tree doesn't exist are skipped.
+    pub fn repair_worktrees(&self) -> Result<Vec<worktree::repair::Repair>, worktree::repair::Error> {
+        use worktree::repair::{Error, Problem, Repair};
+        let mut out = Vec::new();
+        let proxies = self.worktrees().map_err(|source| Error::Io {
+            path: self.common_dir().join("worktrees"),
+            source,
+        })?;
+        for proxy in proxies {
+            let base = match proxy.base() {
+                Ok(base) if base.is_dir() => base,
+                _ => continue,
+            };
+            let dot_git = base.join(gix_discover::DOT_GIT_DIR);
+            if dot_git.is_dir() {
+                continue;
+            }
+            let git_dir = gix_path::realpath(proxy.git_dir())?;
+            let problem = match gix_discover::path::from_gitdir_file(&dot_git) {
+                Ok(target) => {
+                    (gix_path::realpath(target).ok().as_ref() != Some(&git_dir)).then_some(Problem::DotGitFileIncorrect)
+                }
+                Err(_) => Some(Problem::DotGitFileBroken),
+            };
+            if let Some(problem) = problem {
+                write_dot_git_file(&dot_git, &git_dir)?;
+                out.push(Repair { path: dot_git, problem });
+            }
+        }
+        Ok(out)
+    }
+
+    /// Rewrite the `gitdir` file of the linked worktree whose work tree is at `path` if it doesn't point to it anymore, similar
+    /// to `git worktree repair <path>`, and return what was repaired.
+    ///
+    /// This is useful after the work tree was moved manually. If the `.git` file in the work tree is broken, its private git
+    /// directory is inferred from the name of the directory it points to within the `worktrees` directory of this repository,
+    /// and the `.git` file is rewritten as well.
+    pub fn repair_worktree_at(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<worktree::repair::Repair>, worktree::repair::Error> {
+        use worktree::repair::{Error, Problem, Repair};
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return Err(Error::NotADirectory { path: path.to_owned() });
+        }
+        let base = gix_path::realpath(path)?;
+        let dot_git = base.join(gix_discover::DOT_GIT_DIR);
+        if dot_git.is_dir() {
+            return Err(Error::NotALinkedWorktree { path: path.to_owned() });
+        }
+
+        let mut out = Vec::new();
+        let git_dir = match gix_discover::path::from_gitdir_file(&dot_git) {
+            Ok(git_dir) if git_dir.is_dir() => git_dir,
+            res => {
+                let inferred = res
+                    .ok()
+                    .and_then(|git_dir| {
+                        git_dir
+                            .file_name()
+                            .map(|id| self.common_dir().join("worktrees").join(id))
+                    })
+                    .filter(|git_dir| git_dir.is_dir())
+                    .ok_or_else(|| Error::MissingRepository { path: path.to_owned() })?;
+                let inferred = gix_path::realpath(inferred)?;
+                write_dot_git_file(&dot_git, &inferred)?;
+                out.push(Repair {
+                    path: dot_git.clone(),
+                    problem: Problem::DotGitFileBroken,
+                });
+                inferred
+            }
+        };
+
+        let gitdir_file = git_dir.join("gitdir");
+        let problem = match gix_discover::path::from_plain_file(&gitdir_file) {
+            Some(Ok(location)) => (location != dot_git).then_some(Problem::GitDirFileIncorrect),
+            _ => Some(Problem::GitDirFileUnreadable),
+        };
+        if let Some(problem) = problem {
+            write_gitdir_file(&git_dir, &base).map_err(|source| Error::Io {
+                path: gitdir_file.clone(),
+                source,
+            })?;
+            out.push(Repair {
+                path: gitdir_file,
+                problem,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Copy the sparse-checkout patterns and the worktree configuration of the current worktree into the
+    /// private `git_dir` of a new worktree, just like `git` does, so it inherits its sparse checkout.
+    #[allow(clippy::result_large_err)]
+    fn copy_worktree_local_files(&self, git_dir: &Path) -> Result<(), worktree::add::Error> {
+        use crate::config::{cache::util::ApplyLeniency, tree::Core};
+        use worktree::add::Error;
+
+        let sparse_checkout = self
+            .config
+            .resolved
+            .boolean_by_key("core.sparseCheckout")
+            .map(|value| Core::SPARSE_CHECKOUT.enrich_error(value))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+            .unwrap_or(false);
+        if sparse_checkout {
+            let patterns = self.git_dir().join("info").join("sparse-checkout");
+            if patterns.is_file() {
+                let info = git_dir.join("info");
+                std::fs::create_dir_all(&info)
+                    .and_then(|_| std::fs::copy(&patterns, info.join("sparse-checkout")))
+                    .map_err(|source| Error::Io { path: info, source })?;
+            }
+        }
+
+        if self.worktree_config_enabled()? {
+            let path = self.git_dir().join("config.worktree");
+            let mut config = match gix_config::File::from_path_no_includes(path.clone(), gix_config::Source::Worktree) {
+                Ok(config) => config,
+                Err(gix_config::file::init::from_paths::Error::Io(err))
+                    if err.kind() == std::io::ErrorKind::NotFound =>
+                {
+                    return Ok(())
+                }
+                Err(err) => return Err(Error::ReadConfig { path, source: err }),
+            };
+            let remove_bare = matches!(config.boolean("core", None, "bare"), Some(Ok(true)));
+            let core_sections: Vec<_> = config
+                .sections_and_ids_by_name("core")
+                .map(|sections| {
+                    sections
+                        .filter(|(section, _)| section.header().subsection_name().is_none())
+                        .map(|(_, id)| id)
+                        .collect()
+                })
+                .unwrap_or_default();
+            for id in core_sections {
+                let mut section = config.section_mut_by_id(id).expect("id is valid");
+                while section.remove("worktree").is_some() {}
+                while remove_bare && section.remove("bare").is_some() {}
+            }
+            let path = git_dir.join("config.worktree");
+            std::fs::write(&path, config.to_bstring()).map_err(|source| Error::Io { path, source })?;
+        }
+        Ok(())
+    }
+
+    /// Return the work tree of the worktree which has `branch` checked out, if there is one.
+    #[allow(clippy::result_large_err)]
+    fn worktree_with_branch(&self, branch: &FullName) -> Result<Option<PathBuf>, worktree::add::Error> {
+        let main = self.main_repo()?;
+        if let Some(work_dir) = main.work_dir() {
+            if main.head_name()?.as_ref() == Some(branch) {
+                return Ok(Some(work_dir.to_owned()));
+            }
+        }
+        for proxy in self.worktrees().map_err(worktree::add::Error::ListWorktrees)? {
+            let head = self.try_find_reference(format!("worktrees/{}/HEAD", proxy.id()).as_str())?;
+            if head.map_or(false, |head| head.target().try_name() == Some(branch.as_ref())) {
+                return Ok(Some(proxy.base().unwrap_or_else(|_| proxy.git_dir().to_owned())));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Write the `.git` file at `dot_git` to point to the private `git_dir` of a worktree.
+fn write_dot_git_file(dot_git: &Path, git_dir: &Path) -> Result<(), worktree::repair::Error> {
+    let mut content = BString::from("gitdir: ");
+    content.push_str(gix_path::into_bstr(git_dir).as_ref());
+    content.push(b'\n');
+    std::fs::write(dot_git, content).map_err(|source| worktree::repair::Error::Io {
+        path: dot_git.to_owned(),
+        source,
+    })
+}
+
+/// Turn `name` into a name for the private git directory of a worktree that is valid as part of a reference name,
+/// just like `git` does.
+fn sanitized_id(name: &BStr) -> BString {
+    let mut id: BString = name
+        .iter()
+        .map(|&b| {
+            if b <= b' ' || b == 0x7f || b"~^:?*[\\".contains(&b) {
+                b'-'
+            } else {
+                b
+            }
+        })
+        .collect::<Vec<_>>()
+        .into();
+    while id.starts_with(b".") {
+        id.remove(0);
+    }
+    while id.ends_with(b".lock") {
+        let len = id.len() - ".lock".len();
+        id.truncate(len);
+    }
+    id
+}
+
 /// Interact with individual worktrees and their information.
 impl crate::Repository {
     /// Return the repository owning the main worktree, typically from a linked worktree.
@@ -87,6 +548,43 @@ impl crate::Repository {
         .map_err(Into::into)
     }
 
+    /// Write `index` to its path using [`index_write_options_for()`][crate::Repository::index_write_options_for()].
+    ///
+    /// If `index.sparse` is enabled along with a sparse checkout in cone mode, all directories outside of the
+    /// sparse-checkout cone are collapsed into sparse directory entries as far as the tree extension allows, otherwise
+    /// sparse directory entries are expanded so a full index is written.
+    #[allow(clippy::result_large_err)]
+    pub fn write_index(&self, index: &mut gix_index::File) -> Result<(), worktree::write_index::Error> {
+        use gix_odb::FindExt;
+
+        use crate::config::{cache::util::ApplyLeniency, tree::Index};
+
+        let options = self.index_write_options_for(index)?;
+        let sparse_index = self
+            .config
+            .resolved
+            .boolean_by_key("index.sparse")
+            .map(|value| Index::SPARSE.enrich_error(value))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+            .unwrap_or(false);
+        let patterns = match self.worktree() {
+            Some(worktree) if sparse_index => worktree.sparse_checkout_patterns()?,
+            _ => None,
+        };
+        match patterns.filter(|patterns| patterns.cone().is_some()) {
+            Some(patterns) => {
+                index.collapse_sparse_directories(|dir| patterns.is_excluded_directory(dir));
+            }
+            None if index.is_sparse() => {
+                index.expand_sparse_directories(|oid, buf| self.objects.find_tree_iter(oid, buf).ok())?;
+            }
+            None => {}
+        }
+        index.write(options)?;
+        Ok(())
+    }
+
     /// Return a shared worktree index which is updated automatically if the in-memory snapshot has become stale as the underlying file
     /// on disk has changed.
     ///
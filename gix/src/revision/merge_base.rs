@@ -0,0 +1,11 @@
+use gix_hash::ObjectId;
+
+/// The error returned by [`Repository::merge_base()`][crate::Repository::merge_base()] and related methods.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Traverse(#[from] gix_revision::merge_base::Error<gix_odb::store::find::Error>),
+    #[error("No merge-base was found between {first} and {second}")]
+    NotFound { first: ObjectId, second: ObjectId },
+}
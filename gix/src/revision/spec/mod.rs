@@ -1,4 +1,6 @@
-use crate::{ext::ReferenceExt, revision::Spec, Id, Reference};
+use gix_hash::ObjectId;
+
+use crate::{ext::ReferenceExt, revision, revision::Spec, Id, Reference};
 
 ///
 pub mod parse;
@@ -87,4 +89,36 @@ impl<'repo> Spec<'repo> {
             | gix_revision::Spec::IncludeOnlyParents { .. } => None,
         }
     }
+
+    /// Return a platform to walk all commits described by this instance, similar to `git rev-list <spec>`.
+    ///
+    /// For example, `a..b` walks all commits reachable from `b` but hides those reachable from `a`, and `a...b` walks all
+    /// commits reachable from either `a` or `b`, but hides those reachable from their merge-bases.
+    pub fn walk(&self) -> Result<revision::walk::Platform<'repo>, revision::walk::from_spec::Error> {
+        let repo = self.repo;
+        let commit = |id: ObjectId| -> Result<crate::Commit<'repo>, revision::walk::from_spec::Error> {
+            Ok(repo
+                .find_object(id)?
+                .peel_to_kind(gix_object::Kind::Commit)?
+                .into_commit())
+        };
+        let (tips, hidden) = match self.inner {
+            gix_revision::Spec::Include(id) => (vec![commit(id)?.id], Vec::new()),
+            gix_revision::Spec::Exclude(id) => (Vec::new(), vec![commit(id)?.id]),
+            gix_revision::Spec::Range { from, to } => (vec![commit(to)?.id], vec![commit(from)?.id]),
+            gix_revision::Spec::Merge { theirs, ours } => {
+                let (theirs, ours) = (commit(theirs)?.id, commit(ours)?.id);
+                let bases = repo.merge_bases(theirs, Some(ours))?;
+                (vec![theirs, ours], bases.into_iter().map(Id::detach).collect())
+            }
+            gix_revision::Spec::IncludeOnlyParents(id) => {
+                (commit(id)?.parent_ids().map(Id::detach).collect(), Vec::new())
+            }
+            gix_revision::Spec::ExcludeParents(id) => {
+                let commit = commit(id)?;
+                (vec![commit.id], commit.parent_ids().map(Id::detach).collect())
+            }
+        };
+        Ok(repo.rev_walk(tips).with_hidden(hidden))
+    }
 }
@@ -18,14 +18,14 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         if !self.err.is_empty() && self.refs[self.idx].is_some() {
             return None;
         }
-        match self.repo.refs.find(name) {
+        match find_reference(self.repo, name) {
             Ok(r) => {
                 assert!(self.refs[self.idx].is_none(), "BUG: cannot set the same ref twice");
                 self.refs[self.idx] = Some(r);
                 Some(())
             }
             Err(err) => {
-                self.err.push(err.into());
+                self.err.push(err);
                 None
             }
         }
@@ -40,8 +40,18 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         let mut candidates = Some(HashSet::default());
         self.prefix[self.idx] = Some(prefix);
 
+        let id_from_compat = match object_id_from_compat(self.repo, prefix) {
+            Ok(id) => id,
+            Err(err) => {
+                self.err.push(err.into());
+                return None;
+            }
+        };
         let empty_tree_id = gix_hash::ObjectId::empty_tree(prefix.as_oid().kind());
-        let res = if prefix.as_oid() == empty_tree_id {
+        let res = if let Some(id) = id_from_compat {
+            candidates.as_mut().expect("set").insert(id);
+            Ok(Some(Err(())))
+        } else if prefix.as_oid() == empty_tree_id {
             candidates.as_mut().expect("set").insert(empty_tree_id);
             Ok(Some(Err(())))
         } else {
@@ -62,7 +72,9 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
                 let candidates = candidates.expect("set above");
                 match self.opts.refs_hint {
                     RefsHint::PreferObjectOnFullLengthHexShaUseRefOtherwise
-                        if prefix.hex_len() == candidates.iter().next().expect("at least one").kind().len_in_hex() =>
+                        if id_from_compat.is_some()
+                            || prefix.hex_len()
+                                == candidates.iter().next().expect("at least one").kind().len_in_hex() =>
                     {
                         self.ambiguous_objects[self.idx] = Some(candidates.clone());
                         self.objs[self.idx] = Some(candidates);
@@ -74,7 +86,7 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
                         Some(())
                     }
                     RefsHint::PreferRef | RefsHint::PreferObjectOnFullLengthHexShaUseRefOtherwise | RefsHint::Fail => {
-                        match self.repo.refs.find(&prefix.to_string()) {
+                        match find_reference(self.repo, prefix.to_string().as_bytes().as_bstr()) {
                             Ok(ref_) => {
                                 assert!(self.refs[self.idx].is_none(), "BUG: cannot set the same ref twice");
                                 if self.opts.refs_hint == RefsHint::Fail {
@@ -163,7 +175,7 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
     fn nth_checked_out_branch(&mut self, branch_no: usize) -> Option<()> {
         self.unset_disambiguate_call();
         fn prior_checkouts_iter<'a>(
-            platform: &'a mut gix_ref::file::log::iter::Platform<'static, '_>,
+            platform: &'a mut gix_ref::store::reflog::Platform<'static, '_>,
         ) -> Result<impl Iterator<Item = (BString, ObjectId)> + 'a, Error> {
             match platform.rev().ok().flatten() {
                 Some(log) => Ok(log.filter_map(Result::ok).filter_map(|line| {
@@ -223,3 +235,22 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         None
     }
 }
+
+/// Full ids in the compatibility object format are translated into the ones of the repository, as long as they are known.
+fn object_id_from_compat(
+    repo: &crate::Repository,
+    prefix: gix_hash::Prefix,
+) -> Result<Option<ObjectId>, crate::compat::Error> {
+    match repo.compat_object_hash() {
+        Some(compat_hash) if prefix.hex_len() == compat_hash.len_in_hex() && prefix.as_oid().kind() == compat_hash => {
+            repo.object_id_from_compat(prefix.as_oid())
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Find the reference with the given partial `name` in `repo`, using the backend configured with `extensions.refStorage`.
+#[allow(clippy::result_large_err)]
+fn find_reference(repo: &crate::Repository, name: &BStr) -> Result<gix_ref::Reference, Error> {
+    Ok(repo.refs.find(name)?)
+}
@@ -100,11 +100,13 @@ pub enum Error {
     #[error(transparent)]
     FindHead(#[from] reference::find::existing::Error),
     #[error(transparent)]
+    CompatObjectMap(#[from] crate::compat::Error),
+    #[error(transparent)]
     Index(#[from] crate::worktree::open_index::Error),
     #[error(transparent)]
     RevWalkIterInit(#[from] crate::reference::iter::init::Error),
     #[error(transparent)]
-    RevWalkAllReferences(#[from] gix_ref::packed::buffer::open::Error),
+    RevWalkAllReferences(#[from] crate::reference::iter::Error),
     #[cfg(feature = "regex")]
     #[error(transparent)]
     InvalidRegex(#[from] regex::Error),
@@ -143,6 +145,8 @@ pub enum Error {
     #[error(transparent)]
     FindReference(#[from] gix_ref::file::find::existing::Error),
     #[error(transparent)]
+    FindStoreReference(#[from] gix_ref::store::find::existing::Error),
+    #[error(transparent)]
     FindObject(#[from] object::find::existing::Error),
     #[error(transparent)]
     LookupPrefix(#[from] gix_odb::store::prefix::lookup::Error),
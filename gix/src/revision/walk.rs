@@ -1,13 +1,14 @@
 use gix_hash::ObjectId;
 use gix_odb::FindExt;
 
-use crate::{revision, Repository};
+use crate::{ext::ObjectIdExt, revision, Repository};
 
 /// A platform to traverse the revision graph by adding starting points as well as points which shouldn't be crossed,
 /// returned by [`Repository::rev_walk()`].
 pub struct Platform<'repo> {
     pub(crate) repo: &'repo Repository,
     pub(crate) tips: Vec<ObjectId>,
+    pub(crate) hidden: Vec<ObjectId>,
     pub(crate) sorting: gix_traverse::commit::Sorting,
     pub(crate) parents: gix_traverse::commit::Parents,
 }
@@ -17,6 +18,7 @@ impl<'repo> Platform<'repo> {
         revision::walk::Platform {
             repo,
             tips: tips.into_iter().map(Into::into).collect(),
+            hidden: Vec::new(),
             sorting: Default::default(),
             parents: Default::default(),
         }
@@ -36,6 +38,15 @@ impl<'repo> Platform<'repo> {
         self.parents = gix_traverse::commit::Parents::First;
         self
     }
+
+    /// Don't return any of the commits reachable from `tips`, similar to `^tip` in `git rev-list`.
+    ///
+    /// These are looked up when the walk is [created][Platform::all()], which fails if any of them is missing unless it's
+    /// beyond the end of the history of a shallow clone.
+    pub fn with_hidden(mut self, tips: impl IntoIterator<Item = impl Into<ObjectId>>) -> Self {
+        self.hidden.extend(tips.into_iter().map(Into::into));
+        self
+    }
 }
 
 /// Produce the iterator
@@ -50,6 +61,7 @@ impl<'repo> Platform<'repo> {
         let Platform {
             repo,
             tips,
+            hidden,
             sorting,
             parents,
         } = self;
@@ -61,6 +73,7 @@ impl<'repo> Platform<'repo> {
                     gix_traverse::commit::ancestors::State::default(),
                     move |oid, buf| repo.objects.find_commit_iter(oid, buf),
                 )
+                .with_hidden_in_shallow_clone(hidden, shallow_commits(repo))?
                 .sorting(sorting)?
                 .parents(parents),
             ),
@@ -70,6 +83,64 @@ impl<'repo> Platform<'repo> {
     }
 }
 
+/// Return the commits at which the history of a shallow clone ends, as listed in the `shallow` file of the common directory.
+///
+/// The list is empty if the repository isn't shallow, or if the file can't be read, in which case missing commits are errors.
+fn shallow_commits(repo: &Repository) -> Vec<ObjectId> {
+    std::fs::read(repo.common_dir().join("shallow"))
+        .map(|data| {
+            data.split(|b| *b == b'\n')
+                .filter_map(|line| ObjectId::from_hex(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The side of a symmetric difference that a commit is reachable from, as returned by [`LeftRight`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// The commit can only be reached from the left-hand side, like `a` in `a...b`.
+    Left,
+    /// The commit can only be reached from the right-hand side, like `b` in `a...b`.
+    Right,
+}
+
+/// An iterator over all commits that are reachable from either the left or the right tip, but not both, returned by
+/// [`Repository::rev_walk_left_right()`].
+///
+/// All commits reachable only from the left tip are returned first, each side with the most recent commits first.
+pub struct LeftRight<'repo> {
+    pub(crate) left: std::vec::IntoIter<ObjectId>,
+    pub(crate) right: std::vec::IntoIter<ObjectId>,
+    pub(crate) repo: &'repo Repository,
+}
+
+impl<'repo> Iterator for LeftRight<'repo> {
+    type Item = (Side, crate::Id<'repo>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.left.next() {
+            Some(id) => Some((Side::Left, id.attach(self.repo))),
+            None => self.right.next().map(|id| (Side::Right, id.attach(self.repo))),
+        }
+    }
+}
+
+///
+pub mod from_spec {
+    /// The error returned by [`Spec::walk()`][crate::revision::Spec::walk()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindExisting(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        MergeBase(#[from] crate::revision::merge_base::Error),
+    }
+}
+
 pub(crate) mod iter {
     use crate::{ext::ObjectIdExt, Id};
 
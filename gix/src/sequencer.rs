@@ -0,0 +1,318 @@
+//! Apply or undo the changes of individual commits on top of other commits, similar to `git cherry-pick` and `git revert`.
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    merge,
+    prelude::FindExt,
+    Id, Repository,
+};
+
+/// The error returned by [`Repository::cherry_pick()`] and [`Repository::revert()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExisting(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    NotACommit(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Merge(#[from] merge::tree::Error),
+    #[error("Commit {commit} is a merge but no mainline parent was specified")]
+    MainlineMissing { commit: ObjectId },
+    #[error("Commit {commit} is not a merge but mainline parent {mainline} was specified")]
+    MainlineOnNonMerge { commit: ObjectId, mainline: usize },
+    #[error("Commit {commit} does not have a parent with number {mainline}")]
+    MainlineOutOfBounds { commit: ObjectId, mainline: usize },
+    #[error("Commit {commit} is empty and empty commits aren't allowed")]
+    EmptyCommit { commit: ObjectId },
+    #[error("The changes of commit {commit} are already present and would result in an empty commit")]
+    EmptyResult { commit: ObjectId },
+    #[error(transparent)]
+    Identity(#[from] crate::commit::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] crate::worktree::write_index::Error),
+    #[error("Could not write the state file at '{}'", .path.display())]
+    WriteState {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// What to do if applying the changes of a commit doesn't change anything, as they are already present.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Empty {
+    /// Fail with an error, which is what `git` does by default.
+    #[default]
+    Stop,
+    /// Don't create a commit, similar to `--empty=drop`.
+    Drop,
+    /// Create an empty commit, similar to `--empty=keep`.
+    Keep,
+}
+
+/// Options for use in [`Repository::cherry_pick()`] and [`Repository::revert()`].
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Options to control how the trees are merged.
+    ///
+    /// If the labels for conflict markers aren't set, they default to the ones `git` would use, like `HEAD` for ours and
+    /// `<short id>... <summary>` for the picked commit.
+    pub merge: merge::tree::Options,
+    /// The 1-based number of the parent to consider the mainline when picking or reverting a merge commit, similar to `-m`.
+    ///
+    /// It must be set for merge commits, and must not be set otherwise.
+    pub mainline: Option<usize>,
+    /// If true, append a line like `(cherry picked from commit <id>)` to the message of cherry-picked commits, similar to `-x`.
+    pub record_origin: bool,
+    /// If true, the author of cherry-picked commits is the configured author instead of the author of the picked commit.
+    ///
+    /// Note that the author of reverts is always the configured author.
+    pub reset_author: bool,
+    /// If true, commits that don't change anything compared to their parent are allowed and kept, similar to `--allow-empty`.
+    pub allow_empty: bool,
+    /// What to do if the changes of a commit are already present in the commit they are applied onto.
+    pub empty: Empty,
+}
+
+impl Options {
+    /// Create options for `repo` with merge options [as configured][merge::tree::Options::from_repo()].
+    pub fn from_repo(repo: &Repository) -> Result<Self, merge::tree::Error> {
+        Ok(Options {
+            merge: merge::tree::Options::from_repo(repo)?,
+            ..Default::default()
+        })
+    }
+}
+
+/// The operation that produced a [`Conflicted`] outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// A commit was cherry-picked.
+    CherryPick,
+    /// A commit was reverted.
+    Revert,
+}
+
+/// The result of [`Repository::cherry_pick()`] and [`Repository::revert()`].
+#[derive(Debug, Clone)]
+pub enum Outcome<'repo> {
+    /// The new commit, whose only parent is the commit the changes were applied onto.
+    ///
+    /// No reference was changed to point to it.
+    Commit(Id<'repo>),
+    /// No commit was created as the changes are already present, with [`Empty::Drop`].
+    Dropped,
+    /// The changes couldn't be applied without conflicts.
+    Conflict(Conflicted<'repo>),
+}
+
+/// Changes that couldn't be applied without conflicts, which can be [recorded][Conflicted::write_state()] for resolution by the user.
+#[derive(Debug, Clone)]
+pub struct Conflicted<'repo> {
+    /// The operation that caused the conflicts.
+    pub operation: Operation,
+    /// The commit that was picked or reverted.
+    pub commit: ObjectId,
+    /// The commit the changes were applied onto.
+    pub onto: ObjectId,
+    /// The message the commit would have had.
+    pub message: BString,
+    /// The outcome of the merge, with a tree containing conflict markers.
+    pub merge: merge::tree::Outcome<'repo>,
+}
+
+impl Conflicted<'_> {
+    /// Return an index with the entries of the [merged tree][merge::tree::Outcome::tree] and all conflicts as entries of
+    /// stage 1 to 3.
+    pub fn index(&self) -> Result<gix_index::State, Error> {
+        let repo = self.merge.tree.repo;
+        let mut index =
+            gix_index::State::from_tree(&self.merge.tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+        self.merge.write_conflicts_to(&mut index);
+        Ok(index)
+    }
+
+    /// Write the [conflicted index][Conflicted::index()] to the index file of the repository, the picked commit to
+    /// `CHERRY_PICK_HEAD` or `REVERT_HEAD`, and the message along with a list of conflicting files to `MERGE_MSG`, to leave
+    /// the repository in the state `git` would leave it in.
+    ///
+    /// Note that the working tree isn't changed, and that the previous index is overwritten.
+    pub fn write_state(&self) -> Result<(), Error> {
+        let repo = self.merge.tree.repo;
+        let mut index = gix_index::File::from_state(self.index()?, repo.index_path());
+        repo.write_index(&mut index)?;
+
+        let head = match self.operation {
+            Operation::CherryPick => "CHERRY_PICK_HEAD",
+            Operation::Revert => "REVERT_HEAD",
+        };
+        let mut message = self.message.clone();
+        message.push_str("\n# Conflicts:\n");
+        let mut locations: Vec<_> = self
+            .merge
+            .conflicts
+            .iter()
+            .flat_map(|conflict| conflict.stages().map(|(_, entry)| entry.location))
+            .collect();
+        locations.sort();
+        locations.dedup();
+        for location in locations {
+            message.push_str("#\t");
+            message.push_str(location);
+            message.push(b'\n');
+        }
+        for (name, content) in [(head, format!("{}\n", self.commit).into()), ("MERGE_MSG", message)] {
+            let path = repo.git_dir().join(name);
+            std::fs::write(&path, content).map_err(|source| Error::WriteState { path, source })?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn apply<'repo>(
+    repo: &'repo Repository,
+    operation: Operation,
+    commit: ObjectId,
+    onto: ObjectId,
+    options: &Options,
+) -> Result<Outcome<'repo>, Error> {
+    let picked = repo.find_object(commit)?.try_into_commit()?;
+    let picked_ref = picked.decode()?;
+    let parents: Vec<_> = picked_ref.parents().collect();
+    let parent = match (parents.len(), options.mainline) {
+        (0, None) => None,
+        (1, None) => Some(parents[0]),
+        (0 | 1, Some(mainline)) => return Err(Error::MainlineOnNonMerge { commit, mainline }),
+        (_, None) => return Err(Error::MainlineMissing { commit }),
+        (_, Some(mainline)) => Some(
+            *mainline
+                .checked_sub(1)
+                .and_then(|idx| parents.get(idx))
+                .ok_or(Error::MainlineOutOfBounds { commit, mainline })?,
+        ),
+    };
+    let parent_tree = match parent {
+        Some(parent) => repo.find_object(parent)?.try_into_commit()?.tree_id()?.detach(),
+        None => ObjectId::empty_tree(repo.object_hash()),
+    };
+    let tree = picked_ref.tree();
+    let is_empty = tree == parent_tree;
+    if is_empty && !options.allow_empty {
+        return Err(Error::EmptyCommit { commit });
+    }
+
+    let summary = picked_ref.message().summary();
+    let short_id = Id::from_id(commit, repo).shorten_or_id();
+    let (base, theirs, base_label, theirs_label) = match operation {
+        Operation::CherryPick => (
+            parent_tree,
+            tree,
+            format!("parent of {short_id}... {summary}"),
+            format!("{short_id}... {summary}"),
+        ),
+        Operation::Revert => (
+            tree,
+            parent_tree,
+            format!("{short_id}... {summary}"),
+            format!("parent of {short_id}... {summary}"),
+        ),
+    };
+    let mut merge_options = options.merge.clone();
+    merge_options.blob.ours_label.get_or_insert_with(|| "HEAD".into());
+    merge_options.blob.base_label.get_or_insert(base_label);
+    merge_options.blob.theirs_label.get_or_insert(theirs_label);
+
+    let onto_tree = repo.find_object(onto)?.try_into_commit()?.tree_id()?.detach();
+    let merge = repo.merge_trees_opts(base, onto_tree, theirs, &merge_options)?;
+
+    let message = match operation {
+        Operation::CherryPick => {
+            let mut message = picked_ref.message.to_owned();
+            if options.record_origin {
+                append_origin(&mut message, commit);
+            }
+            message
+        }
+        Operation::Revert => {
+            let mut message = BString::from(format!("Revert \"{summary}\"\n\nThis reverts commit {commit}"));
+            match parent.filter(|_| parents.len() > 1) {
+                Some(mainline) => message.push_str(format!(", reversing\nchanges made to {mainline}.\n")),
+                None => message.push_str(".\n"),
+            }
+            message
+        }
+    };
+    if !merge.is_clean() {
+        return Ok(Outcome::Conflict(Conflicted {
+            operation,
+            commit,
+            onto,
+            message,
+            merge,
+        }));
+    }
+    if merge.tree == onto_tree && !is_empty {
+        match options.empty {
+            Empty::Stop => return Err(Error::EmptyResult { commit }),
+            Empty::Drop => return Ok(Outcome::Dropped),
+            Empty::Keep => {}
+        }
+    }
+
+    let committer = repo.committer().ok_or(crate::commit::Error::CommitterMissing)?;
+    let committer = committer.map_err(crate::commit::Error::from)?;
+    let author = match operation {
+        Operation::CherryPick if !options.reset_author => picked_ref.author.to_owned(),
+        Operation::CherryPick | Operation::Revert => repo
+            .author()
+            .ok_or(crate::commit::Error::AuthorMissing)?
+            .map_err(crate::commit::Error::from)?
+            .to_owned(),
+    };
+    let new_commit = gix_object::Commit {
+        tree: merge.tree.detach(),
+        parents: Some(onto).into_iter().collect(),
+        author,
+        committer: committer.to_owned(),
+        encoding: picked_ref.encoding.map(ToOwned::to_owned),
+        message,
+        extra_headers: Default::default(),
+    };
+    Ok(Outcome::Commit(repo.write_commit(new_commit)?.0))
+}
+
+/// Append a line referring to the picked `commit` to `message`, which is separated by a blank line unless the message
+/// already ends with a paragraph of trailers.
+fn append_origin(message: &mut BString, commit: ObjectId) {
+    if !message.ends_with(b"\n") {
+        message.push(b'\n');
+    }
+    if !ends_with_trailers(message.as_ref()) {
+        message.push(b'\n');
+    }
+    message.push_str(format!("(cherry picked from commit {commit})\n"));
+}
+
+/// Return true if the last paragraph of `message` only consists of trailers like `Signed-off-by: name`, or lines added
+/// by previous picks, and isn't the title of the message.
+fn ends_with_trailers(message: &BStr) -> bool {
+    let message = message.trim_end();
+    let last_paragraph = match message.rfind(b"\n\n") {
+        Some(pos) => &message[pos + 2..],
+        None => return false,
+    };
+    last_paragraph.lines().all(|line| {
+        line.starts_with(b"(cherry picked from commit ")
+            || line.find(b": ").map_or(false, |pos| {
+                pos > 0 && line[..pos].iter().all(|b| b.is_ascii_alphanumeric() || *b == b'-')
+            })
+    })
+}
@@ -0,0 +1,364 @@
+//! Sign commits and tags with `gpg`, `gpgsm` or `ssh-keygen`, and [verify][verify] their signatures, similar to how `git` does it.
+//!
+//! Signing is configured with `gpg.format`, `user.signingKey` and `gpg.<format>.program`, and it's used by
+//! [`Repository::commit_as()`] if `commit.gpgSign` is set and by [`Repository::tag()`] if `tag.gpgSign` is set.
+use std::{
+    ffi::OsString,
+    io::Write,
+    path::PathBuf,
+    process::{Output, Stdio},
+};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    config::tree::{gpg, Gpg, User},
+    Repository,
+};
+
+///
+pub mod verify;
+
+/// The error returned by [`Signer::from_repo()`] and [`Signer::sign()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Format(#[from] crate::config::key::GenericErrorWithValue),
+    #[error(transparent)]
+    KeyPath(#[from] gix_config::path::interpolate::Error),
+    #[error("Either user.signingKey or gpg.ssh.defaultKeyCommand needs to be configured to sign with ssh")]
+    MissingSshKey,
+    #[error("gpg.ssh.defaultKeyCommand didn't produce a key: {stderr}")]
+    DefaultKeyCommand { stderr: BString },
+    #[error("Could not run '{}'", .program.to_string_lossy())]
+    Spawn { program: OsString, source: std::io::Error },
+    #[error("'{}' failed to sign the data: {stderr}", .program.to_string_lossy())]
+    Sign { program: OsString, stderr: BString },
+    #[error("Could not write or read temporary files for signing")]
+    Io(#[from] std::io::Error),
+}
+
+/// The kind of signature to create or verify, as configured by `gpg.format`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Create OpenPGP signatures using `gpg`.
+    #[default]
+    OpenPgp,
+    /// Create SSH signatures using `ssh-keygen`.
+    Ssh,
+    /// Create X.509 signatures using `gpgsm`.
+    X509,
+}
+
+impl Format {
+    /// Determine the format of `signature` by its first line, or return `None` if it's not a signature `git` knows.
+    pub fn from_signature(signature: &[u8]) -> Option<Self> {
+        Some(
+            if signature.starts_with(b"-----BEGIN PGP SIGNATURE-----")
+                || signature.starts_with(b"-----BEGIN PGP MESSAGE-----")
+            {
+                Format::OpenPgp
+            } else if signature.starts_with(b"-----BEGIN SSH SIGNATURE-----") {
+                Format::Ssh
+            } else if signature.starts_with(b"-----BEGIN SIGNED MESSAGE-----") {
+                Format::X509
+            } else {
+                return None;
+            },
+        )
+    }
+
+    /// The program to use if none is configured.
+    pub fn default_program(&self) -> &'static str {
+        match self {
+            Format::OpenPgp => "gpg",
+            Format::Ssh => "ssh-keygen",
+            Format::X509 => "gpgsm",
+        }
+    }
+}
+
+/// Return the name of the commit header that holds the signature of commits in repositories using `object_hash`.
+pub fn commit_header_name(object_hash: gix_hash::Kind) -> &'static str {
+    match object_hash {
+        gix_hash::Kind::Sha1 => "gpgsig",
+        gix_hash::Kind::Sha256 => "gpgsig-sha256",
+    }
+}
+
+/// Split the raw commit `data` into the payload that was signed and the signature of the [header][commit_header_name()] for
+/// `object_hash`, or return `None` if the commit isn't signed.
+///
+/// Like `git`, signature headers for all hash kinds are removed from the payload.
+pub fn split_signed_commit(data: &[u8], object_hash: gix_hash::Kind) -> Option<(BString, BString)> {
+    let header_name = commit_header_name(object_hash).as_bytes();
+    let mut payload = Vec::with_capacity(data.len());
+    let mut signature = None::<Vec<u8>>;
+    // `Some(is_ours)` while in the continuation lines of a signature header.
+    let mut in_signature = None;
+    let mut lines = data.lines_with_terminator();
+    for line in lines.by_ref() {
+        if line == b"\n" {
+            payload.extend_from_slice(line);
+            break;
+        }
+        if let (Some(continuation), Some(is_ours)) = (line.strip_prefix(b" "), in_signature) {
+            if is_ours {
+                signature.get_or_insert_with(Vec::new).extend_from_slice(continuation);
+            }
+            continue;
+        }
+        in_signature = None;
+        let (name, value) = line.split_once_str(b" ").unwrap_or((line, b""));
+        if name == b"gpgsig" || name.starts_with(b"gpgsig-") {
+            let is_ours = name == header_name;
+            if is_ours {
+                signature.get_or_insert_with(Vec::new).extend_from_slice(value);
+            }
+            in_signature = Some(is_ours);
+        } else {
+            payload.extend_from_slice(line);
+        }
+    }
+    for line in lines {
+        payload.extend_from_slice(line);
+    }
+    let mut signature = signature?;
+    if !signature.ends_with(b"\n") {
+        signature.push(b'\n');
+    }
+    Some((payload.into(), signature.into()))
+}
+
+/// Split the raw tag `data` into the payload that was signed and the signature that follows its message, or return `None`
+/// if the tag isn't signed.
+pub fn split_signed_tag(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let message_start = data.find(b"\n\n")? + 2;
+    let mut signature_start = None;
+    let mut pos = message_start;
+    for line in data[message_start..].lines_with_terminator() {
+        if Format::from_signature(line).is_some() {
+            signature_start = Some(pos);
+        }
+        pos += line.len();
+    }
+    let signature_start = signature_start?;
+    Some((&data[..signature_start], &data[signature_start..]))
+}
+
+/// Everything needed to sign data, typically [obtained from the configuration][Signer::from_repo()].
+#[derive(Debug, Clone)]
+pub struct Signer {
+    /// The kind of signature to create.
+    pub format: Format,
+    /// The program to run to create the signature.
+    pub program: OsString,
+    /// The key to sign with, which is passed verbatim to `gpg` and `gpgsm`.
+    ///
+    /// For [SSH signatures][Format::Ssh] it's either the path to a private key or public key, or a literal public key
+    /// starting with `ssh-` or `key::`. If unset, `default_key_command` is used to obtain a literal public key.
+    pub key: Option<BString>,
+    /// A command to run with `sh` whose first line of output is the literal public key to sign with if `key` isn't set,
+    /// for [SSH signatures][Format::Ssh] only.
+    pub default_key_command: Option<BString>,
+}
+
+impl Signer {
+    /// Create a signer from the configuration of `repo`, using `gpg.format`, `gpg.<format>.program` (or `gpg.program` for
+    /// OpenPGP) and `user.signingKey`.
+    ///
+    /// For OpenPGP and X.509, the key defaults to the committer identity, as in `Name <email>`.
+    /// Note that programs and keys are only read from trusted configuration files.
+    pub fn from_repo(repo: &Repository) -> Result<Self, Error> {
+        let format = repo.config.gpg_format()?;
+        let config = &repo.config.resolved;
+        let mut trusted_only = repo.filter_config_section();
+        let program = program(repo, format);
+
+        let key = match format {
+            Format::Ssh => match config.string_filter("user", None, User::SIGNING_KEY.name, &mut trusted_only) {
+                Some(key) if is_literal_ssh_key(key.as_ref()) => Some(key.into_owned()),
+                Some(_) => repo
+                    .config
+                    .trusted_file_path("user", None, User::SIGNING_KEY.name)
+                    .transpose()?
+                    .map(|path| gix_path::into_bstr(path).into_owned()),
+                None => None,
+            },
+            Format::OpenPgp | Format::X509 => config
+                .string_filter("user", None, User::SIGNING_KEY.name, &mut trusted_only)
+                .map(|key| key.into_owned())
+                .or_else(|| {
+                    let committer = repo.committer()?.ok()?;
+                    Some(format!("{} <{}>", committer.name, committer.email).into())
+                }),
+        };
+        let default_key_command = config
+            .string_filter(
+                "gpg",
+                Some("ssh".into()),
+                gpg::Ssh::DEFAULT_KEY_COMMAND.name,
+                &mut trusted_only,
+            )
+            .map(|command| command.into_owned());
+        Ok(Signer {
+            format,
+            program,
+            key,
+            default_key_command,
+        })
+    }
+
+    /// Sign `payload` and return the armored signature, which ends with a newline.
+    pub fn sign(&self, payload: &[u8]) -> Result<BString, Error> {
+        let mut signature = match self.format {
+            Format::OpenPgp | Format::X509 => self.sign_gpg(payload)?,
+            Format::Ssh => self.sign_ssh(payload)?,
+        };
+        signature.retain(|b| *b != b'\r');
+        Ok(signature)
+    }
+
+    fn sign_gpg(&self, payload: &[u8]) -> Result<BString, Error> {
+        let mut cmd = gix_command::prepare(self.program.clone()).arg("--status-fd=2");
+        cmd = match &self.key {
+            Some(key) => cmd.arg("-bsau").arg(gix_path::from_bstr(key.as_bstr()).into_owned()),
+            None => cmd.arg("-bsa"),
+        };
+        let output = run(cmd, Some(payload)).map_err(|source| self.spawn_failed(source))?;
+        let created = output
+            .stderr
+            .lines()
+            .any(|line| line.starts_with(b"[GNUPG:] SIG_CREATED "));
+        if !output.status.success() || !created || output.stdout.is_empty() {
+            return Err(self.failed(output.stderr));
+        }
+        Ok(output.stdout.into())
+    }
+
+    fn sign_ssh(&self, payload: &[u8]) -> Result<BString, Error> {
+        let key = match &self.key {
+            Some(key) => key.clone(),
+            None => self.default_key()?,
+        };
+        let literal_key = is_literal_ssh_key(key.as_ref());
+        let key_file = literal_key
+            .then(|| tempfile_with(key.strip_prefix(b"key::").unwrap_or(key.as_slice())))
+            .transpose()?;
+        let (_buffer_file, buffer_path) = tempfile_with(payload)?;
+
+        let mut cmd = gix_command::prepare(self.program.clone())
+            .args(["-Y", "sign", "-n", "git", "-f"])
+            .arg(match &key_file {
+                Some((_, path)) => path.clone().into_os_string(),
+                None => gix_path::from_bstr(key.as_bstr()).into_owned().into_os_string(),
+            });
+        if literal_key {
+            cmd = cmd.arg("-U");
+        }
+        let output = run(cmd.arg(&buffer_path), None).map_err(|source| self.spawn_failed(source))?;
+
+        let mut signature_path = buffer_path.into_os_string();
+        signature_path.push(".sig");
+        let signature = std::fs::read(&signature_path);
+        std::fs::remove_file(&signature_path).ok();
+        match signature {
+            Ok(signature) if output.status.success() => Ok(signature.into()),
+            _ => Err(self.failed(output.stderr)),
+        }
+    }
+
+    fn default_key(&self) -> Result<BString, Error> {
+        let command = self.default_key_command.as_ref().ok_or(Error::MissingSshKey)?;
+        let program = gix_path::from_bstr(command.as_bstr()).into_owned().into_os_string();
+        let output = gix_command::prepare(program.clone())
+            .with_shell()
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|child| child.wait_with_output())
+            .map_err(|source| Error::Spawn { program, source })?;
+        let key = output.stdout.lines().next().map(|line| line.trim().as_bstr());
+        match key {
+            Some(key) if output.status.success() && is_literal_ssh_key(key) => Ok(key.to_owned()),
+            _ => Err(Error::DefaultKeyCommand {
+                stderr: output.stderr.into(),
+            }),
+        }
+    }
+
+    fn spawn_failed(&self, source: std::io::Error) -> Error {
+        Error::Spawn {
+            program: self.program.clone(),
+            source,
+        }
+    }
+
+    fn failed(&self, stderr: Vec<u8>) -> Error {
+        Error::Sign {
+            program: self.program.clone(),
+            stderr: stderr.trim_end().into(),
+        }
+    }
+}
+
+/// Return the program to use for `format` from the trusted configuration of `repo`.
+fn program(repo: &Repository, format: Format) -> OsString {
+    let config = &repo.config.resolved;
+    let mut trusted_only = repo.filter_config_section();
+    let (subsection, key) = match format {
+        Format::OpenPgp => ("openpgp", &gpg::OpenPgp::PROGRAM),
+        Format::Ssh => ("ssh", &gpg::Ssh::PROGRAM),
+        Format::X509 => ("x509", &gpg::X509::PROGRAM),
+    };
+    config
+        .string_filter("gpg", Some(subsection.into()), key.name, &mut trusted_only)
+        .or_else(|| {
+            (format == Format::OpenPgp)
+                .then(|| config.string_filter("gpg", None, Gpg::PROGRAM.name, &mut trusted_only))
+                .flatten()
+        })
+        .map(|program| gix_path::from_bstr(program).into_owned().into_os_string())
+        .unwrap_or_else(|| format.default_program().into())
+}
+
+/// Run `cmd` with `stdin` as its input, if set, and collect its output.
+fn run(cmd: gix_command::Prepare, stdin: Option<&[u8]>) -> std::io::Result<Output> {
+    let mut child = cmd
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stderr(Stdio::piped())
+        .spawn()?;
+    match stdin {
+        Some(data) => {
+            let mut input = child.stdin.take().expect("configured");
+            // Feed the input while collecting the output, as programs may write before they read all of it.
+            std::thread::scope(|scope| {
+                let writer = scope.spawn(move || input.write_all(data));
+                let output = child.wait_with_output()?;
+                let written = writer.join().expect("writing doesn't panic");
+                // Programs that fail early don't read their input, but their own error is more helpful then.
+                if output.status.success() {
+                    written?;
+                }
+                Ok(output)
+            })
+        }
+        None => child.wait_with_output(),
+    }
+}
+
+/// Return true if `key` is a public key rather than a path to a key file.
+fn is_literal_ssh_key(key: &[u8]) -> bool {
+    key.starts_with(b"ssh-") || key.starts_with(b"key::")
+}
+
+/// Write `data` into a new file in the temporary directory which is removed when the returned handle is dropped, and return
+/// its path for use by programs that can only read files.
+fn tempfile_with(data: &[u8]) -> std::io::Result<(gix_tempfile::Handle<gix_tempfile::handle::Writable>, PathBuf)> {
+    let mut file = gix_tempfile::new(
+        std::env::temp_dir(),
+        gix_tempfile::ContainingDirectory::Exists,
+        gix_tempfile::AutoRemove::Tempfile,
+    )?;
+    let path = file.with_mut(|file| file.write_all(data).map(|_| file.path().to_owned()))??;
+    Ok((file, path))
+}
@@ -0,0 +1,315 @@
+//! Verify signatures of commits and tags with `gpg`, `gpgsm` or `ssh-keygen`.
+//!
+//! The program is chosen by the kind of signature, and SSH signatures are checked against the signers in
+//! `gpg.ssh.allowedSignersFile`, just like `git verify-commit` and `git verify-tag` do.
+use std::{ffi::OsString, path::PathBuf};
+
+use super::{program, run, tempfile_with, Format};
+use crate::{
+    bstr::{BString, ByteSlice},
+    config::tree::gpg,
+    Repository,
+};
+
+/// The error returned by [`Verifier::verify()`] and the `verify_signature()` methods of [commits][crate::Commit::verify_signature()]
+/// and [tags][crate::Tag::verify_signature()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FilePath(#[from] gix_config::path::interpolate::Error),
+    #[error("The signature doesn't have a known format")]
+    UnknownFormat,
+    #[error("gpg.ssh.allowedSignersFile needs to be configured for SSH signature verification")]
+    MissingAllowedSignersFile,
+    #[error("Could not run '{}'", .program.to_string_lossy())]
+    Spawn { program: OsString, source: std::io::Error },
+    #[error("Could not write the signature to a temporary file")]
+    Io(#[from] std::io::Error),
+}
+
+/// The result of checking a signature, similar to the `%G?` format of `git log`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The signature is valid.
+    Good,
+    /// The signature doesn't match the signed data, or couldn't be verified for other reasons.
+    Bad,
+    /// The signature couldn't be checked as its key isn't known.
+    ///
+    /// SSH signatures are also reported like this if they are valid, but were made by a key that isn't
+    /// in the allowed signers file, as anyone could have made them. Their key is still reported in this case.
+    UnknownKey,
+    /// The signature is valid but expired.
+    ExpiredSignature,
+    /// The signature is valid but was made by an expired key.
+    ExpiredKey,
+    /// The signature is valid but was made by a revoked key.
+    RevokedKey,
+}
+
+/// How much the key of a signature is trusted, in increasing order, similar to `gpg.minTrustLevel`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Trust {
+    /// Nothing is known about the key, which is the case for SSH keys that aren't in the allowed signers file.
+    #[default]
+    Undefined,
+    /// The key must not be trusted.
+    Never,
+    /// The key is trusted marginally.
+    Marginal,
+    /// The key is trusted fully, which is the case for SSH keys in the allowed signers file.
+    Fully,
+    /// The key is trusted ultimately, usually as it's one's own.
+    Ultimate,
+}
+
+/// The outcome of [verifying a signature][Verifier::verify()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The kind of signature that was verified.
+    pub format: Format,
+    /// The result of the verification.
+    pub status: Status,
+    /// How much the key of the signature is trusted.
+    pub trust: Trust,
+    /// The identity of the signer, like `Name <email>` for OpenPGP, or the principal from the allowed signers file for SSH.
+    pub signer: Option<BString>,
+    /// The id of the key as reported by `gpg` and `gpgsm`, or its fingerprint for SSH.
+    pub key: Option<BString>,
+    /// The fingerprint of the key, like `SHA256:…` for SSH.
+    pub fingerprint: Option<BString>,
+    /// The output of the program that verified the signature, for display to the user.
+    pub output: BString,
+}
+
+impl Outcome {
+    /// Return `true` if the signature is [good][Status::Good] and made by a key that is trusted at least `min_trust`.
+    pub fn is_trusted(&self, min_trust: Trust) -> bool {
+        self.status == Status::Good && self.trust >= min_trust
+    }
+}
+
+/// Everything needed to verify signatures, typically [obtained from the configuration][Verifier::from_repo()].
+///
+/// It can be reused to verify many signatures without reading the configuration each time.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    /// The program to verify OpenPGP signatures with.
+    pub openpgp_program: OsString,
+    /// The program to verify X.509 signatures with.
+    pub x509_program: OsString,
+    /// The program to verify SSH signatures with.
+    pub ssh_program: OsString,
+    /// The file with the principals and keys of trusted signers, in the format of `ssh-keygen`, which is required to
+    /// verify SSH signatures.
+    pub allowed_signers_file: Option<PathBuf>,
+    /// The file with revoked SSH keys, if set.
+    pub revocation_file: Option<PathBuf>,
+}
+
+impl Verifier {
+    /// Create a verifier from the configuration of `repo`, using `gpg.<format>.program` (or `gpg.program` for OpenPGP),
+    /// `gpg.ssh.allowedSignersFile` and `gpg.ssh.revocationFile`.
+    ///
+    /// Note that programs and files are only read from trusted configuration files.
+    pub fn from_repo(repo: &Repository) -> Result<Self, Error> {
+        let file = |key: &'static crate::config::tree::keys::Path| {
+            repo.config
+                .trusted_file_path("gpg", Some("ssh".into()), key.name)
+                .transpose()
+                .map(|path| path.map(|path| path.into_owned()))
+        };
+        Ok(Verifier {
+            openpgp_program: program(repo, Format::OpenPgp),
+            x509_program: program(repo, Format::X509),
+            ssh_program: program(repo, Format::Ssh),
+            allowed_signers_file: file(&gpg::Ssh::ALLOWED_SIGNERS_FILE)?,
+            revocation_file: file(&gpg::Ssh::REVOCATION_FILE)?,
+        })
+    }
+
+    /// Verify that `signature` was made for `payload`, using the program for the [format][Format::from_signature()] of
+    /// `signature`.
+    ///
+    /// Note that a failed verification isn't an error, but is indicated by the [status][Outcome::status] of the outcome.
+    /// Output of `gpg` or `gpgsm` with more than one result for the signature is considered [bad][Status::Bad].
+    pub fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<Outcome, Error> {
+        match Format::from_signature(signature).ok_or(Error::UnknownFormat)? {
+            format @ (Format::OpenPgp | Format::X509) => self.verify_gpg(format, payload, signature),
+            Format::Ssh => self.verify_ssh(payload, signature),
+        }
+    }
+
+    fn verify_gpg(&self, format: Format, payload: &[u8], signature: &[u8]) -> Result<Outcome, Error> {
+        let program = match format {
+            Format::X509 => &self.x509_program,
+            _ => &self.openpgp_program,
+        };
+        let (_signature_file, signature_path) = tempfile_with(signature)?;
+        let output = run(
+            gix_command::prepare(program.clone())
+                .args(["--status-fd=1", "--verify"])
+                .arg(signature_path)
+                .arg("-"),
+            Some(payload),
+        )
+        .map_err(|source| Error::Spawn {
+            program: program.clone(),
+            source,
+        })?;
+
+        let mut outcome = Outcome {
+            format,
+            status: Status::Bad,
+            trust: Trust::Undefined,
+            signer: None,
+            key: None,
+            fingerprint: None,
+            output: output.stderr.into(),
+        };
+        let mut seen_exclusive_status = false;
+        for line in output.stdout.lines() {
+            let line = match line.strip_prefix(b"[GNUPG:] ") {
+                Some(line) => line,
+                None => continue,
+            };
+            let (keyword, rest) = line.split_once_str(b" ").unwrap_or((line, b""));
+            let status = match keyword {
+                b"GOODSIG" => Status::Good,
+                b"BADSIG" => Status::Bad,
+                b"EXPSIG" => Status::ExpiredSignature,
+                b"EXPKEYSIG" => Status::ExpiredKey,
+                b"REVKEYSIG" => Status::RevokedKey,
+                b"ERRSIG" => Status::UnknownKey,
+                b"VALIDSIG" => {
+                    outcome.fingerprint = rest.split_str(b" ").next().map(Into::into);
+                    continue;
+                }
+                b"TRUST_UNDEFINED" | b"TRUST_NEVER" | b"TRUST_MARGINAL" | b"TRUST_FULLY" | b"TRUST_ULTIMATE" => {
+                    outcome.trust = match keyword {
+                        b"TRUST_NEVER" => Trust::Never,
+                        b"TRUST_MARGINAL" => Trust::Marginal,
+                        b"TRUST_FULLY" => Trust::Fully,
+                        b"TRUST_ULTIMATE" => Trust::Ultimate,
+                        _ => Trust::Undefined,
+                    };
+                    continue;
+                }
+                _ => continue,
+            };
+            if seen_exclusive_status {
+                // Like git, don't trust output that claims more than one result for a single signature.
+                outcome.status = Status::Bad;
+                outcome.trust = Trust::Undefined;
+                outcome.signer = None;
+                outcome.key = None;
+                outcome.fingerprint = None;
+                break;
+            }
+            seen_exclusive_status = true;
+            outcome.status = status;
+            let (key, signer) = rest.split_once_str(b" ").unwrap_or((rest, b""));
+            outcome.key = Some(key.into());
+            outcome.signer = (status != Status::UnknownKey && !signer.is_empty()).then(|| signer.into());
+        }
+        Ok(outcome)
+    }
+
+    fn verify_ssh(&self, payload: &[u8], signature: &[u8]) -> Result<Outcome, Error> {
+        let allowed_signers = self
+            .allowed_signers_file
+            .as_ref()
+            .ok_or(Error::MissingAllowedSignersFile)?;
+        let (_signature_file, signature_path) = tempfile_with(signature)?;
+        let program = &self.ssh_program;
+        let spawn_failed = |source| Error::Spawn {
+            program: program.clone(),
+            source,
+        };
+
+        let principals = run(
+            gix_command::prepare(program.clone())
+                .args(["-Y", "find-principals", "-f"])
+                .arg(allowed_signers)
+                .arg("-s")
+                .arg(&signature_path),
+            None,
+        )
+        .map_err(spawn_failed)?;
+        let principals: Vec<_> = if principals.status.success() {
+            principals
+                .stdout
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .map(ToOwned::to_owned)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut output = None;
+        for principal in &principals {
+            let mut cmd = gix_command::prepare(program.clone())
+                .args(["-Y", "verify", "-n", "git", "-f"])
+                .arg(allowed_signers)
+                .arg("-I")
+                .arg(gix_path::from_byte_slice(principal).as_os_str())
+                .arg("-s")
+                .arg(&signature_path);
+            if let Some(revocation_file) = &self.revocation_file {
+                cmd = cmd.arg("-r").arg(revocation_file);
+            }
+            let out = run(cmd, Some(payload)).map_err(spawn_failed)?;
+            let is_good = out.status.success();
+            output = Some(out);
+            if is_good {
+                break;
+            }
+        }
+        let output = match output {
+            Some(output) => output,
+            None => run(
+                gix_command::prepare(program.clone())
+                    .args(["-Y", "check-novalidate", "-n", "git", "-s"])
+                    .arg(&signature_path),
+                Some(payload),
+            )
+            .map_err(spawn_failed)?,
+        };
+
+        let mut outcome = Outcome {
+            format: Format::Ssh,
+            status: Status::Bad,
+            trust: Trust::Undefined,
+            signer: None,
+            key: None,
+            fingerprint: None,
+            output: [output.stdout.as_slice(), output.stderr.as_slice()].concat().into(),
+        };
+        if output.status.success() {
+            let line = output.stdout.lines().next().unwrap_or_default();
+            let (signer, key) = if let Some(rest) = line.strip_prefix(b"Good \"git\" signature for ") {
+                match rest.rfind(b" with ") {
+                    Some(pos) => (Some(&rest[..pos]), &rest[pos + 6..]),
+                    None => (None, rest),
+                }
+            } else if let Some(key) = line.strip_prefix(b"Good \"git\" signature with ") {
+                (None, key)
+            } else {
+                return Ok(outcome);
+            };
+            // Without a principal, the signature was only checked to be made by its own key, which could be anyone's.
+            (outcome.status, outcome.trust) = if signer.is_some() {
+                (Status::Good, Trust::Fully)
+            } else {
+                (Status::UnknownKey, Trust::Undefined)
+            };
+            outcome.signer = signer.map(Into::into);
+            outcome.fingerprint = key.rsplit_str(b" ").next().map(Into::into);
+            outcome.key = outcome.fingerprint.clone();
+        }
+        Ok(outcome)
+    }
+}
@@ -0,0 +1,146 @@
+//! Save local changes away and restore them later, similar to `git stash`.
+//!
+//! Stashes are stored just like `git` stores them, as commits whose first parent is the commit checked out when stashing,
+//! whose second parent records the index and whose optional third parent records untracked files, with all stashes being
+//! accessible through the reflog of `refs/stash`. This makes stashes created by `git` and `gitoxide` interchangeable.
+use std::path::PathBuf;
+
+use gix_hash::ObjectId;
+
+use crate::{bstr::BString, merge, Repository};
+
+/// The error returned by the `stash_*()` methods of [`Repository`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Stashing requires a work tree")]
+    MissingWorkTree,
+    #[error(transparent)]
+    HeadCommit(#[from] Box<crate::reference::head_commit::Error>),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error("Could not open the stash reflog")]
+    OpenReflog(#[source] gix_ref::store::reflog::Error),
+    #[error("Could not read the stash reflog")]
+    ReadReflog(#[from] gix_ref::file::log::iter::reverse::Error),
+    #[error("Could not rewrite the stash reflog")]
+    RewriteReflog(#[source] gix_ref::store::reflog::Error),
+    #[error("There is no stash at stash@{{{index}}}")]
+    NotFound { index: usize },
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error("The index has unresolved conflicts")]
+    UnmergedIndex,
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error<crate::object::write::Error>),
+    #[error(transparent)]
+    IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] crate::worktree::write_index::Error),
+    #[error(transparent)]
+    Status(#[from] crate::worktree::status::Error),
+    #[error(transparent)]
+    FindExisting(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    NotACommit(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    Identity(#[from] crate::commit::Error),
+    #[error(transparent)]
+    Merge(#[from] merge::tree::Error),
+    #[error("The changes in the stashed index conflict with the current index")]
+    IndexConflict,
+    #[error("The local changes to '{path}' would be overwritten")]
+    WouldOverwrite { path: BString },
+    #[error("The stashed untracked file '{path}' already exists")]
+    UntrackedExists { path: BString },
+    #[error("Could not access '{}' in the work tree", .path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+}
+
+/// Options for use in [`Repository::stash_push()`].
+#[derive(Debug, Clone, Default)]
+pub struct PushOptions {
+    /// The message to describe the stash with, or `None` to describe it like `WIP on <branch>: <short id> <summary>`.
+    pub message: Option<String>,
+    /// If true, untracked files are stashed and removed from the work tree as well, similar to `--include-untracked`.
+    ///
+    /// Ignored files are never stashed.
+    pub include_untracked: bool,
+    /// If true, the changes in the index are kept in the index and in the work tree, similar to `--keep-index`.
+    pub keep_index: bool,
+}
+
+/// Options for use in [`Repository::stash_apply()`] and [`Repository::stash_pop()`].
+#[derive(Debug, Clone, Default)]
+pub struct ApplyOptions {
+    /// If true, the changes that were in the index when stashing are restored in the index as well, similar to `--index`.
+    pub index: bool,
+    /// Options to control how the stashed changes are merged with the current ones.
+    ///
+    /// If the labels for conflict markers aren't set, they default to `Updated upstream` and `Stashed changes`.
+    pub merge: merge::tree::Options,
+}
+
+impl ApplyOptions {
+    /// Create options for `repo` with merge options [as configured][merge::tree::Options::from_repo()].
+    pub fn from_repo(repo: &Repository) -> Result<Self, merge::tree::Error> {
+        Ok(ApplyOptions {
+            index: false,
+            merge: merge::tree::Options::from_repo(repo)?,
+        })
+    }
+}
+
+/// A stash as listed by [`Repository::stash_list()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The position of the stash, with `0` being the most recent one, as in `stash@{0}`.
+    pub index: usize,
+    /// The id of the commit recording the stashed changes.
+    pub id: ObjectId,
+    /// The message describing the stash.
+    pub message: BString,
+}
+
+/// The kind of [`Change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The file was added.
+    Addition,
+    /// The file was deleted.
+    Deletion,
+    /// The content or the mode of the file changed.
+    Modification,
+}
+
+/// A change to a file recorded in a stash, as returned by [`Repository::stash_show()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// The path of the file relative to the root of the work tree.
+    pub location: BString,
+    /// The kind of change.
+    pub kind: ChangeKind,
+}
+
+/// The result of [`Repository::stash_apply()`] and [`Repository::stash_pop()`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// All conflicts that were encountered when merging the stashed changes into the work tree, which are recorded in the
+    /// index as entries of stage 1 to 3.
+    pub conflicts: Vec<merge::tree::Conflict>,
+}
+
+impl Outcome {
+    /// Return `true` if the stashed changes were applied without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
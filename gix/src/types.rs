@@ -145,6 +145,8 @@ pub struct Repository {
     pub(crate) common_dir: Option<PathBuf>,
     /// A free-list of re-usable object backing buffers
     pub(crate) bufs: RefCell<Vec<Vec<u8>>>,
+    /// The map between object ids of the repository and the ones of the compatibility object format, loaded on first use.
+    pub(crate) compat_object_map: RefCell<Option<crate::compat::ObjectMap>>,
     /// A pre-assembled selection of often-accessed configuration values for quick access.
     pub(crate) config: crate::config::Cache,
     /// the options obtained when instantiating this repository.
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use crate::bstr::BString;
+
+/// Options for use in [`Repository::add_worktree()`][crate::Repository::add_worktree()].
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// The revision to check out, like `main` or `HEAD~1`, or `None` to check out `HEAD`.
+    ///
+    /// If it names a local branch, this branch is checked out, otherwise `HEAD` of the new worktree will be detached.
+    /// If `None` and neither [`new_branch`][Options::new_branch] nor [`detach`][Options::detach] are set, a branch named
+    /// after the last component of the worktree path is checked out, and created at `HEAD` if it doesn't exist yet.
+    pub commit: Option<BString>,
+    /// The name of a new branch to create at `commit` and to check out, similar to `-b`.
+    pub new_branch: Option<BString>,
+    /// If true, `HEAD` of the new worktree will be detached even if `commit` names a local branch, similar to `--detach`.
+    pub detach: bool,
+    /// If set, the new worktree will be locked with the given reason, which may be empty, similar to `--lock` and `--reason`.
+    pub lock_reason: Option<BString>,
+    /// If true, only `HEAD` of the new worktree is set up, leaving its work tree empty and without index, similar to `--no-checkout`.
+    pub no_checkout: bool,
+    /// If true, check out a branch even if it's already checked out in another worktree, similar to `--force`.
+    pub force: bool,
+}
+
+/// The error returned by [`Repository::add_worktree()`][crate::Repository::add_worktree()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("'{}' already exists", path.display())]
+    DestinationExists { path: PathBuf },
+    #[error("'{}' is a missing but already registered worktree, prune it first", path.display())]
+    AlreadyRegistered { path: PathBuf },
+    #[error("Could not derive the name of a worktree from '{}'", path.display())]
+    InvalidName { path: PathBuf },
+    #[error("The branch '{branch}' is already checked out at '{}'", path.display())]
+    BranchCheckedOut { branch: BString, path: PathBuf },
+    #[error(transparent)]
+    RevParse(#[from] crate::revision::spec::parse::single::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    ReferenceName(#[from] gix_validate::reference::name::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    ListWorktrees(std::io::Error),
+    #[error(transparent)]
+    ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error("Could not read the worktree configuration at '{}'", path.display())]
+    ReadConfig {
+        path: PathBuf,
+        source: gix_config::file::init::from_paths::Error,
+    },
+    #[error("Could not write '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    Realpath(#[from] gix_path::realpath::Error),
+    #[error(transparent)]
+    Open(#[from] crate::open::Error),
+    #[error(transparent)]
+    Checkout(#[from] crate::clone::checkout::main_worktree::Error),
+}
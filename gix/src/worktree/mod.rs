@@ -69,6 +69,13 @@ pub(crate) fn id(git_dir: &std::path::Path, has_common_dir: bool) -> Option<&BSt
 ///
 pub mod proxy;
 
+///
+pub mod add;
+///
+pub mod prune;
+///
+pub mod repair;
+
 ///
 pub mod open_index {
     use crate::bstr::BString;
@@ -100,6 +107,68 @@ pub mod open_index {
     }
 }
 
+///
+pub mod write_index {
+    /// The error returned by [`Repository::write_index()`][crate::Repository::write_index()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        WriteOptions(#[from] crate::config::index_write_options::Error),
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        SparseCheckoutPatterns(#[from] super::sparse_checkout_patterns::Error),
+        #[error(transparent)]
+        ExpandSparseDirectories(#[from] gix_index::sparse::expand::Error),
+        #[error(transparent)]
+        Write(#[from] gix_index::file::write::Error),
+    }
+}
+
+///
+pub mod sparse_checkout_patterns {
+    /// The error returned by [`Worktree::sparse_checkout_patterns()`][crate::Worktree::sparse_checkout_patterns()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the sparse-checkout patterns")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+    }
+
+    impl<'repo> crate::Worktree<'repo> {
+        /// Read the patterns of the sparse checkout from `info/sparse-checkout` if `core.sparseCheckout` is enabled,
+        /// interpreting them in cone mode if `core.sparseCheckoutCone` is enabled.
+        ///
+        /// Return `None` if sparse checkouts are disabled or if there is no patterns file.
+        pub fn sparse_checkout_patterns(&self) -> Result<Option<gix_worktree::sparse::Patterns>, Error> {
+            use crate::config::{cache::util::ApplyLeniency, tree::Core};
+
+            let repo = self.parent;
+            let boolean = |full_key: &str, key: &'static crate::config::tree::keys::Boolean, default: bool| {
+                repo.config
+                    .resolved
+                    .boolean_by_key(full_key)
+                    .map(|value| key.enrich_error(value))
+                    .transpose()
+                    .with_leniency(repo.options.lenient_config)
+                    .map(|value| value.unwrap_or(default))
+            };
+            if !boolean("core.sparseCheckout", &Core::SPARSE_CHECKOUT, false)? {
+                return Ok(None);
+            }
+            let cone = boolean("core.sparseCheckoutCone", &Core::SPARSE_CHECKOUT_CONE, false)?;
+            Ok(gix_worktree::sparse::Patterns::from_git_dir(
+                repo.git_dir(),
+                cone,
+                &mut Vec::new(),
+            )?)
+        }
+    }
+}
+
 ///
 pub mod excludes {
     use std::path::PathBuf;
@@ -158,3 +227,73 @@ pub mod excludes {
         }
     }
 }
+
+///
+pub mod status {
+    use std::path::PathBuf;
+
+    /// The error returned when checking whether the work tree has local changes, for instance when
+    /// [removing a worktree][crate::worktree::Proxy::remove()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("A work tree is required to determine its status")]
+        MissingWorkTree,
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        FindExisting(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        NotACommit(#[from] crate::object::try_into::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+        #[error(transparent)]
+        OpenIndex(#[from] super::open_index::Error),
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error(transparent)]
+        Excludes(#[from] super::excludes::Error),
+        #[error("Could not access '{}' in the work tree", .path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
+///
+pub mod attributes {
+    /// The error returned by [`Worktree::attributes()`][crate::Worktree::attributes()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read repository attributes")]
+        Io(#[from] std::io::Error),
+    }
+
+    impl<'repo> crate::Worktree<'repo> {
+        /// Configure a file-system cache to look up the attributes of files below the repository, as defined in
+        /// `$GIT_DIR/info/attributes` and in `.gitattributes` files of the worktree, or of `index` if they are only present there.
+        pub fn attributes(&self, index: &gix_index::State) -> Result<gix_worktree::fs::Cache, Error> {
+            let repo = self.parent;
+            let case = if repo.config.ignore_case {
+                gix_glob::pattern::Case::Fold
+            } else {
+                gix_glob::pattern::Case::Sensitive
+            };
+            let mut buf = Vec::with_capacity(512);
+            let mut info = gix_attributes::MatchGroup::default();
+            info.add_patterns_file(repo.common_dir().join("info").join("attributes"), true, None, &mut buf)?;
+            let state = gix_worktree::fs::cache::State::for_attributes(
+                gix_worktree::fs::cache::state::Attributes::default().with_info(info),
+            );
+            let attribute_list = state.build_attribute_list(index, index.path_backing(), case);
+            Ok(gix_worktree::fs::Cache::new(
+                self.path,
+                state,
+                case,
+                buf,
+                attribute_list,
+            ))
+        }
+    }
+}
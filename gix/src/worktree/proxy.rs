@@ -24,6 +24,71 @@ pub mod into_repo {
     }
 }
 
+///
+pub mod lock {
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::lock()`][super::Proxy::lock()] and [`Proxy::unlock()`][super::Proxy::unlock()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The worktree '{id}' is already locked")]
+        AlreadyLocked { id: BString, reason: BString },
+        #[error("The worktree '{id}' is not locked")]
+        NotLocked { id: BString },
+        #[error("Could not write or delete the lock of the worktree")]
+        Io(#[from] std::io::Error),
+    }
+}
+
+///
+pub mod move_to {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::move_to()`][super::Proxy::move_to()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot move the locked worktree '{id}', unlock it first")]
+        Locked { id: BString, reason: BString },
+        #[error("Could not read the location of the worktree")]
+        Base(#[source] std::io::Error),
+        #[error("The worktree at '{}' doesn't exist and cannot be moved", base.display())]
+        MissingWorktree { base: PathBuf },
+        #[error("'{}' already exists", path.display())]
+        DestinationExists { path: PathBuf },
+        #[error(transparent)]
+        Realpath(#[from] gix_path::realpath::Error),
+        #[error("Could not move the worktree to '{}'", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
+///
+pub mod remove {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::remove()`][super::Proxy::remove()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot remove the locked worktree '{id}', unlock it first")]
+        Locked { id: BString, reason: BString },
+        #[error("'{}' contains modified or untracked files, use force to delete it", base.display())]
+        LocalChanges { base: PathBuf },
+        #[error(transparent)]
+        Open(#[from] crate::open::Error),
+        #[error("Could not determine if the worktree has local changes")]
+        Status(#[from] crate::worktree::status::Error),
+        #[error("Could not delete '{}'", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
 impl<'repo> Proxy<'repo> {
     pub(crate) fn new(parent: &'repo Repository, git_dir: impl Into<PathBuf>) -> Self {
         Proxy {
@@ -33,6 +98,7 @@ impl<'repo> Proxy<'repo> {
     }
 }
 
+/// Access
 impl<'repo> Proxy<'repo> {
     /// Read the location of the checkout, the base of the work tree.
     /// Note that the location might not exist.
@@ -74,6 +140,126 @@ impl<'repo> Proxy<'repo> {
             .map(|contents| contents.trim().into())
     }
 
+    /// Return the reason for this worktree to be pruned by [`Repository::prune_worktrees()`], or `None` if it will be kept.
+    ///
+    /// Note that the age of the worktree isn't considered, so worktrees whose work tree is missing are always prunable
+    /// unless they are locked.
+    pub fn prunable(&self) -> Option<super::prune::Reason> {
+        super::prune::reason(&self.git_dir, None)
+    }
+}
+
+/// Modification
+impl<'repo> Proxy<'repo> {
+    /// Lock this worktree to prevent it from being pruned, moved or removed, optionally with a `reason` which is typically
+    /// used to explain that it is stored on a device that isn't always available, similar to `git worktree lock`.
+    pub fn lock(&self, reason: Option<&BStr>) -> Result<(), lock::Error> {
+        if let Some(reason) = self.lock_reason() {
+            return Err(lock::Error::AlreadyLocked {
+                id: self.id().to_owned(),
+                reason,
+            });
+        }
+        let mut content = reason.map(ToOwned::to_owned).unwrap_or_default();
+        if !content.is_empty() {
+            content.push(b'\n');
+        }
+        std::fs::write(self.git_dir.join("locked"), content)?;
+        Ok(())
+    }
+
+    /// Unlock this worktree after it was [locked][Proxy::lock()], similar to `git worktree unlock`.
+    pub fn unlock(&self) -> Result<(), lock::Error> {
+        match std::fs::remove_file(self.git_dir.join("locked")) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(lock::Error::NotLocked {
+                id: self.id().to_owned(),
+            }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Move the work tree of this worktree to `destination` and return its new location, similar to `git worktree move`.
+    ///
+    /// If `destination` is an existing directory, the work tree is moved into it while keeping its name.
+    /// Locked worktrees can't be moved.
+    pub fn move_to(&self, destination: impl AsRef<Path>) -> Result<PathBuf, move_to::Error> {
+        if let Some(reason) = self.lock_reason() {
+            return Err(move_to::Error::Locked {
+                id: self.id().to_owned(),
+                reason,
+            });
+        }
+        let base = self.base().map_err(move_to::Error::Base)?;
+        if !base.is_dir() {
+            return Err(move_to::Error::MissingWorktree { base });
+        }
+        let mut destination = destination.as_ref().to_owned();
+        if destination.is_dir() {
+            if let Some(name) = base.file_name() {
+                destination.push(name);
+            }
+        }
+        if destination.exists() {
+            return Err(move_to::Error::DestinationExists { path: destination });
+        }
+        std::fs::rename(&base, &destination).map_err(|source| move_to::Error::Io {
+            path: destination.clone(),
+            source,
+        })?;
+        let destination = gix_path::realpath(&destination)?;
+        write_gitdir_file(&self.git_dir, &destination).map_err(|source| move_to::Error::Io {
+            path: self.git_dir.join("gitdir"),
+            source,
+        })?;
+        Ok(destination)
+    }
+
+    /// Delete the work tree of this worktree along with its private git directory, similar to `git worktree remove`.
+    ///
+    /// Unless `force` is true, worktrees with local changes or untracked files which aren't ignored won't be removed.
+    /// Locked worktrees can't be removed. If the work tree doesn't exist anymore, only the private git directory is removed.
+    pub fn remove(self, force: bool) -> Result<(), remove::Error> {
+        if let Some(reason) = self.lock_reason() {
+            return Err(remove::Error::Locked {
+                id: self.id().to_owned(),
+                reason,
+            });
+        }
+        if let Some(base) = self.base().ok().filter(|base| base.is_dir()) {
+            if !force
+                && self
+                    .clone()
+                    .into_repo_with_possibly_inaccessible_worktree()?
+                    .has_local_changes()?
+            {
+                return Err(remove::Error::LocalChanges { base });
+            }
+            std::fs::remove_dir_all(&base).map_err(|source| remove::Error::Io { path: base, source })?;
+        }
+        std::fs::remove_dir_all(&self.git_dir).map_err(|source| remove::Error::Io {
+            path: self.git_dir.clone(),
+            source,
+        })?;
+        remove_worktrees_dir_if_empty(self.parent);
+        Ok(())
+    }
+}
+
+/// Write the `gitdir` file into the private `git_dir` of a worktree to point to the `.git` file in its work tree at `base`.
+pub(crate) fn write_gitdir_file(git_dir: &Path, base: &Path) -> std::io::Result<()> {
+    let mut content = gix_path::into_bstr(base.join(gix_discover::DOT_GIT_DIR)).into_owned();
+    content.push(b'\n');
+    std::fs::write(git_dir.join("gitdir"), content)
+}
+
+/// Remove the `worktrees` directory of `repo` if it doesn't contain any worktrees anymore, just like `git` does.
+pub(crate) fn remove_worktrees_dir_if_empty(repo: &Repository) {
+    std::fs::remove_dir(repo.common_dir().join("worktrees")).ok();
+}
+
+/// Conversion
+impl<'repo> Proxy<'repo> {
     /// Transform this proxy into a [`Repository`] while ignoring issues reading `base()` and ignoring that it might not exist.
     ///
     /// Most importantly, the `Repository` might be initialized with a non-existing work tree directory as the checkout
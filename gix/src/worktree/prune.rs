@@ -0,0 +1,94 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::bstr::{BString, ByteSlice};
+
+/// The reason for a worktree to be prunable, as its work tree is gone or its administrative files are broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// The private git directory of the worktree isn't a directory.
+    NotADirectory,
+    /// The `gitdir` file which points to the work tree doesn't exist.
+    MissingGitDirFile,
+    /// The `gitdir` file couldn't be read.
+    UnreadableGitDirFile,
+    /// The `gitdir` file is empty.
+    InvalidGitDirFile,
+    /// The `gitdir` file points to a location that doesn't exist, so the work tree was deleted or moved.
+    MissingLocation,
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Reason::NotADirectory => "not a valid directory",
+            Reason::MissingGitDirFile => "gitdir file does not exist",
+            Reason::UnreadableGitDirFile => "unable to read gitdir file",
+            Reason::InvalidGitDirFile => "invalid gitdir file",
+            Reason::MissingLocation => "gitdir file points to non-existent location",
+        })
+    }
+}
+
+/// Options for use in [`Repository::prune_worktrees()`][crate::Repository::prune_worktrees()].
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// If true, only report which worktrees would be pruned without removing anything, similar to `--dry-run`.
+    pub dry_run: bool,
+    /// If set, worktrees whose work tree doesn't exist anymore are only pruned if their `gitdir` file was last modified
+    /// before this time, similar to `--expire`. Otherwise they are pruned right away.
+    pub expire: Option<SystemTime>,
+}
+
+/// A worktree that was pruned by [`Repository::prune_worktrees()`][crate::Repository::prune_worktrees()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pruned {
+    /// The name of the worktree, the name of its private git directory within the `worktrees` directory.
+    pub id: BString,
+    /// The reason it was pruned for.
+    pub reason: Reason,
+}
+
+/// The error returned by [`Repository::prune_worktrees()`][crate::Repository::prune_worktrees()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not access '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+}
+
+/// Return the reason for the worktree with the private `git_dir` to be pruned, or `None` if it must be kept.
+///
+/// If its work tree is missing, it's only prunable if its `gitdir` file was last modified before `expire`, if set.
+/// Locked worktrees are never prunable.
+pub(crate) fn reason(git_dir: &Path, expire: Option<SystemTime>) -> Option<Reason> {
+    if !git_dir.is_dir() {
+        return Some(Reason::NotADirectory);
+    }
+    if git_dir.join("locked").exists() {
+        return None;
+    }
+    let gitdir_file = git_dir.join("gitdir");
+    let modified = match std::fs::metadata(&gitdir_file) {
+        Ok(meta) => meta.modified().ok(),
+        Err(_) => return Some(Reason::MissingGitDirFile),
+    };
+    let content = match std::fs::read(&gitdir_file) {
+        Ok(content) => content,
+        Err(_) => return Some(Reason::UnreadableGitDirFile),
+    };
+    let location = content.trim_end();
+    if location.is_empty() {
+        return Some(Reason::InvalidGitDirFile);
+    }
+    let location = git_dir.join(gix_path::from_byte_slice(location));
+    if location.exists() {
+        return None;
+    }
+    match (expire, modified) {
+        (Some(expire), Some(modified)) if modified > expire => None,
+        _ => Some(Reason::MissingLocation),
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+/// A problem with the links between a linked worktree and its private git directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// The `.git` file in the work tree is missing or couldn't be read.
+    DotGitFileBroken,
+    /// The `.git` file in the work tree points to another directory than the private git directory of the worktree.
+    DotGitFileIncorrect,
+    /// The `gitdir` file in the private git directory of the worktree is missing or couldn't be read.
+    GitDirFileUnreadable,
+    /// The `gitdir` file in the private git directory of the worktree points to another location than the work tree.
+    GitDirFileIncorrect,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Problem::DotGitFileBroken => ".git file broken",
+            Problem::DotGitFileIncorrect => ".git file incorrect",
+            Problem::GitDirFileUnreadable => "gitdir unreadable",
+            Problem::GitDirFileIncorrect => "gitdir incorrect",
+        })
+    }
+}
+
+/// A problem that was fixed by rewriting a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    /// The file that was rewritten, either the `.git` file in a work tree or the `gitdir` file of a worktree.
+    pub path: PathBuf,
+    /// The problem that was fixed.
+    pub problem: Problem,
+}
+
+/// The error returned by [`Repository::repair_worktrees()`][crate::Repository::repair_worktrees()] and
+/// [`Repository::repair_worktree_at()`][crate::Repository::repair_worktree_at()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("'{}' is not a directory", path.display())]
+    NotADirectory { path: PathBuf },
+    #[error("'{}' is not a linked worktree as its .git isn't a file", path.display())]
+    NotALinkedWorktree { path: PathBuf },
+    #[error("Unable to locate the repository of the worktree at '{}' as its .git file is broken", path.display())]
+    MissingRepository { path: PathBuf },
+    #[error(transparent)]
+    Realpath(#[from] gix_path::realpath::Error),
+    #[error("Could not access '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+}
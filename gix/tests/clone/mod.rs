@@ -86,12 +86,10 @@ mod blocking_io {
             &out.ref_map.extra_refspecs.len() - 1,
             "mappings don't refer to non-existing implicit refspecs"
         );
-        let packed_refs = repo
-            .refs
-            .cached_packed_buffer()?
-            .expect("packed refs should be present");
+        let refs = repo.refs.file_store().expect("clones use loose references by default");
+        let packed_refs = refs.cached_packed_buffer()?.expect("packed refs should be present");
         assert_eq!(
-            repo.refs.loose_iter()?.count(),
+            refs.loose_iter()?.count(),
             2,
             "HEAD and an actual symbolic ref we received"
         );
@@ -225,7 +223,7 @@ mod blocking_io {
         Ok(())
     }
 
-    fn assert_reflog(log: std::io::Result<Option<gix_ref::file::log::iter::Forward<'_>>>) {
+    fn assert_reflog(log: Result<Option<gix_ref::file::log::iter::Forward<'_>>, gix_ref::store::reflog::Error>) {
         let lines = log
             .unwrap()
             .expect("log present")
@@ -267,6 +265,71 @@ mod blocking_io {
         Ok(())
     }
 
+    #[test]
+    fn fetch_and_checkout_from_sha256_remote() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            gix_testtools::scripted_fixture_read_only("make_sha256_repo.sh")?,
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted(),
+        )?;
+        let (mut checkout, out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        assert_eq!(out.ref_map.object_hash, gix_hash::Kind::Sha256);
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        assert_eq!(
+            repo.object_hash(),
+            gix_hash::Kind::Sha256,
+            "the object hash of the remote is adopted"
+        );
+        assert_eq!(
+            gix::open_opts(repo.git_dir(), restricted())?.object_hash(),
+            gix_hash::Kind::Sha256,
+            "it's persisted as well"
+        );
+        assert_eq!(repo.head_commit()?.message_raw()?, "second\n");
+        assert_eq!(repo.remote_names().len(), 1, "the remote was configured only once");
+        assert_eq!(repo.index()?.entries().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_and_checkout_runs_hooks() -> crate::Result {
+        let hooks = std::env::current_dir()?
+            .join(gix_testtools::scripted_fixture_read_only("make_hooks_repo.sh")?.join("hooks"));
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut opts = restricted().cli_overrides([format!("core.hooksPath={}", hooks.display())]);
+        opts.permissions.hooks.full = gix_sec::Permission::Allow;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            remote::repo("base").path(),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            opts,
+        )?;
+        let (mut checkout, _out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        let log = std::fs::read_to_string(repo.work_dir().expect("non-bare").join("hook.log"))?;
+        assert!(
+            log.contains("\nreference-transaction committed\n"),
+            "fetching updates references: {log}"
+        );
+        assert!(
+            log.ends_with(&format!(
+                "\npost-checkout {} {} 1\n",
+                repo.object_hash().null(),
+                repo.head_id()?
+            )),
+            "the checkout is announced last: {log}"
+        );
+        Ok(())
+    }
+
     #[test]
     fn fetch_and_checkout_empty_remote_repo() -> crate::Result {
         let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -189,6 +189,59 @@ mod diff {
     }
 }
 
+mod merge {
+    use gix::config::tree::{Key, Merge};
+    use gix_diff::blob::merge::ConflictStyle;
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn conflict_style() -> crate::Result {
+        for (actual, expected) in [
+            ("merge", ConflictStyle::Merge),
+            ("diff3", ConflictStyle::Diff3),
+            ("zdiff3", ConflictStyle::ZealousDiff3),
+        ] {
+            assert_eq!(Merge::CONFLICT_STYLE.try_into_conflict_style(bcow(actual))?, expected);
+            assert!(Merge::CONFLICT_STYLE.validate(actual.into()).is_ok());
+        }
+        assert_eq!(
+            Merge::CONFLICT_STYLE
+                .try_into_conflict_style(bcow("Diff3"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"merge.conflictStyle=Diff3\" was invalid"
+        );
+        Ok(())
+    }
+}
+
+mod gpg {
+    use gix::{
+        config::tree::{Gpg, Key},
+        signing::Format,
+    };
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn format() -> crate::Result {
+        for (actual, expected) in [
+            ("openpgp", Format::OpenPgp),
+            ("ssh", Format::Ssh),
+            ("x509", Format::X509),
+        ] {
+            assert_eq!(Gpg::FORMAT.try_into_format(bcow(actual))?, expected);
+            assert!(Gpg::FORMAT.validate(actual.into()).is_ok());
+        }
+        assert_eq!(
+            Gpg::FORMAT.try_into_format(bcow("gpg")).unwrap_err().to_string(),
+            "The key \"gpg.format=gpg\" was invalid"
+        );
+        Ok(())
+    }
+}
+
 mod core {
     use std::time::Duration;
 
@@ -352,7 +405,7 @@ mod core {
 }
 
 mod extensions {
-    use gix::config::tree::{Extensions, Key};
+    use gix::config::tree::{extensions::RefStorageFormat, Extensions, Key};
 
     use crate::config::tree::bcow;
 
@@ -367,6 +420,10 @@ mod extensions {
             gix_hash::Kind::Sha1,
             "case-insensitive"
         );
+        assert_eq!(
+            Extensions::OBJECT_FORMAT.try_into_object_format(bcow("sha256"))?,
+            gix_hash::Kind::Sha256
+        );
         assert_eq!(
             Extensions::OBJECT_FORMAT
                 .try_into_object_format(bcow("invalid"))
@@ -375,9 +432,50 @@ mod extensions {
             "The key \"extensions.objectFormat=invalid\" was invalid"
         );
         assert!(Extensions::OBJECT_FORMAT.validate("sha1".into()).is_ok());
+        assert!(Extensions::OBJECT_FORMAT.validate("sha256".into()).is_ok());
         assert!(Extensions::OBJECT_FORMAT.validate("invalid".into()).is_err());
         Ok(())
     }
+
+    #[test]
+    fn compat_object_format() -> crate::Result {
+        assert_eq!(
+            Extensions::COMPAT_OBJECT_FORMAT.try_into_object_format(bcow("sha1"))?,
+            gix_hash::Kind::Sha1
+        );
+        assert_eq!(
+            Extensions::COMPAT_OBJECT_FORMAT
+                .try_into_object_format(bcow("invalid"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"extensions.compatObjectFormat=invalid\" was invalid"
+        );
+        assert!(Extensions::COMPAT_OBJECT_FORMAT.validate("sha256".into()).is_ok());
+        assert!(Extensions::COMPAT_OBJECT_FORMAT.validate("invalid".into()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ref_storage() -> crate::Result {
+        assert_eq!(
+            Extensions::REF_STORAGE.try_into_ref_storage(bcow("files"))?,
+            RefStorageFormat::Files
+        );
+        assert_eq!(
+            Extensions::REF_STORAGE.try_into_ref_storage(bcow("reftable"))?,
+            RefStorageFormat::Reftable
+        );
+        assert_eq!(
+            Extensions::REF_STORAGE
+                .try_into_ref_storage(bcow("Files"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"extensions.refStorage=Files\" was invalid"
+        );
+        assert!(Extensions::REF_STORAGE.validate("reftable".into()).is_ok());
+        assert!(Extensions::REF_STORAGE.validate("invalid".into()).is_err());
+        Ok(())
+    }
 }
 
 mod checkout {
@@ -400,6 +498,29 @@ mod checkout {
     }
 }
 
+mod index {
+    use gix::config::tree::{Index, Key};
+
+    #[test]
+    fn version() -> crate::Result {
+        for (value, expected) in [
+            (2, gix::index::Version::V2),
+            (3, gix::index::Version::V3),
+            (4, gix::index::Version::V4),
+        ] {
+            assert_eq!(Index::VERSION.try_into_index_version(Ok(value))?, expected);
+            assert!(Index::VERSION.validate(value.to_string().as_str().into()).is_ok());
+        }
+        assert_eq!(
+            Index::VERSION.try_into_index_version(Ok(1)).unwrap_err().to_string(),
+            "The value of key \"index.version\" was invalid"
+        );
+        assert!(Index::VERSION.validate("5".into()).is_err());
+        assert!(Index::VERSION.validate("-1".into()).is_err());
+        Ok(())
+    }
+}
+
 mod pack {
     use gix::config::tree::{Key, Pack};
 
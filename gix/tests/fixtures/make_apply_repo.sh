@@ -0,0 +1,29 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+seq 10 > file
+mkdir dir
+echo content > dir/moved
+echo deleted > deleted
+seq 300 | gzip -9 -n > bin
+git add .
+git commit -q -m "base"
+
+git checkout -q -b changed
+sed -i.bak 's/^5$/five/' file && rm file.bak
+echo 11 >> file
+chmod +x file
+git mv dir/moved moved
+git rm -q deleted
+printf '\0\1new' > new
+printf XYZW | dd of=bin bs=1 seek=300 conv=notrunc 2>/dev/null
+git add .
+git commit -q -m "changed"
+git diff --binary main changed > .git/changes.patch
+
+git checkout -q main
+seq 10 | sed 's/^5$/FIVE/' > file
+git commit -q -am "conflicting"
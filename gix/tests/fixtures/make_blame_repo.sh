@@ -0,0 +1,49 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+# Commit with a committer date that increases with each commit, so history is traversed in a predictable order.
+time=1000000000
+function commit() {
+  time=$((time + 60))
+  git add -A
+  GIT_AUTHOR_DATE="$time +0000" GIT_COMMITTER_DATE="$time +0000" git commit -q "$@"
+}
+
+seq 10 > file.txt
+commit -m "add file"
+git tag first
+
+sed -i.bak 's/^3$/three/' file.txt && rm file.txt.bak
+echo 11 >> file.txt
+commit -m "change line 3 and append a line" --author "Old Name <old@example.com>"
+
+git mv file.txt renamed.txt
+sed -i.bak 's/^5$/five/' renamed.txt && rm renamed.txt.bak
+commit -m "rename and change line 5"
+
+git checkout -q -b side
+sed -i.bak 's/^8$/eight/' renamed.txt && rm renamed.txt.bak
+commit -m "change line 8 on a side branch"
+
+git checkout -q main
+sed -i.bak 's/^1$/one/' renamed.txt && rm renamed.txt.bak
+commit -m "change line 1"
+time=$((time + 60))
+GIT_AUTHOR_DATE="$time +0000" GIT_COMMITTER_DATE="$time +0000" git merge -q --no-ff -m "merge side" side
+
+sed -i.bak 's/^2$/2  /; s/^three$/three  /' renamed.txt && rm renamed.txt.bak
+commit -m "reformat lines 2 and 3"
+git rev-parse HEAD > ignore-revs
+git tag reformat
+
+sed -i.bak 's/^10$/ten/' renamed.txt && rm renamed.txt.bak
+echo "New Name <new@example.com> Old Name <old@example.com>" > .mailmap
+commit -m "change line 10 and add a mailmap"
+
+git blame --porcelain renamed.txt > .git/all.baseline
+git blame --porcelain -L 2,4 -L 9,11 -L 3,5 renamed.txt > .git/ranges.baseline
+git blame --porcelain --ignore-revs-file ignore-revs renamed.txt > .git/ignore-revs.baseline
+git blame --porcelain first -- file.txt > .git/first.baseline
@@ -0,0 +1,67 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function history() {
+  git checkout -q -b main
+
+  echo content > file
+  mkdir dir && echo other > dir/file
+  git add . && git commit -q -m "initial"
+  git tag -a -m "annotated" v1
+  git repack -adq
+
+  echo changed > file
+  git commit -q -am "second"
+}
+
+function signature() {
+  echo "-----BEGIN PGP SIGNATURE-----
+
+signature made for ${1:?the hash the signature was made for}
+-----END PGP SIGNATURE-----"
+}
+
+# Write a tag whose signature made for `message_hash` goes into its message and whose signature made for `header_hash` goes
+# into the header named after it, which is how `git` stores the signatures made for each object format.
+function signed_tag() {
+  local name=${1:?first argument is the tag name}
+  local message_hash=${2:-}
+  local header_hash=${3:-}
+
+  local header=gpgsig
+  if [ "$header_hash" = sha256 ]; then
+    header=gpgsig-sha256
+  fi
+  local id
+  id=$({
+    echo "object $(git rev-parse HEAD)"
+    echo "type commit"
+    echo "tag $name"
+    echo "tagger T <t@example.com> 1112911993 +0100"
+    if [ -n "$header_hash" ]; then
+      signature "$header_hash" | sed -e "1s/^/$header /" -e "2,\$s/^/ /"
+    fi
+    echo
+    echo "signed"
+    if [ -n "$message_hash" ]; then
+      signature "$message_hash"
+    fi
+  } | git hash-object -t tag -w --stdin)
+  git update-ref "refs/tags/$name" "$id"
+}
+
+git init -q --object-format=sha256 sha256
+(cd sha256
+  history
+  signed_tag signed sha256
+  signed_tag signed-for-both sha256 sha1
+  # set last as `git` might not understand it yet
+  git config extensions.compatObjectFormat sha1
+)
+
+git init -q sha1
+(cd sha1
+  history
+  signed_tag signed "" sha256
+  signed_tag signed-for-both sha1 sha256
+)
@@ -0,0 +1,43 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# A hook that logs how it was called to `hook.log` in its working directory, and which adds a trailer to commit messages.
+mkdir hooks
+cat > hooks/log <<'HOOK'
+#!/bin/sh
+name=$(basename "$0")
+case $name in
+  commit-msg)
+    { echo "$name"; cat "$1"; } >> hook.log
+    printf '\nHook-Trailer: yes\n' >> "$1"
+    ;;
+  reference-transaction)
+    { echo "$name $*"; cat; } >> hook.log
+    ;;
+  *)
+    echo "$name $*" >> hook.log
+    ;;
+esac
+HOOK
+chmod +x hooks/log
+for name in pre-commit commit-msg post-commit reference-transaction post-checkout; do
+  cp hooks/log hooks/$name
+done
+
+# A hook that always fails.
+printf '#!/bin/sh\necho "rejected by $(basename "$0")"\nexit 1\n' > hooks/reject
+chmod +x hooks/reject
+
+git init -q repo
+(cd repo
+  git commit -q --allow-empty -m initial
+  cp ../hooks/* .git/hooks/
+)
+
+# The same operations that the tests perform, as performed by `git`, with the hook log moved to `git-hook.log`.
+cp -R repo git-repo
+(cd git-repo
+  git commit -q --allow-empty -m second
+  git branch other
+  mv hook.log ../git-hook.log
+)
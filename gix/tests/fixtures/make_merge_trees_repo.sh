@@ -0,0 +1,102 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+git commit -q --allow-empty -m "root"
+
+# Create the branches `<name>-base`, `<name>-ours` and `<name>-theirs` from the changes made by the functions
+# `<name>_base`, `<name>_ours` and `<name>_theirs`, and record the output of `git merge-tree` in `.git/<name>.expected`.
+function scenario() {
+  local name=${1:?}
+  git checkout -q -b "$name-base" main
+  "${name}_base"
+  git add -A && git commit -q --allow-empty -m "$name-base"
+  for side in ours theirs; do
+    git checkout -q -b "$name-$side" "$name-base"
+    "${name}_$side"
+    git add -A && git commit -q -m "$name-$side"
+  done
+  git merge-tree --write-tree "$name-ours" "$name-theirs" > ".git/$name.expected" || true
+}
+
+function clean_base() {
+  seq 10 > a
+  echo d > d
+  seq 20 > r
+  mkdir dir && echo same > dir/same
+}
+function clean_ours() {
+  sed -i.bak 's/^1$/one/' a && rm a.bak
+  rm d
+  seq 20 | sed 's/^2$/two/' > r
+  chmod +x dir/same
+}
+function clean_theirs() {
+  sed -i.bak 's/^10$/ten/' a && rm a.bak
+  echo new > n
+  git mv r dir/r-renamed
+  rm d
+}
+scenario clean
+
+function content_base() {
+  seq 10 > a
+}
+function content_ours() {
+  sed -i.bak 's/^5$/ours/' a && rm a.bak
+}
+function content_theirs() {
+  sed -i.bak 's/^5$/theirs/' a && rm a.bak
+}
+scenario content
+
+function modify_delete_base() {
+  seq 10 > a
+  seq 20 > r
+}
+function modify_delete_ours() {
+  echo 11 >> a
+  rm r
+}
+function modify_delete_theirs() {
+  rm a
+  echo 21 >> r
+  git mv r r-renamed
+}
+scenario modify_delete
+
+function rename_rename_base() {
+  seq 10 > a
+}
+function rename_rename_ours() {
+  git mv a a-ours
+}
+function rename_rename_theirs() {
+  git mv a a-theirs
+}
+scenario rename_rename
+
+function add_add_base() {
+  :
+}
+function add_add_ours() {
+  printf 'common\nours\n' > a
+  echo same > same
+}
+function add_add_theirs() {
+  printf 'common\ntheirs\n' > a
+  echo same > same
+}
+scenario add_add
+
+function directory_file_base() {
+  echo base > base
+}
+function directory_file_ours() {
+  echo file > x
+}
+function directory_file_theirs() {
+  mkdir x && echo file > x/y
+}
+scenario directory_file
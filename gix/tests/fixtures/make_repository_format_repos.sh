@@ -0,0 +1,40 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write configuration directly as `git` may refuse to operate on repositories with extensions it doesn't know.
+function config() {
+  git config -f "${1:?first argument is the repository name}/.git/config" "${@:2}"
+}
+
+function repo() {
+  local name=${1:?first argument is the repository name}
+  git init -q "$name"
+  config "$name" core.repositoryFormatVersion "${2:?second argument is the format version}"
+}
+
+repo v0-unknown-extension 0
+config v0-unknown-extension extensions.unknown true
+
+repo v0-v1-only-extension 0
+config v0-v1-only-extension extensions.objectFormat sha1
+
+repo v1-known-extensions 1
+config v1-known-extensions extensions.noop anything
+config v1-known-extensions extensions.noop-v1 anything
+config v1-known-extensions extensions.preciousObjects true
+config v1-known-extensions extensions.partialClone origin
+config v1-known-extensions extensions.worktreeConfig false
+config v1-known-extensions extensions.refStorage files
+config v1-known-extensions extensions.objectFormat sha1
+
+repo v1-unknown-extensions 1
+config v1-unknown-extensions extensions.unknown true
+config v1-unknown-extensions extensions.sub.other true
+
+repo v1-reftable 1
+config v1-reftable extensions.refStorage reftable
+
+repo v1-unknown-ref-storage 1
+config v1-unknown-ref-storage extensions.refStorage unknown
+
+repo v2 2
@@ -0,0 +1,57 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+seq 10 > a
+echo b > b
+git add -A && git commit -q -m "base"
+
+git checkout -q -b feature
+sed -i.bak 's/^2$/feature/' a && rm a.bak
+git commit -q -am "change line 2" -m "Signed-off-by: Someone <someone@example.com>"
+git tag pick-clean
+echo c > c
+git add c && git commit -q -m "add c" --author "Original Author <original@example.com>"
+git tag pick-author
+sed -i.bak 's/^9$/feature/' a && rm a.bak
+git commit -q -am "change line 9 on feature"
+git tag pick-conflict
+git commit -q --allow-empty -m "empty"
+git tag pick-empty
+
+git checkout -q main
+sed -i.bak 's/^9$/main/' a && rm a.bak
+echo b2 >> b
+git commit -q -am "change line 9 and b on main"
+git tag revert-me
+git checkout -q -b side main~1
+echo side > side
+git add side && git commit -q -m "add side"
+git checkout -q main
+git merge -q --no-ff -m "merge side" side
+git tag merge
+
+# Record the outcome of `git cherry-pick` and `git revert` as `.git/<name>.tree` and `.git/<name>.message`.
+function record() {
+  local name=${1:?}
+  git rev-parse HEAD^{tree} > ".git/$name.tree"
+  git cat-file commit HEAD | sed "1,/^$/d" > ".git/$name.message"
+  git log -1 --format="%an <%ae>" > ".git/$name.author"
+  git checkout -q --detach main
+}
+
+git checkout -q --detach main
+
+git cherry-pick -x pick-clean && record pick-clean
+git cherry-pick -x pick-author && record pick-author
+git revert --no-edit revert-me && record revert
+git revert --no-edit -m 1 merge && record revert-merge
+git cherry-pick pick-conflict || {
+  git ls-files --stage --unmerged > .git/pick-conflict.stages
+  cp .git/CHERRY_PICK_HEAD .git/pick-conflict.head
+  cp .git/MERGE_MSG .git/pick-conflict.message
+  git cherry-pick --abort
+}
+git checkout -q main
@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --object-format=sha256
+git checkout -q -b main
+
+echo content > file
+mkdir dir && echo other > dir/file
+git add . && git commit -q -m "initial"
+git repack -adq
+
+echo changed > file
+git commit -q -am "second"
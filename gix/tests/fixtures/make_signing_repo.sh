@@ -0,0 +1,56 @@
+#!/bin/bash
+set -eu -o pipefail
+
+ssh-keygen -q -t ed25519 -N "" -C "signing key" -f signing-key
+ssh-keygen -q -t ed25519 -N "" -C "unknown key" -f unknown-key
+echo "gitoxide@localhost $(cat signing-key.pub)" > allowed-signers
+
+# `gnupg` knows the key of `gpg@example.com`, whereas the key of `unknown@example.com` is only known to `unknown-gnupg`.
+# The keyrings are created in a short temporary directory as the sockets of the agent are placed there, and
+# socket paths must not be too long.
+root=$PWD
+gnupg_tmp=$(mktemp -d)
+known_home=$gnupg_tmp/known
+unknown_home=$gnupg_tmp/unknown
+mkdir -m 700 "$known_home" "$unknown_home"
+GNUPGHOME=$known_home gpg --batch -q --passphrase '' --quick-gen-key "gpg signer <gpg@example.com>" ed25519 sign never 2>/dev/null
+GNUPGHOME=$unknown_home gpg --batch -q --passphrase '' --quick-gen-key "unknown signer <unknown@example.com>" ed25519 sign never 2>/dev/null
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  echo a > a
+  git add a && git commit -q -m "initial"
+
+  ssh_sign=(-c gpg.format=ssh -c user.signingKey="$root/signing-key")
+  git checkout -q -b ssh-signed main
+  git "${ssh_sign[@]}" commit -q -S --allow-empty -m "ssh signed"
+  git "${ssh_sign[@]}" tag -s -m "ssh signed tag" ssh-tag
+
+  git checkout -q -b ssh-unknown main
+  git -c gpg.format=ssh -c user.signingKey="$root/unknown-key" commit -q -S --allow-empty -m "signed with unknown ssh key"
+
+  git checkout -q -b gpg-signed main
+  GNUPGHOME=$known_home git -c user.signingKey=gpg@example.com commit -q -S --allow-empty -m "gpg signed"
+  GNUPGHOME=$known_home git -c user.signingKey=gpg@example.com tag -s -m "gpg signed tag" gpg-tag
+
+  git checkout -q -b gpg-unknown main
+  GNUPGHOME=$unknown_home git -c user.signingKey=unknown@example.com commit -q -S --allow-empty -m "signed with unknown gpg key"
+
+  tampered=$(git cat-file commit gpg-signed | sed 's/^gpg signed$/tampered/' | git hash-object -w -t commit --stdin)
+  git update-ref refs/heads/tampered "$tampered"
+
+  git checkout -q main
+
+  # The verdict of `git` about each commit, as `<ref> <%G?> <signer> <key> <fingerprint>`, separated by tabs.
+  for ref in ssh-signed ssh-unknown gpg-signed gpg-unknown tampered main; do
+    GNUPGHOME=$known_home git -c gpg.ssh.allowedSignersFile="$root/allowed-signers" \
+      log -1 --format="$ref%x09%G?%x09%GS%x09%GK%x09%GF" "$ref"
+  done > ../expected-verifications
+)
+
+GNUPGHOME=$known_home gpgconf --kill all
+GNUPGHOME=$unknown_home gpgconf --kill all
+cp -R "$known_home" gnupg
+cp -R "$unknown_home" unknown-gnupg
+rm -rf "$gnupg_tmp"
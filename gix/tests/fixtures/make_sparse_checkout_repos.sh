@@ -0,0 +1,32 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  touch a
+  mkdir -p c1/c2 d
+  touch c1/a c1/c2/a d/a
+  git add .
+  git commit -q -m "init"
+)
+
+git clone -q base cone
+(cd cone
+  git sparse-checkout set c1/c2
+)
+
+git clone -q base non-cone
+(cd non-cone
+  git sparse-checkout set --no-cone '/d/'
+)
+
+git clone -q base disabled
+(cd disabled
+  git sparse-checkout set d
+  git sparse-checkout disable
+)
+
+git clone -q base cone-sparse-index
+(cd cone-sparse-index
+  git sparse-checkout set --sparse-index c1/c2
+)
@@ -0,0 +1,55 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  git checkout -q -b main
+  echo a > a
+  echo b > b
+  mkdir dir && echo c > dir/c
+  echo "*.ignored" > .gitignore
+  git add -A && git commit -q -m "initial"
+
+  echo a2 >> a
+  echo staged > b && git add b
+  rm dir/c
+  mkdir untracked-dir && echo u > untracked-dir/u
+  echo x > x.ignored
+)
+
+# `push` is stashed by `gitoxide` in tests, and its expectations are recorded after stashing the same changes with `git`.
+cp -R base push
+cp -R base push-git
+(cd push-git
+  git stash push -q --include-untracked -m "a message"
+  for rev in stash stash^2 stash^3; do
+    git rev-parse "$rev^{tree}"
+    git cat-file commit "$rev" | sed "1,/^$/d"
+  done > ../push/.git/expected-commits
+  git log -g --format="%gs" refs/stash > ../push/.git/expected-reflog
+  git status --porcelain --ignored > ../push/.git/expected-status
+)
+
+# `stashes` has two stashes created by `git`.
+cp -R base stashes
+(cd stashes
+  git stash push -q
+  echo "second" > a
+  git stash push -q -m "second stash"
+  git stash list --format="%H %gs" > .git/expected-list
+)
+
+cp -R stashes apply-git
+(cd apply-git
+  git stash apply -q --index stash@{1}
+  git ls-files --stage > ../stashes/.git/expected-apply-index
+  cat a b > ../stashes/.git/expected-apply-content
+  if test -e dir/c; then echo "dir/c exists" >> ../stashes/.git/expected-apply-content; fi
+)
+
+cp -R stashes drop-git
+(cd drop-git
+  git stash drop -q stash@{1}
+  git stash list --format="%H %gs" > ../stashes/.git/expected-list-after-drop
+  git log -g --format="%H %gs" refs/stash > ../stashes/.git/expected-reflog-after-drop
+)
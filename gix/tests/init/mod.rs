@@ -74,6 +74,33 @@ mod non_bare {
         );
         Ok(())
     }
+    #[test]
+    fn init_with_sha256_object_format() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let repo: gix::Repository = gix::ThreadSafeRepository::init_opts(
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            gix::create::Options {
+                object_hash: gix_hash::Kind::Sha256,
+                ..Default::default()
+            },
+            gix::open::Options::isolated(),
+        )?
+        .into();
+        assert_eq!(repo.object_hash(), gix_hash::Kind::Sha256);
+        let config = repo.config_snapshot();
+        assert_eq!(config.integer("core.repositoryFormatVersion"), Some(1));
+        assert_eq!(
+            config.string("extensions.objectFormat").expect("set").as_ref(),
+            "sha256"
+        );
+        assert_eq!(
+            gix::open_opts(repo.git_dir(), gix::open::Options::isolated())?.object_hash(),
+            gix_hash::Kind::Sha256
+        );
+        Ok(())
+    }
+
     #[test]
     fn init_into_empty_directory_creates_a_dot_git_dir() -> crate::Result {
         let tmp = tempfile::tempdir()?;
@@ -100,6 +100,151 @@ fn tree_named(repo: &gix::Repository, rev_spec: impl AsRef<str>) -> gix::Tree {
         .into_tree()
 }
 
+mod attributes {
+    use gix::object::tree::diff::patch::Attributes;
+    use gix_diff::blob::merge::Driver;
+
+    use crate::util::repo_rw;
+
+    #[test]
+    fn binary_diff_merge_driver_and_conflict_marker_size() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_diff_repo.sh")?;
+        std::fs::write(
+            repo.work_dir().expect("non-bare").join(".gitattributes"),
+            "*.bin binary\n*.txt merge=union conflict-marker-size=10\n*.cfg -merge\nlog.txt merge\n*.md merge=custom\n",
+        )?;
+        let attrs = Attributes::from_repo(&repo)?;
+
+        assert_eq!(
+            attrs.is_binary("a.bin".into()),
+            Some(true),
+            "the binary macro unsets 'diff'"
+        );
+        assert_eq!(attrs.merge_driver("a.bin".into()), Driver::Binary, "…and 'merge'");
+        assert_eq!(attrs.is_binary("a.txt".into()), None);
+        assert_eq!(attrs.merge_driver("dir/a.txt".into()), Driver::Union);
+        assert_eq!(attrs.conflict_marker_size("a.txt".into()), Some(10));
+        assert_eq!(attrs.merge_driver("log.txt".into()), Driver::Text, "later patterns win");
+        assert_eq!(attrs.merge_driver("a.cfg".into()), Driver::Binary);
+        assert_eq!(
+            attrs.merge_driver("a.md".into()),
+            Driver::Text,
+            "custom drivers aren't supported and fall back to text"
+        );
+        assert_eq!(attrs.merge_driver("a".into()), Driver::Text);
+        assert_eq!(attrs.conflict_marker_size("a".into()), None);
+        Ok(())
+    }
+
+    #[test]
+    fn attribute_files_in_subdirectories_and_info_attributes() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_diff_repo.sh")?;
+        let work_dir = repo.work_dir().expect("non-bare");
+        std::fs::write(work_dir.join(".gitattributes"), "*.dat merge=union\n")?;
+        std::fs::create_dir_all(work_dir.join("dir").join("sub"))?;
+        std::fs::write(
+            work_dir.join("dir").join(".gitattributes"),
+            "*.dat binary\n/top.txt merge=union\n",
+        )?;
+        std::fs::create_dir_all(repo.git_dir().join("info"))?;
+        std::fs::write(repo.git_dir().join("info").join("attributes"), "dir/sub/*.dat diff\n")?;
+        let attrs = Attributes::from_repo(&repo)?;
+
+        assert_eq!(attrs.merge_driver("a.dat".into()), Driver::Union);
+        assert_eq!(attrs.is_binary("a.dat".into()), None);
+        assert_eq!(
+            attrs.merge_driver("dir/a.dat".into()),
+            Driver::Binary,
+            "files in deeper directories take precedence"
+        );
+        assert_eq!(
+            attrs.merge_driver("dir/sub/a.dat".into()),
+            Driver::Binary,
+            "patterns without slash match in all directories below"
+        );
+        assert_eq!(
+            attrs.is_binary("dir/sub/a.dat".into()),
+            Some(false),
+            "info/attributes take precedence over all attribute files"
+        );
+        assert_eq!(
+            attrs.merge_driver("dir/top.txt".into()),
+            Driver::Union,
+            "anchored patterns are relative to the directory of their file"
+        );
+        assert_eq!(attrs.merge_driver("top.txt".into()), Driver::Text);
+        assert_eq!(
+            attrs.merge_driver("a.dat".into()),
+            Driver::Union,
+            "lookups can go back up"
+        );
+        Ok(())
+    }
+}
+
+mod write_patch {
+    use std::convert::Infallible;
+
+    use gix::object::tree::diff::patch;
+    use gix_object::bstr::{BString, ByteSlice};
+
+    use crate::{object::tree::diff::tree_named, util::named_repo};
+
+    fn patches(repo: &gix::Repository, message: &str) -> crate::Result<(String, Vec<patch::FileStat>)> {
+        let from = tree_named(repo, format!("@^{{/{message}}}~1"));
+        let to = tree_named(repo, format!(":/{message}"));
+        let mut patches = Vec::<(BString, Vec<u8>, Option<patch::FileStat>)>::new();
+        from.changes()?
+            .track_path()
+            .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                let mut out = Vec::new();
+                let stat = change
+                    .write_patch(&Default::default(), &mut out)
+                    .expect("objects are present");
+                patches.push((change.location.to_owned(), out, stat));
+                Ok(Default::default())
+            })?;
+        // `git` sorts by destination path.
+        patches.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut out = String::new();
+        let mut stats = Vec::new();
+        for (_, patch, stat) in patches {
+            out.push_str(patch.to_str()?);
+            stats.extend(stat);
+        }
+        Ok((out, stats))
+    }
+
+    #[test]
+    fn modification_and_modified_rename() -> crate::Result {
+        let repo = named_repo("make_diff_repo.sh")?;
+        let (out, stats) = patches(&repo, "r3-simple")?;
+        assert_eq!(
+            out,
+            "diff --git a/b b/b\nindex 6178079..54781fa 100644\n--- a/b\n+++ b/b\n@@ -1 +1,2 @@\n b\n+n\ndiff --git a/dir/c b/dir/c-moved\nsimilarity index 75%\nrename from dir/c\nrename to dir/c-moved\nindex 6695780..6fd3545 100644\n--- a/dir/c\n+++ b/dir/c-moved\n@@ -1 +1,2 @@\n dir/c\n+n\n"
+        );
+
+        let mut summary = Vec::new();
+        patch::stat::write_stat(&stats, Default::default(), &mut summary)?;
+        assert_eq!(
+            summary.as_bstr(),
+            " b                  | 1 +\n dir/{c => c-moved} | 1 +\n 2 files changed, 2 insertions(+)\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn symlinks_and_exact_renames() -> crate::Result {
+        let repo = named_repo("make_diff_repo.sh")?;
+        let (out, _) = patches(&repo, "r4-symlinks")?;
+        assert_eq!(
+            out,
+            "diff --git a/dir/link-2 b/dir/link-2\ndeleted file mode 120000\nindex dc320d6..0000000\n--- a/dir/link-2\n+++ /dev/null\n@@ -1 +0,0 @@\n-../lt2\n\\ No newline at end of file\ndiff --git a/no-link b/no-link\ndeleted file mode 100644\nindex 07ec3cb..0000000\n--- a/no-link\n+++ /dev/null\n@@ -1 +0,0 @@\n-lt1\ndiff --git a/link-1 b/renamed-link-1\nsimilarity index 100%\nrename from link-1\nrename to renamed-link-1\ndiff --git a/z-link-2 b/z-link-2\nnew file mode 120000\nindex 0000000..9d59f75\n--- /dev/null\n+++ b/z-link-2\n@@ -0,0 +1 @@\n+lt1\n\\ No newline at end of file\n"
+        );
+        Ok(())
+    }
+}
+
 mod track_rewrites {
     use std::convert::Infallible;
 
@@ -0,0 +1,124 @@
+use gix::{apply, bstr::ByteSlice, prelude::FindExt};
+use gix_diff::patch::parse::FilePatch;
+
+use crate::util::{named_repo, repo_rw};
+
+fn patches(repo: &gix::Repository) -> crate::Result<Vec<FilePatch>> {
+    let patch = std::fs::read(repo.git_dir().join("changes.patch"))?;
+    Ok(gix_diff::patch::parse(&patch, Default::default())?)
+}
+
+fn tree_of(repo: &gix::Repository, spec: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(spec)?.object()?.peel_to_tree()?.id)
+}
+
+#[test]
+fn to_tree_with_binary_delta_rename_deletion_and_mode_change() -> crate::Result {
+    let repo = named_repo("make_apply_repo.sh")?;
+    let actual = repo.apply_to_tree(tree_of(&repo, "main~1")?, &patches(&repo)?, &Default::default())?;
+    assert_eq!(
+        actual,
+        tree_of(&repo, "changed")?,
+        "the result is exactly what git produced"
+    );
+    Ok(())
+}
+
+#[test]
+fn to_tree_fails_if_hunks_do_not_apply() -> crate::Result {
+    let repo = named_repo("make_apply_repo.sh")?;
+    let err = repo
+        .apply_to_tree(tree_of(&repo, "main")?, &patches(&repo)?, &Default::default())
+        .unwrap_err();
+    assert!(matches!(err, apply::Error::Hunks { ref path, .. } if path == "file"));
+
+    let err = repo
+        .apply_to_tree(
+            tree_of(&repo, "main")?,
+            &patches(&repo)?,
+            &apply::Options {
+                three_way: true,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, apply::Error::Conflict { ref path } if path == "file"),
+        "trees can't store conflicts"
+    );
+    Ok(())
+}
+
+#[test]
+fn to_index_with_three_way_conflict() -> crate::Result {
+    let repo = named_repo("make_apply_repo.sh")?;
+    let mut index = gix::index::State::from_tree(&tree_of(&repo, "main")?, |oid, buf| {
+        repo.objects.find_tree_iter(oid, buf).ok()
+    })?;
+    let outcome = repo.apply_to_index(
+        &mut index,
+        &patches(&repo)?,
+        &apply::Options {
+            three_way: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.conflicts, ["file"]);
+
+    let stages: Vec<_> = index
+        .entries()
+        .iter()
+        .filter(|e| e.path(&index) == "file" && !e.flags.contains(gix::index::entry::Flags::REMOVE))
+        .map(|e| (e.stage(), e.id.to_hex_with_len(7).to_string()))
+        .collect();
+    let ours = repo.rev_parse_single("main:file")?.to_hex_with_len(7).to_string();
+    assert_eq!(
+        stages[0],
+        (1, "f00c965".to_string()),
+        "the base is the blob from the `index` line"
+    );
+    assert_eq!(stages[1], (2, ours));
+    assert_eq!(stages[2].0, 3);
+    assert_eq!(stages.len(), 3);
+
+    let theirs = repo.find_object(index.entry_by_path_and_stage("file".into(), 3).expect("present").id)?;
+    assert_eq!(theirs.data.as_bstr(), "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n11\n");
+    Ok(())
+}
+
+#[test]
+fn to_worktree_with_three_way_conflict() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_apply_repo.sh")?;
+    let outcome = repo.apply_to_worktree(
+        &patches(&repo)?,
+        &apply::Options {
+            three_way: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.conflicts, ["file"]);
+
+    let root = repo.work_dir().expect("non-bare");
+    assert_eq!(
+        std::fs::read(root.join("file"))?.as_bstr(),
+        "1\n2\n3\n4\n<<<<<<< ours\nFIVE\n=======\nfive\n>>>>>>> theirs\n6\n7\n8\n9\n10\n11\n"
+    );
+    assert!(!root.join("deleted").exists());
+    assert!(
+        !root.join("dir").exists(),
+        "directories that are left empty are removed"
+    );
+    assert_eq!(std::fs::read(root.join("moved"))?, b"content\n");
+    assert_eq!(std::fs::read(root.join("new"))?, b"\0\x01new");
+    assert_eq!(
+        std::fs::read(root.join("bin"))?,
+        repo.rev_parse_single("changed:bin")?.object()?.data,
+        "the binary delta was applied"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_ne!(std::fs::metadata(root.join("file"))?.permissions().mode() & 0o100, 0);
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use gix::{
+    blame::Options,
+    bstr::{BString, ByteSlice},
+    ObjectId,
+};
+
+use crate::util::named_repo;
+
+/// A blamed line as `(line_in_blamed_file, commit, line_in_source_file, source_path, author_name, author_email)`,
+/// with 1-based line numbers.
+type Line = (u32, ObjectId, u32, BString, BString, BString);
+
+/// Parse the output of `git blame --porcelain` stored in `.git/<name>.baseline`.
+fn baseline(repo: &gix::Repository, name: &str) -> crate::Result<Vec<Line>> {
+    let content = std::fs::read(repo.git_dir().join(format!("{name}.baseline")))?;
+    let mut authors = HashMap::<ObjectId, (BString, BString)>::new();
+    let mut paths = HashMap::<ObjectId, BString>::new();
+    let mut out = Vec::new();
+    let mut current = None;
+    for line in content.lines() {
+        if line.starts_with(b"\t") {
+            let (id, source_line, blamed_line): (ObjectId, u32, u32) = current.take().expect("header before content");
+            let (name, email) = authors[&id].clone();
+            out.push((blamed_line, id, source_line, paths[&id].clone(), name, email));
+        } else if let Some(name) = line.strip_prefix(b"author ") {
+            authors.entry(current.expect("header").0).or_default().0 = name.into();
+        } else if let Some(email) = line.strip_prefix(b"author-mail ") {
+            authors.entry(current.expect("header").0).or_default().1 =
+                email.trim_start_with(|c| c == '<').trim_end_with(|c| c == '>').into();
+        } else if let Some(path) = line.strip_prefix(b"filename ") {
+            paths.insert(current.expect("header").0, path.into());
+        } else if let Ok(id) = ObjectId::from_hex(line.get(..40).unwrap_or_default()) {
+            let mut tokens = line[41..]
+                .split_str(" ")
+                .map(|t| t.to_str().expect("ascii").parse::<u32>().expect("number"));
+            current = Some((
+                id,
+                tokens.next().expect("source line"),
+                tokens.next().expect("blamed line"),
+            ));
+        }
+    }
+    Ok(out)
+}
+
+fn blamed_lines(outcome: &gix::blame::Outcome) -> Vec<Line> {
+    outcome
+        .entries
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .range_in_blamed_file
+                .clone()
+                .zip(entry.range_in_source_file.clone())
+                .map(move |(blamed_line, source_line)| {
+                    (
+                        blamed_line + 1,
+                        entry.commit_id,
+                        source_line + 1,
+                        entry.source_path.clone(),
+                        entry.author.name.clone(),
+                        entry.author.email.clone(),
+                    )
+                })
+        })
+        .collect()
+}
+
+#[test]
+fn follows_renames_through_merges_and_resolves_authors_with_the_mailmap() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let outcome = repo.blame("renamed.txt", &Options::from_repo(&repo)?)?;
+    assert_eq!(blamed_lines(&outcome), baseline(&repo, "all")?);
+    assert_eq!(outcome.entries_with_lines().flat_map(|(_, lines)| lines).count(), 11);
+    assert!(
+        outcome
+            .entries
+            .windows(2)
+            .all(|e| e[0].range_in_blamed_file.end == e[1].range_in_blamed_file.start),
+        "entries are ordered and cover the whole file"
+    );
+    Ok(())
+}
+
+#[test]
+fn line_ranges() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let mut options = Options::from_repo(&repo)?;
+    options.ranges = vec![1..4, 8..11, 2..5];
+    let outcome = repo.blame("renamed.txt", &options)?;
+    assert_eq!(blamed_lines(&outcome), baseline(&repo, "ranges")?);
+
+    options.ranges.truncate(1);
+    options.ranges[0].end = 12;
+    assert!(matches!(
+        repo.blame("renamed.txt", &options),
+        Err(gix::blame::Error::InvalidRange { lines: 11, .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn ignored_revisions() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let mut options = Options::from_repo(&repo)?;
+    options.ignore_revs = vec![repo.rev_parse_single("refs/tags/reformat")?.detach()];
+    let outcome = repo.blame("renamed.txt", &options)?;
+    assert_eq!(blamed_lines(&outcome), baseline(&repo, "ignore-revs")?);
+    Ok(())
+}
+
+#[test]
+fn ignored_revisions_from_configured_file() -> crate::Result {
+    let repo: gix::Repository = gix::ThreadSafeRepository::open_opts(
+        gix_testtools::scripted_fixture_read_only("make_blame_repo.sh")?,
+        gix::open::Options::isolated().config_overrides(["blame.ignoreRevsFile=ignore-revs"]),
+    )?
+    .into();
+    let options = Options::from_repo(&repo)?;
+    assert_eq!(
+        options.ignore_revs,
+        [repo.rev_parse_single("refs/tags/reformat")?.detach()]
+    );
+    assert_eq!(
+        blamed_lines(&repo.blame("renamed.txt", &options)?),
+        baseline(&repo, "ignore-revs")?
+    );
+    Ok(())
+}
+
+#[test]
+fn root_commit_and_missing_paths() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let mut options = Options::from_repo(&repo)?;
+    let first = repo.rev_parse_single("refs/tags/first")?.detach();
+    options.commit = Some(first);
+    let outcome = repo.blame("file.txt", &options)?;
+    assert_eq!(blamed_lines(&outcome), baseline(&repo, "first")?);
+    assert_eq!(outcome.entries.len(), 1, "all lines are attributed to the root commit");
+
+    assert!(matches!(
+        repo.blame("renamed.txt", &options),
+        Err(gix::blame::Error::PathNotFound { commit, .. }) if commit == first
+    ));
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use gix_testtools::tempfile;
+
+use crate::util::hex_to_id;
+
+fn open(path: impl Into<std::path::PathBuf>) -> crate::Result<gix::Repository> {
+    Ok(gix::open_opts(path, crate::restricted())?)
+}
+
+#[test]
+fn ids_of_objects_written_by_git_are_computed_on_demand() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("make_compat_object_format_repos.sh")?;
+    let repo = open(tmp.path().join("sha256"))?;
+    let sha1_repo = open(tmp.path().join("sha1"))?;
+    assert_eq!(repo.object_hash(), gix_hash::Kind::Sha256);
+    assert_eq!(repo.compat_object_hash(), Some(gix_hash::Kind::Sha1));
+    assert_eq!(sha1_repo.compat_object_hash(), None);
+
+    for spec in [
+        "HEAD",
+        "HEAD~1",
+        "v1",
+        "signed",
+        "signed-for-both",
+        "HEAD^{tree}",
+        "HEAD:dir/file",
+    ] {
+        let id = repo.rev_parse_single(spec)?.detach();
+        let expected = sha1_repo.rev_parse_single(spec)?.detach();
+        assert_eq!(
+            repo.compat_object_id(id)?,
+            Some(expected),
+            "{spec}: loose and packed objects are converted along with everything they refer to, with tag signatures swapped like git does"
+        );
+        assert_eq!(repo.object_id_from_compat(expected)?, Some(id));
+    }
+
+    let head_id = repo.head_id()?.detach();
+    let sha1_head_id = sha1_repo.head_id()?.detach();
+    let repo = open(tmp.path().join("sha256"))?;
+    assert_eq!(
+        repo.object_id_from_compat(sha1_head_id)?,
+        Some(head_id),
+        "converted ids are persisted"
+    );
+    assert_eq!(
+        repo.rev_parse_single(sha1_head_id.to_string().as_str())?,
+        head_id,
+        "full ids in the compatibility format can be used in rev-specs"
+    );
+    assert_eq!(
+        repo.rev_parse_single(format!("{sha1_head_id}~1:file").as_str())?,
+        repo.rev_parse_single("HEAD~1:file")?
+    );
+    assert_eq!(
+        sha1_repo.compat_object_id(sha1_head_id)?,
+        None,
+        "nothing to do without a compatibility object format"
+    );
+    Ok(())
+}
+
+#[test]
+fn history_is_not_converted_when_writing_objects() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("make_compat_object_format_repos.sh")?;
+    let repo = open(tmp.path().join("sha256"))?;
+    let head = repo.head_commit()?;
+    let signature = gix::actor::Signature {
+        name: "c".into(),
+        email: "c@example.com".into(),
+        time: gix::actor::Time::new(1, 1800),
+    };
+    let commit_id = repo.commit_as(&signature, &signature, "HEAD", "third", head.tree_id()?, Some(head.id))?;
+    assert!(
+        !repo.objects.store_ref().path().join("loose-object-idx").exists(),
+        "the id of the commit isn't recorded as the one of its parent isn't known, which would require converting all history"
+    );
+    assert!(
+        repo.compat_object_id(commit_id)?.is_some(),
+        "it can still be computed on demand"
+    );
+    Ok(())
+}
+
+#[test]
+fn ids_of_written_objects_are_recorded() -> crate::Result {
+    let tmp = tempfile::tempdir()?;
+    let sha256_dir = tmp.path().join("sha256");
+    gix::ThreadSafeRepository::init_opts(
+        &sha256_dir,
+        gix::create::Kind::WithWorktree,
+        gix::create::Options {
+            object_hash: gix_hash::Kind::Sha256,
+            ..Default::default()
+        },
+        crate::restricted(),
+    )?;
+    let config_path = sha256_dir.join(".git").join("config");
+    let mut config = std::fs::read_to_string(&config_path)?;
+    config.push_str("\tcompatObjectFormat = sha1\n");
+    std::fs::write(&config_path, config)?;
+    let repo = open(&sha256_dir)?;
+    assert_eq!(repo.compat_object_hash(), Some(gix_hash::Kind::Sha1));
+    let sha1_repo = open(gix::init(tmp.path().join("sha1"))?.path())?;
+
+    let mut ids = Vec::new();
+    for repo in [&repo, &sha1_repo] {
+        let blob_id = repo.write_blob("content\n")?;
+        let tree_id = repo.write_object(&gix::objs::Tree {
+            entries: vec![gix::objs::tree::Entry {
+                mode: gix::objs::tree::EntryMode::Blob,
+                filename: "file".into(),
+                oid: blob_id.detach(),
+            }],
+        })?;
+        let signature = gix::actor::Signature {
+            name: "c".into(),
+            email: "c@example.com".into(),
+            time: gix::actor::Time::new(1, 1800),
+        };
+        let commit_id = repo.commit_as(
+            &signature,
+            &signature,
+            "HEAD",
+            "initial",
+            tree_id,
+            gix::commit::NO_PARENT_IDS,
+        )?;
+        ids.push([blob_id.detach(), tree_id.detach(), commit_id.detach()]);
+    }
+
+    let object_map = std::fs::read_to_string(sha256_dir.join(".git").join("objects").join("loose-object-idx"))?;
+    assert_eq!(
+        object_map.lines().count(),
+        4,
+        "a header and one line per written object, which was recorded right away"
+    );
+    for (id, sha1_id) in ids[0].iter().zip(ids[1].iter()) {
+        assert!(object_map.contains(&format!("{id} {sha1_id}\n")));
+        assert_eq!(repo.compat_object_id(*id)?, Some(*sha1_id));
+    }
+    assert_eq!(
+        ids[1][0],
+        hex_to_id("d95f3ad14dee633a758d2e331151e950dd13e4ed"),
+        "the same blob id as produced by git"
+    );
+    Ok(())
+}
@@ -1,6 +1,136 @@
 mod config_snapshot;
 mod identity;
 mod remote;
+mod snapshot_mut_at;
+
+mod index_write_options {
+    use crate::util::{repo_opts, restricted};
+
+    fn repo_with(config: &[&str]) -> crate::Result<gix::Repository> {
+        repo_with_opts(config, restricted().strict_config(true))
+    }
+
+    fn repo_with_opts(config: &[&str], opts: gix::open::Options) -> crate::Result<gix::Repository> {
+        let mut repo = repo_opts("make_basic_repo.sh", opts)?.to_thread_local();
+        repo.config_snapshot_mut()
+            .append_config(config, gix_config::Source::Api)?;
+        Ok(repo)
+    }
+
+    #[test]
+    fn defaults_retain_the_version_of_the_index() -> crate::Result {
+        let repo = repo_with(&[])?;
+        assert_eq!(repo.index_write_options()?.version, None);
+        Ok(())
+    }
+
+    #[test]
+    fn index_version_takes_precedence_over_many_files() -> crate::Result {
+        let repo = repo_with(&["feature.manyFiles=true"])?;
+        assert_eq!(repo.index_write_options()?.version, Some(gix::index::Version::V4));
+
+        let repo = repo_with(&["feature.manyFiles=true", "index.version=2"])?;
+        assert_eq!(repo.index_write_options()?.version, Some(gix::index::Version::V2));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_index_version_is_an_error_unless_lenient() -> crate::Result {
+        let repo = repo_with(&["index.version=1"])?;
+        assert!(repo.index_write_options().is_err());
+
+        let repo = repo_with_opts(&["index.version=1"], restricted().strict_config(false))?;
+        assert_eq!(
+            repo.index_write_options()?.version,
+            None,
+            "invalid values are ignored if leniency is enabled"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn split_index_is_configured_by_core_split_index_and_the_split_index_section() -> crate::Result {
+        let repo = repo_with(&[])?;
+        assert!(
+            repo.index_write_options()?.split_index.is_none(),
+            "nothing is configured"
+        );
+        let repo = repo_with(&["core.splitIndex=false"])?;
+        assert!(repo.index_write_options()?.split_index.is_none());
+
+        let tmp = gix_testtools::tempfile::tempdir()?;
+        let repo = gix::open_opts(gix::init(tmp.path())?.path(), restricted())?;
+        let mut index = gix::index::File::from_state(gix::index::State::new(repo.object_hash()), repo.index_path());
+        assert!(repo.index_write_options_for(&index)?.split_index.is_none());
+        index.write(gix::index::write::Options {
+            split_index: Some(Default::default()),
+            ..Default::default()
+        })?;
+        assert!(
+            repo.index_write_options_for(&index)?.split_index.is_some(),
+            "split indices stay split if core.splitIndex is unset"
+        );
+        assert!(
+            repo.index_write_options()?.split_index.is_none(),
+            "without an index, only the configuration counts"
+        );
+        let repo = repo_with(&["core.splitIndex=false"])?;
+        assert!(
+            repo.index_write_options_for(&index)?.split_index.is_none(),
+            "the configuration has the last word"
+        );
+
+        let repo = repo_with(&["core.splitIndex=true"])?;
+        let split_index = repo.index_write_options()?.split_index.expect("configured");
+        let default = gix::index::write::SplitIndex::default();
+        assert_eq!(split_index.max_percent_change, default.max_percent_change);
+        assert_eq!(split_index.shared_index_expire, default.shared_index_expire);
+
+        let repo = repo_with(&[
+            "core.splitIndex=true",
+            "splitIndex.maxPercentChange=50",
+            "splitIndex.sharedIndexExpire=1.day.ago",
+        ])?;
+        let split_index = repo.index_write_options()?.split_index.expect("configured");
+        assert_eq!(split_index.max_percent_change, 50);
+        let expire = split_index.shared_index_expire.expect("set").as_secs();
+        assert!(
+            (24 * 60 * 60..24 * 60 * 60 + 60).contains(&expire),
+            "relative dates are supported: {expire}"
+        );
+
+        for (value, expected) in [("never", None), ("now", Some(std::time::Duration::default()))] {
+            let repo = repo_with(&["core.splitIndex=true", &format!("splitIndex.sharedIndexExpire={value}")])?;
+            assert_eq!(
+                repo.index_write_options()?
+                    .split_index
+                    .expect("configured")
+                    .shared_index_expire,
+                expected
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_split_index_values_are_an_error_unless_lenient() -> crate::Result {
+        for value in [
+            "splitIndex.maxPercentChange=101",
+            "splitIndex.sharedIndexExpire=invalid",
+        ] {
+            let repo = repo_with(&["core.splitIndex=true", value])?;
+            assert!(repo.index_write_options().is_err(), "{value}");
+
+            let repo = repo_with_opts(&["core.splitIndex=true", value], restricted().strict_config(false))?;
+            assert_eq!(
+                repo.index_write_options()?.split_index,
+                Some(gix::index::write::SplitIndex::default()),
+                "{value}: invalid values are ignored if leniency is enabled"
+            );
+        }
+        Ok(())
+    }
+}
 
 #[cfg(feature = "blocking-network-client")]
 mod ssh_options {
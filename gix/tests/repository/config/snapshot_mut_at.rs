@@ -0,0 +1,123 @@
+use gix::config::snapshot_mut_at;
+use gix_testtools::{tempfile, Env};
+use serial_test::serial;
+
+use crate::util::{restricted, restricted_and_git};
+
+fn read(path: impl AsRef<std::path::Path>) -> String {
+    std::fs::read_to_string(path).expect("file exists")
+}
+
+fn config_lock(root: &std::path::Path) -> std::path::PathBuf {
+    root.join(".git").join("config.lock")
+}
+
+#[test]
+fn local_changes_are_written_atomically_and_preserve_formatting() -> crate::Result {
+    let tmp = tempfile::tempdir()?;
+    let config_path = gix::init(tmp.path())?.git_dir().join("config");
+    let mut config = read(&config_path);
+    config.push_str("# a comment that is kept\n[custom]\n\tkey = original ; trailing comment\n");
+    std::fs::write(&config_path, &config)?;
+    let mut repo = gix::open_opts(tmp.path(), restricted())?;
+
+    let mut snapshot = repo.config_snapshot_mut_at(gix::config::Source::Local)?;
+    snapshot.set_raw_value("custom", None, "key", "discarded")?;
+    assert!(
+        config_lock(tmp.path()).exists(),
+        "the file is locked before it's read, and stays locked"
+    );
+    snapshot.forget();
+    assert_eq!(
+        read(&config_path),
+        config,
+        "forgetting the snapshot discards all changes"
+    );
+    assert!(!config_lock(tmp.path()).exists());
+
+    {
+        let mut snapshot = repo.config_snapshot_mut_at(gix::config::Source::Local)?;
+        snapshot.set_raw_value("custom", None, "key", "discarded")?;
+    }
+    assert_eq!(read(&config_path), config, "dropping the snapshot discards all changes");
+    assert!(!config_lock(tmp.path()).exists(), "and releases the lock");
+
+    let mut snapshot = repo.config_snapshot_mut_at(gix::config::Source::Local)?;
+    assert_eq!(
+        snapshot.sections().count(),
+        2,
+        "only the file of the source is contained, without the configuration of the user or the system"
+    );
+    assert_eq!(
+        snapshot.string_by_key("custom.key").expect("present").as_ref(),
+        "original"
+    );
+    snapshot
+        .section_mut("custom", None)?
+        .push("added".try_into()?, Some("value".into()));
+    snapshot.commit()?;
+
+    let written = read(&config_path);
+    assert_eq!(
+        written,
+        format!("{config}\tadded = value\n"),
+        "comments and formatting are preserved"
+    );
+    assert!(
+        !repo.git_dir().join("config.lock").exists(),
+        "the file was replaced through a lock"
+    );
+    assert_eq!(
+        repo.config_snapshot().string("custom.added").expect("present").as_ref(),
+        "value",
+        "the configuration was reloaded"
+    );
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn global_configuration_is_created_if_needed() -> crate::Result {
+    let tmp = tempfile::tempdir()?;
+    let global_path = tmp.path().join("global.config");
+    let _env = Env::new().set("GIT_CONFIG_GLOBAL", global_path.to_str().expect("valid UTF-8"));
+    let mut repo = gix::open_opts(gix::init(tmp.path().join("repo"))?.path(), restricted_and_git())?;
+
+    let mut snapshot = repo.config_snapshot_mut_at(gix::config::Source::User)?;
+    assert_eq!(snapshot.sections().count(), 0, "the file doesn't exist yet");
+    snapshot
+        .section_mut_or_create_new("custom", None)?
+        .push("key".try_into()?, Some("global".into()));
+    snapshot.commit()?;
+    assert_eq!(
+        read(&global_path),
+        "[custom]\n\tkey = global\n",
+        "the environment is respected if permitted"
+    );
+    Ok(())
+}
+
+#[test]
+fn sources_without_file_cannot_be_opened() -> crate::Result {
+    let tmp = tempfile::tempdir()?;
+    let mut repo = gix::open_opts(gix::init(tmp.path())?.path(), restricted())?;
+    for source in [
+        gix::config::Source::Env,
+        gix::config::Source::Cli,
+        gix::config::Source::Api,
+        gix::config::Source::EnvOverride,
+    ] {
+        assert!(matches!(
+            repo.config_snapshot_mut_at(source),
+            Err(snapshot_mut_at::Error::NoStorageLocation { config_source }) if config_source == source
+        ));
+    }
+    assert!(
+        matches!(
+            repo.config_snapshot_mut_at(gix::config::Source::User),
+            Err(snapshot_mut_at::Error::NoStorageLocation { .. })
+        ),
+        "the location of global files can't be determined if the environment can't be used"
+    );
+    Ok(())
+}
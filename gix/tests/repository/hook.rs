@@ -0,0 +1,211 @@
+use std::path::{Path, PathBuf};
+
+use gix::bstr::ByteSlice;
+use gix_ref::transaction::PreviousValue;
+use gix_sec::Permission;
+
+use crate::util::restricted;
+
+/// Return a writable copy of the hooks fixture, and the path to its repository whose hooks log their invocations.
+fn fixture() -> crate::Result<(gix_testtools::tempfile::TempDir, PathBuf)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_hooks_repo.sh")?;
+    let repo = tmp.path().join("repo");
+    Ok((tmp, repo))
+}
+
+fn hooks(full: Permission, reduced: Permission) -> gix::open::Options {
+    let mut opts = restricted();
+    opts.permissions.hooks = gix_sec::trust::Mapping { full, reduced };
+    opts
+}
+
+fn open(path: &Path, opts: gix::open::Options) -> crate::Result<gix::Repository> {
+    Ok(gix::open_opts(path, opts)?)
+}
+
+fn hook_log(repo: &Path) -> crate::Result<String> {
+    Ok(std::fs::read_to_string(repo.join("hook.log")).or_else(|err| {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            Ok(String::new())
+        } else {
+            Err(err)
+        }
+    })?)
+}
+
+/// Replace the hook `name` with one that fails.
+fn reject(repo: &Path, name: &str) -> crate::Result {
+    std::fs::copy(repo.join(".git/hooks/reject"), repo.join(".git/hooks").join(name))?;
+    Ok(())
+}
+
+/// Commit on top of `HEAD` as `git` did in the fixture.
+#[allow(clippy::result_large_err)]
+fn commit(repo: &gix::Repository) -> Result<gix::Id<'_>, gix::commit::Error> {
+    let head = repo.head_commit().expect("valid");
+    let author =
+        gix_actor::SignatureRef::from_bytes::<()>(b"author <author@example.com> 946684800 +0000").expect("valid");
+    let committer =
+        gix_actor::SignatureRef::from_bytes::<()>(b"committer <committer@example.com> 946771200 +0000").expect("valid");
+    repo.commit_as(
+        committer,
+        author,
+        "HEAD",
+        "second\n",
+        head.tree_id().expect("valid"),
+        Some(head.id),
+    )
+}
+
+#[test]
+fn commit_and_reference_creation_match_git() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let repo = open(&path, hooks(Permission::Allow, Permission::Deny))?;
+    let id = commit(&repo)?;
+    repo.reference(
+        "refs/heads/other",
+        id,
+        PreviousValue::MustNotExist,
+        "branch: Created from HEAD",
+    )?;
+
+    let git_repo = open(&tmp.path().join("git-repo"), restricted())?;
+    assert_eq!(
+        id,
+        git_repo.head_id()?,
+        "the commit-msg hook added the same trailer to the message"
+    );
+    assert_eq!(
+        hook_log(&path)?,
+        std::fs::read_to_string(tmp.path().join("git-hook.log"))?,
+        "hooks ran in the work tree, in the same order and with the same arguments and input"
+    );
+    Ok(())
+}
+
+#[test]
+fn failing_hooks_prevent_commits() -> crate::Result {
+    for hook in ["pre-commit", "commit-msg"] {
+        let (_tmp, path) = fixture()?;
+        reject(&path, hook)?;
+        let repo = open(&path, hooks(Permission::Allow, Permission::Allow))?;
+        let head = repo.head_id()?;
+        match commit(&repo) {
+            Err(gix::commit::Error::Hook(gix::hook::Error::Failed { name, status })) => {
+                assert_eq!(name, hook);
+                assert_eq!(status.code(), Some(1));
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        assert_eq!(repo.head_id()?, head, "HEAD didn't change");
+        assert!(
+            !hook_log(&path)?.contains("reference-transaction"),
+            "references weren't touched"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn failing_post_commit_hook_is_ignored() -> crate::Result {
+    let (_tmp, path) = fixture()?;
+    reject(&path, "post-commit")?;
+    let repo = open(&path, hooks(Permission::Allow, Permission::Allow))?;
+    let id = commit(&repo)?;
+    assert_eq!(repo.head_id()?, id);
+    Ok(())
+}
+
+#[test]
+fn reference_transaction_hook_can_abort_the_transaction() -> crate::Result {
+    let (_tmp, path) = fixture()?;
+    let hooks_dir = path.join("rejecting-hooks");
+    std::fs::create_dir(&hooks_dir)?;
+    std::fs::copy(path.join(".git/hooks/reject"), hooks_dir.join("reference-transaction"))?;
+    let repo = open(
+        &path,
+        hooks(Permission::Allow, Permission::Allow).cli_overrides(["core.hooksPath=rejecting-hooks"]),
+    )?;
+    assert_eq!(
+        repo.hooks_dir()?,
+        hooks_dir,
+        "relative paths are relative to the work tree"
+    );
+
+    let head = repo.head_id()?;
+    let err = repo
+        .reference("refs/heads/other", head, PreviousValue::MustNotExist, "")
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            gix::reference::edit::Error::Hook(gix::hook::Error::Failed { ref name, .. }) if name == "reference-transaction"
+        ),
+        "{err:?}"
+    );
+    assert!(repo.try_find_reference("refs/heads/other")?.is_none());
+    assert_eq!(hook_log(&path)?, "", "hooks of the repository are ignored");
+    Ok(())
+}
+
+#[test]
+fn permissions_depend_on_trust() -> crate::Result {
+    let (_tmp, path) = fixture()?;
+    let repo = open(&path, restricted())?;
+    assert!(
+        repo.hook("pre-commit")?.is_none(),
+        "isolated repositories don't run hooks"
+    );
+    commit(&repo)?;
+    assert_eq!(hook_log(&path)?, "");
+
+    let secure = gix::Permissions::secure().hooks;
+    let repo = open(&path, hooks(secure.full, secure.reduced).with(gix_sec::Trust::Reduced))?;
+    assert!(
+        repo.hook("pre-commit")?.is_none(),
+        "by default, hooks don't run in repositories owned by someone else"
+    );
+    let repo = open(&path, hooks(secure.full, secure.reduced).with(gix_sec::Trust::Full))?;
+    let hook = repo.hook("pre-commit")?.expect("hooks run in our own repositories");
+    assert_eq!(hook.path, path.join(".git/hooks/pre-commit"));
+
+    let repo = open(&path, hooks(Permission::Forbid, Permission::Forbid))?;
+    assert!(
+        matches!(repo.hook("pre-commit"), Err(gix::hook::Error::Forbidden { .. })),
+        "existing hooks can be forbidden"
+    );
+    assert!(repo.hook("pre-push")?.is_none(), "hooks that don't exist are fine");
+    std::fs::remove_file(path.join(".git/hooks/pre-commit"))?;
+    assert!(
+        matches!(commit(&repo), Err(gix::commit::Error::Hook(gix::hook::Error::Forbidden { name, .. })) if name == "commit-msg")
+    );
+    Ok(())
+}
+
+#[test]
+fn hooks_receive_arguments_environment_and_input() -> crate::Result {
+    let (_tmp, path) = fixture()?;
+    std::fs::write(
+        path.join(".git/hooks/custom"),
+        "#!/bin/sh\n{ echo \"$* $(pwd) $GIT_DIR $CUSTOM\"; cat; } > custom.log\n",
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path.join(".git/hooks/custom"), std::fs::Permissions::from_mode(0o755))?;
+    }
+    let repo = open(&path, hooks(Permission::Allow, Permission::Allow))?;
+    repo.hook("custom")?
+        .expect("present")
+        .args(["a", "b"])
+        .env("CUSTOM", "value")
+        .stdin("input\n")
+        .run()?;
+    let log = std::fs::read(path.join("custom.log"))?;
+    assert_eq!(
+        log.as_bstr(),
+        format!("a b {} {} value\ninput\n", path.display(), path.join(".git").display()),
+        "hooks run in the work tree"
+    );
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use gix::{
+    bstr::{BString, ByteSlice},
+    merge::tree::{ConflictKind, Options},
+    prelude::FindExt,
+};
+
+use crate::util::named_repo;
+
+/// Merge the branches of scenario `name` and assert that the merged tree and its conflicting entries are the same as
+/// the ones produced by `git merge-tree`, returning the kinds of conflicts.
+fn merge_like_git(name: &str) -> crate::Result<Vec<ConflictKind>> {
+    let repo = named_repo("make_merge_trees_repo.sh")?;
+    let mut options = Options::from_repo(&repo)?;
+    options.blob.ours_label = Some(format!("{name}-ours"));
+    options.blob.theirs_label = Some(format!("{name}-theirs"));
+    let id = |spec: String| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec.as_str())?.detach()) };
+    let outcome = repo.merge_trees_opts(
+        id(format!("{name}-base"))?,
+        id(format!("{name}-ours"))?,
+        id(format!("{name}-theirs"))?,
+        &options,
+    )?;
+
+    let expected = std::fs::read(repo.git_dir().join(format!("{name}.expected")))?;
+    let mut lines = expected.lines();
+    let expected_tree = lines.next().expect("tree id").to_str()?;
+    let expected_stages: Vec<BString> = lines.take_while(|line| !line.is_empty()).map(Into::into).collect();
+    assert_eq!(
+        outcome.tree.to_string(),
+        expected_tree,
+        "{name}: the merged tree is the same"
+    );
+
+    let mut actual_stages: Vec<_> = outcome
+        .conflicts
+        .iter()
+        .flat_map(|c| c.stages())
+        .map(|(stage, e)| (e.location.clone(), stage, e))
+        .collect();
+    actual_stages.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+    let actual_stages: Vec<BString> = actual_stages
+        .into_iter()
+        .map(|(location, stage, e)| format!("{:o} {} {stage}\t{location}", e.mode as u32, e.id).into())
+        .collect();
+    assert_eq!(
+        actual_stages, expected_stages,
+        "{name}: conflicting entries are the same"
+    );
+    assert_eq!(outcome.is_clean(), outcome.conflicts.is_empty());
+    Ok(outcome.conflicts.iter().map(|c| c.kind).collect())
+}
+
+#[test]
+fn clean_with_renames_mode_changes_additions_and_deletions() -> crate::Result {
+    assert_eq!(merge_like_git("clean")?, []);
+    Ok(())
+}
+
+#[test]
+fn content() -> crate::Result {
+    assert_eq!(merge_like_git("content")?, [ConflictKind::Content]);
+    Ok(())
+}
+
+#[test]
+fn modify_delete_and_rename_delete() -> crate::Result {
+    assert_eq!(
+        merge_like_git("modify_delete")?,
+        [ConflictKind::ModifyDelete, ConflictKind::ModifyDelete]
+    );
+    Ok(())
+}
+
+#[test]
+fn rename_rename() -> crate::Result {
+    assert_eq!(merge_like_git("rename_rename")?, [ConflictKind::RenameRename]);
+    Ok(())
+}
+
+#[test]
+fn add_add() -> crate::Result {
+    assert_eq!(merge_like_git("add_add")?, [ConflictKind::AddAdd]);
+    Ok(())
+}
+
+#[test]
+fn directory_file() -> crate::Result {
+    assert_eq!(merge_like_git("directory_file")?, [ConflictKind::DirectoryFile]);
+    Ok(())
+}
+
+#[test]
+fn conflicts_can_be_written_to_an_index() -> crate::Result {
+    let repo = named_repo("make_merge_trees_repo.sh")?;
+    let outcome = repo.merge_trees(
+        repo.rev_parse_single("content-base")?,
+        repo.rev_parse_single("content-ours")?,
+        repo.rev_parse_single("content-theirs")?,
+    )?;
+    let merged = repo
+        .find_object(outcome.tree)?
+        .peel_to_tree()?
+        .lookup_entry_by_path("a")?;
+    assert_eq!(
+        merged.expect("present").object()?.data.as_bstr(),
+        "1\n2\n3\n4\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n6\n7\n8\n9\n10\n",
+        "labels default to 'ours' and 'theirs'"
+    );
+
+    let mut index = gix::index::State::from_tree(&outcome.tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+    outcome.write_conflicts_to(&mut index);
+    let entries: Vec<_> = index
+        .entries()
+        .iter()
+        .filter(|e| !e.flags.contains(gix::index::entry::Flags::REMOVE))
+        .map(|e| (e.path(&index).to_owned(), e.stage()))
+        .collect();
+    assert_eq!(entries, [("a".into(), 1), ("a".into(), 2), ("a".into(), 3)]);
+    Ok(())
+}
+
+#[test]
+fn rename_tracking_is_configured_by_merge_renames_and_falls_back_to_diff_renames() -> crate::Result {
+    fn set(repo: &mut gix::Repository, key: &'static str, value: &str) -> crate::Result {
+        let (section, key) = key.split_once('.').expect("section.key");
+        repo.config_snapshot_mut().set_raw_value(section, None, key, value)?;
+        Ok(())
+    }
+    let mut repo = named_repo("make_merge_trees_repo.sh")?;
+    let rewrites = Options::from_repo(&repo)?
+        .rewrites
+        .expect("renames are tracked by default");
+    assert_eq!(rewrites.copies, None, "copies are never tracked");
+    assert_eq!(rewrites.limit, 7000, "the default limit is the one of git merge");
+
+    set(&mut repo, "diff.renames", "false")?;
+    set(&mut repo, "diff.renameLimit", "42")?;
+    assert_eq!(
+        Options::from_repo(&repo)?.rewrites,
+        None,
+        "diff.renames is used if merge.renames is unset"
+    );
+
+    set(&mut repo, "merge.renames", "copies")?;
+    let rewrites = Options::from_repo(&repo)?
+        .rewrites
+        .expect("merge.renames overrides diff.renames");
+    assert_eq!(rewrites.copies, None, "copies are still not tracked");
+    assert_eq!(
+        rewrites.limit, 42,
+        "diff.renameLimit is used if merge.renameLimit is unset"
+    );
+
+    set(&mut repo, "merge.renameLimit", "5")?;
+    assert_eq!(Options::from_repo(&repo)?.rewrites.expect("still enabled").limit, 5);
+
+    set(&mut repo, "merge.renames", "false")?;
+    assert_eq!(Options::from_repo(&repo)?.rewrites, None);
+    Ok(())
+}
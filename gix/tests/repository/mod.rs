@@ -1,10 +1,18 @@
 use gix::Repository;
 
+mod apply;
+mod blame;
+mod compat;
 mod config;
+mod hook;
+mod merge;
 mod object;
 mod open;
 mod reference;
 mod remote;
+mod sequencer;
+mod signing;
+mod stash;
 mod state;
 mod worktree;
 
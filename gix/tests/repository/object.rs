@@ -304,6 +304,98 @@ mod commit {
     }
 }
 
+mod sha256 {
+    use gix::bstr::ByteSlice;
+    use gix_testtools::tempfile;
+
+    use crate::util::hex_to_id;
+
+    #[test]
+    fn objects_and_index_written_by_git_can_be_read() -> crate::Result {
+        let repo = crate::named_repo("make_sha256_repo.sh")?;
+        assert_eq!(repo.object_hash(), gix_hash::Kind::Sha256);
+
+        let head = repo.head_commit()?;
+        assert_eq!(head.id.kind(), gix_hash::Kind::Sha256);
+        assert_eq!(head.message_raw()?, "second\n", "loose commit");
+        let entry = head.tree()?.lookup_entry_by_path("dir/file")?.expect("present in tree");
+        assert_eq!(
+            entry.oid(),
+            hex_to_id("e18941661c834f08aa0a19e626484916937df12c0e08d5f015b3b53d0284aa02")
+        );
+        assert_eq!(entry.object()?.data.as_bstr(), "other\n");
+
+        let parent = repo
+            .find_object(head.parent_ids().next().expect("one parent"))?
+            .try_into_commit()?;
+        assert_eq!(parent.message_raw()?, "initial\n", "packed commit");
+        let entry = parent.tree()?.lookup_entry_by_path("file")?.expect("present in tree");
+        assert_eq!(entry.object()?.data.as_bstr(), "content\n", "packed blob");
+
+        let index = repo.open_index()?;
+        assert_eq!(
+            index.entries().iter().map(|e| e.id).collect::<Vec<_>>(),
+            [
+                hex_to_id("e18941661c834f08aa0a19e626484916937df12c0e08d5f015b3b53d0284aa02"),
+                hex_to_id("db7a000ac754a51996d44c2aad6e5dd7b92d9fec8082f1fe9b9072bb84ed879c")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn objects_written_to_a_new_repository_are_valid_for_git() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let repo = gix::ThreadSafeRepository::init_opts(
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            gix::create::Options {
+                object_hash: gix_hash::Kind::Sha256,
+                ..Default::default()
+            },
+            crate::restricted(),
+        )?
+        .to_thread_local();
+        assert_eq!(repo.object_hash(), gix_hash::Kind::Sha256);
+
+        let blob_id = repo.write_blob("other\n")?;
+        assert_eq!(
+            blob_id,
+            hex_to_id("e18941661c834f08aa0a19e626484916937df12c0e08d5f015b3b53d0284aa02"),
+            "ids match the ones produced by git"
+        );
+        let empty_tree_id = repo.write_object(&gix::objs::Tree::empty())?;
+        assert_eq!(empty_tree_id, gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha256));
+        let tree_id = repo.write_object(&gix::objs::Tree {
+            entries: vec![gix::objs::tree::Entry {
+                mode: gix::objs::tree::EntryMode::Blob,
+                filename: "file".into(),
+                oid: blob_id.detach(),
+            }],
+        })?;
+        let commit_id = repo.commit("HEAD", "initial", tree_id, gix::commit::NO_PARENT_IDS)?;
+
+        let commit = repo.find_object(commit_id)?.try_into_commit()?;
+        assert_eq!(commit.tree_id()?, tree_id);
+        assert_eq!(
+            commit.tree()?.lookup_entry_by_path("file")?.expect("present").oid(),
+            blob_id.detach()
+        );
+
+        let fsck = std::process::Command::new("git")
+            .current_dir(tmp.path())
+            .args(["fsck", "--strict", "--no-dangling"])
+            .output()?;
+        assert!(fsck.status.success(), "{}", fsck.stderr.as_bstr());
+        let rev_parse = std::process::Command::new("git")
+            .current_dir(tmp.path())
+            .args(["rev-parse", "HEAD"])
+            .output()?;
+        assert_eq!(rev_parse.stdout.trim().as_bstr(), commit_id.to_string());
+        Ok(())
+    }
+}
+
 fn empty_bare_repo() -> crate::Result<(tempfile::TempDir, gix::Repository)> {
     let tmp = tempfile::tempdir()?;
     let repo = gix::ThreadSafeRepository::init_opts(
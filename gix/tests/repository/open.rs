@@ -55,6 +55,82 @@ mod not_a_repository {
     }
 }
 
+mod repository_format {
+    use gix::{bstr::BString, config};
+
+    #[allow(clippy::result_large_err)]
+    fn open(name: &str) -> Result<gix::Repository, gix::open::Error> {
+        let repo_path = gix_testtools::scripted_fixture_read_only("make_repository_format_repos.sh")
+            .expect("fixture script succeeds")
+            .join(name);
+        gix::open_opts(repo_path, gix::open::Options::isolated())
+    }
+
+    fn names(names: &[&str]) -> Vec<BString> {
+        names.iter().map(|name| BString::from(*name)).collect()
+    }
+
+    #[test]
+    fn unknown_extensions_are_ignored_in_version_0() -> crate::Result {
+        open("v0-unknown-extension")?;
+        Ok(())
+    }
+
+    #[test]
+    fn known_extensions_in_version_1() -> crate::Result {
+        let repo = open("v1-known-extensions")?;
+        assert_eq!(repo.object_hash(), gix_hash::Kind::Sha1);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_extensions_are_rejected_in_version_1() {
+        assert!(matches!(
+            open("v1-unknown-extensions"),
+            Err(gix::open::Error::Config(config::Error::UnknownExtensions { names: actual }))
+                if actual == names(&["unknown", "sub.other"])
+        ));
+    }
+
+    #[test]
+    fn version_1_extensions_are_rejected_in_version_0() {
+        assert!(matches!(
+            open("v0-v1-only-extension"),
+            Err(gix::open::Error::Config(config::Error::ExtensionsRequireVersion1 { names: actual }))
+                if actual == names(&["objectFormat"])
+        ));
+    }
+
+    #[test]
+    fn reftable_reference_storage() -> crate::Result {
+        let repo = open("v1-reftable")?;
+        assert_eq!(
+            repo.references()?.all()?.count(),
+            0,
+            "references are read from the stack of tables, which doesn't exist yet"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_reference_storage() {
+        assert!(matches!(
+            open("v1-unknown-ref-storage"),
+            Err(gix::open::Error::Config(config::Error::UnsupportedRefStorage { name })) if name == "unknown"
+        ));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert!(matches!(
+            open("v2"),
+            Err(gix::open::Error::Config(
+                config::Error::UnsupportedRepositoryFormatVersion { version: 2 }
+            ))
+        ));
+    }
+}
+
 mod open_path_as_is {
 
     use crate::util::{named_subrepo_opts, repo_opts};
@@ -224,3 +224,116 @@ mod head {
         Ok(())
     }
 }
+
+mod reftable {
+    use gix::refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit},
+        Target,
+    };
+
+    #[test]
+    fn references_are_read_from_and_written_to_the_stack_of_tables() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable("make_repository_format_repos.sh")?;
+        let repo_path = tmp.path().join("v1-reftable");
+        let repo = gix::open_opts(&repo_path, crate::restricted())?;
+        repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::MustNotExist,
+                new: Target::Symbolic("refs/heads/main".try_into()?),
+            },
+            name: "HEAD".try_into()?,
+            deref: false,
+        })?;
+        let empty_tree_id = repo.write_object(gix::objs::Tree::empty())?;
+        let commit_id = repo.commit("HEAD", "initial", empty_tree_id, gix::commit::NO_PARENT_IDS)?;
+        repo.tag_reference("v1", commit_id, PreviousValue::MustNotExist)?;
+
+        let git_dir = repo_path.join(".git");
+        assert!(
+            !git_dir.join("refs/heads/main").exists() && !git_dir.join("packed-refs").exists(),
+            "nothing is written to the files backend"
+        );
+        assert!(git_dir.join("reftable/tables.list").is_file());
+
+        let repo = gix::open_opts(&repo_path, crate::restricted())?;
+        assert_eq!(repo.head_name()?.expect("symbolic").as_bstr(), "refs/heads/main");
+        assert_eq!(repo.head_id()?, commit_id);
+        assert_eq!(repo.rev_parse_single("v1")?, commit_id);
+        assert_eq!(
+            repo.references()?
+                .prefixed("refs/")?
+                .peeled()
+                .filter_map(Result::ok)
+                .map(|r| (r.name().as_bstr().to_owned(), r.id().detach()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("refs/heads/main".into(), commit_id.detach()),
+                ("refs/tags/v1".into(), commit_id.detach())
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reflogs_are_read_from_and_rewritten_in_the_stack_of_tables() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable("make_repository_format_repos.sh")?;
+        let repo_path = tmp.path().join("v1-reftable");
+        let repo = gix::open_opts(&repo_path, crate::restricted())?;
+        let empty_tree_id = repo.write_object(gix::objs::Tree::empty())?;
+        let first = repo.commit("HEAD", "first", empty_tree_id, gix::commit::NO_PARENT_IDS)?;
+        let second = repo.commit("HEAD", "second", empty_tree_id, Some(first))?;
+
+        let messages = |log: &mut gix::refs::store::reflog::Platform<'_, '_>| -> crate::Result<Vec<String>> {
+            Ok(log
+                .rev()?
+                .expect("log present")
+                .map(|line| line.map(|line| line.message.to_string()))
+                .collect::<Result<_, _>>()?)
+        };
+        assert_eq!(
+            messages(&mut repo.head()?.log_iter())?,
+            ["commit: second", "commit (initial): first"]
+        );
+        assert_eq!(
+            repo.head()?.log_iter().all()?.expect("log present").count(),
+            2,
+            "the forward iterator sees the same lines"
+        );
+        assert_eq!(repo.rev_parse_single("HEAD@{1}")?, first);
+        assert!(
+            !repo_path.join(".git/logs").exists(),
+            "nothing is written to the files backend"
+        );
+
+        repo.write_index(&mut gix::index::File::from_state(
+            gix::index::State::new(repo.object_hash()),
+            repo.index_path(),
+        ))?;
+        let work_dir = repo.work_dir().expect("non-bare").to_owned();
+        let mut stashes = Vec::new();
+        for content in ["one", "two"] {
+            std::fs::write(work_dir.join("file"), content)?;
+            let id = repo
+                .stash_push(&gix::stash::PushOptions {
+                    message: Some(content.into()),
+                    include_untracked: true,
+                    keep_index: false,
+                })?
+                .expect("there are changes to stash");
+            stashes.push(id.detach());
+        }
+        assert_eq!(repo.stash_list()?.len(), 2);
+        assert_eq!(repo.stash_drop(0)?, stashes[1]);
+        let remaining = repo.stash_list()?;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, stashes[0]);
+        assert_eq!(
+            repo.find_reference("refs/stash")?.id(),
+            stashes[0],
+            "the reference points to the most recent remaining stash"
+        );
+        assert_eq!(repo.head_id()?, second, "the head is untouched");
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use gix::{
+    bstr::ByteSlice,
+    sequencer::{Empty, Error, Operation, Options, Outcome},
+    ObjectId,
+};
+
+use crate::util::{named_repo, repo_rw};
+
+fn id(repo: &gix::Repository, tag: &str) -> crate::Result<ObjectId> {
+    Ok(repo.rev_parse_single(format!("refs/tags/{tag}").as_str())?.detach())
+}
+
+fn baseline(repo: &gix::Repository, name: &str) -> crate::Result<String> {
+    Ok(std::fs::read_to_string(repo.git_dir().join(name))?)
+}
+
+/// Assert that `outcome` is a commit on top of `main` with the same tree and message as the one produced by `git`, and with
+/// the same author unless `author` is given.
+fn assert_commit_like_git(
+    repo: &gix::Repository,
+    outcome: Outcome<'_>,
+    name: &str,
+    author: Option<&str>,
+) -> crate::Result {
+    let id = match outcome {
+        Outcome::Commit(id) => id,
+        other => panic!("{name}: expected a commit, got {other:?}"),
+    };
+    let commit = id.object()?.into_commit();
+    assert_eq!(
+        commit.parent_ids().map(|id| id.detach()).collect::<Vec<_>>(),
+        [repo.rev_parse_single("main")?.detach()]
+    );
+    assert_eq!(
+        commit.tree_id()?.to_string(),
+        baseline(repo, &format!("{name}.tree"))?.trim_end(),
+        "{name}: tree"
+    );
+    assert_eq!(
+        commit.message_raw()?,
+        baseline(repo, &format!("{name}.message"))?.as_str(),
+        "{name}: message"
+    );
+    let actual_author = commit.author()?;
+    assert_eq!(
+        format!("{} <{}>", actual_author.name, actual_author.email),
+        match author {
+            Some(author) => author.to_owned(),
+            None => baseline(repo, &format!("{name}.author"))?.trim_end().to_owned(),
+        },
+        "{name}: author"
+    );
+    Ok(())
+}
+
+#[test]
+fn cherry_pick_records_origin_and_preserves_author() -> crate::Result {
+    let repo = named_repo("make_sequencer_repo.sh")?;
+    let main = repo.rev_parse_single("main")?;
+    let options = Options {
+        record_origin: true,
+        ..Options::from_repo(&repo)?
+    };
+    for name in ["pick-clean", "pick-author"] {
+        let outcome = repo.cherry_pick(id(&repo, name)?, main, &options)?;
+        assert_commit_like_git(&repo, outcome, name, None)?;
+    }
+
+    let options = Options {
+        reset_author: true,
+        ..options
+    };
+    let outcome = repo.cherry_pick(id(&repo, "pick-author")?, main, &options)?;
+    match outcome {
+        Outcome::Commit(id) => assert_eq!(id.object()?.into_commit().author()?.name, "gitoxide"),
+        other => panic!("expected commit, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn cherry_pick_keeps_the_message_encoding() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_sequencer_repo.sh")?;
+    let main = repo.rev_parse_single("main")?.detach();
+    let original = repo.find_object(id(&repo, "pick-clean")?)?.into_commit();
+    let mut latin1: gix_object::Commit = original.decode()?.into();
+    latin1.encoding = Some("ISO-8859-1".into());
+    latin1.message = b"caf\xe9\n".as_slice().into();
+    let picked = repo.write_object(&latin1)?;
+
+    match repo.cherry_pick(picked, main, &Options::from_repo(&repo)?)? {
+        Outcome::Commit(id) => {
+            let commit = id.object()?.into_commit();
+            let commit = commit.decode()?;
+            assert_eq!(commit.encoding, Some(b"ISO-8859-1".as_bstr()));
+            assert_eq!(commit.message, b"caf\xe9\n".as_bstr(), "the message is kept as is");
+        }
+        other => panic!("expected commit, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn revert() -> crate::Result {
+    let repo = named_repo("make_sequencer_repo.sh")?;
+    let outcome = repo.revert(
+        id(&repo, "revert-me")?,
+        repo.rev_parse_single("main")?,
+        &Options::from_repo(&repo)?,
+    )?;
+    assert_commit_like_git(&repo, outcome, "revert", Some("gitoxide <gitoxide@localhost>"))
+}
+
+#[test]
+fn merge_commits_need_a_mainline() -> crate::Result {
+    let repo = named_repo("make_sequencer_repo.sh")?;
+    let main = repo.rev_parse_single("main")?;
+    let merge = id(&repo, "merge")?;
+    let mut options = Options::from_repo(&repo)?;
+    assert!(matches!(
+        repo.revert(merge, main, &options),
+        Err(Error::MainlineMissing { .. })
+    ));
+    options.mainline = Some(3);
+    assert!(matches!(
+        repo.revert(merge, main, &options),
+        Err(Error::MainlineOutOfBounds { mainline: 3, .. })
+    ));
+
+    options.mainline = Some(1);
+    let outcome = repo.revert(merge, main, &options)?;
+    assert_commit_like_git(&repo, outcome, "revert-merge", Some("gitoxide <gitoxide@localhost>"))?;
+    options.mainline = Some(2);
+    assert!(
+        matches!(repo.cherry_pick(merge, main, &options), Err(Error::EmptyResult { .. })),
+        "the changes of the merge relative to its second parent are already on main"
+    );
+
+    assert!(matches!(
+        repo.cherry_pick(id(&repo, "pick-clean")?, main, &options),
+        Err(Error::MainlineOnNonMerge { mainline: 2, .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn empty_commits() -> crate::Result {
+    let repo = named_repo("make_sequencer_repo.sh")?;
+    let mut options = Options::from_repo(&repo)?;
+    let empty = id(&repo, "pick-empty")?;
+    let main = repo.rev_parse_single("main")?.detach();
+    assert!(matches!(
+        repo.cherry_pick(empty, main, &options),
+        Err(Error::EmptyCommit { .. })
+    ));
+    options.allow_empty = true;
+    match repo.cherry_pick(empty, main, &options)? {
+        Outcome::Commit(id) => assert_eq!(
+            id.object()?.into_commit().tree_id()?,
+            repo.find_object(main)?.into_commit().tree_id()?,
+            "originally empty commits are kept"
+        ),
+        other => panic!("expected commit, got {other:?}"),
+    }
+
+    let picked_onto_itself = id(&repo, "pick-clean")?;
+    assert!(matches!(
+        repo.cherry_pick(picked_onto_itself, picked_onto_itself, &options),
+        Err(Error::EmptyResult { .. })
+    ));
+    options.empty = Empty::Drop;
+    assert!(matches!(
+        repo.cherry_pick(picked_onto_itself, picked_onto_itself, &options)?,
+        Outcome::Dropped
+    ));
+    options.empty = Empty::Keep;
+    assert!(matches!(
+        repo.cherry_pick(picked_onto_itself, picked_onto_itself, &options)?,
+        Outcome::Commit(_)
+    ));
+    Ok(())
+}
+
+#[test]
+fn conflicts_can_be_written_like_git() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_sequencer_repo.sh")?;
+    let picked = id(&repo, "pick-conflict")?;
+    let outcome = repo.cherry_pick(picked, repo.head_id()?, &Options::from_repo(&repo)?)?;
+    let conflicted = match outcome {
+        Outcome::Conflict(conflicted) => conflicted,
+        other => panic!("expected conflict, got {other:?}"),
+    };
+    assert_eq!(conflicted.operation, Operation::CherryPick);
+    let merged = repo
+        .find_object(conflicted.merge.tree)?
+        .peel_to_tree()?
+        .lookup_entry_by_path("a")?
+        .expect("present")
+        .object()?;
+    assert!(
+        merged.data.contains_str(format!(
+            ">>>>>>> {}... change line 9 on feature",
+            picked.to_hex_with_len(7)
+        )),
+        "the picked commit is used as label"
+    );
+
+    conflicted.write_state()?;
+    assert_eq!(repo.state(), Some(gix::state::InProgress::CherryPick));
+    for (file, baseline_file) in [
+        ("CHERRY_PICK_HEAD", "pick-conflict.head"),
+        ("MERGE_MSG", "pick-conflict.message"),
+    ] {
+        assert_eq!(
+            std::fs::read_to_string(repo.git_dir().join(file))?,
+            baseline(&repo, baseline_file)?,
+            "{file}"
+        );
+    }
+    let index = repo.open_index()?;
+    let stages: String = index
+        .entries()
+        .iter()
+        .filter(|e| e.stage() != 0)
+        .map(|e| format!("{:o} {} {}\t{}\n", e.mode.bits(), e.id, e.stage(), e.path(&index)))
+        .collect();
+    assert_eq!(stages, baseline(&repo, "pick-conflict.stages")?);
+    Ok(())
+}
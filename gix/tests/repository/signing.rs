@@ -0,0 +1,508 @@
+use std::path::{Path, PathBuf};
+
+use gix::bstr::{BString, ByteSlice};
+use gix_ref::transaction::PreviousValue;
+
+use crate::util::restricted;
+
+/// Return a writable copy of the signing fixture, and the path to its repository.
+fn fixture() -> crate::Result<(gix_testtools::tempfile::TempDir, PathBuf)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_signing_repo.sh")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(tmp.path().join("signing-key"), std::fs::Permissions::from_mode(0o600))?;
+    }
+    let repo = tmp.path().join("repo");
+    Ok((tmp, repo))
+}
+
+fn open(path: &Path, config: impl IntoIterator<Item = String>) -> crate::Result<gix::Repository> {
+    Ok(gix::open_opts(path, restricted().cli_overrides(config))?)
+}
+
+fn ssh_config(tmp: &gix_testtools::tempfile::TempDir, sign: &str) -> Vec<String> {
+    vec![
+        format!("{sign}.gpgSign=true"),
+        "gpg.format=ssh".into(),
+        format!("user.signingKey={}", tmp.path().join("signing-key").display()),
+    ]
+}
+
+/// Write a program to `dir` that behaves like `gpg` and `gpgsm` when signing, and which records its arguments and the
+/// signed payload.
+fn fake_gpg(dir: &Path, succeed: bool) -> crate::Result<PathBuf> {
+    let program = dir.join("fake-gpg");
+    std::fs::write(
+        &program,
+        format!(
+            r#"#!/bin/sh
+echo "$@" > "{dir}/args"
+cat > "{dir}/payload"
+{}
+echo "[GNUPG:] BEGIN_SIGNING H8" >&2
+echo "[GNUPG:] SIG_CREATED D 22 8 00 1700000000 FINGERPRINT" >&2
+printf -- '-----BEGIN PGP SIGNATURE-----\n\nZmFrZQ==\n-----END PGP SIGNATURE-----\n'
+"#,
+            if succeed {
+                ""
+            } else {
+                "echo 'no secret key' >&2; exit 2"
+            },
+            dir = dir.display()
+        ),
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(program)
+}
+
+fn git(repo: &Path, tmp: &gix_testtools::tempfile::TempDir, args: &[&str]) -> crate::Result<std::process::Output> {
+    Ok(std::process::Command::new("git")
+        .current_dir(repo)
+        .arg("-c")
+        .arg(format!(
+            "gpg.ssh.allowedSignersFile={}",
+            tmp.path().join("allowed-signers").display()
+        ))
+        .args(args)
+        .output()?)
+}
+
+/// Commit the tree of `HEAD` on top of `HEAD`.
+fn commit(repo: &gix::Repository) -> crate::Result<gix::Id<'_>> {
+    let head = repo.head_commit()?;
+    Ok(repo.commit("HEAD", "signed\n", head.tree_id()?, Some(head.id))?)
+}
+
+/// Return the value of the `gpgsig` header of `id`, with continuation lines unfolded.
+fn gpgsig(repo: &gix::Repository, id: gix::Id<'_>) -> crate::Result<Option<BString>> {
+    let commit = repo.find_object(id)?.into_commit();
+    let signature = commit.decode()?.extra_headers().pgp_signature().map(ToOwned::to_owned);
+    Ok(signature)
+}
+
+#[test]
+fn ssh_signed_commit_verifies_with_git() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let repo = open(&path, ssh_config(&tmp, "commit"))?;
+    let id = commit(&repo)?;
+
+    let signature = gpgsig(&repo, id)?.expect("signed");
+    assert!(signature.starts_with(b"-----BEGIN SSH SIGNATURE-----\n"));
+    assert!(
+        signature.ends_with(b"\n-----END SSH SIGNATURE-----"),
+        "the trailing newline isn't part of the header"
+    );
+
+    let out = git(&path, &tmp, &["verify-commit", "HEAD"])?;
+    assert!(out.status.success(), "{}", out.stderr.as_bstr());
+    assert!(
+        out.stderr.contains_str("Good \"git\" signature for gitoxide@localhost"),
+        "{}",
+        out.stderr.as_bstr()
+    );
+    Ok(())
+}
+
+#[test]
+fn cherry_picked_commits_are_signed() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let repo = open(&path, ssh_config(&tmp, "commit"))?;
+    let options = gix::sequencer::Options {
+        allow_empty: true,
+        ..gix::sequencer::Options::from_repo(&repo)?
+    };
+    let picked = repo.rev_parse_single("ssh-unknown")?;
+    let id = match repo.cherry_pick(picked, repo.head_id()?, &options)? {
+        gix::sequencer::Outcome::Commit(id) => id,
+        other => panic!("expected commit, got {other:?}"),
+    };
+
+    assert!(
+        gpgsig(&repo, id)?.is_some(),
+        "the signature of the picked commit is replaced"
+    );
+    let out = git(&path, &tmp, &["verify-commit", &id.to_string()])?;
+    assert!(out.status.success(), "{}", out.stderr.as_bstr());
+    Ok(())
+}
+
+#[test]
+fn ssh_signed_tag_verifies_with_git() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let repo = open(&path, ssh_config(&tmp, "tag"))?;
+    let head = repo.head_id()?;
+    repo.tag(
+        "v1",
+        head,
+        gix_object::Kind::Commit,
+        Some(repo.committer().expect("present")?),
+        "no newline",
+        PreviousValue::MustNotExist,
+    )?;
+
+    let tag = repo.find_reference("refs/tags/v1")?.id().object()?;
+    assert!(tag.data.contains_str("no newline\n-----BEGIN SSH SIGNATURE-----\n"));
+    let out = git(&path, &tmp, &["verify-tag", "v1"])?;
+    assert!(out.status.success(), "{}", out.stderr.as_bstr());
+
+    let id = commit(&repo)?;
+    assert_eq!(gpgsig(&repo, id)?, None, "only tags are signed with tag.gpgSign");
+    Ok(())
+}
+
+#[test]
+fn openpgp_signs_the_commit_without_signature_with_the_committer_as_default_key() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let program = fake_gpg(tmp.path(), true)?;
+    let repo = open(
+        &path,
+        [
+            "commit.gpgSign=true".into(),
+            format!("gpg.program={}", program.display()),
+        ],
+    )?;
+    let id = commit(&repo)?;
+
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join("args"))?,
+        "--status-fd=2 -bsau gitoxide <gitoxide@localhost>\n"
+    );
+    let object = repo.find_object(id)?;
+    let data = object.data.as_bstr();
+    assert!(
+        data.contains_str(
+            "\ngpgsig -----BEGIN PGP SIGNATURE-----\n \n ZmFrZQ==\n -----END PGP SIGNATURE-----\n\nsigned\n"
+        ),
+        "empty lines in the signature are indented as well, and the header is followed by the message: {data}"
+    );
+    let payload = std::fs::read(tmp.path().join("payload"))?;
+    let commit = object.into_commit();
+    let mut unsigned = commit.decode()?.to_owned();
+    unsigned.extra_headers.clear();
+    let mut expected = Vec::new();
+    gix_object::WriteTo::write_to(&unsigned, &mut expected)?;
+    assert_eq!(
+        payload.as_bstr(),
+        expected.as_bstr(),
+        "the payload is the unsigned commit"
+    );
+    Ok(())
+}
+
+#[test]
+fn x509_uses_its_own_program_and_the_configured_key() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let program = fake_gpg(tmp.path(), true)?;
+    let repo = open(
+        &path,
+        [
+            "commit.gpgSign=true".into(),
+            "gpg.format=x509".into(),
+            "gpg.program=does-not-exist".into(),
+            format!("gpg.x509.program={}", program.display()),
+            "user.signingKey=0xDEADBEEF".into(),
+        ],
+    )?;
+    let id = commit(&repo)?;
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join("args"))?,
+        "--status-fd=2 -bsau 0xDEADBEEF\n"
+    );
+    assert!(gpgsig(&repo, id)?.is_some());
+    Ok(())
+}
+
+#[test]
+fn failed_signing_fails_the_commit() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let program = fake_gpg(tmp.path(), false)?;
+    let repo = open(
+        &path,
+        [
+            "commit.gpgSign=true".into(),
+            format!("gpg.openpgp.program={}", program.display()),
+        ],
+    )?;
+    let head = repo.head_id()?;
+    let err = commit(&repo).unwrap_err();
+    assert!(
+        err.to_string().ends_with("failed to sign the data: no secret key"),
+        "{err}"
+    );
+    assert_eq!(repo.head_id()?, head, "HEAD didn't change");
+    Ok(())
+}
+
+#[test]
+fn programs_may_write_before_reading_large_payloads() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let program = tmp.path().join("chatty-gpg");
+    std::fs::write(
+        &program,
+        r#"#!/bin/sh
+head -c 1000000 /dev/zero | tr '\0' '.' >&2
+echo >&2
+cat > /dev/null
+echo "[GNUPG:] SIG_CREATED D 22 8 00 1700000000 FINGERPRINT" >&2
+printf -- '-----BEGIN PGP SIGNATURE-----\n\nZmFrZQ==\n-----END PGP SIGNATURE-----\n'
+"#,
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755))?;
+    }
+    let repo = open(&path, [format!("gpg.program={}", program.display())])?;
+    let signature = gix::signing::Signer::from_repo(&repo)?.sign(&vec![b'x'; 1_000_000])?;
+    assert!(signature.starts_with(b"-----BEGIN PGP SIGNATURE-----\n"));
+    Ok(())
+}
+
+#[test]
+fn signer_from_repo() -> crate::Result {
+    let (tmp, path) = fixture()?;
+    let repo = open(&path, ["gpg.format=ssh".into(), "user.signingKey=/path/to/key".into()])?;
+    let signer = gix::signing::Signer::from_repo(&repo)?;
+    assert_eq!(signer.format, gix::signing::Format::Ssh);
+    assert_eq!(
+        signer.program, "ssh-keygen",
+        "the default program depends on the format"
+    );
+    assert_eq!(signer.key.expect("set"), "/path/to/key");
+    assert_eq!(signer.default_key_command, None);
+
+    let repo = open(
+        &path,
+        ["gpg.format=ssh".into(), "user.signingKey=key::ssh-ed25519 AAAA".into()],
+    )?;
+    let signer = gix::signing::Signer::from_repo(&repo)?;
+    assert_eq!(
+        signer.key.expect("set"),
+        "key::ssh-ed25519 AAAA",
+        "literal keys are kept as is"
+    );
+
+    let repo = open(&path, ssh_config(&tmp, "commit").into_iter().take(2))?;
+    assert!(matches!(
+        commit(&repo)
+            .unwrap_err()
+            .downcast::<gix::commit::Error>()
+            .map(|err| *err),
+        Ok(gix::commit::Error::Sign(gix::signing::Error::MissingSshKey))
+    ));
+    Ok(())
+}
+
+mod verify {
+    use std::path::Path;
+
+    use gix::{
+        bstr::{BString, ByteSlice},
+        signing::verify::{Outcome, Status, Trust},
+    };
+
+    use super::{fixture, open};
+
+    /// Open the fixture repository so that it verifies OpenPGP signatures with the keyring in `gnupg_home`, and SSH
+    /// signatures with the fixture's allowed signers.
+    fn verifying_repo(
+        tmp: &gix_testtools::tempfile::TempDir,
+        path: &Path,
+        gnupg_home: &str,
+    ) -> crate::Result<gix::Repository> {
+        let gpg = tmp.path().join("gpg");
+        std::fs::write(
+            &gpg,
+            format!(
+                "#!/bin/sh\nGNUPGHOME='{}' exec gpg \"$@\"\n",
+                tmp.path().join(gnupg_home).display()
+            ),
+        )?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&gpg, std::fs::Permissions::from_mode(0o755))?;
+        }
+        open(
+            path,
+            [
+                format!("gpg.program={}", gpg.display()),
+                format!(
+                    "gpg.ssh.allowedSignersFile={}",
+                    tmp.path().join("allowed-signers").display()
+                ),
+            ],
+        )
+    }
+
+    /// The `%G?` placeholder of `git log` for `outcome`.
+    fn git_status(outcome: Option<&Outcome>) -> &'static str {
+        match outcome {
+            None => "N",
+            Some(outcome) => match outcome.status {
+                Status::Good if outcome.trust >= Trust::Marginal => "G",
+                Status::Good => "U",
+                Status::Bad => "B",
+                // `git` considers valid signatures of SSH keys that aren't allowed signers good, but of unknown validity.
+                Status::UnknownKey if outcome.format == gix::signing::Format::Ssh => "U",
+                Status::UnknownKey => "E",
+                Status::ExpiredSignature => "X",
+                Status::ExpiredKey => "Y",
+                Status::RevokedKey => "R",
+            },
+        }
+    }
+
+    #[test]
+    fn commits_match_git() -> crate::Result {
+        let (tmp, path) = fixture()?;
+        let repo = verifying_repo(&tmp, &path, "gnupg")?;
+        let expected = std::fs::read(tmp.path().join("expected-verifications"))?;
+        let mut count = 0;
+        for line in expected.lines() {
+            let fields: Vec<_> = line.split_str(b"\t").collect();
+            let (name, status, signer, key, fingerprint) = (fields[0], fields[1], fields[2], fields[3], fields[4]);
+            let commit = repo
+                .find_reference(name.as_bstr())?
+                .peel_to_id_in_place()?
+                .object()?
+                .into_commit();
+            let outcome = commit.verify_signature()?;
+
+            let name = name.as_bstr();
+            assert_eq!(git_status(outcome.as_ref()), status.to_str()?, "{name}: {outcome:?}");
+            let non_empty = |field: &[u8]| (!field.is_empty()).then(|| BString::from(field));
+            let outcome = match outcome {
+                Some(outcome) => outcome,
+                None => continue,
+            };
+            assert_eq!(outcome.signer, non_empty(signer), "{name}");
+            assert_eq!(outcome.key, non_empty(key), "{name}");
+            assert_eq!(outcome.fingerprint, non_empty(fingerprint), "{name}");
+            count += 1;
+        }
+        assert_eq!(count, 5, "all signed commits were checked");
+        Ok(())
+    }
+
+    #[test]
+    fn signed_tags() -> crate::Result {
+        let (tmp, path) = fixture()?;
+        let repo = verifying_repo(&tmp, &path, "gnupg")?;
+        for (name, format) in [
+            ("ssh-tag", gix::signing::Format::Ssh),
+            ("gpg-tag", gix::signing::Format::OpenPgp),
+        ] {
+            let tag = repo.find_reference(name)?.id().object()?.try_into_tag()?;
+            let outcome = tag.verify_signature()?.expect("signed");
+            assert_eq!(outcome.format, format);
+            assert!(outcome.is_trusted(Trust::Fully), "{name}: {outcome:?}");
+        }
+
+        let repo = verifying_repo(&tmp, &path, "unknown-gnupg")?;
+        let tag = repo.find_reference("gpg-tag")?.id().object()?.try_into_tag()?;
+        let outcome = tag.verify_signature()?.expect("signed");
+        assert_eq!(outcome.status, Status::UnknownKey, "the keyring decides");
+        assert!(!outcome.is_trusted(Trust::Undefined));
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_signatures_of_keys_that_are_not_allowed_are_not_good() -> crate::Result {
+        let (tmp, path) = fixture()?;
+        let repo = verifying_repo(&tmp, &path, "gnupg")?;
+        let commit = repo
+            .find_reference("ssh-unknown")?
+            .peel_to_id_in_place()?
+            .object()?
+            .into_commit();
+        let outcome = commit.verify_signature()?.expect("signed");
+        assert_eq!(outcome.status, Status::UnknownKey, "anyone could have made it");
+        assert_eq!(outcome.signer, None);
+        assert!(outcome.fingerprint.is_some(), "the key is still known");
+        assert!(!outcome.is_trusted(Trust::Undefined));
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_signatures_need_allowed_signers() -> crate::Result {
+        let (_tmp, path) = fixture()?;
+        let repo = open(&path, None)?;
+        let commit = repo
+            .find_reference("ssh-signed")?
+            .peel_to_id_in_place()?
+            .object()?
+            .into_commit();
+        assert!(matches!(
+            commit.verify_signature(),
+            Err(gix::signing::verify::Error::MissingAllowedSignersFile)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn split_signed_commit_inverts_signing() -> crate::Result {
+        let (tmp, path) = fixture()?;
+        let repo = open(&path, super::ssh_config(&tmp, "commit"))?;
+        let id = super::commit(&repo)?;
+        let object = repo.find_object(id)?;
+        let (payload, signature) = gix::signing::split_signed_commit(&object.data, repo.object_hash()).expect("signed");
+
+        let commit = object.into_commit();
+        let mut unsigned = commit.decode()?.to_owned();
+        unsigned.extra_headers.clear();
+        let mut expected = Vec::new();
+        gix_object::WriteTo::write_to(&unsigned, &mut expected)?;
+        assert_eq!(payload.as_bstr(), expected.as_bstr());
+        assert!(signature.starts_with(b"-----BEGIN SSH SIGNATURE-----\n"));
+        assert!(signature.ends_with(b"-----END SSH SIGNATURE-----\n"));
+
+        let outcome = gix::signing::verify::Verifier::from_repo(&verifying_repo(&tmp, &path, "gnupg")?)?
+            .verify(&payload, &signature)?;
+        assert!(outcome.is_trusted(Trust::Fully), "{outcome:?}");
+        assert_eq!(outcome.signer.expect("known").as_bstr(), "gitoxide@localhost");
+        Ok(())
+    }
+    #[test]
+    #[cfg(unix)]
+    fn gpg_output_with_more_than_one_result_is_bad() -> crate::Result {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let gpg = tmp.path().join("gpg");
+        std::fs::write(
+            &gpg,
+            "#!/bin/sh\ncat >/dev/null\n\
+             echo '[GNUPG:] GOODSIG 0123456789ABCDEF Good Signer <good@example.com>'\n\
+             echo '[GNUPG:] VALIDSIG 0123456789ABCDEF0123456789ABCDEF01234567'\n\
+             echo '[GNUPG:] TRUST_ULTIMATE'\n\
+             echo '[GNUPG:] BADSIG FEDCBA9876543210 Bad Signer <bad@example.com>'\n",
+        )?;
+        std::fs::set_permissions(&gpg, std::fs::Permissions::from_mode(0o755))?;
+
+        let verifier = gix::signing::verify::Verifier {
+            openpgp_program: gpg.into(),
+            x509_program: "gpgsm".into(),
+            ssh_program: "ssh-keygen".into(),
+            allowed_signers_file: None,
+            revocation_file: None,
+        };
+        let outcome = verifier.verify(
+            b"payload",
+            b"-----BEGIN PGP SIGNATURE-----\n\n-----END PGP SIGNATURE-----\n",
+        )?;
+        assert_eq!(outcome.status, Status::Bad);
+        assert_eq!(outcome.trust, Trust::Undefined);
+        assert_eq!(
+            (outcome.signer, outcome.key, outcome.fingerprint),
+            (None, None, None),
+            "nothing is reported about the signer if the result is ambiguous"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use gix::{
+    bstr::ByteSlice,
+    stash::{ApplyOptions, Change, ChangeKind, Error, PushOptions},
+};
+
+use crate::util::{named_subrepo_opts, restricted};
+
+fn baseline(repo: &gix::Repository, name: &str) -> crate::Result<String> {
+    Ok(std::fs::read_to_string(repo.git_dir().join(name))?)
+}
+
+fn list(repo: &gix::Repository) -> crate::Result<String> {
+    Ok(repo
+        .stash_list()?
+        .into_iter()
+        .map(|entry| format!("{} {}\n", entry.id, entry.message))
+        .collect())
+}
+
+fn writable(name: &str) -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_stash_repo.sh")?;
+    Ok((gix::open_opts(tmp.path().join(name), restricted())?, tmp))
+}
+
+#[test]
+fn push_with_untracked_files_matches_git() -> crate::Result {
+    let (repo, _tmp) = writable("push")?;
+    let id = repo
+        .stash_push(&PushOptions {
+            message: Some("a message".into()),
+            include_untracked: true,
+            keep_index: false,
+        })?
+        .expect("there are changes to stash");
+
+    let commit = id.object()?.into_commit();
+    let parents: Vec<_> = commit.parent_ids().collect();
+    assert_eq!(parents.len(), 3, "HEAD, index and untracked files");
+    assert_eq!(parents[0], repo.head_id()?);
+    let mut actual = String::new();
+    for id in [id, parents[1], parents[2]] {
+        let commit = id.object()?.into_commit();
+        actual.push_str(&format!("{}\n{}", commit.tree_id()?, commit.message_raw()?));
+    }
+    assert_eq!(actual, baseline(&repo, "expected-commits")?);
+    assert_eq!(
+        repo.stash_list()?
+            .iter()
+            .map(|entry| format!("{}\n", entry.message))
+            .collect::<String>(),
+        baseline(&repo, "expected-reflog")?
+    );
+
+    let root = repo.work_dir().expect("non-bare");
+    assert_eq!(std::fs::read(root.join("a"))?.as_bstr(), "a\n");
+    assert_eq!(std::fs::read(root.join("b"))?.as_bstr(), "b\n");
+    assert_eq!(std::fs::read(root.join("dir/c"))?.as_bstr(), "c\n");
+    assert!(!root.join("untracked-dir").exists(), "untracked files are stashed");
+    assert!(root.join("x.ignored").is_file(), "ignored files are left alone");
+    Ok(())
+}
+
+#[test]
+fn push_without_changes_is_a_no_op() -> crate::Result {
+    let (repo, _tmp) = writable("stashes")?;
+    let before = list(&repo)?;
+    assert_eq!(repo.stash_push(&PushOptions::default())?, None);
+    assert_eq!(list(&repo)?, before);
+    Ok(())
+}
+
+#[test]
+fn list_matches_git() -> crate::Result {
+    let repo = named_subrepo_opts("make_stash_repo.sh", "stashes", restricted())?;
+    assert_eq!(list(&repo)?, baseline(&repo, "expected-list")?);
+    let entries = repo.stash_list()?;
+    assert_eq!(
+        entries.iter().map(|entry| entry.index).collect::<Vec<_>>(),
+        [0, 1],
+        "the most recent stash comes first"
+    );
+    Ok(())
+}
+
+#[test]
+fn show() -> crate::Result {
+    let repo = named_subrepo_opts("make_stash_repo.sh", "stashes", restricted())?;
+    let change = |location: &str, kind| Change {
+        location: location.into(),
+        kind,
+    };
+    assert_eq!(
+        repo.stash_show(1)?,
+        [
+            change("a", ChangeKind::Modification),
+            change("b", ChangeKind::Modification),
+            change("dir/c", ChangeKind::Deletion),
+        ]
+    );
+    assert_eq!(repo.stash_show(0)?, [change("a", ChangeKind::Modification)]);
+    assert!(matches!(repo.stash_show(2), Err(Error::NotFound { index: 2 })));
+    Ok(())
+}
+
+#[test]
+fn apply_with_index_matches_git() -> crate::Result {
+    let (repo, _tmp) = writable("stashes")?;
+    let outcome = repo.stash_apply(
+        1,
+        &ApplyOptions {
+            index: true,
+            ..ApplyOptions::from_repo(&repo)?
+        },
+    )?;
+    assert!(outcome.is_clean());
+
+    let index = repo.open_index()?;
+    let actual: String = index
+        .entries()
+        .iter()
+        .map(|entry| {
+            format!(
+                "{:06o} {} {}\t{}\n",
+                entry.mode.bits(),
+                entry.id,
+                entry.stage(),
+                entry.path(&index)
+            )
+        })
+        .collect();
+    assert_eq!(actual, baseline(&repo, "expected-apply-index")?);
+
+    let root = repo.work_dir().expect("non-bare");
+    let mut content = std::fs::read(root.join("a"))?;
+    content.extend(std::fs::read(root.join("b"))?);
+    assert_eq!(content.as_bstr(), baseline(&repo, "expected-apply-content")?);
+    assert!(!root.join("dir/c").exists(), "deletions are applied as well");
+    assert_eq!(list(&repo)?, baseline(&repo, "expected-list")?, "stashes are kept");
+    Ok(())
+}
+
+#[test]
+fn pop_keeps_the_stash_if_it_cannot_be_applied() -> crate::Result {
+    let (repo, _tmp) = writable("stashes")?;
+    let root = repo.work_dir().expect("non-bare").to_owned();
+    let options = ApplyOptions::from_repo(&repo)?;
+
+    std::fs::write(root.join("a"), "local\n")?;
+    assert!(
+        matches!(repo.stash_pop(0, &options), Err(Error::WouldOverwrite { path }) if path == "a"),
+        "local changes aren't overwritten"
+    );
+    assert_eq!(list(&repo)?, baseline(&repo, "expected-list")?);
+
+    std::fs::write(root.join("a"), "a\n")?;
+    let outcome = repo.stash_pop(0, &options)?;
+    assert!(outcome.is_clean());
+    assert_eq!(std::fs::read(root.join("a"))?.as_bstr(), "second\n");
+    assert_eq!(repo.stash_list()?.len(), 1, "the applied stash was dropped");
+    Ok(())
+}
+
+#[test]
+fn drop_matches_git() -> crate::Result {
+    let (repo, _tmp) = writable("stashes")?;
+    let expected = baseline(&repo, "expected-list")?;
+    let oldest = expected.lines().nth(1).expect("two stashes");
+    assert_eq!(repo.stash_drop(1)?.to_string(), oldest[..40]);
+    assert_eq!(list(&repo)?, baseline(&repo, "expected-list-after-drop")?);
+    assert_eq!(list(&repo)?, baseline(&repo, "expected-reflog-after-drop")?);
+    assert!(matches!(repo.stash_drop(1), Err(Error::NotFound { index: 1 })));
+    Ok(())
+}
+
+#[test]
+fn drop_most_recent_and_last() -> crate::Result {
+    let (repo, _tmp) = writable("stashes")?;
+    let entries = repo.stash_list()?;
+    assert_eq!(repo.stash_drop(0)?, entries[0].id);
+    let remaining = repo.stash_list()?;
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].id, entries[1].id);
+    assert_eq!(remaining[0].index, 0);
+    assert_eq!(
+        repo.find_reference("refs/stash")?.id(),
+        entries[1].id,
+        "the reference points to the most recent remaining stash"
+    );
+
+    assert_eq!(repo.stash_drop(0)?, entries[1].id);
+    assert!(repo.stash_list()?.is_empty());
+    assert!(
+        repo.try_find_reference("refs/stash")?.is_none(),
+        "dropping the last stash removes the reference"
+    );
+    Ok(())
+}
@@ -270,6 +270,11 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
             expected.prunable.is_none(),
             "in our case prunable repos have no worktree base"
         );
+        assert_eq!(
+            actual.prunable().map(|reason| reason.to_string().into()),
+            expected.prunable,
+            "the reason for pruning is the same as the one given by git"
+        );
 
         let repo = if base.is_dir() {
             let repo = actual.into_repo().unwrap();
@@ -301,3 +306,593 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
         );
     }
 }
+
+mod sparse_checkout_patterns {
+    use gix_glob::pattern::Case;
+
+    fn repo(name: &str) -> crate::Result<gix::Repository> {
+        let dir = gix_testtools::scripted_fixture_read_only("make_sparse_checkout_repos.sh")?.join(name);
+        Ok(gix::open(dir)?)
+    }
+
+    #[test]
+    fn cone_mode() -> crate::Result {
+        let repo = repo("cone")?;
+        let patterns = repo
+            .worktree()
+            .expect("non-bare")
+            .sparse_checkout_patterns()?
+            .expect("enabled");
+        let cone = patterns.cone().expect("cone mode is configured");
+        assert_eq!(cone.directories().collect::<Vec<_>>(), ["c1/c2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn non_cone_mode() -> crate::Result {
+        let repo = repo("non-cone")?;
+        let patterns = repo
+            .worktree()
+            .expect("non-bare")
+            .sparse_checkout_patterns()?
+            .expect("enabled");
+        assert!(patterns.cone().is_none());
+        assert!(patterns.is_included("d/a".into(), false, Case::Sensitive));
+        assert!(!patterns.is_included("a".into(), false, Case::Sensitive));
+        Ok(())
+    }
+
+    #[test]
+    fn disabled() -> crate::Result {
+        let repo = repo("disabled")?;
+        assert!(
+            repo.worktree().expect("non-bare").sparse_checkout_patterns()?.is_none(),
+            "the patterns file is ignored if `core.sparseCheckout` is disabled"
+        );
+        Ok(())
+    }
+}
+
+mod write_index {
+    fn sparse_entries(repo: &gix::Repository) -> crate::Result<Vec<String>> {
+        let index = repo.open_index()?;
+        Ok(index
+            .entries()
+            .iter()
+            .filter(|e| e.mode.is_sparse())
+            .map(|e| e.path(&index).to_string())
+            .collect())
+    }
+
+    #[test]
+    fn index_sparse_expands_and_collapses_sparse_directories() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable("make_sparse_checkout_repos.sh")?;
+        let mut repo = gix::open_opts(tmp.path().join("cone-sparse-index"), crate::util::restricted())?;
+        assert!(repo.open_index()?.is_sparse(), "git wrote a sparse index");
+        assert_eq!(sparse_entries(&repo)?, ["d/"]);
+
+        repo.config_snapshot_mut()
+            .set_raw_value("index", None, "sparse", "false")?;
+        let mut index = repo.open_index()?;
+        repo.write_index(&mut index)?;
+        assert!(!repo.open_index()?.is_sparse());
+        assert!(sparse_entries(&repo)?.is_empty(), "sparse directories are expanded");
+
+        repo.config_snapshot_mut()
+            .set_raw_value("index", None, "sparse", "true")?;
+        let mut index = repo.open_index()?;
+        repo.write_index(&mut index)?;
+        assert!(repo.open_index()?.is_sparse());
+        assert_eq!(
+            sparse_entries(&repo)?,
+            ["d/"],
+            "directories outside of the cone are collapsed again"
+        );
+        Ok(())
+    }
+}
+
+mod management {
+    use std::path::{Path, PathBuf};
+
+    use gix::{
+        bstr::ByteSlice,
+        worktree::{add, prune, repair},
+    };
+
+    use crate::util::restricted;
+
+    /// Return a writable copy of the worktree fixture along with its main repository.
+    fn fixture() -> crate::Result<(gix_testtools::tempfile::TempDir, PathBuf, gix::Repository)> {
+        let tmp = gix_testtools::scripted_fixture_writable_with_args(
+            "make_worktree_repo.sh",
+            None::<String>,
+            gix_testtools::Creation::ExecuteScript,
+        )?;
+        let root = gix_path::realpath(tmp.path())?;
+        let repo = gix::open_opts(root.join("repo"), restricted())?;
+        Ok((tmp, root, repo))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn add(repo: &gix::Repository, path: &Path, options: &add::Options) -> Result<gix::Repository, add::Error> {
+        repo.add_worktree(path, options, gix_features::progress::Discard, &Default::default())
+    }
+
+    fn proxy<'repo>(repo: &'repo gix::Repository, id: &str) -> gix::worktree::Proxy<'repo> {
+        repo.worktrees()
+            .expect("readable")
+            .into_iter()
+            .find(|proxy| proxy.id() == id)
+            .expect("worktree exists")
+    }
+
+    fn read(path: impl AsRef<Path>) -> String {
+        std::fs::read_to_string(path).expect("readable")
+    }
+
+    #[test]
+    fn add_creates_the_same_files_as_git() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 31, 0) {
+            return Ok(());
+        }
+        let (_tmp, root, repo) = fixture()?;
+        let worktree = add(&repo, &root.join("wt-new"), &Default::default())?;
+
+        let git_dir = root.join("repo/.git/worktrees/wt-new");
+        assert_eq!(worktree.git_dir(), git_dir);
+        assert_eq!(
+            read(git_dir.join("gitdir")),
+            format!("{}\n", root.join("wt-new/.git").display())
+        );
+        assert_eq!(
+            read(root.join("wt-new/.git")),
+            format!("gitdir: {}\n", git_dir.display()),
+            "the worktree points back to its private git directory"
+        );
+        let git_created = root.join("repo/.git/worktrees/wt-a");
+        for file in ["commondir", "HEAD"] {
+            assert_eq!(
+                read(git_dir.join(file)).trim_end().replace("wt-new", "wt-a"),
+                read(git_created.join(file)).trim_end(),
+                "{file} is the same as for a worktree created by git"
+            );
+        }
+        assert!(
+            !git_dir.join("locked").exists(),
+            "the worktree is only locked while it's created"
+        );
+
+        assert_eq!(
+            worktree.head_name()?.expect("branch").as_bstr(),
+            "refs/heads/wt-new",
+            "by default, a new branch named after the worktree is created"
+        );
+        assert_eq!(worktree.head_id()?, repo.head_id()?);
+        let reflog_message = repo
+            .find_reference("refs/heads/wt-new")?
+            .log_iter()
+            .all()?
+            .expect("present")
+            .next()
+            .expect("one line")?
+            .message
+            .to_owned();
+        assert_eq!(reflog_message, "branch: Created from HEAD");
+        assert_eq!(read(root.join("wt-new/a")), "hello\n");
+        assert!(root.join("wt-new/dir/c").is_file());
+        assert_eq!(worktree.open_index()?.entries().len(), 3);
+
+        let proxy = proxy(&repo, "wt-new");
+        assert_eq!(proxy.base()?, root.join("wt-new"));
+        assert_eq!(proxy.into_repo()?, worktree);
+        assert_eq!(worktree.main_repo()?, repo);
+        Ok(())
+    }
+
+    #[test]
+    fn add_with_options() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 31, 0) {
+            return Ok(());
+        }
+        let (_tmp, root, repo) = fixture()?;
+        let previous = repo.rev_parse_single("HEAD~1")?.detach();
+
+        let worktree = add(
+            &repo,
+            &root.join("detached"),
+            &add::Options {
+                commit: Some("HEAD~1".into()),
+                lock_reason: Some("on a stick".into()),
+                ..Default::default()
+            },
+        )?;
+        assert!(
+            worktree.head_name()?.is_none(),
+            "revisions that aren't branches detach HEAD"
+        );
+        assert_eq!(worktree.head_id()?, previous);
+        assert_eq!(proxy(&repo, "detached").lock_reason().expect("locked"), "on a stick");
+
+        let worktree = add(
+            &repo,
+            &root.join("new-branch"),
+            &add::Options {
+                commit: Some("HEAD~1".into()),
+                new_branch: Some("feature".into()),
+                no_checkout: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(worktree.head_name()?.expect("branch").as_bstr(), "refs/heads/feature");
+        assert_eq!(worktree.head_id()?, previous);
+        assert!(!root.join("new-branch/a").exists(), "nothing was checked out");
+        assert!(!worktree.index_path().exists());
+
+        let err = add(
+            &repo,
+            &root.join("main"),
+            &add::Options {
+                commit: Some("main".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&err, add::Error::BranchCheckedOut { branch, path } if branch == "main" && path == &root.join("repo")),
+            "{err:?}"
+        );
+        assert!(!root.join("main").exists(), "nothing was created");
+        assert!(!root.join("repo/.git/worktrees/main").exists());
+
+        let worktree = add(
+            &repo,
+            &root.join("nested/wt-a"),
+            &add::Options {
+                commit: Some("main".into()),
+                force: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(worktree.head_name()?.expect("branch").as_bstr(), "refs/heads/main");
+        assert_eq!(
+            worktree.worktree().expect("present").id().expect("linked"),
+            "wt-a2",
+            "ids are made unique just like git does it"
+        );
+        repo.reference(
+            "refs/heads/topic",
+            previous,
+            gix_ref::transaction::PreviousValue::MustNotExist,
+            "",
+        )?;
+        let worktree = add(&repo, &root.join("elsewhere/topic"), &add::Options::default())?;
+        assert_eq!(
+            worktree.head_name()?.expect("branch").as_bstr(),
+            "refs/heads/topic",
+            "existing branches are checked out if they are named like the worktree"
+        );
+
+        let err = add(&repo, &root.join("wt-a"), &Default::default()).unwrap_err();
+        assert!(matches!(err, add::Error::DestinationExists { .. }), "{err:?}");
+        let err = add(&repo, &root.join("wt-deleted"), &Default::default()).unwrap_err();
+        assert!(matches!(err, add::Error::AlreadyRegistered { .. }), "{err:?}");
+        let err = add(&repo, &root.join("nested/../wt-deleted"), &Default::default()).unwrap_err();
+        assert!(
+            matches!(err, add::Error::AlreadyRegistered { .. }),
+            "paths are normalized: {err:?}"
+        );
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&root, root.join("link"))?;
+            let err = add(&repo, &root.join("link/wt-deleted"), &Default::default()).unwrap_err();
+            assert!(
+                matches!(err, add::Error::AlreadyRegistered { .. }),
+                "symlinks are resolved: {err:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn lock_move_and_remove() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 31, 0) {
+            return Ok(());
+        }
+        let (_tmp, root, repo) = fixture()?;
+        let wt = proxy(&repo, "wt-b");
+        wt.lock(Some("reason".into()))?;
+        assert_eq!(read(wt.git_dir().join("locked")), "reason\n");
+        assert!(matches!(
+            wt.lock(None),
+            Err(gix::worktree::proxy::lock::Error::AlreadyLocked { .. })
+        ));
+        assert!(matches!(
+            wt.move_to(root.join("moved")),
+            Err(gix::worktree::proxy::move_to::Error::Locked { .. })
+        ));
+        assert!(matches!(
+            wt.clone().remove(true),
+            Err(gix::worktree::proxy::remove::Error::Locked { .. })
+        ));
+        wt.unlock()?;
+        assert!(matches!(
+            wt.unlock(),
+            Err(gix::worktree::proxy::lock::Error::NotLocked { .. })
+        ));
+        wt.lock(None)?;
+        assert_eq!(
+            read(wt.git_dir().join("locked")),
+            "",
+            "without reason, the file is empty like in git"
+        );
+        wt.unlock()?;
+
+        std::fs::create_dir(root.join("moved"))?;
+        let base = wt.move_to(root.join("moved"))?;
+        assert_eq!(
+            base,
+            root.join("moved/wt-b"),
+            "existing directories receive the worktree"
+        );
+        assert_eq!(wt.base()?, base);
+        assert!(!root.join("wt-b").exists());
+        assert_eq!(gix::open_opts(&base, restricted())?.git_dir(), wt.git_dir());
+        std::fs::write(root.join("file"), "")?;
+        assert!(matches!(
+            wt.move_to(root.join("file")),
+            Err(gix::worktree::proxy::move_to::Error::DestinationExists { .. })
+        ));
+
+        std::fs::write(base.join("untracked"), "")?;
+        assert!(matches!(
+            wt.clone().remove(false),
+            Err(gix::worktree::proxy::remove::Error::LocalChanges { .. })
+        ));
+        std::fs::remove_file(base.join("untracked"))?;
+        std::fs::write(base.join("a"), "changed")?;
+        assert!(matches!(
+            wt.clone().remove(false),
+            Err(gix::worktree::proxy::remove::Error::LocalChanges { .. })
+        ));
+        std::fs::write(base.join("a"), "")?;
+        let git_dir = wt.git_dir().to_owned();
+        wt.remove(false)?;
+        assert!(!base.exists() && !git_dir.exists(), "clean worktrees are removed");
+
+        let wt = proxy(&repo, "wt-a");
+        std::fs::write(root.join("wt-a/a"), "changed")?;
+        wt.remove(true)?;
+        assert!(!root.join("wt-a").exists(), "forcing removes local changes as well");
+        Ok(())
+    }
+
+    #[test]
+    fn prune() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 31, 0) {
+            return Ok(());
+        }
+        let (_tmp, root, repo) = fixture()?;
+        std::fs::remove_dir_all(root.join("wt-c-locked"))?;
+        let expected = vec![prune::Pruned {
+            id: "wt-deleted".into(),
+            reason: prune::Reason::MissingLocation,
+        }];
+        assert_eq!(
+            repo.prune_worktrees(&prune::Options {
+                dry_run: true,
+                ..Default::default()
+            })?,
+            expected,
+            "locked worktrees are never pruned"
+        );
+        assert!(
+            root.join("repo/.git/worktrees/wt-deleted").is_dir(),
+            "dry-runs don't delete"
+        );
+        assert_eq!(
+            repo.prune_worktrees(&prune::Options {
+                expire: Some(std::time::SystemTime::UNIX_EPOCH),
+                ..Default::default()
+            })?,
+            Vec::new(),
+            "worktrees that were removed recently can be kept"
+        );
+
+        assert_eq!(repo.prune_worktrees(&Default::default())?, expected);
+        assert!(!root.join("repo/.git/worktrees/wt-deleted").exists());
+        assert!(
+            repo.worktrees()?.iter().all(|proxy| proxy.id() != "wt-deleted"),
+            "it's gone"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn repair() -> crate::Result {
+        if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 31, 0) {
+            return Ok(());
+        }
+        let (_tmp, root, repo) = fixture()?;
+        std::fs::rename(root.join("wt-b"), root.join("moved"))?;
+        let wt = proxy(&repo, "wt-b");
+        assert_eq!(wt.prunable(), Some(prune::Reason::MissingLocation));
+
+        let gitdir_file = wt.git_dir().join("gitdir");
+        assert_eq!(
+            repo.repair_worktree_at(root.join("moved"))?,
+            vec![repair::Repair {
+                path: gitdir_file.clone(),
+                problem: repair::Problem::GitDirFileIncorrect,
+            }]
+        );
+        assert_eq!(wt.base()?, root.join("moved"));
+        assert_eq!(wt.prunable(), None);
+        assert_eq!(
+            repo.repair_worktree_at(root.join("moved"))?,
+            Vec::new(),
+            "nothing to repair"
+        );
+
+        std::fs::write(root.join("moved/.git"), "gitdir: /does/not/exist/worktrees/wt-b\n")?;
+        std::fs::remove_file(&gitdir_file)?;
+        assert_eq!(
+            repo.repair_worktree_at(root.join("moved"))?,
+            vec![
+                repair::Repair {
+                    path: root.join("moved/.git"),
+                    problem: repair::Problem::DotGitFileBroken,
+                },
+                repair::Repair {
+                    path: gitdir_file,
+                    problem: repair::Problem::GitDirFileUnreadable,
+                }
+            ],
+            "the private git directory is inferred from the name of the worktree"
+        );
+        assert_eq!(
+            gix::open_opts(root.join("moved"), restricted())?.git_dir(),
+            wt.git_dir()
+        );
+
+        std::fs::write(root.join("wt-a/.git"), "garbage")?;
+        std::fs::write(
+            root.join("wt-c-locked/.git"),
+            format!("gitdir: {}\n", wt.git_dir().display()),
+        )?;
+        let repairs = repo.repair_worktrees()?;
+        assert_eq!(
+            repairs
+                .iter()
+                .map(|repair| (repair.path.strip_prefix(&root).expect("in root"), repair.problem))
+                .collect::<Vec<_>>(),
+            [
+                (Path::new("wt-a/.git"), repair::Problem::DotGitFileBroken),
+                (Path::new("wt-c-locked/.git"), repair::Problem::DotGitFileIncorrect)
+            ]
+        );
+        assert_eq!(
+            read(root.join("wt-a/.git")).trim_end().as_bytes().as_bstr(),
+            format!("gitdir: {}", root.join("repo/.git/worktrees/wt-a").display())
+        );
+        assert!(repo.repair_worktrees()?.is_empty(), "everything is repaired");
+        assert!(matches!(
+            repo.repair_worktree_at(root.join("repo")),
+            Err(repair::Error::NotALinkedWorktree { .. })
+        ));
+        Ok(())
+    }
+
+    mod worktree_config {
+        use gix::{bstr::ByteSlice, config::snapshot_mut_at};
+
+        use super::{add, read};
+        use crate::util::restricted;
+
+        fn fixture() -> crate::Result<(gix_testtools::tempfile::TempDir, std::path::PathBuf)> {
+            let tmp = gix_testtools::scripted_fixture_writable_with_args(
+                "make_worktree_repo_with_configs.sh",
+                None::<String>,
+                gix_testtools::Creation::ExecuteScript,
+            )?;
+            let root = gix_path::realpath(tmp.path())?;
+            Ok((tmp, root))
+        }
+
+        #[test]
+        fn edit_affects_only_the_current_worktree() -> crate::Result {
+            let (_tmp, root) = fixture()?;
+            let mut wt = gix::open_opts(root.join("wt-1"), restricted())?;
+            let previous = wt.edit_worktree_config(|config| {
+                let mut section = config.section_mut("worktree", None).expect("present");
+                let previous = section.set("setting".try_into().expect("valid"), "changed in wt-1");
+                section.push("added".try_into().expect("valid"), Some("value".into()));
+                previous
+            })?;
+            assert_eq!(
+                previous.as_deref().map(|value| value.to_str_lossy()),
+                Some("set in wt-1".into())
+            );
+
+            let config = wt.config_snapshot();
+            assert_eq!(
+                config.string("worktree.setting").expect("set").as_ref(),
+                "changed in wt-1"
+            );
+            assert_eq!(config.string("worktree.added").expect("set").as_ref(), "value");
+            assert_eq!(
+                config.string("shared.setting").expect("set").as_ref(),
+                "set in the shared config",
+                "the configuration was fully reloaded"
+            );
+            assert!(
+                read(root.join("repo/.git/worktrees/wt-1/config.worktree")).contains("changed in wt-1"),
+                "the private configuration of the worktree was changed"
+            );
+
+            let main = gix::open_opts(root.join("repo"), restricted())?;
+            assert_eq!(
+                main.config_snapshot().string("worktree.setting").expect("set").as_ref(),
+                "set in the main worktree",
+                "other worktrees are unaffected"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn edit_without_extension_changes_the_local_configuration() -> crate::Result {
+            let tmp = gix_testtools::tempfile::TempDir::new()?;
+            let mut repo = gix::init(tmp.path())?;
+            repo.edit_worktree_config(|config| {
+                config
+                    .section_mut_or_create_new("core", None)
+                    .expect("valid")
+                    .push("sparseCheckout".try_into().expect("valid"), Some("true".into()));
+            })?;
+            assert!(repo.config_snapshot().boolean("core.sparseCheckout").expect("set"));
+            assert!(read(repo.git_dir().join("config")).contains("sparseCheckout = true"));
+
+            let (_tmp, _root, mut repo) = super::fixture()?;
+            assert!(
+                matches!(
+                    repo.edit_worktree_config(|_| ()),
+                    Err(snapshot_mut_at::Error::ExtensionDisabled)
+                ),
+                "the shared configuration can't be changed for a single worktree"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn add_inherits_sparse_checkout_and_worktree_configuration() -> crate::Result {
+            let (_tmp, root) = fixture()?;
+            let mut repo = gix::open_opts(root.join("repo"), restricted())?;
+            std::fs::create_dir_all(repo.git_dir().join("info"))?;
+            std::fs::write(repo.git_dir().join("info/sparse-checkout"), "/a\n")?;
+            repo.edit_worktree_config(|config| {
+                let mut core = config.section_mut_or_create_new("core", None).expect("valid");
+                core.push("sparseCheckout".try_into().expect("valid"), Some("true".into()));
+                core.push("worktree".try_into().expect("valid"), Some("/somewhere/else".into()));
+            })?;
+
+            let wt = add(&repo, &root.join("wt-sparse"), &Default::default())?;
+            assert_eq!(
+                read(wt.git_dir().join("info/sparse-checkout")),
+                "/a\n",
+                "the patterns are copied"
+            );
+            let config = read(wt.git_dir().join("config.worktree"));
+            assert!(config.contains("set in the main worktree"));
+            assert!(!config.contains("/somewhere/else"), "core.worktree is never copied");
+            assert!(wt.config_snapshot().boolean("core.sparseCheckout").expect("set"));
+            assert!(root.join("wt-sparse/a").is_file());
+            for excluded in ["b", "c"] {
+                assert!(
+                    !root.join("wt-sparse").join(excluded).exists(),
+                    "{excluded} is outside of the sparse checkout"
+                );
+            }
+            Ok(())
+        }
+    }
+}
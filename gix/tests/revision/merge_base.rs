@@ -0,0 +1,19 @@
+use crate::util::named_repo;
+
+#[test]
+fn merge_base_and_friends() -> crate::Result {
+    let repo = named_repo("make_merge_trees_repo.sh")?;
+    let id = |spec: &str| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec)?.detach()) };
+    let (base, ours, theirs) = (id("content-base")?, id("content-ours")?, id("content-theirs")?);
+
+    assert_eq!(repo.merge_base(ours, theirs)?, base);
+    assert_eq!(repo.merge_bases(ours, [theirs, base])?, [base]);
+    assert_eq!(
+        repo.merge_bases_octopus([ours, theirs, id("clean-ours")?])?,
+        [id("main")?]
+    );
+    assert!(repo.is_ancestor(base, ours)?);
+    assert!(!repo.is_ancestor(ours, base)?);
+    assert_eq!(repo.independent_commits([base, ours, theirs, ours])?, [ours, theirs]);
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use gix::revision::walk::Side;
+
+use crate::util::{named_repo, repo_rw};
+
+fn walk(repo: &gix::Repository, spec: &str) -> crate::Result<Vec<gix::ObjectId>> {
+    Ok(repo
+        .rev_parse(spec)?
+        .walk()?
+        .all()?
+        .map(|id| id.map(|id| id.detach()))
+        .collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn from_spec() -> crate::Result {
+    let repo = named_repo("make_merge_trees_repo.sh")?;
+    let id = |spec: &str| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec)?.detach()) };
+    let (root, base, ours, theirs) = (
+        id("main")?,
+        id("content-base")?,
+        id("content-ours")?,
+        id("content-theirs")?,
+    );
+
+    assert_eq!(walk(&repo, "content-ours")?, [ours, base, root]);
+    assert_eq!(walk(&repo, "content-base..content-ours")?, [ours]);
+    assert!(walk(&repo, "content-ours..content-base")?.is_empty());
+    assert_eq!(walk(&repo, "content-ours...content-theirs")?, [ours, theirs]);
+    assert_eq!(walk(&repo, "content-ours^!")?, [ours]);
+    assert_eq!(walk(&repo, "content-ours^@")?, [base, root]);
+    assert!(walk(&repo, "^content-ours")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn left_right_and_ahead_behind() -> crate::Result {
+    let repo = named_repo("make_merge_trees_repo.sh")?;
+    let id = |spec: &str| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec)?.detach()) };
+    let (base, ours, theirs) = (id("content-base")?, id("content-ours")?, id("content-theirs")?);
+
+    let sides: Vec<_> = repo
+        .rev_walk_left_right(ours, theirs)?
+        .map(|(side, id)| (side, id.detach()))
+        .collect();
+    assert_eq!(sides, [(Side::Left, ours), (Side::Right, theirs)]);
+
+    assert_eq!(repo.ahead_behind(ours, theirs)?, (1, 1));
+    assert_eq!(repo.ahead_behind(ours, base)?, (1, 0));
+    assert_eq!(repo.ahead_behind(id("main")?, ours)?, (0, 2));
+    assert_eq!(repo.ahead_behind(ours, ours)?, (0, 0));
+    assert_eq!(
+        repo.rev_walk([ours]).with_hidden([base, theirs]).all()?.count(),
+        1,
+        "hidden tips can be set on any walk"
+    );
+    Ok(())
+}
+
+#[test]
+fn hidden_commits_of_shallow_clones() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_merge_trees_repo.sh")?;
+    let id = |spec: &str| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec)?.detach()) };
+    let (root, base, ours) = (id("main")?, id("content-base")?, id("content-ours")?);
+    let hex = root.to_hex().to_string();
+    std::fs::remove_file(repo.objects.store_ref().path().join(&hex[..2]).join(&hex[2..]))?;
+
+    assert!(
+        repo.rev_walk([ours]).with_hidden([base]).all().is_err(),
+        "missing commits are an error if the repository isn't shallow"
+    );
+
+    std::fs::write(repo.git_dir().join("shallow"), format!("{base}\n"))?;
+    let ids: Vec<_> = repo
+        .rev_walk([ours])
+        .with_hidden([base])
+        .all()?
+        .map(|id| id.map(|id| id.detach()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(ids, [ours], "the history ends at shallow commits");
+    Ok(())
+}
@@ -136,14 +136,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "core.protectNTFS",
         usage: NotPlanned { reason: "lack of demand"},
     },
-    Record {
-        config: "core.sparseCheckout",
-        usage: Planned { note: Some("we want to support huge repos and be the fastest in doing so") },
-    },
-    Record {
-        config: "core.sparseCheckoutCone",
-        usage: Planned { note: Some("this is a nice improvement over spareCheckout alone and should one day be available too") },
-    },
     Record {
         config: "checkout.defaultRemote",
         usage: Planned { note: Some("needed for correct checkout behaviour, similar to what git does") },
@@ -160,18 +152,10 @@ static GIT_CONFIG: &[Record] = &[
         config: "checkout.thresholdForParallelism",
         usage: NotApplicable {reason: "parallelism is efficient enough to always run with benefit"},
     },
-    Record {
-        config: "feature.manyFile",
-        usage: Planned {note: Some("big repositories are on the roadmap")},
-    },
     Record {
         config: "core.preloadIndex",
         usage: Planned {note: Some("it's enabled by default and allows parallel stat checks - it's using a lot of CPU for just minor performance boosts though")},
     },
-    Record {
-        config: "index.sparse",
-        usage: Planned {note: Some("we can read sparse indices and support for it will be added early on")},
-    },
     Record {
         config: "merge.renormalize",
         usage: Planned {note: Some("once merging is being implemented, renormalization should be respected")},
@@ -280,34 +264,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "advice.updateSparsePath",
         usage: NotApplicable { reason: "gitoxide does not yet have an 'advice' system" },
     },
-    Record {
-        config: "core.sparseCheckout",
-        usage: Planned { note: Some("together with 'index.sparse' and 'core.sparseCheckoutCone', configures if the index should be written sparse or not") },
-    },
-    Record {
-        config: "core.sparseCheckoutCone",
-        usage: Planned { note: Some("non-cone mode is deprecated but should still fail gracefully if encountered") },
-    },
-    Record {
-        config: "core.splitIndex",
-        usage: NotPlanned { reason: "we don't want to be able to create split indices, but we will read them. It's (somewhat) superseded by sparse indices" },
-    },
-    Record {
-        config: "splitIndex.maxPercentageChange",
-        usage: NotPlanned { reason: "seems like it's superseded by sparse indices" },
-    },
-    Record {
-        config: "splitIndex.sharedIndexExpire",
-        usage: NotPlanned { reason: "seems like it's superseded by sparse indices" },
-    },
-    Record {
-        config: "index.sparse",
-        usage: Planned { note: Some("together with 'core.sparseCheckout' and 'core.sparseCheckoutCone', configures if the index should be written sparse or not") },
-    },
-    Record {
-        config: "index.version",
-        usage: Planned { note: Some("once V4 indices can be written, we need to be able to set a desired version. For now we write the smallest possible index version only.") },
-    },
     Record {
         config: "http.<url>.*",
         usage: Planned { note: Some("definitely needed for correctness, testing against baseline is a must") }
@@ -432,14 +388,6 @@ static GIT_CONFIG: &[Record] = &[
             note: Some("required for big monorepos, and typically used in conjunction with sparse indices")
         }
     },
-    Record {
-        config: "merge.renameLimit",
-        usage: Planned { note: Some("The same as diff.renameLimit") }
-    },
-    Record {
-        config: "merge.renames",
-        usage: Planned { note: Some("The same as diff.renames") }
-    },
     Record {
         config: "status.renameLimit",
         usage: Planned { note: Some("definitely needed to do status properly, even though it doesn't have to be there for day one. The same as diff.renameLimit") }
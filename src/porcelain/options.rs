@@ -34,6 +34,9 @@ pub enum Subcommands {
         ///
         /// Defaults to the current working directory.
         directory: Option<PathBuf>,
+        /// The kind of hash to use for all objects in the new repository.
+        #[clap(long, default_value_t = gix::hash::Kind::default(), value_parser = crate::shared::AsHashKind)]
+        object_format: gix::hash::Kind,
     },
     #[cfg(feature = "gitoxide-core-tools")]
     /// A selection of useful tools
@@ -288,7 +288,9 @@ mod clap {
         }
 
         fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
-            Some(Box::new([PossibleValue::new("SHA1")].into_iter()))
+            Some(Box::new(
+                [PossibleValue::new("SHA1"), PossibleValue::new("SHA256")].into_iter(),
+            ))
         }
     }
 